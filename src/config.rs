@@ -10,20 +10,27 @@
 // but are NOT exposed in the Preferences UI.  They carry `#[serde(default)]` so
 // missing keys in old config files won't cause parse failures.
 //
-// TODO: Wire default_atom_scale / default_bond_radius into TabState::new()
+// Defaults vs. tabs: the GENERAL and APPEARANCE values here are only the
+// starting point for new tabs. Each tab copies them when it opens
+// (`TabState::new`) and from then on owns its style and view; editing a
+// default never reaches an open tab by itself. Preferences writes per-tab
+// settings to the current tab, the defaults or both (its "Apply changes to"
+// choice), and View ▸ Reset Tab to Defaults re-copies them into a tab.
+//
 // TODO: Wire cache_size_mb into RenderStyle::create_session_copy()
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 // Import SOTA sprite cache
 use crate::model::elements::ColorScheme;
+use crate::physics::analysis::voids::PRESET_PROBES;
 use crate::rendering::sprite_cache::SpriteCache;
 
 // ============================================================================
@@ -43,6 +50,64 @@ pub enum ExportFormat {
     Pdf,
 }
 
+/// Structure writer preselected in Save As, in the order of its filters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum SaveFormat {
+    #[default]
+    Cif,
+    Poscar,
+    SprKkr,
+    QuantumEspresso,
+    Castep,
+    Xyz,
+}
+
+impl SaveFormat {
+    pub const ALL: [SaveFormat; 6] = [
+        SaveFormat::Cif,
+        SaveFormat::Poscar,
+        SaveFormat::SprKkr,
+        SaveFormat::QuantumEspresso,
+        SaveFormat::Castep,
+        SaveFormat::Xyz,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SaveFormat::Cif => "CIF",
+            SaveFormat::Poscar => "VASP POSCAR",
+            SaveFormat::SprKkr => "SPR-KKR Potential",
+            SaveFormat::QuantumEspresso => "Quantum ESPRESSO Input",
+            SaveFormat::Castep => "CASTEP Cell",
+            SaveFormat::Xyz => "XYZ",
+        }
+    }
+
+    /// Suggested file name; the writer is chosen from it on save.
+    pub fn file_name(self, stem: &str) -> String {
+        match self {
+            SaveFormat::Cif => format!("{stem}.cif"),
+            SaveFormat::Poscar => format!("{stem}.vasp"),
+            SaveFormat::SprKkr => format!("{stem}.pot"),
+            SaveFormat::QuantumEspresso => format!("{stem}.in"),
+            SaveFormat::Castep => format!("{stem}.cell"),
+            SaveFormat::Xyz => format!("{stem}.xyz"),
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|f| *f == self).unwrap_or(0)
+    }
+}
+
+/// Which remembered folder a file chooser starts in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecentDir {
+    Open,
+    Save,
+    Export,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum ColorMode {
     #[default]
@@ -50,6 +115,18 @@ pub enum ColorMode {
     BondValence,
     Coordination,
     Charge,
+    /// Per-atom displacement from the last structure comparison.
+    Displacement,
+}
+
+/// What the atom label overlay shows for each atom.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum LabelContent {
+    #[default]
+    Symbol,
+    /// Index into `structure.atoms`, as in the analysis tables.
+    Index,
+    SymbolIndex,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
@@ -128,19 +205,147 @@ impl Default for ExportPlotSettings {
     }
 }
 
+// ============================================================================
+// REPORT FORMAT
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum LengthUnit {
+    #[default]
+    Angstrom,
+    Picometer,
+}
+
+/// Number formatting for the Structure Info reports (structure summary, BVS
+/// report, selection measurements, neighbor table). Defaults reproduce the
+/// fixed formats the reports used before this was configurable.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReportFormat {
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+    /// Decimal places for lengths in Å; picometres drop two, so the
+    /// precision stays the same.
+    #[serde(default = "d_length_decimals")]
+    pub length_decimals: usize,
+    #[serde(default = "d_angle_decimals")]
+    pub angle_decimals: usize,
+    /// Decimal places for everything else: volume, density, valence units.
+    #[serde(default = "d_value_decimals")]
+    pub value_decimals: usize,
+}
+
+fn d_length_decimals() -> usize {
+    5
+}
+fn d_angle_decimals() -> usize {
+    2
+}
+fn d_value_decimals() -> usize {
+    3
+}
+
+impl Default for ReportFormat {
+    fn default() -> Self {
+        Self {
+            length_unit: LengthUnit::Angstrom,
+            length_decimals: d_length_decimals(),
+            angle_decimals: d_angle_decimals(),
+            value_decimals: d_value_decimals(),
+        }
+    }
+}
+
+impl ReportFormat {
+    pub fn length_symbol(&self) -> &'static str {
+        match self.length_unit {
+            LengthUnit::Angstrom => "Å",
+            LengthUnit::Picometer => "pm",
+        }
+    }
+
+    /// `angstrom` converted to the chosen unit, without the symbol.
+    pub fn length_value(&self, angstrom: f64) -> String {
+        match self.length_unit {
+            LengthUnit::Angstrom => format!("{:.*}", self.length_decimals, angstrom),
+            LengthUnit::Picometer => format!(
+                "{:.*}",
+                self.length_decimals.saturating_sub(2),
+                angstrom * 100.0
+            ),
+        }
+    }
+
+    pub fn length(&self, angstrom: f64) -> String {
+        format!("{} {}", self.length_value(angstrom), self.length_symbol())
+    }
+
+    /// Cell volume, always in Å³ whatever the length unit.
+    pub fn volume(&self, cubic_angstrom: f64) -> String {
+        format!("{:.*} Å³", self.value_decimals, cubic_angstrom)
+    }
+
+    pub fn angle(&self, degrees: f64) -> String {
+        format!("{:.*}°", self.angle_decimals, degrees)
+    }
+
+    pub fn value(&self, x: f64) -> String {
+        format!("{:.*}", self.value_decimals, x)
+    }
+}
+
 // ============================================================================
 // RENDER STYLE
 // ============================================================================
 
+/// Key light for the atom sprites and bond impostors. Angles are in
+/// degrees: azimuth counter-clockwise in the screen plane from +x (135° is
+/// upper-left), elevation from the screen plane toward the viewer (90°
+/// lights the spheres head-on). Intensity 1.0 is the classic look.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub intensity: f64,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            azimuth: 135.0,
+            elevation: 70.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Light {
+    /// Screen-space point (y down) on a unit disc where the surface faces
+    /// the light, i.e. where a sphere's highlight sits.
+    pub fn screen_offset(&self) -> (f64, f64) {
+        let (az, el) = (self.azimuth.to_radians(), self.elevation.to_radians());
+        (el.cos() * az.cos(), -el.cos() * az.sin())
+    }
+}
+
+/// Vertical background gradient: (top color, bottom color), RGB in 0–1.
+pub type Gradient = ((f64, f64, f64), (f64, f64, f64));
+
 #[derive(Clone, Debug)]
 pub struct RenderStyle {
     pub atom_scale: f64,
     pub bond_radius: f64,
     pub bond_color: (f64, f64, f64),
+    /// Draw each bond as two halves in the colors of the atoms it joins
+    /// (VESTA/Jmol style) instead of a single `bond_color` cylinder.
+    pub bond_split_color: bool,
     pub background_color: (f64, f64, f64),
+    /// Optional vertical gradient (top, bottom) drawn instead of the flat
+    /// `background_color`. `None` keeps the flat fill.
+    pub background_gradient: Option<Gradient>,
     pub metallic: f64,
     pub roughness: f64,
     pub transmission: f64,
+    pub light: Light,
     pub element_colors: HashMap<String, (f64, f64, f64)>,
     pub color_mode: ColorMode,
     // BVS thresholds live here (per-tab, tuned live via sidebar sliders).
@@ -151,9 +356,30 @@ pub struct RenderStyle {
     // Polyhedra settings
     pub polyhedra_settings: Option<PolyhedraSettings>,
 
+    // Unit-cell box, shared by the screen and export paths.
+    pub cell_line_color: (f64, f64, f64),
+    /// Stroke width in device units (px on screen, pt in PDF/SVG).
+    pub cell_line_width: f64,
+    pub cell_line_dashed: bool,
+
     // SOTA LRU sprite cache (not serialized)
     pub atom_cache: Rc<RefCell<SpriteCache>>,
     pub show_labels: bool,
+    pub label_content: LabelContent,
+    /// Label font size in device units (px on screen, pt in PDF/SVG).
+    pub label_font_size: f64,
+    /// Draw atoms that carry displacement parameters (`Atom::adp`) as
+    /// thermal ellipsoids; the rest stay spheres.
+    pub show_ellipsoids: bool,
+    /// Probability (0–1) that the atom lies inside its ellipsoid; 0.5 is
+    /// the usual ORTEP plot.
+    pub ellipsoid_probability: f64,
+    /// Species left out of the drawing, with their bonds; neighbour
+    /// searches (coordination, polyhedra) and analyses still see them.
+    pub hidden_elements: HashSet<String>,
+    /// Oxidation state used for BVS per element, set in the sidebar;
+    /// elements not listed get charge-balanced states.
+    pub bvs_oxidation_overrides: HashMap<String, i32>,
 }
 
 // Manual Serialize implementation (skip atom_cache)
@@ -163,17 +389,24 @@ impl Serialize for RenderStyle {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("RenderStyle", 10)?;
+        let mut state = serializer.serialize_struct("RenderStyle", 17)?;
         state.serialize_field("atom_scale", &self.atom_scale)?;
         state.serialize_field("bond_radius", &self.bond_radius)?;
         state.serialize_field("bond_color", &self.bond_color)?;
+        state.serialize_field("bond_split_color", &self.bond_split_color)?;
         state.serialize_field("background_color", &self.background_color)?;
+        state.serialize_field("background_gradient", &self.background_gradient)?;
         state.serialize_field("metallic", &self.metallic)?;
         state.serialize_field("roughness", &self.roughness)?;
         state.serialize_field("transmission", &self.transmission)?;
+        state.serialize_field("light", &self.light)?;
         state.serialize_field("color_mode", &self.color_mode)?;
         state.serialize_field("bvs_threshold_good", &self.bvs_threshold_good)?;
         state.serialize_field("bvs_threshold_warn", &self.bvs_threshold_warn)?;
+        state.serialize_field("cell_line_color", &self.cell_line_color)?;
+        state.serialize_field("cell_line_width", &self.cell_line_width)?;
+        state.serialize_field("cell_line_dashed", &self.cell_line_dashed)?;
+        state.serialize_field("ellipsoid_probability", &self.ellipsoid_probability)?;
         state.end()
     }
 }
@@ -189,13 +422,27 @@ impl<'de> Deserialize<'de> for RenderStyle {
             atom_scale: f64,
             bond_radius: f64,
             bond_color: (f64, f64, f64),
+            #[serde(default)]
+            bond_split_color: bool,
             background_color: (f64, f64, f64),
+            #[serde(default)]
+            background_gradient: Option<Gradient>,
             metallic: f64,
             roughness: f64,
             transmission: f64,
+            #[serde(default)]
+            light: Light,
             color_mode: ColorMode,
             bvs_threshold_good: f64,
             bvs_threshold_warn: f64,
+            #[serde(default = "d_cell_line_color")]
+            cell_line_color: (f64, f64, f64),
+            #[serde(default = "d_cell_line_width")]
+            cell_line_width: f64,
+            #[serde(default)]
+            cell_line_dashed: bool,
+            #[serde(default = "d_ellipsoid_probability")]
+            ellipsoid_probability: f64,
         }
 
         let data = RenderStyleData::deserialize(deserializer)?;
@@ -203,17 +450,29 @@ impl<'de> Deserialize<'de> for RenderStyle {
             atom_scale: data.atom_scale,
             bond_radius: data.bond_radius,
             bond_color: data.bond_color,
+            bond_split_color: data.bond_split_color,
             background_color: data.background_color,
+            background_gradient: data.background_gradient,
             metallic: data.metallic,
             roughness: data.roughness,
             transmission: data.transmission,
+            light: data.light,
             element_colors: HashMap::new(),
             color_mode: data.color_mode,
             bvs_threshold_good: data.bvs_threshold_good,
             bvs_threshold_warn: data.bvs_threshold_warn,
             polyhedra_settings: None,
+            cell_line_color: data.cell_line_color,
+            cell_line_width: data.cell_line_width,
+            cell_line_dashed: data.cell_line_dashed,
             atom_cache: Rc::new(RefCell::new(SpriteCache::default())),
             show_labels: false,
+            label_content: LabelContent::default(),
+            label_font_size: d_label_font_size(),
+            show_ellipsoids: false,
+            ellipsoid_probability: data.ellipsoid_probability,
+            hidden_elements: HashSet::new(),
+            bvs_oxidation_overrides: HashMap::new(),
         })
     }
 }
@@ -234,21 +493,59 @@ impl Default for RenderStyle {
             atom_scale: 0.4,
             bond_radius: 0.12,
             bond_color: (0.5, 0.5, 0.5),
+            bond_split_color: false,
             background_color: (0.9, 0.9, 0.9),
+            background_gradient: None,
             metallic: 0.0,
             roughness: 0.3,
             transmission: 0.0,
+            light: Light::default(),
             element_colors: HashMap::new(),
             color_mode: ColorMode::Element,
             bvs_threshold_good: 0.15,
             bvs_threshold_warn: 0.40,
             polyhedra_settings: None,
+            cell_line_color: d_cell_line_color(),
+            cell_line_width: d_cell_line_width(),
+            cell_line_dashed: false,
             atom_cache: Rc::new(RefCell::new(SpriteCache::default())),
             show_labels: false,
+            label_content: LabelContent::default(),
+            label_font_size: d_label_font_size(),
+            show_ellipsoids: false,
+            ellipsoid_probability: d_ellipsoid_probability(),
+            hidden_elements: HashSet::new(),
+            bvs_oxidation_overrides: HashMap::new(),
         }
     }
 }
 
+// ============================================================================
+// COLOR PRESETS
+// ============================================================================
+
+/// A named element → RGB mapping saved from the sidebar ("Save as Preset").
+/// Built-in schemes (`ColorScheme`) are listed alongside these but are not
+/// stored here — they're computed on the fly.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColorPreset {
+    pub name: String,
+    pub colors: HashMap<String, (f64, f64, f64)>,
+}
+
+// ============================================================================
+// PROBE PRESETS
+// ============================================================================
+
+/// A named probe sphere saved from the Voids tab. Listed after the built-in
+/// `voids::PRESET_PROBES`; a preset with a built-in's name replaces it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProbePreset {
+    pub name: String,
+    /// Probe radius in Å.
+    pub radius: f64,
+}
+
 // ============================================================================
 // MAIN CONFIG
 // ============================================================================
@@ -276,6 +573,26 @@ pub struct Config {
     pub color_scheme: ColorScheme,
     #[serde(default)]
     pub render_quality: RenderQuality,
+    /// Cairo antialiasing for the main view and the analysis-tab drawings.
+    #[serde(default)]
+    pub antialias_level: AntialiasLevel,
+    /// Fit the depth range that fog and depth cueing map onto to the drawn
+    /// atoms every frame; off uses `depth_near`..`depth_far`.
+    #[serde(default = "d_true")]
+    pub depth_range_auto: bool,
+    /// Manual depth range in Å along the line of sight, measured from the
+    /// rotation centre (negative is towards the viewer).
+    #[serde(default = "d_depth_near")]
+    pub depth_near: f64,
+    #[serde(default = "d_depth_far")]
+    pub depth_far: f64,
+    /// Blend atoms and bonds toward the background with depth.
+    #[serde(default)]
+    pub depth_cue_enabled: bool,
+    /// Share of the background in the color at the far end of the depth
+    /// range, 0..=1.
+    #[serde(default = "d_depth_cue_strength")]
+    pub depth_cue_strength: f64,
     #[serde(default = "d_true")]
     pub default_show_axes: bool,
     #[serde(default = "d_true")]
@@ -283,26 +600,112 @@ pub struct Config {
     #[serde(default = "d_true")]
     pub show_ghost_atoms: bool,
     #[serde(default = "d_atom_scale")]
-    pub default_atom_scale: f64, // TODO: wire into TabState::new()
+    pub default_atom_scale: f64,
     #[serde(default = "d_bond_rad")]
-    pub default_bond_radius: f64, // TODO: wire into TabState::new()
+    pub default_bond_radius: f64,
+    /// User-defined element color presets, managed from the sidebar.
+    #[serde(default)]
+    pub color_presets: Vec<ColorPreset>,
 
     // ── EXPORT / PLOT — charge density export defaults ──
     #[serde(default)]
     pub export_plot: ExportPlotSettings,
 
-    // ── LEGACY — retained for backward-compat JSON deserialization only ──
-    // These fields are NOT exposed in the Preferences UI.
+    // ── SAVE — structure writers ──
+    /// Rotate to the standard orientation (a ∥ x, b in xy) when saving
+    /// POSCAR or QE input.
+    #[serde(default)]
+    pub standard_orientation_on_save: bool,
+    /// Writer and file filter preselected in Save As and Export Selection.
+    #[serde(default)]
+    pub default_save_format: SaveFormat,
+
+    // ── RECENT FOLDERS — where file choosers open, updated on success ──
+    #[serde(default)]
+    pub last_open_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub last_save_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub last_export_dir: Option<PathBuf>,
+
+    // ── SELECTION — sidebar neighbor table ──
+    /// How many nearest neighbors to list for a picked atom.
+    #[serde(default = "d_neighbor_count")]
+    pub neighbor_list_count: usize,
+    /// Extra pixels around each atom's projected disk that still count as
+    /// a click or hover on it.
+    #[serde(default = "d_pick_tolerance")]
+    pub pick_tolerance_px: f64,
+    /// Write the distance or angle next to measurement lines, pinned ones
+    /// and those drawn in measure mode; off leaves only the lines. A new
+    /// key: the legacy `show_measurement_labels` defaulted to off.
+    #[serde(default = "d_true")]
+    pub label_measurements: bool,
+
+    // ── VOIDS — probe buttons in the Voids tab ──
+    /// User probes, managed from the Voids tab.
+    #[serde(default)]
+    pub probe_presets: Vec<ProbePreset>,
+
+    // ── REPORTS — number formatting in Structure Info ──
+    #[serde(default)]
+    pub report_format: ReportFormat,
+
+    // ── MATERIALS PROJECT — File → Import (`materials-project` feature) ──
+    /// API key sent as `X-API-KEY`. Stored in plain text in config.json.
+    #[serde(default)]
+    pub materials_project_api_key: String,
+
+    // ── BVS — analysis run when a file is opened ──
+    /// Compute bond valence sums for every newly loaded structure.
     #[serde(default)]
     pub auto_calc_bvs: bool,
+    /// With `auto_calc_bvs`, append the BVS report to Structure Info.
     #[serde(default = "d_true")]
     pub show_bvs_report: bool,
+    /// With `auto_calc_bvs`, pop a warning when the GII is poor.
     #[serde(default = "d_true")]
     pub warn_poor_bvs: bool,
-    #[serde(default)]
-    pub antialias_level: AntialiasLevel,
+
+    // ── PERFORMANCE — large structures ──
+    /// Files whose header announces more atoms than this parse on a
+    /// background thread, and a structure this large opens with bonds and
+    /// boundary images off.
     #[serde(default = "d_max_atoms")]
     pub max_atoms_display: usize,
+
+    // ── AUTOSAVE — crash recovery ──
+    /// Minutes between session snapshots (`io::session`); 0 turns autosave
+    /// off. A snapshot is only written when the session changed.
+    #[serde(default = "d_autosave_minutes")]
+    pub autosave_minutes: u32,
+
+    // ── WINDOW — layout saved on close, restored at startup ──
+    /// Reopen with the size and panes of the last session; off starts from
+    /// the defaults below every time.
+    #[serde(default = "d_true")]
+    pub remember_window_layout: bool,
+    #[serde(default = "d_window_width")]
+    pub window_width: i32,
+    #[serde(default = "d_window_height")]
+    pub window_height: i32,
+    #[serde(default)]
+    pub window_maximized: bool,
+    /// Sidebar pane width in px.
+    #[serde(default = "d_sidebar_width")]
+    pub sidebar_width: i32,
+    #[serde(default = "d_true")]
+    pub sidebar_visible: bool,
+    /// Height (px) of the structure view above the console; `None` lets
+    /// the console keep its natural height.
+    #[serde(default)]
+    pub console_split: Option<i32>,
+    /// Console page shown (Structure Info, System Log).
+    #[serde(default)]
+    pub console_page: u32,
+
+    // ── LEGACY — retained for backward-compat JSON deserialization only ──
+    // These fields are NOT exposed in the Preferences UI.
     #[serde(default = "d_true")]
     pub use_hardware_acceleration: bool,
     #[serde(default = "d_true")]
@@ -326,6 +729,15 @@ pub struct Config {
 }
 
 // Defaults
+fn d_cell_line_color() -> (f64, f64, f64) {
+    (0.5, 0.5, 0.5)
+}
+fn d_cell_line_width() -> f64 {
+    1.5
+}
+fn d_ellipsoid_probability() -> f64 {
+    0.5
+}
 fn d_true() -> bool {
     true
 }
@@ -341,12 +753,42 @@ fn d_atom_scale() -> f64 {
 fn d_bond_rad() -> f64 {
     0.12
 }
+fn d_depth_near() -> f64 {
+    -10.0
+}
+fn d_depth_far() -> f64 {
+    10.0
+}
+fn d_label_font_size() -> f64 {
+    12.0
+}
+fn d_depth_cue_strength() -> f64 {
+    0.6
+}
 fn d_max_atoms() -> usize {
     10000
 }
+fn d_autosave_minutes() -> u32 {
+    5
+}
+fn d_window_width() -> i32 {
+    1200
+}
+fn d_window_height() -> i32 {
+    800
+}
+fn d_sidebar_width() -> i32 {
+    300
+}
 fn d_cache() -> usize {
     200
 }
+fn d_neighbor_count() -> usize {
+    12
+}
+fn d_pick_tolerance() -> f64 {
+    4.0
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -361,20 +803,46 @@ impl Default for Config {
 
             color_scheme: ColorScheme::default(),
             render_quality: RenderQuality::Fast,
+            antialias_level: AntialiasLevel::Good,
+            depth_range_auto: true,
+            depth_near: d_depth_near(),
+            depth_far: d_depth_far(),
+            depth_cue_enabled: false,
+            depth_cue_strength: d_depth_cue_strength(),
             default_show_axes: true,
             default_show_unit_cell: true,
             show_ghost_atoms: true,
             default_atom_scale: 0.4,
             default_bond_radius: 0.12,
+            color_presets: Vec::new(),
 
             export_plot: ExportPlotSettings::default(),
+            standard_orientation_on_save: false,
+            default_save_format: SaveFormat::default(),
+            last_open_dir: None,
+            last_save_dir: None,
+            last_export_dir: None,
+            neighbor_list_count: 12,
+            pick_tolerance_px: 4.0,
+            label_measurements: true,
+            probe_presets: Vec::new(),
+            report_format: ReportFormat::default(),
+            materials_project_api_key: String::new(),
 
             // Legacy — kept for serde compat
             auto_calc_bvs: false,
             show_bvs_report: true,
             warn_poor_bvs: true,
-            antialias_level: AntialiasLevel::Good,
             max_atoms_display: 10000,
+            autosave_minutes: d_autosave_minutes(),
+            remember_window_layout: true,
+            window_width: d_window_width(),
+            window_height: d_window_height(),
+            window_maximized: false,
+            sidebar_width: d_sidebar_width(),
+            sidebar_visible: true,
+            console_split: None,
+            console_page: 0,
             use_hardware_acceleration: true,
             enable_sprite_cache: true,
             cache_size_mb: 200,
@@ -390,7 +858,31 @@ impl Default for Config {
     }
 }
 
+/// Smallest window a restored layout may open at.
+const MIN_WINDOW_SIZE: (i32, i32) = (640, 480);
+
+/// A saved window size shrunk to fit the largest connected monitor, so a
+/// layout saved on a big external screen still fits once it is unplugged.
+/// With no monitor information the size is only kept above the minimum.
+pub fn fit_window_size(size: (i32, i32), monitors: &[(i32, i32)]) -> (i32, i32) {
+    let (mut w, mut h) = (size.0.max(MIN_WINDOW_SIZE.0), size.1.max(MIN_WINDOW_SIZE.1));
+    if let Some(&(mw, mh)) = monitors.iter().max_by_key(|(mw, mh)| mw * mh) {
+        w = w.min(mw.max(MIN_WINDOW_SIZE.0));
+        h = h.min(mh.max(MIN_WINDOW_SIZE.1));
+    }
+    (w, h)
+}
+
 impl Config {
+    /// Style a new tab starts from: `style` with the default atom scale and
+    /// bond radius, and its own sprite cache.
+    pub fn new_tab_style(&self) -> RenderStyle {
+        let mut style = self.style.create_session_copy();
+        style.atom_scale = self.default_atom_scale;
+        style.bond_radius = self.default_bond_radius;
+        style
+    }
+
     pub fn load() -> (Self, String) {
         let path = Self::get_path();
         if path.exists() {
@@ -430,6 +922,98 @@ impl Config {
         }
     }
 
+    /// Put every preference back to its default. What the user saved or
+    /// the app remembers rather than chose survives: presets, the API key,
+    /// recent folders and the window layout.
+    pub fn restore_defaults(&mut self) {
+        *self = Self {
+            color_presets: std::mem::take(&mut self.color_presets),
+            probe_presets: std::mem::take(&mut self.probe_presets),
+            materials_project_api_key: std::mem::take(&mut self.materials_project_api_key),
+            last_open_dir: self.last_open_dir.take(),
+            last_save_dir: self.last_save_dir.take(),
+            last_export_dir: self.last_export_dir.take(),
+            window_width: self.window_width,
+            window_height: self.window_height,
+            window_maximized: self.window_maximized,
+            sidebar_width: self.sidebar_width,
+            sidebar_visible: self.sidebar_visible,
+            console_split: self.console_split,
+            console_page: self.console_page,
+            ..Self::default()
+        };
+    }
+
+    /// Store `preset`, replacing any existing preset with the same name.
+    pub fn upsert_color_preset(&mut self, preset: ColorPreset) {
+        match self.color_presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.color_presets.push(preset),
+        }
+    }
+
+    /// Store `preset`, replacing any existing user probe with the same name.
+    pub fn upsert_probe_preset(&mut self, preset: ProbePreset) {
+        match self.probe_presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.probe_presets.push(preset),
+        }
+    }
+
+    /// Drop the user probe called `name`. Built-ins cannot be removed.
+    pub fn remove_probe_preset(&mut self, name: &str) -> bool {
+        let before = self.probe_presets.len();
+        self.probe_presets.retain(|p| p.name != name);
+        self.probe_presets.len() != before
+    }
+
+    /// Built-in probes followed by the user's, in button order.
+    pub fn all_probe_presets(&self) -> Vec<ProbePreset> {
+        let mut all: Vec<ProbePreset> = PRESET_PROBES
+            .iter()
+            .map(|(name, radius)| ProbePreset {
+                name: name.to_string(),
+                radius: *radius,
+            })
+            .collect();
+        for user in &self.probe_presets {
+            match all.iter_mut().find(|p| p.name == user.name) {
+                Some(builtin) => builtin.radius = user.radius,
+                None => all.push(user.clone()),
+            }
+        }
+        all
+    }
+
+    /// Remembered folder for `kind`, if it still exists.
+    pub fn recent_dir(&self, kind: RecentDir) -> Option<&Path> {
+        let dir = match kind {
+            RecentDir::Open => &self.last_open_dir,
+            RecentDir::Save => &self.last_save_dir,
+            RecentDir::Export => &self.last_export_dir,
+        };
+        dir.as_deref().filter(|d| d.is_dir())
+    }
+
+    /// Remember the folder of `path` (a file just read or written, or a
+    /// folder chosen for a batch export) for the next `kind` chooser.
+    pub fn remember_dir(&mut self, kind: RecentDir, path: &Path) {
+        let dir = if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent().filter(|p| !p.as_os_str().is_empty())
+        };
+        let Some(dir) = dir else {
+            return;
+        };
+        let slot = match kind {
+            RecentDir::Open => &mut self.last_open_dir,
+            RecentDir::Save => &mut self.last_save_dir,
+            RecentDir::Export => &mut self.last_export_dir,
+        };
+        *slot = Some(dir.to_path_buf());
+    }
+
     fn get_path() -> PathBuf {
         if let Some(proj) = ProjectDirs::from("com", "example", "cview") {
             proj.config_dir().join("settings.json")
@@ -478,3 +1062,133 @@ impl Default for PolyhedraSettings {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_size_fits_the_largest_monitor() {
+        let laptop = (1366, 768);
+        let external = (2560, 1440);
+        // Saved on the external screen, which is still connected.
+        assert_eq!(
+            fit_window_size((2400, 1300), &[laptop, external]),
+            (2400, 1300)
+        );
+        // The external screen is gone.
+        assert_eq!(fit_window_size((2400, 1300), &[laptop]), (1366, 768));
+        // Corrupt or tiny values never open an unusable window.
+        assert_eq!(fit_window_size((-5, 10), &[laptop]), MIN_WINDOW_SIZE);
+        assert_eq!(fit_window_size((5000, 5000), &[]), (5000, 5000));
+    }
+
+    #[test]
+    fn report_format_defaults_and_picometres() {
+        let mut fmt = ReportFormat::default();
+        assert_eq!(fmt.length(1.234567), "1.23457 Å");
+        assert_eq!(fmt.angle(109.4712), "109.47°");
+        assert_eq!(fmt.volume(40.5), "40.500 Å³");
+
+        fmt.length_unit = LengthUnit::Picometer;
+        assert_eq!(fmt.length(1.234567), "123.457 pm");
+        assert_eq!(fmt.volume(40.5), "40.500 Å³");
+        fmt.length_decimals = 1;
+        assert_eq!(fmt.length(1.23456), "123 pm");
+
+        // Old config files without the section keep the defaults.
+        let parsed: ReportFormat = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed, ReportFormat::default());
+    }
+
+    #[test]
+    fn measurement_labels_do_not_read_the_legacy_key() {
+        // Configs from before measure mode saved the unused old key as off.
+        let old: Config = serde_json::from_str(r#"{"show_measurement_labels": false}"#).unwrap();
+        assert!(old.label_measurements);
+    }
+
+    #[test]
+    fn recent_dirs_remember_the_parent_folder() {
+        let tmp = std::env::temp_dir();
+        let mut cfg = Config::default();
+        assert!(cfg.recent_dir(RecentDir::Save).is_none());
+
+        cfg.remember_dir(RecentDir::Save, &tmp.join("out.cif"));
+        assert_eq!(cfg.recent_dir(RecentDir::Save), Some(tmp.as_path()));
+        assert!(cfg.recent_dir(RecentDir::Open).is_none());
+
+        // A chosen folder is kept as is; a bare file name changes nothing.
+        cfg.remember_dir(RecentDir::Export, &tmp);
+        assert_eq!(cfg.recent_dir(RecentDir::Export), Some(tmp.as_path()));
+        cfg.remember_dir(RecentDir::Export, Path::new("POSCAR"));
+        assert_eq!(cfg.recent_dir(RecentDir::Export), Some(tmp.as_path()));
+
+        // Folders deleted since are ignored.
+        cfg.last_open_dir = Some(tmp.join("cview-no-such-dir"));
+        assert!(cfg.recent_dir(RecentDir::Open).is_none());
+    }
+
+    #[test]
+    fn user_probes_follow_the_builtins() {
+        let mut cfg = Config::default();
+        let builtin = cfg.all_probe_presets();
+        assert_eq!(builtin.len(), PRESET_PROBES.len());
+
+        let li = |radius| ProbePreset {
+            name: "Li⁺".to_string(),
+            radius,
+        };
+        cfg.upsert_probe_preset(li(0.70));
+        cfg.upsert_probe_preset(li(0.76));
+        cfg.upsert_probe_preset(ProbePreset {
+            name: "N₂".to_string(),
+            radius: 1.80,
+        });
+        let all = cfg.all_probe_presets();
+        assert_eq!(all.len(), PRESET_PROBES.len() + 1);
+        assert_eq!(all.last(), Some(&li(0.76)));
+        let n2 = all.iter().find(|p| p.name == "N₂").unwrap();
+        assert_eq!(n2.radius, 1.80);
+
+        assert!(cfg.remove_probe_preset("N₂"));
+        assert!(!cfg.remove_probe_preset("He"));
+        assert_eq!(cfg.all_probe_presets()[..builtin.len()], builtin[..]);
+    }
+
+    #[test]
+    fn restoring_defaults_keeps_what_was_saved() {
+        let tmp = std::env::temp_dir();
+        let mut cfg = Config {
+            default_atom_scale: 1.4,
+            materials_project_api_key: "key".to_string(),
+            window_width: 1800,
+            console_page: 2,
+            ..Config::default()
+        };
+        cfg.upsert_probe_preset(ProbePreset {
+            name: "Li⁺".to_string(),
+            radius: 0.76,
+        });
+        cfg.remember_dir(RecentDir::Open, &tmp);
+
+        cfg.restore_defaults();
+        assert_eq!(cfg.default_atom_scale, Config::default().default_atom_scale);
+        assert_eq!(cfg.materials_project_api_key, "key");
+        assert_eq!(cfg.probe_presets.len(), 1);
+        assert_eq!(cfg.recent_dir(RecentDir::Open), Some(tmp.as_path()));
+        assert_eq!((cfg.window_width, cfg.console_page), (1800, 2));
+    }
+
+    #[test]
+    fn save_format_names_pick_the_writer() {
+        for format in SaveFormat::ALL {
+            assert_eq!(SaveFormat::ALL[format.index()], format);
+        }
+        assert_eq!(SaveFormat::Poscar.file_name("structure"), "structure.vasp");
+        assert_eq!(
+            SaveFormat::default().file_name("selection"),
+            "selection.cif"
+        );
+    }
+}
@@ -1,8 +1,13 @@
 // src/io.rs
+pub mod castep;
 pub mod chgcar;
 pub mod cif;
+pub mod csv;
+pub mod materials_project;
+pub mod pdb;
 pub mod poscar;
 pub mod qe;
+pub mod session;
 pub mod sprkkr;
 pub mod xrd_exp;
 pub mod xyz;
@@ -11,43 +16,65 @@ use crate::model::Structure;
 use std::io;
 use std::path::Path;
 
+/// Structure formats `load_structure` has a reader for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructureFormat {
+    Cif,
+    Xyz,
+    Poscar,
+    Qe,
+    SprKkr,
+    Castep,
+    Pdb,
+}
+
+/// How much of a file `sniff_formats` looks at. Every marker it checks sits
+/// in the header of its format.
+const SNIFF_BYTES: u64 = 64 * 1024;
+
 pub fn load_structure(path: &str) -> io::Result<Structure> {
-    let p = path.to_lowercase();
+    load_structure_with(path, false)
+}
 
-    // Check extension-based formats first
-    if p.ends_with(".cif") {
-        return cif::parse(path);
-    }
-    if p.ends_with(".xyz") {
-        return xyz::parse(path);
-    }
-    if p.ends_with(".vasp") {
-        return poscar::parse(path);
-    }
-    if p.ends_with(".in")
-        || p.ends_with(".pwi")
-        || p.ends_with(".qe")
-        || p.ends_with(".out")
-        || p.ends_with(".log")
-    {
-        return qe::parse(path);
+/// `load_structure`, optionally choosing the parser from the file contents
+/// (`Config::auto_detect_format`). The extension still wins when the
+/// content also carries its markers, and content that matches no known
+/// format falls back to the extension rules, so auto-detection never
+/// rejects a file the extension rules would have read.
+pub fn load_structure_with(path: &str, auto_detect: bool) -> io::Result<Structure> {
+    parse_as(resolve_format(path, auto_detect)?, path)
+}
+
+/// `load_structure_with`, but every frame of an XYZ trajectory. Other
+/// formats hold one structure and give a single frame.
+pub fn load_frames_with(path: &str, auto_detect: bool) -> io::Result<Vec<Structure>> {
+    match resolve_format(path, auto_detect)? {
+        StructureFormat::Xyz => xyz::parse_frames(path),
+        format => parse_as(format, path).map(|s| vec![s]),
     }
-    if p.ends_with(".inp") || p.ends_with(".pot") || p.ends_with(".sys") {
-        return sprkkr::parse(path);
+}
+
+fn parse_as(format: StructureFormat, path: &str) -> io::Result<Structure> {
+    match format {
+        StructureFormat::Cif => cif::parse(path),
+        StructureFormat::Xyz => xyz::parse(path),
+        StructureFormat::Poscar => poscar::parse(path),
+        StructureFormat::Qe => qe::parse(path),
+        StructureFormat::SprKkr => sprkkr::parse(path),
+        StructureFormat::Castep => castep::parse(path),
+        StructureFormat::Pdb => pdb::parse(path),
     }
+}
 
-    // For extensionless files, check the filename itself (case-insensitive).
-    // This handles POSCAR, CONTCAR, POSCAR_relaxed, CONTCAR.1, etc.
+/// The reader `load_structure_with` uses for `path`.
+fn resolve_format(path: &str, auto_detect: bool) -> io::Result<StructureFormat> {
+    let by_name = format_from_name(path);
     let filename = Path::new(path)
         .file_name()
         .unwrap_or_default()
         .to_string_lossy()
         .to_lowercase();
-
-    if filename.starts_with("poscar") || filename.starts_with("contcar") {
-        return poscar::parse(path);
-    }
-    if filename.starts_with("chgcar") {
+    if by_name.is_none() && filename.starts_with("chgcar") {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
             "CHGCAR files contain volumetric data, not structures. \
@@ -55,8 +82,200 @@ pub fn load_structure(path: &str) -> io::Result<Structure> {
         ));
     }
 
-    // Fallback: try POSCAR parser (most permissive for VASP-family files)
-    poscar::parse(path)
+    let sniffed = if auto_detect {
+        sniff_formats(path)
+    } else {
+        Vec::new()
+    };
+    // A marker can turn up inside another format (a `loop_` in a QE
+    // comment), so the content only overrides an extension it contradicts.
+    let chosen = match by_name {
+        Some(named) if sniffed.contains(&named) => Some(named),
+        _ => sniffed.first().copied().or(by_name),
+    };
+    // Fallback: the POSCAR parser (most permissive for VASP-family files)
+    Ok(chosen.unwrap_or(StructureFormat::Poscar))
+}
+
+/// Format implied by the extension, or for extensionless files by a
+/// POSCAR/CONTCAR name (POSCAR_relaxed, CONTCAR.1, ...). `None` when
+/// neither says anything.
+pub fn format_from_name(path: &str) -> Option<StructureFormat> {
+    let p = path.to_lowercase();
+    let ext = |list: &[&str]| list.iter().any(|e| p.ends_with(e));
+
+    if ext(&[".cif"]) {
+        return Some(StructureFormat::Cif);
+    }
+    if ext(&[".xyz"]) {
+        return Some(StructureFormat::Xyz);
+    }
+    if ext(&[".vasp"]) {
+        return Some(StructureFormat::Poscar);
+    }
+    if ext(&[".in", ".pwi", ".qe", ".out", ".log"]) {
+        return Some(StructureFormat::Qe);
+    }
+    if ext(&[".inp", ".pot", ".sys"]) {
+        return Some(StructureFormat::SprKkr);
+    }
+    if ext(&[".cell"]) {
+        return Some(StructureFormat::Castep);
+    }
+    if ext(&[".pdb", ".ent"]) {
+        return Some(StructureFormat::Pdb);
+    }
+
+    let filename = Path::new(&p)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    if filename.starts_with("poscar") || filename.starts_with("contcar") {
+        return Some(StructureFormat::Poscar);
+    }
+    None
+}
+
+/// Formats recognised from the first `SNIFF_BYTES` of the file, regardless
+/// of its name, in the order of `detect_formats`.
+pub fn sniff_formats(path: &str) -> Vec<StructureFormat> {
+    use std::io::Read;
+    let mut head = Vec::new();
+    let read = std::fs::File::open(path).and_then(|f| f.take(SNIFF_BYTES).read_to_end(&mut head));
+    match read {
+        Ok(_) => detect_formats(&String::from_utf8_lossy(&head)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// The strongest match of `detect_formats`.
+pub fn detect_format(text: &str) -> Option<StructureFormat> {
+    detect_formats(text).first().copied()
+}
+
+/// Every format whose markers `text` carries, strongest first: CIF
+/// `data_`/`loop_` blocks, QE namelists or PWSCF output, CASTEP `%block`s,
+/// SPR-KKR sections, PDB CRYST1/ATOM/HETATM records, an XYZ atom-count
+/// header, or a POSCAR scale line followed by three lattice vectors.
+pub fn detect_formats(text: &str) -> Vec<StructureFormat> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let lower: Vec<String> = lines.iter().map(|l| l.to_ascii_lowercase()).collect();
+    let has_prefix = |prefixes: &[&str]| {
+        lower
+            .iter()
+            .any(|l| prefixes.iter().any(|p| l.starts_with(p)))
+    };
+    let mut found = Vec::new();
+
+    if has_prefix(&["data_", "loop_", "_cell_length_a"]) {
+        found.push(StructureFormat::Cif);
+    }
+    if has_prefix(&["&control", "&system"]) || lower.iter().any(|l| l.contains("program pwscf")) {
+        found.push(StructureFormat::Qe);
+    }
+    if has_prefix(&["%block lattice_", "%block positions_"]) {
+        found.push(StructureFormat::Castep);
+    }
+    if lines.contains(&"LATTICE") && lines.contains(&"SITES") {
+        found.push(StructureFormat::SprKkr);
+    }
+    if has_prefix(&["cryst1", "atom  ", "hetatm"]) {
+        found.push(StructureFormat::Pdb);
+    }
+
+    let floats = |tokens: &[&str]| {
+        tokens
+            .iter()
+            .take_while(|t| t.parse::<f64>().is_ok())
+            .count()
+    };
+    // XYZ: atom count, comment (may be blank, so use the raw lines), then
+    // `El x y z` rows.
+    let raw: Vec<&str> = text.lines().collect();
+    if raw.len() >= 3 && raw[0].trim().parse::<usize>().is_ok() {
+        let tokens: Vec<&str> = raw[2].split_whitespace().collect();
+        let starts_with_element = tokens
+            .first()
+            .is_some_and(|t| t.starts_with(|c: char| c.is_ascii_alphabetic()));
+        if starts_with_element && floats(&tokens[1..]) >= 3 {
+            found.push(StructureFormat::Xyz);
+        }
+    }
+    // POSCAR: comment, scale, three lattice vectors.
+    let row = |l: &str| floats(&l.split_whitespace().collect::<Vec<_>>());
+    if lines.len() >= 5 && row(lines[1]) >= 1 && lines[2..5].iter().all(|l| row(l) >= 3) {
+        found.push(StructureFormat::Poscar);
+    }
+    found
+}
+
+/// Files at least this big count as large even when their header gives no
+/// atom count (CIF, QE, SPR-KKR, CASTEP).
+pub const LARGE_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Atom count from the file header where the format has one (XYZ and the
+/// POSCAR family), picked by the same rules as `load_structure`. Cheap: only
+/// the first lines are read.
+pub fn estimate_atom_count(path: &str) -> Option<usize> {
+    match format_from_name(path) {
+        Some(StructureFormat::Xyz) => xyz::header_atom_count(path),
+        Some(StructureFormat::Poscar) | None => poscar::header_atom_count(path),
+        _ => None,
+    }
+}
+
+/// Whether `path` should be parsed off the UI thread: its header announces
+/// more than `max_atoms` atoms, or it is at least `LARGE_FILE_BYTES`.
+pub fn is_large_file(path: &str, max_atoms: usize) -> bool {
+    estimate_atom_count(path).is_some_and(|n| n > max_atoms)
+        || std::fs::metadata(path).is_ok_and(|m| m.len() >= LARGE_FILE_BYTES)
+}
+
+/// Whether `load_structure` has a dedicated reader for `path`, by the same
+/// extension and POSCAR/CONTCAR name rules. Used to pick files out of a
+/// directory, where the POSCAR fallback would accept anything.
+pub fn is_structure_file(path: &str) -> bool {
+    format_from_name(path).is_some()
+}
+
+/// Writer switches picked in the Save dialog / Preferences.
+#[derive(Clone, Copy, Default)]
+pub struct SaveOptions {
+    /// Rotate to `Structure::to_standard_orientation` first. Applies to
+    /// POSCAR and QE input, whose downstream codes assume that convention.
+    pub standard_orientation: bool,
+    /// CIF only: write the asymmetric unit plus the space group's symmetry
+    /// operations instead of every atom in P1.
+    pub reduce_symmetry: bool,
+}
+
+/// `save_structure` with the writer switches in `options`.
+pub fn save_structure_with(
+    path: &str,
+    structure: &Structure,
+    options: SaveOptions,
+) -> io::Result<()> {
+    let p = path.to_lowercase();
+    if p.ends_with(".cif") {
+        return if options.reduce_symmetry {
+            cif::write_symmetrized(path, structure)
+        } else {
+            cif::write(path, structure)
+        };
+    }
+    let other_format = [".xyz", ".inp", ".pot", ".sys", ".cell"]
+        .iter()
+        .any(|ext| p.ends_with(ext));
+    if options.standard_orientation && !other_format {
+        save_structure(path, &structure.to_standard_orientation())
+    } else {
+        save_structure(path, structure)
+    }
 }
 
 pub fn save_structure(path: &str, structure: &Structure) -> io::Result<()> {
@@ -70,6 +289,8 @@ pub fn save_structure(path: &str, structure: &Structure) -> io::Result<()> {
         qe::write(path, structure)
     } else if p.ends_with(".inp") || p.ends_with(".pot") || p.ends_with(".sys") {
         sprkkr::write(path, structure)
+    } else if p.ends_with(".cell") {
+        castep::write(path, structure)
     } else {
         // Check filename for POSCAR/CONTCAR/VASP patterns
         let filename = Path::new(path)
@@ -87,3 +308,105 @@ pub fn save_structure(path: &str, structure: &Structure) -> io::Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temp file named `name`, removed on drop.
+    struct TmpFile(std::path::PathBuf);
+    impl TmpFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let p = std::env::temp_dir().join(format!("cview_io_{}_{}", std::process::id(), name));
+            std::fs::write(&p, contents).unwrap();
+            TmpFile(p)
+        }
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+    impl Drop for TmpFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    const CIF: &str = "data_x\n_cell_length_a 4\n_cell_length_b 4\n_cell_length_c 4\n\
+                       _cell_angle_alpha 90\n_cell_angle_beta 90\n_cell_angle_gamma 90\n\
+                       loop_\n_atom_site_label\n_atom_site_fract_x\n_atom_site_fract_y\n\
+                       _atom_site_fract_z\nFe1 0 0 0\nO1 0.5 0.5 0.5\n";
+    const POSCAR: &str = "Si\n1.0\n5.43 0 0\n0 5.43 0\n0 0 5.43\nSi\n2\nDirect\n\
+                          0 0 0\n0.25 0.25 0.25\n";
+
+    #[test]
+    fn misnamed_files_load_when_auto_detecting() {
+        let cif_as_xyz = TmpFile::new("cif_as.xyz", CIF);
+        let s = load_structure_with(cif_as_xyz.path(), true).unwrap();
+        assert_eq!(s.atoms.len(), 2);
+        assert!((s.lattice[0][0] - 4.0).abs() < 1e-9);
+        // Extension rules hand it to the XYZ reader.
+        assert!(load_structure_with(cif_as_xyz.path(), false).is_err());
+
+        let poscar_as_cif = TmpFile::new("poscar_as.cif", POSCAR);
+        let s = load_structure_with(poscar_as_cif.path(), true).unwrap();
+        assert_eq!(s.atoms.len(), 2);
+        assert_eq!(s.atoms[0].element, "Si");
+    }
+
+    #[test]
+    fn extension_wins_when_the_content_also_fits_it() {
+        // The comment line looks like a CIF block header.
+        let poscar = TmpFile::new("data_marker.vasp", &POSCAR.replacen("Si", "data_Si", 1));
+        assert_eq!(
+            sniff_formats(poscar.path()),
+            vec![StructureFormat::Cif, StructureFormat::Poscar]
+        );
+        assert_eq!(
+            resolve_format(poscar.path(), true).unwrap(),
+            StructureFormat::Poscar
+        );
+    }
+
+    #[test]
+    fn unrecognised_content_falls_back_to_the_extension() {
+        assert_eq!(detect_format("just some words\n"), None);
+        let poscar = TmpFile::new("model.vasp", POSCAR);
+        assert_eq!(sniff_formats(poscar.path()), vec![StructureFormat::Poscar]);
+        assert_eq!(
+            format_from_name("run/CONTCAR.1"),
+            Some(StructureFormat::Poscar)
+        );
+        assert_eq!(format_from_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn markers_pick_the_parser() {
+        assert_eq!(detect_format(CIF), Some(StructureFormat::Cif));
+        assert_eq!(
+            detect_format("&CONTROL\n calculation='scf'\n/\n&SYSTEM\n ibrav=0\n/\n"),
+            Some(StructureFormat::Qe)
+        );
+        assert_eq!(
+            detect_format("   Program PWSCF v.7.2 starts on ...\n"),
+            Some(StructureFormat::Qe)
+        );
+        assert_eq!(
+            detect_format("%BLOCK LATTICE_CART\n4 0 0\n0 4 0\n0 0 4\n%ENDBLOCK LATTICE_CART\n"),
+            Some(StructureFormat::Castep)
+        );
+        assert_eq!(
+            detect_format("HEADER 'x'\nLATTICE\nSYSDIM 3D\nSITES\nCARTESIAN T\n"),
+            Some(StructureFormat::SprKkr)
+        );
+        assert_eq!(
+            detect_format("2\n\nH 0 0 0\nH 0 0 0.74\n"),
+            Some(StructureFormat::Xyz)
+        );
+        assert_eq!(detect_format(POSCAR), Some(StructureFormat::Poscar));
+        assert_eq!(
+            detect_format("HETATM    1  O   HOH A   1       0.000   0.000   0.000  1.00  0.00\n"),
+            Some(StructureFormat::Pdb)
+        );
+        assert_eq!(format_from_name("1abc.pdb"), Some(StructureFormat::Pdb));
+    }
+}
@@ -0,0 +1,290 @@
+// src/io/castep.rs
+//
+// CASTEP .cell files: the cell from %BLOCK LATTICE_CART or LATTICE_ABC and
+// the atoms from %BLOCK POSITIONS_FRAC or POSITIONS_ABS. Keywords are
+// case-insensitive; `!`, `#` and `;` start comments. A block may open with
+// a unit line (ang, bohr/a0, nm); Cartesian data defaults to Å. Every other
+// block and keyword (k-points, constraints, species pots) is ignored.
+//
+// The writer emits LATTICE_CART in Å and POSITIONS_FRAC.
+
+use crate::model::structure::generate_formula;
+use crate::model::{Atom, Structure};
+use crate::utils::geometry::abc_to_lattice;
+use crate::utils::linalg::{cart_to_frac, frac_to_cart};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+
+const BOHR_TO_ANG: f64 = 0.52917721092; // CODATA 2018
+
+pub fn parse(path: &str) -> io::Result<Structure> {
+    parse_str(&fs::read_to_string(path)?)
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn parse_str(content: &str) -> io::Result<Structure> {
+    let blocks = collect_blocks(content);
+
+    let lattice = if let Some(rows) = blocks.get("lattice_cart") {
+        let (scale, rows) = split_unit(rows)?;
+        if rows.len() < 3 {
+            return Err(invalid("LATTICE_CART needs three vectors"));
+        }
+        let mut lattice = [[0.0; 3]; 3];
+        for (vec, row) in lattice.iter_mut().zip(rows) {
+            let v = numbers(row, 3).ok_or_else(|| invalid(format!("Bad lattice row '{row}'")))?;
+            *vec = [v[0] * scale, v[1] * scale, v[2] * scale];
+        }
+        lattice
+    } else if let Some(rows) = blocks.get("lattice_abc") {
+        let (scale, rows) = split_unit(rows)?;
+        let lengths = rows.first().and_then(|r| numbers(r, 3));
+        let angles = rows.get(1).and_then(|r| numbers(r, 3));
+        let (Some(l), Some(ang)) = (lengths, angles) else {
+            return Err(invalid("LATTICE_ABC needs a b c and alpha beta gamma"));
+        };
+        // a along x and b in the xy-plane, as CASTEP itself orients it.
+        abc_to_lattice(
+            [l[0] * scale, l[1] * scale, l[2] * scale],
+            [ang[0], ang[1], ang[2]],
+        )
+        .ok_or_else(|| invalid("LATTICE_ABC angles do not form a cell"))?
+    } else {
+        return Err(invalid("No LATTICE_CART or LATTICE_ABC block"));
+    };
+
+    let (rows, fractional, scale) = if let Some(rows) = blocks.get("positions_frac") {
+        (rows.as_slice(), true, 1.0)
+    } else if let Some(rows) = blocks.get("positions_abs") {
+        let (scale, rows) = split_unit(rows)?;
+        (rows, false, scale)
+    } else {
+        return Err(invalid("No POSITIONS_FRAC or POSITIONS_ABS block"));
+    };
+
+    let mut atoms = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut parts = row.split_whitespace();
+        let species = parts.next().unwrap_or_default();
+        let coords: Vec<f64> = parts.take(3).filter_map(|s| s.parse().ok()).collect();
+        if coords.len() < 3 {
+            return Err(invalid(format!("Bad position line '{row}'")));
+        }
+        let p = [coords[0], coords[1], coords[2]];
+        let position = if fractional {
+            frac_to_cart(p, lattice)
+        } else {
+            [p[0] * scale, p[1] * scale, p[2] * scale]
+        };
+        atoms.push(Atom {
+            element: element_symbol(species),
+            position,
+            original_index: atoms.len(),
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        });
+    }
+    if atoms.is_empty() {
+        return Err(invalid("Positions block is empty"));
+    }
+
+    let formula = generate_formula(&atoms);
+    Ok(Structure {
+        lattice,
+        atoms,
+        formula,
+        is_periodic: true,
+    })
+}
+
+/// Lines of every `%BLOCK name … %ENDBLOCK name`, keyed by lower-case
+/// name, with comments and blank lines removed.
+fn collect_blocks(content: &str) -> HashMap<String, Vec<String>> {
+    let mut blocks = HashMap::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+    for raw in content.lines() {
+        let line = raw.split(['!', '#', ';']).next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let head = words.next().unwrap_or_default().to_ascii_lowercase();
+        match head.as_str() {
+            "%block" => {
+                let name = words.next().unwrap_or_default().to_ascii_lowercase();
+                current = Some((name, Vec::new()));
+            }
+            "%endblock" => {
+                if let Some((name, rows)) = current.take() {
+                    blocks.insert(name, rows);
+                }
+            }
+            _ => {
+                if let Some((_, rows)) = current.as_mut() {
+                    rows.push(line.to_string());
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// Split an optional leading unit line off a block, returning the factor
+/// to Å and the remaining rows.
+fn split_unit(rows: &[String]) -> io::Result<(f64, &[String])> {
+    let Some(first) = rows.first() else {
+        return Ok((1.0, rows));
+    };
+    if first.split_whitespace().count() != 1 || first.parse::<f64>().is_ok() {
+        return Ok((1.0, rows));
+    }
+    let scale = match first.to_ascii_lowercase().as_str() {
+        "ang" => 1.0,
+        "bohr" | "a0" => BOHR_TO_ANG,
+        "nm" => 10.0,
+        other => return Err(invalid(format!("Unsupported length unit '{other}'"))),
+    };
+    Ok((scale, &rows[1..]))
+}
+
+/// The first `n` whitespace-separated numbers of `row`.
+fn numbers(row: &str, n: usize) -> Option<Vec<f64>> {
+    let v: Vec<f64> = row
+        .split_whitespace()
+        .take(n)
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    (v.len() == n).then_some(v)
+}
+
+/// `FE`, `fe:1` → `Fe`: drop CASTEP's `:label` suffix and title-case.
+fn element_symbol(species: &str) -> String {
+    let base = species.split(':').next().unwrap_or_default();
+    let mut chars = base.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+        None => String::from("X"),
+    }
+}
+
+pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
+    if structure.atoms.iter().any(|a| a.occupancy < 0.99) {
+        crate::utils::console::log_warn(
+            "CASTEP cell is written without MIXTURE tags — partial occupancies are discarded on export",
+        );
+    }
+    let mut file = fs::File::create(path)?;
+
+    writeln!(file, "! {} — exported by CView", structure.formula)?;
+    writeln!(file, "%BLOCK LATTICE_CART")?;
+    writeln!(file, "ang")?;
+    for vec in &structure.lattice {
+        writeln!(file, "  {:15.9} {:15.9} {:15.9}", vec[0], vec[1], vec[2])?;
+    }
+    writeln!(file, "%ENDBLOCK LATTICE_CART")?;
+    writeln!(file)?;
+
+    writeln!(file, "%BLOCK POSITIONS_FRAC")?;
+    for atom in &structure.atoms {
+        let f = cart_to_frac(atom.position, structure.lattice)
+            .ok_or_else(|| invalid("Lattice is singular (volume is zero)"))?;
+        writeln!(
+            file,
+            "  {:<3} {:15.9} {:15.9} {:15.9}",
+            atom.element, f[0], f[1], f[2]
+        )?;
+    }
+    writeln!(file, "%ENDBLOCK POSITIONS_FRAC")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn cartesian_cell_with_fractional_positions() {
+        let s = parse_str(
+            "! rock salt\n\
+             %block lattice_cart\n\
+             ANG\n\
+             5.64 0 0\n0 5.64 0\n0 0 5.64\n\
+             %endblock lattice_cart\n\n\
+             %BLOCK POSITIONS_FRAC\n\
+             NA 0.0 0.0 0.0\n\
+             Cl:1 0.5 0.5 0.5  # tagged species\n\
+             %ENDBLOCK POSITIONS_FRAC\n\
+             kpoint_mp_grid 4 4 4\n",
+        )
+        .unwrap();
+        assert!(s.is_periodic);
+        approx(s.lattice[2][2], 5.64);
+        assert_eq!(s.atoms[0].element, "Na");
+        assert_eq!(s.atoms[1].element, "Cl");
+        approx(s.atoms[1].position[0], 2.82);
+        assert_eq!(s.formula, "ClNa");
+    }
+
+    #[test]
+    fn abc_cell_and_bohr_positions() {
+        let s = parse_str(
+            "%BLOCK LATTICE_ABC\n\
+             3.0 3.0 5.0\n90 90 120\n\
+             %ENDBLOCK LATTICE_ABC\n\
+             %BLOCK POSITIONS_ABS\n\
+             bohr\n\
+             Zn 0.0 0.0 1.0\n\
+             %ENDBLOCK POSITIONS_ABS\n",
+        )
+        .unwrap();
+        approx(s.lattice[1][0], -1.5);
+        approx(s.lattice[1][1], 3.0 * 3f64.sqrt() / 2.0);
+        approx(s.lattice[2][2], 5.0);
+        approx(s.atoms[0].position[2], BOHR_TO_ANG);
+    }
+
+    #[test]
+    fn missing_blocks_and_bad_units_are_errors() {
+        assert!(parse_str("%BLOCK POSITIONS_FRAC\nSi 0 0 0\n%ENDBLOCK POSITIONS_FRAC\n").is_err());
+        assert!(parse_str(
+            "%BLOCK LATTICE_CART\nfurlong\n1 0 0\n0 1 0\n0 0 1\n%ENDBLOCK LATTICE_CART\n\
+             %BLOCK POSITIONS_FRAC\nSi 0 0 0\n%ENDBLOCK POSITIONS_FRAC\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn write_then_parse_round_trips() {
+        let s = parse_str(
+            "%BLOCK LATTICE_ABC\n4.0 4.5 5.0\n80 95 110\n%ENDBLOCK LATTICE_ABC\n\
+             %BLOCK POSITIONS_FRAC\nSi 0.1 0.2 0.3\nO 0.7 0.6 0.5\n%ENDBLOCK POSITIONS_FRAC\n",
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("cview_castep_{}.cell", std::process::id()));
+        let path = path.to_str().unwrap();
+        write(path, &s).unwrap();
+        let back = parse(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        for (u, v) in s.lattice.iter().zip(&back.lattice) {
+            for k in 0..3 {
+                approx(u[k], v[k]);
+            }
+        }
+        for (a, b) in s.atoms.iter().zip(&back.atoms) {
+            assert_eq!(a.element, b.element);
+            for k in 0..3 {
+                approx(a.position[k], b.position[k]);
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // Supports: non-spin-polarized, spin-polarized (two grids), charge density difference
 // Parses atom positions and species for overlay on density slices
 
+use crate::utils::linalg::cart_to_frac;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 
@@ -82,6 +83,7 @@ impl ChgcarData {
                 original_index: i,
                 oxidation: None,
                 occupancy: 1.0,
+                adp: None,
             })
             .collect();
         crate::model::structure::Structure {
@@ -232,7 +234,8 @@ pub fn parse(path: &str) -> io::Result<ChgcarData> {
         count_in_species += 1;
 
         let frac_coords = if is_cartesian {
-            cart_to_frac_inline(&[vals[0], vals[1], vals[2]], &lattice)
+            let cart = [vals[0], vals[1], vals[2]];
+            cart_to_frac(cart, lattice).unwrap_or(cart)
         } else {
             [vals[0], vals[1], vals[2]]
         };
@@ -414,27 +417,3 @@ where
     }
     None
 }
-
-/// Inline Cartesian → fractional conversion.
-fn cart_to_frac_inline(cart: &[f64; 3], lattice: &[[f64; 3]; 3]) -> [f64; 3] {
-    use nalgebra::{Matrix3, Vector3};
-    let lat = Matrix3::new(
-        lattice[0][0],
-        lattice[0][1],
-        lattice[0][2],
-        lattice[1][0],
-        lattice[1][1],
-        lattice[1][2],
-        lattice[2][0],
-        lattice[2][1],
-        lattice[2][2],
-    );
-    match lat.transpose().try_inverse() {
-        Some(inv) => {
-            let c = Vector3::new(cart[0], cart[1], cart[2]);
-            let f = inv * c;
-            [f.x, f.y, f.z]
-        }
-        None => [cart[0], cart[1], cart[2]],
-    }
-}
@@ -1,5 +1,7 @@
+use crate::model::structure::transform_adp;
 use crate::model::{Atom, Structure};
-use crate::utils::linalg::frac_to_cart;
+use crate::utils::geometry::{abc_to_lattice, cell_parameters};
+use crate::utils::linalg::{frac_to_cart, invert_matrix_3x3, lattice_to_matrix3};
 use std::fs::File;
 use std::io::Write;
 use std::io::{self, BufRead};
@@ -17,6 +19,10 @@ use std::path::Path;
 /// - Atom sites: fractional coordinates via `_atom_site_fract_{x,y,z}`.
 ///   Element symbol comes from `_atom_site_type_symbol` when present (IUCr
 ///   precedence rule), otherwise from component_0 of `_atom_site_label`.
+/// - Displacement parameters: `_atom_site_U_iso_or_equiv` /
+///   `_atom_site_B_iso_or_equiv`, overridden per label by an
+///   `_atom_site_aniso_U_ij` / `_atom_site_aniso_B_ij` loop. Stored on
+///   `Atom.adp` as a Cartesian U tensor, rotated with each symmetry image.
 ///
 /// Not yet supported (silently ignored):
 /// - Partial occupancy (`_atom_site_occupancy`): parsed onto
@@ -46,6 +52,13 @@ pub fn parse(path: &str) -> io::Result<Structure> {
 
     let mut symmetry_ops: Vec<String> = Vec::new();
     let mut base_atoms: Vec<Atom> = Vec::new();
+    // `_atom_site_label` of each base atom, to match the aniso loop rows.
+    let mut base_labels: Vec<Option<String>> = Vec::new();
+    // `_atom_site_aniso_label` → (U11, U22, U33, U12, U13, U23) in Å², in the
+    // CIF convention (components along the direct axes, reciprocal-length
+    // normalized).
+    let mut aniso_u: std::collections::HashMap<String, [f64; 6]> =
+        std::collections::HashMap::new();
     // Optional `_atom_type_*` loop side-table:
     //   key   = `_atom_type_symbol` value as it appears in the CIF
     //           (may include charge, e.g. "Fe3+", or be bare "Fe")
@@ -58,9 +71,8 @@ pub fn parse(path: &str) -> io::Result<Structure> {
     let mut in_loop = false;
     let mut current_loop_headers: Vec<String> = Vec::new();
 
-    // Sites with `_atom_site_occupancy` < 1: the Atom model has no occupancy
-    // field, so downstream analyses (BVS, XRD) assume full occupancy. We at
-    // least tell the user at import time.
+    // Sites with `_atom_site_occupancy` < 1, counted so the import log can
+    // say that analyses weight them (see `Atom::occupancy`).
     let mut partial_occupancy_sites = 0usize;
 
     // Per CIF spec (IUCr, Hall/Allen/Brown 1991), a `;` at column 1 opens
@@ -71,8 +83,9 @@ pub fn parse(path: &str) -> io::Result<Structure> {
     // hkl reflection dumps here, often 100k+ lines).
     let mut in_text_field = false;
 
-    for line_res in reader.lines() {
+    for (idx, line_res) in reader.lines().enumerate() {
         let line = line_res?;
+        let line_no = idx + 1;
 
         // --- Semicolon text field (highest priority, before trim) ---
         // The CIF rule is strict: the `;` must be the first character of
@@ -95,27 +108,27 @@ pub fn parse(path: &str) -> io::Result<Structure> {
 
         // --- Cell parameters (scalar, not in a loop) ---
         if trimmed.starts_with("_cell_length_a") {
-            a = parse_cif_val(trimmed);
+            a = parse_cell_param(trimmed, line_no)?;
             continue;
         }
         if trimmed.starts_with("_cell_length_b") {
-            b = parse_cif_val(trimmed);
+            b = parse_cell_param(trimmed, line_no)?;
             continue;
         }
         if trimmed.starts_with("_cell_length_c") {
-            c = parse_cif_val(trimmed);
+            c = parse_cell_param(trimmed, line_no)?;
             continue;
         }
         if trimmed.starts_with("_cell_angle_alpha") {
-            alpha = parse_cif_val(trimmed);
+            alpha = parse_cell_param(trimmed, line_no)?;
             continue;
         }
         if trimmed.starts_with("_cell_angle_beta") {
-            beta = parse_cif_val(trimmed);
+            beta = parse_cell_param(trimmed, line_no)?;
             continue;
         }
         if trimmed.starts_with("_cell_angle_gamma") {
-            gamma = parse_cif_val(trimmed);
+            gamma = parse_cell_param(trimmed, line_no)?;
             continue;
         }
 
@@ -167,6 +180,11 @@ pub fn parse(path: &str) -> io::Result<Structure> {
             .iter()
             .any(|h| h.contains("_atom_site_fract_x"));
 
+        let is_aniso_loop = !is_atom_loop
+            && current_loop_headers
+                .iter()
+                .any(|h| h.contains("_atom_site_aniso_label"));
+
         let is_sym_loop = current_loop_headers.iter().any(|h| {
             h.contains("_symmetry_equiv_pos_as_xyz")
                 || h.contains("_space_group_symop_operation_xyz")
@@ -188,11 +206,18 @@ pub fn parse(path: &str) -> io::Result<Structure> {
                 symmetry_ops.push(op);
             }
         } else if is_atom_loop {
-            if let Some(atom) = parse_atom_row(trimmed, &current_loop_headers) {
+            let row = parse_atom_row(trimmed, &current_loop_headers)
+                .map_err(|msg| invalid(line_no, &msg))?;
+            if let Some(atom) = row {
                 if atom.occupancy < 0.99 {
                     partial_occupancy_sites += 1;
                 }
                 base_atoms.push(atom);
+                base_labels.push(row_label(trimmed, &current_loop_headers));
+            }
+        } else if is_aniso_loop {
+            if let Some((label, u)) = parse_aniso_row(trimmed, &current_loop_headers) {
+                aniso_u.insert(label, u);
             }
         } else if is_atom_type_loop {
             if let Some((sym, ox)) =
@@ -203,6 +228,35 @@ pub fn parse(path: &str) -> io::Result<Structure> {
         }
     }
 
+    // A file that parsed line-by-line can still be unusable as a structure;
+    // say which piece is missing rather than producing a degenerate cell.
+    for (tag, value) in [("_cell_length_a", a), ("_cell_length_b", b), ("_cell_length_c", c)] {
+        if value <= 0.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("missing or non-positive {}", tag),
+            ));
+        }
+    }
+    for (tag, value) in [
+        ("_cell_angle_alpha", alpha),
+        ("_cell_angle_beta", beta),
+        ("_cell_angle_gamma", gamma),
+    ] {
+        if value <= 0.0 || value >= 180.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} = {} is outside (0, 180) degrees", tag, value),
+            ));
+        }
+    }
+    if base_atoms.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no atom sites found (expected an _atom_site_fract_x/y/z loop)",
+        ));
+    }
+
     // Apply _atom_type_oxidation_number fallback to atoms with no inline charge.
     if !atom_type_ox.is_empty() {
         for atom in base_atoms.iter_mut() {
@@ -238,6 +292,29 @@ pub fn parse(path: &str) -> io::Result<Structure> {
         }
     }
 
+    // --- Build lattice matrix ---
+    let lattice = abc_to_lattice([a, b, c], [alpha, beta, gamma]).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "cell angles {}, {}, {} do not form a cell",
+                alpha, beta, gamma
+            ),
+        )
+    })?;
+
+    // Cartesian displacement tensor of each base atom: the aniso loop wins
+    // over the isotropic value parse_atom_row stored.
+    for (atom, label) in base_atoms.iter_mut().zip(&base_labels) {
+        if let Some(u) = label.as_ref().and_then(|l| aniso_u.get(l)) {
+            atom.adp = Some(aniso_to_cartesian(*u, lattice));
+        }
+    }
+    let op_rotations: Vec<nalgebra::Matrix3<f64>> = symmetry_ops
+        .iter()
+        .map(|op| cartesian_rotation(op, lattice))
+        .collect();
+
     // --- Expand asymmetric unit with symmetry ---
     let mut final_atoms: Vec<Atom> = Vec::new();
     let epsilon = 1e-3;
@@ -248,7 +325,7 @@ pub fn parse(path: &str) -> io::Result<Structure> {
     let mut split_site_pairs = 0usize;
 
     for atom in &base_atoms {
-        for op in &symmetry_ops {
+        for (op, rot) in symmetry_ops.iter().zip(&op_rotations) {
             let new_pos = apply_symmetry(atom.position, op);
 
             let wx = new_pos[0].rem_euclid(1.0);
@@ -284,30 +361,13 @@ pub fn parse(path: &str) -> io::Result<Structure> {
                     original_index: idx,
                     oxidation: atom.oxidation,
                     occupancy: atom.occupancy,
+                    adp: atom.adp.map(|u| transform_adp(rot, u)),
                 });
             }
         }
     }
 
-    // --- Build lattice matrix and convert to Cartesian ---
-    let to_rad = std::f64::consts::PI / 180.0;
-    let alpha_r = alpha * to_rad;
-    let beta_r = beta * to_rad;
-    let gamma_r = gamma * to_rad;
-    let v = (1.0 - alpha_r.cos().powi(2) - beta_r.cos().powi(2) - gamma_r.cos().powi(2)
-        + 2.0 * alpha_r.cos() * beta_r.cos() * gamma_r.cos())
-    .sqrt();
-
-    let lattice = [
-        [a, 0.0, 0.0],
-        [b * gamma_r.cos(), b * gamma_r.sin(), 0.0],
-        [
-            c * beta_r.cos(),
-            c * (alpha_r.cos() - beta_r.cos() * gamma_r.cos()) / gamma_r.sin(),
-            c * v / gamma_r.sin(),
-        ],
-    ];
-
+    // --- Convert to Cartesian ---
     for atom in &mut final_atoms {
         atom.position = frac_to_cart(atom.position, lattice);
     }
@@ -517,10 +577,12 @@ fn format_frac(v: f64) -> String {
 // Atom loop row parsing
 // =========================================================================
 
-fn parse_atom_row(line: &str, headers: &[String]) -> Option<Atom> {
+/// `Ok(None)` for rows that aren't a complete site (too few columns);
+/// `Err` for a site whose fractional coordinate isn't a number.
+fn parse_atom_row(line: &str, headers: &[String]) -> Result<Option<Atom>, String> {
     let parts: Vec<&str> = line.split_whitespace().collect();
     if parts.len() < 4 {
-        return None;
+        return Ok(None);
     }
 
     let mut label_val: Option<&str> = None;
@@ -529,6 +591,7 @@ fn parse_atom_row(line: &str, headers: &[String]) -> Option<Atom> {
     let mut fy = None;
     let mut fz = None;
     let mut occupancy: Option<f64> = None;
+    let mut u_iso: Option<f64> = None;
 
     for (i, header) in headers.iter().enumerate() {
         if i >= parts.len() {
@@ -544,21 +607,25 @@ fn parse_atom_row(line: &str, headers: &[String]) -> Option<Atom> {
         } else if header.contains("_atom_site_label") {
             label_val = Some(val);
         } else if header.contains("_atom_site_fract_x") {
-            fx = Some(parse_cif_float(val));
+            fx = Some(parse_coordinate(val, "_atom_site_fract_x")?);
         } else if header.contains("_atom_site_fract_y") {
-            fy = Some(parse_cif_float(val));
+            fy = Some(parse_coordinate(val, "_atom_site_fract_y")?);
         } else if header.contains("_atom_site_fract_z") {
-            fz = Some(parse_cif_float(val));
+            fz = Some(parse_coordinate(val, "_atom_site_fract_z")?);
         } else if header.contains("_atom_site_occupancy") {
             // "0.5(2)" parses as 0.5; "." / "?" stay None (full occupancy).
             let cleaned: String = val.chars().take_while(|c| *c != '(').collect();
             occupancy = cleaned.parse::<f64>().ok();
+        } else if header.contains("_atom_site_U_iso_or_equiv") {
+            u_iso = parse_cif_number(val);
+        } else if header.contains("_atom_site_B_iso_or_equiv") {
+            u_iso = parse_cif_number(val).map(b_to_u);
         }
     }
 
     let (fx, fy, fz) = match (fx, fy, fz) {
         (Some(x), Some(y), Some(z)) => (x, y, z),
-        _ => return None,
+        _ => return Ok(None),
     };
 
     // IUCr rule: type_symbol takes precedence. Fall back to label component_0.
@@ -571,13 +638,88 @@ fn parse_atom_row(line: &str, headers: &[String]) -> Option<Atom> {
         ("X".to_string(), None)
     };
 
-    Some(Atom {
+    Ok(Some(Atom {
         element,
         position: [fx, fy, fz],
         original_index: 0,
         oxidation,
         occupancy: occupancy.unwrap_or(1.0).clamp(0.0, 1.0),
-    })
+        adp: u_iso
+            .filter(|u| *u > 0.0)
+            .map(|u| [[u, 0.0, 0.0], [0.0, u, 0.0], [0.0, 0.0, u]]),
+    }))
+}
+
+/// `_atom_site_label` column of an atom-site row, if the loop has one.
+fn row_label(line: &str, headers: &[String]) -> Option<String> {
+    let col = headers
+        .iter()
+        .position(|h| h.split_whitespace().next() == Some("_atom_site_label"))?;
+    line.split_whitespace().nth(col).map(str::to_string)
+}
+
+/// One row of an `_atom_site_aniso_*` loop: the site label and
+/// (U11, U22, U33, U12, U13, U23) in Å². B columns are converted to U.
+/// `None` unless all six components parse.
+fn parse_aniso_row(line: &str, headers: &[String]) -> Option<(String, [f64; 6])> {
+    const ORDER: [&str; 6] = ["11", "22", "33", "12", "13", "23"];
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let mut label = None;
+    let mut u = [None; 6];
+    for (header, val) in headers.iter().zip(&parts) {
+        let tag = header.split_whitespace().next().unwrap_or("");
+        if tag == "_atom_site_aniso_label" {
+            label = Some(val.to_string());
+            continue;
+        }
+        let component = tag
+            .strip_prefix("_atom_site_aniso_U_")
+            .map(|ij| (ij, false))
+            .or_else(|| tag.strip_prefix("_atom_site_aniso_B_").map(|ij| (ij, true)));
+        let Some((ij, is_b)) = component else {
+            continue;
+        };
+        if let Some(k) = ORDER.iter().position(|o| *o == ij) {
+            u[k] = parse_cif_number(val).map(|v| if is_b { b_to_u(v) } else { v });
+        }
+    }
+    let [u11, u22, u33, u12, u13, u23] = u;
+    Some((label?, [u11?, u22?, u33?, u12?, u13?, u23?]))
+}
+
+/// B = 8π²U.
+fn b_to_u(b: f64) -> f64 {
+    b / (8.0 * std::f64::consts::PI * std::f64::consts::PI)
+}
+
+/// CIF U_ij → Cartesian U: U_cart = A N U Nᵀ Aᵀ with A the column matrix
+/// of lattice vectors and N = diag(a*, b*, c*) (Grosse-Kunstleve & Adams,
+/// J. Appl. Cryst. 35, 477 (2002)).
+fn aniso_to_cartesian(u: [f64; 6], lattice: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let [u11, u22, u33, u12, u13, u23] = u;
+    let a = lattice_to_matrix3(lattice).transpose();
+    // Columns of inv(lattice) are the reciprocal vectors.
+    let recip = invert_matrix_3x3(lattice);
+    let norm = |k: usize| (recip[0][k].powi(2) + recip[1][k].powi(2) + recip[2][k].powi(2)).sqrt();
+    let n = nalgebra::Matrix3::from_diagonal(&nalgebra::Vector3::new(norm(0), norm(1), norm(2)));
+    let u_cif = [[u11, u12, u13], [u12, u22, u23], [u13, u23, u33]];
+    transform_adp(&(a * n), u_cif)
+}
+
+/// Rotation part of a symmetry operation in Cartesian axes, A R A⁻¹.
+fn cartesian_rotation(op: &str, lattice: [[f64; 3]; 3]) -> nalgebra::Matrix3<f64> {
+    let origin = apply_symmetry([0.0; 3], op);
+    let mut r = nalgebra::Matrix3::zeros();
+    for j in 0..3 {
+        let mut e = [0.0; 3];
+        e[j] = 1.0;
+        let image = apply_symmetry(e, op);
+        for i in 0..3 {
+            r[(i, j)] = image[i] - origin[i];
+        }
+    }
+    let a = lattice_to_matrix3(lattice).transpose();
+    a * r * a.try_inverse().unwrap_or_else(nalgebra::Matrix3::identity)
 }
 
 /// Parse one row of an `_atom_type_*` loop. Returns the (`_atom_type_symbol`,
@@ -742,16 +884,35 @@ fn normalize_element(s: &str) -> String {
 // Low-level value extraction
 // =========================================================================
 
-fn parse_cif_val(line: &str) -> f64 {
-    if let Some(idx) = line.find(char::is_whitespace) {
-        return parse_cif_float(line[idx..].trim());
-    }
-    0.0
+fn invalid(line_no: usize, msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_no, msg))
 }
 
-fn parse_cif_float(s: &str) -> f64 {
+/// Numeric CIF value with its standard uncertainty stripped: "5.4321(8)" → 5.4321.
+fn parse_cif_number(s: &str) -> Option<f64> {
     let clean: String = s.chars().take_while(|c| *c != '(').collect();
-    clean.parse().unwrap_or(0.0)
+    clean.parse().ok()
+}
+
+/// Value of a scalar cell tag line such as `_cell_length_a 5.4321(8)`. A tag
+/// with no value on the line is left at 0 for the post-parse completeness
+/// check; a value that is present but not numeric is an error.
+fn parse_cell_param(line: &str, line_no: usize) -> io::Result<f64> {
+    let (tag, value) = match line.find(char::is_whitespace) {
+        Some(idx) => (&line[..idx], line[idx..].trim()),
+        None => return Ok(0.0),
+    };
+    parse_cif_number(value).ok_or_else(|| {
+        invalid(
+            line_no,
+            &format!("{}: expected a number, found '{}'", tag, value),
+        )
+    })
+}
+
+fn parse_coordinate(val: &str, tag: &str) -> Result<f64, String> {
+    parse_cif_number(val)
+        .ok_or_else(|| format!("{}: expected a number, found '{}'", tag, val))
 }
 
 /// Extract a (possibly quoted) value from a scalar CIF line like
@@ -786,37 +947,8 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     writeln!(file, "_pd_phase_name 'Exported Structure'")?;
     writeln!(file, "_symmetry_space_group_name_H-M 'P 1'")?;
     writeln!(file, "_symmetry_Int_Tables_number 1")?;
-
-    let a_vec = structure.lattice[0];
-    let b_vec = structure.lattice[1];
-    let c_vec = structure.lattice[2];
-
-    let a = (a_vec[0].powi(2) + a_vec[1].powi(2) + a_vec[2].powi(2)).sqrt();
-    let b = (b_vec[0].powi(2) + b_vec[1].powi(2) + b_vec[2].powi(2)).sqrt();
-    let c = (c_vec[0].powi(2) + c_vec[1].powi(2) + c_vec[2].powi(2)).sqrt();
-
-    let b_dot_c = b_vec[0] * c_vec[0] + b_vec[1] * c_vec[1] + b_vec[2] * c_vec[2];
-    let a_dot_c = a_vec[0] * c_vec[0] + a_vec[1] * c_vec[1] + a_vec[2] * c_vec[2];
-    let a_dot_b = a_vec[0] * b_vec[0] + a_vec[1] * b_vec[1] + a_vec[2] * b_vec[2];
-
-    let to_deg = 180.0 / std::f64::consts::PI;
-    let alpha = (b_dot_c / (b * c)).acos() * to_deg;
-    let beta = (a_dot_c / (a * c)).acos() * to_deg;
-    let gamma = (a_dot_b / (a * b)).acos() * to_deg;
-
-    writeln!(file, "_cell_length_a    {:.6}", a)?;
-    writeln!(file, "_cell_length_b    {:.6}", b)?;
-    writeln!(file, "_cell_length_c    {:.6}", c)?;
-    writeln!(file, "_cell_angle_alpha {:.6}", alpha)?;
-    writeln!(file, "_cell_angle_beta  {:.6}", beta)?;
-    writeln!(file, "_cell_angle_gamma {:.6}", gamma)?;
-
-    writeln!(file, "loop_")?;
-    writeln!(file, " _atom_site_label")?;
-    writeln!(file, " _atom_site_fract_x")?;
-    writeln!(file, " _atom_site_fract_y")?;
-    writeln!(file, " _atom_site_fract_z")?;
-    writeln!(file, " _atom_site_occupancy")?;
+    write_cell(&mut file, structure.lattice)?;
+    write_site_header(&mut file)?;
 
     use crate::utils::linalg::cart_to_frac;
 
@@ -838,6 +970,164 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     Ok(())
 }
 
+/// Write the asymmetric unit of `structure` in its standardized
+/// conventional cell, with the space group symbol and a
+/// `_symmetry_equiv_pos_as_xyz` loop, so `parse` expands it back to the
+/// full cell.
+///
+/// The group is detected with the app-wide `symmetry::SYMPREC`. Molecules
+/// and partially occupied structures are written in P1 via [`write`]: the
+/// symmetry search would see split sites as clashing atoms.
+pub fn write_symmetrized(path: &str, structure: &Structure) -> io::Result<()> {
+    use crate::physics::analysis::symmetry::{
+        moyo_cell, spacegroup_symbol, species_element, SYMPREC,
+    };
+    use crate::utils::linalg::matrix3_to_arr;
+    use moyo::base::{AngleTolerance, Cell};
+    use moyo::data::Setting;
+    use moyo::MoyoDataset;
+
+    if !structure.is_periodic || structure.atoms.is_empty() {
+        return write(path, structure);
+    }
+    if structure.atoms.iter().any(|a| a.occupancy < 1.0 - 1e-6) {
+        crate::utils::console::log_warn(
+            "CIF: partial occupancies present — writing all sites in P 1 instead",
+        );
+        return write(path, structure);
+    }
+
+    let fail = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let search = |cell: &Cell| {
+        MoyoDataset::new(
+            cell,
+            SYMPREC,
+            AngleTolerance::Default,
+            Setting::Spglib,
+            true,
+        )
+        .map_err(|e| fail(format!("symmetry search failed: {:?}", e)))
+    };
+    let input = moyo_cell(structure).map_err(fail)?;
+    // Operations and orbits must refer to the standardized cell for the
+    // H-M symbol to describe them, so search again on that cell.
+    let std_cell = search(&input)?.std_cell;
+    let dataset = search(&std_cell)?;
+
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "data_generated_by_cview")?;
+    writeln!(file, "_pd_phase_name 'Exported Structure'")?;
+    writeln!(
+        file,
+        "_symmetry_space_group_name_H-M '{}'",
+        spacegroup_symbol(dataset.number)
+    )?;
+    writeln!(file, "_symmetry_Int_Tables_number {}", dataset.number)?;
+    let std_lattice = matrix3_to_arr(std_cell.lattice.basis.transpose());
+    write_cell(&mut file, std_lattice)?;
+
+    writeln!(file, "loop_")?;
+    writeln!(file, " _symmetry_equiv_pos_as_xyz")?;
+    for op in &dataset.operations {
+        let rows: Vec<String> = (0..3)
+            .map(|r| {
+                let coeffs = [0, 1, 2].map(|c| op.rotation[(r, c)]);
+                format_symop_row(coeffs, op.translation[r])
+            })
+            .collect();
+        writeln!(file, " '{}'", rows.join(","))?;
+    }
+
+    write_site_header(&mut file)?;
+    let mut per_element: Vec<(String, usize)> = Vec::new();
+    for (i, pos) in std_cell.positions.iter().enumerate() {
+        if dataset.orbits[i] != i {
+            continue;
+        }
+        let id = std_cell.numbers[i];
+        let element = species_element(structure, &input, id).unwrap_or("X");
+        let count = match per_element.iter_mut().find(|(el, _)| el == element) {
+            Some((_, n)) => {
+                *n += 1;
+                *n
+            }
+            None => {
+                per_element.push((element.to_string(), 1));
+                1
+            }
+        };
+        let [u, v, w] = [0, 1, 2].map(|k| clean_frac(pos[k].rem_euclid(1.0)));
+        writeln!(
+            file,
+            " {}{} {:.6} {:.6} {:.6} {:.4}",
+            element, count, u, v, w, 1.0
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_cell(file: &mut impl Write, lattice: [[f64; 3]; 3]) -> io::Result<()> {
+    let cell = cell_parameters(lattice)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let [a, b, c] = cell.lengths;
+    let [alpha, beta, gamma] = cell.angles;
+
+    writeln!(file, "_cell_length_a    {:.6}", a)?;
+    writeln!(file, "_cell_length_b    {:.6}", b)?;
+    writeln!(file, "_cell_length_c    {:.6}", c)?;
+    writeln!(file, "_cell_angle_alpha {:.6}", alpha)?;
+    writeln!(file, "_cell_angle_beta  {:.6}", beta)?;
+    writeln!(file, "_cell_angle_gamma {:.6}", gamma)
+}
+
+fn write_site_header(file: &mut impl Write) -> io::Result<()> {
+    writeln!(file, "loop_")?;
+    writeln!(file, " _atom_site_label")?;
+    writeln!(file, " _atom_site_fract_x")?;
+    writeln!(file, " _atom_site_fract_y")?;
+    writeln!(file, " _atom_site_fract_z")?;
+    writeln!(file, " _atom_site_occupancy")
+}
+
+/// One coordinate of a symop in the `x,y,z` form `parse` reads, e.g.
+/// `-x+y` or `z+1/2`. Translations are wrapped to [0, 1).
+fn format_symop_row(coeffs: [i32; 3], translation: f64) -> String {
+    let mut out = String::new();
+    for (c, axis) in coeffs.iter().zip(["x", "y", "z"]) {
+        match *c {
+            0 => continue,
+            1 if out.is_empty() => {}
+            1 => out.push('+'),
+            -1 => out.push('-'),
+            n => out.push_str(&format!("{:+}*", n)),
+        }
+        out.push_str(axis);
+    }
+    let t = clean_frac(translation.rem_euclid(1.0));
+    if t > 1e-6 && t < 1.0 - 1e-6 {
+        if !out.is_empty() {
+            out.push('+');
+        }
+        out.push_str(&format_frac(t));
+    }
+    if out.is_empty() {
+        out.push('0');
+    }
+    out
+}
+
+/// Snap values within 1e-6 of 0 or 1 to 0, so wrapped coordinates never
+/// print as 1.000000.
+fn clean_frac(v: f64) -> f64 {
+    if !(1e-6..=1.0 - 1e-6).contains(&v) {
+        0.0
+    } else {
+        v
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -880,6 +1170,141 @@ mod tests {
         assert_eq!(parse_oxidation_from_species("Fe(III)"), None);
     }
 
+    fn write_tmp(name: &str, contents: &str) -> std::path::PathBuf {
+        let mut p = std::env::temp_dir();
+        p.push(format!("cview_cif_{}_{}.cif", std::process::id(), name));
+        std::fs::write(&p, contents).unwrap();
+        p
+    }
+
+    #[test]
+    fn errors_name_line_and_tag() {
+        let p = write_tmp(
+            "bad_coord",
+            "data_x\n_cell_length_a 4.0\n_cell_length_b 4.0\n_cell_length_c 4.0\n\
+             loop_\n_atom_site_label\n_atom_site_fract_x\n_atom_site_fract_y\n_atom_site_fract_z\n\
+             Si1 0.0 0.0 0.0\nSi2 0.5 abc 0.5\n",
+        );
+        let msg = parse(p.to_str().unwrap()).unwrap_err().to_string();
+        let _ = std::fs::remove_file(&p);
+        assert!(msg.starts_with("line 11:"), "{msg}");
+        assert!(msg.contains("_atom_site_fract_y"), "{msg}");
+
+        let p = write_tmp("bad_cell", "data_x\n_cell_length_a 4.0(1)\n_cell_length_b ?\n");
+        let msg = parse(p.to_str().unwrap()).unwrap_err().to_string();
+        let _ = std::fs::remove_file(&p);
+        assert!(msg.starts_with("line 3:"), "{msg}");
+        assert!(msg.contains("_cell_length_b"), "{msg}");
+    }
+
+    #[test]
+    fn errors_on_missing_cell_or_atoms() {
+        let p = write_tmp("no_atoms", "data_x\n_cell_length_a 4\n_cell_length_b 4\n_cell_length_c 4\n");
+        let msg = parse(p.to_str().unwrap()).unwrap_err().to_string();
+        let _ = std::fs::remove_file(&p);
+        assert!(msg.contains("no atom sites"), "{msg}");
+
+        let p = write_tmp("no_c", "data_x\n_cell_length_a 4\n_cell_length_b 4\n");
+        let msg = parse(p.to_str().unwrap()).unwrap_err().to_string();
+        let _ = std::fs::remove_file(&p);
+        assert!(msg.contains("_cell_length_c"), "{msg}");
+    }
+
+    #[test]
+    fn displacement_parameters_follow_symmetry() {
+        let p = write_tmp(
+            "adp",
+            "data_x\n_cell_length_a 5\n_cell_length_b 5\n_cell_length_c 5\n\
+             loop_\n_symmetry_equiv_pos_as_xyz\nx,y,z\n-y,x,z\n\
+             loop_\n_atom_site_label\n_atom_site_fract_x\n_atom_site_fract_y\n\
+             _atom_site_fract_z\n_atom_site_U_iso_or_equiv\n_atom_site_B_iso_or_equiv\n\
+             Fe1 0.1 0.2 0.3 0.02 .\nO1 0.4 0.0 0.0 . 1.5\nN1 0.0 0.0 0.5 ? ?\n\
+             loop_\n_atom_site_aniso_label\n_atom_site_aniso_U_11\n_atom_site_aniso_U_22\n\
+             _atom_site_aniso_U_33\n_atom_site_aniso_U_12\n_atom_site_aniso_U_13\n\
+             _atom_site_aniso_U_23\nFe1 0.010(1) 0.020(1) 0.030(2) 0 0 0\n",
+        );
+        let s = parse(p.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&p);
+
+        let fe: Vec<_> = s.atoms.iter().filter(|a| a.element == "Fe").collect();
+        assert_eq!(fe.len(), 2);
+        // The aniso loop overrides U_iso; the 4-fold image swaps U11/U22.
+        let diag = |a: &Atom| {
+            let u = a.adp.unwrap();
+            [u[0][0], u[1][1], u[2][2]]
+        };
+        for (got, want) in diag(fe[0]).iter().zip([0.01, 0.02, 0.03]) {
+            assert!((got - want).abs() < 1e-12);
+        }
+        for (got, want) in diag(fe[1]).iter().zip([0.02, 0.01, 0.03]) {
+            assert!((got - want).abs() < 1e-12);
+        }
+
+        let o = s.atoms.iter().find(|a| a.element == "O").unwrap();
+        let u_o = 1.5 / (8.0 * std::f64::consts::PI.powi(2));
+        assert!(diag(o).iter().all(|u| (u - u_o).abs() < 1e-12));
+        assert!(s.atoms.iter().find(|a| a.element == "N").unwrap().adp.is_none());
+    }
+
+    #[test]
+    fn symop_rows_use_parser_syntax() {
+        assert_eq!(format_symop_row([1, 0, 0], 0.0), "x");
+        assert_eq!(format_symop_row([-1, 1, 0], 0.5), "-x+y+1/2");
+        assert_eq!(format_symop_row([0, 0, 1], -0.25), "z+3/4");
+        assert_eq!(format_symop_row([0, -1, 0], 1.0), "-y");
+    }
+
+    #[test]
+    fn symmetrized_rock_salt_round_trips() {
+        let a = 5.64;
+        let lattice = [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]];
+        let sites = [
+            ("Na", [0.0, 0.0, 0.0]),
+            ("Na", [0.5, 0.5, 0.0]),
+            ("Na", [0.5, 0.0, 0.5]),
+            ("Na", [0.0, 0.5, 0.5]),
+            ("Cl", [0.5, 0.5, 0.5]),
+            ("Cl", [0.0, 0.0, 0.5]),
+            ("Cl", [0.0, 0.5, 0.0]),
+            ("Cl", [0.5, 0.0, 0.0]),
+        ];
+        let s = Structure {
+            lattice,
+            atoms: sites
+                .iter()
+                .enumerate()
+                .map(|(i, (el, f))| Atom {
+                    element: el.to_string(),
+                    position: frac_to_cart(*f, lattice),
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        };
+
+        let p = write_tmp("symmetrized", "");
+        write_symmetrized(p.to_str().unwrap(), &s).unwrap();
+        let text = std::fs::read_to_string(&p).unwrap();
+        let back = parse(p.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&p);
+
+        assert!(text.contains("_symmetry_Int_Tables_number 225"), "{text}");
+        // Asymmetric unit: one Na and one Cl site.
+        let site_rows = text
+            .lines()
+            .filter(|l| l.starts_with(" Na") || l.starts_with(" Cl"))
+            .count();
+        assert_eq!(site_rows, 2, "{text}");
+
+        assert_eq!(back.atoms.len(), 8);
+        assert_eq!(back.atoms.iter().filter(|a| a.element == "Na").count(), 4);
+        assert!((back.cell_volume() - s.cell_volume()).abs() < 1e-3);
+    }
+
     #[test]
     fn parses_batio3_with_inline_oxidation() {
         // The repo ships BaTiO3.cif with inline charges in
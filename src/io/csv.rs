@@ -0,0 +1,109 @@
+// src/io/csv.rs
+//
+// Plain-data export for spreadsheets and scripts. One table of atoms
+// (Cartesian and fractional coordinates) and, optionally, one of bonds as
+// reported by `rendering::bonds::structure_bonds`.
+
+use crate::model::Structure;
+use crate::utils::linalg::cart_to_frac;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Write `index,element,x,y,z,frac_a,frac_b,frac_c` rows (Å). Fractional
+/// columns are left empty for molecules, which have no cell.
+pub fn write_atoms(path: &str, structure: &Structure) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_atoms_to(&mut w, structure)?;
+    w.flush()
+}
+
+/// Write `i,element_i,j,element_j,length` rows (Å), indices into the atom table.
+pub fn write_bonds(
+    path: &str,
+    structure: &Structure,
+    bonds: &[(usize, usize, f64)],
+) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_bonds_to(&mut w, structure, bonds)?;
+    w.flush()
+}
+
+fn write_atoms_to(w: &mut impl Write, structure: &Structure) -> io::Result<()> {
+    writeln!(w, "index,element,x,y,z,frac_a,frac_b,frac_c")?;
+    for (i, atom) in structure.atoms.iter().enumerate() {
+        let [x, y, z] = atom.position;
+        write!(w, "{},{},{:.6},{:.6},{:.6}", i, atom.element, x, y, z)?;
+        match cart_to_frac(atom.position, structure.lattice).filter(|_| structure.is_periodic) {
+            Some([fa, fb, fc]) => writeln!(w, ",{:.6},{:.6},{:.6}", fa, fb, fc)?,
+            None => writeln!(w, ",,,")?,
+        }
+    }
+    Ok(())
+}
+
+fn write_bonds_to(
+    w: &mut impl Write,
+    structure: &Structure,
+    bonds: &[(usize, usize, f64)],
+) -> io::Result<()> {
+    writeln!(w, "i,element_i,j,element_j,length")?;
+    for &(i, j, d) in bonds {
+        let el = |k: usize| structure.atoms.get(k).map_or("", |a| a.element.as_str());
+        writeln!(w, "{},{},{},{},{:.6}", i, el(i), j, el(j), d)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Atom;
+
+    fn atom(element: &str, position: [f64; 3]) -> Atom {
+        Atom {
+            element: element.to_string(),
+            position,
+            original_index: 0,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        }
+    }
+
+    #[test]
+    fn atoms_and_bonds_tables() {
+        let mut s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![atom("Na", [0.0, 0.0, 0.0]), atom("Cl", [2.0, 0.0, 0.0])],
+            formula: String::new(),
+            is_periodic: true,
+        };
+
+        let mut out = Vec::new();
+        write_atoms_to(&mut out, &s).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows[0], "index,element,x,y,z,frac_a,frac_b,frac_c");
+        assert_eq!(
+            rows[2],
+            "1,Cl,2.000000,0.000000,0.000000,0.500000,0.000000,0.000000"
+        );
+
+        s.is_periodic = false;
+        let mut out = Vec::new();
+        write_atoms_to(&mut out, &s).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .nth(2)
+            .unwrap()
+            .ends_with(",,,"));
+
+        let mut out = Vec::new();
+        write_bonds_to(&mut out, &s, &[(0, 1, 2.0)]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "i,element_i,j,element_j,length\n0,Na,1,Cl,2.000000\n"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+// src/io/materials_project.rs
+//
+// Materials Project import. The summary endpoint returns the structure as a
+// pymatgen `Structure.as_dict()`: a lattice matrix (rows = a, b, c in Å)
+// and sites carrying Cartesian `xyz` and fractional `abc` coordinates with a
+// `species` list of {element, occu, oxidation_state}. A disordered site
+// becomes one coincident atom per species, the same way the CIF reader
+// handles partial occupancy.
+//
+// Parsing is always built; the HTTP request itself needs the
+// `materials-project` cargo feature so default builds carry no network
+// dependencies.
+
+use crate::model::structure::{generate_formula, Atom, Structure};
+use crate::utils::linalg::frac_to_cart;
+use serde_json::Value;
+use std::fmt;
+
+/// Summary endpoint of the Materials Project API (v2).
+pub const API_URL: &str = "https://api.materialsproject.org/materials/summary/";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MpError {
+    /// The ID is not of the `mp-1234` form.
+    InvalidId(String),
+    /// No API key in the preferences, or the server rejected it.
+    Auth(String),
+    /// The query succeeded but matched no material.
+    NotFound(String),
+    /// Connection, TLS, timeout or an unexpected HTTP status.
+    Network(String),
+    /// The response did not contain a usable structure.
+    Parse(String),
+}
+
+impl fmt::Display for MpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MpError::InvalidId(id) => write!(
+                f,
+                "'{}' is not a Materials Project ID (expected e.g. mp-149)",
+                id
+            ),
+            MpError::Auth(msg) => write!(
+                f,
+                "Authentication failed: {}. Check the API key from your \
+                 Materials Project dashboard.",
+                msg
+            ),
+            MpError::NotFound(id) => write!(f, "No material with ID '{}' was found", id),
+            MpError::Network(msg) => write!(f, "Network error: {}", msg),
+            MpError::Parse(msg) => write!(f, "Unexpected response from the server: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MpError {}
+
+/// Lowercased, trimmed ID of the form `<prefix>-<digits>` (`mp-149`,
+/// `mvc-12`). Bare digits get the `mp-` prefix.
+pub fn normalize_id(input: &str) -> Result<String, MpError> {
+    let id = input.trim().to_lowercase();
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(format!("mp-{}", id));
+    }
+    let valid = id.split_once('-').is_some_and(|(prefix, num)| {
+        !prefix.is_empty()
+            && prefix.chars().all(|c| c.is_ascii_lowercase())
+            && !num.is_empty()
+            && num.chars().all(|c| c.is_ascii_digit())
+    });
+    if valid {
+        Ok(id)
+    } else {
+        Err(MpError::InvalidId(input.trim().to_string()))
+    }
+}
+
+/// Structure of the first document in a summary response.
+pub fn parse_summary(json: &str, id: &str) -> Result<Structure, MpError> {
+    let root: Value = serde_json::from_str(json).map_err(|e| MpError::Parse(e.to_string()))?;
+    let doc = root
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or_else(|| MpError::Parse("missing 'data' array".into()))?
+        .first()
+        .ok_or_else(|| MpError::NotFound(id.to_string()))?;
+    let dict = doc
+        .get("structure")
+        .ok_or_else(|| MpError::Parse("document has no 'structure' field".into()))?;
+
+    let mut structure = structure_from_dict(dict)?;
+    if let Some(formula) = doc.get("formula_pretty").and_then(Value::as_str) {
+        structure.formula = formula.to_string();
+    }
+    Ok(structure)
+}
+
+fn structure_from_dict(dict: &Value) -> Result<Structure, MpError> {
+    let rows = dict
+        .pointer("/lattice/matrix")
+        .and_then(Value::as_array)
+        .filter(|m| m.len() == 3)
+        .ok_or_else(|| MpError::Parse("lattice matrix must have three rows".into()))?;
+    let mut lattice = [[0.0; 3]; 3];
+    for (row, out) in rows.iter().zip(lattice.iter_mut()) {
+        *out = vec3(row).ok_or_else(|| MpError::Parse("malformed lattice row".into()))?;
+    }
+
+    let sites = dict
+        .get("sites")
+        .and_then(Value::as_array)
+        .ok_or_else(|| MpError::Parse("structure has no sites".into()))?;
+
+    let mut atoms = Vec::new();
+    for (i, site) in sites.iter().enumerate() {
+        let position = match site.get("xyz").and_then(vec3) {
+            Some(xyz) => xyz,
+            None => {
+                let f = site
+                    .get("abc")
+                    .and_then(vec3)
+                    .ok_or_else(|| MpError::Parse(format!("site {} has no coordinates", i)))?;
+                frac_to_cart(f, lattice)
+            }
+        };
+
+        let species = site
+            .get("species")
+            .and_then(Value::as_array)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| MpError::Parse(format!("site {} has no species", i)))?;
+        for sp in species {
+            let element = sp
+                .get("element")
+                .and_then(Value::as_str)
+                .ok_or_else(|| MpError::Parse(format!("site {} species has no element", i)))?;
+            // Fractional valences (mixed-valence averages) are not formal
+            // oxidation states; leave those for the BVS guesser.
+            let oxidation = sp
+                .get("oxidation_state")
+                .and_then(Value::as_f64)
+                .filter(|q| (q - q.round()).abs() < 1e-6)
+                .map(|q| q.round() as i32);
+            atoms.push(Atom {
+                element: element.to_string(),
+                position,
+                original_index: atoms.len(),
+                oxidation,
+                occupancy: sp.get("occu").and_then(Value::as_f64).unwrap_or(1.0),
+                adp: None,
+            });
+        }
+    }
+
+    let formula = generate_formula(&atoms);
+    Ok(Structure {
+        lattice,
+        atoms,
+        formula,
+        is_periodic: true,
+    })
+}
+
+fn vec3(v: &Value) -> Option<[f64; 3]> {
+    let a = v.as_array().filter(|a| a.len() == 3)?;
+    Some([a[0].as_f64()?, a[1].as_f64()?, a[2].as_f64()?])
+}
+
+/// Fetch `id` (already normalized) from the summary endpoint.
+#[cfg(feature = "materials-project")]
+pub fn fetch(id: &str, api_key: &str) -> Result<Structure, MpError> {
+    if api_key.trim().is_empty() {
+        return Err(MpError::Auth("no API key set".into()));
+    }
+    let response = ureq::get(API_URL)
+        .query("material_ids", id)
+        .query("_fields", "material_id,formula_pretty,structure")
+        .set("X-API-KEY", api_key.trim())
+        .set("Accept", "application/json")
+        .timeout(std::time::Duration::from_secs(30))
+        .call();
+
+    let body = match response {
+        Ok(r) => r
+            .into_string()
+            .map_err(|e| MpError::Network(e.to_string()))?,
+        Err(ureq::Error::Status(401 | 403, _)) => {
+            return Err(MpError::Auth("the server rejected the API key".into()))
+        }
+        Err(ureq::Error::Status(404, _)) => return Err(MpError::NotFound(id.to_string())),
+        Err(ureq::Error::Status(code, r)) => {
+            return Err(MpError::Network(format!(
+                "HTTP {} {}",
+                code,
+                r.status_text()
+            )))
+        }
+        Err(ureq::Error::Transport(t)) => return Err(MpError::Network(t.to_string())),
+    };
+    parse_summary(&body, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SI: &str = r#"{
+        "data": [{
+            "material_id": "mp-149",
+            "formula_pretty": "Si",
+            "structure": {
+                "@module": "pymatgen.core.structure",
+                "lattice": {"matrix": [[0.0, 2.73, 2.73], [2.73, 0.0, 2.73], [2.73, 2.73, 0.0]]},
+                "sites": [
+                    {"species": [{"element": "Si", "occu": 1}], "abc": [0.0, 0.0, 0.0],
+                     "xyz": [0.0, 0.0, 0.0], "label": "Si"},
+                    {"species": [{"element": "Si", "occu": 1}], "abc": [0.25, 0.25, 0.25],
+                     "label": "Si"}
+                ]
+            }
+        }]
+    }"#;
+
+    #[test]
+    fn parses_pymatgen_structure() {
+        let s = parse_summary(SI, "mp-149").unwrap();
+        assert!(s.is_periodic);
+        assert_eq!(s.formula, "Si");
+        assert_eq!(s.atoms.len(), 2);
+        assert_eq!(s.lattice[0], [0.0, 2.73, 2.73]);
+        // Second site only has fractional coordinates.
+        for k in 0..3 {
+            assert!((s.atoms[1].position[k] - 1.365).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn disordered_site_splits_into_species() {
+        let json = r#"{"data": [{"structure": {
+            "lattice": {"matrix": [[4, 0, 0], [0, 4, 0], [0, 0, 4]]},
+            "sites": [{"species": [
+                {"element": "Fe", "occu": 0.5, "oxidation_state": 2.0},
+                {"element": "Mn", "occu": 0.5, "oxidation_state": 2.5}
+            ], "xyz": [1, 1, 1]}]
+        }}]}"#;
+        let s = parse_summary(json, "mp-1").unwrap();
+        assert_eq!(s.atoms.len(), 2);
+        assert_eq!(s.atoms[0].position, s.atoms[1].position);
+        assert_eq!(s.atoms[0].occupancy, 0.5);
+        assert_eq!(s.atoms[0].oxidation, Some(2));
+        assert_eq!(s.atoms[1].oxidation, None);
+        assert_eq!(s.formula, "FeMn");
+    }
+
+    #[test]
+    fn empty_result_is_not_found() {
+        assert!(matches!(
+            parse_summary(r#"{"data": [], "meta": {}}"#, "mp-0"),
+            Err(MpError::NotFound(id)) if id == "mp-0"
+        ));
+        assert!(matches!(
+            parse_summary("not json", "mp-0"),
+            Err(MpError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn ids_are_normalized() {
+        assert_eq!(normalize_id(" MP-149 ").unwrap(), "mp-149");
+        assert_eq!(normalize_id("149").unwrap(), "mp-149");
+        assert_eq!(normalize_id("mvc-12").unwrap(), "mvc-12");
+        assert!(normalize_id("mp-").is_err());
+        assert!(normalize_id("Si").is_err());
+        assert!(normalize_id("").is_err());
+    }
+}
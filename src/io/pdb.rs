@@ -0,0 +1,246 @@
+// src/io/pdb.rs
+//
+// Protein Data Bank files: ATOM/HETATM records for the atoms and CRYST1
+// for the cell. PDB is a fixed-column format, so fields are cut by column
+// (1-based, inclusive, as in the wwPDB specification) rather than split on
+// whitespace. Only the first MODEL of an NMR ensemble is read; every other
+// record is ignored.
+//
+// Without a usable CRYST1 the structure is a molecule: it gets a box that
+// holds its atoms with `VACUUM` Å to spare and is not treated as periodic.
+
+use crate::model::elements::get_atomic_number;
+use crate::model::structure::generate_formula;
+use crate::model::{Atom, Structure};
+use crate::utils::geometry::abc_to_lattice;
+use std::fs;
+use std::io;
+
+/// Vacuum added to the atoms' extent along each axis of the box that
+/// stands in for a missing CRYST1.
+const VACUUM: f64 = 10.0;
+
+pub fn parse(path: &str) -> io::Result<Structure> {
+    parse_str(&fs::read_to_string(path)?)
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn parse_str(content: &str) -> io::Result<Structure> {
+    let mut cell = None;
+    let mut atoms = Vec::new();
+
+    for (line_no, line) in content.lines().enumerate() {
+        let record = column(line, 1, 6);
+        match record {
+            "CRYST1" => {
+                let field = |start, end| column(line, start, end).parse::<f64>().ok();
+                let params = [
+                    field(7, 15),
+                    field(16, 24),
+                    field(25, 33),
+                    field(34, 40),
+                    field(41, 47),
+                    field(48, 54),
+                ];
+                let Some(p) = params.into_iter().collect::<Option<Vec<f64>>>() else {
+                    return Err(invalid(format!("Line {}: bad CRYST1 record", line_no + 1)));
+                };
+                cell = Some(([p[0], p[1], p[2]], [p[3], p[4], p[5]]));
+            }
+            "ATOM" | "HETATM" => {
+                let coord = |start, end| column(line, start, end).parse::<f64>().ok();
+                let (Some(x), Some(y), Some(z)) = (coord(31, 38), coord(39, 46), coord(47, 54))
+                else {
+                    return Err(invalid(format!(
+                        "Line {}: bad coordinates in {} record",
+                        line_no + 1,
+                        record
+                    )));
+                };
+                let occupancy = column(line, 55, 60).parse().unwrap_or(1.0);
+                atoms.push(Atom {
+                    element: element_symbol(line),
+                    position: [x, y, z],
+                    original_index: atoms.len(),
+                    oxidation: None,
+                    occupancy,
+                    adp: None,
+                });
+            }
+            "ENDMDL" => break,
+            _ => {}
+        }
+    }
+    if atoms.is_empty() {
+        return Err(invalid("No ATOM or HETATM records"));
+    }
+
+    // Structures without a crystal cell (NMR, models) carry the placeholder
+    // CRYST1 1 1 1 90 90 90. PDB coordinates are orthogonalized with a
+    // along x and b in the xy-plane, which is what `abc_to_lattice` builds.
+    let lattice = cell
+        .filter(|(len, _)| len.iter().any(|&l| (l - 1.0).abs() > 1e-6))
+        .map(|(len, deg)| {
+            abc_to_lattice(len, deg).ok_or_else(|| invalid("CRYST1 angles do not form a cell"))
+        })
+        .transpose()?;
+    let is_periodic = lattice.is_some();
+    let lattice = lattice.unwrap_or_else(|| vacuum_box(&mut atoms));
+
+    let formula = generate_formula(&atoms);
+    Ok(Structure {
+        lattice,
+        atoms,
+        formula,
+        is_periodic,
+    })
+}
+
+/// Columns `start..=end` (1-based) of `line`, trimmed; empty when the line
+/// is shorter.
+fn column(line: &str, start: usize, end: usize) -> &str {
+    let end = end.min(line.len());
+    line.get(start - 1..end).unwrap_or_default().trim()
+}
+
+/// Element from columns 77-78, or else from the atom name in columns
+/// 13-16. Names of two-letter elements start in column 13 (`FE  `), those
+/// of one-letter elements in column 14 (` CA ` is a Cα carbon). Only
+/// HETATM records hold two-letter elements: in ATOM records a name from
+/// column 13 is a four-character hydrogen (`HG21`, `HE21`), not Hg or He.
+fn element_symbol(line: &str) -> String {
+    let symbol = column(line, 77, 78);
+    if !symbol.is_empty() {
+        return title_case(symbol);
+    }
+    let name = line.get(12..16).unwrap_or_default();
+    let letters: String = name
+        .trim()
+        .chars()
+        .skip_while(|c| c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    if line.starts_with("HETATM") && !name.starts_with(' ') && letters.len() >= 2 {
+        let two = title_case(&letters[..2]);
+        if get_atomic_number(&two) > 0 {
+            return two;
+        }
+    }
+    match letters.chars().next() {
+        Some(c) => c.to_ascii_uppercase().to_string(),
+        None => String::from("X"),
+    }
+}
+
+fn title_case(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+        None => String::from("X"),
+    }
+}
+
+/// Orthogonal box around `atoms` with `VACUUM` Å of slack per axis. The
+/// atoms are moved so their extent sits in the middle of it.
+fn vacuum_box(atoms: &mut [Atom]) -> [[f64; 3]; 3] {
+    let mut lo = [f64::INFINITY; 3];
+    let mut hi = [f64::NEG_INFINITY; 3];
+    for a in atoms.iter() {
+        for (k, &p) in a.position.iter().enumerate() {
+            lo[k] = lo[k].min(p);
+            hi[k] = hi[k].max(p);
+        }
+    }
+    for a in atoms.iter_mut() {
+        for (p, l) in a.position.iter_mut().zip(lo) {
+            *p += VACUUM / 2.0 - l;
+        }
+    }
+    let size = |k: usize| hi[k] - lo[k] + VACUUM;
+    [
+        [size(0), 0.0, 0.0],
+        [0.0, size(1), 0.0],
+        [0.0, 0.0, size(2)],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn cryst1_sets_the_cell() {
+        let s = parse_str(
+            "HEADER    TEST\n\
+             CRYST1    5.000    6.000    7.000  90.00  90.00 120.00 P 1           1\n\
+             ATOM      1  N   GLY A   1       1.000   2.000   3.000  1.00  0.00           N\n\
+             HETATM    2 FE   HEM A   2       0.500  -1.250   4.000  0.50  0.00\n\
+             ATOM      3  CA  GLY A   1       1.500   2.500   3.500  1.00  0.00\n\
+             END\n",
+        )
+        .unwrap();
+        assert!(s.is_periodic);
+        approx(s.lattice[0][0], 5.0);
+        approx(s.lattice[1][0], -3.0);
+        approx(s.lattice[1][1], 3.0 * 3f64.sqrt());
+        approx(s.lattice[2][2], 7.0);
+
+        let elements: Vec<&str> = s.atoms.iter().map(|a| a.element.as_str()).collect();
+        assert_eq!(elements, ["N", "Fe", "C"]);
+        approx(s.atoms[1].position[1], -1.25);
+        approx(s.atoms[1].occupancy, 0.5);
+        assert_eq!(s.formula, "CFeN");
+    }
+
+    #[test]
+    fn four_character_hydrogen_names_are_hydrogen() {
+        let s = parse_str(
+            "ATOM      1 HG21 THR A   1       0.000   0.000   0.000  1.00  0.00\n\
+             ATOM      2 HE21 GLN A   2       1.000   0.000   0.000  1.00  0.00\n\
+             HETATM    3 HG    HG A   3       2.000   0.000   0.000  1.00  0.00\n",
+        )
+        .unwrap();
+        let elements: Vec<&str> = s.atoms.iter().map(|a| a.element.as_str()).collect();
+        assert_eq!(elements, ["H", "H", "Hg"]);
+    }
+
+    #[test]
+    fn molecules_without_cryst1_get_a_vacuum_box() {
+        let s = parse_str(
+            "HETATM    1  O   HOH A   1      -1.000   0.000   2.000  1.00  0.00           O\n\
+             HETATM    2  H1  HOH A   1      -0.200   0.600   2.000  1.00  0.00           H\n\
+             HETATM    3  H2  HOH A   1      -0.200  -0.600   2.000  1.00  0.00           H\n\
+             ENDMDL\n\
+             HETATM    4  O   HOH A   1       9.000   9.000   9.000  1.00  0.00           O\n",
+        )
+        .unwrap();
+        assert!(!s.is_periodic);
+        assert_eq!(s.atoms.len(), 3);
+        approx(s.lattice[0][0], 0.8 + VACUUM);
+        approx(s.lattice[1][1], 1.2 + VACUUM);
+        approx(s.lattice[2][2], VACUUM);
+        for a in &s.atoms {
+            for k in 0..3 {
+                assert!(a.position[k] >= VACUUM / 2.0 - 1e-9);
+                assert!(a.position[k] <= s.lattice[k][k] - VACUUM / 2.0 + 1e-9);
+            }
+        }
+        assert_eq!(s.formula, "H2O");
+
+        // The 1 Å placeholder cell of NMR entries means "no cell".
+        let nmr = parse_str(
+            "CRYST1    1.000    1.000    1.000  90.00  90.00  90.00 P 1           1\n\
+             ATOM      1  C   ALA A   1       0.000   0.000   0.000  1.00  0.00           C\n",
+        )
+        .unwrap();
+        assert!(!nmr.is_periodic);
+        assert!(parse_str("HEADER    EMPTY\nEND\n").is_err());
+    }
+}
@@ -1,110 +1,213 @@
 // src/io/poscar.rs
 
 use crate::model::structure::{Atom, Structure};
-use crate::utils::linalg::{cart_to_frac, frac_to_cart};
+use crate::utils::linalg::{frac_to_cart, mat3_det, PeriodicCell};
+use nalgebra::Vector3;
 use std::fs::File;
 use std::io::{self, BufRead, Write};
 
+/// Line-counting wrapper so every parse error can say where the file went
+/// wrong ("line 7: expected 3 floats ...") instead of a bare `InvalidData`.
+struct NumberedLines<R> {
+    inner: io::Lines<R>,
+    line_no: usize,
+}
+
+impl<R: BufRead> NumberedLines<R> {
+    /// Next line, or an error naming what was expected there.
+    fn next_line(&mut self, expected: &str) -> io::Result<String> {
+        self.line_no += 1;
+        match self.inner.next() {
+            Some(line) => line,
+            None => Err(invalid(
+                self.line_no,
+                &format!("unexpected end of file, expected {}", expected),
+            )),
+        }
+    }
+}
+
+fn invalid(line_no: usize, msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_no, msg))
+}
+
+/// First three whitespace-separated floats of `line`; trailing columns
+/// (selective-dynamics flags, site labels) are ignored.
+fn parse_three_floats(line: &str, line_no: usize, what: &str) -> io::Result<[f64; 3]> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 3 {
+        return Err(invalid(
+            line_no,
+            &format!("expected 3 floats for {}, found '{}'", what, line.trim()),
+        ));
+    }
+    let mut out = [0.0; 3];
+    for (j, part) in parts.iter().take(3).enumerate() {
+        out[j] = part.parse().map_err(|_| {
+            invalid(
+                line_no,
+                &format!("invalid number '{}' in {}", part, what),
+            )
+        })?;
+    }
+    Ok(out)
+}
+
+fn parse_counts(parts: &[&str], line_no: usize) -> io::Result<Vec<usize>> {
+    parts
+        .iter()
+        .map(|s| {
+            s.parse::<usize>().map_err(|_| {
+                invalid(
+                    line_no,
+                    &format!("invalid atom count '{}' (expected a non-negative integer)", s),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Total atom count from the header alone (the counts line of a VASP 4 or
+/// VASP 5 file), without reading any coordinates. `None` when the first
+/// seven lines do not look like a POSCAR header.
+pub fn header_atom_count(path: &str) -> Option<usize> {
+    let reader = io::BufReader::new(File::open(path).ok()?);
+    let head: Vec<String> = reader.lines().take(7).collect::<io::Result<_>>().ok()?;
+    let total = |line: &String| -> Option<usize> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.is_empty() {
+            return None;
+        }
+        parts.iter().map(|p| p.parse::<usize>().ok()).sum()
+    };
+    // VASP 4 has the counts on line 6; VASP 5 puts symbols there first.
+    head.get(5)
+        .and_then(total)
+        .or_else(|| head.get(6).and_then(total))
+}
+
+/// A parsed POSCAR: the structure plus the per-atom data `Structure` has no
+/// slot for, kept so a writer can round-trip it.
+pub struct Poscar {
+    pub structure: Structure,
+    /// Selective-dynamics flags per atom in file order (`true` = free to
+    /// relax along x/y/z). `None` when the file has no "Selective dynamics"
+    /// line.
+    #[allow(dead_code)]
+    pub selective_dynamics: Option<Vec<[bool; 3]>>,
+}
+
 pub fn parse(path: &str) -> io::Result<Structure> {
+    parse_full(path).map(|p| p.structure)
+}
+
+/// Parse a VASP 4 or VASP 5 POSCAR/CONTCAR.
+///
+/// - Scaling factor: positive values multiply lattice and Cartesian
+///   coordinates; a negative value is the target cell volume in Å³.
+/// - Species: VASP 5 symbol line when present (POTCAR suffixes such as
+///   `Si_pv` or `Fe/abc123` are stripped). VASP 4 files have none, so the
+///   species come from [`vasp4_species`].
+/// - "Selective dynamics" is honoured and its T/F flags are kept on
+///   [`Poscar::selective_dynamics`].
+pub fn parse_full(path: &str) -> io::Result<Poscar> {
     let file = File::open(path)?;
     let reader = io::BufReader::new(file);
-    let mut lines = reader.lines();
+    let mut lines = NumberedLines {
+        inner: reader.lines(),
+        line_no: 0,
+    };
 
     // 1. Comment Line
-    let _comment = lines
-        .next()
-        .ok_or(io::Error::new(io::ErrorKind::InvalidData, "Empty file"))??;
+    let comment = lines.next_line("comment line (file is empty)")?;
 
     // 2. Scaling Factor
-    let scale_line = lines.next().ok_or(io::Error::new(
-        io::ErrorKind::InvalidData,
-        "Missing scaling factor",
-    ))??;
+    let scale_line = lines.next_line("scaling factor")?;
     let scale: f64 = scale_line
-        .trim()
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
         .parse()
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid scaling factor"))?;
-
-    // 3. Lattice Vectors
-    let mut lattice = [[0.0; 3]; 3];
-    for i in 0..3 {
-        let line = lines.next().ok_or(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Missing lattice vector",
-        ))??;
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 3 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Invalid lattice vector",
+        .map_err(|_| {
+            invalid(
+                lines.line_no,
+                &format!("invalid scaling factor '{}'", scale_line.trim()),
+            )
+        })?;
+    let scale_line_no = lines.line_no;
+
+    // 3. Lattice Vectors (unscaled until the factor is known)
+    let mut raw_lattice = [[0.0; 3]; 3];
+    for (i, row) in raw_lattice.iter_mut().enumerate() {
+        let what = format!("lattice vector {}", i + 1);
+        let line = lines.next_line(&what)?;
+        *row = parse_three_floats(&line, lines.line_no, &what)?;
+    }
+
+    // Negative scale = target volume; the factor is the isotropic stretch
+    // that takes the raw cell to that volume.
+    let scale = if scale < 0.0 {
+        let raw_volume = mat3_det(raw_lattice).abs();
+        if raw_volume < 1e-12 {
+            return Err(invalid(
+                scale_line_no,
+                "negative scaling factor (target volume) given for a degenerate cell",
             ));
         }
-        for j in 0..3 {
-            let val: f64 = parts[j].parse().map_err(|_| {
-                io::Error::new(io::ErrorKind::InvalidData, "Invalid float in lattice")
-            })?;
-            lattice[i][j] = val * scale;
-        }
-    }
+        (-scale / raw_volume).cbrt()
+    } else if scale == 0.0 {
+        return Err(invalid(scale_line_no, "scaling factor must be non-zero"));
+    } else {
+        scale
+    };
+    let lattice = raw_lattice.map(|row| row.map(|x| x * scale));
 
     // 4. Elements & Counts
-    let line_a = lines.next().ok_or(io::Error::new(
-        io::ErrorKind::InvalidData,
-        "Missing atoms info",
-    ))??;
+    let line_a = lines.next_line("element symbols or atom counts")?;
     let parts_a: Vec<&str> = line_a.split_whitespace().collect();
+    if parts_a.is_empty() {
+        return Err(invalid(
+            lines.line_no,
+            "expected element symbols or atom counts, found an empty line",
+        ));
+    }
 
-    let mut elements = Vec::new();
-    let mut counts = Vec::new();
+    let elements: Vec<String>;
+    let counts;
 
     // Check if line A contains letters (Elements) or numbers (Counts)
     let first_char = parts_a.first().unwrap_or(&"").chars().next().unwrap_or(' ');
 
     if first_char.is_alphabetic() {
-        // Line A is Symbols
-        for s in parts_a {
-            elements.push(s.to_string());
-        }
+        // Line A is Symbols (VASP 5)
+        elements = parts_a.iter().map(|s| normalize_species(s)).collect();
         // Read next line for counts
-        let line_b = lines.next().ok_or(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Missing atom counts",
-        ))??;
+        let line_b = lines.next_line("atom counts")?;
         let parts_b: Vec<&str> = line_b.split_whitespace().collect();
-        for s in parts_b {
-            counts.push(
-                s.parse::<usize>().map_err(|_| {
-                    io::Error::new(io::ErrorKind::InvalidData, "Invalid atom count")
-                })?,
-            );
+        counts = parse_counts(&parts_b, lines.line_no)?;
+        if counts.len() != elements.len() {
+            return Err(invalid(
+                lines.line_no,
+                &format!(
+                    "{} element symbol(s) but {} atom count(s)",
+                    elements.len(),
+                    counts.len()
+                ),
+            ));
         }
     } else {
         // Line A is Counts (VASP 4 style)
-        for s in parts_a {
-            counts.push(
-                s.parse::<usize>().map_err(|_| {
-                    io::Error::new(io::ErrorKind::InvalidData, "Invalid atom count")
-                })?,
-            );
-        }
-        // Generate placeholder elements
-        for i in 0..counts.len() {
-            elements.push(format!("El{}", i + 1));
-        }
+        counts = parse_counts(&parts_a, lines.line_no)?;
+        elements = vasp4_species(path, &comment, counts.len());
     }
 
     // 5. Check for "Selective dynamics"
-    let mut line_mode = lines.next().ok_or(io::Error::new(
-        io::ErrorKind::InvalidData,
-        "Unexpected end of file",
-    ))??;
+    let mut line_mode = lines.next_line("coordinate mode (Direct/Cartesian)")?;
 
-    if line_mode.trim().to_lowercase().starts_with("s") {
+    let selective = line_mode.trim().to_lowercase().starts_with('s');
+    if selective {
         // Skip this line and read the next one
-        line_mode = lines.next().ok_or(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Missing mode after Selective dynamics",
-        ))??;
+        line_mode = lines.next_line("coordinate mode after Selective dynamics")?;
     }
 
     // 6. Coordinate Mode
@@ -112,44 +215,33 @@ pub fn parse(path: &str) -> io::Result<Structure> {
         Some('d') => true,              // Direct
         Some('c') | Some('k') => false, // Cartesian
         _ => {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Unknown coordinate mode",
+            return Err(invalid(
+                lines.line_no,
+                &format!(
+                    "unknown coordinate mode '{}' (expected Direct or Cartesian)",
+                    line_mode.trim()
+                ),
             ))
         }
     };
 
     // 7. Read Atoms
     let mut atoms = Vec::new();
+    let mut flags: Vec<[bool; 3]> = Vec::new();
     let mut atom_id = 0;
+    let total: usize = counts.iter().sum();
 
     for (elem_idx, &count) in counts.iter().enumerate() {
         let element = &elements[elem_idx];
 
         for _ in 0..count {
-            let line = lines.next().ok_or(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Not enough atom lines",
-            ))??;
-            let parts: Vec<&str> = line.split_whitespace().collect();
-
-            // Only take first 3 parts (ignores "T T T" selective dynamics flags)
-            if parts.len() < 3 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Invalid atom line",
-                ));
-            }
+            let what = format!("atom {} of {}", atom_id + 1, total);
+            let line = lines.next_line(&what)?;
+            let [c1, c2, c3] = parse_three_floats(&line, lines.line_no, &what)?;
 
-            let c1: f64 = parts[0]
-                .parse()
-                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid coordinate"))?;
-            let c2: f64 = parts[1]
-                .parse()
-                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid coordinate"))?;
-            let c3: f64 = parts[2]
-                .parse()
-                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid coordinate"))?;
+            if selective {
+                flags.push(parse_selective_flags(&line));
+            }
 
             let position = if is_fractional {
                 // Convert fractional to Cartesian using nalgebra
@@ -165,6 +257,7 @@ pub fn parse(path: &str) -> io::Result<Structure> {
                 original_index: atom_id,
                 oxidation: None,
                 occupancy: 1.0,
+                adp: None,
             });
             atom_id += 1;
         }
@@ -178,24 +271,121 @@ pub fn parse(path: &str) -> io::Result<Structure> {
         .collect::<Vec<String>>()
         .join("");
 
-    Ok(Structure {
-        lattice,
-        atoms,
-        formula,
-        is_periodic: true,
+    Ok(Poscar {
+        structure: Structure {
+            lattice,
+            atoms,
+            formula,
+            is_periodic: true,
+        },
+        selective_dynamics: selective.then_some(flags),
     })
 }
 
+/// Bare element symbol from a VASP 5 species token: POTCAR variants carry
+/// suffixes ("Si_pv", "O_s", "Fe/5a3c1b" from VASP 6 hashes).
+fn normalize_species(token: &str) -> String {
+    token
+        .split(['_', '/', '.'])
+        .next()
+        .unwrap_or(token)
+        .to_string()
+}
+
+/// Species for a VASP 4 file, which has counts but no symbol line. Sources
+/// in order: the `TITEL` lines of a POTCAR next to the file (how VASP itself
+/// resolves them), element symbols in the comment line (ASE and pymatgen
+/// write them there), then `El1`, `El2`, ... placeholders with a warning.
+fn vasp4_species(path: &str, comment: &str, n_species: usize) -> Vec<String> {
+    let potcar = std::path::Path::new(path).with_file_name("POTCAR");
+    if let Ok(text) = std::fs::read_to_string(&potcar) {
+        let from_potcar = species_from_potcar(&text);
+        if from_potcar.len() == n_species {
+            return from_potcar;
+        }
+    }
+
+    let from_comment: Vec<String> = comment
+        .split_whitespace()
+        .map(normalize_species)
+        .filter(|s| crate::model::elements::get_atomic_number(s) > 0)
+        .collect();
+    if from_comment.len() == n_species {
+        return from_comment;
+    }
+
+    crate::utils::console::log_warn(
+        "VASP 4 POSCAR without species names and no matching POTCAR — \
+         using placeholders El1, El2, ...",
+    );
+    (1..=n_species).map(|i| format!("El{}", i)).collect()
+}
+
+/// Element symbols in POTCAR order, one per `TITEL = PAW_PBE Si 05Jan2001`.
+fn species_from_potcar(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|l| {
+            let rest = l.trim().strip_prefix("TITEL")?;
+            let value = rest.trim_start().strip_prefix('=')?;
+            value.split_whitespace().nth(1).map(normalize_species)
+        })
+        .collect()
+}
+
+/// T/F flags after the three coordinates. Missing or unrecognized flags
+/// count as free (`T`), which is VASP's own default for unconstrained atoms.
+fn parse_selective_flags(line: &str) -> [bool; 3] {
+    let mut out = [true; 3];
+    for (slot, tok) in out.iter_mut().zip(line.split_whitespace().skip(3)) {
+        if matches!(tok.trim_start_matches('.').chars().next(), Some('F' | 'f')) {
+            *slot = false;
+        }
+    }
+    out
+}
+
+/// Write a VASP 5 POSCAR: formula comment, scale 1.0, lattice, species and
+/// counts lines, then `Direct` coordinates grouped by element (alphabetical,
+/// file order preserved within each element).
 pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     if structure.atoms.iter().any(|a| a.occupancy < 0.99) {
         crate::utils::console::log_warn(
             "POSCAR format has no occupancy field — partial occupancies are discarded on export",
         );
     }
+
+    // Structure stores Cartesian positions; POSCAR wants fractional ones.
+    // Invert once rather than per atom, and refuse a singular cell instead
+    // of silently writing zeros.
+    let cell = PeriodicCell::new(structure.lattice).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "lattice is singular; cannot convert to fractional coordinates",
+        )
+    })?;
+
+    // Group atoms by element. VASP requires each species to be contiguous,
+    // and the counts line must match that order exactly. `sort_by` is stable.
+    let mut sorted: Vec<&Atom> = structure.atoms.iter().collect();
+    sorted.sort_by(|a, b| a.element.cmp(&b.element));
+
+    let mut groups: Vec<(&str, usize)> = Vec::new();
+    for atom in &sorted {
+        match groups.last_mut() {
+            Some((el, n)) if *el == atom.element => *n += 1,
+            _ => groups.push((atom.element.as_str(), 1)),
+        }
+    }
+
     let mut file = std::fs::File::create(path)?;
 
-    // 1. Comment
-    writeln!(file, "Exported by CView")?;
+    // 1. Comment: formula in species order, e.g. "Cl4Na4"
+    let formula: String = groups.iter().map(|(el, n)| format!("{}{}", el, n)).collect();
+    if formula.is_empty() {
+        writeln!(file, "Exported by CView")?;
+    } else {
+        writeln!(file, "{} - exported by CView", formula)?;
+    }
 
     // 2. Scale
     writeln!(file, "1.0")?;
@@ -205,52 +395,25 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
         writeln!(file, "  {:15.9} {:15.9} {:15.9}", vec[0], vec[1], vec[2])?;
     }
 
-    // 4. Species logic (VASP 5 format)
-    let mut groups: Vec<(String, usize)> = Vec::new();
-    if !structure.atoms.is_empty() {
-        // Group consecutive atoms of same type
-        // VASP expects grouped atoms - sort by element first
-        let mut temp_atoms = structure.atoms.clone();
-        temp_atoms.sort_by(|a, b| a.element.cmp(&b.element));
-
-        let mut current_el = temp_atoms[0].element.clone();
-        let mut count = 0;
-
-        // Count groups
-        for atom in &temp_atoms {
-            if atom.element == current_el {
-                count += 1;
-            } else {
-                groups.push((current_el.clone(), count));
-                current_el = atom.element.clone();
-                count = 1;
-            }
-        }
-        groups.push((current_el, count));
-
-        // Write Element Symbols
+    // 4. Species and counts (VASP 5). An empty structure has neither line.
+    if !groups.is_empty() {
         for (el, _) in &groups {
             write!(file, " {:>4} ", el)?;
         }
         writeln!(file)?;
-
-        // Write Counts
-        for (_, c) in &groups {
-            write!(file, " {:>4} ", c)?;
+        for (_, n) in &groups {
+            write!(file, " {:>4} ", n)?;
         }
         writeln!(file)?;
+    }
 
-        // 5. Mode (Direct = fractional coordinates)
-        writeln!(file, "Direct")?;
+    // 5. Mode (Direct = fractional coordinates)
+    writeln!(file, "Direct")?;
 
-        // 6. Coordinates in fractional (must match sorted order!)
-        for atom in &temp_atoms {
-            let frac = cart_to_frac(atom.position, structure.lattice).unwrap_or([0.0, 0.0, 0.0]);
-            writeln!(file, "  {:15.9} {:15.9} {:15.9}", frac[0], frac[1], frac[2])?;
-        }
-    } else {
-        // Empty case
-        writeln!(file, "Direct")?;
+    // 6. Fractional coordinates, in the same grouped order as the counts
+    for atom in &sorted {
+        let f = cell.to_frac(Vector3::from(atom.position));
+        writeln!(file, "  {:15.9} {:15.9} {:15.9}", f.x, f.y, f.z)?;
     }
 
     Ok(())
@@ -288,6 +451,19 @@ mod tests {
         assert!((a - b).abs() < 1e-6, "{a} != {b}");
     }
 
+    #[test]
+    fn header_count_without_parsing_coordinates() {
+        let vasp5 = TmpFile::new(
+            ".vasp",
+            "NaCl\n1.0\n5.6 0 0\n0 5.6 0\n0 0 5.6\nNa Cl\n4 4\nDirect\n",
+        );
+        assert_eq!(header_atom_count(vasp5.path()), Some(8));
+        let vasp4 = TmpFile::new("", "Si\n5.43\n1 0 0\n0 1 0\n0 0 1\n2\nDirect\n");
+        assert_eq!(header_atom_count(vasp4.path()), Some(2));
+        let junk = TmpFile::new(".vasp", "not a poscar\n");
+        assert_eq!(header_atom_count(junk.path()), None);
+    }
+
     #[test]
     fn parses_vasp5_direct_coords() {
         // Element symbols + counts, fractional (Direct) coordinates.
@@ -354,6 +530,188 @@ mod tests {
         approx(s.atoms[0].position[0], 0.0);
     }
 
+    // Fixtures for each header variant. All describe rock-salt-like NaCl
+    // fragments in a 4 Å cube so the expected Cartesian values are easy to
+    // read off.
+    const VASP5_SELECTIVE_CART: &str = "NaCl selective\n1.0\n\
+        4.0 0.0 0.0\n0.0 4.0 0.0\n0.0 0.0 4.0\n\
+        Na_pv Cl\n1 1\nSelective Dynamics\nCartesian\n\
+        0.0 0.0 0.0 F F F\n2.0 2.0 2.0 T F T\n";
+    const VASP4_SPECIES_IN_COMMENT: &str = "Na Cl\n1.0\n\
+        4.0 0.0 0.0\n0.0 4.0 0.0\n0.0 0.0 4.0\n\
+        1 1\nDirect\n0.0 0.0 0.0\n0.5 0.5 0.5\n";
+    const NEGATIVE_SCALE_VOLUME: &str = "Target volume 64\n-64.0\n\
+        1.0 0.0 0.0\n0.0 1.0 0.0\n0.0 0.0 1.0\n\
+        Na Cl\n1 1\nCartesian\n0.0 0.0 0.0\n0.5 0.5 0.5\n";
+
+    #[test]
+    fn parses_selective_dynamics_flags_and_potcar_suffixes() {
+        let f = TmpFile::new(".vasp", VASP5_SELECTIVE_CART);
+        let p = parse_full(f.path()).unwrap();
+        assert_eq!(p.structure.atoms.len(), 2);
+        assert_eq!(p.structure.atoms[0].element, "Na");
+        assert_eq!(p.structure.atoms[1].element, "Cl");
+        approx(p.structure.atoms[1].position[2], 2.0);
+        let flags = p.selective_dynamics.expect("flags kept");
+        assert_eq!(flags, vec![[false, false, false], [true, false, true]]);
+
+        // No "Selective dynamics" line → no flags.
+        let f = TmpFile::new(".vasp", VASP4_SPECIES_IN_COMMENT);
+        assert!(parse_full(f.path()).unwrap().selective_dynamics.is_none());
+    }
+
+    #[test]
+    fn vasp4_takes_species_from_comment_line() {
+        let f = TmpFile::new(".vasp", VASP4_SPECIES_IN_COMMENT);
+        let s = parse(f.path()).unwrap();
+        assert_eq!(s.atoms.len(), 2);
+        assert_eq!(s.atoms[0].element, "Na");
+        assert_eq!(s.atoms[1].element, "Cl");
+        approx(s.atoms[1].position[0], 2.0);
+    }
+
+    #[test]
+    fn vasp4_takes_species_from_neighbouring_potcar() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "cview_poscar_potcar_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let poscar = dir.join("POSCAR");
+        std::fs::write(
+            &poscar,
+            "no species here\n1.0\n4 0 0\n0 4 0\n0 0 4\n\
+             1 2\nDirect\n0 0 0\n0.5 0 0\n0 0.5 0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("POTCAR"),
+            "  PAW_PBE Ti_sv 26Sep2005\n   TITEL  = PAW_PBE Ti_sv 26Sep2005\n End of Dataset\n\
+             \x20 PAW_PBE O 08Apr2002\n   TITEL  = PAW_PBE O 08Apr2002\n End of Dataset\n",
+        )
+        .unwrap();
+        let s = parse(poscar.to_str().unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+        let s = s.unwrap();
+        let els: Vec<&str> = s.atoms.iter().map(|a| a.element.as_str()).collect();
+        assert_eq!(els, vec!["Ti", "O", "O"]);
+    }
+
+    #[test]
+    fn negative_scale_is_target_volume() {
+        let f = TmpFile::new(".vasp", NEGATIVE_SCALE_VOLUME);
+        let s = parse(f.path()).unwrap();
+        // Unit cube stretched to 64 Å³ → 4 Å edges; Cartesian coords follow.
+        approx(s.lattice[0][0], 4.0);
+        approx(s.lattice[2][2], 4.0);
+        approx(s.atoms[1].position[0], 2.0);
+        approx(s.atoms[1].position[1], 2.0);
+    }
+
+    #[test]
+    fn errors_name_the_offending_line() {
+        // Second atom line (line 10) has only two columns.
+        let f = TmpFile::new(
+            ".vasp",
+            "Bad\n1.0\n\
+             4.0 0.0 0.0\n0.0 4.0 0.0\n0.0 0.0 4.0\n\
+             Si\n2\nDirect\n0.0 0.0 0.0\n0.5 0.5\n",
+        );
+        let msg = parse(f.path()).unwrap_err().to_string();
+        assert!(msg.starts_with("line 10:"), "{msg}");
+        assert!(msg.contains("expected 3 floats"), "{msg}");
+
+        let f = TmpFile::new(
+            ".vasp",
+            "Bad\n1.0\n4.0 0.0 0.0\n0.0 x 0.0\n0.0 0.0 4.0\nSi\n1\nDirect\n0 0 0\n",
+        );
+        let msg = parse(f.path()).unwrap_err().to_string();
+        assert!(msg.starts_with("line 4:"), "{msg}");
+        assert!(msg.contains("'x'"), "{msg}");
+    }
+
+    #[test]
+    fn errors_on_truncated_file_and_count_mismatch() {
+        let f = TmpFile::new(".vasp", "Short\n1.0\n4 0 0\n");
+        let msg = parse(f.path()).unwrap_err().to_string();
+        assert!(msg.starts_with("line 4:"), "{msg}");
+        assert!(msg.contains("lattice vector 2"), "{msg}");
+
+        let f = TmpFile::new(
+            ".vasp",
+            "Mismatch\n1.0\n4 0 0\n0 4 0\n0 0 4\nSi O\n1\nDirect\n0 0 0\n",
+        );
+        let msg = parse(f.path()).unwrap_err().to_string();
+        assert!(msg.starts_with("line 7:"), "{msg}");
+    }
+
+    #[test]
+    fn writer_groups_interleaved_elements_and_roundtrips_triclinic() {
+        let lattice = [[5.1, 0.0, 0.0], [1.2, 4.8, 0.0], [0.7, -0.9, 6.3]];
+        let frac = [
+            ("O", [0.1, 0.2, 0.3]),
+            ("Ti", [0.5, 0.5, 0.5]),
+            ("O", [0.9, 0.15, 0.05]),
+            ("Ti", [0.0, 0.25, 0.75]),
+            ("O", [0.333333333, 0.666666667, 0.123456789]),
+        ];
+        let atoms = frac
+            .iter()
+            .enumerate()
+            .map(|(i, (el, f))| Atom {
+                element: el.to_string(),
+                position: frac_to_cart(*f, lattice),
+                original_index: i,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            })
+            .collect();
+        let original = Structure {
+            lattice,
+            atoms,
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let f = TmpFile::new(".vasp", "");
+        write(f.path(), &original).unwrap();
+
+        let text = std::fs::read_to_string(f.path()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("O3Ti2"), "{}", lines[0]);
+        assert_eq!(lines[5].split_whitespace().collect::<Vec<_>>(), ["O", "Ti"]);
+        assert_eq!(lines[6].split_whitespace().collect::<Vec<_>>(), ["3", "2"]);
+        assert_eq!(lines[7], "Direct");
+
+        let s = parse(f.path()).unwrap();
+        let expected: Vec<&Atom> = original
+            .atoms
+            .iter()
+            .filter(|a| a.element == "O")
+            .chain(original.atoms.iter().filter(|a| a.element == "Ti"))
+            .collect();
+        for (got, want) in s.atoms.iter().zip(expected) {
+            assert_eq!(got.element, want.element);
+            for k in 0..3 {
+                approx(got.position[k], want.position[k]);
+            }
+        }
+    }
+
+    #[test]
+    fn writer_rejects_singular_lattice() {
+        let s = Structure {
+            lattice: [[1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+            atoms: vec![],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let f = TmpFile::new(".vasp", "");
+        assert!(write(f.path(), &s).is_err());
+    }
+
     #[test]
     fn write_then_parse_roundtrips_positions() {
         let original = Structure {
@@ -365,6 +723,7 @@ mod tests {
                     original_index: 0,
                     oxidation: None,
                     occupancy: 1.0,
+                    adp: None,
                 },
                 Atom {
                     element: "Cl".into(),
@@ -372,6 +731,7 @@ mod tests {
                     original_index: 1,
                     oxidation: None,
                     occupancy: 1.0,
+                    adp: None,
                 },
             ],
             formula: String::new(),
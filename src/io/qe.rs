@@ -1,5 +1,6 @@
 // src/io/qe.rs
 
+use crate::model::structure::generate_formula;
 use crate::model::{Atom, Structure};
 use crate::utils::linalg::frac_to_cart;
 use std::fs;
@@ -108,6 +109,7 @@ fn parse_output(content: &str) -> io::Result<Structure> {
                         original_index: atoms.len(),
                         oxidation: None,
                         occupancy: 1.0,
+                        adp: None,
                     });
                 }
                 i += 1;
@@ -251,6 +253,7 @@ fn parse_input(content: &str) -> io::Result<Structure> {
                         original_index: atoms.len(),
                         oxidation: None,
                         occupancy: 1.0,
+                        adp: None,
                     });
                 }
                 i += 1;
@@ -315,26 +318,6 @@ fn generate_lattice_from_ibrav(ibrav: i32, a: f64) -> Option<[[f64; 3]; 3]> {
     }
 }
 
-fn generate_formula(atoms: &[Atom]) -> String {
-    use std::collections::HashMap;
-    let mut counts = HashMap::new();
-    for a in atoms {
-        *counts.entry(a.element.clone()).or_insert(0) += 1;
-    }
-    let mut parts: Vec<_> = counts.into_iter().collect();
-    parts.sort_by(|a, b| a.0.cmp(&b.0));
-    parts
-        .iter()
-        .map(|(el, c)| {
-            if *c > 1 {
-                format!("{}{}", el, c)
-            } else {
-                el.clone()
-            }
-        })
-        .collect()
-}
-
 fn parse_header_unit(header: &str, global_alat: f64) -> (String, f64) {
     parse_header_unit_with_default(header, global_alat, "angstrom")
 }
@@ -490,6 +473,11 @@ fn contains_celldm_one(line: &str) -> bool {
     false
 }
 
+/// Write a minimal pw.x input: `ibrav = 0` with an explicit
+/// `CELL_PARAMETERS angstrom` block, `ATOMIC_SPECIES` with standard atomic
+/// masses and `<El>.UPF` pseudopotential names, and Cartesian
+/// `ATOMIC_POSITIONS angstrom`. Cutoffs and pseudo names are placeholders
+/// the user is expected to edit.
 pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     if structure.atoms.iter().any(|a| a.occupancy < 0.99) {
         crate::utils::console::log_warn(
@@ -527,20 +515,20 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     writeln!(file, "  conv_thr = 1.0d-8")?;
     writeln!(file, "/")?;
 
-    // Atomic Species (Placeholder masses and pseudos)
+    // Atomic Species (standard masses, placeholder pseudos)
     writeln!(file, "ATOMIC_SPECIES")?;
     for el in &unique_els {
-        writeln!(file, " {:<3}  1.000  {}.UPF", el, el)?;
+        writeln!(file, " {:<3}  {:>9.4}  {}.UPF", el, species_mass(el), el)?;
     }
 
     // Cell Parameters
-    writeln!(file, "CELL_PARAMETERS (angstrom)")?;
+    writeln!(file, "CELL_PARAMETERS angstrom")?;
     for vec in &structure.lattice {
         writeln!(file, "  {:15.9} {:15.9} {:15.9}", vec[0], vec[1], vec[2])?;
     }
 
     // Atomic Positions
-    writeln!(file, "ATOMIC_POSITIONS (angstrom)")?;
+    writeln!(file, "ATOMIC_POSITIONS angstrom")?;
     for atom in &structure.atoms {
         writeln!(
             file,
@@ -552,6 +540,21 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     Ok(())
 }
 
+/// Atomic mass for ATOMIC_SPECIES. pw.x rejects a zero mass, so unknown
+/// symbols (placeholders such as `El1`) get 1.0 and a warning.
+fn species_mass(element: &str) -> f64 {
+    let m = crate::model::elements::get_atomic_mass(element);
+    if m > 0.0 {
+        m
+    } else {
+        crate::utils::console::log_warn(&format!(
+            "QE export: no atomic mass for '{}' — writing 1.0, edit ATOMIC_SPECIES before running",
+            element
+        ));
+        1.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -641,6 +644,7 @@ mod tests {
                 original_index: 0,
                 oxidation: None,
                 occupancy: 1.0,
+                adp: None,
             }],
             formula: String::new(),
             is_periodic: true,
@@ -653,4 +657,66 @@ mod tests {
         approx(s.atoms[0].position[1], 2.0);
         approx(s.lattice[2][2], 5.0);
     }
+
+    #[test]
+    fn writer_emits_masses_and_roundtrips_species() {
+        let original = Structure {
+            lattice: [[3.9, 0.0, 0.0], [0.0, 3.9, 0.0], [0.0, 0.0, 4.1]],
+            atoms: vec![
+                Atom {
+                    element: "Ti".into(),
+                    position: [1.95, 1.95, 2.05],
+                    original_index: 0,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                },
+                Atom {
+                    element: "O".into(),
+                    position: [0.0, 1.95, 2.05],
+                    original_index: 1,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                },
+                Atom {
+                    element: "O".into(),
+                    position: [1.95, 0.0, 2.05],
+                    original_index: 2,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                },
+            ],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let f = TmpFile::new("");
+        write(f.path(), &original).unwrap();
+
+        let text = std::fs::read_to_string(f.path()).unwrap();
+        assert!(text.contains("ibrav = 0"));
+        assert!(text.contains("nat = 3"));
+        assert!(text.contains("ntyp = 2"));
+        assert!(text.contains("CELL_PARAMETERS angstrom"));
+        let mass_of = |el: &str| -> f64 {
+            text.lines()
+                .find(|l| l.split_whitespace().next() == Some(el) && l.contains(".UPF"))
+                .and_then(|l| l.split_whitespace().nth(1))
+                .and_then(|m| m.parse().ok())
+                .unwrap()
+        };
+        assert!((mass_of("Ti") - 47.867).abs() < 1e-3);
+        assert!((mass_of("O") - 15.999).abs() < 1e-2);
+
+        let s = parse(f.path()).unwrap();
+        assert_eq!(s.atoms.len(), 3);
+        for (got, want) in s.atoms.iter().zip(&original.atoms) {
+            assert_eq!(got.element, want.element);
+            for k in 0..3 {
+                approx(got.position[k], want.position[k]);
+            }
+        }
+        approx(s.lattice[2][2], 4.1);
+    }
 }
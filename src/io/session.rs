@@ -0,0 +1,292 @@
+// src/io/session.rs
+//
+// Snapshot of the open tabs as JSON: structures (edited and as loaded),
+// styles, camera and the per-atom and per-pair settings that have no file
+// format of their own. Autosave writes one every few minutes and removes
+// it on a clean exit, so a snapshot still there at startup means the last
+// run did not end normally.
+
+use crate::config::{Config, RenderStyle};
+use crate::model::Structure;
+use crate::state::{AppState, AtomOverride, BondRule, TabState, ViewState};
+use directories::ProjectDirs;
+use nalgebra::{Quaternion, UnitQuaternion};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Bumped when the layout changes incompatibly; other versions are refused.
+const SESSION_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    pub active_tab: usize,
+    pub tabs: Vec<SessionTab>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SessionTab {
+    pub file_name: String,
+    pub modified: bool,
+    pub structure: Option<Structure>,
+    pub original_structure: Option<Structure>,
+    /// `Structure::formula` is skipped when a structure is serialized.
+    pub formula: String,
+    pub style: RenderStyle,
+    pub view: SessionView,
+    pub overrides: Vec<(usize, AtomOverride)>,
+    pub bond_pair_cutoffs: Vec<(String, String, f64)>,
+    /// Absent from snapshots written before pair rules existed.
+    #[serde(default)]
+    pub bond_pair_rules: Vec<(String, String, BondRule)>,
+}
+
+/// The parts of `ViewState` worth keeping; the rest starts from the
+/// defaults again.
+#[derive(Serialize, Deserialize)]
+pub struct SessionView {
+    /// Unit quaternion as (w, i, j, k).
+    pub rotation: [f64; 4],
+    pub zoom: f64,
+    pub pan_x: f64,
+    pub pan_y: f64,
+    pub show_bonds: bool,
+    pub bond_cutoff: f64,
+    pub show_full_unit_cell: bool,
+    pub faint_ghosts: bool,
+    pub replication: [u32; 3],
+}
+
+impl Session {
+    pub fn capture(state: &AppState) -> Self {
+        Self {
+            version: SESSION_VERSION,
+            active_tab: state.active_tab_index,
+            tabs: state.tabs.iter().map(SessionTab::capture).collect(),
+        }
+    }
+
+    /// Rebuild the tabs, each on top of a fresh tab from `config`. Returns
+    /// them with the index of the tab that was active.
+    pub fn into_tabs(self, config: &Config) -> (Vec<TabState>, usize) {
+        let tabs: Vec<TabState> = self.tabs.into_iter().map(|t| t.restore(config)).collect();
+        let active = self.active_tab.min(tabs.len().saturating_sub(1));
+        (tabs, active)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let session: Self = serde_json::from_str(json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if session.version != SESSION_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported session version {}", session.version),
+            ));
+        }
+        Ok(session)
+    }
+}
+
+impl SessionTab {
+    fn capture(tab: &TabState) -> Self {
+        let v = &tab.view;
+        let q = v.rotation.quaternion();
+        let mut overrides: Vec<_> = tab.overrides.iter().map(|(&i, o)| (i, o.clone())).collect();
+        overrides.sort_by_key(|(i, _)| *i);
+        let mut bond_pair_cutoffs: Vec<_> = tab
+            .bond_pair_cutoffs
+            .iter()
+            .map(|((a, b), &d)| (a.clone(), b.clone(), d))
+            .collect();
+        bond_pair_cutoffs.sort_by(|x, y| (&x.0, &x.1).cmp(&(&y.0, &y.1)));
+        let mut bond_pair_rules: Vec<_> = tab
+            .bond_pair_rules
+            .iter()
+            .map(|((a, b), &rule)| (a.clone(), b.clone(), rule))
+            .collect();
+        bond_pair_rules.sort_by(|x, y| (&x.0, &x.1).cmp(&(&y.0, &y.1)));
+
+        Self {
+            file_name: tab.file_name.clone(),
+            modified: tab.modified,
+            structure: tab.structure.clone(),
+            original_structure: tab.original_structure.clone(),
+            formula: tab
+                .structure
+                .as_ref()
+                .map(|s| s.formula.clone())
+                .unwrap_or_default(),
+            style: tab.style.clone(),
+            view: SessionView {
+                rotation: [q.w, q.i, q.j, q.k],
+                zoom: v.zoom,
+                pan_x: v.pan_x,
+                pan_y: v.pan_y,
+                show_bonds: v.show_bonds,
+                bond_cutoff: v.bond_cutoff,
+                show_full_unit_cell: v.show_full_unit_cell,
+                faint_ghosts: v.faint_ghosts,
+                replication: v.replication,
+            },
+            overrides,
+            bond_pair_cutoffs,
+            bond_pair_rules,
+        }
+    }
+
+    fn restore(self, config: &Config) -> TabState {
+        let mut tab = TabState::new(config);
+        let [w, i, j, k] = self.view.rotation;
+        tab.view = ViewState {
+            rotation: UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k)),
+            zoom: self.view.zoom,
+            pan_x: self.view.pan_x,
+            pan_y: self.view.pan_y,
+            show_bonds: self.view.show_bonds,
+            bond_cutoff: self.view.bond_cutoff,
+            show_full_unit_cell: self.view.show_full_unit_cell,
+            faint_ghosts: self.view.faint_ghosts,
+            replication: self.view.replication,
+            ..tab.view
+        };
+        tab.structure = self.structure.map(|mut s| {
+            s.formula = self.formula;
+            s
+        });
+        if let Some(s) = &tab.structure {
+            tab.bvs_cache.resize(s.atoms.len(), 0.0);
+        }
+        tab.original_structure = self.original_structure;
+        tab.file_name = self.file_name;
+        tab.modified = self.modified;
+        tab.style = self.style;
+        tab.overrides = self.overrides.into_iter().collect();
+        tab.bond_pair_cutoffs = self
+            .bond_pair_cutoffs
+            .into_iter()
+            .map(|(a, b, d)| ((a, b), d))
+            .collect();
+        tab.bond_pair_rules = self
+            .bond_pair_rules
+            .into_iter()
+            .map(|(a, b, rule)| ((a, b), rule))
+            .collect();
+        tab
+    }
+}
+
+/// Where autosave keeps its snapshot: the per-user cache directory, or the
+/// system temp directory when there is none.
+pub fn autosave_path() -> PathBuf {
+    match ProjectDirs::from("com", "example", "cview") {
+        Some(proj) => proj.cache_dir().join("autosave.json"),
+        None => std::env::temp_dir().join("cview-autosave.json"),
+    }
+}
+
+/// Write `json` to `path` through a temporary file and a rename, so a crash
+/// mid-write leaves the previous snapshot intact.
+pub fn write_atomic(path: &Path, json: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+pub fn read(path: &Path) -> io::Result<Session> {
+    Session::from_json(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Atom;
+    use crate::state::bond_pair_key;
+    use nalgebra::Vector3;
+
+    fn nacl() -> Structure {
+        let atom = |element: &str, position: [f64; 3], i: usize| Atom {
+            element: element.to_string(),
+            position,
+            original_index: i,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        };
+        Structure {
+            lattice: [[5.64, 0.0, 0.0], [0.0, 5.64, 0.0], [0.0, 0.0, 5.64]],
+            atoms: vec![atom("Na", [0.0; 3], 0), atom("Cl", [2.82, 2.82, 2.82], 1)],
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        }
+    }
+
+    #[test]
+    fn session_round_trips_tabs() {
+        let config = Config::default();
+        let mut tab = TabState::new(&config);
+        tab.structure = Some(nacl());
+        tab.original_structure = Some(nacl());
+        tab.file_name = "NaCl.cif".to_string();
+        tab.modified = true;
+        tab.style.background_color = (0.1, 0.2, 0.3);
+        tab.view.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.7);
+        tab.view.replication = [2, 1, 1];
+        tab.overrides.insert(
+            1,
+            AtomOverride {
+                hidden: true,
+                ..Default::default()
+            },
+        );
+        tab.bond_pair_cutoffs.insert(bond_pair_key("Na", "Cl"), 3.1);
+        tab.bond_pair_rules
+            .insert(bond_pair_key("Na", "Na"), BondRule::Never);
+        let state = AppState {
+            tabs: vec![TabState::new(&config), tab],
+            active_tab_index: 1,
+            config: config.clone(),
+        };
+
+        let json = Session::capture(&state).to_json().unwrap();
+        let (tabs, active) = Session::from_json(&json).unwrap().into_tabs(&config);
+        assert_eq!((tabs.len(), active), (2, 1));
+        assert!(tabs[0].structure.is_none());
+
+        let t = &tabs[1];
+        assert_eq!(t.file_name, "NaCl.cif");
+        assert!(t.modified);
+        let s = t.structure.as_ref().unwrap();
+        assert_eq!((s.formula.as_str(), s.atoms.len()), ("NaCl", 2));
+        assert_eq!(t.bvs_cache.len(), 2);
+        assert_eq!(t.style.background_color, (0.1, 0.2, 0.3));
+        assert!(t.view.rotation.angle_to(&state.tabs[1].view.rotation) < 1e-12);
+        assert_eq!(t.view.replication, [2, 1, 1]);
+        assert!(t.is_hidden(1));
+        assert_eq!(t.bond_pair_cutoffs[&bond_pair_key("Cl", "Na")], 3.1);
+        assert_eq!(
+            t.bond_pair_rules[&bond_pair_key("Na", "Na")],
+            BondRule::Never
+        );
+
+        // The same state serializes the same way, which autosave relies on
+        // to skip unchanged snapshots.
+        assert_eq!(Session::capture(&state).to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let json = r#"{"version":99,"active_tab":0,"tabs":[]}"#;
+        let err = Session::from_json(json).err().unwrap();
+        assert!(err.to_string().contains("version 99"), "{err}");
+        assert!(Session::from_json("not json").is_err());
+    }
+}
@@ -22,6 +22,7 @@
 // - Multiple occupation at single site
 
 use crate::model::elements::get_atomic_number;
+use crate::model::structure::OVERLAP_DIST;
 use crate::model::{Atom, Structure};
 use crate::utils::linalg::frac_to_cart;
 use std::collections::HashMap;
@@ -459,6 +460,7 @@ fn build_structure(data: SprkkrData) -> io::Result<Structure> {
                     original_index: atoms.len(),
                     oxidation: None,
                     occupancy: concentration.clamp(0.0, 1.0),
+                    adp: None,
                 });
             }
         }
@@ -493,11 +495,52 @@ fn build_structure(data: SprkkrData) -> io::Result<Structure> {
 // WRITER
 // ============================================================================
 
+/// Group atoms into SPR-KKR sites: partially occupied atoms within
+/// `OVERLAP_DIST` of each other whose occupancies sum to at most 1 share a
+/// site (the inverse of the CPA import); every other atom is its own site.
+fn group_sites(structure: &Structure) -> Vec<Vec<usize>> {
+    let atoms = &structure.atoms;
+    let mut site_of: Vec<Option<usize>> = vec![None; atoms.len()];
+    let mut sites: Vec<Vec<usize>> = Vec::new();
+    for i in 0..atoms.len() {
+        if site_of[i].is_some() {
+            continue;
+        }
+        let mut members = vec![i];
+        let mut total = atoms[i].occupancy;
+        if atoms[i].occupancy < 0.999 {
+            for j in (i + 1)..atoms.len() {
+                let d2: f64 = (0..3)
+                    .map(|k| (atoms[j].position[k] - atoms[i].position[k]).powi(2))
+                    .sum();
+                if site_of[j].is_none()
+                    && atoms[j].occupancy < 0.999
+                    && total + atoms[j].occupancy <= 1.0 + 1e-6
+                    && d2 < OVERLAP_DIST * OVERLAP_DIST
+                {
+                    members.push(j);
+                    total += atoms[j].occupancy;
+                }
+            }
+        }
+        for &m in &members {
+            site_of[m] = Some(sites.len());
+        }
+        sites.push(members);
+    }
+    sites
+}
+
 pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
-    if structure.atoms.iter().any(|a| a.occupancy < 0.99) {
-        crate::utils::console::log_warn(
-            "SPR-KKR format has no occupancy field — partial occupancies are discarded on export",
-        );
+    let sites = group_sites(structure);
+    let vacant = sites
+        .iter()
+        .filter(|s| s.iter().map(|&i| structure.atoms[i].occupancy).sum::<f64>() < 0.99)
+        .count();
+    if vacant > 0 {
+        crate::utils::console::log_warn(&format!(
+            "SPR-KKR sites must be fully occupied — {vacant} site(s) with vacancies are written with concentrations rescaled to sum to 1"
+        ));
     }
     let mut file = File::create(path)?;
 
@@ -568,14 +611,15 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
         "   IQ       QX                   QY                   QZ"
     )?;
 
-    for (i, atom) in structure.atoms.iter().enumerate() {
+    for (i, site) in sites.iter().enumerate() {
+        let position = structure.atoms[site[0]].position;
         writeln!(
             file,
             " {:4}    {:18.10} {:18.10} {:18.10}",
             i + 1,
-            atom.position[0],
-            atom.position[1],
-            atom.position[2]
+            position[0],
+            position[1],
+            position[2]
         )?;
     }
 
@@ -589,15 +633,27 @@ pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     writeln!(file, "OCCUPATION")?;
     writeln!(file, "   IQ     IREFQ       IMQ       NOQ  ITOQ  CONC")?;
 
-    for (i, type_id) in atom_to_type_id.iter().enumerate() {
+    for (i, site) in sites.iter().enumerate() {
+        let total: f64 = site.iter().map(|&a| structure.atoms[a].occupancy).sum();
+        let occupants: String = site
+            .iter()
+            .map(|&a| {
+                let conc = if total > 0.0 {
+                    structure.atoms[a].occupancy / total
+                } else {
+                    1.0 / site.len() as f64
+                };
+                format!(" {:5}   {:.5}", atom_to_type_id[a], conc)
+            })
+            .collect();
         writeln!(
             file,
-            " {:4} {:9} {:9} {:9} {:5}   1.00000",
-            i + 1,   // IQ: site number
-            i + 1,   // IREFQ: reference site (self)
-            i + 1,   // IMQ: magnetic site index
-            1,       // NOQ: number of occupations (pure site = 1)
-            type_id  // ITOQ: type ID
+            " {:4} {:9} {:9} {:9}{}",
+            i + 1,      // IQ: site number
+            i + 1,      // IREFQ: reference site (self)
+            i + 1,      // IMQ: magnetic site index
+            site.len(), // NOQ: number of occupations (CPA site > 1)
+            occupants   // ITOQ CONC pairs
         )?;
     }
 
@@ -685,4 +741,50 @@ mod tests {
         assert_eq!(extract_first_number("ALAT = 5.42"), Some(5.42));
         assert_eq!(extract_first_number("ALAT 9.44"), Some(9.44));
     }
+
+    #[test]
+    fn cpa_site_round_trips_with_concentrations() {
+        let atom = |element: &str, position: [f64; 3], occupancy: f64| Atom {
+            element: element.to_string(),
+            position,
+            original_index: 0,
+            oxidation: None,
+            occupancy,
+            adp: None,
+        };
+        let s = Structure {
+            lattice: [
+                [1.435, 1.435, 1.435],
+                [-1.435, 1.435, 1.435],
+                [1.435, -1.435, 1.435],
+            ],
+            atoms: vec![
+                atom("Fe", [0.0, 0.0, 0.0], 0.7),
+                atom("Al", [1.435, 1.435, 1.435], 1.0),
+                atom("Cr", [0.0, 0.0, 0.0], 0.3),
+            ],
+            formula: "FeCrAl".to_string(),
+            is_periodic: true,
+        };
+        assert_eq!(group_sites(&s), vec![vec![0, 2], vec![1]]);
+
+        let path = std::env::temp_dir().join(format!("cview_cpa_{}.sys", std::process::id()));
+        let path = path.to_str().unwrap();
+        write(path, &s).unwrap();
+        let back = parse(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        let occ: Vec<(&str, f64)> = back
+            .atoms
+            .iter()
+            .map(|a| (a.element.as_str(), a.occupancy))
+            .collect();
+        assert_eq!(occ.len(), 3);
+        assert_eq!(occ[0].0, "Fe");
+        assert!((occ[0].1 - 0.7).abs() < 1e-5);
+        assert_eq!(occ[1].0, "Cr");
+        assert!((occ[1].1 - 0.3).abs() < 1e-5);
+        assert_eq!(occ[2], ("Al", 1.0));
+        assert_eq!(back.atoms[1].position, back.atoms[0].position);
+    }
 }
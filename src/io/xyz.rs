@@ -1,17 +1,74 @@
+use crate::model::structure::generate_formula;
 use crate::model::{Atom, Structure};
 use std::fs::File;
 use std::io::Write;
 use std::io::{self, BufRead}; // Ensure Write is imported
 
+/// Atom count of the first frame, read from the first line alone.
+pub fn header_atom_count(path: &str) -> Option<usize> {
+    let mut line = String::new();
+    io::BufReader::new(File::open(path).ok()?)
+        .read_line(&mut line)
+        .ok()?;
+    line.trim().parse().ok()
+}
+
+/// First frame of the file; see `parse_frames` for trajectories.
 pub fn parse(path: &str) -> io::Result<Structure> {
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
-    let mut lines = reader.lines();
+    let mut lines = io::BufReader::new(File::open(path)?).lines();
+    read_frame(&mut lines)?.ok_or_else(empty_file)
+}
+
+/// Every frame of a concatenated XYZ trajectory (relaxation or MD output),
+/// in file order. Frames may differ in atom count. A malformed block after
+/// the first frame ends the trajectory there with a console warning,
+/// rather than losing the frames read so far.
+pub fn parse_frames(path: &str) -> io::Result<Vec<Structure>> {
+    let mut lines = io::BufReader::new(File::open(path)?).lines();
+    let mut frames = Vec::new();
+    loop {
+        match read_frame(&mut lines) {
+            Ok(Some(frame)) => frames.push(frame),
+            Ok(None) => break,
+            Err(e) if !frames.is_empty() => {
+                crate::utils::console::log_warn(&format!(
+                    "XYZ: stopped after frame {} of {}: {}",
+                    frames.len(),
+                    path,
+                    e
+                ));
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if frames.is_empty() {
+        return Err(empty_file());
+    }
+    Ok(frames)
+}
 
+fn empty_file() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Empty XYZ file")
+}
+
+/// Next count + comment + atoms block from `lines`, or `None` at the end of
+/// the input. Blank lines between frames are skipped.
+fn read_frame(
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+) -> io::Result<Option<Structure>> {
     // 1. Number of Atoms
-    let n_atoms_str = lines
-        .next()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Empty XYZ file"))??;
+    let n_atoms_str = loop {
+        match lines.next() {
+            None => return Ok(None),
+            Some(line) => {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    break line;
+                }
+            }
+        }
+    };
     let n_atoms: usize = n_atoms_str
         .trim()
         .parse()
@@ -49,14 +106,13 @@ pub fn parse(path: &str) -> io::Result<Structure> {
     // 3. Atoms
     //
     // Respect n_atoms strictly: XYZ trajectory files concatenate multiple
-    // frames, each with its own count+comment header. Reading to EOF would
-    // merge frames and scale linearly with trajectory length; stopping at
-    // n_atoms loads just the first frame.
+    // frames, each with its own count+comment header, and the next frame
+    // starts right after this one's last atom.
     let mut atoms = Vec::with_capacity(n_atoms);
-    for (i, line) in lines.enumerate() {
-        if atoms.len() >= n_atoms {
+    while atoms.len() < n_atoms {
+        let Some(line) = lines.next() else {
             break;
-        }
+        };
         let line = line?;
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() < 4 {
@@ -77,44 +133,78 @@ pub fn parse(path: &str) -> io::Result<Structure> {
         atoms.push(Atom {
             element: el,
             position: [x, y, z],
-            original_index: i,
+            original_index: atoms.len(),
             oxidation: None,
             occupancy: 1.0,
+            adp: None,
         });
     }
 
-    Ok(Structure {
+    Ok(Some(Structure {
         lattice,
         atoms,
         formula: "XYZ Import".to_string(),
         is_periodic,
-    })
+    }))
 }
 
+/// Single-frame XYZ: atom count, a comment with the formula, then
+/// `El x y z` rows in Cartesian Å. Periodic structures get the
+/// extended-XYZ lattice in the comment so the cell survives a round trip.
 pub fn write(path: &str, structure: &Structure) -> io::Result<()> {
     if structure.atoms.iter().any(|a| a.occupancy < 0.99) {
         crate::utils::console::log_warn(
             "XYZ format has no occupancy field — partial occupancies are discarded on export",
         );
     }
-    let mut file = std::fs::File::create(path)?;
+    let mut file = io::BufWriter::new(File::create(path)?);
+    let comment = frame_comment(structure, &generate_formula(&structure.atoms));
+    write_frame(&mut file, structure, &comment)?;
+    file.flush()
+}
+
+/// Concatenated frames (a trajectory or scan), one `(comment, structure)`
+/// per frame. Periodic frames get the extended-XYZ lattice in front of the
+/// comment so each frame stays self-describing.
+pub fn write_frames(path: &str, frames: &[(String, Structure)]) -> io::Result<()> {
+    let mut file = io::BufWriter::new(File::create(path)?);
+    for (comment, structure) in frames {
+        write_frame(&mut file, structure, &frame_comment(structure, comment))?;
+    }
+    file.flush()
+}
 
+/// `comment`, behind the lattice header when the structure is periodic.
+fn frame_comment(structure: &Structure, comment: &str) -> String {
+    let full = if structure.is_periodic {
+        format!("{} {}", lattice_header(structure.lattice), comment)
+    } else {
+        comment.to_string()
+    };
+    full.trim_end().to_string()
+}
+
+/// Extended XYZ comment: `Lattice="ax ay az bx by bz cx cy cz"` and the
+/// column layout.
+fn lattice_header(l: [[f64; 3]; 3]) -> String {
+    format!(
+        "Lattice=\"{:.9} {:.9} {:.9} {:.9} {:.9} {:.9} {:.9} {:.9} {:.9}\" \
+         Properties=species:S:1:pos:R:3",
+        l[0][0], l[0][1], l[0][2], l[1][0], l[1][1], l[1][2], l[2][0], l[2][1], l[2][2]
+    )
+}
+
+fn write_frame(out: &mut impl Write, structure: &Structure, comment: &str) -> io::Result<()> {
     // 1. Number of atoms
-    writeln!(file, "{}", structure.atoms.len())?;
+    writeln!(out, "{}", structure.atoms.len())?;
 
-    // 2. Comment line: Write Lattice in Extended XYZ format
-    // Lattice="ax ay az bx by bz cx cy cz"
-    let l = structure.lattice;
-    writeln!(file, "Lattice=\"{:.9} {:.9} {:.9} {:.9} {:.9} {:.9} {:.9} {:.9} {:.9}\" Properties=species:S:1:pos:R:3",
-        l[0][0], l[0][1], l[0][2],
-        l[1][0], l[1][1], l[1][2],
-        l[2][0], l[2][1], l[2][2]
-    )?;
+    // 2. Comment line
+    writeln!(out, "{}", comment)?;
 
     // 3. Atom lines
     for atom in &structure.atoms {
         writeln!(
-            file,
+            out,
             "{:<4} {:15.9} {:15.9} {:15.9}",
             atom.element, atom.position[0], atom.position[1], atom.position[2]
         )?;
@@ -178,6 +268,14 @@ mod tests {
         approx(s.lattice[2][2], 3.0);
     }
 
+    #[test]
+    fn header_count_is_the_first_line() {
+        let f = TmpFile::new("120000\nbig\nH 0 0 0\n");
+        assert_eq!(header_atom_count(f.path()), Some(120000));
+        let junk = TmpFile::new("H 0 0 0\n");
+        assert_eq!(header_atom_count(junk.path()), None);
+    }
+
     #[test]
     fn multiframe_trajectory_reads_only_first_frame() {
         // Two concatenated frames of 1 atom each; parser must stop at n_atoms.
@@ -187,6 +285,28 @@ mod tests {
         approx(s.atoms[0].position[0], 0.0);
     }
 
+    #[test]
+    fn trajectory_frames_are_all_read() {
+        // Frames of different sizes, blank lines between them, and junk
+        // after the last complete frame.
+        let f = TmpFile::new(
+            "1\nstep 0\nH 0.0 0.0 0.0\n\n2\nstep 1\nH 0.5 0.0 0.0\nH 0.0 0.5 0.0\n\
+             1\nstep 2\nO 1.0 1.0 1.0\ngarbage\n",
+        );
+        let frames = parse_frames(f.path()).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(
+            frames.iter().map(|s| s.atoms.len()).collect::<Vec<_>>(),
+            vec![1, 2, 1]
+        );
+        approx(frames[1].atoms[1].position[1], 0.5);
+        assert_eq!(frames[1].atoms[1].original_index, 1);
+        assert_eq!(frames[2].atoms[0].element, "O");
+
+        let empty = TmpFile::new("\n\n");
+        assert!(parse_frames(empty.path()).is_err());
+    }
+
     #[test]
     fn write_then_parse_roundtrips() {
         let original = Structure {
@@ -197,6 +317,7 @@ mod tests {
                 original_index: 0,
                 oxidation: None,
                 occupancy: 1.0,
+                adp: None,
             }],
             formula: String::new(),
             is_periodic: true,
@@ -209,4 +330,90 @@ mod tests {
         assert_eq!(s.atoms[0].element, "Fe");
         approx(s.atoms[0].position[2], 3.0);
     }
+
+    #[test]
+    fn cell_and_positions_survive_a_round_trip() {
+        let atom = |element: &str, position: [f64; 3], i: usize| Atom {
+            element: element.into(),
+            position,
+            original_index: i,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        };
+        let original = Structure {
+            lattice: [[5.64, 0.0, 0.0], [0.3, 5.1, 0.0], [0.0, -0.2, 6.0123456]],
+            atoms: vec![
+                atom("Na", [0.0, 0.0, 0.0], 0),
+                atom("Cl", [2.97, 2.45, 3.0061728], 1),
+            ],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let f = TmpFile::new("");
+        write(f.path(), &original).unwrap();
+
+        let text = std::fs::read_to_string(f.path()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "2");
+        assert!(lines[1].starts_with("Lattice=\""), "{}", lines[1]);
+        assert!(lines[1].ends_with(" ClNa"), "{}", lines[1]);
+
+        let s = parse(f.path()).unwrap();
+        assert!(s.is_periodic);
+        for (row, expected) in s.lattice.iter().zip(&original.lattice) {
+            for (x, y) in row.iter().zip(expected) {
+                approx(*x, *y);
+            }
+        }
+        for (a, b) in s.atoms.iter().zip(&original.atoms) {
+            assert_eq!(a.element, b.element);
+            for (x, y) in a.position.iter().zip(&b.position) {
+                approx(*x, *y);
+            }
+        }
+
+        // A molecule is written without a cell and reads back as one.
+        let molecule = Structure {
+            is_periodic: false,
+            ..original
+        };
+        write(f.path(), &molecule).unwrap();
+        let text = std::fs::read_to_string(f.path()).unwrap();
+        assert_eq!(text.lines().nth(1), Some("ClNa"));
+        assert!(!parse(f.path()).unwrap().is_periodic);
+    }
+
+    #[test]
+    fn frames_are_concatenated_with_their_comments() {
+        let frame = |x: f64| Structure {
+            lattice: [[20.0, 0.0, 0.0], [0.0, 20.0, 0.0], [0.0, 0.0, 20.0]],
+            atoms: vec![Atom {
+                element: "H".into(),
+                position: [x, 0.0, 0.0],
+                original_index: 0,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            }],
+            formula: String::new(),
+            is_periodic: false,
+        };
+        let frames = vec![
+            ("step 0".to_string(), frame(0.0)),
+            ("step 1".to_string(), frame(1.5)),
+        ];
+        let f = TmpFile::new("");
+        write_frames(f.path(), &frames).unwrap();
+
+        let text = std::fs::read_to_string(f.path()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[1], "step 0");
+        assert_eq!(lines[4], "step 1");
+        // Molecules carry no lattice, so frames read back non-periodic.
+        let s = parse(f.path()).unwrap();
+        assert!(!s.is_periodic);
+        approx(s.atoms[0].position[0], 0.0);
+    }
 }
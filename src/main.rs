@@ -3,7 +3,7 @@
 use gtk4::prelude::*;
 use gtk4::Box as GtkBox;
 use gtk4::{
-    Application, ApplicationWindow, Frame, Label, Notebook, Orientation, Revealer,
+    gdk, Application, ApplicationWindow, Frame, Label, Notebook, Orientation, Paned, Revealer,
     RevealerTransitionType, ScrolledWindow, TextView,
 };
 use std::cell::RefCell;
@@ -26,6 +26,12 @@ use ui::interactions::setup_interactions;
 use utils::console;
 
 fn main() {
+    // Headless modes run before GTK is touched.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("--thumbnails") {
+        std::process::exit(run_thumbnails(&args[2..]));
+    }
+
     let app = Application::builder()
         .application_id("org.mavensgroup.cview")
         .build();
@@ -36,6 +42,62 @@ fn main() {
     app.run_with_args(&Vec::<String>::new());
 }
 
+/// Width and height of every connected monitor, for fitting a restored
+/// window size.
+fn monitor_sizes() -> Vec<(i32, i32)> {
+    let Some(display) = gdk::Display::default() else {
+        return Vec::new();
+    };
+    let monitors = display.monitors();
+    (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i).and_downcast::<gdk::Monitor>())
+        .map(|m| {
+            let g = m.geometry();
+            (g.width(), g.height())
+        })
+        .collect()
+}
+
+/// `cview --thumbnails DIR --out DIR2`: one PNG per structure file in DIR,
+/// no window. Exits 1 if any file failed, 2 on bad arguments.
+fn run_thumbnails(args: &[String]) -> i32 {
+    let (Some(src), Some("--out"), Some(out)) =
+        (args.first(), args.get(1).map(String::as_str), args.get(2))
+    else {
+        eprintln!("Usage: cview --thumbnails DIR --out DIR2");
+        return 2;
+    };
+    let (config, _) = config::Config::load();
+    let results = match rendering::export::write_thumbnails(
+        std::path::Path::new(src),
+        std::path::Path::new(out),
+        &config,
+    ) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+
+    let mut failed = 0;
+    for (name, result) in &results {
+        match result {
+            Ok(msg) => println!("{}: {}", name, msg),
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    println!(
+        "{} thumbnail(s) written, {} failed",
+        results.len() - failed,
+        failed
+    );
+    i32::from(failed > 0)
+}
+
 fn build_ui(app: &Application) {
     // Initialize libadwaita so Adwaita widgets (e.g. the About dialog) and the
     // Adwaita stylesheet are available. Must run before any widget is built.
@@ -47,22 +109,45 @@ fn build_ui(app: &Application) {
     let (initial_state, _startup_log) = AppState::new_with_log();
     let state = Rc::new(RefCell::new(initial_state));
 
+    // Window and pane sizes from the last session, or the defaults.
+    let layout = {
+        let st = state.borrow();
+        if st.config.remember_window_layout {
+            st.config.clone()
+        } else {
+            config::Config::default()
+        }
+    };
+    let (width, height) = config::fit_window_size(
+        (layout.window_width, layout.window_height),
+        &monitor_sizes(),
+    );
     let window = ApplicationWindow::builder()
         .application(app)
         .title("CView - Crystal Structure Viewer")
-        .default_width(1200)
-        .default_height(800)
+        .default_width(width)
+        .default_height(height)
+        .maximized(layout.window_maximized)
         .build();
 
     // --- LAYOUT ---
     let root_vbox = GtkBox::new(Orientation::Vertical, 0);
     window.set_child(Some(&root_vbox));
 
-    let main_hbox = GtkBox::new(Orientation::Horizontal, 0);
+    // Sidebar | (view above console), both splits draggable.
+    let main_paned = Paned::new(Orientation::Horizontal);
+    main_paned.set_vexpand(true);
+    main_paned.set_resize_start_child(false);
+    main_paned.set_shrink_start_child(false);
+    main_paned.set_position(layout.sidebar_width);
 
     // Right Panel
-    let right_vbox = GtkBox::new(Orientation::Vertical, 0);
-    right_vbox.set_hexpand(true);
+    let right_paned = Paned::new(Orientation::Vertical);
+    right_paned.set_hexpand(true);
+    right_paned.set_shrink_end_child(false);
+    if let Some(split) = layout.console_split {
+        right_paned.set_position(split);
+    }
 
     // --- VIEW NOTEBOOK (TABBED INTERFACE) ---
     let view_notebook = Notebook::new();
@@ -110,11 +195,14 @@ fn build_ui(app: &Application) {
     let scroll_logs = ScrolledWindow::builder().child(&log_view).build();
     console_notebook.append_page(&scroll_logs, Some(&Label::new(Some("System Log"))));
 
+    let last_page = console_notebook.n_pages().saturating_sub(1);
+    console_notebook.set_current_page(Some(layout.console_page.min(last_page)));
+
     let info_frame = Frame::new(None);
     info_frame.set_child(Some(&console_notebook));
 
-    right_vbox.append(&view_notebook);
-    right_vbox.append(&info_frame);
+    right_paned.set_start_child(Some(&view_notebook));
+    right_paned.set_end_child(Some(&info_frame));
 
     // --- INITIAL TAB SETUP ---
     let (first_da, first_tab_box) = ui::create_tab_content(state.clone(), 0);
@@ -129,13 +217,21 @@ fn build_ui(app: &Application) {
     let sidebar_revealer = Revealer::builder()
         .transition_type(RevealerTransitionType::SlideRight)
         .child(&sidebar_widget)
-        .reveal_child(true)
+        .reveal_child(layout.sidebar_visible)
+        .visible(layout.sidebar_visible)
         .build();
+    // A collapsed revealer still holds its pane; hide it once the slide
+    // finishes so the view takes the whole width.
+    sidebar_revealer.connect_child_revealed_notify(|rev| {
+        if !rev.reveals_child() && !rev.is_child_revealed() {
+            rev.set_visible(false);
+        }
+    });
 
     sidebar_widget.set_width_request(300);
     sidebar_widget.set_hexpand(false);
-    main_hbox.append(&sidebar_revealer);
-    main_hbox.append(&right_vbox);
+    main_paned.set_start_child(Some(&sidebar_revealer));
+    main_paned.set_end_child(Some(&right_paned));
 
     // --- MENU BAR ---
     let menu_bar = menu::build_menu_and_actions(
@@ -155,7 +251,11 @@ fn build_ui(app: &Application) {
     let rev_weak = sidebar_revealer.downgrade();
     toggle_action.connect_activate(move |_, _| {
         if let Some(rev) = rev_weak.upgrade() {
-            rev.set_reveal_child(!rev.reveals_child());
+            let show = !rev.reveals_child();
+            if show {
+                rev.set_visible(true);
+            }
+            rev.set_reveal_child(show);
         }
     });
     app.add_action(&toggle_action);
@@ -182,8 +282,7 @@ fn build_ui(app: &Application) {
     close_tab_action.connect_activate(move |_, _| {
         if let Some(nb) = nb_close.upgrade() {
             if let Some(page_idx) = nb.current_page() {
-                st_close.borrow_mut().remove_tab(page_idx as usize);
-                nb.remove_page(Some(page_idx));
+                ui::close_tab(&nb, st_close.clone(), page_idx);
             }
         }
     });
@@ -192,7 +291,7 @@ fn build_ui(app: &Application) {
 
     // --- ASSEMBLE ---
     root_vbox.append(&menu_bar);
-    root_vbox.append(&main_hbox);
+    root_vbox.append(&main_paned);
 
     // --- INTERACTIONS ---
     setup_interactions(&window, state.clone(), &first_da, sidebar_handles.clone());
@@ -205,58 +304,78 @@ fn build_ui(app: &Application) {
         st.active_tab_index = page_num as usize;
         // Newly-active tab has its own ViewState; push it into the sliders.
         handles_nb.sync_from_view(&st.active_tab().view);
+        handles_nb.sync_frames(st.active_tab());
     });
 
-    window.present();
+    // Edits made from the analysis window have no handle on the notebook;
+    // pick up their unsaved-changes markers when focus comes back.
+    let state_focus = state.clone();
+    let nb_focus = view_notebook.downgrade();
+    window.connect_is_active_notify(move |w| {
+        if let (true, Some(nb)) = (w.is_active(), nb_focus.upgrade()) {
+            ui::refresh_tab_labels(&nb, &state_focus.borrow());
+        }
+    });
 
-    // --- CLI LATE LOAD ---
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 {
-        let path = &args[1];
-        console::log_info(&format!("CLI: Opening '{}'", path));
-
-        match io::load_structure(path) {
-            Ok(structure) => {
-                {
-                    let mut st = state.borrow_mut();
-                    let tab = st.active_tab_mut();
-                    tab.original_structure = Some(structure.clone());
-                    tab.structure = Some(structure);
-                    tab.file_name = std::path::Path::new(path)
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string();
-
-                    if let Some(page) = view_notebook.nth_page(Some(0)) {
-                        if let Some(lbl_widget) = view_notebook.tab_label(&page) {
-                            if let Some(bx) = lbl_widget.downcast_ref::<GtkBox>() {
-                                if let Some(first_child) = bx.first_child() {
-                                    if let Some(l) = first_child.downcast_ref::<Label>() {
-                                        l.set_text(&tab.file_name);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                console::log_info(&format!("Loaded: {}", path));
-
-                let st = state.borrow();
-                let tab = st.active_tab();
-                if let Some(s) = &tab.structure {
-                    let report = utils::report::structure_summary(s, &tab.file_name);
-                    console::info_report(&report);
-                }
-                drop(st);
-
-                panels::sidebar::refresh_atom_list(&atom_list_box, state.clone(), &view_notebook);
-                first_da.queue_draw();
+    // Ask about unsaved edits before any other close handler runs.
+    ui::guard_unsaved_on_close(&window, state.clone());
+
+    // Save the layout for the next start. A clean exit also leaves no
+    // snapshot behind for the next start to offer.
+    let state_close = state.clone();
+    let (main_weak, right_weak) = (main_paned.downgrade(), right_paned.downgrade());
+    let (rev_close, console_close) = (sidebar_revealer.downgrade(), console_notebook.downgrade());
+    window.connect_close_request(move |w| {
+        let mut st = state_close.borrow_mut();
+        let config = &mut st.config;
+        if config.remember_window_layout {
+            // The default size tracks resizes except while maximized, so
+            // it is the size to unmaximize back to.
+            let (width, height) = w.default_size();
+            config.window_width = width;
+            config.window_height = height;
+            config.window_maximized = w.is_maximized();
+            if let Some(rev) = rev_close.upgrade() {
+                config.sidebar_visible = rev.reveals_child();
             }
-            Err(e) => {
-                console::log_error(&format!("Error loading '{}': {}", path, e));
+            if let Some(p) = main_weak.upgrade().filter(|_| config.sidebar_visible) {
+                config.sidebar_width = p.position();
+            }
+            if let Some(p) = right_weak.upgrade() {
+                config.console_split = Some(p.position());
+            }
+            if let Some(nb) = console_close.upgrade() {
+                config.console_page = nb.current_page().unwrap_or(0);
             }
+            console::log_info(&st.save_config());
         }
+        drop(st);
+        ui::autosave::finish(&state_close);
+        gtk4::glib::Propagation::Proceed
+    });
+
+    window.present();
+
+    // --- CLI LATE LOAD ---
+    if let Some(path) = std::env::args_os().nth(1) {
+        menu::actions_file::open_from_cli(
+            &window,
+            &state,
+            &view_notebook,
+            &first_da,
+            &atom_list_box,
+            sidebar_handles.clone(),
+            path.into(),
+        );
     }
+
+    // --- CRASH RECOVERY & AUTOSAVE ---
+    ui::autosave::offer_recovery(
+        &window,
+        state.clone(),
+        &view_notebook,
+        &atom_list_box,
+        sidebar_handles.clone(),
+    );
+    ui::autosave::start(&state);
 }
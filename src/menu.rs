@@ -42,7 +42,7 @@ pub fn build_menu_and_actions(
         sidebar_handles,
     );
     actions_tools::setup(app, window, state.clone(), notebook, drawing_area);
-    actions_analysis::setup(app, window, state.clone());
+    actions_analysis::setup(app, window, state.clone(), notebook);
     actions_help::setup(app, window);
 
     // --- 2. KEYBOARD SHORTCUTS ---
@@ -56,6 +56,10 @@ pub fn build_menu_and_actions(
     app.set_accels_for_action("app.toggle_boundaries", &["<Primary>b"]);
     app.set_accels_for_action("app.supercell", &["<Primary><Shift>c"]);
     app.set_accels_for_action("app.miller_planes", &["<Primary>m"]);
+    app.set_accels_for_action("app.select_all", &["<Primary>a"]);
+    app.set_accels_for_action("app.select_none", &["<Primary><Shift>a"]);
+    app.set_accels_for_action("app.select_invert", &["<Primary>i"]);
+    app.set_accels_for_action("app.hide_selected", &["<Primary>h"]);
 
     // --- 3. BUILD VISUAL MENU BAR ---
     let menu_bar = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
@@ -64,8 +68,16 @@ pub fn build_menu_and_actions(
     // --- FILE MENU ---
     let file_menu = gtk4::gio::Menu::new();
     file_menu.append(Some("Open..."), Some("app.open"));
+    #[cfg(feature = "materials-project")]
+    file_menu.append(
+        Some("Import from Materials Project..."),
+        Some("app.import_mp"),
+    );
     file_menu.append(Some("Save Structure As..."), Some("app.save_as"));
+    file_menu.append(Some("Export Selection..."), Some("app.export_selection"));
     file_menu.append(Some("Export Image/PDF..."), Some("app.export"));
+    file_menu.append(Some("Export All Tabs..."), Some("app.export_all"));
+    file_menu.append(Some("Export Data (CSV)..."), Some("app.export_csv"));
     file_menu.append(Some("Preferences..."), Some("app.preferences"));
     file_menu.append(Some("Quit"), Some("app.quit"));
     root_model.append_submenu(Some("File"), &file_menu);
@@ -73,41 +85,103 @@ pub fn build_menu_and_actions(
     // --- VIEW MENU ---
     let view_menu = gtk4::gio::Menu::new();
     view_menu.append(Some("Restore View"), Some("app.view_reset"));
+    view_menu.append(Some("Reset Tab to Defaults"), Some("app.reset_tab_defaults"));
+    view_menu.append(Some("Center on Centroid"), Some("app.recenter_centroid"));
+    view_menu.append(
+        Some("Center on Center of Mass"),
+        Some("app.recenter_center_of_mass"),
+    );
 
     let view_along_submenu = gtk4::gio::Menu::new();
-    view_along_submenu.append(Some("Along a-axis (X)"), Some("app.view_along_a"));
-    view_along_submenu.append(Some("Along b-axis (Y)"), Some("app.view_along_b"));
-    view_along_submenu.append(Some("Along c-axis (Z)"), Some("app.view_along_c"));
+    view_along_submenu.append(Some("Along a-axis"), Some("app.view_along_a"));
+    view_along_submenu.append(Some("Along b-axis"), Some("app.view_along_b"));
+    view_along_submenu.append(Some("Along c-axis"), Some("app.view_along_c"));
+    view_along_submenu.append(Some("Along [111] Diagonal"), Some("app.view_along_111"));
     view_menu.append_submenu(Some("View Along"), &view_along_submenu);
 
+    let center_submenu = gtk4::gio::Menu::new();
+    center_submenu.append(Some("As in Preferences"), Some("app.center_default"));
+    center_submenu.append(Some("Structure Centroid"), Some("app.center_centroid"));
+    center_submenu.append(Some("Unit Cell Center"), Some("app.center_unitcell"));
+    view_menu.append_submenu(Some("Rotation Center"), &center_submenu);
+
+    let stereo_submenu = gtk4::gio::Menu::new();
+    stereo_submenu.append(Some("Off"), Some("app.stereo_off"));
+    stereo_submenu.append(Some("Red/Cyan Anaglyph"), Some("app.stereo_anaglyph"));
+    stereo_submenu.append(Some("Side-by-Side Pair"), Some("app.stereo_side_by_side"));
+    view_menu.append_submenu(Some("Stereo 3D"), &stereo_submenu);
+
+    let axes_submenu = gtk4::gio::Menu::new();
+    axes_submenu.append(Some("Screen XYZ"), Some("app.axes_cartesian"));
+    axes_submenu.append(Some("Lattice abc"), Some("app.axes_lattice"));
+    view_menu.append_submenu(Some("Axes Gizmo"), &axes_submenu);
+
     view_menu.append(Some("Hide Symmetric Basis"), Some("app.toggle_boundaries"));
+    view_menu.append(
+        Some("Show/Hide Measurements"),
+        Some("app.toggle_measurements"),
+    );
+    view_menu.append(Some("Show/Hide Scale Bar"), Some("app.toggle_scale_bar"));
+    view_menu.append(Some("Show/Hide Element Legend"), Some("app.toggle_legend"));
     root_model.append_submenu(Some("View"), &view_menu);
 
     // --- TOOLS MENU ---
     let tools_menu = gtk4::gio::Menu::new();
     tools_menu.append(Some("Supercell..."), Some("app.supercell"));
+    tools_menu.append(Some("Build Supercell..."), Some("app.build_supercell"));
     tools_menu.append(Some("Basis..."), Some("app.basis"));
     tools_menu.append(Some("Atom Instances..."), Some("app.atom_instances"));
     tools_menu.append(Some("Miller Indices..."), Some("app.miller_planes"));
+    tools_menu.append(Some("Symmetrize Structure..."), Some("app.symmetrize"));
+    tools_menu.append(Some("Apply Strain..."), Some("app.strain"));
+    tools_menu.append(Some("Torsion Scan..."), Some("app.torsion_scan"));
+    tools_menu.append(
+        Some("Rotate to Standard Orientation"),
+        Some("app.standard_orientation"),
+    );
+    tools_menu.append(Some("Niggli-Reduce Cell"), Some("app.niggli_reduce"));
     tools_menu.append(
         Some("Toggle Primitive/Conventional"),
         Some("app.toggle_cell_view"),
     );
+
+    let selection_section = gtk4::gio::Menu::new();
+    selection_section.append(Some("Select All"), Some("app.select_all"));
+    selection_section.append(Some("Select None"), Some("app.select_none"));
+    selection_section.append(Some("Invert Selection"), Some("app.select_invert"));
+    tools_menu.append_section(None, &selection_section);
+
+    let measure_section = gtk4::gio::Menu::new();
+    measure_section.append(Some("Measure Mode"), Some("app.measure_mode"));
+    measure_section.append(Some("Pin Measurement"), Some("app.measure_pin"));
+    measure_section.append(Some("Clear Measurements"), Some("app.measure_clear"));
+    tools_menu.append_section(None, &measure_section);
+
+    let overrides_section = gtk4::gio::Menu::new();
+    overrides_section.append(Some("Recolor Selected..."), Some("app.recolor_selected"));
+    overrides_section.append(Some("Hide Selected"), Some("app.hide_selected"));
+    overrides_section.append(Some("Show Hidden Atoms"), Some("app.show_hidden"));
+    overrides_section.append(Some("Clear Overrides"), Some("app.clear_overrides"));
+    tools_menu.append_section(None, &overrides_section);
     root_model.append_submenu(Some("Tools"), &tools_menu);
 
     // --- ANALYSIS MENU ---
     let analysis_menu = gtk4::gio::Menu::new();
     analysis_menu.append(Some("Analysis Tools..."), Some("app.analysis"));
     analysis_menu.append(Some("Charge Density..."), Some("app.open_chgcar"));
+    analysis_menu.append(Some("Madelung Energy"), Some("app.madelung"));
     root_model.append_submenu(Some("Analysis"), &analysis_menu);
 
     // --- HELP MENU ---
     let help_menu = gtk4::gio::Menu::new();
     help_menu.append(Some("Controls & Shortcuts"), Some("app.help_controls"));
+    help_menu.append(Some("Keyboard Shortcuts"), Some("app.help_shortcuts"));
     help_menu.append(Some("Manual"), Some("app.help_manual"));
     help_menu.append(Some("About"), Some("app.help_about"));
     root_model.append_submenu(Some("Help"), &help_menu);
 
+    actions_help::setup_shortcuts(app, window, root_model.upcast_ref());
+
     let popover_bar = gtk4::PopoverMenuBar::from_model(Some(&root_model));
     menu_bar.append(&popover_bar);
 
@@ -2,20 +2,27 @@
 
 use crate::state::AppState;
 use crate::ui::analysis::window::{show_analysis_window, show_charge_density_window};
+use crate::utils::{console, report};
 use gtk4::prelude::*;
-use gtk4::{Application, ApplicationWindow};
+use gtk4::{Application, ApplicationWindow, Notebook};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-pub fn setup(app: &Application, window: &ApplicationWindow, state: Rc<RefCell<AppState>>) {
-    // --- Analysis Tools (Symmetry, XRD, Band Path, Voids, Slab) ---
+pub fn setup(
+    app: &Application,
+    window: &ApplicationWindow,
+    state: Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+) {
+    // --- Analysis Tools (Symmetry, XRD, G(r), Band Path, Voids, Slab, Compare) ---
     let action = gtk4::gio::SimpleAction::new("analysis", None);
     let win_weak = window.downgrade();
+    let nb_weak = notebook.downgrade();
     let state_c = state.clone();
 
     action.connect_activate(move |_, _| {
-        if let Some(win) = win_weak.upgrade() {
-            show_analysis_window(&win, state_c.clone());
+        if let (Some(win), Some(nb)) = (win_weak.upgrade(), nb_weak.upgrade()) {
+            show_analysis_window(&win, state_c.clone(), &nb);
         }
     });
     app.add_action(&action);
@@ -31,4 +38,16 @@ pub fn setup(app: &Application, window: &ApplicationWindow, state: Rc<RefCell<Ap
         }
     });
     app.add_action(&chgcar_action);
+
+    // --- Madelung energy — report in the Structure Info tab ---
+    let madelung_action = gtk4::gio::SimpleAction::new("madelung", None);
+    let state_c3 = state.clone();
+
+    madelung_action.connect_activate(move |_, _| {
+        let st = state_c3.borrow();
+        if let Some(structure) = &st.active_tab().structure {
+            console::info_report(&report::madelung_analysis(structure));
+        }
+    });
+    app.add_action(&madelung_action);
 }
@@ -1,17 +1,24 @@
 // src/menu/actions_file.rs
 
+use crate::config::{RecentDir, SaveFormat};
 use crate::io;
+use crate::model::structure::{Structure, OVERLAP_CHECK_MAX_ATOMS, OVERLAP_DIST};
 use crate::panels::sidebar::SidebarHandles;
+use crate::physics::bond_valence::{
+    analyze_structure, charge_balance, with_oxidation_overrides, BVSQuality,
+};
 use crate::state::AppState;
 use crate::ui::create_tab_content;
 use crate::ui::preferences::show_preferences_window;
 use crate::utils::{console, report};
+use gtk4::glib::WeakRef;
 use gtk4::prelude::*;
 use gtk4::{
     Application, ApplicationWindow, DrawingArea, FileChooserAction, FileChooserNative, FileFilter,
     Label, Notebook, ResponseType,
 };
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 pub fn setup(
@@ -70,6 +77,9 @@ pub fn setup(
         filter_struct.add_pattern("*.qe");
         filter_struct.add_pattern("*.out");
         filter_struct.add_pattern("*.log");
+        filter_struct.add_pattern("*.cell");
+        filter_struct.add_pattern("*.pdb");
+        filter_struct.add_pattern("*.PDB");
         dialog.add_filter(&filter_struct);
 
         let f_cif = FileFilter::new();
@@ -113,143 +123,41 @@ pub fn setup(
         f_qe.add_pattern("*.log");
         dialog.add_filter(&f_qe);
 
+        let f_castep = FileFilter::new();
+        f_castep.set_name(Some("CASTEP (*.cell)"));
+        f_castep.add_pattern("*.cell");
+        dialog.add_filter(&f_castep);
+
+        let f_pdb = FileFilter::new();
+        f_pdb.set_name(Some("Protein Data Bank (*.pdb)"));
+        f_pdb.add_pattern("*.pdb");
+        f_pdb.add_pattern("*.PDB");
+        dialog.add_filter(&f_pdb);
+
         let filter_any = FileFilter::new();
         filter_any.set_name(Some("All Files"));
         filter_any.add_pattern("*");
         dialog.add_filter(&filter_any);
 
+        if let Some(st) = state_weak.upgrade() {
+            crate::ui::start_in_recent_dir(&dialog, &st.borrow().config, RecentDir::Open);
+        }
+
         let state_inner = state_weak.clone();
-        let da_inner = da_weak.clone();
-        let nb_inner = notebook_weak.clone();
-        let atom_box_inner = atom_box_weak.clone();
-        let win_weak_inner = win.downgrade();
-        let handles_inner = sidebar_handles.clone();
+        let target = OpenTarget {
+            notebook: notebook_weak.clone(),
+            drawing_area: da_weak.clone(),
+            atom_box: atom_box_weak.clone(),
+            window: win.downgrade(),
+            handles: sidebar_handles.clone(),
+        };
 
         dialog.connect_response(move |d, response| {
             if response == ResponseType::Accept {
                 if let Some(file) = d.file() {
                     if let Some(path) = file.path() {
-                        let path_str = path.to_string_lossy().to_string();
-                        let filename = path
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_string();
-
                         if let Some(st_rc) = state_inner.upgrade() {
-                            match io::load_structure(&path_str) {
-                                Ok(structure) => {
-                                    let mut new_tab_index: Option<usize> = None;
-                                    let mut replace_current_tab = false;
-
-                                    {
-                                        let mut s = st_rc.borrow_mut();
-                                        let is_replace_mode = if s.tabs.is_empty() {
-                                            false
-                                        } else {
-                                            let t = s.active_tab();
-                                            t.structure.is_none() && t.file_name == "Untitled"
-                                        };
-
-                                        if is_replace_mode {
-                                            let tab = s.active_tab_mut();
-                                            tab.original_structure = Some(structure.clone());
-                                            tab.structure = Some(structure);
-                                            tab.file_name = filename.clone();
-                                            // Replacing the structure in-place must reset
-                                            // every per-tab piece of state that referred
-                                            // to the previous structure — otherwise old
-                                            // selections appear as "pre-highlighted"
-                                            // atoms on the new one.
-                                            tab.interaction.selected.clear();
-                                            tab.interaction.undo_stack.clear();
-                                            tab.miller_planes.clear();
-                                            tab.kpath_result = None;
-                                            tab.void_result = None;
-                                            tab.invalidate_bvs_cache();
-                                            replace_current_tab = true;
-                                        } else {
-                                            s.add_tab(structure, filename.clone());
-                                            new_tab_index = Some(s.tabs.len() - 1);
-                                        }
-                                    }
-
-                                    if let Some(nb) = nb_inner.upgrade() {
-                                        if replace_current_tab {
-                                            if let Some(page) = nb.nth_page(nb.current_page()) {
-                                                if let Some(lbl_box) = nb.tab_label(&page) {
-                                                    if let Some(bx) =
-                                                        lbl_box.downcast_ref::<gtk4::Box>()
-                                                    {
-                                                        if let Some(first_child) = bx.first_child()
-                                                        {
-                                                            if let Some(l) =
-                                                                first_child.downcast_ref::<Label>()
-                                                            {
-                                                                l.set_text(&filename);
-                                                            }
-                                                        }
-                                                    } else if let Some(l) =
-                                                        lbl_box.downcast_ref::<Label>()
-                                                    {
-                                                        l.set_text(&filename);
-                                                    }
-                                                }
-                                            }
-                                            if let Some(da) = da_inner.upgrade() {
-                                                da.queue_draw();
-                                            }
-                                        } else if let Some(idx) = new_tab_index {
-                                            let (new_da, container) =
-                                                create_tab_content(st_rc.clone(), idx);
-                                            crate::ui::add_closable_tab(
-                                                &nb,
-                                                &container,
-                                                &filename,
-                                                st_rc.clone(),
-                                            );
-                                            container.show();
-
-                                            if let Some(w) = win_weak_inner.upgrade() {
-                                                crate::ui::setup_interactions(
-                                                    &w,
-                                                    st_rc.clone(),
-                                                    &new_da,
-                                                    handles_inner.clone(),
-                                                );
-                                            }
-                                            nb.set_current_page(Some(idx as u32));
-                                        }
-                                    }
-
-                                    // Refresh sidebar & log
-                                    if let (Some(nb), Some(ab)) =
-                                        (nb_inner.upgrade(), atom_box_inner.upgrade())
-                                    {
-                                        crate::panels::sidebar::refresh_atom_list(
-                                            &ab,
-                                            st_rc.clone(),
-                                            &nb,
-                                        );
-                                    }
-
-                                    console::log_info(&format!("Loaded: {}", filename));
-
-                                    let s = st_rc.borrow();
-                                    let tab = s.active_tab();
-                                    if let Some(strc) = &tab.structure {
-                                        let report_text =
-                                            report::structure_summary(strc, &filename);
-                                        console::info_report(&report_text);
-                                    }
-                                }
-                                Err(e) => {
-                                    console::log_error(&format!(
-                                        "Error loading '{}': {}",
-                                        filename, e
-                                    ));
-                                }
-                            }
+                            open_structure_file(&st_rc, path, &target);
                         }
                     }
                 }
@@ -260,10 +168,34 @@ pub fn setup(
     });
     app.add_action(&open_action);
 
+    // --- IMPORT FROM MATERIALS PROJECT ---
+    #[cfg(feature = "materials-project")]
+    {
+        let act_mp = gtk4::gio::SimpleAction::new("import_mp", None);
+        let win_weak_mp = window.downgrade();
+        let state_weak_mp = Rc::downgrade(&state);
+        let target = OpenTarget {
+            notebook: notebook.downgrade(),
+            drawing_area: drawing_area.downgrade(),
+            atom_box: atom_list_box.downgrade(),
+            window: window.downgrade(),
+            handles: sidebar_handles.clone(),
+        };
+
+        act_mp.connect_activate(move |_, _| {
+            let (Some(win), Some(state)) = (win_weak_mp.upgrade(), state_weak_mp.upgrade()) else {
+                return;
+            };
+            show_mp_import_dialog(&win, state, target.clone());
+        });
+        app.add_action(&act_mp);
+    }
+
     // --- SAVE AS ---
     let act_save = gtk4::gio::SimpleAction::new("save_as", None);
     let win_weak_s = window.downgrade();
     let state_weak_s = Rc::downgrade(&state);
+    let nb_weak_s = notebook.downgrade();
 
     act_save.connect_activate(move |_, _| {
         let win = match win_weak_s.upgrade() {
@@ -279,48 +211,41 @@ pub fn setup(
             Some("Cancel"),
         );
 
-        let f_cif = FileFilter::new();
-        f_cif.set_name(Some("CIF File (*.cif)"));
-        f_cif.add_pattern("*.cif");
-        dialog.add_filter(&f_cif);
-
-        let f_vasp = FileFilter::new();
-        f_vasp.set_name(Some("VASP POSCAR (*.vasp)"));
-        f_vasp.add_pattern("POSCAR");
-        f_vasp.add_pattern("*.vasp");
-        dialog.add_filter(&f_vasp);
-
-        let f_pot = FileFilter::new();
-        f_pot.set_name(Some("SPR-KKR Potential (*.pot)"));
-        f_pot.add_pattern("*.pot");
-        dialog.add_filter(&f_pot);
-
-        let f_qe = FileFilter::new();
-        f_qe.set_name(Some("Quantum ESPRESSO Input (*.in)"));
-        f_qe.add_pattern("*.in");
-        f_qe.add_pattern("*.qe");
-        dialog.add_filter(&f_qe);
-
-        let f_xyz = FileFilter::new();
-        f_xyz.set_name(Some("XYZ File (*.xyz)"));
-        f_xyz.add_pattern("*.xyz");
-        dialog.add_filter(&f_xyz);
-
-        dialog.set_current_name("structure.cif");
+        if let Some(st) = state_weak_s.upgrade() {
+            let st = st.borrow();
+            let format = st.config.default_save_format;
+            add_save_filters(&dialog, format);
+            dialog.set_current_name(&format.file_name("structure"));
+            crate::ui::start_in_recent_dir(&dialog, &st.config, RecentDir::Save);
+        }
+        dialog.add_choice(
+            "reduce_symmetry",
+            "Reduce symmetry (CIF: asymmetric unit + space group)",
+            &[],
+        );
 
         let state_inner = state_weak_s.clone();
+        let nb_inner = nb_weak_s.clone();
         dialog.connect_response(move |d, r| {
             if r == ResponseType::Accept {
                 if let Some(f) = d.file() {
                     if let Some(p) = f.path() {
                         if let Some(st) = state_inner.upgrade() {
+                            let mut saved = false;
                             let s = st.borrow();
                             if !s.tabs.is_empty() {
                                 if let Some(strc) = &s.active_tab().structure {
                                     let path_str = p.to_string_lossy();
-                                    match io::save_structure(&path_str, strc) {
+                                    let options = io::SaveOptions {
+                                        standard_orientation: s.config.standard_orientation_on_save,
+                                        reduce_symmetry: d
+                                            .choice("reduce_symmetry")
+                                            .is_some_and(|v| v == "true"),
+                                    };
+                                    match io::save_structure_with(&path_str, strc, options) {
                                         Ok(_) => {
                                             console::log_info(&format!("Saved to {}", path_str));
+                                            saved = true;
                                         }
                                         Err(e) => {
                                             console::log_error(&format!("Error saving: {}", e));
@@ -328,6 +253,15 @@ pub fn setup(
                                     }
                                 }
                             }
+                            drop(s);
+
+                            if saved {
+                                crate::ui::remember_recent_dir(&st, RecentDir::Save, &p);
+                                st.borrow_mut().active_tab_mut().modified = false;
+                                if let Some(nb) = nb_inner.upgrade() {
+                                    crate::ui::refresh_tab_labels(&nb, &st.borrow());
+                                }
+                            }
                         }
                     }
                 }
@@ -338,6 +272,18 @@ pub fn setup(
     });
     app.add_action(&act_save);
 
+    // --- EXPORT SELECTION ---
+    let act_export_sel = gtk4::gio::SimpleAction::new("export_selection", None);
+    let win_weak_x = window.downgrade();
+    let state_weak_x = Rc::downgrade(&state);
+    act_export_sel.connect_activate(move |_, _| {
+        let (Some(win), Some(st)) = (win_weak_x.upgrade(), state_weak_x.upgrade()) else {
+            return;
+        };
+        export_selection(&win, &st);
+    });
+    app.add_action(&act_export_sel);
+
     // --- EXPORT ACTION (ADVANCED DIALOG) ---
     let act_export = gtk4::gio::SimpleAction::new("export", None);
     let win_weak_e = window.downgrade();
@@ -358,10 +304,82 @@ pub fn setup(
             return;
         }
 
-        crate::ui::export_dialog::show_export_dialog(&win, state);
+        crate::ui::export_dialog::show_export_dialog(&win, state, false);
     });
     app.add_action(&act_export);
 
+    // --- EXPORT ALL TABS (same dialog, folder target) ---
+    let act_export_all = gtk4::gio::SimpleAction::new("export_all", None);
+    let win_weak_a = window.downgrade();
+    let state_weak_a = Rc::downgrade(&state);
+
+    act_export_all.connect_activate(move |_, _| {
+        let (Some(win), Some(state)) = (win_weak_a.upgrade(), state_weak_a.upgrade()) else {
+            return;
+        };
+        if state.borrow().tabs.is_empty() {
+            return;
+        }
+        crate::ui::export_dialog::show_export_dialog(&win, state, true);
+    });
+    app.add_action(&act_export_all);
+
+    // --- EXPORT DATA (CSV) ---
+    let act_csv = gtk4::gio::SimpleAction::new("export_csv", None);
+    let win_weak_c = window.downgrade();
+    let state_weak_c = Rc::downgrade(&state);
+
+    act_csv.connect_activate(move |_, _| {
+        let win = match win_weak_c.upgrade() {
+            Some(w) => w,
+            None => return,
+        };
+
+        let dialog = FileChooserNative::new(
+            Some("Export Atom Data"),
+            Some(&win),
+            FileChooserAction::Save,
+            Some("Export"),
+            Some("Cancel"),
+        );
+
+        let f_csv = FileFilter::new();
+        f_csv.set_name(Some("CSV Table (*.csv)"));
+        f_csv.add_pattern("*.csv");
+        dialog.add_filter(&f_csv);
+        dialog.set_current_name("atoms.csv");
+        if let Some(st) = state_weak_c.upgrade() {
+            crate::ui::start_in_recent_dir(&dialog, &st.borrow().config, RecentDir::Export);
+        }
+        // Boolean choice: no options list.
+        dialog.add_choice("bonds", "Also write bond list (<name>_bonds.csv)", &[]);
+        dialog.set_choice("bonds", "true");
+
+        let state_inner = state_weak_c.clone();
+        dialog.connect_response(move |d, r| {
+            if r == ResponseType::Accept {
+                let path = d.file().and_then(|f| f.path());
+                if let (Some(path), Some(st)) = (path, state_inner.upgrade()) {
+                    let with_bonds = d.choice("bonds").is_some_and(|v| v == "true");
+                    let s = st.borrow();
+                    if !s.tabs.is_empty() {
+                        let tab = s.active_tab();
+                        if let Some(strc) = &tab.structure {
+                            let bonds = with_bonds
+                                .then(|| crate::rendering::bonds::structure_bonds(tab, &s.config));
+                            export_csv(&path, strc, bonds);
+                        }
+                    }
+                    drop(s);
+                    crate::ui::remember_recent_dir(&st, RecentDir::Export, &path);
+                }
+            }
+            d.destroy();
+        });
+        dialog.show();
+    });
+    app.add_action(&act_csv);
+
     // --- PREFS & QUIT ---
     let act_pref = gtk4::gio::SimpleAction::new("preferences", None);
     let win_weak_p = window.downgrade();
@@ -388,3 +406,764 @@ pub fn setup(
     });
     app.add_action(&act_quit);
 }
+
+/// Save the selected atoms as a structure of their own, optionally
+/// dropping the cell so a cluster carved from a crystal saves as a molecule.
+fn export_selection(win: &ApplicationWindow, state: &Rc<RefCell<AppState>>) {
+    let (picks, periodic, format) = {
+        let st = state.borrow();
+        let tab = st.active_tab();
+        let Some(structure) = &tab.structure else {
+            return;
+        };
+        // Selection order isn't kept; sort so the output is reproducible.
+        let mut picks: Vec<_> = tab.interaction.selected.values().collect();
+        picks.sort_by_key(|a| a.unique_id);
+        let picks: Vec<(usize, [f64; 3])> = picks
+            .iter()
+            .map(|a| (a.original_index, a.cart_pos))
+            .collect();
+        (picks, structure.is_periodic, st.config.default_save_format)
+    };
+    if picks.is_empty() {
+        console::log_warn("Export Selection: no atoms selected.");
+        return;
+    }
+
+    let dialog = FileChooserNative::new(
+        Some("Export Selection"),
+        Some(win),
+        FileChooserAction::Save,
+        Some("Save"),
+        Some("Cancel"),
+    );
+    add_save_filters(&dialog, format);
+    dialog.set_current_name(&format.file_name("selection"));
+    crate::ui::start_in_recent_dir(&dialog, &state.borrow().config, RecentDir::Save);
+    if periodic {
+        dialog.add_choice("keep_cell", "Keep the unit cell (periodic)", &[]);
+        dialog.set_choice("keep_cell", "false");
+    }
+
+    let state_weak = Rc::downgrade(state);
+    dialog.connect_response(move |d, r| {
+        if r == ResponseType::Accept {
+            if let (Some(p), Some(st)) = (d.file().and_then(|f| f.path()), state_weak.upgrade()) {
+                let s = st.borrow();
+                if let Some(structure) = &s.active_tab().structure {
+                    let keep_cell = d.choice("keep_cell").is_some_and(|v| v == "true");
+                    let sub = structure.extract(&picks, keep_cell);
+                    let path_str = p.to_string_lossy();
+                    let options = io::SaveOptions {
+                        standard_orientation: s.config.standard_orientation_on_save,
+                        reduce_symmetry: false,
+                    };
+                    match io::save_structure_with(&path_str, &sub, options) {
+                        Ok(_) => {
+                            console::log_info(&format!(
+                                "Exported {} selected atom(s) to {}",
+                                sub.atoms.len(),
+                                path_str
+                            ));
+                            drop(s);
+                            crate::ui::remember_recent_dir(&st, RecentDir::Save, &p);
+                        }
+                        Err(e) => console::log_error(&format!("Error saving: {}", e)),
+                    }
+                }
+            }
+        }
+        d.destroy();
+    });
+    dialog.show();
+}
+
+/// File-type filters for the structure writers, shared by Save As and
+/// Export Selection, with the one for `preferred` selected. The writer is
+/// picked from the file name.
+fn add_save_filters(dialog: &FileChooserNative, preferred: SaveFormat) {
+    let f_cif = FileFilter::new();
+    f_cif.set_name(Some("CIF File (*.cif)"));
+    f_cif.add_pattern("*.cif");
+    dialog.add_filter(&f_cif);
+
+    let f_vasp = FileFilter::new();
+    f_vasp.set_name(Some("VASP POSCAR (*.vasp)"));
+    f_vasp.add_pattern("POSCAR");
+    f_vasp.add_pattern("*.vasp");
+    dialog.add_filter(&f_vasp);
+
+    let f_pot = FileFilter::new();
+    f_pot.set_name(Some("SPR-KKR Potential (*.pot)"));
+    f_pot.add_pattern("*.pot");
+    dialog.add_filter(&f_pot);
+
+    let f_qe = FileFilter::new();
+    f_qe.set_name(Some("Quantum ESPRESSO Input (*.in)"));
+    f_qe.add_pattern("*.in");
+    f_qe.add_pattern("*.qe");
+    dialog.add_filter(&f_qe);
+
+    let f_castep = FileFilter::new();
+    f_castep.set_name(Some("CASTEP Cell (*.cell)"));
+    f_castep.add_pattern("*.cell");
+    dialog.add_filter(&f_castep);
+
+    let f_xyz = FileFilter::new();
+    f_xyz.set_name(Some("XYZ File (*.xyz)"));
+    f_xyz.add_pattern("*.xyz");
+    dialog.add_filter(&f_xyz);
+
+    // Same order as `SaveFormat::ALL`.
+    let filters = [f_cif, f_vasp, f_pot, f_qe, f_castep, f_xyz];
+    dialog.set_filter(&filters[preferred.index()]);
+}
+
+/// Write the atom table to `path` and, when given, the bond list next to it
+/// as `<stem>_bonds.csv`. Results go to the console log.
+fn export_csv(
+    path: &std::path::Path,
+    structure: &crate::model::Structure,
+    bonds: Option<Vec<(usize, usize, f64)>>,
+) {
+    let path_str = path.to_string_lossy();
+    match io::csv::write_atoms(&path_str, structure) {
+        Ok(_) => console::log_info(&format!(
+            "Exported {} atoms to {}",
+            structure.atoms.len(),
+            path_str
+        )),
+        Err(e) => {
+            console::log_error(&format!("Error exporting atoms: {}", e));
+            return;
+        }
+    }
+
+    let Some(bonds) = bonds else { return };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let bonds_path = path.with_file_name(format!("{}_bonds.csv", stem));
+    let bonds_str = bonds_path.to_string_lossy();
+    match io::csv::write_bonds(&bonds_str, structure, &bonds) {
+        Ok(_) => console::log_info(&format!("Exported {} bonds to {}", bonds.len(), bonds_str)),
+        Err(e) => console::log_error(&format!("Error exporting bonds: {}", e)),
+    }
+}
+
+/// Weak handles the open paths need to put a freshly loaded structure on
+/// screen.
+#[derive(Clone)]
+struct OpenTarget {
+    notebook: WeakRef<Notebook>,
+    drawing_area: WeakRef<DrawingArea>,
+    atom_box: WeakRef<gtk4::Box>,
+    window: WeakRef<ApplicationWindow>,
+    handles: Rc<SidebarHandles>,
+}
+
+/// Open the file named on the command line the way File → Open would:
+/// large files parse in the background, failures get the error dialog.
+pub fn open_from_cli(
+    window: &ApplicationWindow,
+    state: &Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+    drawing_area: &DrawingArea,
+    atom_list_box: &gtk4::Box,
+    sidebar_handles: Rc<SidebarHandles>,
+    path: PathBuf,
+) {
+    console::log_info(&format!("CLI: Opening '{}'", path.display()));
+    let target = OpenTarget {
+        notebook: notebook.downgrade(),
+        drawing_area: drawing_area.downgrade(),
+        atom_box: atom_list_box.downgrade(),
+        window: window.downgrade(),
+        handles: sidebar_handles,
+    };
+    open_structure_file(state, path, &target);
+}
+
+/// Parse `path` and put it on screen. Files that `io::is_large_file` flags
+/// (header atom count above `max_atoms_display`, or just big on disk) parse
+/// on a worker thread behind a spinner so the window keeps redrawing;
+/// everything else loads inline.
+fn open_structure_file(st_rc: &Rc<RefCell<AppState>>, path: PathBuf, target: &OpenTarget) {
+    use gtk4::glib;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let path_str = path.to_string_lossy().to_string();
+    let filename = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    let (max_atoms, auto_detect) = {
+        let st = st_rc.borrow();
+        (st.config.max_atoms_display, st.config.auto_detect_format)
+    };
+    if !io::is_large_file(&path_str, max_atoms) {
+        let result = io::load_frames_with(&path_str, auto_detect);
+        finish_open(st_rc, &path, filename, result, target);
+        return;
+    }
+
+    let what = match io::estimate_atom_count(&path_str) {
+        Some(n) => format!("{} (~{} atoms)", filename, n),
+        None => filename.clone(),
+    };
+    console::log_info(&format!("Parsing large file {} in the background…", what));
+    let busy = show_busy_window(
+        target.window.upgrade().as_ref(),
+        &format!("Reading {}…", what),
+    );
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(io::load_frames_with(&path_str, auto_detect));
+    });
+
+    let state_weak = Rc::downgrade(st_rc);
+    let target = target.clone();
+    glib::timeout_add_local(Duration::from_millis(100), move || match rx.try_recv() {
+        Ok(result) => {
+            busy.close();
+            if let Some(st) = state_weak.upgrade() {
+                finish_open(&st, &path, filename.clone(), result, &target);
+            }
+            glib::ControlFlow::Break
+        }
+        Err(mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(mpsc::TryRecvError::Disconnected) => {
+            busy.close();
+            console::log_error(&format!("Parser stopped unexpectedly on '{}'", filename));
+            glib::ControlFlow::Break
+        }
+    });
+}
+
+/// Second half of `open_structure_file`, on the main thread once parsing is
+/// done.
+fn finish_open(
+    st_rc: &Rc<RefCell<AppState>>,
+    path: &Path,
+    filename: String,
+    result: std::io::Result<Vec<Structure>>,
+    target: &OpenTarget,
+) {
+    match result {
+        Ok(frames) => {
+            // The first frame is shown; a trajectory keeps the rest for the
+            // sidebar's frame slider.
+            show_loaded_structure(st_rc, frames[0].clone(), filename, target);
+            let mut s = st_rc.borrow_mut();
+            let tab = s.active_tab_mut();
+            tab.set_frames(frames);
+            if !tab.frames.is_empty() {
+                console::log_info(&format!("Trajectory with {} frames", tab.frames.len()));
+            }
+            target.handles.sync_frames(tab);
+            drop(s);
+            crate::ui::remember_recent_dir(st_rc, RecentDir::Open, path);
+        }
+        Err(e) => {
+            console::log_error(&format!("Error loading '{}': {}", filename, e));
+            // The log panel may be collapsed; make sure the
+            // user actually sees why nothing was opened.
+            let win = target.window.upgrade();
+            show_load_error(win.as_ref(), &filename, &e);
+        }
+    }
+}
+
+/// Small modal window with a spinner and `text`, closed by the caller.
+fn show_busy_window(parent: Option<&ApplicationWindow>, text: &str) -> gtk4::Window {
+    let busy = gtk4::Window::builder()
+        .title("Opening")
+        .modal(true)
+        .resizable(false)
+        .deletable(false)
+        .build();
+    if let Some(p) = parent {
+        busy.set_transient_for(Some(p));
+    }
+    let row = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+    row.set_margin_top(20);
+    row.set_margin_bottom(20);
+    row.set_margin_start(20);
+    row.set_margin_end(20);
+    let spinner = gtk4::Spinner::new();
+    spinner.start();
+    row.append(&spinner);
+    row.append(&Label::new(Some(text)));
+    busy.set_child(Some(&row));
+    busy.present();
+    busy
+}
+
+/// Show `structure` as `filename`: reuse an empty "Untitled" tab or open a
+/// new one, refresh the sidebar, then run the post-load checks and reports.
+fn show_loaded_structure(
+    st_rc: &Rc<RefCell<AppState>>,
+    structure: Structure,
+    filename: String,
+    target: &OpenTarget,
+) {
+    let mut new_tab_index: Option<usize> = None;
+    let mut replace_current_tab = false;
+    let n_atoms = structure.atoms.len();
+
+    {
+        let mut s = st_rc.borrow_mut();
+        let is_replace_mode = if s.tabs.is_empty() {
+            false
+        } else {
+            let t = s.active_tab();
+            t.structure.is_none() && t.file_name == "Untitled"
+        };
+
+        if is_replace_mode {
+            let tab = s.active_tab_mut();
+            tab.original_structure = Some(structure.clone());
+            tab.structure = Some(structure);
+            tab.file_name = filename.clone();
+            // Replacing the structure in-place must reset
+            // every per-tab piece of state that referred
+            // to the previous structure — otherwise old
+            // selections appear as "pre-highlighted"
+            // atoms on the new one.
+            tab.interaction.selected.clear();
+            tab.interaction.undo_stack.clear();
+            tab.miller_planes.clear();
+            tab.kpath_result = None;
+            tab.void_result = None;
+            tab.displacement.clear();
+            tab.invalidate_bvs_cache();
+            tab.modified = false;
+            replace_current_tab = true;
+        } else {
+            s.add_tab(structure, filename.clone());
+            new_tab_index = Some(s.tabs.len() - 1);
+        }
+
+        // Bonds and boundary images multiply the work of every frame; start
+        // a very large structure without them.
+        let max_atoms = s.config.max_atoms_display;
+        if n_atoms > max_atoms {
+            let view = &mut s.active_tab_mut().view;
+            view.show_bonds = false;
+            view.show_full_unit_cell = false;
+            console::log_warn(&format!(
+                "{} atoms exceed the large-structure threshold ({}): bonds and \
+                 boundary images start off",
+                n_atoms, max_atoms
+            ));
+        }
+    }
+
+    if let Some(nb) = target.notebook.upgrade() {
+        if replace_current_tab {
+            if let Some(page) = nb.nth_page(nb.current_page()) {
+                if let Some(lbl_box) = nb.tab_label(&page) {
+                    if let Some(bx) = lbl_box.downcast_ref::<gtk4::Box>() {
+                        if let Some(first_child) = bx.first_child() {
+                            if let Some(l) = first_child.downcast_ref::<Label>() {
+                                l.set_text(&filename);
+                            }
+                        }
+                    } else if let Some(l) = lbl_box.downcast_ref::<Label>() {
+                        l.set_text(&filename);
+                    }
+                }
+            }
+            if let Some(da) = target.drawing_area.upgrade() {
+                da.queue_draw();
+            }
+        } else if let Some(idx) = new_tab_index {
+            let (new_da, container) = create_tab_content(st_rc.clone(), idx);
+            crate::ui::add_closable_tab(&nb, &container, &filename, st_rc.clone());
+            container.show();
+
+            if let Some(w) = target.window.upgrade() {
+                crate::ui::setup_interactions(&w, st_rc.clone(), &new_da, target.handles.clone());
+            }
+            nb.set_current_page(Some(idx as u32));
+        }
+    }
+
+    // Refresh sidebar & log
+    if let (Some(nb), Some(ab)) = (target.notebook.upgrade(), target.atom_box.upgrade()) {
+        crate::panels::sidebar::refresh_atom_list(&ab, st_rc.clone(), &nb);
+    }
+
+    console::log_info(&format!("Loaded: {}", filename));
+
+    if let (Some(nb), Some(ab)) = (target.notebook.upgrade(), target.atom_box.upgrade()) {
+        let win = target.window.upgrade();
+        check_overlaps(win.as_ref(), st_rc.clone(), &nb, &ab);
+    }
+
+    let s = st_rc.borrow();
+    let tab = s.active_tab();
+    if let Some(strc) = &tab.structure {
+        let report_text = report::structure_summary(strc, &filename, &s.config.report_format);
+        console::info_report(&report_text);
+        warn_if_not_neutral(strc);
+    }
+    drop(s);
+
+    let win = target.window.upgrade();
+    auto_bvs(win.as_ref(), st_rc);
+}
+
+/// Console warning for a periodic cell whose oxidation states do not sum
+/// to zero. The Structure Info summary carries the details.
+fn warn_if_not_neutral(structure: &Structure) {
+    if !structure.is_periodic {
+        return;
+    }
+    let balance = charge_balance(structure);
+    if !balance.is_neutral() {
+        console::log_warn(&format!(
+            "Cell is not charge-neutral (net {:+.2} e) — see Structure Info",
+            balance.net
+        ));
+    }
+}
+
+/// Modal error dialog for a file that failed to parse. Companion to the
+/// console log entry, which stays the persistent record.
+fn show_load_error(parent: Option<&ApplicationWindow>, filename: &str, err: &std::io::Error) {
+    let dialog = gtk4::MessageDialog::new(
+        parent,
+        gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+        gtk4::MessageType::Error,
+        gtk4::ButtonsType::Close,
+        format!("Could not open '{}'", filename),
+    );
+    dialog.set_title(Some("Open Failed"));
+    dialog.set_secondary_text(Some(&err.to_string()));
+    dialog.connect_response(|d, _| d.close());
+    dialog.present();
+}
+
+/// Prompt for a Materials Project ID and API key, fetch the structure on a
+/// worker thread and open it like a file named after the ID. The key is
+/// written to the config when the import starts.
+#[cfg(feature = "materials-project")]
+fn show_mp_import_dialog(
+    win: &ApplicationWindow,
+    state: Rc<RefCell<AppState>>,
+    target: OpenTarget,
+) {
+    use crate::io::materials_project;
+    use gtk4::{glib, Dialog, Entry, Grid, PasswordEntry};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let dialog = Dialog::builder()
+        .title("Import from Materials Project")
+        .transient_for(win)
+        .modal(true)
+        .default_width(380)
+        .build();
+
+    let content = dialog.content_area();
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_spacing(10);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(10);
+
+    let id_entry = Entry::new();
+    id_entry.set_placeholder_text(Some("mp-149"));
+    id_entry.set_hexpand(true);
+    id_entry.set_activates_default(true);
+    let key_entry = PasswordEntry::new();
+    key_entry.set_show_peek_icon(true);
+    key_entry.set_text(&state.borrow().config.materials_project_api_key);
+
+    let lbl_id = Label::new(Some("Material ID:"));
+    lbl_id.set_xalign(0.0);
+    let lbl_key = Label::new(Some("API key:"));
+    lbl_key.set_xalign(0.0);
+    grid.attach(&lbl_id, 0, 0, 1, 1);
+    grid.attach(&id_entry, 1, 0, 1, 1);
+    grid.attach(&lbl_key, 0, 1, 1, 1);
+    grid.attach(&key_entry, 1, 1, 1, 1);
+    content.append(&grid);
+
+    let status = Label::new(Some(
+        "The key is saved unencrypted in config.json with your preferences.",
+    ));
+    status.set_xalign(0.0);
+    status.add_css_class("dim-label");
+    content.append(&status);
+
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    let btn_import = dialog.add_button("Import", ResponseType::Accept);
+    btn_import.add_css_class("suggested-action");
+    dialog.set_default_response(ResponseType::Accept);
+
+    let state_weak = Rc::downgrade(&state);
+    dialog.connect_response(move |dlg, resp| {
+        if resp != ResponseType::Accept {
+            dlg.close();
+            return;
+        }
+        let Some(st) = state_weak.upgrade() else {
+            return;
+        };
+
+        let id = match materials_project::normalize_id(&id_entry.text()) {
+            Ok(id) => id,
+            Err(e) => {
+                status.set_text(&e.to_string());
+                return;
+            }
+        };
+        let api_key = key_entry.text().trim().to_string();
+        {
+            let mut s = st.borrow_mut();
+            if s.config.materials_project_api_key != api_key {
+                s.config.materials_project_api_key = api_key.clone();
+                s.save_config();
+            }
+        }
+
+        btn_import.set_sensitive(false);
+        status.set_text(&format!("Fetching {}…", id));
+
+        let (tx, rx) = mpsc::channel();
+        let worker_id = id.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(materials_project::fetch(&worker_id, &api_key));
+        });
+
+        let dlg = dlg.clone();
+        let btn = btn_import.clone();
+        let status = status.clone();
+        let target = target.clone();
+        let state_weak = Rc::downgrade(&st);
+        glib::timeout_add_local(Duration::from_millis(100), move || match rx.try_recv() {
+            Ok(Ok(structure)) => {
+                dlg.close();
+                if let Some(st) = state_weak.upgrade() {
+                    show_loaded_structure(&st, structure, id.clone(), &target);
+                }
+                glib::ControlFlow::Break
+            }
+            Ok(Err(e)) => {
+                console::log_error(&format!(
+                    "Materials Project import of '{}' failed: {}",
+                    id, e
+                ));
+                btn.set_sensitive(true);
+                status.set_text("Import failed.");
+                show_mp_error(&dlg, &id, &e);
+                glib::ControlFlow::Break
+            }
+            Err(mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                btn.set_sensitive(true);
+                status.set_text("Import failed.");
+                glib::ControlFlow::Break
+            }
+        });
+    });
+
+    dialog.present();
+}
+
+#[cfg(feature = "materials-project")]
+fn show_mp_error(
+    parent: &impl IsA<gtk4::Window>,
+    id: &str,
+    err: &crate::io::materials_project::MpError,
+) {
+    let dialog = gtk4::MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+        gtk4::MessageType::Error,
+        gtk4::ButtonsType::Close,
+        &format!("Could not import '{}'", id),
+    );
+    dialog.set_title(Some("Import Failed"));
+    dialog.set_secondary_text(Some(&err.to_string()));
+    dialog.connect_response(|d, _| d.close());
+    dialog.present();
+}
+
+/// GII above which `warn_poor_bvs` pops a dialog: past the "Good" band
+/// (< 0.20 v.u.) the structure is strained or the oxidation states are off.
+const POOR_BVS_GII: f64 = 0.20;
+
+/// Post-load BVS pass, gated on `auto_calc_bvs`: fills the tab's BVS cache,
+/// appends the report to Structure Info (`show_bvs_report`) and warns when
+/// the GII is high (`warn_poor_bvs`).
+fn auto_bvs(parent: Option<&ApplicationWindow>, state: &Rc<RefCell<AppState>>) {
+    let (report_text, warning) = {
+        let mut s = state.borrow_mut();
+        let (auto, show, warn) = (
+            s.config.auto_calc_bvs,
+            s.config.show_bvs_report,
+            s.config.warn_poor_bvs,
+        );
+        let fmt = s.config.report_format.clone();
+        if !auto {
+            return;
+        }
+        let tab = s.active_tab_mut();
+        let Some(strc) = &tab.structure else {
+            return;
+        };
+        // The sidebar's per-element states count here too.
+        let strc = with_oxidation_overrides(strc, &tab.style.bvs_oxidation_overrides);
+        let result = analyze_structure(&strc);
+        let report_text = show.then(|| report::bvs_analysis_tagged(&strc, &fmt));
+        tab.invalidate_bvs_cache();
+        let _ = tab.get_bvs_values();
+
+        let warning = (warn && result.validated > 0 && result.gii >= POOR_BVS_GII).then(|| {
+            let quality = BVSQuality::from_deviation(result.gii);
+            format!(
+                "GII = {:.3} v.u. ({}), worst |ΔV| = {:.3} v.u. over {} atom(s) with known \
+                 oxidation states.\n\nCheck the oxidation states, missing atoms (H, O) \
+                 or duplicated sites.",
+                result.gii,
+                quality.as_str(),
+                result.max_abs_dev,
+                result.validated
+            )
+        });
+        (report_text, warning)
+    };
+
+    if let Some((text, tagged)) = report_text {
+        console::info_report_tagged(&text, &tagged);
+    }
+    let Some(detail) = warning else {
+        return;
+    };
+    console::log_warn(&format!(
+        "Poor bond valence sums: {}",
+        detail.replace('\n', " ")
+    ));
+
+    let dialog = gtk4::MessageDialog::new(
+        parent,
+        gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+        gtk4::MessageType::Warning,
+        gtk4::ButtonsType::Close,
+        "Bond valence sums indicate a poor structure",
+    );
+    dialog.set_title(Some("Bond Valence Check"));
+    dialog.set_secondary_text(Some(&detail));
+    dialog.connect_response(|d, _| d.close());
+    dialog.present();
+}
+
+/// Post-load sanity check: atoms closer than `OVERLAP_DIST` are almost
+/// always a duplicated symmetry image (the classic CIF double-count) and
+/// skew BVS and coordination. Log them and offer to drop the duplicates.
+fn check_overlaps(
+    parent: Option<&ApplicationWindow>,
+    state: Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+    atom_box: &gtk4::Box,
+) {
+    let pairs = {
+        let s = state.borrow();
+        let strc = match &s.active_tab().structure {
+            Some(strc) => strc,
+            None => return,
+        };
+        if strc.atoms.len() > OVERLAP_CHECK_MAX_ATOMS {
+            console::log_info(&format!(
+                "Overlap check skipped ({} atoms > {})",
+                strc.atoms.len(),
+                OVERLAP_CHECK_MAX_ATOMS
+            ));
+            return;
+        }
+        strc.find_overlaps(OVERLAP_DIST)
+    };
+    if pairs.is_empty() {
+        return;
+    }
+
+    let listed: Vec<String> = pairs
+        .iter()
+        .take(10)
+        .map(|(i, j, d)| format!("#{}–#{} ({:.3} Å)", i, j, d))
+        .collect();
+    let more = if pairs.len() > 10 {
+        format!(" … and {} more", pairs.len() - 10)
+    } else {
+        String::new()
+    };
+    console::log_warn(&format!(
+        "{} overlapping atom pair(s) closer than {} Å: {}{}",
+        pairs.len(),
+        OVERLAP_DIST,
+        listed.join(", "),
+        more
+    ));
+
+    let dialog = gtk4::MessageDialog::new(
+        parent,
+        gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+        gtk4::MessageType::Warning,
+        gtk4::ButtonsType::YesNo,
+        format!("{} overlapping atom pair(s) found", pairs.len()),
+    );
+    dialog.set_title(Some("Overlapping Atoms"));
+    dialog.set_secondary_text(Some(&format!(
+        "Atoms closer than {} Å usually mean a duplicated symmetry image.\n\
+         {}{}\n\nRemove the duplicates (keeps the lower-numbered atom of each pair)?",
+        OVERLAP_DIST,
+        listed.join("\n"),
+        more
+    )));
+
+    let nb_weak = notebook.downgrade();
+    let ab_weak = atom_box.downgrade();
+    dialog.connect_response(move |d, resp| {
+        if resp == ResponseType::Yes {
+            let removed = {
+                let mut s = state.borrow_mut();
+                let tab = s.active_tab_mut();
+                let mut removed = 0;
+                if let Some(strc) = tab.structure.as_mut() {
+                    removed = strc.merge_overlaps(OVERLAP_DIST);
+                }
+                if let Some(orig) = tab.original_structure.as_mut() {
+                    orig.merge_overlaps(OVERLAP_DIST);
+                }
+                // Per-atom state is index-keyed and the indices just shifted.
+                tab.interaction.selected.clear();
+                tab.interaction.undo_stack.clear();
+                tab.overrides.clear();
+                tab.invalidate_bvs_cache();
+                tab.modified = true;
+                tab.displacement.clear();
+                removed
+            };
+            console::log_info(&format!("Removed {} duplicate atom(s)", removed));
+
+            if let (Some(nb), Some(ab)) = (nb_weak.upgrade(), ab_weak.upgrade()) {
+                crate::panels::sidebar::refresh_atom_list(&ab, state.clone(), &nb);
+                if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                    da.queue_draw();
+                }
+                crate::ui::refresh_tab_labels(&nb, &state.borrow());
+            }
+        }
+        d.close();
+    });
+    dialog.present();
+}
@@ -2,6 +2,41 @@
 
 use adw::prelude::*;
 use gtk4::{Application, ApplicationWindow, ButtonsType, MessageDialog, MessageType};
+use std::collections::HashMap;
+
+/// Literature behind the bundled data tables (see the doc comments in
+/// `model::elements`, `model::bvs` and `physics::bond_valence`), shown on the
+/// About dialog's legal page for citing in methods sections.
+const DATA_SOURCES: &str = "\
+Ionic radii: R.D. Shannon, Acta Cryst. A32, 751-767 (1976). doi:10.1107/S056773947600155X
+
+X-ray form factors (Cromer-Mann): International Tables for Crystallography, Vol. C, Table 6.1.1.4 (1992).
+
+Van der Waals radii: S. Alvarez, Dalton Trans. 42, 8617-8636 (2013). doi:10.1039/C3DT50599E
+
+Covalent radii: B. Cordero et al., Dalton Trans. 2832-2838 (2008). doi:10.1039/B801115J
+
+Neutron scattering lengths: V.F. Sears, Neutron News 3(3), 26-37 (1992).
+
+Bond valence parameters: IUCr bvparm2020.cif; I.D. Brown & D. Altermatt, Acta Cryst. B41, 244-247 (1985); N.E. Brese & M. O'Keeffe, Acta Cryst. B47, 192-197 (1991).
+
+Bond valence estimation: M. O'Keeffe & N.E. Brese, J. Am. Chem. Soc. 113, 3226-3229 (1991).
+
+Global instability index: I.D. Brown, The Chemical Bond in Inorganic Chemistry: The Bond Valence Model, IUCr Monograph 12, OUP (2002).";
+
+/// Canvas bindings handled by the key/mouse controllers in
+/// `ui::interactions` rather than by application actions.
+const CANVAS_BINDINGS: [(&str, &str); 9] = [
+    ("Left Drag", "Rotate view"),
+    ("Right Drag", "Pan view"),
+    ("Scroll", "Zoom in/out"),
+    ("Click", "Select atom"),
+    ("Shift + Drag", "Box-select atoms"),
+    ("Delete", "Delete selected atoms"),
+    ("Ctrl + Z", "Undo"),
+    ("M", "Toggle measure mode"),
+    ("Esc", "Clear measure-mode picks"),
+];
 
 pub fn setup(app: &Application, window: &ApplicationWindow) {
     // --- 1. CONTROLS ACTION ---
@@ -18,16 +53,10 @@ pub fn setup(app: &Application, window: &ApplicationWindow) {
 • <b>Left Click + Drag:</b> Rotate View
 • <b>Right Click + Drag:</b> Pan View
 • <b>Scroll Wheel:</b> Zoom In/Out
+• <b>Shift + Drag:</b> Box-select atoms
 
 <b>Keyboard Shortcuts:</b>
-• <b>Ctrl + O:</b> Open File
-• <b>Ctrl + Shift + S:</b> Save As
-• <b>Ctrl + E:</b> Export Image
-• <b>Ctrl + P:</b> Preferences
-• <b>Ctrl + R:</b> Reset View
-• <b>Ctrl + B:</b> Toggle Bonds
-• <b>Ctrl + Shift + C:</b> Supercell Tool
-• <b>Ctrl + M:</b> Miller Indices Tool
+See Help → Keyboard Shortcuts (Ctrl + ?) for the full list.
 "#;
 
         let dialog = MessageDialog::new(
@@ -82,8 +111,190 @@ pub fn setup(app: &Application, window: &ApplicationWindow) {
             .website("https://mavensgroup.github.io/cview/")
             .license_type(gtk4::License::Gpl30)
             .build();
+        dialog.add_legal_section(
+            "Data Sources",
+            None,
+            gtk4::License::Custom,
+            Some(DATA_SOURCES),
+        );
 
         dialog.present(&win);
     });
     app.add_action(&about_action);
 }
+
+/// Help → Keyboard Shortcuts. The table is built when the dialog opens from
+/// every action registered on `app`, so actions added later (including the
+/// ones `main` installs after the menu bar) are listed without touching this
+/// file. Labels come from the menu bar `model`; actions that are not in a
+/// menu fall back to their action name.
+pub fn setup_shortcuts(
+    app: &Application,
+    window: &ApplicationWindow,
+    model: &gtk4::gio::MenuModel,
+) {
+    let action = gtk4::gio::SimpleAction::new("help_shortcuts", None);
+    let app_weak = app.downgrade();
+    let win_weak = window.downgrade();
+    let model = model.clone();
+
+    action.connect_activate(move |_, _| {
+        let (Some(app), Some(win)) = (app_weak.upgrade(), win_weak.upgrade()) else {
+            return;
+        };
+        show_shortcuts(&app, &win, &model);
+    });
+    app.add_action(&action);
+    app.set_accels_for_action("app.help_shortcuts", &["<Primary>question", "F1"]);
+}
+
+fn show_shortcuts(app: &Application, window: &ApplicationWindow, model: &gtk4::gio::MenuModel) {
+    let mut labels = HashMap::new();
+    collect_menu_labels(model, "", &mut labels);
+
+    // (menu, label, accelerator) per action, bound shortcuts first.
+    let mut rows: Vec<(String, String, String)> = app
+        .list_actions()
+        .iter()
+        .map(|name| {
+            let detailed = format!("app.{}", name);
+            let accel = app
+                .accels_for_action(&detailed)
+                .iter()
+                .filter_map(|a| gtk4::accelerator_parse(a.as_str()))
+                .map(|(key, mods)| gtk4::accelerator_get_label(key, mods).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let (menu, label) = labels
+                .get(&detailed)
+                .cloned()
+                .unwrap_or_else(|| ("Other".to_string(), humanize(name)));
+            (menu, label, accel)
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        a.2.is_empty()
+            .cmp(&b.2.is_empty())
+            .then_with(|| a.0.cmp(&b.0))
+            .then_with(|| a.1.cmp(&b.1))
+    });
+
+    let grid = gtk4::Grid::new();
+    grid.set_row_spacing(6);
+    grid.set_column_spacing(24);
+    grid.set_margin_top(15);
+    grid.set_margin_bottom(15);
+    grid.set_margin_start(15);
+    grid.set_margin_end(15);
+
+    let mut row = 0;
+    let header = |text: &str, row: &mut i32| {
+        let l = gtk4::Label::new(None);
+        l.set_markup(&format!("<b>{}</b>", text));
+        l.set_xalign(0.0);
+        if *row > 0 {
+            l.set_margin_top(10);
+        }
+        grid.attach(&l, 0, *row, 3, 1);
+        *row += 1;
+    };
+    let entry = |cells: [&str; 3], row: &mut i32| {
+        for (col, text) in cells.iter().enumerate() {
+            let l = gtk4::Label::new(Some(text));
+            l.set_xalign(0.0);
+            if col == 0 {
+                l.add_css_class("monospace");
+            } else if col == 2 {
+                l.add_css_class("dim-label");
+            }
+            grid.attach(&l, col as i32, *row, 1, 1);
+        }
+        *row += 1;
+    };
+
+    header("Application", &mut row);
+    for (menu, label, accel) in &rows {
+        let key = if accel.is_empty() {
+            "—"
+        } else {
+            accel.as_str()
+        };
+        entry([key, label, menu], &mut row);
+    }
+    header("Structure View", &mut row);
+    for (key, what) in CANVAS_BINDINGS {
+        entry([key, what, ""], &mut row);
+    }
+
+    let scroll = gtk4::ScrolledWindow::new();
+    scroll.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+    scroll.set_child(Some(&grid));
+    scroll.set_vexpand(true);
+
+    let dialog = gtk4::Window::builder()
+        .title("Keyboard Shortcuts")
+        .transient_for(window)
+        .modal(true)
+        .default_width(520)
+        .default_height(560)
+        .child(&scroll)
+        .build();
+    dialog.present();
+}
+
+/// Map `app.*` action names to (top-level menu, item label), recursing into
+/// submenus and sections.
+fn collect_menu_labels(
+    model: &gtk4::gio::MenuModel,
+    menu: &str,
+    out: &mut HashMap<String, (String, String)>,
+) {
+    for i in 0..model.n_items() {
+        let label = model
+            .item_attribute_value(i, "label", None)
+            .and_then(|v| v.str().map(|s| s.trim_end_matches("...").to_string()));
+        if let (Some(action), Some(label)) = (
+            model
+                .item_attribute_value(i, "action", None)
+                .and_then(|v| v.str().map(str::to_string)),
+            label.clone(),
+        ) {
+            out.entry(action)
+                .or_insert_with(|| (menu.to_string(), label));
+        }
+        if let Some(sub) = model.item_link(i, "submenu") {
+            // Only the top-level name is shown; nested submenus keep it.
+            let name = if menu.is_empty() {
+                label.unwrap_or_default()
+            } else {
+                menu.to_string()
+            };
+            collect_menu_labels(&sub, &name, out);
+        }
+        if let Some(section) = model.item_link(i, "section") {
+            collect_menu_labels(&section, menu, out);
+        }
+    }
+}
+
+/// `toggle_cell_view` → "Toggle cell view".
+fn humanize(action: &str) -> String {
+    let text = action.replace('_', " ");
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn humanize_action_names() {
+        assert_eq!(humanize("toggle_cell_view"), "Toggle cell view");
+        assert_eq!(humanize("quit"), "Quit");
+        assert_eq!(humanize(""), "");
+    }
+}
@@ -1,8 +1,13 @@
 // src/menu/actions_tools.rs
 
+use crate::model::symmetry::niggli_reduce_structure;
 use crate::physics::operations::conversion::{convert_structure, CellType};
-use crate::state::AppState;
-use crate::ui::dialogs::{atom_instances_dlg, basis_dlg, miller_dlg, supercell_dlg};
+use crate::rendering::scene;
+use crate::state::{AppState, SelectedAtom, SelectionOp};
+use crate::ui::dialogs::{
+    atom_instances_dlg, basis_dlg, build_supercell_dlg, miller_dlg, strain_dlg, supercell_dlg,
+    symmetrize_dlg, torsion_dlg,
+};
 use crate::utils::console;
 use gtk4::prelude::*;
 use gtk4::{Application, ApplicationWindow, DrawingArea, Notebook};
@@ -33,6 +38,23 @@ pub fn setup(
     });
     app.add_action(&sc_action);
 
+    // --- BUILD SUPERCELL (na × nb × nc of the current structure) ---
+    let build_sc_action = gtk4::gio::SimpleAction::new("build_supercell", None);
+    let win_weak_sc = window.downgrade();
+    let state_weak_sc = Rc::downgrade(&state);
+    let nb_weak_sc = notebook.downgrade();
+
+    build_sc_action.connect_activate(move |_, _| {
+        if let Some(win) = win_weak_sc.upgrade() {
+            if let Some(st) = state_weak_sc.upgrade() {
+                if let Some(nb) = nb_weak_sc.upgrade() {
+                    build_supercell_dlg::show(&win, st, &nb);
+                }
+            }
+        }
+    });
+    app.add_action(&build_sc_action);
+
     // --- BASIS / CHEMISTRY ---
     let basis_action = gtk4::gio::SimpleAction::new("basis", None);
     let win_weak_b = window.downgrade();
@@ -84,6 +106,125 @@ pub fn setup(
     });
     app.add_action(&mil_action);
 
+    // --- STRAIN ---
+    let strain_action = gtk4::gio::SimpleAction::new("strain", None);
+    let win_weak_e = window.downgrade();
+    let state_weak_e = Rc::downgrade(&state);
+    let nb_weak_e = notebook.downgrade();
+
+    strain_action.connect_activate(move |_, _| {
+        if let Some(win) = win_weak_e.upgrade() {
+            if let Some(st) = state_weak_e.upgrade() {
+                if let Some(nb) = nb_weak_e.upgrade() {
+                    strain_dlg::show(&win, st, &nb);
+                }
+            }
+        }
+    });
+    app.add_action(&strain_action);
+
+    // --- SYMMETRIZE ---
+    let sym_action = gtk4::gio::SimpleAction::new("symmetrize", None);
+    let win_weak_y = window.downgrade();
+    let state_weak_y = Rc::downgrade(&state);
+    let nb_weak_y = notebook.downgrade();
+
+    sym_action.connect_activate(move |_, _| {
+        if let Some(win) = win_weak_y.upgrade() {
+            if let Some(st) = state_weak_y.upgrade() {
+                if let Some(nb) = nb_weak_y.upgrade() {
+                    symmetrize_dlg::show(&win, st, &nb);
+                }
+            }
+        }
+    });
+    app.add_action(&sym_action);
+
+    // --- TORSION SCAN ---
+    let torsion_action = gtk4::gio::SimpleAction::new("torsion_scan", None);
+    let win_weak_t = window.downgrade();
+    let state_weak_t = Rc::downgrade(&state);
+
+    torsion_action.connect_activate(move |_, _| {
+        if let (Some(win), Some(st)) = (win_weak_t.upgrade(), state_weak_t.upgrade()) {
+            torsion_dlg::show(&win, st);
+        }
+    });
+    app.add_action(&torsion_action);
+
+    // --- STANDARD ORIENTATION ---
+    let orient_action = gtk4::gio::SimpleAction::new("standard_orientation", None);
+    let st_weak_o = Rc::downgrade(&state);
+    let nb_weak_o = notebook.downgrade();
+
+    orient_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_o.upgrade(), nb_weak_o.upgrade()) {
+            {
+                let mut s = st.borrow_mut();
+                let tab = s.active_tab_mut();
+                let Some(structure) = tab.structure.take() else {
+                    return;
+                };
+                tab.structure = Some(structure.to_standard_orientation());
+                tab.interaction.undo_stack.push(structure);
+                tab.interaction.selected.clear();
+                tab.interaction.highlighted.clear();
+                tab.modified = true;
+                tab.displacement.clear();
+            }
+            console::log_info("Rotated structure to standard orientation (a ∥ x, b in xy).");
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+            crate::ui::refresh_tab_labels(&nb, &st.borrow());
+        }
+    });
+    app.add_action(&orient_action);
+
+    // --- NIGGLI REDUCTION ---
+    let niggli_action = gtk4::gio::SimpleAction::new("niggli_reduce", None);
+    let st_weak_n = Rc::downgrade(&state);
+    let nb_weak_n = notebook.downgrade();
+
+    niggli_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_n.upgrade(), nb_weak_n.upgrade()) {
+            {
+                let mut s = st.borrow_mut();
+                let tab = s.active_tab_mut();
+                let Some(structure) = tab.structure.take() else {
+                    return;
+                };
+                if !structure.is_periodic {
+                    tab.structure = Some(structure);
+                    console::log_warn("Niggli reduction needs a periodic cell.");
+                    return;
+                }
+                let reduced = niggli_reduce_structure(&structure);
+                if reduced.lattice == structure.lattice {
+                    tab.structure = Some(structure);
+                    console::log_info("Cell is already Niggli-reduced.");
+                    return;
+                }
+                tab.structure = Some(reduced);
+                tab.interaction.undo_stack.push(structure);
+                tab.interaction.selected.clear();
+                tab.interaction.highlighted.clear();
+                tab.interaction.measure_picks.clear();
+                // Plane indices and the k-path refer to the old cell vectors.
+                tab.miller_planes.clear();
+                tab.kpath_result = None;
+                tab.modified = true;
+                tab.displacement.clear();
+            }
+            console::log_info("Transformed structure to its Niggli-reduced cell.");
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+            crate::ui::refresh_tab_labels(&nb, &st.borrow());
+        }
+    });
+    app.add_action(&niggli_action);
+
     // --- TOGGLE CELL VIEW (Ctrl+T) ---
     let toggle_action = gtk4::gio::SimpleAction::new("toggle_cell_view", None);
     let st_weak_t = Rc::downgrade(&state);
@@ -111,6 +252,219 @@ pub fn setup(
     });
 
     app.add_action(&toggle_action);
+
+    // --- SELECTION (All / None / Invert) ---
+    for (name, op) in [
+        ("select_all", SelectionOp::All),
+        ("select_none", SelectionOp::None),
+        ("select_invert", SelectionOp::Invert),
+    ] {
+        let action = gtk4::gio::SimpleAction::new(name, None);
+        let st_weak = Rc::downgrade(&state);
+        let nb_weak = notebook.downgrade();
+
+        action.connect_activate(move |_, _| {
+            if let (Some(st), Some(nb)) = (st_weak.upgrade(), nb_weak.upgrade()) {
+                if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                    apply_selection(&st, &da, op);
+                }
+            }
+        });
+        app.add_action(&action);
+    }
+
+    // --- RECOLOR / HIDE SELECTED ---
+    let recolor_action = gtk4::gio::SimpleAction::new("recolor_selected", None);
+    let win_weak_rc = window.downgrade();
+    let st_weak_rc = Rc::downgrade(&state);
+    let nb_weak_rc = notebook.downgrade();
+
+    recolor_action.connect_activate(move |_, _| {
+        let (Some(win), Some(st), Some(nb)) = (
+            win_weak_rc.upgrade(),
+            st_weak_rc.upgrade(),
+            nb_weak_rc.upgrade(),
+        ) else {
+            return;
+        };
+        if st.borrow().active_tab().interaction.selected.is_empty() {
+            console::info("No atoms selected.");
+            return;
+        }
+
+        let chooser = gtk4::ColorChooserDialog::new(Some("Recolor Selected Atoms"), Some(&win));
+        chooser.set_modal(true);
+        chooser.set_use_alpha(false);
+        chooser.connect_response(move |dlg, resp| {
+            if resp == gtk4::ResponseType::Ok {
+                let c = dlg.rgba();
+                let rgb = (c.red() as f64, c.green() as f64, c.blue() as f64);
+                let count = st.borrow_mut().active_tab_mut().recolor_selected(rgb);
+                console::info(&format!("Recolored {} atom(s).", count));
+                if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                    da.queue_draw();
+                }
+            }
+            dlg.close();
+        });
+        chooser.show();
+    });
+    app.add_action(&recolor_action);
+
+    let hide_action = gtk4::gio::SimpleAction::new("hide_selected", None);
+    let st_weak_h = Rc::downgrade(&state);
+    let nb_weak_h = notebook.downgrade();
+
+    hide_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_h.upgrade(), nb_weak_h.upgrade()) {
+            let count = st.borrow_mut().active_tab_mut().hide_selected();
+            console::info(&format!("Hid {} atom(s).", count));
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    });
+    app.add_action(&hide_action);
+
+    let show_action = gtk4::gio::SimpleAction::new("show_hidden", None);
+    let st_weak_s = Rc::downgrade(&state);
+    let nb_weak_s = notebook.downgrade();
+
+    show_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_s.upgrade(), nb_weak_s.upgrade()) {
+            let count = st.borrow_mut().active_tab_mut().show_hidden();
+            console::info(&format!("Showing {} previously hidden atom(s).", count));
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    });
+    app.add_action(&show_action);
+
+    // --- CLEAR OVERRIDES ---
+    // Restores element-based coloring and visibility for the whole tab.
+    let clear_action = gtk4::gio::SimpleAction::new("clear_overrides", None);
+    let st_weak_c = Rc::downgrade(&state);
+    let nb_weak_c = notebook.downgrade();
+
+    clear_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_c.upgrade(), nb_weak_c.upgrade()) {
+            let count = {
+                let mut s = st.borrow_mut();
+                let tab = s.active_tab_mut();
+                let n = tab.overrides.len();
+                tab.overrides.clear();
+                n
+            };
+            console::info(&format!("Cleared overrides on {} atom(s).", count));
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    });
+    app.add_action(&clear_action);
+
+    // --- MEASURE MODE ---
+    // Clicks pick up to three atoms for a live distance or angle instead of
+    // selecting them. M over the canvas toggles the same mode.
+    let mode_action = gtk4::gio::SimpleAction::new("measure_mode", None);
+    let st_weak_mm = Rc::downgrade(&state);
+    let nb_weak_mm = notebook.downgrade();
+
+    mode_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_mm.upgrade(), nb_weak_mm.upgrade()) {
+            let on = st.borrow_mut().active_tab_mut().toggle_measure_mode();
+            if on {
+                console::info(
+                    "Measure mode on: click 2 atoms for a distance, 3 for an angle. Esc clears.",
+                );
+            } else {
+                console::info("Measure mode off.");
+            }
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    });
+    app.add_action(&mode_action);
+
+    // --- PINNED MEASUREMENTS ---
+    // Keep the distance/angle/dihedral of the current selection on screen
+    // (and in exports) after the selection moves on.
+    let pin_action = gtk4::gio::SimpleAction::new("measure_pin", None);
+    let st_weak_p = Rc::downgrade(&state);
+    let nb_weak_p = notebook.downgrade();
+
+    pin_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_p.upgrade(), nb_weak_p.upgrade()) {
+            if st.borrow_mut().active_tab_mut().pin_measurement() {
+                console::info("Measurement pinned.");
+            } else {
+                console::info("Select 2 (distance), 3 (angle) or 4 (dihedral) atoms to pin.");
+            }
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    });
+    app.add_action(&pin_action);
+
+    let clear_meas_action = gtk4::gio::SimpleAction::new("measure_clear", None);
+    let st_weak_m = Rc::downgrade(&state);
+    let nb_weak_m = notebook.downgrade();
+
+    clear_meas_action.connect_activate(move |_, _| {
+        if let (Some(st), Some(nb)) = (st_weak_m.upgrade(), nb_weak_m.upgrade()) {
+            let count = {
+                let mut s = st.borrow_mut();
+                let measurements = &mut s.active_tab_mut().interaction.measurements;
+                let n = measurements.len();
+                measurements.clear();
+                n
+            };
+            console::info(&format!("Cleared {} measurement(s).", count));
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    });
+    app.add_action(&clear_meas_action);
+}
+
+/// Run a bulk selection op over the atoms currently drawn in `da`. Uses the
+/// same pickability rules as click/marquee so hidden ghosts never get selected.
+fn apply_selection(state: &Rc<RefCell<AppState>>, da: &DrawingArea, op: SelectionOp) {
+    let mut st = state.borrow_mut();
+    if st.active_tab().structure.is_none() {
+        return;
+    }
+
+    let show_ghosts = st.active_tab().view.show_full_unit_cell;
+    let (atoms, _, _) = scene::calculate_scene(
+        st.active_tab(),
+        &st.config,
+        da.width() as f64,
+        da.height() as f64,
+        false,
+        None,
+        None,
+    );
+    let pickable = atoms
+        .into_iter()
+        .filter(|a| a.is_pickable(show_ghosts))
+        .map(|a| SelectedAtom {
+            unique_id: a.unique_id,
+            original_index: a.original_index,
+            cart_pos: a.cart_pos,
+            element: a.element,
+        });
+
+    let count = st
+        .active_tab_mut()
+        .interaction
+        .apply_selection_op(op, pickable);
+    console::info(&format!("{} atom(s) selected.", count));
+    da.queue_draw();
 }
 
 // --- HELPER FUNCTION ---
@@ -144,8 +498,16 @@ fn convert_and_update(state: &Rc<RefCell<AppState>>, da: &DrawingArea, cell_type
                 let tab = st.active_tab_mut();
                 tab.structure = Some(new_struct);
                 tab.invalidate_bvs_cache();
+                tab.modified = true;
+                tab.displacement.clear();
 
                 da.queue_draw();
+                if let Some(nb) = da
+                    .ancestor(Notebook::static_type())
+                    .and_downcast::<Notebook>()
+                {
+                    crate::ui::refresh_tab_labels(&nb, &st);
+                }
             }
             Err(e) => {
                 console::log_error(&format!("Cell conversion failed: {}", e));
@@ -2,13 +2,23 @@
 
 use crate::config::RotationCenter;
 use crate::panels::sidebar::SidebarHandles;
-use crate::state::AppState;
+use crate::state::{AppState, AxesMode, StereoMode, ViewState};
 use crate::ui::show_preferences_window;
+use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{Application, ApplicationWindow, DrawingArea, Notebook};
+use nalgebra::UnitQuaternion;
 use std::cell::RefCell;
 use std::rc::Rc;
-// Note: std::f64::consts::PI is removed as we now use degrees (90.0) instead of radians (PI/2)
+use std::time::Duration;
+
+/// Number of frames used to slerp into a "View Along" orientation.
+/// At ~16 ms per frame the transition takes about 0.2 s.
+const SNAP_FRAMES: u32 = 12;
+
+/// Lattice used when no structure is loaded: presets fall back to the
+/// Cartesian axes.
+const IDENTITY_LATTICE: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
 
 pub fn setup(
     app: &Application,
@@ -39,88 +49,125 @@ pub fn setup(
             let tab = st.active_tab_mut();
             tab.view.reset_rotation();
             tab.view.zoom = 1.0;
+            tab.view.pan_x = 0.0;
+            tab.view.pan_y = 0.0;
             h_reset.sync_from_view(&st.active_tab().view);
             da.queue_draw();
         }
     });
     app.add_action(&act_reset);
 
-    // 2. View Along Axes
-    // Along A -> Rotate Y by -90 degrees
-    let act_a = gtk4::gio::SimpleAction::new("view_along_a", None);
-    let s_a = state.clone();
-    let nb_a = notebook.downgrade();
-    let h_a = sidebar_handles.clone();
-
-    act_a.connect_activate(move |_, _| {
-        if let Some(da) = get_da(&nb_a) {
-            let mut st = s_a.borrow_mut();
-            let tab = st.active_tab_mut();
-            tab.view.set_euler_xyz_deg(0.0, -90.0, 0.0);
-            h_a.sync_from_view(&st.active_tab().view);
-            da.queue_draw();
-        }
-    });
-    app.add_action(&act_a);
+    // 1b. Reset Tab to Defaults: the tab's style and view toggles go back to
+    // what Preferences holds for new tabs; the camera stays where it is.
+    let act_reset_tab = gtk4::gio::SimpleAction::new("reset_tab_defaults", None);
+    let s_reset_tab = state.clone();
+    let nb_reset_tab = notebook.downgrade();
 
-    // Along B -> Rotate X by 90 degrees
-    let act_b = gtk4::gio::SimpleAction::new("view_along_b", None);
-    let s_b = state.clone();
-    let nb_b = notebook.downgrade();
-    let h_b = sidebar_handles.clone();
-
-    act_b.connect_activate(move |_, _| {
-        if let Some(da) = get_da(&nb_b) {
-            let mut st = s_b.borrow_mut();
-            let tab = st.active_tab_mut();
-            tab.view.set_euler_xyz_deg(90.0, 0.0, 0.0);
-            h_b.sync_from_view(&st.active_tab().view);
+    act_reset_tab.connect_activate(move |_, _| {
+        if let Some(da) = get_da(&nb_reset_tab) {
+            let mut st = s_reset_tab.borrow_mut();
+            let config = st.config.clone();
+            st.active_tab_mut().reset_to_defaults(&config);
             da.queue_draw();
         }
     });
-    app.add_action(&act_b);
+    app.add_action(&act_reset_tab);
 
-    // Along C -> Reset Rotation (0, 0)
-    let act_c = gtk4::gio::SimpleAction::new("view_along_c", None);
-    let s_c = state.clone();
-    let nb_c = notebook.downgrade();
-    let h_c = sidebar_handles.clone();
-
-    act_c.connect_activate(move |_, _| {
-        if let Some(da) = get_da(&nb_c) {
-            let mut st = s_c.borrow_mut();
-            let tab = st.active_tab_mut();
-            tab.view.reset_rotation();
-            h_c.sync_from_view(&st.active_tab().view);
-            da.queue_draw();
-        }
+    // 2. View Along Axes
+    // Directions come from the lattice itself, so the presets stay exact for
+    // non-orthogonal cells. The "right" vector fixes the in-plane roll so the
+    // same preset always produces the same figure orientation.
+    register_view_along(app, "view_along_a", &state, notebook, &sidebar_handles, |l| {
+        (l[0], l[1])
+    });
+    register_view_along(app, "view_along_b", &state, notebook, &sidebar_handles, |l| {
+        (l[1], l[2])
+    });
+    register_view_along(app, "view_along_c", &state, notebook, &sidebar_handles, |l| {
+        (l[2], l[0])
+    });
+    register_view_along(app, "view_along_111", &state, notebook, &sidebar_handles, |l| {
+        let diag = [
+            l[0][0] + l[1][0] + l[2][0],
+            l[0][1] + l[1][1] + l[2][1],
+            l[0][2] + l[1][2] + l[2][2],
+        ];
+        let right = [l[0][0] - l[1][0], l[0][1] - l[1][1], l[0][2] - l[1][2]];
+        (diag, right)
     });
-    app.add_action(&act_c);
 
-    // 3. Rotation Center Modes
-    let act_centroid = gtk4::gio::SimpleAction::new("center_centroid", None);
-    let s_cent = state.clone();
-    let nb_cent = notebook.downgrade();
+    // 2b. Stereo modes (main structure view only; analysis tabs and exports
+    // always render mono).
+    for (name, mode) in [
+        ("stereo_off", StereoMode::Off),
+        ("stereo_anaglyph", StereoMode::Anaglyph),
+        ("stereo_side_by_side", StereoMode::SideBySide),
+    ] {
+        let action = gtk4::gio::SimpleAction::new(name, None);
+        let s_st = state.clone();
+        let nb_st = notebook.downgrade();
+        action.connect_activate(move |_, _| {
+            if let Some(da) = get_da(&nb_st) {
+                s_st.borrow_mut().active_tab_mut().view.stereo = mode;
+                da.queue_draw();
+            }
+        });
+        app.add_action(&action);
+    }
 
-    act_centroid.connect_activate(move |_, _| {
-        if let Some(da) = get_da(&nb_cent) {
-            s_cent.borrow_mut().config.rotation_mode = RotationCenter::Centroid;
-            da.queue_draw();
-        }
-    });
-    app.add_action(&act_centroid);
+    // 2c. Orientation gizmo frame
+    for (name, mode) in [
+        ("axes_cartesian", AxesMode::Cartesian),
+        ("axes_lattice", AxesMode::Lattice),
+    ] {
+        let action = gtk4::gio::SimpleAction::new(name, None);
+        let s_ax = state.clone();
+        let nb_ax = notebook.downgrade();
+        action.connect_activate(move |_, _| {
+            if let Some(da) = get_da(&nb_ax) {
+                s_ax.borrow_mut().active_tab_mut().view.axes_mode = mode;
+                da.queue_draw();
+            }
+        });
+        app.add_action(&action);
+    }
 
-    let act_uc = gtk4::gio::SimpleAction::new("center_unitcell", None);
-    let s_uc = state.clone();
-    let nb_uc = notebook.downgrade();
+    // 3. Rotation Center Modes — per tab; "default" follows Preferences.
+    for (name, mode) in [
+        ("center_default", None),
+        ("center_centroid", Some(RotationCenter::Centroid)),
+        ("center_unitcell", Some(RotationCenter::UnitCell)),
+    ] {
+        let action = gtk4::gio::SimpleAction::new(name, None);
+        let s_cent = state.clone();
+        let nb_cent = notebook.downgrade();
+        action.connect_activate(move |_, _| {
+            if let Some(da) = get_da(&nb_cent) {
+                s_cent.borrow_mut().active_tab_mut().view.rotation_center = mode;
+                da.queue_draw();
+            }
+        });
+        app.add_action(&action);
+    }
 
-    act_uc.connect_activate(move |_, _| {
-        if let Some(da) = get_da(&nb_uc) {
-            s_uc.borrow_mut().config.rotation_mode = RotationCenter::UnitCell;
-            da.queue_draw();
-        }
-    });
-    app.add_action(&act_uc);
+    // 3b. Recenter the view (pan only) on the geometric centroid or the
+    // center of mass, reporting it with the radius of gyration.
+    for (name, mass_weighted) in [
+        ("recenter_centroid", false),
+        ("recenter_center_of_mass", true),
+    ] {
+        let action = gtk4::gio::SimpleAction::new(name, None);
+        let st = state.clone();
+        let nb_weak = notebook.downgrade();
+
+        action.connect_activate(move |_, _| {
+            if let Some(da) = get_da(&nb_weak) {
+                recenter_on_centroid(&mut st.borrow_mut(), &da, mass_weighted);
+                da.queue_draw();
+            }
+        });
+        app.add_action(&action);
+    }
 
     // 4. Toggle Bonds
     let act_bonds = gtk4::gio::SimpleAction::new("toggle_bonds", None);
@@ -176,4 +223,185 @@ pub fn setup(
         }
     });
     app.add_action(&act_boundary);
+
+    // 7. Toggle Measurements (keeps them, only hides the overlay)
+    let act_meas = gtk4::gio::SimpleAction::new("toggle_measurements", None);
+    let s_meas = state.clone();
+    let nb_meas = notebook.downgrade();
+
+    act_meas.connect_activate(move |_, _| {
+        if let Some(da) = get_da(&nb_meas) {
+            let mut st = s_meas.borrow_mut();
+            let tab = st.active_tab_mut();
+            tab.view.show_measurements = !tab.view.show_measurements;
+            da.queue_draw();
+        }
+    });
+    app.add_action(&act_meas);
+
+    // 8. Toggle Scale Bar (length set in View Controls)
+    let act_bar = gtk4::gio::SimpleAction::new("toggle_scale_bar", None);
+    let s_bar = state.clone();
+    let nb_bar = notebook.downgrade();
+
+    act_bar.connect_activate(move |_, _| {
+        if let Some(da) = get_da(&nb_bar) {
+            let mut st = s_bar.borrow_mut();
+            let tab = st.active_tab_mut();
+            tab.view.show_scale_bar = !tab.view.show_scale_bar;
+            da.queue_draw();
+        }
+    });
+    app.add_action(&act_bar);
+
+    // 9. Toggle Element Legend
+    let act_legend = gtk4::gio::SimpleAction::new("toggle_legend", None);
+    let s_legend = state.clone();
+    let nb_legend = notebook.downgrade();
+
+    act_legend.connect_activate(move |_, _| {
+        if let Some(da) = get_da(&nb_legend) {
+            let mut st = s_legend.borrow_mut();
+            let tab = st.active_tab_mut();
+            tab.view.show_legend = !tab.view.show_legend;
+            da.queue_draw();
+        }
+    });
+    app.add_action(&act_legend);
+}
+
+/// Pan so the (mass-weighted) centroid of the structure sits at the center
+/// of `da`. Screen positions are an affine image of Cartesian ones, so the
+/// centroid's screen position is the same weighted mean of the atoms'.
+fn recenter_on_centroid(st: &mut AppState, da: &DrawingArea, mass_weighted: bool) {
+    let Some(structure) = &st.active_tab().structure else {
+        return;
+    };
+    let c = structure.centroid(mass_weighted);
+    let rg = structure.radius_of_gyration(mass_weighted);
+    crate::utils::console::info(&format!(
+        "{}: ({:.4}, {:.4}, {:.4}) Å, Rg = {:.4} Å",
+        if mass_weighted {
+            "Center of mass"
+        } else {
+            "Centroid"
+        },
+        c[0],
+        c[1],
+        c[2],
+        rg
+    ));
+
+    let (w, h) = (da.width() as f64, da.height() as f64);
+    let (atoms, _, bounds) = crate::rendering::scene::calculate_scene(
+        st.active_tab(),
+        &st.config,
+        w,
+        h,
+        false,
+        None,
+        None,
+    );
+    let (mut sx, mut sy, mut total) = (0.0, 0.0, 0.0);
+    for a in atoms.iter().filter(|a| a.is_home()) {
+        let wgt = if mass_weighted {
+            crate::model::elements::get_atomic_mass(&a.element)
+        } else {
+            1.0
+        };
+        sx += wgt * a.screen_pos[0];
+        sy += wgt * a.screen_pos[1];
+        total += wgt;
+    }
+    if total > 0.0 {
+        let view = &mut st.active_tab_mut().view;
+        view.pan_x += (sx / total - w / 2.0) / bounds.scale;
+        view.pan_y += (sy / total - h / 2.0) / bounds.scale;
+    }
+}
+
+/// Picks (view direction, screen-right reference) from lattice rows.
+type AxesFn = fn(&[[f64; 3]; 3]) -> ([f64; 3], [f64; 3]);
+
+/// Register a "View Along" action. `axes` picks the view from the active
+/// structure's lattice rows.
+fn register_view_along(
+    app: &Application,
+    name: &str,
+    state: &Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+    sidebar_handles: &Rc<SidebarHandles>,
+    axes: AxesFn,
+) {
+    let action = gtk4::gio::SimpleAction::new(name, None);
+    let st = state.clone();
+    let nb_weak = notebook.downgrade();
+    let handles = sidebar_handles.clone();
+
+    action.connect_activate(move |_, _| {
+        let da = match nb_weak
+            .upgrade()
+            .and_then(|nb| crate::ui::get_active_drawing_area(&nb))
+        {
+            Some(da) => da,
+            None => return,
+        };
+
+        let (start, target) = {
+            let s = st.borrow();
+            let tab = s.active_tab();
+            let lattice = tab
+                .structure
+                .as_ref()
+                .map(|strc| strc.lattice)
+                .unwrap_or(IDENTITY_LATTICE);
+            let (dir, right) = axes(&lattice);
+            match ViewState::orientation_along(dir, right) {
+                Some(q) => (tab.view.rotation, q),
+                None => return,
+            }
+        };
+
+        animate_rotation(st.clone(), da, handles.clone(), start, target);
+    });
+    app.add_action(&action);
+}
+
+/// Slerp the active tab's camera from `start` to `target` over
+/// `SNAP_FRAMES` frames. Aborts if the user switches or closes the tab
+/// mid-animation so the rotation never lands on the wrong view.
+fn animate_rotation(
+    state: Rc<RefCell<AppState>>,
+    da: DrawingArea,
+    handles: Rc<SidebarHandles>,
+    start: UnitQuaternion<f64>,
+    target: UnitQuaternion<f64>,
+) {
+    let tab_index = state.borrow().active_tab_index;
+    let mut frame = 0;
+
+    glib::timeout_add_local(Duration::from_millis(16), move || {
+        frame += 1;
+        let t = frame as f64 / SNAP_FRAMES as f64;
+        // Smoothstep easing: starts and ends at rest.
+        let eased = t * t * (3.0 - 2.0 * t);
+
+        let mut st = state.borrow_mut();
+        if st.active_tab_index != tab_index || tab_index >= st.tabs.len() {
+            return glib::ControlFlow::Break;
+        }
+        // try_slerp fails only for exactly opposite orientations, where the
+        // interpolation axis is undefined — snap straight to the target then.
+        let view = &mut st.tabs[tab_index].view;
+        view.rotation = start.try_slerp(&target, eased, 1e-9).unwrap_or(target);
+        handles.sync_from_view(view);
+        drop(st);
+        da.queue_draw();
+
+        if frame >= SNAP_FRAMES {
+            glib::ControlFlow::Break
+        } else {
+            glib::ControlFlow::Continue
+        }
+    });
 }
@@ -1368,6 +1368,69 @@ pub fn get_atom_cov(element: &str) -> f64 {
     get_atom_data(element).covalent_radius
 }
 
+/// Standard atomic weights (g/mol) indexed by `Z - 1`, H through Lr.
+/// Elements without a stable isotope carry the mass number of their
+/// longest-lived isotope.
+///
+/// **Source:** IUPAC CIAAW, "Standard atomic weights of the elements 2021".
+const ATOMIC_MASSES: [f64; 103] = [
+    1.008, 4.0026, 6.94, 9.0122, 10.81, 12.011, 14.007, 15.999, 18.998, 20.180,
+    22.990, 24.305, 26.982, 28.085, 30.974, 32.06, 35.45, 39.948, 39.098, 40.078,
+    44.956, 47.867, 50.942, 51.996, 54.938, 55.845, 58.933, 58.693, 63.546, 65.38,
+    69.723, 72.630, 74.922, 78.971, 79.904, 83.798, 85.468, 87.62, 88.906, 91.224,
+    92.906, 95.95, 98.0, 101.07, 102.91, 106.42, 107.87, 112.41, 114.82, 118.71,
+    121.76, 127.60, 126.90, 131.29, 132.91, 137.33, 138.91, 140.12, 140.91, 144.24,
+    145.0, 150.36, 151.96, 157.25, 158.93, 162.50, 164.93, 167.26, 168.93, 173.05,
+    174.97, 178.49, 180.95, 183.84, 186.21, 190.23, 192.22, 195.08, 196.97, 200.59,
+    204.38, 207.2, 208.98, 209.0, 210.0, 222.0, 223.0, 226.0, 227.0, 232.04,
+    231.04, 238.03, 237.0, 244.0, 243.0, 247.0, 247.0, 251.0, 252.0, 257.0,
+    258.0, 259.0, 262.0,
+];
+
+/// Returns the standard atomic weight (g/mol), or 0.0 for unknown symbols.
+///
+/// **Source:** IUPAC CIAAW (2021) conventional values.
+pub fn get_atomic_mass(element: &str) -> f64 {
+    let z = get_atomic_number(element);
+    if z >= 1 && (z as usize) <= ATOMIC_MASSES.len() {
+        ATOMIC_MASSES[z as usize - 1]
+    } else {
+        0.0
+    }
+}
+
+/// Bound coherent neutron scattering lengths (fm) indexed by `Z - 1`, H
+/// through U, for the natural isotopic mixture. Absorbing nuclei (B, Cd, Sm,
+/// Eu, Gd) carry the real part only; elements without a tabulated value are 0.
+///
+/// **Source:** Sears, V.F. "Neutron scattering lengths and cross sections."
+/// *Neutron News*, 3(3), 26-37 (1992).
+const NEUTRON_SCATTERING_LENGTHS: [f64; 92] = [
+    -3.739, 3.26, -1.90, 7.79, 5.30, 6.646, 9.36, 5.803, 5.654, 4.566,
+    3.63, 5.375, 3.449, 4.1491, 5.13, 2.847, 9.577, 1.909, 3.67, 4.70,
+    12.29, -3.438, -0.3824, 3.635, -3.73, 9.45, 2.49, 10.3, 7.718, 5.680,
+    7.288, 8.185, 6.58, 7.970, 6.795, 7.81, 7.09, 7.02, 7.75, 7.16,
+    7.054, 6.715, 6.8, 7.03, 5.88, 5.91, 5.922, 4.87, 4.065, 6.225,
+    5.57, 5.80, 5.28, 4.92, 5.42, 5.07, 8.24, 4.84, 4.58, 7.69,
+    12.6, 0.80, 7.22, 6.5, 7.38, 16.9, 8.01, 7.79, 7.07, 12.43,
+    7.21, 7.7, 6.91, 4.86, 9.2, 10.7, 10.6, 9.60, 7.63, 12.692,
+    8.776, 9.405, 8.532, 0.0, 0.0, 0.0, 0.0, 10.0, 0.0, 10.31,
+    9.1, 8.417,
+];
+
+/// Returns the coherent neutron scattering length b (fm), or 0.0 for
+/// unknown symbols and elements beyond U.
+///
+/// **Source:** Sears (1992), *Neutron News* 3, 26.
+pub fn get_neutron_scattering_length(element: &str) -> f64 {
+    let z = get_atomic_number(element);
+    if z >= 1 && (z as usize) <= NEUTRON_SCATTERING_LENGTHS.len() {
+        NEUTRON_SCATTERING_LENGTHS[z as usize - 1]
+    } else {
+        0.0
+    }
+}
+
 // =============================================================================
 // COLOR SCHEMES
 // =============================================================================
@@ -1399,6 +1462,29 @@ pub enum ColorScheme {
     AtomicRadius,
 }
 
+impl ColorScheme {
+    /// Every built-in scheme, in the order shown in scheme pickers.
+    pub const ALL: [ColorScheme; 6] = [
+        ColorScheme::GroupMaterial,
+        ColorScheme::Jmol,
+        ColorScheme::CpkClassic,
+        ColorScheme::RasMol,
+        ColorScheme::Electronegativity,
+        ColorScheme::AtomicRadius,
+    ];
+
+    /// Human-readable name for menus and preset lists.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorScheme::GroupMaterial => "Group/Material",
+            ColorScheme::Jmol => "Jmol",
+            ColorScheme::CpkClassic => "CPK Classic",
+            ColorScheme::RasMol => "RasMol",
+            ColorScheme::Electronegativity => "Electronegativity",
+            ColorScheme::AtomicRadius => "Atomic Radius",
+        }
+    }
+}
 
 /// Returns the visualization color for an element under the given scheme.
 pub fn get_element_color(element: &str, scheme: ColorScheme) -> (f64, f64, f64) {
@@ -1956,3 +2042,30 @@ pub fn get_bvs_atom_props(element: &str) -> Option<BvsAtomProps> {
     };
     Some(BvsAtomProps { r, c })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jmol_uses_community_standard_colors() {
+        // The palette users expect when matching published figures.
+        assert_eq!(get_element_color("H", ColorScheme::Jmol), (1.0, 1.0, 1.0));
+        let (r, g, b) = get_element_color("O", ColorScheme::Jmol);
+        assert!(r > 0.9 && g < 0.1 && b < 0.1, "O should be red");
+        let (r, _, b) = get_element_color("N", ColorScheme::Jmol);
+        assert!(b > 0.9 && r < 0.3, "N should be blue");
+        assert_ne!(
+            get_element_color("O", ColorScheme::Jmol),
+            get_element_color("O", ColorScheme::GroupMaterial)
+        );
+    }
+
+    #[test]
+    fn every_scheme_has_a_label() {
+        for scheme in ColorScheme::ALL {
+            assert!(!scheme.label().is_empty());
+        }
+        assert_eq!(ColorScheme::ALL[0], ColorScheme::default());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::model::elements::get_atomic_mass;
+use crate::utils::linalg::PeriodicCell;
+use nalgebra::Vector3;
 use serde::{Deserialize, Serialize}; // Assuming you use these for saving/loading
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,11 +24,20 @@ pub struct Atom {
     pub oxidation: Option<i32>,
     /// Site occupancy in [0, 1]. 1.0 (full) for formats without an
     /// occupancy field (POSCAR, QE, XYZ); CIF fills it from
-    /// `_atom_site_occupancy`. Consumers weight per-site contributions by
-    /// it: XRD scales the atomic form factor (virtual-crystal
-    /// approximation), BVS scales each neighbor's bond valence.
+    /// `_atom_site_occupancy`, SPR-KKR from the OCCUPATION concentrations
+    /// (one coincident atom per occupant). Consumers weight per-site
+    /// contributions by it: XRD scales the atomic form factor
+    /// (virtual-crystal approximation), BVS scales each neighbor's bond
+    /// valence, and the painter draws shared sites as pie slices.
     #[serde(default = "default_occupancy")]
     pub occupancy: f64,
+    /// Atomic displacement tensor U in Cartesian axes (Å²), when the source
+    /// file gives one. CIF fills it from `_atom_site_aniso_U_*` (or `_B_*`),
+    /// or from `_atom_site_U_iso_or_equiv` as U·I; every other format leaves
+    /// it `None`. Rigid rotations and strain carry it along; operations that
+    /// rebuild atoms from scratch (supercell, slab, cell conversion) drop it.
+    #[serde(default)]
+    pub adp: Option<[[f64; 3]; 3]>,
 }
 
 fn default_occupancy() -> f64 {
@@ -50,3 +62,793 @@ pub struct Structure {
 fn default_periodic() -> bool {
     true
 }
+
+/// Interatomic distance (Å) below which two atoms are treated as overlapping.
+/// Shorter than any real bond (H–H is 0.74 Å), so a hit almost always means a
+/// duplicated symmetry image or a parsing problem.
+pub const OVERLAP_DIST: f64 = 0.5;
+
+/// Above this many atoms the O(N²) overlap scan is skipped on load.
+pub const OVERLAP_CHECK_MAX_ATOMS: usize = 5000;
+
+/// Net charge (e) below which a cell counts as neutral. Absorbs the
+/// rounding in refined occupancies such as 0.333.
+pub const NEUTRALITY_TOLERANCE: f64 = 1e-2;
+
+/// Conversion factor from amu/Å³ to g/cm³ (1 amu = 1.66054e-24 g, 1 Å³ = 1e-24 cm³).
+const AMU_PER_A3_TO_G_PER_CM3: f64 = 1.660_539_066_60;
+
+impl Structure {
+    /// Unit-cell volume in Å³: the absolute scalar triple product a · (b × c).
+    ///
+    /// Shared by every consumer that needs the cell volume (info panel,
+    /// density, BVS lattice checks) so they all agree on the same number.
+    pub fn cell_volume(&self) -> f64 {
+        let a = Vector3::from(self.lattice[0]);
+        let b = Vector3::from(self.lattice[1]);
+        let c = Vector3::from(self.lattice[2]);
+        a.dot(&b.cross(&c)).abs()
+    }
+
+    /// Crystallographic density in g/cm³ from standard atomic weights,
+    /// weighted by site occupancy.
+    ///
+    /// Returns `None` for non-periodic structures or degenerate cells, where
+    /// a per-cell density has no meaning.
+    pub fn density_g_cm3(&self) -> Option<f64> {
+        let volume = self.cell_volume();
+        if !self.is_periodic || volume < 1e-6 {
+            return None;
+        }
+        let mass: f64 = self
+            .atoms
+            .iter()
+            .map(|a| get_atomic_mass(&a.element) * a.occupancy)
+            .sum();
+        Some(mass * AMU_PER_A3_TO_G_PER_CM3 / volume)
+    }
+
+    /// Net charge in e for one formal charge per atom, each weighted by
+    /// the site occupancy. Entries beyond the atom count are ignored.
+    pub fn net_charge(&self, charges: &[f64]) -> f64 {
+        self.atoms
+            .iter()
+            .zip(charges)
+            .map(|(a, q)| q * a.occupancy)
+            .sum()
+    }
+
+    /// Mean atom position in Å, optionally weighted by atomic mass. Uses
+    /// the stored Cartesian coordinates as-is, so a cluster split across a
+    /// periodic boundary must be unwrapped first. The origin when empty.
+    pub fn centroid(&self, mass_weighted: bool) -> [f64; 3] {
+        let mut sum = Vector3::zeros();
+        let mut total = 0.0;
+        for atom in &self.atoms {
+            let w = atom_weight(atom, mass_weighted);
+            sum += w * Vector3::from(atom.position);
+            total += w;
+        }
+        if total > 0.0 {
+            (sum / total).into()
+        } else {
+            [0.0; 3]
+        }
+    }
+
+    /// Radius of gyration in Å about [`centroid`](Self::centroid) with the
+    /// same weighting: sqrt(Σ wᵢ |rᵢ − r₀|² / Σ wᵢ).
+    pub fn radius_of_gyration(&self, mass_weighted: bool) -> f64 {
+        let c = Vector3::from(self.centroid(mass_weighted));
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        for atom in &self.atoms {
+            let w = atom_weight(atom, mass_weighted);
+            sum += w * (Vector3::from(atom.position) - c).norm_squared();
+            total += w;
+        }
+        if total > 0.0 {
+            (sum / total).sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Indices of atoms matching a search query, sorted and deduplicated.
+    ///
+    /// The query is a comma- or space-separated list of terms; each term is
+    /// an element symbol (case-insensitive, `"fe"`), a 0-based index (`"5"`)
+    /// or an inclusive index range (`"3-10"`). Indices are the same `#n`
+    /// numbering shown in the Atom Instances dialog. Out-of-range indices
+    /// and malformed ranges are errors so a typo isn't silently ignored.
+    pub fn find_atoms(&self, query: &str) -> Result<Vec<usize>, String> {
+        let n = self.atoms.len();
+        let mut hits = Vec::new();
+
+        for term in query
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+        {
+            if term.starts_with(|c: char| c.is_ascii_digit()) {
+                let (lo, hi) = match term.split_once('-') {
+                    Some((a, b)) => (a.parse::<usize>(), b.parse::<usize>()),
+                    None => (term.parse::<usize>(), term.parse::<usize>()),
+                };
+                let (lo, hi) = match (lo, hi) {
+                    (Ok(lo), Ok(hi)) if lo <= hi => (lo, hi),
+                    _ => return Err(format!("Invalid index or range '{}'", term)),
+                };
+                if hi >= n {
+                    return Err(format!(
+                        "Index {} out of range (structure has {} atoms)",
+                        hi, n
+                    ));
+                }
+                hits.extend(lo..=hi);
+            } else {
+                hits.extend(
+                    self.atoms
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, a)| a.element.eq_ignore_ascii_case(term))
+                        .map(|(i, _)| i),
+                );
+            }
+        }
+
+        hits.sort_unstable();
+        hits.dedup();
+        Ok(hits)
+    }
+
+    /// Atom pairs `(i, j, distance)` with `i < j` closer than `threshold` Å,
+    /// using the minimum image when the structure is periodic.
+    ///
+    /// Pairs whose occupancies sum to at most 1 are split-site disorder
+    /// (two species sharing one site, e.g. a CIF Fe/Cr site) and are not
+    /// reported.
+    pub fn find_overlaps(&self, threshold: f64) -> Vec<(usize, usize, f64)> {
+        let cell = if self.is_periodic {
+            PeriodicCell::new(self.lattice)
+        } else {
+            None
+        };
+
+        let t2 = threshold * threshold;
+        let mut out = Vec::new();
+        for i in 0..self.atoms.len() {
+            for j in (i + 1)..self.atoms.len() {
+                if self.atoms[i].occupancy + self.atoms[j].occupancy <= 1.0 + 1e-6 {
+                    continue;
+                }
+                let d =
+                    Vector3::from(self.atoms[j].position) - Vector3::from(self.atoms[i].position);
+                let d2 = match &cell {
+                    Some(cell) => cell.min_image(d).norm_squared(),
+                    None => d.norm_squared(),
+                };
+                if d2 < t2 {
+                    out.push((i, j, d2.sqrt()));
+                }
+            }
+        }
+        out
+    }
+
+    /// The `n` atoms nearest to atom `index`, sorted by distance, as
+    /// `(atom_index, distance, displacement)` where `displacement` is the
+    /// Cartesian vector from `index` to that neighbor. Periodic structures
+    /// search the surrounding images too, so one atom can appear several
+    /// times at different offsets (and `index` itself via its images).
+    pub fn neighbors_of(&self, index: usize, n: usize) -> Vec<(usize, f64, [f64; 3])> {
+        let Some(center) = self.atoms.get(index).map(|a| Vector3::from(a.position)) else {
+            return Vec::new();
+        };
+        let cell = if self.is_periodic {
+            PeriodicCell::new(self.lattice)
+        } else {
+            None
+        };
+        let Some(cell) = cell else {
+            let mut out: Vec<_> = self
+                .atoms
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != index)
+                .map(|(j, atom)| {
+                    let d = Vector3::from(atom.position) - center;
+                    (j, d.norm(), d.into())
+                })
+                .collect();
+            out.sort_by(|a, b| a.1.total_cmp(&b.1));
+            out.truncate(n);
+            return out;
+        };
+        if n == 0 {
+            return Vec::new();
+        }
+
+        // Start from the radius that holds about n atoms at the average
+        // density and double it until at least n images fall inside. Every
+        // image within the radius is found, so the n nearest are exact.
+        let [a, b, c] = self.lattice.map(Vector3::from);
+        let volume = self.cell_volume().max(1e-12);
+        let per_atom = volume / self.atoms.len() as f64;
+        let mut radius = (3.0 * (n + 1) as f64 * per_atom / (4.0 * std::f64::consts::PI)).cbrt();
+        let spacing = [
+            volume / b.cross(&c).norm().max(1e-12),
+            volume / c.cross(&a).norm().max(1e-12),
+            volume / a.cross(&b).norm().max(1e-12),
+        ];
+        loop {
+            // After wrapping to [-1/2, 1/2) an image at offset m lies at
+            // least (|m| - 1/2) plane spacings away along that axis.
+            let reach = spacing.map(|d| (radius / d + 0.5).ceil() as i32);
+            let mut out = Vec::new();
+            for (j, atom) in self.atoms.iter().enumerate() {
+                let mut df = cell.to_frac(Vector3::from(atom.position) - center);
+                for k in 0..3 {
+                    df[k] -= df[k].round();
+                }
+                for nx in -reach[0]..=reach[0] {
+                    for ny in -reach[1]..=reach[1] {
+                        for nz in -reach[2]..=reach[2] {
+                            let offset = Vector3::new(nx as f64, ny as f64, nz as f64);
+                            let img = cell.to_cart(df + offset);
+                            let dist = img.norm();
+                            if dist > 1e-8 && dist <= radius {
+                                out.push((j, dist, img.into()));
+                            }
+                        }
+                    }
+                }
+            }
+            if out.len() >= n {
+                out.sort_by(|a, b| a.1.total_cmp(&b.1));
+                out.truncate(n);
+                return out;
+            }
+            radius *= 2.0;
+        }
+    }
+
+    /// Remove the higher-indexed atom of every pair reported by
+    /// [`find_overlaps`](Self::find_overlaps), renumbering `original_index`.
+    /// Returns how many atoms were removed.
+    pub fn merge_overlaps(&mut self, threshold: f64) -> usize {
+        let doomed: std::collections::HashSet<usize> = self
+            .find_overlaps(threshold)
+            .into_iter()
+            .map(|(_, j, _)| j)
+            .collect();
+        if doomed.is_empty() {
+            return 0;
+        }
+        let mut i = 0;
+        self.atoms.retain(|_| {
+            let keep = !doomed.contains(&i);
+            i += 1;
+            keep
+        });
+        for (idx, atom) in self.atoms.iter_mut().enumerate() {
+            atom.original_index = idx;
+        }
+        doomed.len()
+    }
+
+    /// New structure made of the picked atom instances, `(atom index,
+    /// Cartesian position)` as stored in the selection, so boundary copies
+    /// keep the place they were picked at. Atoms are renumbered in the
+    /// order of `picks` and keep element, oxidation, occupancy and ADP. With
+    /// `keep_cell` the lattice and periodicity are kept and images of an
+    /// already-taken atom are dropped; otherwise the result is a molecule
+    /// (the lattice stays as a placeholder box).
+    pub fn extract(&self, picks: &[(usize, [f64; 3])], keep_cell: bool) -> Structure {
+        let periodic = keep_cell && self.is_periodic;
+        let cell = PeriodicCell::new(self.lattice);
+        let wrapped =
+            |p: [f64; 3]| cell.map(|cell| cell.to_frac(Vector3::from(p)).map(|x| x - x.floor()));
+
+        let mut taken: Vec<(usize, Vector3<f64>)> = Vec::new();
+        let mut atoms: Vec<Atom> = Vec::new();
+        for &(index, position) in picks {
+            let Some(src) = self.atoms.get(index) else {
+                continue;
+            };
+            if let Some(f) = wrapped(position).filter(|_| periodic) {
+                let seen = taken.iter().any(|(i, g)| {
+                    *i == index && (g - f).iter().all(|d| (d - d.round()).abs() < 1e-6)
+                });
+                if seen {
+                    continue;
+                }
+                taken.push((index, f));
+            }
+            atoms.push(Atom {
+                position,
+                original_index: atoms.len(),
+                ..src.clone()
+            });
+        }
+
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for a in &atoms {
+            match counts.iter_mut().find(|(el, _)| *el == a.element) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((a.element.clone(), 1)),
+            }
+        }
+        counts.sort();
+        let formula = counts
+            .iter()
+            .map(|(el, n)| {
+                if *n > 1 {
+                    format!("{el}{n}")
+                } else {
+                    el.clone()
+                }
+            })
+            .collect();
+
+        Structure {
+            lattice: self.lattice,
+            atoms,
+            formula,
+            is_periodic: periodic,
+        }
+    }
+
+    /// Rigidly rotated copy with `a` along +x and `b` in the xy-plane
+    /// (positive y), i.e. a lower-triangular lattice matrix. Lattice and
+    /// atoms rotate together, so fractional coordinates are unchanged.
+    /// Returns a clone when `a` and `b` are (nearly) parallel.
+    pub fn to_standard_orientation(&self) -> Structure {
+        let a = Vector3::from(self.lattice[0]);
+        let b = Vector3::from(self.lattice[1]);
+        let (Some(x), Some(y)) = (
+            a.try_normalize(1e-12),
+            (b - b.dot(&a) / a.norm_squared() * a).try_normalize(1e-12),
+        ) else {
+            return self.clone();
+        };
+        // Rows are the new axes expressed in the old frame.
+        let rot =
+            nalgebra::Matrix3::from_rows(&[x.transpose(), y.transpose(), x.cross(&y).transpose()]);
+        let map = |v: [f64; 3]| -> [f64; 3] { (rot * Vector3::from(v)).into() };
+
+        let mut out = self.clone();
+        out.lattice = self.lattice.map(map);
+        for atom in &mut out.atoms {
+            atom.position = map(atom.position);
+            atom.adp = atom.adp.map(|u| transform_adp(&rot, u));
+        }
+        out
+    }
+
+    /// Deformed copy under the strain tensor `strain` (ε, dimensionless):
+    /// every lattice vector and atom position is mapped by (I + ε), so
+    /// fractional coordinates are unchanged.
+    pub fn apply_strain(&self, strain: [[f64; 3]; 3]) -> Structure {
+        let eps = nalgebra::Matrix3::from_row_slice(&strain.concat());
+        let deform = nalgebra::Matrix3::identity() + eps;
+        let map = |v: [f64; 3]| -> [f64; 3] { (deform * Vector3::from(v)).into() };
+
+        let mut out = self.clone();
+        out.lattice = self.lattice.map(map);
+        for atom in &mut out.atoms {
+            atom.position = map(atom.position);
+            atom.adp = atom.adp.map(|u| transform_adp(&deform, u));
+        }
+        out
+    }
+
+    /// The `na` × `nb` × `nc` supercell: every atom repeated at each lattice
+    /// translation i·a + j·b + k·c (0 ≤ i < na, ...) and the lattice vectors
+    /// scaled by the counts. The copies of one atom follow each other, so
+    /// the element blocks of the parent keep their order, and
+    /// `original_index` is renumbered to the new positions. A zero count is
+    /// treated as one.
+    pub fn make_supercell(self, na: usize, nb: usize, nc: usize) -> Structure {
+        let counts = [na.max(1), nb.max(1), nc.max(1)];
+        let [a, b, c] = self.lattice.map(Vector3::from);
+
+        let mut atoms = Vec::with_capacity(self.atoms.len() * counts.iter().product::<usize>());
+        for atom in &self.atoms {
+            let base = Vector3::from(atom.position);
+            for i in 0..counts[0] {
+                for j in 0..counts[1] {
+                    for k in 0..counts[2] {
+                        let shift = a * i as f64 + b * j as f64 + c * k as f64;
+                        let mut copy = atom.clone();
+                        copy.position = (base + shift).into();
+                        copy.original_index = atoms.len();
+                        atoms.push(copy);
+                    }
+                }
+            }
+        }
+
+        let mut lattice = self.lattice;
+        for (row, n) in lattice.iter_mut().zip(counts) {
+            *row = row.map(|x| x * n as f64);
+        }
+        Structure {
+            lattice,
+            atoms,
+            ..self
+        }
+    }
+}
+
+/// U' = M U Mᵀ: a displacement tensor carried through the linear map `m`
+/// applied to the positions.
+pub fn transform_adp(m: &nalgebra::Matrix3<f64>, u: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let u = nalgebra::Matrix3::from_row_slice(&u.concat());
+    let t = m * u * m.transpose();
+    [
+        [t[(0, 0)], t[(0, 1)], t[(0, 2)]],
+        [t[(1, 0)], t[(1, 1)], t[(1, 2)]],
+        [t[(2, 0)], t[(2, 1)], t[(2, 2)]],
+    ]
+}
+
+/// Formula of `atoms`: elements in alphabetical order with counts above
+/// one appended, e.g. "ClNa" or "H2O".
+pub fn generate_formula(atoms: &[Atom]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for a in atoms {
+        match counts.iter_mut().find(|(el, _)| *el == a.element) {
+            Some((_, c)) => *c += 1,
+            None => counts.push((&a.element, 1)),
+        }
+    }
+    counts.sort();
+    counts
+        .iter()
+        .map(|&(el, c)| {
+            if c > 1 {
+                format!("{el}{c}")
+            } else {
+                el.to_string()
+            }
+        })
+        .collect()
+}
+
+fn atom_weight(atom: &Atom, mass_weighted: bool) -> f64 {
+    if mass_weighted {
+        get_atomic_mass(&atom.element)
+    } else {
+        1.0
+    }
+}
+
+/// Symmetric strain tensor from Voigt components `[e1, e2, e3, e4, e5, e6]`
+/// = `[εxx, εyy, εzz, γyz, γzx, γxy]`. Shear entries are engineering
+/// strains, so the tensor holds half of each.
+pub fn strain_from_voigt(v: [f64; 6]) -> [[f64; 3]; 3] {
+    [
+        [v[0], v[5] / 2.0, v[4] / 2.0],
+        [v[5] / 2.0, v[1], v[3] / 2.0],
+        [v[4] / 2.0, v[3] / 2.0, v[2]],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::linalg::cart_to_frac;
+
+    fn atom(element: &str, position: [f64; 3]) -> Atom {
+        Atom {
+            element: element.to_string(),
+            position,
+            original_index: 0,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        }
+    }
+
+    #[test]
+    fn cubic_cell_volume_and_density() {
+        // Diamond-cubic Si, a = 5.431 Å, 8 atoms per conventional cell.
+        let a = 5.431;
+        let frac = [
+            [0.0, 0.0, 0.0],
+            [0.0, 0.5, 0.5],
+            [0.5, 0.0, 0.5],
+            [0.5, 0.5, 0.0],
+            [0.25, 0.25, 0.25],
+            [0.25, 0.75, 0.75],
+            [0.75, 0.25, 0.75],
+            [0.75, 0.75, 0.25],
+        ];
+        let s = Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: frac
+                .iter()
+                .map(|f| atom("Si", [f[0] * a, f[1] * a, f[2] * a]))
+                .collect(),
+            formula: "Si8".to_string(),
+            is_periodic: true,
+        };
+
+        assert!((s.cell_volume() - a * a * a).abs() < 1e-9);
+        let rho = s.density_g_cm3().expect("periodic cell has a density");
+        assert!((rho - 2.329).abs() < 0.005, "Si density should be ~2.329, got {}", rho);
+    }
+
+    #[test]
+    fn net_charge_weights_by_occupancy() {
+        let mut s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![
+                atom("Fe", [0.0, 0.0, 0.0]),
+                atom("Mn", [0.0, 0.0, 0.0]),
+                atom("O", [2.0, 2.0, 2.0]),
+            ],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        s.atoms[0].occupancy = 0.5;
+        s.atoms[1].occupancy = 0.5;
+        assert!(s.net_charge(&[2.0, 2.0, -2.0]).abs() < 1e-12);
+        assert!((s.net_charge(&[3.0, 2.0, -2.0]) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn extracted_selection_is_renumbered() {
+        let mut s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![
+                atom("Na", [0.0, 0.0, 0.0]),
+                atom("Cl", [2.0, 2.0, 2.0]),
+                atom("Na", [2.0, 2.0, 0.0]),
+            ],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        s.atoms[1].oxidation = Some(-1);
+        // Cl, Na at the origin and its copy on the far a-face.
+        let picks = [
+            (1, [2.0, 2.0, 2.0]),
+            (0, [0.0, 0.0, 0.0]),
+            (0, [4.0, 0.0, 0.0]),
+        ];
+
+        let cluster = s.extract(&picks, false);
+        assert!(!cluster.is_periodic);
+        assert_eq!(cluster.atoms.len(), 3);
+        assert_eq!(cluster.atoms[2].position, [4.0, 0.0, 0.0]);
+        assert_eq!(cluster.atoms[0].oxidation, Some(-1));
+        let idx: Vec<usize> = cluster.atoms.iter().map(|a| a.original_index).collect();
+        assert_eq!(idx, vec![0, 1, 2]);
+        assert_eq!(cluster.formula, "ClNa2");
+
+        // In the cell the face copy is the same atom.
+        let cell = s.extract(&picks, true);
+        assert!(cell.is_periodic);
+        assert_eq!(cell.atoms.len(), 2);
+        assert_eq!(cell.formula, "ClNa");
+    }
+
+    #[test]
+    fn find_atoms_by_element_index_and_range() {
+        let s = Structure {
+            lattice: [[5.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 5.0]],
+            atoms: ["Ti", "O", "O", "O", "Sr"]
+                .iter()
+                .map(|el| atom(el, [0.0; 3]))
+                .collect(),
+            formula: "SrTiO3".to_string(),
+            is_periodic: true,
+        };
+
+        assert_eq!(s.find_atoms("o").unwrap(), vec![1, 2, 3]);
+        assert_eq!(s.find_atoms("Sr, 0-1").unwrap(), vec![0, 1, 4]);
+        assert_eq!(s.find_atoms("2 2").unwrap(), vec![2]);
+        assert!(s.find_atoms("Fe").unwrap().is_empty());
+        assert!(s.find_atoms("3-1").is_err());
+        assert!(s.find_atoms("5").is_err());
+    }
+
+    #[test]
+    fn overlaps_found_across_the_cell_boundary_and_merged() {
+        let mut s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![
+                atom("Na", [0.0, 0.0, 0.0]),
+                atom("Cl", [2.0, 2.0, 2.0]),
+                // Image of atom 0 through the a-face, 0.1 Å away under PBC.
+                atom("Na", [3.9, 0.0, 0.0]),
+            ],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let hits = s.find_overlaps(OVERLAP_DIST);
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].0, hits[0].1), (0, 2));
+        assert!((hits[0].2 - 0.1).abs() < 1e-9);
+
+        // Without PBC the same pair is 3.9 Å apart.
+        s.is_periodic = false;
+        assert!(s.find_overlaps(OVERLAP_DIST).is_empty());
+        s.is_periodic = true;
+
+        assert_eq!(s.merge_overlaps(OVERLAP_DIST), 1);
+        assert_eq!(s.atoms.len(), 2);
+        assert_eq!(s.atoms[1].element, "Cl");
+        assert_eq!(s.atoms[1].original_index, 1);
+    }
+
+    #[test]
+    fn neighbors_of_sorted_under_pbc() {
+        let s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![atom("Na", [0.0, 0.0, 0.0]), atom("Cl", [2.0, 0.0, 0.0])],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let nn = s.neighbors_of(0, 8);
+        assert_eq!(nn.len(), 8);
+        // Two Cl at ±a/2 along x, then six Na images at 4 Å.
+        assert!(nn[..2]
+            .iter()
+            .all(|&(j, d, _)| j == 1 && (d - 2.0).abs() < 1e-12));
+        assert!(nn[2..]
+            .iter()
+            .all(|&(j, d, _)| j == 0 && (d - 4.0).abs() < 1e-12));
+        let dx: Vec<f64> = nn[..2].iter().map(|n| n.2[0]).collect();
+        assert!(dx.contains(&2.0) && dx.contains(&-2.0));
+
+        let molecule = Structure {
+            is_periodic: false,
+            ..s.clone()
+        };
+        let nn = molecule.neighbors_of(0, 8);
+        assert_eq!(nn.len(), 1);
+        assert_eq!((nn[0].0, nn[0].1), (1, 2.0));
+        assert!(s.neighbors_of(5, 3).is_empty());
+    }
+
+    #[test]
+    fn neighbors_of_reaches_past_the_first_image_shell() {
+        // A short c axis puts the 20 nearest images up to ten cells away
+        // along z, far beyond a fixed ±1 search.
+        let s = Structure {
+            lattice: [[10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [0.0, 0.0, 1.0]],
+            atoms: vec![atom("C", [0.0, 0.0, 0.0])],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let nn = s.neighbors_of(0, 20);
+        assert_eq!(nn.len(), 20);
+        let expected: Vec<f64> = (1..=10).flat_map(|k| [k as f64; 2]).collect();
+        for (&(_, d, _), e) in nn.iter().zip(&expected) {
+            assert!((d - e).abs() < 1e-12, "{} vs {}", d, e);
+        }
+    }
+
+    #[test]
+    fn centroid_and_radius_of_gyration() {
+        let s = Structure {
+            lattice: [[10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [0.0, 0.0, 10.0]],
+            atoms: vec![atom("O", [1.0, 2.0, 3.0]), atom("H", [3.0, 2.0, 3.0])],
+            formula: String::new(),
+            is_periodic: false,
+        };
+        assert_eq!(s.centroid(false), [2.0, 2.0, 3.0]);
+        assert!((s.radius_of_gyration(false) - 1.0).abs() < 1e-12);
+
+        // Mass weighting pulls the centroid toward oxygen.
+        let (m_o, m_h) = (get_atomic_mass("O"), get_atomic_mass("H"));
+        let x = (m_o * 1.0 + m_h * 3.0) / (m_o + m_h);
+        let c = s.centroid(true);
+        assert!((c[0] - x).abs() < 1e-12 && c[0] < 2.0);
+        let rg = ((m_o * (1.0 - x).powi(2) + m_h * (3.0 - x).powi(2)) / (m_o + m_h)).sqrt();
+        assert!((s.radius_of_gyration(true) - rg).abs() < 1e-12);
+    }
+
+    #[test]
+    fn split_sites_are_not_overlaps() {
+        let mut fe = atom("Fe", [1.0, 1.0, 1.0]);
+        fe.occupancy = 0.6;
+        let mut cr = atom("Cr", [1.0, 1.0, 1.0]);
+        cr.occupancy = 0.4;
+        let s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![fe, cr],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        assert!(s.find_overlaps(OVERLAP_DIST).is_empty());
+    }
+
+    #[test]
+    fn uniaxial_strain_stretches_one_axis_only() {
+        let s = Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 6.0]],
+            atoms: vec![atom("Ti", [1.0, 2.5, 4.5])],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let strained = s.apply_strain(strain_from_voigt([0.02, 0.0, 0.0, 0.0, 0.0, 0.0]));
+
+        let len = |v: [f64; 3]| Vector3::from(v).norm();
+        assert!((len(strained.lattice[0]) - 4.08).abs() < 1e-12);
+        assert!((len(strained.lattice[1]) - 5.0).abs() < 1e-12);
+        assert!((len(strained.lattice[2]) - 6.0).abs() < 1e-12);
+
+        let frac =
+            |st: &Structure| Vector3::from(cart_to_frac(st.atoms[0].position, st.lattice).unwrap());
+        assert!((frac(&strained) - frac(&s)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn standard_orientation_is_lower_triangular() {
+        // Triclinic cell in an arbitrary orientation.
+        let s = Structure {
+            lattice: [[1.0, 2.0, 3.0], [-2.0, 1.5, 0.5], [0.3, -1.0, 4.0]],
+            atoms: vec![atom("Si", [0.7, 0.2, 1.9])],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let r = s.to_standard_orientation();
+        let l = r.lattice;
+        assert!(l[0][1].abs() < 1e-12 && l[0][2].abs() < 1e-12);
+        assert!(l[0][0] > 0.0);
+        assert!(l[1][2].abs() < 1e-12 && l[1][1] > 0.0);
+
+        // Rigid rotation: lengths, volume and fractional coordinates survive.
+        for (rotated, original) in l.iter().zip(&s.lattice) {
+            let n0 = Vector3::from(*original).norm();
+            assert!((Vector3::from(*rotated).norm() - n0).abs() < 1e-12);
+        }
+        assert!((r.cell_volume() - s.cell_volume()).abs() < 1e-9);
+        let frac =
+            |st: &Structure| Vector3::from(cart_to_frac(st.atoms[0].position, st.lattice).unwrap());
+        assert!((frac(&r) - frac(&s)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn supercell_repeats_atoms_and_scales_the_cell() {
+        let s = Structure {
+            lattice: [[3.0, 0.0, 0.0], [1.0, 4.0, 0.0], [0.0, 0.0, 5.0]],
+            atoms: vec![atom("Na", [0.0, 0.0, 0.0]), atom("Cl", [2.0, 2.0, 2.5])],
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        };
+        let volume = s.cell_volume();
+        let sc = s.make_supercell(2, 3, 1);
+
+        assert_eq!(
+            sc.lattice,
+            [[6.0, 0.0, 0.0], [3.0, 12.0, 0.0], [0.0, 0.0, 5.0]]
+        );
+        assert!((sc.cell_volume() - 6.0 * volume).abs() < 1e-9);
+        assert_eq!(sc.atoms.len(), 12);
+        // Copies of each atom stay together and are renumbered.
+        assert!(sc.atoms[..6].iter().all(|a| a.element == "Na"));
+        assert!(sc.atoms[6..].iter().all(|a| a.element == "Cl"));
+        assert!(sc
+            .atoms
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a.original_index == i));
+        // Translation (1, 2, 0) of Cl: 2.0 + 3 + 2·1, 2.0 + 2·4.
+        assert!(sc.atoms[6..].iter().any(|a| a.position == [7.0, 10.0, 2.5]));
+    }
+
+    #[test]
+    fn non_periodic_structure_has_no_density() {
+        let s = Structure {
+            lattice: [[10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [0.0, 0.0, 10.0]],
+            atoms: vec![atom("O", [0.0, 0.0, 0.0])],
+            formula: "O".to_string(),
+            is_periodic: false,
+        };
+        assert!((s.cell_volume() - 1000.0).abs() < 1e-9);
+        assert!(s.density_g_cm3().is_none());
+    }
+}
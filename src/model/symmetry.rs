@@ -0,0 +1,268 @@
+// src/model/symmetry.rs
+//
+// Niggli reduction of a lattice: the Křivý–Gruber algorithm (Acta Cryst.
+// A32, 297 (1976)) with the relative tolerance of Grosse-Kunstleve,
+// Sauter & Adams (Acta Cryst. A60, 1 (2004)). It works on the metric
+//
+//   (A, B, C, ξ, η, ζ) = (a·a, b·b, c·c, 2b·c, 2a·c, 2a·b)
+//
+// but applies every step to the cell vectors themselves, so the result is
+// the reduced basis of the same lattice, not just its parameters. Each step
+// is unimodular with determinant +1, which keeps the volume and handedness.
+
+use crate::model::structure::Structure;
+use crate::utils::linalg::{cart_to_frac, frac_to_cart};
+
+/// Steps before giving up on a cell that keeps cycling, which only
+/// near-degenerate input does; the basis reached so far is returned.
+const MAX_STEPS: usize = 1000;
+
+/// The Niggli-reduced basis of the lattice spanned by the rows of
+/// `lattice`. Satisfies a ≤ b ≤ c, with the angles all acute or all
+/// non-acute and the special-position conditions of the Niggli cell.
+/// A degenerate cell is returned unchanged.
+pub fn niggli_reduce(lattice: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let [mut a, mut b, mut c] = lattice;
+    let volume = dot(a, cross(b, c)).abs();
+    if volume < 1e-12 {
+        return lattice;
+    }
+    // Tolerance on the metric, which is in Å²: relative to the squared
+    // length of a cube of the same volume.
+    let eps = 1e-5 * volume.powf(2.0 / 3.0);
+    let sign = |x: f64| {
+        if x > eps {
+            1
+        } else if x < -eps {
+            -1
+        } else {
+            0
+        }
+    };
+
+    for _ in 0..MAX_STEPS {
+        // A1: A ≤ B, and |ξ| ≤ |η| when they tie.
+        let [aa, bb, _, xi, eta, _] = metric([a, b, c]);
+        if aa > bb + eps || ((aa - bb).abs() < eps && xi.abs() > eta.abs() + eps) {
+            (a, b, c) = (neg(b), neg(a), neg(c));
+        }
+        // A2: B ≤ C, and |η| ≤ |ζ| when they tie; then A1 again.
+        let [_, bb, cc, _, eta, zeta] = metric([a, b, c]);
+        if bb > cc + eps || ((bb - cc).abs() < eps && eta.abs() > zeta.abs() + eps) {
+            (a, b, c) = (neg(a), neg(c), neg(b));
+            continue;
+        }
+
+        // A3/A4: ξ, η, ζ all positive or all non-positive, by flipping
+        // two vectors (each flip changes the sign of two of them).
+        let [_, _, _, xi, eta, zeta] = metric([a, b, c]);
+        let signs = [sign(xi), sign(eta), sign(zeta)];
+        let flip = if signs.iter().product::<i32>() == 1 {
+            signs.map(|s| if s == -1 { -1.0 } else { 1.0 })
+        } else {
+            let mut flip = signs.map(|s| if s == 1 { -1.0 } else { 1.0 });
+            if flip.iter().product::<f64>() < 0.0 {
+                // Only reachable with a zero product; flipping that vector
+                // too keeps the determinant positive.
+                if let Some(k) = signs.iter().rposition(|&s| s == 0) {
+                    flip[k] = -1.0;
+                }
+            }
+            flip
+        };
+        a = scale(a, flip[0]);
+        b = scale(b, flip[1]);
+        c = scale(c, flip[2]);
+
+        // A5–A8: shorten c or b by a or b, or replace c by a + b + c. Any
+        // change starts over at A1.
+        let [aa, bb, _, xi, eta, zeta] = metric([a, b, c]);
+        if xi.abs() > bb + eps
+            || ((xi - bb).abs() < eps && 2.0 * eta < zeta - eps)
+            || ((xi + bb).abs() < eps && zeta < -eps)
+        {
+            c = sub(c, scale(b, xi.signum()));
+            continue;
+        }
+        if eta.abs() > aa + eps
+            || ((eta - aa).abs() < eps && 2.0 * xi < zeta - eps)
+            || ((eta + aa).abs() < eps && zeta < -eps)
+        {
+            c = sub(c, scale(a, eta.signum()));
+            continue;
+        }
+        if zeta.abs() > aa + eps
+            || ((zeta - aa).abs() < eps && 2.0 * xi < eta - eps)
+            || ((zeta + aa).abs() < eps && eta < -eps)
+        {
+            b = sub(b, scale(a, zeta.signum()));
+            continue;
+        }
+        let sum = xi + eta + zeta + aa + bb;
+        if sum < -eps || (sum.abs() < eps && 2.0 * (aa + eta) + zeta > eps) {
+            c = add(c, add(a, b));
+            continue;
+        }
+        return [a, b, c];
+    }
+    [a, b, c]
+}
+
+/// `structure` in its Niggli-reduced cell. The reduced cell spans the same
+/// lattice with the same volume, so the atoms keep their Cartesian
+/// positions up to a lattice translation: each is wrapped into the new
+/// cell, and the atom count and order are unchanged.
+pub fn niggli_reduce_structure(structure: &Structure) -> Structure {
+    let lattice = niggli_reduce(structure.lattice);
+    let mut out = structure.clone();
+    out.lattice = lattice;
+    for atom in &mut out.atoms {
+        if let Some(frac) = cart_to_frac(atom.position, lattice) {
+            // rem_euclid can round -1e-17 up to exactly 1.0.
+            let wrapped = frac.map(|f| {
+                let w = f.rem_euclid(1.0);
+                if w >= 1.0 {
+                    0.0
+                } else {
+                    w
+                }
+            });
+            atom.position = frac_to_cart(wrapped, lattice);
+        }
+    }
+    out
+}
+
+/// (A, B, C, ξ, η, ζ) of the basis `v`.
+fn metric(v: [[f64; 3]; 3]) -> [f64; 6] {
+    let [a, b, c] = v;
+    [
+        dot(a, a),
+        dot(b, b),
+        dot(c, c),
+        2.0 * dot(b, c),
+        2.0 * dot(a, c),
+        2.0 * dot(a, b),
+    ]
+}
+
+fn dot(u: [f64; 3], v: [f64; 3]) -> f64 {
+    u[0] * v[0] + u[1] * v[1] + u[2] * v[2]
+}
+
+fn cross(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+fn add(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [u[0] + v[0], u[1] + v[1], u[2] + v[2]]
+}
+
+fn sub(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [u[0] - v[0], u[1] - v[1], u[2] - v[2]]
+}
+
+fn scale(u: [f64; 3], s: f64) -> [f64; 3] {
+    u.map(|x| x * s)
+}
+
+fn neg(u: [f64; 3]) -> [f64; 3] {
+    scale(u, -1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cell vectors with the metric (A, B, C, ξ, η, ζ), a along x and b in
+    /// the xy-plane.
+    fn from_metric(g: [f64; 6]) -> [[f64; 3]; 3] {
+        let (a, b, c) = (g[0].sqrt(), g[1].sqrt(), g[2].sqrt());
+        let cos_al = g[3] / (2.0 * b * c);
+        let cos_be = g[4] / (2.0 * a * c);
+        let cos_ga = g[5] / (2.0 * a * b);
+        let sin_ga = (1.0 - cos_ga * cos_ga).sqrt();
+        let cx = c * cos_be;
+        let cy = c * (cos_al - cos_be * cos_ga) / sin_ga;
+        [
+            [a, 0.0, 0.0],
+            [b * cos_ga, b * sin_ga, 0.0],
+            [cx, cy, (c * c - cx * cx - cy * cy).sqrt()],
+        ]
+    }
+
+    fn assert_niggli(lattice: [[f64; 3]; 3]) {
+        let [aa, bb, cc, xi, eta, zeta] = metric(lattice);
+        let eps = 1e-6;
+        assert!(
+            aa <= bb + eps && bb <= cc + eps,
+            "a ≤ b ≤ c: {:?}",
+            metric(lattice)
+        );
+        assert!(xi.abs() <= bb + eps && eta.abs() <= aa + eps && zeta.abs() <= aa + eps);
+        let all_pos = xi > eps && eta > eps && zeta > eps;
+        let all_non_pos = xi <= eps && eta <= eps && zeta <= eps;
+        assert!(all_pos || all_non_pos, "mixed signs: {:?}", metric(lattice));
+        assert!(xi + eta + zeta + aa + bb >= -eps);
+    }
+
+    #[test]
+    fn krivy_gruber_example_reduces() {
+        // The worked example of Křivý & Gruber (1976).
+        let lattice = from_metric([9.0, 27.0, 4.0, -5.0, -4.0, -22.0]);
+        let reduced = niggli_reduce(lattice);
+        assert_niggli(reduced);
+        let expected = [4.0, 9.0, 9.0, 9.0, 3.0, 4.0];
+        for (got, want) in metric(reduced).iter().zip(expected) {
+            assert!((got - want).abs() < 1e-6, "{:?}", metric(reduced));
+        }
+        let volume = |l: [[f64; 3]; 3]| dot(l[0], cross(l[1], l[2]));
+        assert!((volume(reduced) - volume(lattice)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn skewed_triclinic_cell_recovers_its_reduced_parameters() {
+        let base = from_metric([16.0, 25.0, 36.0, 10.0, 6.0, 4.0]);
+        assert_niggli(base);
+        // The same lattice through a unimodular, strongly sheared basis.
+        let m = [[1.0, 2.0, 0.0], [0.0, 1.0, 0.0], [3.0, 1.0, 1.0]];
+        let skewed: [[f64; 3]; 3] = std::array::from_fn(|i| {
+            (0..3).fold([0.0; 3], |acc, j| add(acc, scale(base[j], m[i][j])))
+        });
+        let reduced = niggli_reduce(skewed);
+        assert_niggli(reduced);
+        for (got, want) in metric(reduced).iter().zip(metric(base)) {
+            assert!((got - want).abs() < 1e-6, "{:?}", metric(reduced));
+        }
+    }
+
+    #[test]
+    fn atoms_are_wrapped_into_the_reduced_cell() {
+        use crate::model::structure::Atom;
+        let lattice = [[3.0, 0.0, 0.0], [9.0, 3.0, 0.0], [0.0, 0.0, 4.0]];
+        let s = Structure {
+            lattice,
+            atoms: vec![Atom {
+                element: "Si".to_string(),
+                position: frac_to_cart([0.25, 0.5, 0.5], lattice),
+                original_index: 0,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            }],
+            formula: "Si".to_string(),
+            is_periodic: true,
+        };
+        let r = niggli_reduce_structure(&s);
+        assert_niggli(r.lattice);
+        let frac = cart_to_frac(r.atoms[0].position, r.lattice).unwrap();
+        assert!(frac.iter().all(|f| (0.0..1.0).contains(f)));
+        // Same site: the displacement is a lattice vector of the old cell.
+        let d = cart_to_frac(sub(r.atoms[0].position, s.atoms[0].position), lattice).unwrap();
+        assert!(d.iter().all(|x| (x - x.round()).abs() < 1e-9));
+    }
+}
@@ -2,19 +2,19 @@
 // All features preserved — logging via centralized utils::console
 
 use gtk4::gdk;
-use gtk4::glib::SignalHandlerId;
+use gtk4::glib::{SignalHandlerId, WeakRef};
 use gtk4::prelude::*;
 use gtk4::{
     Adjustment, Align, Box as GtkBox, Button, CheckButton, ColorButton, CssProvider, DropDown,
-    Expander, Frame, Label, Notebook, Orientation, PolicyType, Scale, ScrolledWindow, Separator,
-    STYLE_PROVIDER_PRIORITY_APPLICATION,
+    Expander, Frame, Label, Notebook, Orientation, PolicyType, Scale, ScrolledWindow, SearchEntry,
+    Separator, SpinButton, STYLE_PROVIDER_PRIORITY_APPLICATION,
 };
 
-use crate::config::ColorMode;
-use crate::model::elements::get_element_color;
-use crate::state::{AppState, ViewState};
+use crate::config::{ColorMode, Gradient, LabelContent, Light};
+use crate::model::elements::{get_element_color, ColorScheme};
+use crate::state::{AppState, SelectedAtom, TabState, ViewState, ZOOM_MAX, ZOOM_MIN};
 use crate::utils::console;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 /// Handles to the View Controls sliders so external events (mouse drag, scroll,
@@ -29,18 +29,70 @@ pub struct SidebarHandles {
     rot_x_handler: SignalHandlerId,
     rot_y_handler: SignalHandlerId,
     rot_z_handler: SignalHandlerId,
+    /// Trajectory frame slider; hidden unless the tab holds several frames.
+    frame_box: GtkBox,
+    frame_scale: Scale,
+    frame_handler: SignalHandlerId,
+    /// "Replicate View" spins, one per axis.
+    rep_spins: Vec<(SpinButton, SignalHandlerId)>,
+    preview_check: CheckButton,
+    preview_handler: SignalHandlerId,
+    neighbors: NeighborTable,
+}
+
+/// Picked atom as `(structure index, Cartesian position of the instance
+/// that was clicked)`.
+type PickedAtom = (usize, [f64; 3]);
+
+/// "Nearest Neighbors" rows for the picked atom, rebuilt on selection.
+#[derive(Clone)]
+struct NeighborTable {
+    rows: GtkBox,
+    /// `None` when nothing is picked.
+    center: Rc<Cell<Option<PickedAtom>>>,
+    notebook: WeakRef<Notebook>,
 }
 
 impl SidebarHandles {
-    /// Push current view state into the slider widgets without re-firing their
-    /// value-changed callbacks (which would write back into state and could
-    /// re-quantize through the snapping logic).
+    /// Push current view state into the slider and replication widgets
+    /// without re-firing their value-changed callbacks (which would write
+    /// back into state and could re-quantize through the snapping logic).
     pub fn sync_from_view(&self, view: &ViewState) {
         let (rx, ry, rz) = view.euler_xyz_deg();
         Self::set_blocked(&self.zoom_scale, &self.zoom_handler, view.zoom);
         Self::set_blocked(&self.rot_x_scale, &self.rot_x_handler, rx);
         Self::set_blocked(&self.rot_y_scale, &self.rot_y_handler, ry);
         Self::set_blocked(&self.rot_z_scale, &self.rot_z_handler, rz);
+        for ((spin, handler), n) in self.rep_spins.iter().zip(view.replication) {
+            spin.block_signal(handler);
+            spin.set_value(n as f64);
+            spin.unblock_signal(handler);
+        }
+        self.preview_check.block_signal(&self.preview_handler);
+        self.preview_check.set_active(view.replication == [2, 2, 2]);
+        self.preview_check.unblock_signal(&self.preview_handler);
+    }
+
+    /// Fit the frame slider to the tab's trajectory, or hide it when the
+    /// tab holds a single structure.
+    pub fn sync_frames(&self, tab: &TabState) {
+        let n = tab.frames.len();
+        self.frame_box.set_visible(n > 1);
+        if n > 1 {
+            // Shrinking the range can move the value, so keep the handler
+            // blocked for both writes.
+            self.frame_scale.block_signal(&self.frame_handler);
+            self.frame_scale.adjustment().set_upper((n - 1) as f64);
+            self.frame_scale.set_value(tab.current_frame as f64);
+            self.frame_scale.unblock_signal(&self.frame_handler);
+        }
+    }
+
+    /// Show the nearest neighbors of `center` (see `NeighborTable::center`)
+    /// in the sidebar, or clear the table.
+    pub fn show_neighbors(&self, state: &Rc<RefCell<AppState>>, center: Option<PickedAtom>) {
+        self.neighbors.center.set(center);
+        self.neighbors.refresh(state);
     }
 
     fn set_blocked(scale: &Scale, handler: &SignalHandlerId, value: f64) {
@@ -50,6 +102,112 @@ impl SidebarHandles {
     }
 }
 
+impl NeighborTable {
+    fn refresh(&self, state: &Rc<RefCell<AppState>>) {
+        while let Some(child) = self.rows.first_child() {
+            self.rows.remove(&child);
+        }
+
+        let (neighbors, fmt) = {
+            let st = state.borrow();
+            let count = st.config.neighbor_list_count;
+            let neighbors = match (self.center.get(), &st.active_tab().structure) {
+                (Some((index, _)), Some(structure)) if index < structure.atoms.len() => Some((
+                    format!("{}{}", structure.atoms[index].element, index),
+                    structure
+                        .neighbors_of(index, count)
+                        .into_iter()
+                        .map(|(j, d, disp)| (j, structure.atoms[j].element.clone(), d, disp))
+                        .collect::<Vec<_>>(),
+                )),
+                _ => None,
+            };
+            (neighbors, st.config.report_format.clone())
+        };
+
+        let Some((name, neighbors)) = neighbors else {
+            let lbl = Label::new(Some("(Click an atom to list its neighbors)"));
+            lbl.set_opacity(0.6);
+            self.rows.append(&lbl);
+            return;
+        };
+
+        let header = Label::new(Some(&format!("Neighbors of {}", name)));
+        header.set_xalign(0.0);
+        self.rows.append(&header);
+
+        for (j, element, dist, disp) in neighbors {
+            let btn = Button::with_label(&format!("{}{}    {}", element, j, fmt.length(dist)));
+            btn.add_css_class("flat");
+            btn.set_tooltip_text(Some("Add to selection"));
+            if let Some(lbl) = btn.child().and_downcast::<Label>() {
+                lbl.set_xalign(0.0);
+            }
+
+            let st = state.clone();
+            let table = self.clone();
+            btn.connect_clicked(move |_| table.select_neighbor(&st, j, disp));
+            self.rows.append(&btn);
+        }
+    }
+
+    /// Add the instance of atom `index` sitting at `disp` from the picked
+    /// atom to the selection, so the Structure Info tab reports the
+    /// distance. Falls back to the nearest drawn instance when that exact
+    /// image isn't on screen.
+    fn select_neighbor(&self, state: &Rc<RefCell<AppState>>, index: usize, disp: [f64; 3]) {
+        let (Some(da), Some((_, origin))) = (
+            self.notebook
+                .upgrade()
+                .and_then(|nb| crate::ui::get_active_drawing_area(&nb)),
+            self.center.get(),
+        ) else {
+            return;
+        };
+        let target = [
+            origin[0] + disp[0],
+            origin[1] + disp[1],
+            origin[2] + disp[2],
+        ];
+
+        let mut st = state.borrow_mut();
+        let show_ghosts = st.active_tab().view.show_full_unit_cell;
+        let (atoms, _, _) = crate::rendering::scene::calculate_scene(
+            st.active_tab(),
+            &st.config,
+            da.width() as f64,
+            da.height() as f64,
+            false,
+            None,
+            None,
+        );
+        let dist2 = |p: [f64; 3]| (0..3).map(|k| (p[k] - target[k]).powi(2)).sum::<f64>();
+        let Some(hit) = atoms
+            .into_iter()
+            .filter(|a| a.original_index == index && a.is_pickable(show_ghosts))
+            .min_by(|a, b| dist2(a.cart_pos).total_cmp(&dist2(b.cart_pos)))
+        else {
+            return;
+        };
+
+        if !st
+            .active_tab()
+            .interaction
+            .selected
+            .contains_key(&hit.unique_id)
+        {
+            st.toggle_selection(SelectedAtom {
+                unique_id: hit.unique_id,
+                original_index: hit.original_index,
+                cart_pos: hit.cart_pos,
+                element: hit.element,
+            });
+        }
+        crate::ui::interactions::report_selection(st.active_tab(), &st.config.report_format);
+        da.queue_draw();
+    }
+}
+
 /// Same as the build()-local `create_slider` helper, but returns the underlying
 /// Scale and the value-changed handler ID so callers that need to drive the
 /// slider programmatically can block the handler during writes.
@@ -180,12 +338,12 @@ pub fn build(
     let cb_z = queue_active_draw;
     let (zoom_box, zoom_scale, zoom_handler) = create_tracked_slider(
         "Zoom",
-        0.1,
-        5.0,
+        ZOOM_MIN,
+        ZOOM_MAX,
         0.1,
         state.borrow().active_tab().view.zoom,
         Box::new(move |v| {
-            s_z.borrow_mut().active_tab_mut().view.zoom = v;
+            s_z.borrow_mut().active_tab_mut().view.set_zoom(v);
             cb_z(&nb_z);
         }),
     );
@@ -255,6 +413,106 @@ pub fn build(
     );
     controls_box.append(&rz_box);
 
+    // Trajectory frames; frames may differ in atom count, in which case the
+    // atom list is rebuilt for the new frame.
+    let s_fr = state.clone();
+    let nb_fr = nb_weak.clone();
+    let list_fr = atoms_list_container.clone();
+    let (frame_box, frame_scale, frame_handler) = create_tracked_slider(
+        "Frame",
+        0.0,
+        1.0,
+        1.0,
+        0.0,
+        Box::new(move |v| {
+            let mut st = s_fr.borrow_mut();
+            let tab = st.active_tab_mut();
+            let before = tab.structure.as_ref().map(|s| s.atoms.len());
+            if !tab.show_frame(v as usize) {
+                return;
+            }
+            let resized = tab.structure.as_ref().map(|s| s.atoms.len()) != before;
+            drop(st);
+            if let Some(nb) = nb_fr.upgrade() {
+                if resized {
+                    refresh_atom_list(&list_fr, s_fr.clone(), &nb);
+                }
+                if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                    da.queue_draw();
+                }
+            }
+        }),
+    );
+    frame_scale.set_digits(0);
+    frame_box.set_visible(false);
+    controls_box.append(&frame_box);
+
+    // Only takes effect with View → Stereo 3D enabled.
+    let s_eye = state.clone();
+    let nb_eye = nb_weak.clone();
+    let cb_eye = queue_active_draw;
+    controls_box.append(&create_slider(
+        "Stereo Separation (°)",
+        1.0,
+        10.0,
+        0.5,
+        state.borrow().active_tab().view.eye_separation_deg,
+        Box::new(move |v| {
+            s_eye.borrow_mut().active_tab_mut().view.eye_separation_deg = v;
+            cb_eye(&nb_eye);
+        }),
+    ));
+
+    // Only takes effect with View → Show/Hide Scale Bar enabled.
+    let s_bar = state.clone();
+    let nb_bar = nb_weak.clone();
+    let cb_bar = queue_active_draw;
+    controls_box.append(&create_slider(
+        "Scale Bar (Å)",
+        1.0,
+        50.0,
+        1.0,
+        state.borrow().active_tab().view.scale_bar_length,
+        Box::new(move |v| {
+            s_bar.borrow_mut().active_tab_mut().view.scale_bar_length = v;
+            cb_bar(&nb_bar);
+        }),
+    ));
+
+    // Translucent periodic images around the cell; the structure itself
+    // stays one cell.
+    let rep_row = GtkBox::new(Orientation::Horizontal, 4);
+    rep_row.append(&Label::new(Some("Replicate View")));
+    let rep_spins: Vec<(SpinButton, SignalHandlerId)> = (0..3)
+        .map(|k| {
+            let spin = SpinButton::with_range(1.0, 5.0, 1.0);
+            spin.set_value(state.borrow().active_tab().view.replication[k] as f64);
+            let s_rep = state.clone();
+            let nb_rep = nb_weak.clone();
+            let cb_rep = queue_active_draw;
+            let handler = spin.connect_value_changed(move |sp| {
+                s_rep.borrow_mut().active_tab_mut().view.replication[k] = sp.value() as u32;
+                cb_rep(&nb_rep);
+            });
+            rep_row.append(&spin);
+            (spin, handler)
+        })
+        .collect();
+    controls_box.append(&rep_row);
+
+    let check_preview = CheckButton::with_label("2×2×2 Preview");
+    check_preview.set_active(state.borrow().active_tab().view.replication == [2, 2, 2]);
+    let spins_preview: Vec<SpinButton> = rep_spins.iter().map(|(s, _)| s.clone()).collect();
+    let preview_handler = check_preview.connect_toggled(move |c| {
+        let n = if c.is_active() { 2.0 } else { 1.0 };
+        for spin in &spins_preview {
+            spin.set_value(n);
+        }
+    });
+    controls_box.append(&check_preview);
+
+    let (neighbors_expander, neighbors) = build_neighbor_table(&state, notebook);
+
     let handles = SidebarHandles {
         zoom_scale,
         rot_x_scale,
@@ -264,6 +522,13 @@ pub fn build(
         rot_x_handler,
         rot_y_handler,
         rot_z_handler,
+        frame_box,
+        frame_scale,
+        frame_handler,
+        rep_spins,
+        preview_check: check_preview,
+        preview_handler,
+        neighbors,
     };
 
     controls_expander.set_child(Some(&controls_box));
@@ -349,6 +614,51 @@ pub fn build(
     frame_mat.set_child(Some(&vbox_mat));
     style_box.append(&frame_mat);
 
+    // --- LIGHTING ---
+    let frame_light = Frame::new(Some("Lighting"));
+    let vbox_light = GtkBox::new(Orientation::Vertical, 10);
+    vbox_light.set_margin_top(10);
+    vbox_light.set_margin_bottom(10);
+    vbox_light.set_margin_start(10);
+    vbox_light.set_margin_end(10);
+
+    let light = state.borrow().active_tab().style.light;
+    /// (label, min, max, step, current value, setter)
+    type LightSlider = (&'static str, f64, f64, f64, f64, fn(&mut Light, f64));
+    let light_sliders: [LightSlider; 3] = [
+        ("Azimuth (°)", 0.0, 360.0, 5.0, light.azimuth, |l, v| {
+            l.azimuth = v
+        }),
+        ("Elevation (°)", 0.0, 90.0, 5.0, light.elevation, |l, v| {
+            l.elevation = v
+        }),
+        ("Intensity", 0.2, 2.0, 0.05, light.intensity, |l, v| {
+            l.intensity = v
+        }),
+    ];
+    for (label, min, max, step, val, set) in light_sliders {
+        let s_light = state.clone();
+        let nb_light = nb_weak.clone();
+        let cb_light = queue_active_draw;
+        vbox_light.append(&create_slider(
+            label,
+            min,
+            max,
+            step,
+            val,
+            Box::new(move |v| {
+                let mut st = s_light.borrow_mut();
+                let tab = st.active_tab_mut();
+                set(&mut tab.style.light, v);
+                tab.style.atom_cache.borrow_mut().clear();
+                cb_light(&nb_light);
+            }),
+        ));
+    }
+
+    frame_light.set_child(Some(&vbox_light));
+    style_box.append(&frame_light);
+
     // --- ATOM SIZE ---
     let frame_atom = Frame::new(Some("Atom Size"));
     let vbox_atom = GtkBox::new(Orientation::Vertical, 10);
@@ -373,7 +683,7 @@ pub fn build(
     ));
 
     // --- Show Labels Toggle ---
-    let check_labels = CheckButton::with_label("Show Atomic Symbols");
+    let check_labels = CheckButton::with_label("Show Labels");
     // Set initial state
     check_labels.set_active(state.borrow().active_tab().style.show_labels);
 
@@ -388,6 +698,79 @@ pub fn build(
         cb_lbl(&nb_lbl);
     });
     vbox_atom.append(&check_labels);
+
+    let label_row = GtkBox::new(Orientation::Horizontal, 10);
+    label_row.append(&Label::new(Some("Label:")));
+    let label_dropdown = DropDown::from_strings(&["Symbol", "Index", "Symbol + Index"]);
+    label_dropdown.set_selected(match state.borrow().active_tab().style.label_content {
+        LabelContent::Symbol => 0,
+        LabelContent::Index => 1,
+        LabelContent::SymbolIndex => 2,
+    });
+    label_dropdown.set_hexpand(true);
+    let s_lc = state.clone();
+    let nb_lc = nb_weak.clone();
+    let cb_lc = queue_active_draw;
+    label_dropdown.connect_selected_notify(move |dd| {
+        let content = match dd.selected() {
+            1 => LabelContent::Index,
+            2 => LabelContent::SymbolIndex,
+            _ => LabelContent::Symbol,
+        };
+        s_lc.borrow_mut().active_tab_mut().style.label_content = content;
+        cb_lc(&nb_lc);
+    });
+    label_row.append(&label_dropdown);
+    vbox_atom.append(&label_row);
+
+    let s_lfs = state.clone();
+    let nb_lfs = nb_weak.clone();
+    let cb_lfs = queue_active_draw;
+    vbox_atom.append(&create_slider(
+        "Label Size",
+        6.0,
+        32.0,
+        1.0,
+        state.borrow().active_tab().style.label_font_size,
+        Box::new(move |v| {
+            s_lfs.borrow_mut().active_tab_mut().style.label_font_size = v;
+            cb_lfs(&nb_lfs);
+        }),
+    ));
+
+    // --- Thermal Ellipsoids (atoms without ADPs stay spheres) ---
+    let check_adp = CheckButton::with_label("Thermal Ellipsoids (ADP)");
+    check_adp.set_active(state.borrow().active_tab().style.show_ellipsoids);
+
+    let s_adp = state.clone();
+    let nb_adp = nb_weak.clone();
+    let cb_adp = queue_active_draw;
+    check_adp.connect_toggled(move |btn| {
+        let mut st = s_adp.borrow_mut();
+        st.active_tab_mut().style.show_ellipsoids = btn.is_active();
+        drop(st);
+        cb_adp(&nb_adp);
+    });
+    vbox_atom.append(&check_adp);
+
+    let s_prob = state.clone();
+    let nb_prob = nb_weak.clone();
+    let cb_prob = queue_active_draw;
+    vbox_atom.append(&create_slider(
+        "Probability (%)",
+        10.0,
+        99.0,
+        1.0,
+        state.borrow().active_tab().style.ellipsoid_probability * 100.0,
+        Box::new(move |v| {
+            s_prob
+                .borrow_mut()
+                .active_tab_mut()
+                .style
+                .ellipsoid_probability = v / 100.0;
+            cb_prob(&nb_prob);
+        }),
+    ));
     frame_atom.set_child(Some(&vbox_atom));
     style_box.append(&frame_atom);
 
@@ -460,10 +843,110 @@ pub fn build(
     box_bcol.append(&btn_bcol);
     vbox_bonds.append(&box_bcol);
 
+    // Split-color bonds: each half takes its atom's color, and the single
+    // bond color above no longer applies.
+    let check_split = CheckButton::with_label("Color by Atoms (Split)");
+    let split_on = state.borrow().active_tab().style.bond_split_color;
+    check_split.set_active(split_on);
+    btn_bcol.set_sensitive(!split_on);
+
+    let s_split = state.clone();
+    let nb_split = nb_weak.clone();
+    let cb_split = queue_active_draw;
+    let btn_bcol_split = btn_bcol.clone();
+    check_split.connect_toggled(move |btn| {
+        s_split.borrow_mut().active_tab_mut().style.bond_split_color = btn.is_active();
+        btn_bcol_split.set_sensitive(!btn.is_active());
+        cb_split(&nb_split);
+    });
+    vbox_bonds.append(&check_split);
+
+    // Per-pair limits and rules for cases the single tolerance gets wrong
+    // (long ionic bonds missed, or spurious short contacts drawn).
+    let btn_pairs = Button::with_label("Pair Cutoffs & Rules…");
+    let s_pairs = state.clone();
+    let nb_pairs = nb_weak.clone();
+    btn_pairs.connect_clicked(move |btn| {
+        let win = btn.root().and_then(|r| r.downcast::<gtk4::Window>().ok());
+        if let (Some(win), Some(nb)) = (win, nb_pairs.upgrade()) {
+            crate::ui::dialogs::bond_cutoffs_dlg::show(&win, s_pairs.clone(), &nb);
+        }
+    });
+    vbox_bonds.append(&btn_pairs);
+
     frame_bonds.set_child(Some(&vbox_bonds));
     style_box.append(&frame_bonds);
 
+    // --- BACKGROUND ---
+    // Flat color comes from Preferences; this adds an optional per-tab
+    // top→bottom gradient for publication figures.
+    let frame_bg = Frame::new(Some("Background"));
+    let vbox_bg = GtkBox::new(Orientation::Vertical, 10);
+    vbox_bg.set_margin_top(10);
+    vbox_bg.set_margin_bottom(10);
+    vbox_bg.set_margin_start(10);
+    vbox_bg.set_margin_end(10);
+
+    const DEFAULT_GRADIENT: Gradient = ((0.98, 0.98, 0.98), (0.74, 0.77, 0.82));
+    let current_grad = state.borrow().active_tab().style.background_gradient;
+    let (init_top, init_bottom) = current_grad.unwrap_or(DEFAULT_GRADIENT);
+    let to_rgba = |c: (f64, f64, f64)| gdk::RGBA::new(c.0 as f32, c.1 as f32, c.2 as f32, 1.0);
+    let from_rgba = |c: gdk::RGBA| (c.red() as f64, c.green() as f64, c.blue() as f64);
+
+    let check_grad = CheckButton::with_label("Vertical Gradient");
+    check_grad.set_active(current_grad.is_some());
+    vbox_bg.append(&check_grad);
+
+    let box_grad = GtkBox::new(Orientation::Horizontal, 10);
+    box_grad.append(&Label::new(Some("Top:")));
+    let btn_top = ColorButton::new();
+    btn_top.set_rgba(&to_rgba(init_top));
+    box_grad.append(&btn_top);
+    box_grad.append(&Label::new(Some("Bottom:")));
+    let btn_bottom = ColorButton::new();
+    btn_bottom.set_rgba(&to_rgba(init_bottom));
+    box_grad.append(&btn_bottom);
+    box_grad.set_sensitive(current_grad.is_some());
+    vbox_bg.append(&box_grad);
+
+    // One writer for all three widgets: read them back and store the result.
+    let apply_gradient = {
+        let s = state.clone();
+        let nb = nb_weak.clone();
+        let check = check_grad.downgrade();
+        let top = btn_top.downgrade();
+        let bottom = btn_bottom.downgrade();
+        move || {
+            let (Some(check), Some(top), Some(bottom)) =
+                (check.upgrade(), top.upgrade(), bottom.upgrade())
+            else {
+                return;
+            };
+            let grad = check
+                .is_active()
+                .then(|| (from_rgba(top.rgba()), from_rgba(bottom.rgba())));
+            s.borrow_mut().active_tab_mut().style.background_gradient = grad;
+            queue_active_draw(&nb);
+        }
+    };
+    let apply_check = apply_gradient.clone();
+    let box_grad_w = box_grad.downgrade();
+    check_grad.connect_toggled(move |btn| {
+        if let Some(b) = box_grad_w.upgrade() {
+            b.set_sensitive(btn.is_active());
+        }
+        apply_check();
+    });
+    let apply_top = apply_gradient.clone();
+    btn_top.connect_color_set(move |_| apply_top());
+    btn_bottom.connect_color_set(move |_| apply_gradient());
+
+    frame_bg.set_child(Some(&vbox_bg));
+    style_box.append(&frame_bg);
+
     style_expander.set_child(Some(&style_box));
+    root_vbox.append(&build_atom_search(&state, notebook));
+    root_vbox.append(&neighbors_expander);
     root_vbox.append(&style_expander);
     root_vbox.append(&controls_expander);
 
@@ -483,12 +966,13 @@ pub fn build(
     let mode_row = GtkBox::new(Orientation::Horizontal, 10);
     mode_row.append(&Label::new(Some("Color Mode:")));
 
-    let mode_dropdown = DropDown::from_strings(&["Element Colors", "Bond Valence"]);
+    let mode_dropdown = DropDown::from_strings(&["Element Colors", "Bond Valence", "Displacement"]);
 
     // Set initial selection based on current mode
     mode_dropdown.set_selected(match state.borrow().active_tab().style.color_mode {
         ColorMode::Element => 0,
         ColorMode::BondValence => 1,
+        ColorMode::Displacement => 2,
         _ => 0,
     });
 
@@ -501,10 +985,12 @@ pub fn build(
         let mode = match dd.selected() {
             0 => ColorMode::Element,
             1 => ColorMode::BondValence,
+            2 => ColorMode::Displacement,
             _ => ColorMode::Element,
         };
 
         let mut st = state_mode.borrow_mut();
+        let fmt = st.config.report_format.clone();
         let tab = st.active_tab_mut();
         tab.style.color_mode = mode;
 
@@ -515,9 +1001,12 @@ pub fn build(
 
             // Show BVS report in Structure Info tab
             if let Some(ref structure) = tab.structure {
+                use crate::physics::bond_valence::with_oxidation_overrides;
                 use crate::utils::report;
-                let report_text = report::bvs_analysis(structure);
-                console::info_report(&report_text);
+                let structure =
+                    with_oxidation_overrides(structure, &tab.style.bvs_oxidation_overrides);
+                let (report_text, tagged) = report::bvs_analysis_tagged(&structure, &fmt);
+                console::info_report_tagged(&report_text, &tagged);
             }
         }
 
@@ -616,6 +1105,16 @@ pub fn build(
     bvs_box.append(&good_row);
     bvs_box.append(&warn_row);
 
+    let check_values = CheckButton::with_label("Show BVS Values on Atoms");
+    check_values.set_active(state.borrow().active_tab().view.show_bvs_values);
+    let s_values = state.clone();
+    let nb_values = nb_weak.clone();
+    check_values.connect_toggled(move |btn| {
+        s_values.borrow_mut().active_tab_mut().view.show_bvs_values = btn.is_active();
+        queue_active_draw(&nb_values);
+    });
+    bvs_box.append(&check_values);
+
     // Help Text
     // let help_text = Label::new(Some(
     // "💡 Load a structure (e.g., Li₂O, NaCl)\n\n\
@@ -658,6 +1157,328 @@ pub fn build(
     (scroll, atoms_list_container, handles)
 }
 
+/// Number of on/off ticks the highlight ring flashes after a search.
+const HIGHLIGHT_FLASH_TICKS: u32 = 6;
+
+/// "Find Atoms" section: highlights atoms matching an element symbol or
+/// index range (see `Structure::find_atoms`) and optionally centers the
+/// view on the first match.
+fn build_atom_search(state: &Rc<RefCell<AppState>>, notebook: &Notebook) -> Expander {
+    let expander = Expander::new(Some("Find Atoms"));
+    expander.set_expanded(false);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 8);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+
+    let entry = SearchEntry::new();
+    entry.set_placeholder_text(Some("e.g. O, 0-7, Fe 12"));
+    vbox.append(&entry);
+
+    let check_center = CheckButton::with_label("Center on first match");
+    vbox.append(&check_center);
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    status.set_opacity(0.7);
+    vbox.append(&status);
+
+    let st = state.clone();
+    let nb_weak = notebook.downgrade();
+    let flash_timer = Rc::new(RefCell::new(None));
+    entry.connect_search_changed(move |e| {
+        let Some(da) = nb_weak
+            .upgrade()
+            .and_then(|nb| crate::ui::get_active_drawing_area(&nb))
+        else {
+            return;
+        };
+        let query = e.text();
+
+        let result = {
+            let s = st.borrow();
+            match &s.active_tab().structure {
+                Some(structure) if !query.trim().is_empty() => Some(structure.find_atoms(&query)),
+                _ => None,
+            }
+        };
+
+        let mut s = st.borrow_mut();
+        match result {
+            None => {
+                s.active_tab_mut().interaction.highlighted.clear();
+                status.set_text("");
+            }
+            Some(Err(msg)) => {
+                s.active_tab_mut().interaction.highlighted.clear();
+                status.set_text(&msg);
+            }
+            Some(Ok(hits)) => {
+                status.set_text(&format!("{} match(es)", hits.len()));
+                if check_center.is_active() {
+                    if let Some(&first) = hits.first() {
+                        center_on_atom(&mut s, &da, first);
+                    }
+                }
+                let tab = s.active_tab_mut();
+                tab.interaction.highlighted = hits.into_iter().collect();
+                if !tab.interaction.highlighted.is_empty() {
+                    drop(s);
+                    flash_highlight(st.clone(), da.clone(), &flash_timer);
+                }
+            }
+        }
+        da.queue_draw();
+    });
+
+    expander.set_child(Some(&vbox));
+    expander
+}
+
+/// "Nearest Neighbors" section: a count spinner (persisted in the config)
+/// above the table filled by `SidebarHandles::show_neighbors`.
+fn build_neighbor_table(
+    state: &Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+) -> (Expander, NeighborTable) {
+    let expander = Expander::new(Some("Nearest Neighbors"));
+    expander.set_expanded(false);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 8);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+
+    let count_row = GtkBox::new(Orientation::Horizontal, 8);
+    count_row.append(&Label::new(Some("Show")));
+    let count_spin = SpinButton::with_range(1.0, 50.0, 1.0);
+    count_spin.set_value(state.borrow().config.neighbor_list_count as f64);
+    count_row.append(&count_spin);
+    count_row.append(&Label::new(Some("neighbors")));
+    vbox.append(&count_row);
+
+    let table = NeighborTable {
+        rows: GtkBox::new(Orientation::Vertical, 2),
+        center: Rc::new(Cell::new(None)),
+        notebook: notebook.downgrade(),
+    };
+    vbox.append(&table.rows);
+    table.refresh(state);
+
+    let st = state.clone();
+    let table_c = table.clone();
+    count_spin.connect_value_changed(move |spin| {
+        {
+            let mut s = st.borrow_mut();
+            s.config.neighbor_list_count = spin.value() as usize;
+            s.save_config();
+        }
+        table_c.refresh(&st);
+    });
+
+    expander.set_child(Some(&vbox));
+    (expander, table)
+}
+
+/// Shift the pan so the base (non-ghost) instance of atom `index` lands at the
+/// center of `da`. Zoom and rotation are untouched.
+fn center_on_atom(st: &mut AppState, da: &gtk4::DrawingArea, index: usize) {
+    let (w, h) = (da.width() as f64, da.height() as f64);
+    let (atoms, _, bounds) = crate::rendering::scene::calculate_scene(
+        st.active_tab(),
+        &st.config,
+        w,
+        h,
+        false,
+        None,
+        None,
+    );
+    if let Some(a) = atoms
+        .iter()
+        .find(|a| a.original_index == index && a.is_home())
+    {
+        let view = &mut st.active_tab_mut().view;
+        view.pan_x += (a.screen_pos[0] - w / 2.0) / bounds.scale;
+        view.pan_y += (a.screen_pos[1] - h / 2.0) / bounds.scale;
+    }
+}
+
+/// Blink the search highlight a few times so matches are easy to spot, then
+/// leave it on. Stops early if the user switches tabs. `timer` holds the
+/// running blink, which a new search cancels so timers never overlap.
+fn flash_highlight(
+    state: Rc<RefCell<AppState>>,
+    da: gtk4::DrawingArea,
+    timer: &Rc<RefCell<Option<gtk4::glib::SourceId>>>,
+) {
+    if let Some(id) = timer.borrow_mut().take() {
+        id.remove();
+    }
+    let tab_index = state.borrow().active_tab_index;
+    let mut tick = 0;
+    let slot = timer.clone();
+    let id = gtk4::glib::timeout_add_local(std::time::Duration::from_millis(120), move || {
+        tick += 1;
+        let mut st = state.borrow_mut();
+        if st.active_tab_index != tab_index || tab_index >= st.tabs.len() {
+            slot.borrow_mut().take();
+            return gtk4::glib::ControlFlow::Break;
+        }
+        let done = tick >= HIGHLIGHT_FLASH_TICKS;
+        st.tabs[tab_index].interaction.highlight_dimmed = !done && tick % 2 == 1;
+        drop(st);
+        da.queue_draw();
+        if done {
+            slot.borrow_mut().take();
+            gtk4::glib::ControlFlow::Break
+        } else {
+            gtk4::glib::ControlFlow::Continue
+        }
+    });
+    *timer.borrow_mut() = Some(id);
+}
+
+/// Preset picker + "Save" button above the per-element color rows. The list
+/// holds the built-in schemes followed by the user's saved presets; picking
+/// one rewrites `tab.style.element_colors` and rebuilds the rows.
+fn build_color_preset_row(
+    container: &GtkBox,
+    state: &Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 8);
+    row.set_margin_bottom(6);
+    row.append(&Label::new(Some("Preset:")));
+
+    let mut names: Vec<String> = vec!["Apply…".to_string()];
+    names.extend(ColorScheme::ALL.iter().map(|s| s.label().to_string()));
+    names.extend(
+        state
+            .borrow()
+            .config
+            .color_presets
+            .iter()
+            .map(|p| p.name.clone()),
+    );
+    let name_refs: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+    let dropdown = DropDown::from_strings(&name_refs);
+    dropdown.set_hexpand(true);
+    row.append(&dropdown);
+
+    let btn_save = Button::with_label("Save…");
+    btn_save.set_tooltip_text(Some("Save the current element colors as a named preset"));
+    row.append(&btn_save);
+
+    let s_dd = state.clone();
+    let nb_dd = notebook.downgrade();
+    let container_dd = container.downgrade();
+    dropdown.connect_selected_notify(move |dd| {
+        let idx = dd.selected() as usize;
+        if idx == 0 {
+            return;
+        }
+        {
+            let mut st = s_dd.borrow_mut();
+            let n_builtin = ColorScheme::ALL.len();
+            let colors = if idx <= n_builtin {
+                let scheme = ColorScheme::ALL[idx - 1];
+                st.active_tab()
+                    .element_symbols()
+                    .into_iter()
+                    .map(|el| {
+                        let rgb = get_element_color(&el, scheme);
+                        (el, rgb)
+                    })
+                    .collect()
+            } else {
+                match st.config.color_presets.get(idx - 1 - n_builtin) {
+                    Some(p) => p.colors.clone(),
+                    None => return,
+                }
+            };
+            st.active_tab_mut().set_element_colors(colors);
+        }
+        rebuild_after_preset_change(&s_dd, &nb_dd, &container_dd);
+    });
+
+    let s_save = state.clone();
+    let nb_save = notebook.downgrade();
+    let container_save = container.downgrade();
+    btn_save.connect_clicked(move |btn| {
+        let dialog = gtk4::Dialog::builder()
+            .title("Save Color Preset")
+            .modal(true)
+            .default_width(300)
+            .build();
+        if let Some(win) = btn.root().and_then(|r| r.downcast::<gtk4::Window>().ok()) {
+            dialog.set_transient_for(Some(&win));
+        }
+        let entry = gtk4::Entry::new();
+        entry.set_placeholder_text(Some("Preset name"));
+        entry.set_activates_default(true);
+        entry.set_margin_top(10);
+        entry.set_margin_bottom(10);
+        entry.set_margin_start(10);
+        entry.set_margin_end(10);
+        dialog.content_area().append(&entry);
+        dialog.add_button("Cancel", gtk4::ResponseType::Cancel);
+        dialog.add_button("Save", gtk4::ResponseType::Ok);
+        dialog.set_default_response(gtk4::ResponseType::Ok);
+
+        let s = s_save.clone();
+        let nb = nb_save.clone();
+        let container = container_save.clone();
+        dialog.connect_response(move |dlg, resp| {
+            let name = entry.text().trim().to_string();
+            if resp == gtk4::ResponseType::Ok && !name.is_empty() {
+                {
+                    let mut st = s.borrow_mut();
+                    let colors = st
+                        .active_tab()
+                        .effective_element_colors(st.config.color_scheme);
+                    st.config.upsert_color_preset(crate::config::ColorPreset {
+                        name: name.clone(),
+                        colors,
+                    });
+                    let msg = st.save_config();
+                    console::log_info(&msg);
+                }
+                console::info(&format!("Saved color preset \"{}\".", name));
+                rebuild_after_preset_change(&s, &nb, &container);
+            }
+            dlg.close();
+        });
+        dialog.show();
+    });
+
+    row
+}
+
+/// Redraw the view and rebuild the element-color rows so every color button
+/// reflects the new mapping. Deferred to idle because it's called from
+/// signal handlers of widgets that the rebuild destroys.
+fn rebuild_after_preset_change(
+    state: &Rc<RefCell<AppState>>,
+    notebook: &gtk4::glib::WeakRef<Notebook>,
+    container: &gtk4::glib::WeakRef<GtkBox>,
+) {
+    let state = state.clone();
+    let notebook = notebook.clone();
+    let container = container.clone();
+    gtk4::glib::idle_add_local_once(move || {
+        let (Some(nb), Some(c)) = (notebook.upgrade(), container.upgrade()) else {
+            return;
+        };
+        refresh_atom_list(&c, state, &nb);
+        if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+            da.queue_draw();
+        }
+    });
+}
+
 /// Public helper to rebuild the list of atom colors dynamically.
 /// SOTA: adds CN label on poly checkbox, transparency slider per element,
 /// and an "Auto-detect Polyhedra" button at the top.
@@ -703,6 +1524,9 @@ pub fn refresh_atom_list(container: &GtkBox, state: Rc<RefCell<AppState>>, noteb
 
     let nb_weak = notebook.downgrade();
 
+    // ── Color presets ────────────────────────────────────────────────────────
+    container.append(&build_color_preset_row(container, &state, notebook));
+
     // ── Auto-detect button ───────────────────────────────────────────────────
     let btn_auto = Button::with_label("Auto-detect Polyhedra");
     btn_auto.set_tooltip_text(Some(
@@ -1021,6 +1845,40 @@ pub fn refresh_atom_list(container: &GtkBox, state: Rc<RefCell<AppState>>, noteb
             }
         });
         row.append(&btn_reset);
+
+        // Show/hide the whole species (drawing only; analyses keep it).
+        let check_show = gtk4::CheckButton::with_label("Show");
+        check_show.set_active(
+            !state
+                .borrow()
+                .active_tab()
+                .style
+                .hidden_elements
+                .contains(&elem),
+        );
+        let s_show = state.clone();
+        let nb_show = nb_weak.clone();
+        let elem_show = elem.clone();
+        check_show.connect_toggled(move |c| {
+            let mut st = s_show.borrow_mut();
+            let tab = st.active_tab_mut();
+            if c.is_active() {
+                tab.style.hidden_elements.remove(&elem_show);
+            } else {
+                tab.style.hidden_elements.insert(elem_show.clone());
+                // A hidden atom can't stay selected.
+                tab.interaction
+                    .selected
+                    .retain(|_, a| a.element != elem_show);
+            }
+            drop(st);
+            if let Some(nb) = nb_show.upgrade() {
+                if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                    da.queue_draw();
+                }
+            }
+        });
+        row.append(&check_show);
         col.append(&row);
 
         // --- Polyhedra row ---
@@ -1082,6 +1940,48 @@ pub fn refresh_atom_list(container: &GtkBox, state: Rc<RefCell<AppState>>, noteb
 
         poly_row.append(&cn_label);
         poly_row.append(&check_poly);
+
+        // Oxidation state for BVS: "Auto" is charge-balanced assignment.
+        let ox_states = crate::physics::bond_valence::plausible_oxidation_states(&elem);
+        if !ox_states.is_empty() {
+            let labels: Vec<String> = std::iter::once("Auto".to_string())
+                .chain(ox_states.iter().map(|v| format!("{:+}", v)))
+                .collect();
+            let label_refs: Vec<&str> = labels.iter().map(String::as_str).collect();
+            let ox_dropdown = DropDown::from_strings(&label_refs);
+            ox_dropdown.set_tooltip_text(Some("Oxidation state used for bond valence sums"));
+            let current = state
+                .borrow()
+                .active_tab()
+                .style
+                .bvs_oxidation_overrides
+                .get(&elem)
+                .and_then(|v| ox_states.iter().position(|s| s == v))
+                .map_or(0, |k| k + 1);
+            ox_dropdown.set_selected(current as u32);
+
+            let s_ox = state.clone();
+            let elem_ox = elem.clone();
+            let nb_ox = nb_weak.clone();
+            ox_dropdown.connect_selected_notify(move |dd| {
+                let mut st = s_ox.borrow_mut();
+                let tab = st.active_tab_mut();
+                let overrides = &mut tab.style.bvs_oxidation_overrides;
+                match (dd.selected() as usize).checked_sub(1) {
+                    Some(k) => overrides.insert(elem_ox.clone(), ox_states[k]),
+                    None => overrides.remove(&elem_ox),
+                };
+                tab.invalidate_bvs_cache();
+                drop(st);
+                if let Some(nb) = nb_ox.upgrade() {
+                    if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                        da.queue_draw();
+                    }
+                }
+            });
+            poly_row.append(&Label::new(Some("Ox:")));
+            poly_row.append(&ox_dropdown);
+        }
         col.append(&poly_row);
 
         container.append(&col);
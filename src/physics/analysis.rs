@@ -1,6 +1,13 @@
 pub mod bravais;
 pub mod charge_density;
+pub mod compare;
+pub mod coordination;
+pub mod ewald;
 pub mod kpath;
+pub mod pdf;
+pub mod rdf;
+pub mod reflections;
+pub mod stereographic;
 pub mod symmetry;
 pub mod voids;
 pub mod voronoi;
@@ -0,0 +1,445 @@
+// src/physics/analysis/compare.rs
+//
+// Per-atom comparison of two structures with the same composition, e.g. a
+// relaxed cell against its starting point or two conformers. Atoms are
+// paired by nearest same-element neighbour, or by index when the element
+// sequences agree and that fits at least as well; the second structure is
+// superposed on the first by the Kabsch rotation about the centroids, and
+// what is left over is the per-atom displacement.
+//
+// For two periodic structures every partner is first moved to its periodic
+// image closest to the atom it is paired with, so an atom that crossed a
+// cell face between the two files is not reported as a cell-length jump.
+
+use crate::model::structure::Structure;
+use crate::utils::linalg::PeriodicCell;
+use nalgebra::{Matrix3, Vector3};
+use std::collections::{BTreeMap, HashMap};
+
+/// Same-element pairs closer than this (Å) are found through a grid;
+/// atoms that moved further are paired among themselves afterwards.
+const PAIR_RADIUS: f64 = 2.0;
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// For each atom of `a`, the index of its partner in `b`.
+    pub partner: Vec<usize>,
+    /// Distance (Å) from each atom of `a` to its superposed partner.
+    pub displacement: Vec<f64>,
+    pub rmsd: f64,
+    pub max_displacement: f64,
+    /// Atom of `a` with the largest displacement.
+    pub max_atom: usize,
+    /// Atoms were paired out of index order.
+    pub reordered: bool,
+    /// Rotation applied to `b` (about its centroid) to superpose it on `a`.
+    pub rotation: Matrix3<f64>,
+}
+
+/// Superpose `b` on `a` and measure every atom's displacement.
+pub fn kabsch_rmsd(a: &Structure, b: &Structure) -> Result<Comparison, String> {
+    if a.atoms.is_empty() {
+        return Err("The structures have no atoms".to_string());
+    }
+    let (ca, cb) = (composition(a), composition(b));
+    if ca != cb {
+        return Err(format!(
+            "Compositions differ ({} vs {})",
+            formula(&ca),
+            formula(&cb)
+        ));
+    }
+    let lattice = (a.is_periodic && b.is_periodic).then_some(b.lattice);
+    let pa: Vec<Vector3<f64>> = a.atoms.iter().map(|x| Vector3::from(x.position)).collect();
+    let pb: Vec<Vector3<f64>> = b.atoms.iter().map(|x| Vector3::from(x.position)).collect();
+
+    // Equal element sequences can still list the atoms in another order,
+    // so distance pairing is always tried. Index pairing stays in the
+    // running and wins ties, which keeps large relaxations paired by index.
+    let in_order = a
+        .atoms
+        .iter()
+        .zip(&b.atoms)
+        .all(|(x, y)| x.element == y.element);
+    let by_distance = distance_partners(a, b, &pa, &pb, lattice);
+    let mut fits = Vec::new();
+    if in_order {
+        fits.push(fit(&pa, &pb, (0..pa.len()).collect(), lattice));
+    }
+    fits.push(fit(&pa, &pb, by_distance, lattice));
+    let Fit {
+        partner,
+        displacement,
+        rmsd,
+        rotation,
+    } = fits
+        .into_iter()
+        .min_by(|x, y| x.rmsd.total_cmp(&y.rmsd))
+        .expect("at least one pairing");
+
+    let (max_atom, max_displacement) = displacement
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap_or((0, 0.0));
+    let reordered = partner.iter().enumerate().any(|(i, &j)| i != j);
+
+    Ok(Comparison {
+        partner,
+        displacement,
+        rmsd,
+        max_displacement,
+        max_atom,
+        reordered,
+        rotation,
+    })
+}
+
+/// One way of pairing the atoms, superposed and measured.
+struct Fit {
+    partner: Vec<usize>,
+    displacement: Vec<f64>,
+    rmsd: f64,
+    rotation: Matrix3<f64>,
+}
+
+/// Superpose the partners of `pa` in `pb` onto it. With a lattice every
+/// partner first moves to its image closest to its atom.
+fn fit(
+    pa: &[Vector3<f64>],
+    pb: &[Vector3<f64>],
+    partner: Vec<usize>,
+    lattice: Option<[[f64; 3]; 3]>,
+) -> Fit {
+    let mut matched = gather(pb, &partner);
+    if let Some(cell) = lattice.and_then(PeriodicCell::new) {
+        for (q, p) in matched.iter_mut().zip(pa) {
+            *q = p + cell.min_image(*q - p);
+        }
+    }
+
+    let (rotation, c_a, c_b) = superpose(pa, &matched);
+    let displacement: Vec<f64> = pa
+        .iter()
+        .zip(&matched)
+        .map(|(p, q)| (rotation * (q - c_b) + c_a - p).norm())
+        .collect();
+    let rmsd = (displacement.iter().map(|d| d * d).sum::<f64>() / displacement.len() as f64).sqrt();
+    Fit {
+        partner,
+        displacement,
+        rmsd,
+        rotation,
+    }
+}
+
+/// Partner in `b` of every atom of `a` by distance: first on centred (or,
+/// for crystals, minimum-image) positions, then again after a trial
+/// superposition so a rotated copy still pairs up.
+fn distance_partners(
+    a: &Structure,
+    b: &Structure,
+    pa: &[Vector3<f64>],
+    pb: &[Vector3<f64>],
+    lattice: Option<[[f64; 3]; 3]>,
+) -> Vec<usize> {
+    if lattice.is_some() {
+        return nearest_pairs(a, b, pa, pb, lattice);
+    }
+    let shift = centroid(pa) - centroid(pb);
+    let shifted: Vec<_> = pb.iter().map(|p| p + shift).collect();
+    let partner = nearest_pairs(a, b, pa, &shifted, None);
+    let (rotation, ..) = superpose(pa, &gather(pb, &partner));
+    let (c_a, c_b) = (centroid(pa), centroid(pb));
+    let aligned: Vec<_> = pb.iter().map(|p| rotation * (p - c_b) + c_a).collect();
+    nearest_pairs(a, b, pa, &aligned, None)
+}
+
+fn composition(s: &Structure) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for atom in &s.atoms {
+        *counts.entry(atom.element.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn formula(counts: &BTreeMap<&str, usize>) -> String {
+    counts
+        .iter()
+        .map(|(el, n)| {
+            if *n == 1 {
+                el.to_string()
+            } else {
+                format!("{}{}", el, n)
+            }
+        })
+        .collect()
+}
+
+fn centroid(points: &[Vector3<f64>]) -> Vector3<f64> {
+    points.iter().sum::<Vector3<f64>>() / points.len().max(1) as f64
+}
+
+fn gather(points: &[Vector3<f64>], order: &[usize]) -> Vec<Vector3<f64>> {
+    order.iter().map(|&j| points[j]).collect()
+}
+
+/// Greedy same-element pairing, closest pairs first. With a lattice the
+/// distance is the minimum-image one. Pairs within `PAIR_RADIUS` come from
+/// a grid, so the usual case of small displacements costs about O(n).
+fn nearest_pairs(
+    a: &Structure,
+    b: &Structure,
+    pa: &[Vector3<f64>],
+    pb: &[Vector3<f64>],
+    lattice: Option<[[f64; 3]; 3]>,
+) -> Vec<usize> {
+    let grid = PairGrid::new(pb, lattice);
+    let dist = |i: usize, j: usize| {
+        let d = pb[j] - pa[i];
+        grid.cell.map_or(d, |cell| cell.min_image(d)).norm()
+    };
+    let same = |i: usize, j: usize| a.atoms[i].element == b.atoms[j].element;
+
+    let mut close = Vec::new();
+    for (i, p) in pa.iter().enumerate() {
+        for j in grid.near(*p) {
+            if same(i, j) {
+                let d = dist(i, j);
+                if d <= PAIR_RADIUS {
+                    close.push((d, i, j));
+                }
+            }
+        }
+    }
+    let mut partner = vec![usize::MAX; pa.len()];
+    let mut taken = vec![false; pb.len()];
+    take_closest(close, &mut partner, &mut taken);
+
+    // Every pair within the radius has been offered, so what is left moved
+    // further and only pairs with what is left.
+    let free: Vec<usize> = (0..pb.len()).filter(|&j| !taken[j]).collect();
+    let mut far = Vec::new();
+    for i in (0..pa.len()).filter(|&i| partner[i] == usize::MAX) {
+        for &j in &free {
+            if same(i, j) {
+                far.push((dist(i, j), i, j));
+            }
+        }
+    }
+    take_closest(far, &mut partner, &mut taken);
+    partner
+}
+
+/// Pair `(distance, i, j)` candidates closest first, skipping atoms that
+/// already have a partner.
+fn take_closest(
+    mut candidates: Vec<(f64, usize, usize)>,
+    partner: &mut [usize],
+    taken: &mut [bool],
+) {
+    candidates.sort_by(|x, y| x.0.total_cmp(&y.0));
+    for (_, i, j) in candidates {
+        if partner[i] == usize::MAX && !taken[j] {
+            partner[i] = j;
+            taken[j] = true;
+        }
+    }
+}
+
+/// Atoms bucketed into bins at least `PAIR_RADIUS` wide, so everything
+/// within that distance of a point sits in the 27 bins around it. Periodic
+/// cells are binned in fractional coordinates and wrap.
+struct PairGrid {
+    bins: HashMap<[i64; 3], Vec<usize>>,
+    cell: Option<PeriodicCell>,
+    /// Bins along each axis of the cell; `None` for a molecule.
+    wrap: Option<[i64; 3]>,
+}
+
+impl PairGrid {
+    fn new(points: &[Vector3<f64>], lattice: Option<[[f64; 3]; 3]>) -> Self {
+        let cell = lattice.and_then(PeriodicCell::new);
+        let wrap = cell.and(lattice).map(|l| {
+            let l = l.map(Vector3::from);
+            let volume = l[0].dot(&l[1].cross(&l[2])).abs();
+            [0, 1, 2].map(|k| {
+                let spacing = volume / l[(k + 1) % 3].cross(&l[(k + 2) % 3]).norm();
+                ((spacing / PAIR_RADIUS).floor() as i64).max(1)
+            })
+        });
+        let mut grid = PairGrid {
+            bins: HashMap::new(),
+            cell,
+            wrap,
+        };
+        for (j, p) in points.iter().enumerate() {
+            let key = grid.key(*p);
+            grid.bins.entry(key).or_default().push(j);
+        }
+        grid
+    }
+
+    fn key(&self, p: Vector3<f64>) -> [i64; 3] {
+        match (self.cell, self.wrap) {
+            (Some(cell), Some(n)) => {
+                let f = cell.to_frac(p);
+                [0, 1, 2].map(|k| (((f[k] - f[k].floor()) * n[k] as f64) as i64).min(n[k] - 1))
+            }
+            _ => p.map(|x| (x / PAIR_RADIUS).floor() as i64).into(),
+        }
+    }
+
+    /// Points in the bins around `p`, each once.
+    fn near(&self, p: Vector3<f64>) -> Vec<usize> {
+        let center = self.key(p);
+        let mut keys: Vec<[i64; 3]> = Vec::with_capacity(27);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let mut key = [center[0] + dx, center[1] + dy, center[2] + dz];
+                    if let Some(n) = self.wrap {
+                        for k in 0..3 {
+                            key[k] = key[k].rem_euclid(n[k]);
+                        }
+                    }
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+            }
+        }
+        keys.iter()
+            .filter_map(|key| self.bins.get(key))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Kabsch: the proper rotation R minimising Σ |R (q − c_q) − (p − c_p)|²,
+/// returned with the two centroids.
+fn superpose(p: &[Vector3<f64>], q: &[Vector3<f64>]) -> (Matrix3<f64>, Vector3<f64>, Vector3<f64>) {
+    let (cp, cq) = (centroid(p), centroid(q));
+    let mut h = Matrix3::zeros();
+    for (x, y) in p.iter().zip(q) {
+        h += (y - cq) * (x - cp).transpose();
+    }
+    let svd = h.svd(true, true);
+    let (Some(u), Some(v_t)) = (svd.u, svd.v_t) else {
+        return (Matrix3::identity(), cp, cq);
+    };
+    let v = v_t.transpose();
+    let d = (v * u.transpose()).determinant().signum();
+    let rotation = v * Matrix3::from_diagonal(&Vector3::new(1.0, 1.0, d)) * u.transpose();
+    (rotation, cp, cq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+    use nalgebra::{Rotation3, Unit};
+
+    fn structure(sites: &[(&str, [f64; 3])], periodic: bool) -> Structure {
+        Structure {
+            lattice: [[5.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 5.0]],
+            atoms: sites
+                .iter()
+                .enumerate()
+                .map(|(i, (el, p))| Atom {
+                    element: el.to_string(),
+                    position: *p,
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: String::new(),
+            is_periodic: periodic,
+        }
+    }
+
+    fn water() -> Structure {
+        structure(
+            &[
+                ("O", [0.0, 0.0, 0.0]),
+                ("H", [0.96, 0.0, 0.0]),
+                ("H", [-0.24, 0.93, 0.0]),
+                ("C", [0.3, 0.2, 1.4]),
+            ],
+            false,
+        )
+    }
+
+    #[test]
+    fn rigid_motion_leaves_no_displacement() {
+        let a = water();
+        let rot =
+            Rotation3::from_axis_angle(&Unit::new_normalize(Vector3::new(1.0, 2.0, 0.5)), 1.2);
+        let mut b = a.clone();
+        for atom in &mut b.atoms {
+            let p = rot * Vector3::from(atom.position) + Vector3::new(3.0, -1.0, 2.0);
+            atom.position = p.into();
+        }
+        let cmp = kabsch_rmsd(&a, &b).unwrap();
+        assert!(!cmp.reordered);
+        assert!(cmp.rmsd < 1e-9 && cmp.max_displacement < 1e-9);
+    }
+
+    #[test]
+    fn shuffled_atoms_are_paired_by_distance() {
+        let a = water();
+        let mut b = a.clone();
+        b.atoms.reverse(); // C, H, H, O
+        b.atoms[1].position[2] += 0.3; // second H (atom 2 of `a`) moves
+
+        let cmp = kabsch_rmsd(&a, &b).unwrap();
+        assert!(cmp.reordered);
+        assert_eq!(cmp.partner, vec![3, 2, 1, 0]);
+        assert_eq!(cmp.max_atom, 2);
+        assert!(cmp.max_displacement > 0.1 && cmp.rmsd < cmp.max_displacement);
+    }
+
+    #[test]
+    fn swapped_atoms_of_one_element_are_paired_by_distance() {
+        let a = water();
+        let mut b = a.clone();
+        b.atoms.swap(1, 2); // element order still O, H, H, C
+
+        let cmp = kabsch_rmsd(&a, &b).unwrap();
+        assert!(cmp.reordered);
+        assert_eq!(cmp.partner, vec![0, 2, 1, 3]);
+        assert!(cmp.rmsd < 1e-9);
+    }
+
+    #[test]
+    fn atoms_beyond_the_pair_radius_are_still_paired() {
+        let a = water();
+        let mut b = a.clone();
+        b.atoms[3].position[2] += 2.5 * PAIR_RADIUS;
+
+        let cmp = kabsch_rmsd(&a, &b).unwrap();
+        assert_eq!(cmp.partner, vec![0, 1, 2, 3]);
+        assert_eq!(cmp.max_atom, 3);
+    }
+
+    #[test]
+    fn different_compositions_are_rejected() {
+        let mut b = water();
+        b.atoms[3].element = "N".to_string();
+        let err = kabsch_rmsd(&water(), &b).unwrap_err();
+        assert!(err.contains("CH2O") && err.contains("H2NO"), "{err}");
+    }
+
+    #[test]
+    fn periodic_partners_use_the_nearest_image() {
+        let a = structure(&[("Na", [0.05, 0.0, 0.0]), ("Cl", [2.5, 2.5, 2.5])], true);
+        let b = structure(&[("Na", [4.95, 0.0, 0.0]), ("Cl", [2.5, 2.5, 2.5])], true);
+        let cmp = kabsch_rmsd(&a, &b).unwrap();
+        // 0.1 Å apart across the cell face, not 4.9 Å.
+        assert!(cmp.max_displacement < 0.11, "{}", cmp.max_displacement);
+    }
+}
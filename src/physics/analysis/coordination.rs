@@ -0,0 +1,278 @@
+// src/physics/analysis/coordination.rs
+//
+// Coordination numbers from a distance criterion: two atoms are neighbours
+// when they are closer than the sum of their covalent radii times a
+// tolerance. That is the bond renderer's default rule, but without the
+// per-pair cutoffs and bond rules a tab can add on top. In a periodic cell
+// every image within the cutoff counts, so a one-atom fcc cell gives CN 12
+// and an atom can list the same neighbour index more than once; molecules
+// use plain distances.
+
+use crate::model::elements::get_covalent_radius;
+use crate::model::structure::Structure;
+use crate::utils::linalg::PeriodicCell;
+use nalgebra::Vector3;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+/// Index, element, coordination number and the indices of the neighbours
+/// (one entry per bonded image) of one atom.
+pub type AtomCoordination = (usize, String, u32, Vec<usize>);
+
+/// Every atom's coordination and, alongside, its mean neighbour distance
+/// (Å), `None` for an atom with no neighbours. Both come from one
+/// neighbour search.
+pub fn coordination_numbers(
+    structure: &Structure,
+    cutoff_scale: f64,
+) -> (Vec<AtomCoordination>, Vec<Option<f64>>) {
+    shells(structure, cutoff_scale)
+        .into_iter()
+        .enumerate()
+        .map(|(i, shell)| {
+            let neighbors: Vec<usize> = shell.iter().map(|&(j, _)| j).collect();
+            let mean = (!shell.is_empty())
+                .then(|| shell.iter().map(|&(_, d)| d).sum::<f64>() / shell.len() as f64);
+            let cn = (
+                i,
+                structure.atoms[i].element.clone(),
+                neighbors.len() as u32,
+                neighbors,
+            );
+            (cn, mean)
+        })
+        .unzip()
+}
+
+/// Coordination numbers usual for `element` in oxides and covalent
+/// solids, for the elements where one range covers most structures.
+pub fn typical_coordination(element: &str) -> Option<RangeInclusive<u32>> {
+    Some(match element {
+        "H" => 1..=1,
+        "B" => 3..=4,
+        "C" => 2..=4,
+        "Si" | "Ge" => 4..=4,
+        "P" => 3..=4,
+        "Al" => 4..=6,
+        "Ti" => 6..=6,
+        _ => return None,
+    })
+}
+
+/// Whether `cn` falls outside `typical_coordination(element)`.
+pub fn is_unusual(element: &str, cn: u32) -> bool {
+    typical_coordination(element).is_some_and(|range| !range.contains(&cn))
+}
+
+/// For each atom, its neighbours as (index, distance), sorted by index.
+///
+/// Every atom and, in a cell, every lattice image that could reach an atom
+/// of the home cell goes into cubic bins as wide as the longest possible
+/// cutoff, so each atom only measures against the 27 bins around it.
+fn shells(structure: &Structure, cutoff_scale: f64) -> Vec<Vec<(usize, f64)>> {
+    let atoms = &structure.atoms;
+    let radius: Vec<f64> = atoms
+        .iter()
+        .map(|a| get_covalent_radius(&a.element))
+        .collect();
+    let cutoff = |i: usize, j: usize| (radius[i] + radius[j]) * cutoff_scale;
+    let reach = radius.iter().cloned().fold(0.0, f64::max) * 2.0 * cutoff_scale;
+    let mut shells = vec![Vec::new(); atoms.len()];
+    if atoms.is_empty() || reach <= 0.0 {
+        return shells;
+    }
+
+    let cell = if structure.is_periodic {
+        PeriodicCell::new(structure.lattice)
+    } else {
+        None
+    };
+    let cart: Vec<Vector3<f64>> = atoms.iter().map(|a| Vector3::from(a.position)).collect();
+    // Home positions, wrapped into the cell, and the (atom, position) of
+    // every copy that can be a neighbour.
+    let (home, images) = match cell {
+        None => (cart.clone(), cart.iter().copied().enumerate().collect()),
+        Some(cell) => {
+            let frac: Vec<Vector3<f64>> = cart
+                .iter()
+                .map(|&p| cell.to_frac(p).map(|x| x - x.floor()))
+                .collect();
+            // Translations to try along each axis: a point within `reach`
+            // of a home atom differs from it by at most reach · |row k of
+            // the inverse lattice| in fractional coordinate k, and home
+            // atoms lie in [0, 1).
+            let columns = [Vector3::x(), Vector3::y(), Vector3::z()].map(|e| cell.to_frac(e));
+            let span = [0, 1, 2].map(|k| {
+                let row_norm = columns.iter().map(|c| c[k] * c[k]).sum::<f64>().sqrt();
+                (reach * row_norm).ceil() as i32
+            });
+            let mut images: Vec<(usize, Vector3<f64>)> = Vec::new();
+            for (j, f) in frac.iter().enumerate() {
+                for ox in -span[0]..=span[0] {
+                    for oy in -span[1]..=span[1] {
+                        for oz in -span[2]..=span[2] {
+                            let offset = Vector3::new(ox as f64, oy as f64, oz as f64);
+                            images.push((j, cell.to_cart(f + offset)));
+                        }
+                    }
+                }
+            }
+            (frac.iter().map(|&f| cell.to_cart(f)).collect(), images)
+        }
+    };
+
+    let bin = |p: &Vector3<f64>| p.map(|x| (x / reach).floor() as i64);
+    let mut bins: HashMap<Vector3<i64>, Vec<usize>> = HashMap::new();
+    for (k, (_, p)) in images.iter().enumerate() {
+        bins.entry(bin(p)).or_default().push(k);
+    }
+    for (i, p) in home.iter().enumerate() {
+        let center = bin(p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(found) = bins.get(&(center + Vector3::new(dx, dy, dz))) else {
+                        continue;
+                    };
+                    for &k in found {
+                        let (j, q) = images[k];
+                        let d = (q - p).norm();
+                        if d >= 1e-6 && d <= cutoff(i, j) {
+                            shells[i].push((j, d));
+                        }
+                    }
+                }
+            }
+        }
+        shells[i].sort_by(|x, y| x.0.cmp(&y.0).then(x.1.total_cmp(&y.1)));
+    }
+    shells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+
+    fn structure(lattice: [[f64; 3]; 3], sites: &[(&str, [f64; 3])], periodic: bool) -> Structure {
+        Structure {
+            lattice,
+            atoms: sites
+                .iter()
+                .enumerate()
+                .map(|(i, (el, p))| Atom {
+                    element: el.to_string(),
+                    position: *p,
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: String::new(),
+            is_periodic: periodic,
+        }
+    }
+
+    #[test]
+    fn diamond_silicon_is_tetrahedral() {
+        let a = 5.431;
+        let frac = [
+            [0.0, 0.0, 0.0],
+            [0.0, 0.5, 0.5],
+            [0.5, 0.0, 0.5],
+            [0.5, 0.5, 0.0],
+            [0.25, 0.25, 0.25],
+            [0.25, 0.75, 0.75],
+            [0.75, 0.25, 0.75],
+            [0.75, 0.75, 0.25],
+        ];
+        let sites: Vec<(&str, [f64; 3])> = frac
+            .iter()
+            .map(|f| ("Si", [f[0] * a, f[1] * a, f[2] * a]))
+            .collect();
+        let s = structure([[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]], &sites, true);
+
+        let (cns, lengths) = coordination_numbers(&s, 1.15);
+        assert!(cns
+            .iter()
+            .all(|(_, el, cn, nb)| el == "Si" && *cn == 4 && nb.len() == 4));
+        for d in lengths {
+            assert!((d.unwrap() - a * 3f64.sqrt() / 4.0).abs() < 1e-9);
+        }
+        assert!(!is_unusual("Si", 4));
+        assert!(is_unusual("Si", 5));
+        assert!(!is_unusual("Xe", 5));
+    }
+
+    #[test]
+    fn one_atom_cell_counts_every_image() {
+        // Primitive fcc copper: twelve nearest neighbours, all of them
+        // images of the one atom.
+        let h = 3.615 / 2.0;
+        let s = structure(
+            [[0.0, h, h], [h, 0.0, h], [h, h, 0.0]],
+            &[("Cu", [0.0, 0.0, 0.0])],
+            true,
+        );
+        let (cns, _) = coordination_numbers(&s, 1.15);
+        assert_eq!(cns[0].2, 12);
+        assert_eq!(cns[0].3, vec![0; 12]);
+    }
+
+    #[test]
+    fn binned_search_matches_every_image_pair() {
+        // A skewed cell packed densely enough that neighbours come from
+        // several images, checked against a plain loop over translations.
+        let lattice = [[4.2, 0.0, 0.0], [1.3, 3.8, 0.0], [-0.9, 0.7, 4.5]];
+        let sites: Vec<(&str, [f64; 3])> = (0..12)
+            .map(|n| {
+                let f = [0.37, 0.61, 0.83].map(|k: f64| (n as f64 * k).fract());
+                let el = ["O", "Si", "Ti"][n % 3];
+                (el, crate::utils::linalg::frac_to_cart(f, lattice))
+            })
+            .collect();
+        let s = structure(lattice, &sites, true);
+        let shells = shells(&s, 1.3);
+
+        let cell = PeriodicCell::new(lattice).unwrap();
+        for (i, (ei, pi)) in sites.iter().enumerate() {
+            let mut expected = Vec::new();
+            for (j, (ej, pj)) in sites.iter().enumerate() {
+                let limit = (get_covalent_radius(ei) + get_covalent_radius(ej)) * 1.3;
+                let df = cell.to_frac(Vector3::from(*pj) - Vector3::from(*pi));
+                for ox in -3..=3 {
+                    for oy in -3..=3 {
+                        for oz in -3..=3 {
+                            let offset = Vector3::new(ox as f64, oy as f64, oz as f64);
+                            let d = cell.to_cart(df + offset).norm();
+                            if d >= 1e-6 && d <= limit {
+                                expected.push(j);
+                            }
+                        }
+                    }
+                }
+            }
+            expected.sort();
+            let found: Vec<usize> = shells[i].iter().map(|&(j, _)| j).collect();
+            assert_eq!(found, expected, "atom {i}");
+        }
+        assert!(shells.iter().any(|shell| !shell.is_empty()));
+    }
+
+    #[test]
+    fn molecules_use_plain_distances() {
+        let s = structure(
+            [[5.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 5.0]],
+            &[
+                ("O", [0.0, 0.0, 0.0]),
+                ("H", [0.96, 0.0, 0.0]),
+                ("H", [-0.24, 0.93, 0.0]),
+            ],
+            false,
+        );
+        let (cns, _) = coordination_numbers(&s, 1.15);
+        assert_eq!(cns.iter().map(|c| c.2).collect::<Vec<_>>(), vec![2, 1, 1]);
+        assert_eq!(cns[0].3, vec![1, 2]);
+    }
+}
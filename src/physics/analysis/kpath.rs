@@ -15,17 +15,15 @@
 
 use super::bravais;
 use super::voronoi;
-use crate::model::elements::get_atomic_number;
 use crate::model::structure::Structure;
-use crate::utils::linalg::{cart_to_frac, lattice_to_matrix3};
-use moyo::base::{AngleTolerance, Cell, Lattice};
+use moyo::base::AngleTolerance;
 use moyo::data::Setting;
 use moyo::MoyoDataset;
 use nalgebra::{Matrix3, Vector3};
 
 // Unified application-wide symmetry tolerance (KP-12/SY-2): kpath must
 // agree with the symmetry tab and cell conversion about the space group.
-use super::symmetry::SYMPREC;
+use super::symmetry::{moyo_cell, SYMPREC};
 
 #[derive(Debug, Clone)]
 pub struct KPoint {
@@ -47,20 +45,7 @@ pub struct KPathResult {
 
 pub fn calculate_kpath(structure: &Structure) -> Option<KPathResult> {
     // 1. Convert input structure to Moyo cell
-    //    lattice_to_matrix3 produces rows = lattice vectors;
-    //    Moyo's Lattice::new expects the same convention.
-    let lat_mat = lattice_to_matrix3(structure.lattice);
-
-    let mut positions = Vec::new();
-    let mut numbers = Vec::new();
-
-    for atom in &structure.atoms {
-        let frac = cart_to_frac(atom.position, structure.lattice)?;
-        positions.push(Vector3::from(frac));
-        numbers.push(get_atomic_number(&atom.element));
-    }
-
-    let moyo_cell = Cell::new(Lattice::new(lat_mat), positions, numbers);
+    let moyo_cell = moyo_cell(structure).ok()?;
 
     // 2. Symmetry detection
     let dataset = MoyoDataset::new(
@@ -188,6 +173,7 @@ mod tests {
                         original_index: i,
                         oxidation: None,
                         occupancy: 1.0,
+                        adp: None,
                     }
                 })
                 .collect(),
@@ -0,0 +1,228 @@
+// src/physics/analysis/pdf.rs
+//
+// Reduced pair distribution function for total-scattering comparison:
+//
+//   G(r) = 4πr ρ₀ [g(r) − 1]
+//        = (1 / N r) Σᵢ Σⱼ wᵢⱼ δσ(r − rᵢⱼ) − 4πr ρ₀
+//
+// with pair weights wᵢⱼ = cᵢcⱼ bᵢbⱼ / ⟨b⟩² (c = occupancy, b = neutron
+// scattering length or X-ray f(0)), Gaussian peaks of width σ and the
+// instrument-resolution envelope exp(−(Q_damp r)² / 2) used by PDFgui.
+//
+// Model scope: Q-independent weights (X-ray f taken at Q = 0), no
+// termination ripples from a finite Q_max, a single σ for every pair.
+
+use crate::model::elements;
+use crate::model::structure::Structure;
+use nalgebra::Vector3;
+use std::f64::consts::PI;
+
+/// Radial grid spacing of the returned curve (Å).
+pub const R_STEP: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radiation {
+    XRay,
+    Neutron,
+}
+
+/// Scattering power used to weight pairs: coherent b (fm) for neutrons,
+/// the forward-scattering form factor f(0) ≈ Z for X-rays.
+pub fn scattering_power(element: &str, radiation: Radiation) -> f64 {
+    match radiation {
+        Radiation::Neutron => elements::get_neutron_scattering_length(element),
+        Radiation::XRay => {
+            let cm = elements::get_cromer_mann_coeffs(element);
+            cm[0] + cm[2] + cm[4] + cm[6] + cm[8]
+        }
+    }
+}
+
+/// G(r) sampled every `R_STEP` on (0, `r_max`], as (r, G) pairs in Å and Å⁻².
+///
+/// `sigma` is the Gaussian peak width (Å) and `q_damp` the resolution
+/// damping (Å⁻¹, 0 disables it). Molecules, empty cells and compositions
+/// whose average scattering power vanishes give an empty curve.
+pub fn calculate_gr(
+    structure: &Structure,
+    r_max: f64,
+    q_damp: f64,
+    sigma: f64,
+    radiation: Radiation,
+) -> Vec<(f64, f64)> {
+    if !structure.is_periodic || structure.atoms.is_empty() || r_max <= 0.0 {
+        return vec![];
+    }
+
+    let a = Vector3::from(structure.lattice[0]);
+    let b = Vector3::from(structure.lattice[1]);
+    let c = Vector3::from(structure.lattice[2]);
+    let volume = a.dot(&b.cross(&c)).abs();
+    if volume < 1e-6 {
+        return vec![];
+    }
+
+    let power: Vec<f64> = structure
+        .atoms
+        .iter()
+        .map(|atom| scattering_power(&atom.element, radiation))
+        .collect();
+    let n: f64 = structure.atoms.iter().map(|atom| atom.occupancy).sum();
+    if n <= 0.0 {
+        return vec![];
+    }
+    let b_mean = structure
+        .atoms
+        .iter()
+        .zip(&power)
+        .map(|(atom, p)| atom.occupancy * p)
+        .sum::<f64>()
+        / n;
+    if b_mean.abs() < 1e-9 {
+        return vec![];
+    }
+    let rho0 = n / volume;
+
+    let sigma = sigma.max(R_STEP);
+    let reach = r_max + 5.0 * sigma;
+    // Images needed along each axis: the reach divided by the spacing of
+    // the lattice planes spanned by the other two vectors.
+    let images =
+        |u: &Vector3<f64>, v: &Vector3<f64>| (reach * u.cross(v).norm() / volume).ceil() as i32;
+    let (na, nb, nc) = (images(&b, &c), images(&c, &a), images(&a, &b));
+
+    let bins = (r_max / R_STEP).round() as usize;
+    let mut hist = vec![0.0; bins];
+    let norm = 1.0 / (sigma * (2.0 * PI).sqrt());
+    let half = (5.0 * sigma / R_STEP).ceil() as isize;
+
+    for (i, ai) in structure.atoms.iter().enumerate() {
+        let ri = Vector3::from(ai.position);
+        for (j, aj) in structure.atoms.iter().enumerate() {
+            let w = ai.occupancy * aj.occupancy * power[i] * power[j] / (b_mean * b_mean);
+            if w == 0.0 {
+                continue;
+            }
+            let base = Vector3::from(aj.position) - ri;
+            for ia in -na..=na {
+                for ib in -nb..=nb {
+                    for ic in -nc..=nc {
+                        let d = (base + a * ia as f64 + b * ib as f64 + c * ic as f64).norm();
+                        if d < 1e-8 || d > reach {
+                            continue;
+                        }
+                        let center = (d / R_STEP) as isize;
+                        for k in (center - half).max(0)..=(center + half) {
+                            let Some(slot) = hist.get_mut(k as usize) else {
+                                break;
+                            };
+                            let r = (k as f64 + 1.0) * R_STEP;
+                            let x = (r - d) / sigma;
+                            *slot += w * norm * (-0.5 * x * x).exp();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    hist.iter()
+        .enumerate()
+        .map(|(k, sum)| {
+            let r = (k as f64 + 1.0) * R_STEP;
+            let g = sum / (n * r) - 4.0 * PI * r * rho0;
+            (r, g * (-0.5 * (q_damp * r).powi(2)).exp())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+
+    /// Conventional rock-salt NaCl, a = 5.64 Å.
+    fn rock_salt() -> Structure {
+        let a = 5.64;
+        let sites = [
+            ("Na", [0.0, 0.0, 0.0]),
+            ("Na", [0.5, 0.5, 0.0]),
+            ("Na", [0.5, 0.0, 0.5]),
+            ("Na", [0.0, 0.5, 0.5]),
+            ("Cl", [0.5, 0.5, 0.5]),
+            ("Cl", [0.0, 0.0, 0.5]),
+            ("Cl", [0.0, 0.5, 0.0]),
+            ("Cl", [0.5, 0.0, 0.0]),
+        ];
+        Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: sites
+                .iter()
+                .enumerate()
+                .map(|(i, (el, f))| Atom {
+                    element: el.to_string(),
+                    position: [f[0] * a, f[1] * a, f[2] * a],
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        }
+    }
+
+    /// ∫ r [G(r) + 4πrρ₀] dr over a shell is (1/N) Σ wᵢⱼ for the pairs in it.
+    fn shell_weight(curve: &[(f64, f64)], rho0: f64, lo: f64, hi: f64) -> f64 {
+        curve
+            .iter()
+            .filter(|(r, _)| *r >= lo && *r <= hi)
+            .map(|(r, g)| r * (g + 4.0 * PI * r * rho0) * R_STEP)
+            .sum()
+    }
+
+    #[test]
+    fn first_shell_carries_scattering_weights() {
+        let s = rock_salt();
+        let rho0 = 8.0 / s.cell_volume();
+        for radiation in [Radiation::Neutron, Radiation::XRay] {
+            let curve = calculate_gr(&s, 6.0, 0.0, 0.05, radiation);
+            assert_eq!(curve.len(), 600);
+
+            let (b_na, b_cl) = (
+                scattering_power("Na", radiation),
+                scattering_power("Cl", radiation),
+            );
+            let b_mean = (b_na + b_cl) / 2.0;
+            // Six unlike neighbours at a/2 around every atom.
+            let expected = 6.0 * b_na * b_cl / (b_mean * b_mean);
+            let got = shell_weight(&curve, rho0, 2.3, 3.3);
+            assert!(
+                (got - expected).abs() < 0.01 * expected,
+                "{radiation:?}: first shell {got:.3}, expected {expected:.3}"
+            );
+
+            // Below the first shell G(r) is the bare −4πrρ₀ baseline.
+            let (r, g) = curve[149];
+            assert!((g + 4.0 * PI * r * rho0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn q_damp_attenuates_large_r() {
+        let s = rock_salt();
+        let sharp = calculate_gr(&s, 10.0, 0.0, 0.05, Radiation::XRay);
+        let damped = calculate_gr(&s, 10.0, 0.1, 0.05, Radiation::XRay);
+        let (r, g) = sharp[998];
+        let expected = g * (-0.5 * (0.1 * r).powi(2)).exp();
+        assert!((damped[998].1 - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn molecule_gives_empty_curve() {
+        let mut s = rock_salt();
+        s.is_periodic = false;
+        assert!(calculate_gr(&s, 10.0, 0.0, 0.05, Radiation::Neutron).is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+// src/physics/analysis/rdf.rs
+//
+// Radial distribution function g(r) from a histogram of interatomic
+// distances:
+//
+//   g_AB(r) = n_AB(r) / (N_A ρ_B V_shell(r))
+//
+// where n_AB(r) counts B atoms in the shell [r, r + Δr) around every A atom,
+// ρ_B = N_B / V and V_shell is the exact shell volume. With no pair selected
+// every atom is both A and B, and g(r) → 1 at large r for any crystal.
+// Atoms are weighted by occupancy, so a half-occupied site counts half.
+//
+// Unlike G(r) in pdf.rs this is the plain, unweighted pair statistic, the
+// one used to compare against simulations.
+
+use crate::model::structure::Structure;
+use nalgebra::Vector3;
+use std::f64::consts::PI;
+
+/// g(r) on bins of `bin_width` out to `r_max` (Å), as (bin centre, g)
+/// pairs. `pair` restricts it to the partial g_AB(r) of two elements (the
+/// order does not matter). Molecules give an empty curve, since g(r) needs
+/// a number density.
+pub fn compute_rdf(
+    structure: &Structure,
+    r_max: f64,
+    bin_width: f64,
+    pair: Option<(String, String)>,
+) -> Vec<(f64, f64)> {
+    if !structure.is_periodic || r_max <= 0.0 || bin_width <= 0.0 {
+        return vec![];
+    }
+    let a = Vector3::from(structure.lattice[0]);
+    let b = Vector3::from(structure.lattice[1]);
+    let c = Vector3::from(structure.lattice[2]);
+    let volume = a.dot(&b.cross(&c)).abs();
+    if volume < 1e-6 {
+        return vec![];
+    }
+
+    let is = |element: &str, side: usize| match &pair {
+        None => true,
+        Some((x, y)) => element == if side == 0 { x.as_str() } else { y.as_str() },
+    };
+    let weight = |side: usize| -> f64 {
+        structure
+            .atoms
+            .iter()
+            .filter(|atom| is(&atom.element, side))
+            .map(|atom| atom.occupancy)
+            .sum()
+    };
+    let (n_a, n_b) = (weight(0), weight(1));
+    if n_a <= 0.0 || n_b <= 0.0 {
+        return vec![];
+    }
+
+    // Images needed along each axis: r_max over the spacing of the
+    // lattice planes spanned by the other two vectors. For an orthogonal
+    // cell that is ceil(r_max / lattice length); oblique cells need more.
+    let images =
+        |u: &Vector3<f64>, v: &Vector3<f64>| (r_max * u.cross(v).norm() / volume).ceil() as i32;
+    let (na, nb, nc) = (images(&b, &c), images(&c, &a), images(&a, &b));
+
+    let bins = (r_max / bin_width - 1e-9).ceil() as usize;
+    let mut hist = vec![0.0; bins];
+    for ai in &structure.atoms {
+        let ri = Vector3::from(ai.position);
+        for aj in &structure.atoms {
+            // Either atom may be the centre of the pair; for A = B the two
+            // tests coincide.
+            let ab = is(&ai.element, 0) && is(&aj.element, 1);
+            let ba = is(&ai.element, 1) && is(&aj.element, 0);
+            if !ab && !ba {
+                continue;
+            }
+            let w = ai.occupancy * aj.occupancy;
+            let base = Vector3::from(aj.position) - ri;
+            for ia in -na..=na {
+                for ib in -nb..=nb {
+                    for ic in -nc..=nc {
+                        let d = (base + a * ia as f64 + b * ib as f64 + c * ic as f64).norm();
+                        if d < 1e-8 || d >= r_max {
+                            continue;
+                        }
+                        if let Some(slot) = hist.get_mut((d / bin_width) as usize) {
+                            *slot += w;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // A mixed partial counted every A–B pair from both ends, so it is
+    // normalised by N_A ρ_B + N_B ρ_A = 2 N_A N_B / V.
+    let norm = match &pair {
+        Some((x, y)) if x != y => 2.0 * n_a * n_b / volume,
+        _ => n_a * n_b / volume,
+    };
+    hist.iter()
+        .enumerate()
+        .map(|(k, count)| {
+            let lo = k as f64 * bin_width;
+            let hi = lo + bin_width;
+            let shell = 4.0 / 3.0 * PI * (hi.powi(3) - lo.powi(3));
+            (lo + 0.5 * bin_width, count / (norm * shell))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+
+    /// Conventional rock-salt NaCl, a = 5.64 Å.
+    fn rock_salt() -> Structure {
+        let a = 5.64;
+        let sites = [
+            ("Na", [0.0, 0.0, 0.0]),
+            ("Na", [0.5, 0.5, 0.0]),
+            ("Na", [0.5, 0.0, 0.5]),
+            ("Na", [0.0, 0.5, 0.5]),
+            ("Cl", [0.5, 0.5, 0.5]),
+            ("Cl", [0.0, 0.0, 0.5]),
+            ("Cl", [0.0, 0.5, 0.0]),
+            ("Cl", [0.5, 0.0, 0.0]),
+        ];
+        Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: sites
+                .iter()
+                .enumerate()
+                .map(|(i, (el, f))| Atom {
+                    element: el.to_string(),
+                    position: [f[0] * a, f[1] * a, f[2] * a],
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        }
+    }
+
+    /// Neighbours of the second kind per atom of the first kind between
+    /// `lo` and `hi`: ∫ ρ g(r) 4πr² dr.
+    fn neighbours(curve: &[(f64, f64)], rho: f64, width: f64, lo: f64, hi: f64) -> f64 {
+        curve
+            .iter()
+            .filter(|(r, _)| *r >= lo && *r <= hi)
+            .map(|(r, g)| {
+                let (r0, r1) = (r - width / 2.0, r + width / 2.0);
+                rho * g * 4.0 / 3.0 * PI * (r1.powi(3) - r0.powi(3))
+            })
+            .sum()
+    }
+
+    #[test]
+    fn shells_integrate_to_coordination_numbers() {
+        let s = rock_salt();
+        let rho = 8.0 / s.cell_volume();
+        let all = compute_rdf(&s, 8.0, 0.05, None);
+        assert_eq!(all.len(), 160);
+        // Six Cl at a/2, twelve Na at a/√2.
+        assert!((neighbours(&all, rho, 0.05, 2.7, 2.9) - 6.0).abs() < 1e-9);
+        assert!((neighbours(&all, rho, 0.05, 3.9, 4.1) - 12.0).abs() < 1e-9);
+        // Past one cell: six like atoms at a.
+        assert!((neighbours(&all, rho, 0.05, 5.6, 5.7) - 6.0).abs() < 1e-9);
+
+        let pair = Some(("Cl".to_string(), "Na".to_string()));
+        let na_cl = compute_rdf(&s, 8.0, 0.05, pair);
+        assert!((neighbours(&na_cl, rho / 2.0, 0.05, 2.7, 2.9) - 6.0).abs() < 1e-9);
+        assert!(neighbours(&na_cl, rho / 2.0, 0.05, 3.9, 4.1).abs() < 1e-9);
+
+        let pair = Some(("Na".to_string(), "Na".to_string()));
+        let na_na = compute_rdf(&s, 8.0, 0.05, pair);
+        assert!(neighbours(&na_na, rho / 2.0, 0.05, 2.7, 2.9).abs() < 1e-9);
+        assert!((neighbours(&na_na, rho / 2.0, 0.05, 3.9, 4.1) - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn molecules_and_missing_elements_give_empty_curves() {
+        let mut s = rock_salt();
+        assert!(compute_rdf(&s, 8.0, 0.05, Some(("K".to_string(), "Cl".to_string()))).is_empty());
+        s.is_periodic = false;
+        assert!(compute_rdf(&s, 8.0, 0.05, None).is_empty());
+    }
+}
@@ -0,0 +1,441 @@
+// src/physics/analysis/reflections.rs
+//
+// Reflection conditions (systematic absences) of the detected space group,
+// for checking a simulated powder pattern against the structure's symmetry.
+//
+// For an operation x' = R·x + t, F(h) = exp(2πi h·t) F(hR). Reflections
+// left invariant by the rotation (hR = h) therefore vanish unless h·t is
+// an integer: centring vectors (R = 1) act on every hkl, glide planes on a
+// zone and screw axes on a row. The test is made per operation in the
+// fractional frame of the input cell, so it matches the hkl indices
+// `xrd::calculate_pattern` enumerates, whatever the cell setting; h·t on
+// the invariant set does not depend on the choice of origin.
+
+use crate::model::structure::Structure;
+use crate::physics::analysis::symmetry::{space_group_operations, spacegroup_symbol, SymmetryOp};
+use nalgebra::{Matrix3, Vector3};
+use std::fmt;
+
+/// h·t within this of an integer counts as integer (moyo translations
+/// carry the symmetry tolerance).
+const PHASE_TOLERANCE: f64 = 1e-3;
+
+/// Largest denominator tried when writing a translation as a fraction;
+/// crystallographic translations are multiples of 1/12 at most.
+const MAX_DENOMINATOR: i64 = 12;
+
+const INDEX: [&str; 3] = ["h", "k", "l"];
+
+/// One condition, e.g. class "h0l" with rule "l=2n": reflections of the
+/// class are present only when the rule holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflectionCondition {
+    pub class: String,
+    pub rule: String,
+}
+
+impl fmt::Display for ReflectionCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.class, self.rule)
+    }
+}
+
+struct Operation {
+    rotation: Matrix3<i32>,
+    translation: Vector3<f64>,
+    /// Index into `conditions` of the rule this operation imposes.
+    condition: usize,
+}
+
+pub struct ReflectionConditions {
+    pub number: i32,
+    pub symbol: String,
+    /// Distinct conditions, general (centring) first, then zones, then rows.
+    pub conditions: Vec<ReflectionCondition>,
+    operations: Vec<Operation>,
+}
+
+/// Reflection conditions of the space group `structure` is detected in.
+pub fn analyze(structure: &Structure) -> Result<ReflectionConditions, String> {
+    if !structure.is_periodic {
+        return Err("Reflection conditions need a periodic cell".to_string());
+    }
+    let (number, ops) = space_group_operations(structure)?;
+    Ok(ReflectionConditions::from_operations(
+        number,
+        spacegroup_symbol(number),
+        &ops,
+    ))
+}
+
+impl ReflectionConditions {
+    pub fn from_operations(number: i32, symbol: &str, ops: &[SymmetryOp]) -> Self {
+        let mut found: Vec<(usize, ReflectionCondition)> = Vec::new();
+        let mut operations = Vec::new();
+        for (rotation, translation) in ops {
+            let Some((rank, cond)) = describe(rotation, translation) else {
+                continue;
+            };
+            let condition = match found.iter().position(|(_, c)| *c == cond) {
+                Some(i) => i,
+                None => {
+                    found.push((rank, cond));
+                    found.len() - 1
+                }
+            };
+            operations.push(Operation {
+                rotation: *rotation,
+                translation: *translation,
+                condition,
+            });
+        }
+
+        // Order by how many reflections a rule covers, keeping detection
+        // order within a kind, and renumber the operations to match.
+        let mut order: Vec<usize> = (0..found.len()).collect();
+        order.sort_by_key(|&i| found[i].0);
+        let mut new_index = vec![0; found.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        for op in &mut operations {
+            op.condition = new_index[op.condition];
+        }
+        let conditions = order.iter().map(|&i| found[i].1.clone()).collect();
+
+        Self {
+            number,
+            symbol: symbol.to_string(),
+            conditions,
+            operations,
+        }
+    }
+
+    /// The condition that extinguishes `hkl`, if any.
+    pub fn forbidden_by(&self, hkl: (i32, i32, i32)) -> Option<&ReflectionCondition> {
+        let h = Vector3::new(hkl.0, hkl.1, hkl.2);
+        self.operations
+            .iter()
+            .find(|op| {
+                if op.rotation.transpose() * h != h {
+                    return false;
+                }
+                let phase = h.cast::<f64>().dot(&op.translation);
+                (phase - phase.round()).abs() > PHASE_TOLERANCE
+            })
+            .map(|op| &self.conditions[op.condition])
+    }
+
+    pub fn is_forbidden(&self, hkl: (i32, i32, i32)) -> bool {
+        self.forbidden_by(hkl).is_some()
+    }
+}
+
+/// The condition one operation imposes, with the rank of Rᵀ − 1 (0 for a
+/// centring vector, 1 for a zone, 2 for a row), or `None` when it imposes
+/// nothing.
+fn describe(
+    rotation: &Matrix3<i32>,
+    translation: &Vector3<f64>,
+) -> Option<(usize, ReflectionCondition)> {
+    let (num, den) = as_fraction(translation)?;
+    if den == 1 {
+        return None;
+    }
+    let m = rotation.transpose() - Matrix3::identity();
+    let rows: Vec<Vector3<i64>> = (0..3)
+        .map(|r| Vector3::new(m[(r, 0)], m[(r, 1)], m[(r, 2)]).cast::<i64>())
+        .filter(|r| *r != Vector3::zeros())
+        .collect();
+
+    if rows.is_empty() {
+        let rule = linear_rule(&[(0, num.x), (1, num.y), (2, num.z)], den)?;
+        return Some((
+            0,
+            ReflectionCondition {
+                class: "hkl".to_string(),
+                rule,
+            },
+        ));
+    }
+
+    // Any two independent rows leave a one-dimensional invariant set.
+    let direction = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| rows[i + 1..].iter().map(move |b| a.cross(b)))
+        .find(|v| *v != Vector3::zeros());
+    match direction {
+        Some(v) => row_condition(&rows, normalized(v), num, den).map(|c| (2, c)),
+        None => zone_condition(normalized(rows[0]), num, den).map(|c| (1, c)),
+    }
+}
+
+/// Reflections along the row n·v: the rule on the smallest non-zero index.
+fn row_condition(
+    rows: &[Vector3<i64>],
+    v: Vector3<i64>,
+    num: Vector3<i64>,
+    den: i64,
+) -> Option<ReflectionCondition> {
+    // Three independent rows: only h = 0 is invariant.
+    if rows.iter().any(|r| r.dot(&v) != 0) {
+        return None;
+    }
+    let lead = (0..3).filter(|&i| v[i] != 0).min_by_key(|&i| v[i].abs())?;
+    let class: String = (0..3)
+        .map(|i| match v[i] {
+            0 => "0".to_string(),
+            c if c == v[lead] => INDEX[lead].to_string(),
+            c if c == -v[lead] => bar(INDEX[lead]),
+            c => format!("{}{}", c / v[lead], INDEX[lead]),
+        })
+        .collect();
+    // With the lead index equal to m, h·t = m (v·t) / v_lead.
+    let (n, d) = reduce(num.dot(&v), den * v[lead]);
+    let modulus = d.abs();
+    if n == 0 || modulus == 1 {
+        return None;
+    }
+    Some(ReflectionCondition {
+        class,
+        rule: format!("{}={}n", INDEX[lead], modulus),
+    })
+}
+
+/// Reflections in the plane e·h = 0. Planes of the form h_i = 0 or
+/// h_j = ±h_i get their ITA name ("h0l", "hhl"); others are spelled out.
+fn zone_condition(e: Vector3<i64>, num: Vector3<i64>, den: i64) -> Option<ReflectionCondition> {
+    let nonzero: Vec<usize> = (0..3).filter(|&i| e[i] != 0).collect();
+    let mut class: Vec<String> = INDEX.iter().map(|s| s.to_string()).collect();
+    let mut coeffs = vec![(0, num.x), (1, num.y), (2, num.z)];
+    match nonzero.as_slice() {
+        [i] => {
+            class[*i] = "0".to_string();
+            coeffs.retain(|&(k, _)| k != *i);
+        }
+        [i, j] if e[*i].abs() == 1 && e[*j].abs() == 1 => {
+            // e_i h_i + e_j h_j = 0, so h_j = s h_i.
+            let s = -e[*i] * e[*j];
+            class[*j] = if s > 0 {
+                INDEX[*i].to_string()
+            } else {
+                bar(INDEX[*i])
+            };
+            coeffs[*i].1 += s * num[*j];
+            coeffs.retain(|&(k, _)| k != *j);
+        }
+        _ => {
+            let plane = linear_rule(&[(0, e.x), (1, e.y), (2, e.z)], 0)?;
+            return linear_rule(&coeffs, den).map(|rule| ReflectionCondition {
+                class: format!("hkl with {}", plane),
+                rule,
+            });
+        }
+    }
+    linear_rule(&coeffs, den).map(|rule| ReflectionCondition {
+        class: class.concat(),
+        rule,
+    })
+}
+
+/// "c₁h+c₂k=dn" for `den` > 0, or "c₁h+c₂k=0" for `den` = 0. Coefficients
+/// are reduced modulo `den` into (−den/2, den/2]; `None` when nothing is
+/// left, i.e. the condition always holds.
+fn linear_rule(coeffs: &[(usize, i64)], den: i64) -> Option<String> {
+    let wrap = |c: i64| {
+        if den == 0 {
+            return c;
+        }
+        let r = c.rem_euclid(den);
+        if 2 * r > den {
+            r - den
+        } else {
+            r
+        }
+    };
+    let mut terms: Vec<(usize, i64)> = coeffs
+        .iter()
+        .map(|&(i, c)| (i, wrap(c)))
+        .filter(|&(_, c)| c != 0)
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    // A rule and its negation are the same condition; prefer the spelling
+    // with more positive terms, then a positive first term.
+    let negative = terms.iter().filter(|t| t.1 < 0).count();
+    if 2 * negative > terms.len() || (2 * negative == terms.len() && terms[0].1 < 0) {
+        for t in &mut terms {
+            t.1 = wrap(-t.1);
+        }
+    }
+    let g = terms.iter().fold(den, |g, &(_, c)| gcd(g, c));
+    let den = den / g;
+    for t in &mut terms {
+        t.1 /= g;
+    }
+    if den == 1 {
+        return None;
+    }
+
+    let mut out = String::new();
+    for (n, &(i, c)) in terms.iter().enumerate() {
+        match c {
+            1 if n > 0 => out.push('+'),
+            1 => {}
+            -1 => out.push('-'),
+            c if c > 0 && n > 0 => out.push_str(&format!("+{}", c)),
+            c => out.push_str(&c.to_string()),
+        }
+        out.push_str(INDEX[i]);
+    }
+    if den == 0 {
+        out.push_str("=0");
+    } else {
+        out.push_str(&format!("={}n", den));
+    }
+    Some(out)
+}
+
+/// `t` as `num / den` with the smallest common denominator, components
+/// wrapped into [0, 1).
+fn as_fraction(t: &Vector3<f64>) -> Option<(Vector3<i64>, i64)> {
+    let t = t.map(|x| x.rem_euclid(1.0));
+    (1..=MAX_DENOMINATOR).find_map(|den| {
+        let scaled = t * den as f64;
+        let num = scaled.map(|x| x.round());
+        ((scaled - num).amax() < PHASE_TOLERANCE * den as f64)
+            .then(|| (num.map(|x| x as i64 % den), den))
+    })
+}
+
+/// Divide out the common factor and make the first non-zero component
+/// positive.
+fn normalized(v: Vector3<i64>) -> Vector3<i64> {
+    let g = gcd(gcd(v.x, v.y), v.z).max(1);
+    let sign = if v.iter().find(|&&c| c != 0).is_some_and(|&c| c < 0) {
+        -1
+    } else {
+        1
+    };
+    v.map(|c| sign * c / g)
+}
+
+fn reduce(n: i64, d: i64) -> (i64, i64) {
+    let g = gcd(n, d).max(1);
+    (n / g, d / g)
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// An index with an overbar, as in "hh̄l".
+fn bar(index: &str) -> String {
+    format!("{}\u{0304}", index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+
+    fn op(diag: [i32; 3], t: [f64; 3]) -> SymmetryOp {
+        (
+            Matrix3::from_diagonal(&Vector3::from(diag)),
+            Vector3::from(t),
+        )
+    }
+
+    fn conditions(rc: &ReflectionConditions) -> Vec<String> {
+        rc.conditions.iter().map(|c| c.to_string()).collect()
+    }
+
+    /// P2₁/c (unique axis b): 2₁ along b and a c-glide perpendicular to it.
+    #[test]
+    fn screw_axis_and_glide_plane() {
+        let ops = [
+            op([1, 1, 1], [0.0; 3]),
+            op([-1, 1, -1], [0.0, 0.5, 0.5]),
+            op([-1, -1, -1], [0.0; 3]),
+            op([1, -1, 1], [0.0, 0.5, 0.5]),
+        ];
+        let rc = ReflectionConditions::from_operations(14, "P12_1/c1", &ops);
+        assert_eq!(conditions(&rc), vec!["h0l: l=2n", "0k0: k=2n"]);
+
+        assert!(rc.is_forbidden((0, 1, 0)));
+        assert!(rc.is_forbidden((1, 0, 1)));
+        assert!(!rc.is_forbidden((0, 2, 0)));
+        assert!(!rc.is_forbidden((1, 0, 2)));
+        assert!(!rc.is_forbidden((1, 1, 1)));
+        assert_eq!(rc.forbidden_by((2, 0, -3)).unwrap().rule, "l=2n");
+    }
+
+    #[test]
+    fn centring_conditions() {
+        let identity = Matrix3::identity();
+        let centred = |t: [[f64; 3]; 3]| -> Vec<_> {
+            std::iter::once((identity, Vector3::zeros()))
+                .chain(t.iter().map(|&v| (identity, Vector3::from(v))))
+                .collect()
+        };
+        let f = ReflectionConditions::from_operations(
+            225,
+            "Fm-3m",
+            &centred([[0.0, 0.5, 0.5], [0.5, 0.0, 0.5], [0.5, 0.5, 0.0]]),
+        );
+        assert_eq!(
+            conditions(&f),
+            vec!["hkl: k+l=2n", "hkl: h+l=2n", "hkl: h+k=2n"]
+        );
+        assert!(f.is_forbidden((1, 0, 0)) && f.is_forbidden((2, 1, 0)));
+        assert!(!f.is_forbidden((1, 1, 1)) && !f.is_forbidden((2, 0, 0)));
+
+        // Rhombohedral centring (obverse) in the hexagonal setting.
+        let r = ReflectionConditions::from_operations(
+            146,
+            "R3",
+            &[
+                (identity, Vector3::zeros()),
+                (identity, Vector3::new(2.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0)),
+                (identity, Vector3::new(1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0)),
+            ],
+        );
+        // Both centring vectors give the same rule.
+        assert_eq!(conditions(&r), vec!["hkl: -h+k+l=3n"]);
+        assert!(r.is_forbidden((1, 0, 0)) && !r.is_forbidden((1, 0, 1)));
+    }
+
+    #[test]
+    fn bcc_iron_pattern_has_no_forbidden_peaks() {
+        let a = 2.8665;
+        let atom = |i: usize, x: f64| Atom {
+            element: "Fe".to_string(),
+            position: [x; 3],
+            original_index: i,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        };
+        let s = Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: vec![atom(0, 0.0), atom(1, a / 2.0)],
+            formula: "Fe".to_string(),
+            is_periodic: true,
+        };
+        let rc = analyze(&s).unwrap();
+        assert_eq!(rc.number, 229);
+        assert!(conditions(&rc).contains(&"hkl: h+k+l=2n".to_string()));
+
+        let peaks = crate::physics::analysis::xrd::calculate_pattern(&s, &Default::default());
+        assert!(!peaks.is_empty());
+        assert!(peaks
+            .iter()
+            .all(|p| p.hkl.iter().all(|&hkl| !rc.is_forbidden(hkl))));
+    }
+}
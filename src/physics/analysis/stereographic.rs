@@ -0,0 +1,243 @@
+// src/physics/analysis/stereographic.rs
+//
+// Crystallographic projections of lattice-plane poles (hkl) and lattice
+// directions [uvw], viewed down Cartesian +z:
+//
+//   stereographic:  (x, y) / (1 + |z|)    primitive circle = equator
+//   gnomonic:       (x, y) / z            upper hemisphere only
+//
+// Plane normals come from the reciprocal lattice (G = h a* + k b* + l c*),
+// directions from the direct lattice (u a + v b + w c). A plane and its
+// negative are the same plane, so plane poles are folded onto the upper
+// hemisphere; directions in the lower hemisphere are kept and flagged so
+// the plot can draw them as open markers.
+
+use nalgebra::{Matrix3, Vector3};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoleKind {
+    /// Normal of the lattice plane (hkl).
+    Plane,
+    /// Lattice direction [uvw].
+    Direction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Projection {
+    Stereographic,
+    Gnomonic,
+}
+
+/// Gnomonic poles further than this from the centre (≈ 76° off-axis) are
+/// dropped: the projection diverges towards the equator.
+pub const GNOMONIC_LIMIT: f64 = 4.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pole {
+    pub kind: PoleKind,
+    pub indices: [i32; 3],
+    /// Position in the projection plane; the stereographic primitive circle
+    /// has radius 1.
+    pub x: f64,
+    pub y: f64,
+    /// False for a direction pointing into the lower hemisphere.
+    pub upper: bool,
+}
+
+/// `(h k l)` for planes, `[u v w]` for directions.
+pub fn label(kind: PoleKind, indices: [i32; 3]) -> String {
+    let [a, b, c] = indices;
+    match kind {
+        PoleKind::Plane => format!("({} {} {})", a, b, c),
+        PoleKind::Direction => format!("[{} {} {}]", a, b, c),
+    }
+}
+
+/// Cartesian vector of a plane normal or direction. `None` for a singular
+/// lattice or all-zero indices.
+pub fn cartesian(
+    lattice: [[f64; 3]; 3],
+    kind: PoleKind,
+    indices: [i32; 3],
+) -> Option<Vector3<f64>> {
+    let [a, b, c] = lattice.map(Vector3::from);
+    let basis = match kind {
+        PoleKind::Direction => [a, b, c],
+        PoleKind::Plane => {
+            let volume = a.dot(&b.cross(&c));
+            if volume.abs() < 1e-10 {
+                return None;
+            }
+            [
+                b.cross(&c) / volume,
+                c.cross(&a) / volume,
+                a.cross(&b) / volume,
+            ]
+        }
+    };
+    let v =
+        basis[0] * indices[0] as f64 + basis[1] * indices[1] as f64 + basis[2] * indices[2] as f64;
+    (v.norm() > 1e-12).then_some(v)
+}
+
+/// Symmetry-equivalent members of a family, `indices` first. `rotations`
+/// are fractional-coordinate rotations (see
+/// `symmetry::point_group_rotations`); directions transform as R·[uvw],
+/// plane indices as Rᵀ·(hkl).
+pub fn family(kind: PoleKind, indices: [i32; 3], rotations: &[Matrix3<i32>]) -> Vec<[i32; 3]> {
+    let v = Vector3::from(indices);
+    let mut members = vec![indices];
+    for r in rotations {
+        let image = match kind {
+            PoleKind::Direction => r * v,
+            PoleKind::Plane => r.transpose() * v,
+        };
+        let image = [image.x, image.y, image.z];
+        if !members.contains(&image) {
+            members.push(image);
+        }
+    }
+    members
+}
+
+/// Position of the unit vector along `v` in the projection plane, and
+/// whether it lies in the upper hemisphere. `None` where the projection is
+/// undefined (gnomonic at or beyond `GNOMONIC_LIMIT`).
+pub fn project(v: Vector3<f64>, projection: Projection) -> Option<(f64, f64, bool)> {
+    let n = v.try_normalize(1e-12)?;
+    let upper = n.z >= -1e-12;
+    match projection {
+        Projection::Stereographic => {
+            let s = 1.0 + n.z.abs();
+            Some((n.x / s, n.y / s, upper))
+        }
+        Projection::Gnomonic => {
+            if n.z < 1e-6 {
+                return None;
+            }
+            let (x, y) = (n.x / n.z, n.y / n.z);
+            (x.hypot(y) <= GNOMONIC_LIMIT).then_some((x, y, true))
+        }
+    }
+}
+
+/// Projected poles of the family of `indices`. Plane poles are folded onto
+/// the upper hemisphere, so `(hkl)` and `(h̄k̄l̄)` share one marker.
+pub fn family_poles(
+    lattice: [[f64; 3]; 3],
+    kind: PoleKind,
+    indices: [i32; 3],
+    rotations: &[Matrix3<i32>],
+    projection: Projection,
+) -> Vec<Pole> {
+    let mut poles: Vec<Pole> = Vec::new();
+    for mut member in family(kind, indices, rotations) {
+        let Some(mut v) = cartesian(lattice, kind, member) else {
+            continue;
+        };
+        if kind == PoleKind::Plane && v.z < -1e-12 {
+            v = -v;
+            member = member.map(|i| -i);
+        }
+        let Some((x, y, upper)) = project(v, projection) else {
+            continue;
+        };
+        let duplicate = poles
+            .iter()
+            .any(|p| p.upper == upper && (p.x - x).abs() < 1e-9 && (p.y - y).abs() < 1e-9);
+        if !duplicate {
+            poles.push(Pole {
+                kind,
+                indices: member,
+                x,
+                y,
+                upper,
+            });
+        }
+    }
+    poles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBIC: [[f64; 3]; 3] = [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]];
+
+    /// Fourfold rotation about c and its powers.
+    fn c4() -> Vec<Matrix3<i32>> {
+        let r = Matrix3::new(0, -1, 0, 1, 0, 0, 0, 0, 1);
+        vec![Matrix3::identity(), r, r * r, r * r * r]
+    }
+
+    #[test]
+    fn cubic_poles_land_at_textbook_radii() {
+        let radius = |hkl| {
+            let p = &family_poles(CUBIC, PoleKind::Plane, hkl, &[], Projection::Stereographic)[0];
+            p.x.hypot(p.y)
+        };
+        assert!(radius([0, 0, 1]) < 1e-12);
+        assert!((radius([1, 0, 0]) - 1.0).abs() < 1e-12);
+        // (111) sits 54.74° from the pole: r = tan(θ/2).
+        let theta = (1.0 / 3.0_f64.sqrt()).acos();
+        assert!((radius([1, 1, 1]) - (theta / 2.0).tan()).abs() < 1e-12);
+
+        let g = &family_poles(
+            CUBIC,
+            PoleKind::Direction,
+            [1, 1, 1],
+            &[],
+            Projection::Gnomonic,
+        )[0];
+        assert!((g.x - 1.0).abs() < 1e-12 && (g.y - 1.0).abs() < 1e-12);
+        assert!(project(Vector3::new(1.0, 1.0, 0.0), Projection::Gnomonic).is_none());
+    }
+
+    #[test]
+    fn families_expand_under_the_point_group() {
+        let members = family(PoleKind::Plane, [1, 0, 0], &c4());
+        assert_eq!(members.len(), 4);
+        assert!(members.contains(&[0, -1, 0]));
+        assert_eq!(
+            family(PoleKind::Direction, [0, 0, 1], &c4()),
+            vec![[0, 0, 1]]
+        );
+    }
+
+    #[test]
+    fn planes_fold_onto_the_upper_hemisphere_but_directions_do_not() {
+        let plane = &family_poles(
+            CUBIC,
+            PoleKind::Plane,
+            [1, 1, -1],
+            &[],
+            Projection::Stereographic,
+        )[0];
+        assert!(plane.upper);
+        assert_eq!(plane.indices, [-1, -1, 1]);
+
+        let dir = &family_poles(
+            CUBIC,
+            PoleKind::Direction,
+            [1, 1, -1],
+            &[],
+            Projection::Stereographic,
+        )[0];
+        assert!(!dir.upper);
+        assert_eq!(dir.indices, [1, 1, -1]);
+    }
+
+    #[test]
+    fn hexagonal_plane_normals_use_the_reciprocal_lattice() {
+        let s3 = 3.0_f64.sqrt();
+        let hex = [[3.0, 0.0, 0.0], [-1.5, 1.5 * s3, 0.0], [0.0, 0.0, 5.0]];
+        let n = cartesian(hex, PoleKind::Plane, [1, 0, 0]).unwrap();
+        // (100) contains b and c, so its normal is not along a.
+        assert!(n.dot(&Vector3::from(hex[1])).abs() < 1e-12);
+        assert!(n.dot(&Vector3::from(hex[2])).abs() < 1e-12);
+        assert!(n.normalize().dot(&Vector3::from(hex[0]).normalize()) < 0.9);
+        assert!(cartesian(hex, PoleKind::Direction, [0, 0, 0]).is_none());
+        assert_eq!(label(PoleKind::Plane, [1, -1, 0]), "(1 -1 0)");
+        assert_eq!(label(PoleKind::Direction, [0, 0, 1]), "[0 0 1]");
+    }
+}
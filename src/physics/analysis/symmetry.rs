@@ -12,6 +12,7 @@ use crate::utils::linalg::{cart_to_frac, lattice_to_matrix3};
 use moyo::base::{AngleTolerance, Cell, Lattice};
 use moyo::data::Setting;
 use moyo::MoyoDataset;
+use nalgebra::{Matrix3, Vector3};
 
 /// Single symmetry tolerance for the whole application. The symmetry tab,
 /// k-path, and primitive/conventional conversion must all use this value —
@@ -29,21 +30,14 @@ pub struct SymmetryInfo {
 // ANALYSIS: Read-only check of the Space Group (Used by UI)
 // =========================================================================
 pub fn analyze(structure: &Structure) -> Result<SymmetryInfo, String> {
-    let lattice_mat = lattice_to_matrix3(structure.lattice);
-
-    let mut positions = Vec::new();
-    let mut numbers = Vec::new();
-
-    for atom in &structure.atoms {
-        let frac = cart_to_frac(atom.position, structure.lattice).ok_or("Invalid lattice")?;
-        positions.push(nalgebra::Vector3::from(frac));
-        let z = get_atomic_number(&atom.element);
-        numbers.push(if z == 0 { 1 } else { z });
-    }
+    analyze_with(structure, SYMPREC)
+}
 
-    let cell = Cell::new(Lattice::new(lattice_mat), positions, numbers);
-    let dataset = MoyoDataset::new(&cell, SYMPREC, AngleTolerance::Default, Setting::Spglib, true)
-        .map_err(|_| "Symmetry search failed".to_string())?;
+/// Same as `analyze` with an explicit distance tolerance (Å). Only for
+/// tools that let the user loosen the tolerance on purpose, such as
+/// symmetrizing a relaxed structure; everything else uses `SYMPREC`.
+pub fn analyze_with(structure: &Structure, symprec: f64) -> Result<SymmetryInfo, String> {
+    let dataset = detect(structure, symprec)?;
 
     let sys_name = match dataset.number {
         1..=2 => "Triclinic",
@@ -63,6 +57,87 @@ pub fn analyze(structure: &Structure) -> Result<SymmetryInfo, String> {
     })
 }
 
+/// Distinct rotation parts of the detected space group, as integer
+/// matrices acting on fractional coordinates of the input cell: a direction
+/// [uvw] maps to R·[uvw] and a plane normal (hkl) to Rᵀ·(hkl). Pure
+/// translations and centring vectors collapse onto the identity.
+pub fn point_group_rotations(structure: &Structure) -> Result<Vec<Matrix3<i32>>, String> {
+    let dataset = detect(structure, SYMPREC)?;
+    let mut rotations: Vec<Matrix3<i32>> = Vec::new();
+    for op in dataset.operations.iter() {
+        if !rotations.contains(&op.rotation) {
+            rotations.push(op.rotation);
+        }
+    }
+    Ok(rotations)
+}
+
+/// A symmetry operation `(R, t)` acting as `x' = R·x + t` on fractional
+/// coordinates.
+pub type SymmetryOp = (Matrix3<i32>, Vector3<f64>);
+
+/// Space-group number and every operation `x' = R·x + t` of the detected
+/// group, on fractional coordinates of the input cell. Unlike
+/// `point_group_rotations` the centring vectors and screw/glide
+/// translations are kept, since systematic absences come from them.
+pub fn space_group_operations(structure: &Structure) -> Result<(i32, Vec<SymmetryOp>), String> {
+    let dataset = detect(structure, SYMPREC)?;
+    let ops = dataset
+        .operations
+        .iter()
+        .map(|op| (op.rotation, op.translation))
+        .collect();
+    Ok((dataset.number, ops))
+}
+
+/// The moyo input cell for `structure`: lattice rows, fractional
+/// positions, and atomic numbers as species ids. Unknown elements get
+/// unique negative ids so they never merge with each other or with real
+/// species; [`species_element`] maps an id back.
+pub fn moyo_cell(structure: &Structure) -> Result<Cell, String> {
+    let mut ids: Vec<(&str, i32)> = Vec::new();
+    let mut positions = Vec::with_capacity(structure.atoms.len());
+    let mut numbers = Vec::with_capacity(structure.atoms.len());
+    for atom in &structure.atoms {
+        let id = match ids.iter().find(|(el, _)| *el == atom.element) {
+            Some(&(_, id)) => id,
+            None => {
+                let z = get_atomic_number(&atom.element);
+                let id = if z > 0 { z } else { -(ids.len() as i32 + 1) };
+                ids.push((&atom.element, id));
+                id
+            }
+        };
+        let frac = cart_to_frac(atom.position, structure.lattice)
+            .ok_or("Lattice is singular (volume is zero)")?;
+        positions.push(Vector3::from(frac));
+        numbers.push(id);
+    }
+    Ok(Cell::new(
+        Lattice::new(lattice_to_matrix3(structure.lattice)),
+        positions,
+        numbers,
+    ))
+}
+
+/// Element behind a species id of [`moyo_cell`]`(structure)`.
+pub fn species_element<'a>(structure: &'a Structure, cell: &Cell, id: i32) -> Option<&'a str> {
+    let i = cell.numbers.iter().position(|&n| n == id)?;
+    Some(structure.atoms[i].element.as_str())
+}
+
+fn detect(structure: &Structure, symprec: f64) -> Result<MoyoDataset, String> {
+    let cell = moyo_cell(structure)?;
+    MoyoDataset::new(
+        &cell,
+        symprec,
+        AngleTolerance::Default,
+        Setting::Spglib,
+        true,
+    )
+    .map_err(|_| "Symmetry search failed".to_string())
+}
+
 /// Hermann-Mauguin symbol for a space-group number (1-230).
 /// Shared by the symmetry tab and the k-path panel so both always show
 /// the same name for the same group.
@@ -334,6 +409,7 @@ mod tests {
                         original_index: i,
                         oxidation: None,
                         occupancy: 1.0,
+                        adp: None,
                     }
                 })
                 .collect(),
@@ -342,6 +418,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn moyo_cell_keeps_unknown_species_apart() {
+        let lat = [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]];
+        let s = make_structure(
+            lat,
+            vec![
+                ("Na", [0.0, 0.0, 0.0]),
+                ("Xx", [0.5, 0.0, 0.0]),
+                ("Yy", [0.0, 0.5, 0.0]),
+                ("Xx", [0.0, 0.0, 0.5]),
+            ],
+        );
+        let cell = moyo_cell(&s).unwrap();
+        assert_eq!(cell.numbers, vec![11, -2, -3, -2]);
+        assert!((cell.positions[1].x - 0.5).abs() < 1e-12);
+        assert_eq!(species_element(&s, &cell, -3), Some("Yy"));
+        assert_eq!(species_element(&s, &cell, 1), None);
+    }
+
     /// NaCl, Fm-3m → space group 225.
     #[test]
     fn test_spacegroup_nacl() {
@@ -429,4 +524,29 @@ mod tests {
         );
         assert_eq!(info.system, "Trigonal");
     }
+
+    #[test]
+    fn rock_salt_has_the_full_cubic_point_group() {
+        let a = 5.6402;
+        let lat = [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]];
+        let s = make_structure(
+            lat,
+            vec![
+                ("Na", [0.0, 0.0, 0.0]),
+                ("Na", [0.5, 0.5, 0.0]),
+                ("Na", [0.5, 0.0, 0.5]),
+                ("Na", [0.0, 0.5, 0.5]),
+                ("Cl", [0.5, 0.5, 0.5]),
+                ("Cl", [0.0, 0.0, 0.5]),
+                ("Cl", [0.0, 0.5, 0.0]),
+                ("Cl", [0.5, 0.0, 0.0]),
+            ],
+        );
+        // m-3m: 48 rotations, each listed once despite the four F-centring
+        // translations.
+        let rotations = point_group_rotations(&s).unwrap();
+        assert_eq!(rotations.len(), 48);
+        assert!(rotations.contains(&Matrix3::identity()));
+        assert!(rotations.contains(&-Matrix3::identity()));
+    }
 }
@@ -2,10 +2,11 @@
 
 use crate::model::elements::{get_atom_cov, get_atom_ionic_radius, get_atom_vdw};
 use crate::model::structure::Structure;
-use crate::utils::linalg::cart_to_frac;
+use crate::utils::linalg::PeriodicCell;
 use nalgebra::{Matrix3, Vector3};
 use rayon::prelude::*;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 // --- 1. PUBLIC CONSTANTS (Single Source of Truth) ---
 
@@ -60,6 +61,9 @@ pub enum VoidError {
     InvalidGridResolution(f64),
     GridTooLarge { requested: usize, max: usize },
     NoAtoms,
+    NonPeriodic,
+    /// The progress callback asked to stop.
+    Cancelled,
 }
 
 impl fmt::Display for VoidError {
@@ -79,6 +83,10 @@ impl fmt::Display for VoidError {
                 requested, max
             ),
             VoidError::NoAtoms => write!(f, "Structure contains no atoms"),
+            VoidError::NonPeriodic => {
+                write!(f, "Void analysis requires a periodic cell (structure is a molecule)")
+            }
+            VoidError::Cancelled => write!(f, "Void calculation cancelled"),
         }
     }
 }
@@ -122,6 +130,9 @@ pub struct VoidConfig {
 
     /// Maximum grid points to prevent memory issues
     pub max_grid_points: usize,
+
+    /// How many distinct cavities to report in `VoidResult::sites`
+    pub max_sites: usize,
 }
 
 impl Default for VoidConfig {
@@ -132,6 +143,7 @@ impl Default for VoidConfig {
             radii_scale: 1.0,               // No scaling
             radius_type: RadiusType::Ionic, // Best for most crystals
             max_grid_points: 10_000_000,    // ~10M points limit
+            max_sites: 8,                   // e.g. both spinel interstices
         }
     }
 }
@@ -210,11 +222,70 @@ pub struct VoidResult {
     /// Percentage of volume accessible to the probe (%)
     pub void_fraction: f64,
 
+    /// Accessible volume per cell (Å³): void fraction × cell volume
+    pub void_volume: f64,
+
+    /// Specific pore volume (cm³/g), the quantity porosimetry reports:
+    /// void fraction / density. `None` when the density is undefined.
+    pub specific_pore_volume: Option<f64>,
+
     /// Configuration used for this calculation
     pub config: VoidConfig,
 
     /// Grid statistics
     pub grid_info: GridInfo,
+
+    /// Distinct local maxima of the distance field, largest first (at most
+    /// `config.max_sites`). `sites[0]` is the largest sphere.
+    pub sites: Vec<VoidSite>,
+
+    /// Connectivity of the probe-accessible void space across the cell
+    pub percolation: Percolation,
+}
+
+/// Whether probe-accessible void points join into channels that cross the
+/// periodic boundaries, i.e. whether a sphere of the probe radius can
+/// diffuse through the crystal.
+#[derive(Clone, Debug)]
+pub struct Percolation {
+    /// 0 = isolated pockets, 1 = channels, 2 = layers, 3 = 3D network
+    pub dimensionality: usize,
+
+    /// Largest sphere radius (Å) that still percolates in at least one
+    /// direction — the channel bottleneck. `None` if even a point probe
+    /// cannot cross the cell.
+    pub bottleneck_radius: Option<f64>,
+
+    /// Cartesian grid points (Å) of the percolating void components at the
+    /// probe radius, thinned to at most `MAX_CLUSTER_POINTS` for display.
+    pub cluster_points: Vec<[f64; 3]>,
+}
+
+/// Display cap for `Percolation::cluster_points`.
+pub const MAX_CLUSTER_POINTS: usize = 4000;
+
+/// One cavity: a local maximum of the distance-to-surface field.
+#[derive(Clone, Debug)]
+pub struct VoidSite {
+    /// Radius of the sphere that fits at this maximum (Å)
+    pub radius: f64,
+
+    /// Cartesian coordinates of the sphere center (Å)
+    pub center: [f64; 3],
+
+    /// Fractional coordinates of the sphere center, in [0, 1)
+    pub frac: [f64; 3],
+}
+
+impl VoidSite {
+    /// Ions from CANDIDATE_IONS that fit in this cavity
+    pub fn fitting_ions(&self) -> Vec<(&'static str, f64)> {
+        CANDIDATE_IONS
+            .iter()
+            .filter(|(_, r)| *r <= self.radius)
+            .copied()
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -251,17 +322,41 @@ impl VoidResult {
 /// 2. For each grid point, find distance to nearest atom surface
 /// 3. Points farther than probe_radius from all atoms = voids
 /// 4. Track largest inscribed sphere
+/// 5. Collect distinct local maxima of the distance field (secondary voids)
 ///
 /// # Returns
 /// - `Ok(VoidResult)` with void analysis
 /// - `Err(VoidError)` if inputs are invalid
 pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<VoidResult, VoidError> {
+    calculate_voids_with_progress(structure, config, &|_, _| true)
+}
+
+/// One z-slice of the grid: its largest distance and where, its void
+/// point count, and its distance field.
+type SliceResult = (f64, [f64; 3], usize, Vec<f32>);
+
+/// `calculate_voids` reporting `(steps_done, total_steps)`: one step per
+/// z-slice of the grid, then a last one for the percolation analysis, which
+/// polls the callback while it runs. Returning `false` from `progress`
+/// stops the run with `VoidError::Cancelled`; slices already in flight
+/// still finish.
+///
+/// The slice steps are reported from rayon worker threads, in completion
+/// order.
+pub fn calculate_voids_with_progress(
+    structure: &Structure,
+    config: VoidConfig,
+    progress: &(dyn Fn(usize, usize) -> bool + Sync),
+) -> Result<VoidResult, VoidError> {
     // --- Validation ---
     config.validate()?;
 
     if structure.atoms.is_empty() {
         return Err(VoidError::NoAtoms);
     }
+    if !structure.is_periodic {
+        return Err(VoidError::NonPeriodic);
+    }
 
     let lat = structure.lattice;
 
@@ -271,7 +366,7 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
         Vector3::from(lat[1]),
         Vector3::from(lat[2]),
     ]);
-    basis.try_inverse().ok_or(VoidError::SingularLattice)?; // singular check
+    let cell = PeriodicCell::new(lat).ok_or(VoidError::SingularLattice)?;
 
     // Grid dimensions from lattice vector lengths
     let a_len = Vector3::from(lat[0]).norm();
@@ -300,39 +395,36 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
     let atoms_data: Vec<ProcessedAtom> = structure
         .atoms
         .iter()
-        .filter_map(|a| {
-            let frac = cart_to_frac(a.position, lat)?;
+        .map(|a| {
+            let frac = cell.to_frac(Vector3::from(a.position));
             let raw_radius = match config.radius_type {
                 RadiusType::Ionic => get_atom_ionic_radius(&a.element),
                 RadiusType::VanDerWaals => get_atom_vdw(&a.element),
                 RadiusType::Covalent => get_atom_cov(&a.element),
             };
-            Some(ProcessedAtom {
-                frac: Vector3::from(frac),
+            ProcessedAtom {
+                frac,
                 radius: raw_radius * config.radii_scale,
-            })
+            }
         })
         .collect();
 
-    // Component-wise fractional rounding is an exact minimum image only
-    // for orthogonal cells; for oblique cells the true nearest image can
-    // be a neighboring offset. Detect obliqueness once and only then pay
-    // for the 27-offset scan in the hot loop.
-    let a_col = basis.column(0);
-    let b_col = basis.column(1);
-    let c_col = basis.column(2);
-    let oblique = a_col.dot(&b_col).abs() > 1e-9
-        || a_col.dot(&c_col).abs() > 1e-9
-        || b_col.dot(&c_col).abs() > 1e-9;
-
     // --- Parallel Grid Sampling ---
     // Parallelize over z-slices for good load balancing
-    let results: Vec<(f64, [f64; 3], usize)> = (0..nz)
+    let slices_done = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let results: Option<Vec<SliceResult>> = (0..nz)
         .into_par_iter()
         .map(|k| {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
             let mut local_max_dist = f64::NEG_INFINITY;
             let mut local_best_point = [0.0; 3];
             let mut local_void_count = 0;
+            // Distance field of this slice, kept for the local-maximum search
+            // (f32 halves the memory of large grids).
+            let mut slice = Vec::with_capacity(nx * ny);
 
             let frac_k = k as f64 / nz as f64;
 
@@ -349,32 +441,8 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
                     let mut min_dist_to_surface = f64::MAX;
 
                     for atom in &atoms_data {
-                        // Apply minimum image convention (periodic boundaries)
-                        let mut df = pt_frac - atom.frac;
-                        df.x -= df.x.round();
-                        df.y -= df.y.round();
-                        df.z -= df.z.round();
-
-                        // Convert to Cartesian for distance measurement.
-                        // Oblique cells: the rounded image may not be the
-                        // nearest — check the 27 offsets around it (V-1).
-                        let center_dist = if oblique {
-                            let mut min2 = f64::MAX;
-                            for ox in -1..=1 {
-                                for oy in -1..=1 {
-                                    for oz in -1..=1 {
-                                        let d = basis
-                                            * (df + Vector3::new(
-                                                ox as f64, oy as f64, oz as f64,
-                                            ));
-                                        min2 = min2.min(d.norm_squared());
-                                    }
-                                }
-                            }
-                            min2.sqrt()
-                        } else {
-                            (basis * df).norm()
-                        };
+                        // Minimum image convention (periodic boundaries)
+                        let center_dist = cell.min_image_frac(pt_frac - atom.frac).norm();
 
                         // Distance to atom surface = distance to center - radius
                         let surface_dist = center_dist - atom.radius;
@@ -382,6 +450,8 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
                         min_dist_to_surface = min_dist_to_surface.min(surface_dist);
                     }
 
+                    slice.push(min_dist_to_surface as f32);
+
                     // Track largest sphere
                     if min_dist_to_surface > local_max_dist {
                         local_max_dist = min_dist_to_surface;
@@ -396,17 +466,27 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
                 }
             }
 
-            (local_max_dist, local_best_point, local_void_count)
+            let done = slices_done.fetch_add(1, Ordering::Relaxed) + 1;
+            if !progress(done, nz + 1) {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            Some((local_max_dist, local_best_point, local_void_count, slice))
         })
         .collect();
+    let results = match results {
+        Some(r) if !cancelled.load(Ordering::Relaxed) => r,
+        _ => return Err(VoidError::Cancelled),
+    };
 
     // --- Aggregate Results ---
     let mut max_sphere_radius = f64::NEG_INFINITY;
     let mut max_sphere_center = [0.0; 3];
     let mut total_void_points = 0;
+    let mut field = Vec::with_capacity(total_points);
 
-    for (max_dist, point, void_count) in results {
+    for (max_dist, point, void_count, slice) in results {
         total_void_points += void_count;
+        field.extend(slice);
         if max_dist > max_sphere_radius {
             max_sphere_radius = max_dist;
             max_sphere_center = point;
@@ -424,11 +504,34 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
         0.0
     };
 
+    let void_volume = void_fraction / 100.0 * structure.cell_volume();
+    let specific_pore_volume = structure
+        .density_g_cm3()
+        .filter(|rho| *rho > 0.0)
+        .map(|rho| void_fraction / 100.0 / rho);
+
+    let sites = find_sites(&field, [nx, ny, nz], &cell, config.max_sites);
+    let keep_going = || progress(nz, nz + 1);
+    let percolation = percolation(
+        &field,
+        [nx, ny, nz],
+        &basis,
+        config.probe_radius,
+        &keep_going,
+    )?;
+    if !progress(nz + 1, nz + 1) {
+        return Err(VoidError::Cancelled);
+    }
+
     Ok(VoidResult {
         max_sphere_radius,
         max_sphere_center,
         void_fraction,
+        void_volume,
+        specific_pore_volume,
         config,
+        sites,
+        percolation,
         grid_info: GridInfo {
             nx,
             ny,
@@ -439,7 +542,302 @@ pub fn calculate_voids(structure: &Structure, config: VoidConfig) -> Result<Void
     })
 }
 
-// --- 6. CONVENIENCE FUNCTIONS ---
+/// Local maxima of the periodic distance field (≥ all 26 neighbours, > 0),
+/// largest first. A maximum closer to a larger one than that one's radius
+/// lies inside the same cavity and is dropped.
+fn find_sites(
+    field: &[f32],
+    dims: [usize; 3],
+    cell: &PeriodicCell,
+    max_sites: usize,
+) -> Vec<VoidSite> {
+    let [nx, ny, nz] = dims;
+    let at = |i: isize, j: isize, k: isize| {
+        let i = i.rem_euclid(nx as isize) as usize;
+        let j = j.rem_euclid(ny as isize) as usize;
+        let k = k.rem_euclid(nz as isize) as usize;
+        field[(k * ny + j) * nx + i]
+    };
+
+    let mut maxima: Vec<(f32, [f64; 3])> = Vec::new();
+    for k in 0..nz as isize {
+        for j in 0..ny as isize {
+            for i in 0..nx as isize {
+                let v = at(i, j, k);
+                if v <= 0.0 {
+                    continue;
+                }
+                let mut is_max = true;
+                'nb: for dk in -1..=1 {
+                    for dj in -1..=1 {
+                        for di in -1..=1 {
+                            if (di, dj, dk) != (0, 0, 0) && at(i + di, j + dj, k + dk) > v {
+                                is_max = false;
+                                break 'nb;
+                            }
+                        }
+                    }
+                }
+                if is_max {
+                    let frac = [
+                        i as f64 / nx as f64,
+                        j as f64 / ny as f64,
+                        k as f64 / nz as f64,
+                    ];
+                    maxima.push((v, frac));
+                }
+            }
+        }
+    }
+    maxima.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut sites: Vec<VoidSite> = Vec::new();
+    for (v, frac) in maxima {
+        if sites.len() >= max_sites {
+            break;
+        }
+        let f = Vector3::from(frac);
+        let inside_larger = sites
+            .iter()
+            .any(|site| cell.min_image_frac(f - Vector3::from(site.frac)).norm() < site.radius);
+        if inside_larger {
+            continue;
+        }
+        let cart = cell.to_cart(f);
+        sites.push(VoidSite {
+            radius: v as f64,
+            center: [cart.x, cart.y, cart.z],
+            frac,
+        });
+    }
+    sites
+}
+
+// --- 6. PERCOLATION ---
+
+/// Void components above `threshold` on the periodic grid (6-connected).
+struct Components {
+    /// Component id per grid point, `u32::MAX` for points below threshold
+    label: Vec<u32>,
+    /// Percolation dimensionality of each component
+    dims: Vec<usize>,
+}
+
+/// Flood-fill the points with field > `threshold`. Every point records the
+/// cell image it was reached in; meeting an already-visited point in a
+/// different image means the component wraps around the cell along that
+/// lattice translation. The rank of those translations is the component's
+/// dimensionality.
+fn components(field: &[f32], dims: [usize; 3], threshold: f64) -> Components {
+    let [nx, ny, nz] = dims;
+    let mut label = vec![u32::MAX; field.len()];
+    let mut image = vec![[0i32; 3]; field.len()];
+    let mut comp_dims = Vec::new();
+    let mut stack = Vec::new();
+    let open = |idx: usize| field[idx] as f64 > threshold;
+
+    for seed in 0..field.len() {
+        if label[seed] != u32::MAX || !open(seed) {
+            continue;
+        }
+        let id = comp_dims.len() as u32;
+        let mut wraps: Vec<Vector3<f64>> = Vec::new();
+        label[seed] = id;
+        image[seed] = [0, 0, 0];
+        stack.push(seed);
+
+        while let Some(idx) = stack.pop() {
+            let (i, j, k) = (idx % nx, (idx / nx) % ny, idx / (nx * ny));
+            let here = image[idx];
+            for (axis, step) in [(0, -1), (0, 1), (1, -1), (1, 1), (2, -1), (2, 1)] {
+                let mut pos = [i as i64, j as i64, k as i64];
+                let n = [nx, ny, nz][axis] as i64;
+                let mut img = here;
+                pos[axis] += step;
+                if pos[axis] < 0 {
+                    pos[axis] += n;
+                    img[axis] -= 1;
+                } else if pos[axis] >= n {
+                    pos[axis] -= n;
+                    img[axis] += 1;
+                }
+                let next = (pos[2] as usize * ny + pos[1] as usize) * nx + pos[0] as usize;
+                if !open(next) {
+                    continue;
+                }
+                if label[next] == u32::MAX {
+                    label[next] = id;
+                    image[next] = img;
+                    stack.push(next);
+                } else if image[next] != img && wraps.len() < 3 {
+                    let seen = image[next];
+                    let t = Vector3::new(
+                        (img[0] - seen[0]) as f64,
+                        (img[1] - seen[1]) as f64,
+                        (img[2] - seen[2]) as f64,
+                    );
+                    if is_independent(&wraps, t) {
+                        wraps.push(t);
+                    }
+                }
+            }
+        }
+        comp_dims.push(wraps.len());
+    }
+
+    Components {
+        label,
+        dims: comp_dims,
+    }
+}
+
+/// Whether `t` is outside the span of `basis` (at most two vectors here).
+fn is_independent(basis: &[Vector3<f64>], t: Vector3<f64>) -> bool {
+    match basis {
+        [] => t.norm_squared() > 0.5,
+        [a] => a.cross(&t).norm_squared() > 1e-9,
+        [a, b] => a.cross(b).dot(&t).abs() > 1e-9,
+        _ => false,
+    }
+}
+
+/// Root of `x` and the cell image `x` sits in relative to it. Walks up
+/// once to sum the offsets, then points every node on the path straight at
+/// the root.
+fn find_root(parent: &mut [u32], offset: &mut [[i32; 3]], x: usize) -> (usize, [i32; 3]) {
+    let mut root = x;
+    let mut total = [0i32; 3];
+    while parent[root] as usize != root {
+        let o = offset[root];
+        total = [total[0] + o[0], total[1] + o[1], total[2] + o[2]];
+        root = parent[root] as usize;
+    }
+    let (mut node, mut rest) = (x, total);
+    while node != root {
+        let (next, o) = (parent[node] as usize, offset[node]);
+        parent[node] = root as u32;
+        offset[node] = rest;
+        rest = [rest[0] - o[0], rest[1] - o[1], rest[2] - o[2]];
+        node = next;
+    }
+    (root, total)
+}
+
+/// Largest threshold at which some void component still wraps around the
+/// cell: the clearance at the narrowest point of the widest channel.
+///
+/// Grid points are opened in descending field order and joined to their
+/// open neighbours by a union-find that records the cell image of every
+/// point relative to its root. Joining two points of one component in
+/// different images closes a loop around the cell, so the field value at
+/// that moment is the bottleneck. `None` if no channel with positive
+/// clearance exists. `keep_going` is polled once per grid slice's worth of
+/// points; returning `false` stops the pass with `VoidError::Cancelled`.
+fn bottleneck_radius(
+    field: &[f32],
+    dims: [usize; 3],
+    keep_going: &dyn Fn() -> bool,
+) -> Result<Option<f64>, VoidError> {
+    let [nx, ny, nz] = dims;
+    let mut order: Vec<u32> = (0..field.len() as u32)
+        .filter(|&idx| field[idx as usize] > 0.0)
+        .collect();
+    order.sort_unstable_by(|&a, &b| field[b as usize].total_cmp(&field[a as usize]));
+
+    let mut parent = vec![u32::MAX; field.len()];
+    let mut offset = vec![[0i32; 3]; field.len()];
+    let mut size = vec![1u32; field.len()];
+    let batch = (nx * ny).max(1);
+
+    for (n, &idx) in order.iter().enumerate() {
+        if n % batch == 0 && !keep_going() {
+            return Err(VoidError::Cancelled);
+        }
+        let idx = idx as usize;
+        parent[idx] = idx as u32;
+        let (i, j, k) = (idx % nx, (idx / nx) % ny, idx / (nx * ny));
+        for (axis, step) in [(0, -1), (0, 1), (1, -1), (1, 1), (2, -1), (2, 1)] {
+            let mut pos = [i as i64, j as i64, k as i64];
+            let len = [nx, ny, nz][axis] as i64;
+            let mut shift = [0i32; 3];
+            pos[axis] += step;
+            if pos[axis] < 0 {
+                pos[axis] += len;
+                shift[axis] = -1;
+            } else if pos[axis] >= len {
+                pos[axis] -= len;
+                shift[axis] = 1;
+            }
+            let next = (pos[2] as usize * ny + pos[1] as usize) * nx + pos[0] as usize;
+            if parent[next] == u32::MAX {
+                continue;
+            }
+            let (ra, oa) = find_root(&mut parent, &mut offset, idx);
+            let (rb, ob) = find_root(&mut parent, &mut offset, next);
+            // Image of rb relative to ra once `next` is reached from `idx`.
+            let rel = [
+                oa[0] + shift[0] - ob[0],
+                oa[1] + shift[1] - ob[1],
+                oa[2] + shift[2] - ob[2],
+            ];
+            if ra == rb {
+                if rel != [0, 0, 0] {
+                    return Ok(Some(field[idx] as f64));
+                }
+            } else if size[ra] >= size[rb] {
+                parent[rb] = ra as u32;
+                offset[rb] = rel;
+                size[ra] += size[rb];
+            } else {
+                parent[ra] = rb as u32;
+                offset[ra] = [-rel[0], -rel[1], -rel[2]];
+                size[rb] += size[ra];
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn percolation(
+    field: &[f32],
+    dims: [usize; 3],
+    basis: &Matrix3<f64>,
+    probe_radius: f64,
+    keep_going: &dyn Fn() -> bool,
+) -> Result<Percolation, VoidError> {
+    let [nx, ny, nz] = dims;
+    let comps = components(field, dims, probe_radius);
+    let dimensionality = comps.dims.iter().copied().max().unwrap_or(0);
+
+    let members: Vec<usize> = (0..field.len())
+        .filter(|&idx| {
+            let id = comps.label[idx];
+            id != u32::MAX && comps.dims[id as usize] > 0
+        })
+        .collect();
+    let stride = members.len().div_ceil(MAX_CLUSTER_POINTS).max(1);
+    let cluster_points = members
+        .iter()
+        .step_by(stride)
+        .map(|&idx| {
+            let f = Vector3::new(
+                (idx % nx) as f64 / nx as f64,
+                ((idx / nx) % ny) as f64 / ny as f64,
+                (idx / (nx * ny)) as f64 / nz as f64,
+            );
+            let c = basis * f;
+            [c.x, c.y, c.z]
+        })
+        .collect();
+
+    Ok(Percolation {
+        dimensionality,
+        bottleneck_radius: bottleneck_radius(field, dims, keep_going)?,
+        cluster_points,
+    })
+}
+
+// --- 7. CONVENIENCE FUNCTIONS ---
 
 /// Quick void analysis with default settings (He probe, ionic radii)
 pub fn quick_void_analysis(structure: &Structure) -> Result<VoidResult, VoidError> {
@@ -457,3 +855,144 @@ pub fn analyze_ion_intercalation(
         .map(|(name, radius)| (*name, *radius <= result.max_sphere_radius))
         .collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::elements::get_atomic_mass;
+    use crate::model::structure::Atom;
+
+    fn simple_cubic(a: f64) -> Structure {
+        Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: vec![Atom {
+                element: "Cs".to_string(),
+                position: [0.0, 0.0, 0.0],
+                original_index: 0,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            }],
+            formula: "Cs".to_string(),
+            is_periodic: true,
+        }
+    }
+
+    #[test]
+    fn progress_reaches_every_slice() {
+        let s = simple_cubic(4.0);
+        let config = VoidConfig::geometric();
+        let last = AtomicUsize::new(0);
+        let result = calculate_voids_with_progress(&s, config, &|done, total| {
+            assert!(done <= total);
+            last.fetch_max(done, Ordering::Relaxed);
+            true
+        })
+        .unwrap();
+        // Every slice, then the percolation step.
+        assert_eq!(last.load(Ordering::Relaxed), result.grid_info.nz + 1);
+
+        let plain = calculate_voids(&s, config).unwrap();
+        assert_eq!(plain.grid_info.void_points, result.grid_info.void_points);
+    }
+
+    #[test]
+    fn fcc_reports_octahedral_and_tetrahedral_sites() {
+        let a = 4.0;
+        let fcc = [
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0],
+            [0.5, 0.0, 0.5],
+            [0.0, 0.5, 0.5],
+        ];
+        let atoms = fcc
+            .iter()
+            .enumerate()
+            .map(|(i, f)| Atom {
+                element: "Cu".to_string(),
+                position: [f[0] * a, f[1] * a, f[2] * a],
+                original_index: i,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            })
+            .collect();
+        let s = Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms,
+            formula: "Cu".to_string(),
+            is_periodic: true,
+        };
+        let config = VoidConfig {
+            max_sites: 16,
+            ..VoidConfig::geometric()
+        };
+        let result = calculate_voids(&s, config).unwrap();
+
+        let r_cu = get_atom_ionic_radius("Cu");
+        let oct = a / 2.0 - r_cu;
+        let tet = a * 3f64.sqrt() / 4.0 - r_cu;
+        assert_eq!(result.sites.len(), 12, "{:?}", result.sites);
+        let near = |v: &VoidSite, r: f64| (v.radius - r).abs() < 1e-4;
+        assert!(result.sites[..4].iter().all(|v| near(v, oct)));
+        assert!(result.sites[4..].iter().all(|v| near(v, tet)));
+        assert!((result.sites[0].radius - result.max_sphere_radius).abs() < 1e-4);
+
+        let default = calculate_voids(&s, VoidConfig::geometric()).unwrap();
+        assert_eq!(default.sites.len(), 8);
+    }
+
+    #[test]
+    fn simple_cubic_voids_percolate_in_3d() {
+        // Cs spheres (r = 1.67 Å) on a 4 Å cube leave a 3D void network;
+        // the narrowest passage is the face center, a/√2 from four atoms.
+        let s = simple_cubic(4.0);
+        let bottleneck = 4.0 / 2f64.sqrt() - get_atom_ionic_radius("Cs");
+
+        let li = calculate_voids(&s, VoidConfig::ion_probe(0.76)).unwrap();
+        assert_eq!(li.percolation.dimensionality, 3);
+        assert!(!li.percolation.cluster_points.is_empty());
+        let found = li.percolation.bottleneck_radius.unwrap();
+        assert!((found - bottleneck).abs() < 1e-3, "{found} vs {bottleneck}");
+
+        let too_big = calculate_voids(&s, VoidConfig::ion_probe(1.3)).unwrap();
+        assert_eq!(too_big.percolation.dimensionality, 0);
+        assert!(too_big.percolation.cluster_points.is_empty());
+    }
+
+    #[test]
+    fn void_volume_in_absolute_and_specific_units() {
+        let s = simple_cubic(4.0);
+        let r = calculate_voids(&s, VoidConfig::geometric()).unwrap();
+        let fraction = r.void_fraction / 100.0;
+        assert!((r.void_volume - fraction * 64.0).abs() < 1e-9);
+
+        // cm³/g = Å³ per cell over grams per cell.
+        let grams = get_atomic_mass("Cs") * 1.660_539_066_60e-24;
+        let expected = r.void_volume * 1e-24 / grams;
+        let found = r.specific_pore_volume.unwrap();
+        assert!(
+            (found - expected).abs() < 1e-9 * expected,
+            "{found} vs {expected}"
+        );
+    }
+
+    #[test]
+    fn callback_can_cancel() {
+        let s = simple_cubic(4.0);
+        let result = calculate_voids_with_progress(&s, VoidConfig::geometric(), &|_, _| false);
+        assert!(matches!(result, Err(VoidError::Cancelled)));
+
+        // Cancelling once every slice is done still stops the percolation pass.
+        let nz = calculate_voids(&s, VoidConfig::geometric())
+            .unwrap()
+            .grid_info
+            .nz;
+        let calls = AtomicUsize::new(0);
+        let result = calculate_voids_with_progress(&s, VoidConfig::geometric(), &|_, _| {
+            calls.fetch_add(1, Ordering::Relaxed) < nz
+        });
+        assert!(matches!(result, Err(VoidError::Cancelled)));
+        assert!(calls.load(Ordering::Relaxed) > nz);
+    }
+}
@@ -7,7 +7,8 @@
 //     anomalous dispersion f'/f'')
 //   - Single global isotropic Debye-Waller B factor
 //   - Kα₁ only (no Kα₂ doublet)
-//   - Constant-FWHM display broadening (no Caglioti U,V,W profile)
+//   - Constant-FWHM display broadening (no Caglioti U,V,W profile), with
+//     a Gaussian, Lorentzian or pseudo-Voigt shape (`PeakShape`)
 //   - Occupancy-weighted form factors (virtual-crystal approximation):
 //     correct Bragg intensities for substitutional disorder, no diffuse
 //     scattering / short-range order
@@ -19,13 +20,54 @@ use nalgebra::Vector3;
 use std::cmp::Ordering;
 use std::f64::consts::PI;
 
+/// Profile each peak is broadened with for display. All shapes have unit
+/// area, so switching between them keeps integrated intensities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PeakShape {
+    Gaussian,
+    Lorentzian,
+    /// η·Lorentzian + (1 − η)·Gaussian with a shared FWHM, η in [0, 1].
+    PseudoVoigt {
+        eta: f64,
+    },
+}
+
+impl PeakShape {
+    /// Profile value at offset `x` (degrees) from the peak centre.
+    pub fn value(self, x: f64, fwhm: f64) -> f64 {
+        match self {
+            PeakShape::Gaussian => gaussian(x, fwhm),
+            PeakShape::Lorentzian => lorentzian(x, fwhm),
+            PeakShape::PseudoVoigt { eta } => {
+                let eta = eta.clamp(0.0, 1.0);
+                eta * lorentzian(x, fwhm) + (1.0 - eta) * gaussian(x, fwhm)
+            }
+        }
+    }
+}
+
+fn gaussian(x: f64, fwhm: f64) -> f64 {
+    let sigma = fwhm / (2.0 * (2.0 * 2.0_f64.ln()).sqrt());
+    // Beyond 5σ the Gaussian is below 4·10⁻⁶ of its height.
+    if x.abs() > 5.0 * sigma {
+        return 0.0;
+    }
+    (-0.5 * (x / sigma).powi(2)).exp() / (sigma * (2.0 * PI).sqrt())
+}
+
+fn lorentzian(x: f64, fwhm: f64) -> f64 {
+    let gamma = fwhm / 2.0;
+    gamma / (PI * (x * x + gamma * gamma))
+}
+
 #[derive(Debug, Clone)]
 pub struct XRDSettings {
     pub wavelength: f64, // e.g. 1.5406 for Cu K-alpha
     pub min_2theta: f64,
     pub max_2theta: f64,
-    pub smoothing: f64,          // For potential future Gaussian broadening
+    pub smoothing: f64,          // Display broadening FWHM (degrees 2θ)
     pub temperature_factor: f64, // Debye-Waller B-factor (approx 1.0)
+    pub peak_shape: PeakShape,
 }
 
 impl Default for XRDSettings {
@@ -36,6 +78,7 @@ impl Default for XRDSettings {
             max_2theta: 90.0,
             smoothing: 0.2,
             temperature_factor: 1.0,
+            peak_shape: PeakShape::Gaussian,
         }
     }
 }
@@ -59,7 +102,13 @@ fn canonical_hkl(hkl: (i32, i32, i32)) -> (i32, i32, i32) {
 }
 
 /// Main calculation: Returns discrete peaks with proper physics (Structure Factor)
+///
+/// Diffraction needs a lattice; non-periodic structures yield no peaks.
 pub fn calculate_pattern(structure: &Structure, settings: &XRDSettings) -> Vec<XRDPattern> {
+    if !structure.is_periodic {
+        return vec![];
+    }
+
     // 1. Calculate Real Lattice Vectors (a1, a2, a3)
     let a1 = Vector3::from(structure.lattice[0]);
     let a2 = Vector3::from(structure.lattice[1]);
@@ -291,6 +340,27 @@ pub fn calculate_pattern(structure: &Structure, settings: &XRDSettings) -> Vec<X
     merged_peaks
 }
 
+/// Continuous pattern over the settings' 2θ window, sampled every `step`
+/// degrees: each peak's intensity spread over `settings.peak_shape` with
+/// FWHM `settings.smoothing`. Lorentzian tails are summed over every peak,
+/// not cut off, so they reach the whole window.
+pub fn broadened_curve(peaks: &[XRDPattern], settings: &XRDSettings, step: f64) -> Vec<(f64, f64)> {
+    let fwhm = settings.smoothing.max(1e-3);
+    let n = ((settings.max_2theta - settings.min_2theta) / step)
+        .floor()
+        .max(0.0) as usize;
+    (0..=n)
+        .map(|i| {
+            let t = settings.min_2theta + i as f64 * step;
+            let y = peaks
+                .iter()
+                .map(|p| p.intensity * settings.peak_shape.value(t - p.two_theta, fwhm))
+                .sum();
+            (t, y)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -308,6 +378,7 @@ mod tests {
                     original_index: i,
                     oxidation: None,
                     occupancy: 1.0,
+                    adp: None,
                 })
                 .collect(),
             formula: String::new(),
@@ -522,4 +593,59 @@ mod tests {
             assert!(p.two_theta >= settings.min_2theta && p.two_theta <= settings.max_2theta);
         }
     }
+
+    #[test]
+    fn peak_shapes_preserve_integrated_intensity() {
+        let peak = XRDPattern {
+            two_theta: 50.0,
+            intensity: 100.0,
+            hkl: vec![(1, 0, 0)],
+            d_spacing: 1.82,
+            multiplicity: 1,
+        };
+        let step = 0.005;
+        let area = |peak_shape: PeakShape| {
+            let settings = XRDSettings {
+                min_2theta: 0.0,
+                max_2theta: 100.0,
+                peak_shape,
+                ..Default::default()
+            };
+            broadened_curve(std::slice::from_ref(&peak), &settings, step)
+                .iter()
+                .map(|(_, y)| y * step)
+                .sum::<f64>()
+        };
+        // Lorentzian tails beyond ±50° hold ~0.1 % of the area.
+        for shape in [
+            PeakShape::Gaussian,
+            PeakShape::Lorentzian,
+            PeakShape::PseudoVoigt { eta: 0.5 },
+        ] {
+            let a = area(shape);
+            assert!((a - 100.0).abs() < 0.5, "{shape:?}: area {a:.3}");
+        }
+
+        // Same FWHM, so every shape is at half height 0.1° from the centre.
+        for shape in [
+            PeakShape::Gaussian,
+            PeakShape::Lorentzian,
+            PeakShape::PseudoVoigt { eta: 0.3 },
+        ] {
+            let ratio = shape.value(0.1, 0.2) / shape.value(0.0, 0.2);
+            assert!((ratio - 0.5).abs() < 1e-9, "{shape:?}: {ratio}");
+        }
+    }
+
+    #[test]
+    fn non_periodic_structure_gives_no_peaks() {
+        let a = 5.4309;
+        let mut s = make_structure(
+            [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            vec![("Si", [0.0, 0.0, 0.0])],
+        );
+        assert!(!calculate_pattern(&s, &XRDSettings::default()).is_empty());
+        s.is_periodic = false;
+        assert!(calculate_pattern(&s, &XRDSettings::default()).is_empty());
+    }
 }
@@ -3,8 +3,10 @@
 pub mod calculator;
 
 pub use calculator::{
-    analyze_structure, assess_structure_quality, calculate_bvs, calculate_bvs_all,
-    calculate_bvs_all_auto, calculate_bvs_all_pbc, calculate_bvs_auto, calculate_bvs_deviation,
-    calculate_bvs_pbc, calculate_structure_quality, get_ideal_oxidation_state, AtomBVS,
-    BVSQuality, ParamSource, StructureBVS, BOND_VALENCE_THRESHOLD,
+    analyze_structure, assess_structure_quality, assign_oxidation_states, calculate_bvs,
+    calculate_bvs_all, calculate_bvs_all_auto, calculate_bvs_all_pbc, calculate_bvs_auto,
+    calculate_bvs_deviation, calculate_bvs_pbc, calculate_structure_quality, charge_balance,
+    get_ideal_oxidation_state, global_instability_index, plausible_oxidation_states,
+    with_oxidation_overrides, AtomBVS, BVSQuality, ChargeBalance, ParamSource, StructureBVS,
+    BOND_VALENCE_THRESHOLD,
 };
@@ -80,10 +80,13 @@
 //   (perchlorates, iodates) are not recognized unless the file supplies
 //   explicit oxidation states — then they work via the override.
 //
-// - **No self-consistent valence assignment** for mixed-valence systems
-//   without explicit oxidation states (e.g. Fe₃O₄ from a bare XYZ): the
-//   priority list picks one state per element. Supply charges in the CIF
-//   or set per-site oxidation to resolve.
+// - **Valence assignment is not self-consistent.** For mixed-valence
+//   systems without explicit oxidation states (e.g. Fe₃O₄ from a bare XYZ)
+//   `analyze_structure` picks one state per element from the priority list.
+//   `assign_oxidation_states` searches for a charge-balanced split instead,
+//   ranking sites by their BVS under the default states, but does not
+//   iterate BVS and assignment to convergence. Supply charges in the CIF
+//   or set per-site oxidation to be certain.
 //
 // - **Estimation coverage is partial by design.** Elements whose
 //   O'Keeffe-Brese parameters failed validation against the published
@@ -100,7 +103,8 @@
 
 use crate::model::bvs::BvsParams;
 use crate::model::elements::get_electronegativity;
-use crate::model::structure::Structure;
+use crate::model::structure::{Structure, NEUTRALITY_TOLERANCE};
+use crate::utils::linalg::{lattice_to_matrix3, PeriodicCell};
 use nalgebra::{Matrix3, Vector3};
 use rayon::prelude::*;
 use std::collections::HashMap;
@@ -264,17 +268,6 @@ impl BVSQuality {
 
 // ─── Lattice / image-range helpers ────────────────────────────────────────────
 
-fn lattice_matrix(lat: [[f64; 3]; 3]) -> Matrix3<f64> {
-    Matrix3::new(
-        lat[0][0], lat[0][1], lat[0][2], lat[1][0], lat[1][1], lat[1][2], lat[2][0], lat[2][1],
-        lat[2][2],
-    )
-}
-
-fn cell_volume(lat_mat: &Matrix3<f64>) -> f64 {
-    lat_mat.determinant().abs()
-}
-
 /// Required image-search range for each lattice direction.
 ///
 /// The perpendicular spacing of lattice planes normal to direction `i` is
@@ -285,11 +278,9 @@ fn cell_volume(lat_mat: &Matrix3<f64>) -> f64 {
 /// rigorous for any cell shape AND any input coordinates — including atoms
 /// parsed at fractional 1.0 or outside [0,1), which the pre-wrap bound
 /// `⌈CUTOFF/d_i⌉` silently under-covered by one shell.
-fn image_ranges(lat_mat: &Matrix3<f64>) -> [i32; 3] {
-    let a = lat_mat.row(0).transpose();
-    let b = lat_mat.row(1).transpose();
-    let c = lat_mat.row(2).transpose();
-    let v = cell_volume(lat_mat).max(1e-12);
+fn image_ranges(structure: &Structure) -> [i32; 3] {
+    let [a, b, c] = structure.lattice.map(Vector3::from);
+    let v = structure.cell_volume().max(1e-12);
 
     let d_a = v / b.cross(&c).norm().max(1e-12);
     let d_b = v / c.cross(&a).norm().max(1e-12);
@@ -403,12 +394,15 @@ fn nearest_neighbor_chi(
     atom_idx: usize,
     skip_same_element: bool,
 ) -> Option<f64> {
-    let lat = lattice_matrix(structure.lattice);
-    let pbc = structure.is_periodic && cell_volume(&lat) >= MIN_LATTICE_VOLUME;
+    let pbc = structure.is_periodic && structure.cell_volume() >= MIN_LATTICE_VOLUME;
+    let cell = if pbc {
+        PeriodicCell::new(structure.lattice)
+    } else {
+        None
+    };
 
     let element_i = &structure.atoms[atom_idx].element;
     let p_i = Vector3::from(structure.atoms[atom_idx].position);
-    let inv_lt = lat.transpose().try_inverse();
 
     let mut best_d2 = f64::MAX;
     let mut best_chi = None;
@@ -423,29 +417,11 @@ fn nearest_neighbor_chi(
         }
         let p_j = Vector3::from(neighbor.position);
 
-        let d2_min = if let (true, Some(inv)) = (pbc, inv_lt) {
-            // Minimum image: wrap Δfrac to [-1/2, 1/2) per component. Exact
-            // for the nearest-neighbor question in all but pathologically
-            // oblique cells, and immune to unwrapped input coordinates.
-            let mut df = inv * p_j - inv * p_i;
-            for k in 0..3 {
-                df[k] -= df[k].round();
-            }
-            let mut min2 = f64::MAX;
-            for nx in -1..=1_i32 {
-                for ny in -1..=1_i32 {
-                    for nz in -1..=1_i32 {
-                        let img = df + Vector3::new(nx as f64, ny as f64, nz as f64);
-                        let d2 = (lat.transpose() * img).norm_squared();
-                        if d2 < min2 {
-                            min2 = d2;
-                        }
-                    }
-                }
-            }
-            min2
-        } else {
-            (p_i - p_j).norm_squared()
+        let d2_min = match &cell {
+            // Minimum image, so unwrapped input coordinates still find
+            // the true nearest neighbor.
+            Some(cell) => cell.min_image(p_j - p_i).norm_squared(),
+            None => (p_i - p_j).norm_squared(),
         };
 
         if d2_min < best_d2 {
@@ -510,6 +486,223 @@ fn resolve_valences(structure: &Structure) -> Vec<i32> {
         .collect()
 }
 
+/// Charge balance of the working oxidation states (see `resolve_valences`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeBalance {
+    /// Occupancy-weighted sum of the working states, e per cell.
+    pub net: f64,
+    /// Atoms whose state came from the file rather than the guesser.
+    pub explicit: usize,
+    /// Atoms left without a state (they count as zero charge).
+    pub unknown: usize,
+    /// Total number of atoms.
+    pub total: usize,
+}
+
+impl ChargeBalance {
+    pub fn is_neutral(&self) -> bool {
+        self.net.abs() < NEUTRALITY_TOLERANCE
+    }
+    /// True when at least one state was guessed rather than read.
+    pub fn is_auto_assigned(&self) -> bool {
+        self.explicit < self.total
+    }
+}
+
+/// Sum the working oxidation states of `structure`. The same states feed
+/// the BVS expected values and the Madelung charges, so a non-zero net
+/// charge here flags both of those as suspect.
+pub fn charge_balance(structure: &Structure) -> ChargeBalance {
+    let valences = resolve_valences(structure);
+    let charges: Vec<f64> = valences.iter().map(|&v| v as f64).collect();
+    ChargeBalance {
+        net: structure.net_charge(&charges),
+        explicit: structure
+            .atoms
+            .iter()
+            .filter(|a| a.oxidation.is_some())
+            .count(),
+        unknown: valences.iter().filter(|&&v| v == 0).count(),
+        total: structure.atoms.len(),
+    }
+}
+
+/// Element-level combinations `assign_oxidation_states` tries before it
+/// drops mixed-valence splits from the search.
+const MAX_ASSIGNMENTS: usize = 100_000;
+
+/// Charge-balanced oxidation state of every atom, keyed by index into
+/// `structure.atoms`; atoms with no known state are left out.
+///
+/// Explicit states, anions and dual-role sites keep their working valence
+/// (see `resolve_valences`). Each other cation element takes either one
+/// state from its plausible list for all its sites, or two states split
+/// between its sites, the higher one going to the sites with the larger
+/// BVS under the default states. Of the combinations with zero net charge
+/// the one closest to those BVS (least Σ (BVS − |V|)²) wins; when none
+/// balances, the default states are returned unchanged.
+pub fn assign_oxidation_states(structure: &Structure) -> HashMap<usize, i32> {
+    let mut valences = resolve_valences(structure);
+    let bvs: Vec<f64> = analyze_structure(structure)
+        .atoms
+        .iter()
+        .map(|a| a.bvs)
+        .collect();
+
+    // Cation elements still open, sites sorted most strongly bonded first.
+    let mut free: Vec<(&str, Vec<usize>, Vec<i32>)> = Vec::new();
+    for (i, atom) in structure.atoms.iter().enumerate() {
+        if atom.oxidation.is_some()
+            || is_dual_role(&atom.element)
+            || primary_role(&atom.element) != Role::Cation
+        {
+            continue;
+        }
+        match free.iter_mut().find(|(el, _, _)| *el == atom.element) {
+            Some((_, sites, _)) => sites.push(i),
+            None => {
+                let states = cation_valences(&atom.element)
+                    .iter()
+                    .copied()
+                    .filter(|&v| v != 9)
+                    .collect();
+                free.push((atom.element.as_str(), vec![i], states));
+            }
+        }
+    }
+    for (_, sites, _) in &mut free {
+        sites.sort_by(|&i, &j| bvs[j].total_cmp(&bvs[i]));
+    }
+
+    // Options per element as (high, low, k): the first k sites take the
+    // high state, the rest the low one. Single states come first, in
+    // priority order, so they win ties.
+    let options_for = |sites: &[usize], states: &[i32], mixed: bool| {
+        let mut options: Vec<(i32, i32, usize)> =
+            states.iter().map(|&v| (v, v, sites.len())).collect();
+        if mixed {
+            for (a, &va) in states.iter().enumerate() {
+                for &vb in &states[a + 1..] {
+                    let (hi, lo) = (va.max(vb), va.min(vb));
+                    options.extend((1..sites.len()).map(|k| (hi, lo, k)));
+                }
+            }
+        }
+        options
+    };
+    let count = |mixed: bool| {
+        free.iter().try_fold(1_usize, |n, (_, sites, states)| {
+            n.checked_mul(options_for(sites, states, mixed).len())
+                .filter(|&n| n <= MAX_ASSIGNMENTS)
+        })
+    };
+    let mixed = count(true).is_some();
+    if !mixed && count(false).is_none() {
+        return known_states(&valences);
+    }
+    let options: Vec<Vec<(i32, i32, usize)>> = free
+        .iter()
+        .map(|(_, sites, states)| options_for(sites, states, mixed))
+        .collect();
+
+    // Charge and BVS misfit add up per element, so each option is scored
+    // once and a combination is a sum of its options' scores.
+    let mut is_free = vec![false; structure.atoms.len()];
+    for (_, sites, _) in &free {
+        sites.iter().for_each(|&i| is_free[i] = true);
+    }
+    let fixed_charge: f64 = structure
+        .atoms
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| !is_free[i])
+        .map(|(i, atom)| valences[i] as f64 * atom.occupancy)
+        .sum();
+    let scores: Vec<Vec<(f64, f64)>> = free
+        .iter()
+        .zip(&options)
+        .map(|((_, sites, _), opts)| {
+            opts.iter()
+                .map(|&(hi, lo, k)| {
+                    let (mut charge, mut misfit) = (0.0, 0.0);
+                    for (rank, &i) in sites.iter().enumerate() {
+                        let v = if rank < k { hi } else { lo } as f64;
+                        charge += v * structure.atoms[i].occupancy;
+                        misfit += (bvs[i] - v).powi(2);
+                    }
+                    (charge, misfit)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut best: Option<(f64, Vec<usize>)> = None;
+    let mut pick = vec![0_usize; free.len()];
+    loop {
+        let (mut charge, mut misfit) = (fixed_charge, 0.0);
+        for (&o, s) in pick.iter().zip(&scores) {
+            charge += s[o].0;
+            misfit += s[o].1;
+        }
+        let better = !best.as_ref().is_some_and(|(m, _)| *m <= misfit);
+        if charge.abs() < NEUTRALITY_TOLERANCE && better {
+            best = Some((misfit, pick.clone()));
+        }
+        // Next combination, counting in mixed radix.
+        let Some(e) = (0..pick.len()).find(|&e| pick[e] + 1 < options[e].len()) else {
+            break;
+        };
+        pick[e] += 1;
+        pick[..e].iter_mut().for_each(|p| *p = 0);
+    }
+
+    if let Some((_, pick)) = best {
+        for (((_, sites, _), opts), &o) in free.iter().zip(&options).zip(&pick) {
+            let (hi, lo, k) = opts[o];
+            for (rank, &i) in sites.iter().enumerate() {
+                valences[i] = if rank < k { hi } else { lo };
+            }
+        }
+    }
+    known_states(&valences)
+}
+
+/// Oxidation states worth offering for `element`, cation states first in
+/// priority order; empty when none is known.
+pub fn plausible_oxidation_states(element: &str) -> Vec<i32> {
+    cation_valences(element)
+        .iter()
+        .chain(anion_valences(element))
+        .copied()
+        .filter(|&v| v != 9)
+        .collect()
+}
+
+/// `structure` with every atom of an element in `overrides` given that
+/// oxidation state. Overrides replace file states and guesses alike, and
+/// `assign_oxidation_states` treats them as explicit.
+pub fn with_oxidation_overrides(
+    structure: &Structure,
+    overrides: &HashMap<String, i32>,
+) -> Structure {
+    let mut out = structure.clone();
+    for atom in &mut out.atoms {
+        if let Some(&v) = overrides.get(&atom.element) {
+            atom.oxidation = Some(v);
+        }
+    }
+    out
+}
+
+fn known_states(valences: &[i32]) -> HashMap<usize, i32> {
+    valences
+        .iter()
+        .enumerate()
+        .filter(|&(_, &v)| v != 0)
+        .map(|(i, &v)| (i, v))
+        .collect()
+}
+
 // ─── Pair-parameter resolution & cache ───────────────────────────────────────
 
 /// Resolve parameters for a directed (cation, anion) bond given working
@@ -760,12 +953,12 @@ fn analyze_atom(
 
 /// Full-structure BVS analysis. Parallel over atoms via rayon.
 pub fn analyze_structure(structure: &Structure) -> StructureBVS {
-    let lat_mat = lattice_matrix(structure.lattice);
-    let vol = cell_volume(&lat_mat);
+    let lat_mat = lattice_to_matrix3(structure.lattice);
+    let vol = structure.cell_volume();
     let inv_lat_t = lat_mat.transpose().try_inverse();
     let use_pbc = structure.is_periodic && vol >= MIN_LATTICE_VOLUME && inv_lat_t.is_some();
     let ranges = if use_pbc {
-        image_ranges(&lat_mat)
+        image_ranges(structure)
     } else {
         [0, 0, 0]
     };
@@ -813,15 +1006,15 @@ pub fn analyze_structure(structure: &Structure) -> StructureBVS {
 // tests don't have to change.
 
 pub fn calculate_bvs_pbc(structure: &Structure, atom_idx: usize) -> f64 {
-    let lat_mat = lattice_matrix(structure.lattice);
-    let vol = cell_volume(&lat_mat);
+    let lat_mat = lattice_to_matrix3(structure.lattice);
+    let vol = structure.cell_volume();
     let inv_lat_t = lat_mat.transpose().try_inverse();
     if vol < MIN_LATTICE_VOLUME || inv_lat_t.is_none() {
         return calculate_bvs(structure, atom_idx);
     }
     let valences = resolve_valences(structure);
     let cache = build_pair_cache(structure, &valences);
-    let ranges = image_ranges(&lat_mat);
+    let ranges = image_ranges(structure);
     analyze_atom(
         structure,
         atom_idx,
@@ -836,7 +1029,7 @@ pub fn calculate_bvs_pbc(structure: &Structure, atom_idx: usize) -> f64 {
 }
 
 pub fn calculate_bvs(structure: &Structure, atom_idx: usize) -> f64 {
-    let lat_mat = lattice_matrix(structure.lattice);
+    let lat_mat = lattice_to_matrix3(structure.lattice);
     let valences = resolve_valences(structure);
     let cache = build_pair_cache(structure, &valences);
     analyze_atom(
@@ -870,7 +1063,7 @@ pub fn calculate_bvs_all_auto(structure: &Structure) -> Vec<f64> {
 }
 
 pub fn calculate_bvs_all(structure: &Structure) -> Vec<f64> {
-    let lat_mat = lattice_matrix(structure.lattice);
+    let lat_mat = lattice_to_matrix3(structure.lattice);
     let valences = resolve_valences(structure);
     let cache = build_pair_cache(structure, &valences);
     (0..structure.atoms.len())
@@ -885,14 +1078,14 @@ pub fn calculate_bvs_all(structure: &Structure) -> Vec<f64> {
 }
 
 pub fn calculate_bvs_all_pbc(structure: &Structure) -> Vec<f64> {
-    let lat_mat = lattice_matrix(structure.lattice);
+    let lat_mat = lattice_to_matrix3(structure.lattice);
     let inv_lat_t = lat_mat.transpose().try_inverse();
-    if cell_volume(&lat_mat) < MIN_LATTICE_VOLUME || inv_lat_t.is_none() {
+    if structure.cell_volume() < MIN_LATTICE_VOLUME || inv_lat_t.is_none() {
         return calculate_bvs_all(structure);
     }
     let valences = resolve_valences(structure);
     let cache = build_pair_cache(structure, &valences);
-    let ranges = image_ranges(&lat_mat);
+    let ranges = image_ranges(structure);
     (0..structure.atoms.len())
         .into_par_iter()
         .map(|i| {
@@ -936,6 +1129,36 @@ pub fn assess_structure_quality(structure: &Structure) -> BVSQuality {
     BVSQuality::from_deviation(analyze_structure(structure).gii)
 }
 
+/// Global instability index against the *ideal* oxidation states of
+/// [`get_ideal_oxidation_state`], ignoring any states given in the file or
+/// resolved by charge balance:
+///
+///   GII = √( Σᵢ (BVSᵢ − |Vᵢ|)² / N )
+///
+/// over the N atoms with a known ideal state, BVS with periodic images.
+/// Returns 0.0 when no atom has one.
+pub fn global_instability_index(structure: &Structure) -> f64 {
+    ideal_instability_index(structure, &calculate_bvs_all_pbc(structure))
+}
+
+/// [`global_instability_index`] for bond-valence sums already computed,
+/// parallel to `structure.atoms`, e.g. those of [`analyze_structure`].
+pub fn ideal_instability_index(structure: &Structure, bvs: &[f64]) -> f64 {
+    let (mut sum_sq, mut n) = (0.0_f64, 0_usize);
+    for (atom, v) in structure.atoms.iter().zip(bvs) {
+        let ideal = get_ideal_oxidation_state(&atom.element);
+        if ideal > 0.0 {
+            sum_sq += (v - ideal).powi(2);
+            n += 1;
+        }
+    }
+    if n > 0 {
+        (sum_sq / n as f64).sqrt()
+    } else {
+        0.0
+    }
+}
+
 // ─── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -950,6 +1173,7 @@ mod tests {
             original_index: 0,
             oxidation: None,
             occupancy: 1.0,
+            adp: None,
         }
     }
 
@@ -960,6 +1184,7 @@ mod tests {
             original_index: 0,
             oxidation: Some(ox),
             occupancy: 1.0,
+            adp: None,
         }
     }
 
@@ -970,6 +1195,7 @@ mod tests {
             original_index: 0,
             oxidation: None,
             occupancy: occ,
+            adp: None,
         }
     }
 
@@ -1142,12 +1368,17 @@ mod tests {
     /// for each axis on a representative monoclinic cell.
     #[test]
     fn image_range_monoclinic_is_positive() {
-        let lat = lattice_matrix([
-            [5.0, 0.0, 0.0],
-            [0.0, 5.0, 0.0],
-            [3.0, 0.0, 4.0], // β ≈ 53° (not orthogonal)
-        ]);
-        let r = image_ranges(&lat);
+        let s = Structure {
+            lattice: [
+                [5.0, 0.0, 0.0],
+                [0.0, 5.0, 0.0],
+                [3.0, 0.0, 4.0], // β ≈ 53° (not orthogonal)
+            ],
+            atoms: vec![],
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let r = image_ranges(&s);
         assert!(r[0] >= 1 && r[1] >= 1 && r[2] >= 1, "{:?}", r);
     }
 
@@ -1193,6 +1424,122 @@ mod tests {
         assert_eq!(r.atoms[1].coordination, 6, "Ti CN should be 6");
     }
 
+    /// Fe₃O₄ cannot balance with the guessed Fe³⁺ everywhere; the
+    /// explicit Fe²⁺/Fe³⁺ split of the inverse spinel does.
+    #[test]
+    fn charge_balance_of_magnetite() {
+        let a = 8.4;
+        let cell = |atoms: Vec<Atom>| Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms,
+            formula: "Fe3O4".into(),
+            is_periodic: true,
+        };
+        let oxygens = || (0..4).map(|i| atom("O", [i as f64, 4.0, 4.0]));
+
+        let guessed = cell(
+            (0..3)
+                .map(|i| atom("Fe", [i as f64, 0.0, 0.0]))
+                .chain(oxygens())
+                .collect(),
+        );
+        let b = charge_balance(&guessed);
+        assert!((b.net - 1.0).abs() < 1e-9, "net {}", b.net);
+        assert!(!b.is_neutral());
+        assert!(b.is_auto_assigned());
+
+        let explicit = cell(
+            [2, 3, 3]
+                .iter()
+                .enumerate()
+                .map(|(i, &ox)| atom_ox("Fe", [i as f64, 0.0, 0.0], ox))
+                .chain(oxygens())
+                .collect(),
+        );
+        let b = charge_balance(&explicit);
+        assert!(b.is_neutral(), "net {}", b.net);
+        assert_eq!((b.explicit, b.unknown, b.total), (3, 0, 7));
+    }
+
+    /// Three Fe and four O only balance as Fe²⁺ + 2 Fe³⁺; the priority
+    /// list alone gives Fe³⁺ everywhere and a net +1.
+    #[test]
+    fn assignment_balances_magnetite() {
+        let a = 8.4;
+        let s = Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: (0..3)
+                .map(|i| atom("Fe", [2.0 * i as f64, 0.0, 0.0]))
+                .chain((0..4).map(|i| atom("O", [2.0 * i as f64, 1.9, 0.0])))
+                .collect(),
+            formula: "Fe3O4".into(),
+            is_periodic: true,
+        };
+        let states = assign_oxidation_states(&s);
+        let mut fe: Vec<i32> = (0..3).map(|i| states[&i]).collect();
+        fe.sort();
+        assert_eq!(fe, [2, 3, 3]);
+        assert!((3..7).all(|i| states[&i] == -2));
+    }
+
+    /// Explicit states are kept even when they leave a net charge, and a
+    /// cell with no balanced solution gets the default states back.
+    #[test]
+    fn assignment_keeps_explicit_states_and_falls_back() {
+        let a = 4.0;
+        let cell = |atoms: Vec<Atom>| Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms,
+            formula: String::new(),
+            is_periodic: true,
+        };
+        let explicit = cell(vec![
+            atom_ox("Fe", [0.0, 0.0, 0.0], 2),
+            atom("O", [2.0, 0.0, 0.0]),
+            atom("O", [0.0, 2.0, 0.0]),
+        ]);
+        let states = assign_oxidation_states(&explicit);
+        assert_eq!((states[&0], states[&1], states[&2]), (2, -2, -2));
+
+        // Na is +1 only: Na + O cannot balance.
+        let unbalanced = cell(vec![atom("Na", [0.0, 0.0, 0.0]), atom("O", [2.0, 0.0, 0.0])]);
+        let states = assign_oxidation_states(&unbalanced);
+        assert_eq!((states[&0], states[&1]), (1, -2));
+    }
+
+    /// An override fixes the state of every site of its element, whether
+    /// or not the rest of the cell can balance it.
+    #[test]
+    fn overrides_fix_the_state_of_an_element() {
+        let a = 6.0;
+        let s = Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: vec![
+                atom("Mn", [0.0, 0.0, 0.0]),
+                atom("Mn", [3.0, 0.0, 0.0]),
+                atom_ox("O", [1.5, 0.0, 0.0], -2),
+                atom("O", [4.5, 0.0, 0.0]),
+                atom("O", [0.0, 3.0, 0.0]),
+            ],
+            formula: "Mn2O3".into(),
+            is_periodic: true,
+        };
+        let overrides = HashMap::from([("Mn".to_string(), 3), ("O".to_string(), -2)]);
+        let o = with_oxidation_overrides(&s, &overrides);
+        assert!(o.atoms.iter().all(|a| a.oxidation.is_some()));
+        let states = assign_oxidation_states(&o);
+        assert_eq!((states[&0], states[&1]), (3, 3));
+
+        let forced = HashMap::from([("Mn".to_string(), 2)]);
+        let states = assign_oxidation_states(&with_oxidation_overrides(&s, &forced));
+        // Unbalanced (2·2 − 6), but the user's +2 stands.
+        assert_eq!((states[&0], states[&1]), (2, 2));
+
+        assert_eq!(plausible_oxidation_states("Mn"), [2, 3, 4, 7]);
+        assert_eq!(plausible_oxidation_states("S"), [6, 4, -2]);
+        assert!(plausible_oxidation_states("Xx").is_empty());
+    }
+
     /// Classic ideal oxidation table used by the painter color gradient.
     #[test]
     fn ideal_oxidation_states() {
@@ -1401,4 +1748,54 @@ mod tests {
         let bvs = calculate_bvs_pbc(&s, 0);
         assert!(bvs > 0.0);
     }
+
+    /// Rock-salt NaCl is the textbook unstrained ionic solid: every BVS is
+    /// within a few hundredths of ±1, so the GII is far inside the stable band.
+    #[test]
+    fn gii_of_rock_salt_is_small() {
+        let a = 5.64_f64;
+        let s = Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: vec![
+                atom("Na", [0.0, 0.0, 0.0]),
+                atom("Na", [a / 2.0, a / 2.0, 0.0]),
+                atom("Na", [a / 2.0, 0.0, a / 2.0]),
+                atom("Na", [0.0, a / 2.0, a / 2.0]),
+                atom("Cl", [a / 2.0, 0.0, 0.0]),
+                atom("Cl", [0.0, a / 2.0, 0.0]),
+                atom("Cl", [0.0, 0.0, a / 2.0]),
+                atom("Cl", [a / 2.0, a / 2.0, a / 2.0]),
+            ],
+            formula: "NaCl".into(),
+            is_periodic: true,
+        };
+        let gii = global_instability_index(&s);
+        assert!(gii < 0.1, "NaCl GII {gii}");
+    }
+
+    /// Pushing Ti 0.4 Å off-centre in cubic SrTiO₃ over-bonds it to the
+    /// near apical O: the GII must rise well past the strained threshold.
+    #[test]
+    fn gii_of_distorted_perovskite_is_large() {
+        let a = 3.905_f64;
+        let make = |dz: f64| Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: vec![
+                atom("Sr", [0.0, 0.0, 0.0]),
+                atom("Ti", [a / 2.0, a / 2.0, a / 2.0 + dz]),
+                atom("O", [a / 2.0, a / 2.0, 0.0]),
+                atom("O", [a / 2.0, 0.0, a / 2.0]),
+                atom("O", [0.0, a / 2.0, a / 2.0]),
+            ],
+            formula: "SrTiO3".into(),
+            is_periodic: true,
+        };
+        let cubic = global_instability_index(&make(0.0));
+        let distorted = global_instability_index(&make(0.4));
+        assert!(distorted > 0.2, "distorted GII {distorted}");
+        assert!(
+            distorted > cubic,
+            "distortion must raise the GII: {distorted} vs {cubic}"
+        );
+    }
 }
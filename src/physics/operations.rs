@@ -3,3 +3,5 @@ pub mod conversion;
 pub mod miller_algo;
 pub mod slab;
 pub mod supercell;
+pub mod symmetrize;
+pub mod torsion;
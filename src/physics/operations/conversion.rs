@@ -3,14 +3,12 @@
 // Converts a Structure between Primitive and Conventional standard cells
 // using the Moyo symmetry library (IUCr/Spglib conventions).
 
-use crate::model::elements::get_atomic_number;
-use crate::model::structure::{Atom, Structure};
-use crate::utils::linalg::{cart_to_frac, frac_to_cart, lattice_to_matrix3, matrix3_to_arr};
-use moyo::base::{AngleTolerance, Cell, Lattice};
+use crate::model::structure::{generate_formula, Atom, Structure};
+use crate::physics::analysis::symmetry::{moyo_cell, species_element};
+use crate::utils::linalg::{frac_to_cart, matrix3_to_arr};
+use moyo::base::AngleTolerance;
 use moyo::data::Setting;
 use moyo::MoyoDataset;
-use nalgebra::Vector3;
-use std::collections::HashMap;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum CellType {
@@ -19,42 +17,10 @@ pub enum CellType {
 }
 
 pub fn convert_structure(structure: &Structure, cell_type: CellType) -> Result<Structure, String> {
-    // 1. Build element ↔ integer mapping using atomic numbers.
-    //    This is scientifically correct (unique per element) and avoids
-    //    fragile alphabetical-sort or insertion-order schemes.
-    let mut element_to_z: HashMap<String, i32> = HashMap::new();
-    let mut z_to_element: HashMap<i32, String> = HashMap::new();
-
-    for atom in &structure.atoms {
-        if !element_to_z.contains_key(&atom.element) {
-            let z = get_atomic_number(&atom.element);
-            // Fallback: if element is unknown, assign a unique negative id
-            let id = if z > 0 {
-                z
-            } else {
-                -(element_to_z.len() as i32 + 1)
-            };
-            element_to_z.insert(atom.element.clone(), id);
-            z_to_element.insert(id, atom.element.clone());
-        }
-    }
-
-    // 2. Prepare Moyo input: lattice matrix (rows = lattice vectors) + fractional positions
-    let lat_mat = lattice_to_matrix3(structure.lattice);
-
-    let mut positions = Vec::with_capacity(structure.atoms.len());
-    let mut numbers = Vec::with_capacity(structure.atoms.len());
-
-    for atom in &structure.atoms {
-        let frac = cart_to_frac(atom.position, structure.lattice)
-            .ok_or("Lattice is singular (volume is zero)")?;
-        positions.push(Vector3::from(frac));
-        numbers.push(element_to_z[&atom.element]);
-    }
+    // 1. Moyo input: lattice rows, fractional positions, atomic numbers
+    let moyo_cell = moyo_cell(structure)?;
 
-    let moyo_cell = Cell::new(Lattice::new(lat_mat), positions, numbers);
-
-    // 3. Symmetry detection + standardization
+    // 2. Symmetry detection + standardization
     let dataset = MoyoDataset::new(
         &moyo_cell,
         crate::physics::analysis::symmetry::SYMPREC,
@@ -64,13 +30,13 @@ pub fn convert_structure(structure: &Structure, cell_type: CellType) -> Result<S
     )
     .map_err(|e| format!("Symmetry search failed: {:?}", e))?;
 
-    // 4. Select the output cell
+    // 3. Select the output cell
     let result_cell = match cell_type {
         CellType::Primitive => &dataset.prim_std_cell,
         CellType::Conventional => &dataset.std_cell,
     };
 
-    // 5. Convert Moyo result back to Structure.
+    // 4. Convert Moyo result back to Structure.
     //    moyo stores lattice vectors as COLUMNS of `basis` (Lattice::new
     //    transposes its row-vector input), while Structure.lattice holds
     //    rows = vectors — transpose back.
@@ -88,10 +54,9 @@ pub fn convert_structure(structure: &Structure, cell_type: CellType) -> Result<S
         let position = frac_to_cart(frac, new_lattice);
 
         let type_id = result_cell.numbers[i];
-        let element = z_to_element
-            .get(&type_id)
-            .cloned()
-            .unwrap_or_else(|| "X".to_string());
+        let element = species_element(structure, &moyo_cell, type_id)
+            .unwrap_or("X")
+            .to_string();
 
         new_atoms.push(Atom {
             element,
@@ -102,11 +67,12 @@ pub fn convert_structure(structure: &Structure, cell_type: CellType) -> Result<S
             // oxidation hint here — downstream BVS will re-infer.
             oxidation: None,
             occupancy: 1.0,
+            adp: None,
         });
     }
 
-    // 6. Build formula string
-    let formula = build_formula(&new_atoms);
+    // 5. Build formula string
+    let formula = generate_formula(&new_atoms);
 
     Ok(Structure {
         lattice: new_lattice,
@@ -116,31 +82,10 @@ pub fn convert_structure(structure: &Structure, cell_type: CellType) -> Result<S
     })
 }
 
-/// Build a chemical formula string sorted alphabetically (e.g. "Cl6Cs2Mo").
-fn build_formula(atoms: &[Atom]) -> String {
-    let mut counts: HashMap<&str, usize> = HashMap::new();
-    for atom in atoms {
-        *counts.entry(&atom.element).or_insert(0) += 1;
-    }
-    let mut parts: Vec<_> = counts.into_iter().collect();
-    parts.sort_by(|a, b| a.0.cmp(b.0));
-
-    parts
-        .iter()
-        .map(|(el, count)| {
-            if *count > 1 {
-                format!("{}{}", el, count)
-            } else {
-                (*el).to_string()
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nalgebra::Vector3;
 
     /// Regression for the moyo basis row/column mix-up: converting a
     /// hexagonal structure must return a lattice with the hexagonal metric
@@ -164,6 +109,7 @@ mod tests {
                 original_index: i,
                 oxidation: None,
                 occupancy: 1.0,
+                adp: None,
             })
             .collect();
         let s = Structure {
@@ -203,6 +203,7 @@ pub fn generate_slab(
             // tuple pipeline. BVS will fall back to inference.
             oxidation: None,
             occupancy: 1.0,
+            adp: None,
         });
     }
 
@@ -322,6 +323,7 @@ mod tests {
                     original_index: i,
                     oxidation: None,
                     occupancy: 1.0,
+                    adp: None,
                 })
                 .collect(),
             formula: "TiO2".into(),
@@ -148,6 +148,7 @@ mod tests {
                 original_index: 0,
                 oxidation: None,
                 occupancy: 1.0,
+                adp: None,
             }],
             formula: "Na".into(),
             is_periodic: true,
@@ -0,0 +1,195 @@
+// src/physics/operations/symmetrize.rs
+//
+// Snaps a slightly distorted structure (typically DFT-relaxed coordinates
+// with numerical noise) onto the exact positions of its detected space
+// group. Every atom is replaced by the average of all symmetry images that
+// land on it, i.e. the projection onto the symmetric configuration.
+//
+// Only positions move: the lattice, species, oxidation states and
+// occupancies are kept as given.
+
+use crate::model::structure::Structure;
+use crate::physics::analysis::symmetry::moyo_cell;
+use crate::utils::linalg::frac_to_cart;
+use moyo::base::{AngleTolerance, Cell};
+use moyo::data::Setting;
+use moyo::MoyoDataset;
+use nalgebra::Vector3;
+
+/// Operations found on noisy input carry slightly fitted translations, so
+/// one projection is not exactly invariant. Re-detecting on the cleaned
+/// positions converges in a couple of passes.
+const MAX_PASSES: usize = 5;
+
+pub struct Symmetrized {
+    pub structure: Structure,
+    /// Space group number the structure was snapped to.
+    pub number: i32,
+    /// Largest Cartesian shift applied to any atom (Å).
+    pub max_displacement: f64,
+}
+
+/// Symmetrize `structure` using the space group moyo finds at `symprec` (Å).
+pub fn symmetrize(structure: &Structure, symprec: f64) -> Result<Symmetrized, String> {
+    if !structure.is_periodic {
+        return Err("Symmetrization needs a periodic structure".to_string());
+    }
+    if structure.atoms.is_empty() {
+        return Err("Structure has no atoms".to_string());
+    }
+
+    let lattice = structure.lattice;
+    let input = moyo_cell(structure)?;
+    let numbers = &input.numbers;
+    let original = &input.positions;
+
+    let mut frac = original.clone();
+    let mut number = 0;
+    for _ in 0..MAX_PASSES {
+        let cell = Cell::new(input.lattice.clone(), frac.clone(), numbers.clone());
+        let dataset = MoyoDataset::new(
+            &cell,
+            symprec,
+            AngleTolerance::Default,
+            Setting::Spglib,
+            true,
+        )
+        .map_err(|e| format!("Symmetry search failed: {:?}", e))?;
+        number = dataset.number;
+
+        let mut sum = vec![Vector3::zeros(); frac.len()];
+        let mut count = vec![0usize; frac.len()];
+        for op in dataset.operations.iter() {
+            let rot = op.rotation.map(|v| v as f64);
+            for (i, x) in frac.iter().enumerate() {
+                let image = rot * x + op.translation;
+                let j = nearest_same_species(&frac, numbers, numbers[i], image, lattice);
+                // Accumulate next to atom j's own position so the average
+                // never straddles a cell boundary.
+                sum[j] += frac[j] + wrap(image - frac[j]);
+                count[j] += 1;
+            }
+        }
+
+        let mut change: f64 = 0.0;
+        for j in 0..frac.len() {
+            if count[j] == 0 {
+                continue;
+            }
+            let next = sum[j] / count[j] as f64;
+            change = change.max(cart_norm(next - frac[j], lattice));
+            frac[j] = next;
+        }
+        if change < 1e-10 {
+            break;
+        }
+    }
+
+    let mut out = structure.clone();
+    let mut max_displacement: f64 = 0.0;
+    for ((atom, x), x0) in out.atoms.iter_mut().zip(&frac).zip(original) {
+        max_displacement = max_displacement.max(cart_norm(x - x0, lattice));
+        atom.position = frac_to_cart([x.x, x.y, x.z], lattice);
+    }
+
+    Ok(Symmetrized {
+        structure: out,
+        number,
+        max_displacement,
+    })
+}
+
+/// Index of the atom of species `z` closest to `image` under PBC.
+fn nearest_same_species(
+    frac: &[Vector3<f64>],
+    numbers: &[i32],
+    z: i32,
+    image: Vector3<f64>,
+    lattice: [[f64; 3]; 3],
+) -> usize {
+    let mut best = (usize::MAX, f64::MAX);
+    for (j, x) in frac.iter().enumerate() {
+        if numbers[j] != z {
+            continue;
+        }
+        let d = cart_norm(wrap(image - x), lattice);
+        if d < best.1 {
+            best = (j, d);
+        }
+    }
+    best.0
+}
+
+fn wrap(d: Vector3<f64>) -> Vector3<f64> {
+    d.map(|v| v - v.round())
+}
+
+fn cart_norm(d: Vector3<f64>, lattice: [[f64; 3]; 3]) -> f64 {
+    Vector3::from(frac_to_cart([d.x, d.y, d.z], lattice)).norm()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+    use crate::physics::analysis::symmetry;
+
+    fn rock_salt(noise: f64) -> Structure {
+        let a = 5.6402;
+        let lattice = [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]];
+        let sites = [
+            ("Na", [0.0, 0.0, 0.0]),
+            ("Na", [0.5, 0.5, 0.0]),
+            ("Na", [0.5, 0.0, 0.5]),
+            ("Na", [0.0, 0.5, 0.5]),
+            ("Cl", [0.5, 0.5, 0.5]),
+            ("Cl", [0.0, 0.0, 0.5]),
+            ("Cl", [0.0, 0.5, 0.0]),
+            ("Cl", [0.5, 0.0, 0.0]),
+        ];
+        let atoms = sites
+            .iter()
+            .enumerate()
+            .map(|(i, (el, f))| {
+                // Deterministic pseudo-random jitter, a few mÅ per axis.
+                let k = i as f64 + 1.0;
+                let mut p = frac_to_cart(*f, lattice);
+                p[0] += noise * (1.7 * k).sin();
+                p[1] += noise * (2.3 * k).cos();
+                p[2] += noise * (3.1 * k).sin();
+                Atom {
+                    element: el.to_string(),
+                    position: p,
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                }
+            })
+            .collect();
+        Structure {
+            lattice,
+            atoms,
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        }
+    }
+
+    #[test]
+    fn noisy_rock_salt_snaps_back_to_fm3m() {
+        let noisy = rock_salt(0.005);
+        assert_ne!(symmetry::analyze(&noisy).unwrap().number, 225);
+
+        let result = symmetrize(&noisy, 0.05).expect("symmetrize failed");
+        assert_eq!(result.number, 225);
+        assert!(result.max_displacement > 1e-3 && result.max_displacement < 0.02);
+        assert_eq!(symmetry::analyze(&result.structure).unwrap().number, 225);
+    }
+
+    #[test]
+    fn ideal_structure_does_not_move() {
+        let result = symmetrize(&rock_salt(0.0), 1e-3).expect("symmetrize failed");
+        assert_eq!(result.number, 225);
+        assert!(result.max_displacement < 1e-9);
+    }
+}
@@ -0,0 +1,253 @@
+// src/physics/operations/torsion.rs
+//
+// Torsion (dihedral) scans of molecules. For a torsion A–B–C–D the bond
+// graph is cut at the central bond B–C: the fragment holding C (and D)
+// turns about the B→C axis while the side holding B (and A) stays put.
+// A ring through B–C cannot be split that way and is rejected.
+
+use crate::model::elements::get_covalent_radius;
+use crate::model::structure::Structure;
+use crate::utils::geometry::calculate_dihedral;
+use nalgebra::{Point3, Rotation3, Unit, Vector3};
+use std::collections::VecDeque;
+
+/// Two atoms count as bonded within this factor of their summed covalent
+/// radii, the default bond tolerance of the view.
+const COVALENT_TOLERANCE: f64 = 1.15;
+
+/// Bonds of a molecule from covalent radii alone, as `(i, j, length)`
+/// with `i < j`. Unlike the bonds on screen they don't change with hidden
+/// atoms, pair cutoffs or the tab's bond tolerance, so a scan saved twice
+/// moves the same fragment.
+pub fn covalent_bonds(structure: &Structure) -> Vec<(usize, usize, f64)> {
+    let atoms = &structure.atoms;
+    let radii: Vec<f64> = atoms
+        .iter()
+        .map(|a| get_covalent_radius(&a.element))
+        .collect();
+    let mut bonds = Vec::new();
+    for i in 0..atoms.len() {
+        for j in i + 1..atoms.len() {
+            let d = (Vector3::from(atoms[i].position) - Vector3::from(atoms[j].position)).norm();
+            if d <= COVALENT_TOLERANCE * (radii[i] + radii[j]) {
+                bonds.push((i, j, d));
+            }
+        }
+    }
+    bonds
+}
+
+/// Atoms that move when the torsion `[a, b, c, d]` turns: everything
+/// reachable from `c` without crossing the `b`–`c` bond. `bonds` are
+/// `(i, j, length)` pairs of structure indices, as from `covalent_bonds`.
+pub fn moving_fragment(
+    n_atoms: usize,
+    bonds: &[(usize, usize, f64)],
+    torsion: [usize; 4],
+) -> Result<Vec<usize>, String> {
+    let [a, b, c, d] = torsion;
+    if torsion.iter().any(|&i| i >= n_atoms) {
+        return Err(format!("Atom indices must be below {}", n_atoms));
+    }
+    if (1..4).any(|k| torsion[..k].contains(&torsion[k])) {
+        return Err("The four torsion atoms must be distinct".to_string());
+    }
+
+    let mut adjacency = vec![Vec::new(); n_atoms];
+    for &(i, j, _) in bonds {
+        if i != j {
+            adjacency[i].push(j);
+            adjacency[j].push(i);
+        }
+    }
+    if !adjacency[b].contains(&c) {
+        return Err(format!("Atoms {} and {} are not bonded", b, c));
+    }
+
+    let mut seen = vec![false; n_atoms];
+    seen[c] = true;
+    let mut queue = VecDeque::from([c]);
+    while let Some(i) = queue.pop_front() {
+        for &j in &adjacency[i] {
+            if (i == c && j == b) || seen[j] {
+                continue;
+            }
+            seen[j] = true;
+            queue.push_back(j);
+        }
+    }
+
+    if seen[b] || seen[a] {
+        return Err(format!(
+            "Bond {}–{} is part of a ring; the molecule cannot be split there",
+            b, c
+        ));
+    }
+    if !seen[d] {
+        return Err(format!("Atom {} is not on the {} side of the bond", d, c));
+    }
+    Ok((0..n_atoms).filter(|&i| seen[i]).collect())
+}
+
+/// Copy of `structure` with the `moving` atoms turned by `angle_deg` about
+/// the axis from atom `b` to atom `c` (right-handed, so the dihedral
+/// A–B–C–D grows by `angle_deg`).
+pub fn rotate_fragment(
+    structure: &Structure,
+    b: usize,
+    c: usize,
+    moving: &[usize],
+    angle_deg: f64,
+) -> Structure {
+    let pb = Point3::from(structure.atoms[b].position);
+    let pc = Point3::from(structure.atoms[c].position);
+    let mut out = structure.clone();
+    let Some(axis) = Unit::try_new(pc - pb, 1e-8) else {
+        return out;
+    };
+    let rot = Rotation3::from_axis_angle(&axis, angle_deg.to_radians());
+    for &i in moving {
+        let p = Point3::from(structure.atoms[i].position);
+        let turned = pc + rot * (p - pc);
+        out.atoms[i].position = [turned.x, turned.y, turned.z];
+    }
+    out
+}
+
+/// Dihedral A–B–C–D of `structure` in degrees, within ±180°.
+pub fn dihedral(structure: &Structure, torsion: [usize; 4]) -> f64 {
+    let p = torsion.map(|i| structure.atoms[i].position);
+    calculate_dihedral(p[0], p[1], p[2], p[3])
+}
+
+/// `steps + 1` frames starting from `structure` itself, each turned
+/// `step_deg` further than the last, with the dihedral of every frame.
+pub fn scan(
+    structure: &Structure,
+    torsion: [usize; 4],
+    moving: &[usize],
+    step_deg: f64,
+    steps: usize,
+) -> Vec<(f64, Structure)> {
+    (0..=steps)
+        .map(|k| {
+            let frame = rotate_fragment(
+                structure,
+                torsion[1],
+                torsion[2],
+                moving,
+                k as f64 * step_deg,
+            );
+            (dihedral(&frame, torsion), frame)
+        })
+        .collect()
+}
+
+/// Largest change in any bond length between two frames; zero for a rigid
+/// rotation.
+pub fn max_bond_change(a: &Structure, b: &Structure, bonds: &[(usize, usize, f64)]) -> f64 {
+    let length = |s: &Structure, i: usize, j: usize| {
+        (Vector3::from(s.atoms[i].position) - Vector3::from(s.atoms[j].position)).norm()
+    };
+    bonds
+        .iter()
+        .map(|&(i, j, _)| (length(a, i, j) - length(b, i, j)).abs())
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::Atom;
+
+    /// Planar cis H–C–C–H chain (dihedral 0°) plus one more H on each carbon.
+    fn chain() -> (Structure, Vec<(usize, usize, f64)>) {
+        let sites = [
+            ("H", [-0.5, 1.0, 0.0]),  // 0, on C1
+            ("C", [0.0, 0.0, 0.0]),   // 1
+            ("C", [1.5, 0.0, 0.0]),   // 2
+            ("H", [2.0, 1.0, 0.0]),   // 3, on C2
+            ("H", [-0.5, -1.0, 0.0]), // 4, on C1
+            ("H", [2.0, 0.0, 1.0]),   // 5, on C2
+        ];
+        let atoms = sites
+            .iter()
+            .enumerate()
+            .map(|(i, (el, p))| Atom {
+                element: el.to_string(),
+                position: *p,
+                original_index: i,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            })
+            .collect();
+        let structure = Structure {
+            lattice: [[20.0, 0.0, 0.0], [0.0, 20.0, 0.0], [0.0, 0.0, 20.0]],
+            atoms,
+            formula: "C2H4".to_string(),
+            is_periodic: false,
+        };
+        let bonds = vec![
+            (0, 1, 1.1),
+            (1, 2, 1.5),
+            (2, 3, 1.1),
+            (1, 4, 1.1),
+            (2, 5, 1.1),
+        ];
+        (structure, bonds)
+    }
+
+    #[test]
+    fn covalent_bonds_find_the_chain() {
+        let (s, bonds) = chain();
+        let mut expected: Vec<(usize, usize)> = bonds.iter().map(|&(i, j, _)| (i, j)).collect();
+        expected.sort();
+        let found: Vec<(usize, usize)> =
+            covalent_bonds(&s).iter().map(|&(i, j, _)| (i, j)).collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn fragment_is_split_at_the_central_bond() {
+        let (s, bonds) = chain();
+        let n = s.atoms.len();
+        assert_eq!(
+            moving_fragment(n, &bonds, [0, 1, 2, 3]).unwrap(),
+            vec![2, 3, 5]
+        );
+        // Reversed torsion moves the other side.
+        assert_eq!(
+            moving_fragment(n, &bonds, [3, 2, 1, 0]).unwrap(),
+            vec![0, 1, 4]
+        );
+
+        assert!(moving_fragment(n, &bonds, [0, 1, 3, 2]).is_err()); // 1–3 not bonded
+        assert!(moving_fragment(n, &bonds, [0, 1, 1, 2]).is_err());
+        let mut ring = bonds.clone();
+        ring.push((4, 5, 2.0));
+        assert!(moving_fragment(n, &ring, [0, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn scan_steps_the_dihedral_rigidly() {
+        let (s, bonds) = chain();
+        let torsion = [0, 1, 2, 3];
+        let moving = moving_fragment(s.atoms.len(), &bonds, torsion).unwrap();
+        let frames = scan(&s, torsion, &moving, 30.0, 12);
+        assert_eq!(frames.len(), 13);
+
+        let start = frames[0].0;
+        assert!(start.abs() < 1e-9);
+        for (k, (angle, frame)) in frames.iter().enumerate() {
+            let expected = start + 30.0 * k as f64;
+            // Compare on the circle: 180° and −180° are the same torsion.
+            let diff = (angle - expected).rem_euclid(360.0);
+            assert!(!(1e-6..=360.0 - 1e-6).contains(&diff), "frame {k}: {angle}");
+            assert!(max_bond_change(&s, frame, &bonds) < 1e-9);
+            // The fixed side never moves.
+            assert_eq!(frame.atoms[0].position, s.atoms[0].position);
+            assert_eq!(frame.atoms[4].position, s.atoms[4].position);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bonds;
 pub mod export;
 pub mod painter;
 pub mod polyhedra;
@@ -5,6 +6,7 @@ pub mod polyhedra_lighting;
 pub mod primitives;
 pub mod scene;
 pub mod sprite_cache;
+pub mod threejs;
 
 // Re-export specific functions to keep the API clean for the rest of the app
 pub use export::setup_drawing;
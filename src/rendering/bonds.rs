@@ -0,0 +1,305 @@
+// src/rendering/bonds.rs
+//
+// Bond detection over scene atoms. The painter draws these every frame and
+// the CSV export lists them, so both see exactly the same bonds: a pair is
+// bonded when its Cartesian distance lies between `MIN_BOND_DIST` and the
+// tab's per-pair limit (`TabState::max_bond_length`), unless a per-pair
+// rule forces it either way (`TabState::is_bonded`).
+//
+// The neighbor search is memoized per tab (`BondCache`): dragging the
+// tolerance slider only re-filters the cached candidate pairs, and the grid
+// search reruns only when the scene atoms or the pair cutoffs and rules
+// change.
+
+use crate::config::Config;
+use crate::rendering::scene::{self, RenderAtom};
+use crate::state::{BondRule, TabState, ALWAYS_BOND_STRETCH};
+use crate::utils::spatial_grid::SpatialGrid;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// Pairs closer than this are overlapping atoms, not bonds.
+pub const MIN_BOND_DIST: f64 = 0.4;
+
+/// Tolerances are compared after rounding to this step, so slider values
+/// that differ only by float noise share one cached bond list.
+const TOLERANCE_QUANTUM: f64 = 1e-3;
+
+/// Atom pairs as `(i, j, distance)` with `i < j`.
+pub type BondList = Vec<(usize, usize, f64)>;
+
+/// Memoized bond search for one tab, held in `TabState::bond_cache`.
+///
+/// Entries are keyed by a fingerprint of the scene atoms (element and
+/// Cartesian position, in order) and the pair cutoffs and rules, so
+/// structure edits, supercells and cutoff changes invalidate it without any call
+/// site having to remember to. Rotation, zoom and pan leave the atoms'
+/// Cartesian positions alone and keep hitting the cache.
+#[derive(Default)]
+pub struct BondCache {
+    key: Option<u64>,
+    /// Every pair within the grid query radius.
+    candidates: BondList,
+    /// Last tolerance-filtered list, keyed by quantized tolerance.
+    bonds: Option<(i64, BondList)>,
+}
+
+/// Bond tolerance the tab actually uses; out-of-range slider values fall
+/// back to the default.
+pub fn effective_tolerance(tab: &TabState) -> f64 {
+    if tab.view.bond_cutoff < 0.1 || tab.view.bond_cutoff > 2.0 {
+        1.15
+    } else {
+        tab.view.bond_cutoff
+    }
+}
+
+/// Every bonded pair among the atoms passing `include`, as `(i, j, length)`
+/// with `i < j` indexing `atoms`.
+///
+/// Results come from the tab's [`BondCache`] when the atoms, cutoffs, rules
+/// and (quantized) tolerance match the previous call; `include` is applied on
+/// top, so callers with different filters share one cache entry.
+pub fn find_bonds<F>(tab: &TabState, atoms: &[RenderAtom], tolerance: f64, include: F) -> BondList
+where
+    F: Fn(&RenderAtom) -> bool,
+{
+    let tol_key = (tolerance / TOLERANCE_QUANTUM).round() as i64;
+    let tolerance = tol_key as f64 * TOLERANCE_QUANTUM;
+    let key = scene_fingerprint(tab, atoms);
+
+    let mut cache = tab.bond_cache.borrow_mut();
+    if cache.key != Some(key) {
+        cache.candidates = candidate_pairs(tab, atoms);
+        cache.bonds = None;
+        cache.key = Some(key);
+    }
+    if cache.bonds.as_ref().map(|(k, _)| *k) != Some(tol_key) {
+        let bonds = cache
+            .candidates
+            .iter()
+            .filter(|&&(i, j, dist)| {
+                tab.is_bonded(&atoms[i].element, &atoms[j].element, dist, tolerance)
+            })
+            .copied()
+            .collect();
+        cache.bonds = Some((tol_key, bonds));
+    }
+
+    let Some((_, bonds)) = &cache.bonds else {
+        return Vec::new();
+    };
+    bonds
+        .iter()
+        .filter(|&&(i, j, _)| include(&atoms[i]) && include(&atoms[j]))
+        .copied()
+        .collect()
+}
+
+/// All pairs between `MIN_BOND_DIST` and the search radius, whatever the
+/// tolerance. Uses a spatial grid to avoid the O(N²) nested scan. Grid cell
+/// size = max bond distance (4 Å), so each query visits a 3×3×3 block at
+/// most.
+fn candidate_pairs(tab: &TabState, atoms: &[RenderAtom]) -> BondList {
+    // 4 Å covers every tolerance-rule bond; a per-pair cutoff or an
+    // "always" rule may ask for more, in which case the grid search widens
+    // to match. Rules are sized for the top of the tolerance slider.
+    let always = tab
+        .bond_pair_rules
+        .iter()
+        .filter(|(_, &rule)| rule == BondRule::Always)
+        .map(|((a, b), _)| tab.max_bond_length(a, b, 2.0) * ALWAYS_BOND_STRETCH);
+    let max_bond_query = tab
+        .bond_pair_cutoffs
+        .values()
+        .copied()
+        .chain(always)
+        .fold(4.0_f64, f64::max);
+
+    let grid = SpatialGrid::build(atoms, max_bond_query, |_| true);
+    let mut neighbors: Vec<usize> = Vec::with_capacity(64);
+    let mut pairs = Vec::new();
+
+    for (i, r1) in atoms.iter().enumerate() {
+        neighbors.clear();
+        grid.query(r1.cart_pos, max_bond_query, &mut neighbors);
+
+        for &j in &neighbors {
+            // Enforce unique (i, j) ordering so each pair is emitted once.
+            if j <= i {
+                continue;
+            }
+            let dist = distance(r1.cart_pos, atoms[j].cart_pos);
+            if dist > MIN_BOND_DIST && dist < max_bond_query {
+                pairs.push((i, j, dist));
+            }
+        }
+    }
+    pairs
+}
+
+fn scene_fingerprint(tab: &TabState, atoms: &[RenderAtom]) -> u64 {
+    let mut h = DefaultHasher::new();
+    atoms.len().hash(&mut h);
+    for atom in atoms {
+        atom.element.hash(&mut h);
+        atom.cart_pos.map(f64::to_bits).hash(&mut h);
+    }
+    let mut cutoffs: Vec<_> = tab.bond_pair_cutoffs.iter().collect();
+    cutoffs.sort_by(|a, b| a.0.cmp(b.0));
+    for (pair, d) in cutoffs {
+        pair.hash(&mut h);
+        d.to_bits().hash(&mut h);
+    }
+    let mut rules: Vec<_> = tab.bond_pair_rules.iter().collect();
+    rules.sort_by(|a, b| a.0.cmp(b.0));
+    rules.hash(&mut h);
+    h.finish()
+}
+
+/// Unique bonds of the structure itself, as `(atom_i, atom_j, length)` with
+/// structure indices and `atom_i <= atom_j`. Bonds across a cell boundary
+/// appear once, however many periodic images the scene holds: two bonds are
+/// the same when they join the same pair of atoms with the same displacement.
+pub fn structure_bonds(tab: &TabState, config: &Config) -> BondList {
+    let (atoms, _, _) = scene::calculate_scene(tab, config, 100.0, 100.0, true, None, None);
+    let tolerance = effective_tolerance(tab);
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for (i, j, dist) in find_bonds(tab, &atoms, tolerance, |_| true) {
+        let (a, b) = (&atoms[i], &atoms[j]);
+        let mut d = [
+            b.cart_pos[0] - a.cart_pos[0],
+            b.cart_pos[1] - a.cart_pos[1],
+            b.cart_pos[2] - a.cart_pos[2],
+        ];
+        let (mut lo, mut hi) = (a.original_index, b.original_index);
+        // Orient each bond from the lower index; for a self-image bond pick
+        // the lexicographically positive direction.
+        if lo > hi || (lo == hi && d < [0.0; 3]) {
+            std::mem::swap(&mut lo, &mut hi);
+            d = [-d[0], -d[1], -d[2]];
+        }
+        let key = (lo, hi, d.map(|x| (x * 1000.0).round() as i64));
+        if seen.insert(key) {
+            out.push((lo, hi, dist));
+        }
+    }
+    out.sort_by(|x, y| (x.0, x.1).cmp(&(y.0, y.1)).then(x.2.total_cmp(&y.2)));
+    out
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let dx = b[0] - a[0];
+    let dy = b[1] - a[1];
+    let dz = b[2] - a[2];
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Atom, Structure};
+
+    #[test]
+    fn periodic_bonds_are_listed_once() {
+        // Simple-cubic 2.0 Å cell of a single carbon: each atom has six
+        // neighbors at 2.0 Å, i.e. three unique self-image bonds per atom.
+        let structure = Structure {
+            lattice: [[2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 2.0]],
+            atoms: vec![Atom {
+                element: "C".to_string(),
+                position: [0.0, 0.0, 0.0],
+                original_index: 0,
+                oxidation: None,
+                occupancy: 1.0,
+                adp: None,
+            }],
+            formula: "C".to_string(),
+            is_periodic: true,
+        };
+        let mut tab = TabState::new(&Config::default());
+        tab.structure = Some(structure);
+        tab.bond_pair_cutoffs
+            .insert(crate::state::bond_pair_key("C", "C"), 2.1);
+
+        let bonds = structure_bonds(&tab, &Config::default());
+        assert_eq!(bonds.len(), 3);
+        assert!(bonds
+            .iter()
+            .all(|&(i, j, d)| i == 0 && j == 0 && (d - 2.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn cached_bonds_follow_tolerance_edits_and_cutoffs() {
+        let carbon = |x: f64, uid: usize| RenderAtom {
+            screen_pos: [0.0; 3],
+            cart_pos: [x, 0.0, 0.0],
+            element: "C".to_string(),
+            original_index: uid,
+            unique_id: uid,
+            is_ghost: uid == 2,
+            is_coord_only: false,
+            is_replica: false,
+            is_hidden: false,
+            screen_radius: 0.0,
+        };
+        let mut tab = TabState::new(&Config::default());
+        // C–C limit is 2 × 0.77 × tolerance: 1.77 Å at 1.15, 1.39 Å at 0.9.
+        let mut atoms = vec![carbon(0.0, 0), carbon(1.5, 1), carbon(3.0, 2)];
+
+        assert_eq!(find_bonds(&tab, &atoms, 1.15, |_| true).len(), 2);
+        assert_eq!(find_bonds(&tab, &atoms, 1.15, |a| !a.is_ghost).len(), 1);
+        assert!(find_bonds(&tab, &atoms, 0.9, |_| true).is_empty());
+        assert_eq!(find_bonds(&tab, &atoms, 1.150_000_1, |_| true).len(), 2);
+
+        // Moving an atom (a structure edit) rebuilds the neighbor list.
+        atoms[2].cart_pos[0] = 5.0;
+        assert_eq!(find_bonds(&tab, &atoms, 1.15, |_| true).len(), 1);
+
+        // So does a pair cutoff, even with the tolerance unchanged.
+        tab.bond_pair_cutoffs
+            .insert(crate::state::bond_pair_key("C", "C"), 1.2);
+        assert!(find_bonds(&tab, &atoms, 1.15, |_| true).is_empty());
+    }
+
+    #[test]
+    fn pair_rules_override_the_distance_test() {
+        let atom = |element: &str, x: f64, uid: usize| RenderAtom {
+            screen_pos: [0.0; 3],
+            cart_pos: [x, 0.0, 0.0],
+            element: element.to_string(),
+            original_index: uid,
+            unique_id: uid,
+            is_ghost: false,
+            is_coord_only: false,
+            is_replica: false,
+            is_hidden: false,
+            screen_radius: 0.0,
+        };
+        // Both Fe–O contacts are inside the 2.28 Å limit; Fe–Fe at 3.0 Å is
+        // past its 2.88 Å one but within the "always" stretch.
+        let atoms = vec![atom("Fe", 0.0, 0), atom("O", 2.0, 1), atom("Fe", 3.0, 2)];
+        let mut tab = TabState::new(&Config::default());
+        let pairs = |tab: &TabState| {
+            let mut p: Vec<_> = find_bonds(tab, &atoms, 1.15, |_| true)
+                .into_iter()
+                .map(|(i, j, _)| (i, j))
+                .collect();
+            p.sort();
+            p
+        };
+        assert_eq!(pairs(&tab), vec![(0, 1), (1, 2)]);
+
+        let key = crate::state::bond_pair_key("Fe", "Fe");
+        tab.bond_pair_rules.insert(key.clone(), BondRule::Always);
+        assert_eq!(pairs(&tab), vec![(0, 1), (0, 2), (1, 2)]);
+
+        tab.bond_pair_rules.remove(&key);
+        tab.bond_pair_rules
+            .insert(crate::state::bond_pair_key("O", "Fe"), BondRule::Never);
+        assert!(pairs(&tab).is_empty());
+    }
+}
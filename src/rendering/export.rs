@@ -2,12 +2,14 @@
 // STATE-OF-THE-ART EXPORT SYSTEM
 // Publication-quality PNG, PDF, SVG exports with advanced features
 
-use super::{painter, scene};
+use super::{painter, scene, threejs};
+use crate::config::Config;
 use crate::state::{AppState, SelectedAtom, TabState};
 use gtk4::cairo;
 use gtk4::prelude::*;
 use gtk4::{DrawingArea, GestureClick, PropagationPhase};
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 // ============================================================================
@@ -19,6 +21,10 @@ pub enum ExportFormat {
     PNG,
     PDF,
     SVG,
+    /// Multi-section report PDF (`write_report_pdf`).
+    Report,
+    /// Interactive three.js page (`threejs::write_html`).
+    Html,
 }
 
 #[derive(Debug, Clone)]
@@ -27,7 +33,7 @@ pub struct ExportSettings {
     pub width: Option<f64>,  // None = auto from structure
     pub height: Option<f64>, // None = auto from structure
     pub dpi: u32,            // 72, 150, 300, 600
-    pub scale: f64,          // Zoom factor (default: 100.0)
+    pub scale: f64,          // Zoom in % of the view fit (default: 100.0)
 
     // Background
     pub transparent: bool,                         // PNG only
@@ -38,6 +44,10 @@ pub struct ExportSettings {
     pub include_axes: bool,
     pub include_miller_planes: bool,
     pub include_selection_box: bool,
+    /// Pinned distance/angle labels, independent of the on-screen toggle.
+    pub include_measurements: bool,
+    /// Report PDF only: append the bond-valence/coordination table.
+    pub include_bvs_summary: bool,
 
     // Quality (PNG/PDF)
     pub antialiasing: AntialiasMode,
@@ -72,6 +82,8 @@ impl Default for ExportSettings {
             include_axes: true,
             include_miller_planes: true,
             include_selection_box: false,
+            include_measurements: true,
+            include_bvs_summary: true,
             antialiasing: AntialiasMode::Best,
             line_quality: LineQuality::Best,
         }
@@ -144,30 +156,15 @@ pub fn setup_drawing(drawing_area: &DrawingArea, state: Rc<RefCell<AppState>>) {
         );
 
         let show_ghosts = st.active_tab().view.show_full_unit_cell;
-        let mut closest: Option<SelectedAtom> = None;
-        let mut min_dist = 40.0;
-
-        for atom in render_atoms.iter() {
-            if atom.is_coord_only {
-                continue;
-            }
-            if atom.is_ghost && !show_ghosts {
-                continue;
-            }
-            let dx = atom.screen_pos[0] - x;
-            let dy = atom.screen_pos[1] - y;
-            let dist = (dx * dx + dy * dy).sqrt();
-
-            if dist < min_dist {
-                min_dist = dist;
-                closest = Some(SelectedAtom {
-                    unique_id: atom.unique_id,
-                    original_index: atom.original_index,
-                    cart_pos: atom.cart_pos,
-                    element: atom.element.clone(),
-                });
+        let tolerance = st.config.pick_tolerance_px;
+        let closest = scene::pick_atom(&render_atoms, x, y, tolerance, show_ghosts).map(|atom| {
+            SelectedAtom {
+                unique_id: atom.unique_id,
+                original_index: atom.original_index,
+                cart_pos: atom.cart_pos,
+                element: atom.element.clone(),
             }
-        }
+        });
 
         if let Some(sel) = closest {
             st.toggle_selection(sel);
@@ -181,19 +178,33 @@ pub fn setup_drawing(drawing_area: &DrawingArea, state: Rc<RefCell<AppState>>) {
         let st = state.borrow();
         let tab = st.active_tab();
 
-        let (r, g, b) = tab.style.background_color;
-        cr.set_source_rgb(r, g, b);
-        cr.paint().expect("Failed to paint background");
-
         let w = width_px as f64;
         let h = height_px as f64;
 
+        painter::draw_background(cr, &tab.style, w, h);
+
         let (render_atoms, lattice_corners, bounds) =
             scene::calculate_scene(tab, &st.config, w, h, false, None, None);
 
-        painter::draw_unit_cell(cr, &lattice_corners, false);
-        painter::draw_structure(cr, &render_atoms, tab, bounds.scale, false, st.config.color_scheme);
-        painter::draw_axes(cr, tab, w, h);
+        painter::draw_unit_cell(cr, &lattice_corners, &tab.style);
+        painter::draw_structure(
+            cr,
+            &render_atoms,
+            tab,
+            bounds.scale,
+            false,
+            st.config.color_scheme,
+            st.config.render_quality,
+            painter::DepthCue::from_config(
+                &st.config,
+                bounds.depth,
+                &painter::Backdrop::of_style(&tab.style),
+            ),
+        );
+        if tab.style.show_labels {
+            painter::draw_atom_labels(cr, &render_atoms, tab);
+        }
+        painter::draw_axes(cr, tab, w, h, tab.view.show_axes);
     });
 }
 
@@ -202,42 +213,103 @@ pub fn setup_drawing(drawing_area: &DrawingArea, state: Rc<RefCell<AppState>>) {
 // ============================================================================
 
 /// Export to PNG with full control
+///
+/// The scene is laid out in points (1/72 in) exactly like the PDF/SVG
+/// exports — `settings.scale` is points per Å — and rasterized at
+/// `settings.dpi`, so the pixel size is the point size × DPI/72 and the
+/// file carries a matching pHYs chunk.
 pub fn export_png_advanced(
     state: Rc<RefCell<AppState>>,
     path: &str,
     settings: ExportSettings,
 ) -> Result<String, String> {
     let st = state.borrow();
-    let tab = st.active_tab();
+    write_png(st.active_tab(), &st.config, path, &settings)
+}
+
+fn write_png(
+    tab: &TabState,
+    config: &Config,
+    path: &str,
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    let surface = render_png_surface(tab, config, settings)?;
+    let (px_w, px_h) = (surface.width(), surface.height());
+
+    let mut png = Vec::new();
+    surface
+        .write_to_png(&mut png)
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    let png = insert_phys_chunk(&png, settings.dpi);
 
+    std::fs::write(path, png).map_err(|e| format!("Failed to create file: {}", e))?;
+
+    Ok(format!(
+        "Exported PNG to: {} ({}×{} px @ {} DPI)",
+        path, px_w, px_h, settings.dpi
+    ))
+}
+
+/// Canvas side (points) that image exports fit the view into when the
+/// settings leave the size open.
+const EXPORT_CANVAS: f64 = 1200.0;
+
+/// Scene for an image export, framed tightly around the structure. The
+/// scale is the view's own fit (`scene::view_fit_scale`, tab zoom
+/// included) for the export canvas, times the dialog's zoom percentage, so
+/// a figure doesn't depend on the size of the window it was exported from.
+fn export_scene(
+    tab: &TabState,
+    config: &Config,
+    settings: &ExportSettings,
+) -> (Vec<scene::RenderAtom>, Vec<[f64; 2]>, scene::SceneBounds) {
+    let canvas_w = settings.width.unwrap_or(EXPORT_CANVAS);
+    let canvas_h = settings.height.unwrap_or(canvas_w);
+    let (_, _, fit) = scene::calculate_scene(tab, config, canvas_w, canvas_h, true, None, None);
+    let scale = fit.scale * settings.scale / 100.0;
+    scene::calculate_scene(tab, config, 0.0, 0.0, true, Some(scale), None)
+}
+
+/// Rasterize `tab` for PNG export.
+fn render_png_surface(
+    tab: &TabState,
+    config: &Config,
+    settings: &ExportSettings,
+) -> Result<cairo::ImageSurface, String> {
     // Calculate scene dimensions
-    let (render_atoms, lattice_corners, bounds) =
-        scene::calculate_scene(tab, &st.config, 0.0, 0.0, true, Some(settings.scale), None);
+    let (render_atoms, lattice_corners, bounds) = export_scene(tab, config, settings);
 
     let img_width = settings.width.unwrap_or(bounds.width);
     let img_height = settings.height.unwrap_or(bounds.height);
+    let (px_w, px_h) = png_pixel_size(img_width, img_height, settings.dpi);
 
     // Create high-quality surface
-    let surface =
-        cairo::ImageSurface::create(cairo::Format::ARgb32, img_width as i32, img_height as i32)
-            .map_err(|e| format!("Failed to create surface: {}", e))?;
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, px_w, px_h)
+        .map_err(|e| format!("Failed to create surface: {}", e))?;
 
     let cr =
         cairo::Context::new(&surface).map_err(|e| format!("Failed to create context: {}", e))?;
 
+    // Draw in points; line widths and HUD sizes scale with the DPI.
+    cr.scale(
+        px_w as f64 / img_width.max(1e-9),
+        px_h as f64 / img_height.max(1e-9),
+    );
+
     // Apply quality settings
-    apply_quality_settings(&cr, &settings);
+    apply_quality_settings(&cr, settings);
 
-    // Background
-    if settings.transparent {
-        cr.set_source_rgba(0.0, 0.0, 0.0, 0.0);
-    } else {
-        let (r, g, b) = settings
-            .background_color
-            .unwrap_or(tab.style.background_color);
-        cr.set_source_rgb(r, g, b);
+    // Background. A fresh ARGB32 surface is fully transparent, so a
+    // transparent export simply never paints one.
+    if !settings.transparent {
+        if let Some((r, g, b)) = settings.background_color {
+            cr.set_source_rgb(r, g, b);
+            cr.paint().expect("Failed to paint background");
+        } else {
+            painter::draw_background(&cr, &tab.style, img_width, img_height);
+        }
     }
-    cr.paint().expect("Failed to paint background");
+    let backdrop = export_backdrop(tab, settings, false);
 
     // Draw content
     draw_export_content(
@@ -248,22 +320,14 @@ pub fn export_png_advanced(
         settings.scale,
         img_width,
         img_height,
-        &settings,
-        st.config.color_scheme,
+        settings,
+        config,
+        &backdrop,
+        painter::DepthCue::from_config(config, bounds.depth, &backdrop),
     );
 
-    // Write to file
-    let mut file =
-        std::fs::File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
-
-    surface
-        .write_to_png(&mut file)
-        .map_err(|e| format!("Failed to write PNG: {}", e))?;
-
-    Ok(format!(
-        "Exported PNG to: {} ({}×{} @ {} DPI)",
-        path, img_width as i32, img_height as i32, settings.dpi
-    ))
+    drop(cr);
+    Ok(surface)
 }
 
 /// Export to PDF with vector quality
@@ -273,10 +337,16 @@ pub fn export_pdf_advanced(
     settings: ExportSettings,
 ) -> Result<String, String> {
     let st = state.borrow();
-    let tab = st.active_tab();
+    write_pdf(st.active_tab(), &st.config, path, &settings)
+}
 
-    let (render_atoms, lattice_corners, bounds) =
-        scene::calculate_scene(tab, &st.config, 0.0, 0.0, true, Some(settings.scale), None);
+fn write_pdf(
+    tab: &TabState,
+    config: &Config,
+    path: &str,
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    let (render_atoms, lattice_corners, bounds) = export_scene(tab, config, settings);
 
     let img_width = settings.width.unwrap_or(bounds.width);
     let img_height = settings.height.unwrap_or(bounds.height);
@@ -289,14 +359,13 @@ pub fn export_pdf_advanced(
         cairo::Context::new(&surface).map_err(|e| format!("Failed to create context: {}", e))?;
 
     // Apply quality settings
-    apply_quality_settings(&cr, &settings);
+    apply_quality_settings(&cr, settings);
 
-    // Background (usually white for PDF)
+    // Background (usually white for PDF; a tab gradient is kept if set)
     if !settings.transparent {
-        let (r, g, b) = settings.background_color.unwrap_or((1.0, 1.0, 1.0)); // White default
-        cr.set_source_rgb(r, g, b);
-        cr.paint().expect("Failed to paint background");
+        paint_vector_background(&cr, tab, settings, img_width, img_height);
     }
+    let backdrop = export_backdrop(tab, settings, true);
 
     // Draw content
     draw_export_content(
@@ -307,8 +376,10 @@ pub fn export_pdf_advanced(
         settings.scale,
         img_width,
         img_height,
-        &settings,
-        st.config.color_scheme,
+        settings,
+        config,
+        &backdrop,
+        painter::DepthCue::from_config(config, bounds.depth, &backdrop),
     );
 
     // Finalize PDF
@@ -321,16 +392,28 @@ pub fn export_pdf_advanced(
 }
 
 /// Export to SVG (pure vector, best for publications)
+///
+/// Everything is drawn as native SVG: atoms as radial-gradient-filled
+/// circles (`draw_structure` never uses the sprite cache when exporting),
+/// bonds as gradient-filled paths, and the unit cell and axes as strokes and
+/// fills. No Cairo operation used here needs a raster fallback, so Inkscape
+/// and Illustrator open the file with every shape selectable.
 pub fn export_svg_advanced(
     state: Rc<RefCell<AppState>>,
     path: &str,
     settings: ExportSettings,
 ) -> Result<String, String> {
     let st = state.borrow();
-    let tab = st.active_tab();
+    write_svg(st.active_tab(), &st.config, path, &settings)
+}
 
-    let (render_atoms, lattice_corners, bounds) =
-        scene::calculate_scene(tab, &st.config, 0.0, 0.0, true, Some(settings.scale), None);
+fn write_svg(
+    tab: &TabState,
+    config: &Config,
+    path: &str,
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    let (render_atoms, lattice_corners, bounds) = export_scene(tab, config, settings);
 
     let img_width = settings.width.unwrap_or(bounds.width);
     let img_height = settings.height.unwrap_or(bounds.height);
@@ -338,19 +421,20 @@ pub fn export_svg_advanced(
     // Create SVG surface (editable vector output)
     let surface = cairo::SvgSurface::new(img_width, img_height, Some(path))
         .map_err(|e| format!("Failed to create SVG surface: {}", e))?;
+    // SVG 1.1 is what vector editors and journal pipelines import best.
+    surface.restrict(cairo::SvgVersion::_1_1);
 
     let cr =
         cairo::Context::new(&surface).map_err(|e| format!("Failed to create context: {}", e))?;
 
     // Apply quality settings
-    apply_quality_settings(&cr, &settings);
+    apply_quality_settings(&cr, settings);
 
     // Background
     if !settings.transparent {
-        let (r, g, b) = settings.background_color.unwrap_or((1.0, 1.0, 1.0));
-        cr.set_source_rgb(r, g, b);
-        cr.paint().expect("Failed to paint background");
+        paint_vector_background(&cr, tab, settings, img_width, img_height);
     }
+    let backdrop = export_backdrop(tab, settings, true);
 
     // Draw content
     draw_export_content(
@@ -361,8 +445,10 @@ pub fn export_svg_advanced(
         settings.scale,
         img_width,
         img_height,
-        &settings,
-        st.config.color_scheme,
+        settings,
+        config,
+        &backdrop,
+        painter::DepthCue::from_config(config, bounds.depth, &backdrop),
     );
 
     // Finalize SVG
@@ -374,15 +460,222 @@ pub fn export_svg_advanced(
     ))
 }
 
+/// Export an interactive 3D page (three.js) for sharing in a browser.
+///
+/// Only the content flags apply; size, DPI and antialiasing are up to
+/// the viewer's browser.
+pub fn export_html_advanced(
+    state: Rc<RefCell<AppState>>,
+    path: &str,
+    settings: ExportSettings,
+) -> Result<String, String> {
+    let st = state.borrow();
+    threejs::write_html(st.active_tab(), &st.config, path, &settings)
+}
+
+// ============================================================================
+// REPORT PDF
+// ============================================================================
+
+/// A4 portrait page size in points.
+const REPORT_PAGE: (f64, f64) = (595.0, 842.0);
+const REPORT_MARGIN: f64 = 50.0;
+/// Height of the structure figure on the first page.
+const REPORT_FIGURE_HEIGHT: f64 = 320.0;
+const REPORT_TEXT_SIZE: f64 = 8.5;
+const REPORT_LINE_HEIGHT: f64 = 11.0;
+
+/// One-page (more if the tables run long) crystallographic report of the
+/// active tab: a render of the structure, then the cell, composition and
+/// optionally bond-valence sections from `utils::report`. The figure is
+/// fitted to the page width, so `settings.scale` and the explicit size are
+/// ignored; the content flags apply as for a plain PDF.
+pub fn export_report_pdf(
+    state: Rc<RefCell<AppState>>,
+    path: &str,
+    settings: ExportSettings,
+) -> Result<String, String> {
+    let st = state.borrow();
+    write_report_pdf(st.active_tab(), &st.config, path, &settings)
+}
+
+fn write_report_pdf(
+    tab: &TabState,
+    config: &Config,
+    path: &str,
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    use crate::utils::report;
+
+    let structure = tab.structure.as_ref().ok_or("no structure loaded")?;
+    let (page_w, page_h) = REPORT_PAGE;
+
+    let surface = cairo::PdfSurface::new(page_w, page_h, path)
+        .map_err(|e| format!("Failed to create PDF surface: {}", e))?;
+    let cr =
+        cairo::Context::new(&surface).map_err(|e| format!("Failed to create context: {}", e))?;
+    apply_quality_settings(&cr, settings);
+
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.paint().map_err(|e| e.to_string())?;
+
+    let mut page = ReportPage::new(&cr);
+    page.heading(&structure.formula, 18.0);
+    page.text_line(&tab.file_name, 10.0, false);
+    page.skip(8.0);
+
+    // Figure: fit the scene into the box under the title, centered.
+    let box_w = page_w - 2.0 * REPORT_MARGIN;
+    let (_, _, unit) = scene::calculate_scene(tab, config, 0.0, 0.0, true, Some(1.0), None);
+    let fit = scene::fit_scale(box_w, REPORT_FIGURE_HEIGHT, unit.width, unit.height);
+    let (render_atoms, lattice_corners, bounds) =
+        scene::calculate_scene(tab, config, 0.0, 0.0, true, Some(fit), None);
+
+    cr.save().map_err(|e| e.to_string())?;
+    cr.translate(
+        REPORT_MARGIN + (box_w - bounds.width) / 2.0,
+        page.y + (REPORT_FIGURE_HEIGHT - bounds.height) / 2.0,
+    );
+    cr.rectangle(0.0, 0.0, bounds.width, bounds.height);
+    cr.clip();
+    if !settings.transparent {
+        paint_vector_background(&cr, tab, settings, bounds.width, bounds.height);
+    }
+    let backdrop = export_backdrop(tab, settings, true);
+    draw_export_content(
+        &cr,
+        &render_atoms,
+        &lattice_corners,
+        tab,
+        fit,
+        bounds.width,
+        bounds.height,
+        settings,
+        config,
+        &backdrop,
+        painter::DepthCue::from_config(config, bounds.depth, &backdrop),
+    );
+    cr.restore().map_err(|e| e.to_string())?;
+    page.skip(REPORT_FIGURE_HEIGHT + 16.0);
+
+    let fmt = &config.report_format;
+    page.section("Unit Cell", &report::cell_summary(structure, fmt));
+    page.section(
+        "Composition",
+        &report::structure_summary(structure, &tab.file_name, fmt),
+    );
+    if settings.include_bvs_summary && structure.is_periodic {
+        page.section(
+            "Bond Valence & Coordination",
+            &report::bvs_analysis(structure, fmt),
+        );
+    }
+    page.footer();
+
+    let pages = page.number;
+    surface.finish();
+
+    Ok(format!(
+        "Exported report to: {} ({} page{})",
+        path,
+        pages,
+        if pages == 1 { "" } else { "s" }
+    ))
+}
+
+/// Top-to-bottom text cursor over the report pages; starts a new page
+/// (with a footer on the old one) when the next line would cross the
+/// bottom margin.
+struct ReportPage<'a> {
+    cr: &'a cairo::Context,
+    y: f64,
+    number: u32,
+}
+
+impl<'a> ReportPage<'a> {
+    fn new(cr: &'a cairo::Context) -> Self {
+        Self {
+            cr,
+            y: REPORT_MARGIN,
+            number: 1,
+        }
+    }
+
+    fn skip(&mut self, dy: f64) {
+        self.y += dy;
+    }
+
+    fn ensure_room(&mut self, dy: f64) {
+        if self.y + dy <= REPORT_PAGE.1 - REPORT_MARGIN {
+            return;
+        }
+        self.footer();
+        let _ = self.cr.show_page();
+        self.number += 1;
+        self.y = REPORT_MARGIN;
+    }
+
+    fn heading(&mut self, text: &str, size: f64) {
+        self.ensure_room(size * 1.6);
+        self.y += size;
+        self.cr
+            .select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+        self.show(text, size);
+        self.y += size * 0.6;
+    }
+
+    fn text_line(&mut self, text: &str, size: f64, mono: bool) {
+        let line_h = REPORT_LINE_HEIGHT * size / REPORT_TEXT_SIZE;
+        self.ensure_room(line_h);
+        self.y += line_h;
+        let family = if mono { "Monospace" } else { "Sans" };
+        self.cr
+            .select_font_face(family, cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+        self.show(text, size);
+    }
+
+    /// Bold title over a block of preformatted report text.
+    fn section(&mut self, title: &str, body: &str) {
+        // Keep the title together with at least the first few lines.
+        self.ensure_room(13.0 * 1.6 + 3.0 * REPORT_LINE_HEIGHT);
+        self.heading(title, 13.0);
+        for line in body.lines() {
+            self.text_line(line, REPORT_TEXT_SIZE, true);
+        }
+        self.y += 14.0;
+    }
+
+    fn show(&self, text: &str, size: f64) {
+        self.cr.set_source_rgb(0.1, 0.1, 0.1);
+        self.cr.set_font_size(size);
+        self.cr.move_to(REPORT_MARGIN, self.y);
+        let _ = self.cr.show_text(text);
+    }
+
+    fn footer(&self) {
+        self.cr.set_source_rgb(0.45, 0.45, 0.45);
+        self.cr
+            .select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+        self.cr.set_font_size(8.0);
+        self.cr
+            .move_to(REPORT_MARGIN, REPORT_PAGE.1 - REPORT_MARGIN / 2.0);
+        let _ = self
+            .cr
+            .show_text(&format!("CView structure report — page {}", self.number));
+    }
+}
+
 // ============================================================================
 // BACKWARD COMPATIBILITY - Simple exports
 // ============================================================================
 
-/// Simple PNG export (backward compatible)
+/// Simple PNG export (backward compatible). `width`/`height` are pixels,
+/// hence 72 DPI (one pixel per point).
 pub fn export_png(state: Rc<RefCell<AppState>>, width: f64, height: f64, path: &str) -> String {
     let settings = ExportSettings {
         width: if width > 0.0 { Some(width) } else { None },
         height: if height > 0.0 { Some(height) } else { None },
+        dpi: 72,
         ..Default::default()
     };
 
@@ -399,6 +692,85 @@ pub fn export_pdf(state: Rc<RefCell<AppState>>, path: &str) -> String {
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// The backdrop an export ends up on, for HUD ink and depth fog: what
+/// the PNG or vector (`vector`) background painting chooses. Transparent
+/// exports are assumed to be placed on a white page.
+fn export_backdrop(tab: &TabState, settings: &ExportSettings, vector: bool) -> painter::Backdrop {
+    const WHITE: (f64, f64, f64) = (1.0, 1.0, 1.0);
+    match (settings.background_color, tab.style.background_gradient) {
+        _ if settings.transparent => painter::Backdrop::flat(WHITE),
+        (Some(color), _) => painter::Backdrop::flat(color),
+        (None, None) if vector => painter::Backdrop::flat(WHITE),
+        (None, _) => painter::Backdrop::of_style(&tab.style),
+    }
+}
+
+/// PDF/SVG background: an explicit settings color wins, then the tab's
+/// gradient if one is enabled, else white (page-friendly default).
+fn paint_vector_background(
+    cr: &cairo::Context,
+    tab: &TabState,
+    settings: &ExportSettings,
+    width: f64,
+    height: f64,
+) {
+    match (settings.background_color, tab.style.background_gradient) {
+        (None, Some(_)) => painter::draw_background(cr, &tab.style, width, height),
+        (color, _) => {
+            let (r, g, b) = color.unwrap_or((1.0, 1.0, 1.0));
+            cr.set_source_rgb(r, g, b);
+            cr.paint().expect("Failed to paint background");
+        }
+    }
+}
+
+/// Pixel size of a `width_pt` × `height_pt` export rasterized at `dpi`.
+fn png_pixel_size(width_pt: f64, height_pt: f64, dpi: u32) -> (i32, i32) {
+    let k = dpi.max(1) as f64 / 72.0;
+    (
+        (width_pt * k).round().max(1.0) as i32,
+        (height_pt * k).round().max(1.0) as i32,
+    )
+}
+
+/// Insert a pHYs chunk (pixels per metre) right after IHDR so viewers and
+/// layout tools report the intended print size; Cairo doesn't write one.
+/// Input that doesn't start with a PNG header is returned unchanged.
+fn insert_phys_chunk(png: &[u8], dpi: u32) -> Vec<u8> {
+    // Signature (8) + IHDR chunk (length 4, type 4, data 13, CRC 4).
+    const IHDR_END: usize = 8 + 4 + 4 + 13 + 4;
+    if png.len() < IHDR_END || &png[12..16] != b"IHDR" {
+        return png.to_vec();
+    }
+
+    let ppm = (dpi as f64 / 0.0254).round() as u32;
+    let mut body = b"pHYs".to_vec();
+    body.extend_from_slice(&ppm.to_be_bytes());
+    body.extend_from_slice(&ppm.to_be_bytes());
+    body.push(1); // unit: metre
+
+    let mut out = Vec::with_capacity(png.len() + 21);
+    out.extend_from_slice(&png[..IHDR_END]);
+    out.extend_from_slice(&9u32.to_be_bytes());
+    out.extend_from_slice(&body);
+    out.extend_from_slice(&crc32(&body).to_be_bytes());
+    out.extend_from_slice(&png[IHDR_END..]);
+    out
+}
+
+/// CRC-32 (IEEE) as used by PNG chunk checksums.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
 /// Apply quality settings to Cairo context
 fn apply_quality_settings(cr: &cairo::Context, settings: &ExportSettings) {
     // Antialiasing
@@ -429,6 +801,7 @@ fn apply_quality_settings(cr: &cairo::Context, settings: &ExportSettings) {
 }
 
 /// Draw all export content
+#[allow(clippy::too_many_arguments)]
 fn draw_export_content(
     cr: &cairo::Context,
     render_atoms: &[scene::RenderAtom],
@@ -438,25 +811,64 @@ fn draw_export_content(
     width: f64,
     height: f64,
     settings: &ExportSettings,
-    color_scheme: crate::model::elements::ColorScheme,
+    config: &Config,
+    backdrop: &painter::Backdrop,
+    depth_cue: Option<painter::DepthCue>,
 ) {
     // Unit cell
     if settings.include_unit_cell {
-        painter::draw_unit_cell(cr, lattice_corners, true);
+        painter::draw_unit_cell(cr, lattice_corners, &tab.style);
     }
 
     // Structure (atoms + bonds)
-    painter::draw_structure(cr, render_atoms, tab, scale, true, color_scheme);
+    painter::draw_structure(
+        cr,
+        render_atoms,
+        tab,
+        scale,
+        true,
+        config.color_scheme,
+        crate::config::RenderQuality::High,
+        depth_cue,
+    );
+    if tab.style.show_labels {
+        painter::draw_atom_labels(cr, render_atoms, tab);
+    }
 
     // Miller planes
     if settings.include_miller_planes && !tab.miller_planes.is_empty() {
         painter::draw_miller_planes(cr, tab, lattice_corners, scale, width, height);
     }
 
-    // Axes (optional - can look odd in exports)
+    if tab.view.show_bvs_values {
+        painter::draw_bvs_values(cr, render_atoms, tab);
+    }
+    if settings.include_measurements {
+        painter::draw_measurements(cr, render_atoms, tab, config.label_measurements);
+    }
+
+    // Axes (optional - can look odd in exports). The dialog's checkbox is
+    // the whole story: all three arrows, whatever the live view hides.
     if settings.include_axes {
-        painter::draw_axes(cr, tab, width, height);
+        painter::draw_axes(cr, tab, width, height, [true; 3]);
+    }
+
+    // Scale bar follows the view toggle; `scale` is already points per Å.
+    if tab.view.show_scale_bar {
+        painter::draw_scale_bar(
+            cr,
+            backdrop,
+            scale,
+            tab.view.scale_bar_length,
+            width,
+            height,
+        );
+    }
+
+    if tab.view.show_legend {
+        painter::draw_element_legend(cr, tab, config.color_scheme, backdrop, width, height);
     }
+    painter::draw_bvs_legend(cr, tab, backdrop, width, height);
 
     // Selection box (if active)
     if settings.include_selection_box {
@@ -464,6 +876,160 @@ fn draw_export_content(
     }
 }
 
+// ============================================================================
+// BATCH EXPORT
+// ============================================================================
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::PNG => "png",
+            ExportFormat::PDF => "pdf",
+            ExportFormat::SVG => "svg",
+            ExportFormat::Report => "pdf",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Target file for every open tab of a batch export into `dir`, in tab
+/// order, named after its source file (`NaCl.cif` → `NaCl.png`). Returns
+/// each file name with its full path.
+pub fn batch_targets(state: &AppState, dir: &Path, format: ExportFormat) -> Vec<(String, PathBuf)> {
+    let names: Vec<&str> = state.tabs.iter().map(|t| t.file_name.as_str()).collect();
+    batch_file_names(&names, format.extension())
+        .into_iter()
+        .map(|file| {
+            let path = dir.join(&file);
+            (file, path)
+        })
+        .collect()
+}
+
+/// Export one tab to `path`. Tabs render offscreen, so it needn't be the
+/// visible one.
+pub fn export_tab(
+    tab: &TabState,
+    config: &Config,
+    path: &Path,
+    format: ExportFormat,
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    if tab.structure.is_none() {
+        return Err("no structure loaded".to_string());
+    }
+    let path = path.to_string_lossy();
+    match format {
+        ExportFormat::PNG => write_png(tab, config, &path, settings),
+        ExportFormat::PDF => write_pdf(tab, config, &path, settings),
+        ExportFormat::SVG => write_svg(tab, config, &path, settings),
+        ExportFormat::Report => write_report_pdf(tab, config, &path, settings),
+        ExportFormat::Html => threejs::write_html(tab, config, &path, settings),
+    }
+}
+
+/// Longest edge of a `--thumbnails` image in pixels (rendered at 72 DPI,
+/// so one point is one pixel).
+pub const THUMBNAIL_SIZE: f64 = 256.0;
+
+/// One gallery entry: the source file name and the export's status message,
+/// or why it failed.
+pub type ThumbnailResult = (String, Result<String, String>);
+
+/// Headless gallery: render every structure file in `src_dir` (see
+/// [`io::is_structure_file`](crate::io::is_structure_file)) to a PNG in
+/// `out_dir`, named like a batch export. Each file gets a fresh tab, so
+/// all thumbnails share the default orientation (looking down z) and the
+/// configured style, scaled so the longer edge is `THUMBNAIL_SIZE`.
+/// Returns each source file name with its result, sorted by name; a file
+/// that fails to load or render doesn't stop the rest.
+pub fn write_thumbnails(
+    src_dir: &Path,
+    out_dir: &Path,
+    config: &Config,
+) -> Result<Vec<ThumbnailResult>, String> {
+    let mut sources: Vec<_> = std::fs::read_dir(src_dir)
+        .map_err(|e| format!("Cannot read {}: {}", src_dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && crate::io::is_structure_file(&p.to_string_lossy()))
+        .collect();
+    sources.sort();
+    std::fs::create_dir_all(out_dir)
+        .map_err(|e| format!("Cannot create {}: {}", out_dir.display(), e))?;
+
+    let names: Vec<String> = sources
+        .iter()
+        .map(|p| {
+            p.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let name_refs: Vec<&str> = names.iter().map(String::as_str).collect();
+    let files = batch_file_names(&name_refs, "png");
+
+    Ok(sources
+        .iter()
+        .zip(names.iter().zip(files))
+        .map(|(src, (name, file))| {
+            let path = src.to_string_lossy();
+            let result = crate::io::load_structure_with(&path, config.auto_detect_format)
+                .map_err(|e| format!("load failed: {}", e))
+                .and_then(|structure| {
+                    let mut tab = TabState::new(config);
+                    tab.structure = Some(structure);
+                    tab.file_name = name.clone();
+
+                    let (_, _, unit) =
+                        scene::calculate_scene(&tab, config, 0.0, 0.0, true, Some(1.0), None);
+                    let settings = ExportSettings {
+                        dpi: 72,
+                        scale: scene::fit_scale(
+                            THUMBNAIL_SIZE,
+                            THUMBNAIL_SIZE,
+                            unit.width,
+                            unit.height,
+                        ),
+                        include_axes: false,
+                        include_miller_planes: false,
+                        ..Default::default()
+                    };
+                    write_png(
+                        &tab,
+                        config,
+                        &out_dir.join(&file).to_string_lossy(),
+                        &settings,
+                    )
+                });
+            (name.clone(), result)
+        })
+        .collect())
+}
+
+/// Output file names for a batch: the stem of each tab's file name plus
+/// `ext`, with `_2`, `_3`, … appended when two tabs share a stem.
+fn batch_file_names(tab_names: &[&str], ext: &str) -> Vec<String> {
+    let mut used = std::collections::HashSet::new();
+    tab_names
+        .iter()
+        .map(|name| {
+            let stem = Path::new(name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "structure".to_string());
+            let mut candidate = format!("{}.{}", stem, ext);
+            let mut n = 2;
+            while !used.insert(candidate.clone()) {
+                candidate = format!("{}_{}.{}", stem, n, ext);
+                n += 1;
+            }
+            candidate
+        })
+        .collect()
+}
+
 // ============================================================================
 // PRESET EXPORTS
 // ============================================================================
@@ -480,6 +1046,8 @@ pub fn export_for_journal(
         ExportFormat::PNG => export_png_advanced(state, path, settings),
         ExportFormat::PDF => export_pdf_advanced(state, path, settings),
         ExportFormat::SVG => export_svg_advanced(state, path, settings),
+        ExportFormat::Report => export_report_pdf(state, path, settings),
+        ExportFormat::Html => export_html_advanced(state, path, settings),
     }
 }
 
@@ -494,3 +1062,324 @@ pub fn export_for_web(state: Rc<RefCell<AppState>>, path: &str) -> Result<String
     let settings = ExportSettings::web_preset();
     export_png_advanced(state, path, settings)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Atom, Structure};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    struct TmpFile(std::path::PathBuf);
+    impl TmpFile {
+        fn new(ext: &str) -> Self {
+            let mut p = std::env::temp_dir();
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            p.push(format!("cview_export_{}_{}{}", std::process::id(), n, ext));
+            TmpFile(p)
+        }
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+    impl Drop for TmpFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn atom(element: &str, position: [f64; 3]) -> Atom {
+        Atom {
+            element: element.to_string(),
+            position,
+            original_index: 0,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        }
+    }
+
+    /// Rock-salt pair in a 4 Å cube, loaded into a single tab.
+    fn test_state() -> Rc<RefCell<AppState>> {
+        let config = Config::default();
+        let mut tab = TabState::new(&config);
+        tab.structure = Some(Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: vec![atom("Na", [0.0, 0.0, 0.0]), atom("Cl", [2.0, 2.0, 2.0])],
+            formula: "NaCl".to_string(),
+            is_periodic: true,
+        });
+        Rc::new(RefCell::new(AppState {
+            tabs: vec![tab],
+            active_tab_index: 0,
+            config,
+        }))
+    }
+
+    #[test]
+    fn thumbnails_skip_unsupported_and_report_failures() {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let root = std::env::temp_dir().join(format!("cview_thumbs_{}_{}", std::process::id(), n));
+        let (src, out) = (root.join("in"), root.join("out"));
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("h2.xyz"), "2\n\nH 0 0 0\nH 0.74 0 0\n").unwrap();
+        std::fs::write(src.join("broken.cif"), "data_x\n").unwrap();
+        std::fs::write(src.join("README.txt"), "not a structure\n").unwrap();
+
+        let results = write_thumbnails(&src, &out, &Config::default()).unwrap();
+        let png = std::fs::read(out.join("h2.png"));
+        std::fs::remove_dir_all(&root).ok();
+
+        let names: Vec<&str> = results.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["broken.cif", "h2.xyz"]);
+        assert!(results[0].1.is_err());
+        assert!(results[1].1.is_ok(), "{:?}", results[1].1);
+        let png = png.unwrap();
+        // IHDR width/height: the longer (horizontal) edge is THUMBNAIL_SIZE.
+        let w = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+        assert_eq!(w, THUMBNAIL_SIZE as u32);
+    }
+
+    #[test]
+    fn svg_export_is_pure_vector() {
+        let out = TmpFile::new(".svg");
+        let settings = ExportSettings {
+            scale: 20.0,
+            ..ExportSettings::default()
+        };
+        export_svg_advanced(test_state(), out.path(), settings).unwrap();
+
+        let svg = std::fs::read_to_string(out.path()).unwrap();
+        assert!(!svg.contains("<image"), "SVG contains an embedded raster");
+        assert!(svg.contains("radialGradient"), "atoms must be gradients");
+        // 12 stroked cell edges plus the atom discs.
+        assert!(svg.matches("<path").count() > 12);
+    }
+
+    #[test]
+    fn export_scale_is_the_view_fit_times_both_zooms() {
+        let state = test_state();
+        let settings = ExportSettings {
+            scale: 50.0,
+            ..ExportSettings::default()
+        };
+        let scale = |st: &AppState| export_scene(st.active_tab(), &st.config, &settings).2.scale;
+
+        let mut st = state.borrow_mut();
+        let (_, _, view) = scene::calculate_scene(
+            st.active_tab(),
+            &st.config,
+            EXPORT_CANVAS,
+            EXPORT_CANVAS,
+            false,
+            None,
+            None,
+        );
+        let base = scale(&st);
+        assert!((base - view.scale * 0.5).abs() < 1e-9);
+
+        st.active_tab_mut().view.zoom *= 2.0;
+        assert!((scale(&st) - 2.0 * base).abs() < 1e-9);
+    }
+
+    #[test]
+    fn png_size_and_phys_follow_dpi() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(png_pixel_size(100.0, 50.0, 300), (417, 208));
+
+        let state = test_state();
+        let settings = ExportSettings {
+            dpi: 144,
+            scale: 20.0,
+            ..ExportSettings::default()
+        };
+        let (_, _, bounds) = {
+            let st = state.borrow();
+            export_scene(st.active_tab(), &st.config, &settings)
+        };
+
+        let out = TmpFile::new(".png");
+        export_png_advanced(state, out.path(), settings).unwrap();
+
+        let png = std::fs::read(out.path()).unwrap();
+        let be = |at: usize| u32::from_be_bytes(png[at..at + 4].try_into().unwrap());
+        // IHDR data starts at byte 16: width, height.
+        assert_eq!(be(16) as i32, (bounds.width * 2.0).round() as i32);
+        assert_eq!(be(20) as i32, (bounds.height * 2.0).round() as i32);
+
+        // pHYs follows IHDR: 144 DPI = 5669 px/m on both axes.
+        assert_eq!(&png[37..41], b"pHYs");
+        assert_eq!((be(41), be(45), png[49]), (5669, 5669, 1));
+    }
+
+    /// Alpha of the four corner pixels of a decoded PNG.
+    fn corner_alphas(path: &str) -> [u8; 4] {
+        let mut file = std::fs::File::open(path).unwrap();
+        let mut img = cairo::ImageSurface::create_from_png(&mut file).unwrap();
+        let (w, h) = (img.width() as usize, img.height() as usize);
+        let stride = img.stride() as usize;
+        let data = img.data().unwrap();
+        // ARGB32 is a native-endian u32 per pixel with alpha in the top byte.
+        let alpha = |x: usize, y: usize| {
+            let i = y * stride + x * 4;
+            (u32::from_ne_bytes(data[i..i + 4].try_into().unwrap()) >> 24) as u8
+        };
+        let (r, b) = (w - 1, h - 1);
+        [alpha(0, 0), alpha(r, 0), alpha(0, b), alpha(r, b)]
+    }
+
+    #[test]
+    fn transparent_png_leaves_background_unpainted() {
+        for (transparent, expected) in [(true, 0), (false, 255)] {
+            let out = TmpFile::new(".png");
+            let settings = ExportSettings {
+                dpi: 72,
+                scale: 20.0,
+                transparent,
+                include_axes: false,
+                ..ExportSettings::default()
+            };
+            export_png_advanced(test_state(), out.path(), settings).unwrap();
+            assert_eq!(corner_alphas(out.path()), [expected; 4]);
+        }
+    }
+
+    #[test]
+    fn hud_backdrop_is_the_painted_background() {
+        let state = test_state();
+        let mut st = state.borrow_mut();
+        st.active_tab_mut().style.background_color = (0.0, 0.0, 0.0);
+        let tab = st.active_tab();
+        let white = painter::Backdrop::flat((1.0, 1.0, 1.0));
+        let plain = ExportSettings::default();
+
+        // A dark tab exports to a white page as vector, as itself as PNG.
+        assert_eq!(export_backdrop(tab, &plain, true), white);
+        assert_eq!(
+            export_backdrop(tab, &plain, false),
+            painter::Backdrop::of_style(&tab.style)
+        );
+        let gray = ExportSettings {
+            background_color: Some((0.5, 0.5, 0.5)),
+            ..plain.clone()
+        };
+        assert_eq!(
+            export_backdrop(tab, &gray, false),
+            painter::Backdrop::flat((0.5, 0.5, 0.5))
+        );
+        let clear = ExportSettings {
+            transparent: true,
+            ..gray
+        };
+        assert_eq!(export_backdrop(tab, &clear, false), white);
+    }
+
+    #[test]
+    fn axes_and_cell_follow_export_flags_not_view() {
+        let state = test_state();
+        // Hide everything on screen; the export flags alone must decide.
+        state.borrow_mut().active_tab_mut().view.show_axes = [false; 3];
+
+        let opaque = |include_unit_cell: bool, include_axes: bool| {
+            let settings = ExportSettings {
+                dpi: 72,
+                scale: 20.0,
+                transparent: true,
+                include_unit_cell,
+                include_axes,
+                ..ExportSettings::default()
+            };
+            let st = state.borrow();
+            let mut img = render_png_surface(st.active_tab(), &st.config, &settings).unwrap();
+            let data = img.data().unwrap();
+            data.chunks_exact(4)
+                .filter(|px| u32::from_ne_bytes((*px).try_into().unwrap()) >> 24 != 0)
+                .count()
+        };
+
+        let atoms_only = opaque(false, false);
+        let with_cell = opaque(true, false);
+        let with_axes = opaque(false, true);
+        let everything = opaque(true, true);
+        assert!(atoms_only > 0);
+        assert!(with_cell > atoms_only);
+        assert!(with_axes > atoms_only);
+        assert!(everything > with_cell && everything > with_axes);
+    }
+
+    #[test]
+    fn batch_names_are_unique_per_tab() {
+        let names = batch_file_names(&["NaCl.cif", "POSCAR", "/tmp/NaCl.vasp", ""], "png");
+        let expected = ["NaCl.png", "POSCAR.png", "NaCl_2.png", "structure.png"];
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn batch_export_writes_every_tab() {
+        let state = test_state();
+        {
+            let mut st = state.borrow_mut();
+            st.tabs[0].file_name = "NaCl.cif".to_string();
+            let mut second = TabState::new(&st.config);
+            second.structure = st.tabs[0].structure.clone();
+            second.file_name = "KCl.cif".to_string();
+            st.tabs.push(second);
+            st.tabs.push(TabState::new(&Config::default()));
+        }
+
+        let dir = TmpFile::new("_batch");
+        std::fs::create_dir(&dir.0).unwrap();
+        let settings = ExportSettings {
+            scale: 10.0,
+            ..ExportSettings::default()
+        };
+        let st = state.borrow();
+        let targets = batch_targets(&st, &dir.0, ExportFormat::SVG);
+        let results: Vec<_> = st
+            .tabs
+            .iter()
+            .zip(&targets)
+            .map(|(tab, (_, path))| export_tab(tab, &st.config, path, ExportFormat::SVG, &settings))
+            .collect();
+
+        assert_eq!(targets[1].0, "KCl.svg");
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[1].is_ok());
+        assert!(results[2].is_err(), "empty tab must report, not abort");
+        assert!(dir.0.join("NaCl.svg").exists() && dir.0.join("KCl.svg").exists());
+        let _ = std::fs::remove_dir_all(&dir.0);
+    }
+
+    #[test]
+    fn report_pdf_flows_onto_extra_pages() {
+        let out = TmpFile::new(".pdf");
+        let state = test_state();
+        let brief = ExportSettings {
+            include_bvs_summary: false,
+            ..ExportSettings::default()
+        };
+        let msg = export_report_pdf(state.clone(), out.path(), brief.clone()).unwrap();
+        assert!(msg.ends_with("(1 page)"), "{msg}");
+        let pdf = std::fs::read(out.path()).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // 128 atoms: the composition table is capped, the BVS table is not.
+        {
+            let mut st = state.borrow_mut();
+            let s = st.tabs[0].structure.as_mut().unwrap();
+            s.lattice = [[16.0, 0.0, 0.0], [0.0, 8.0, 0.0], [0.0, 0.0, 16.0]];
+            s.atoms = (0..128)
+                .map(|i| {
+                    let p = [(i % 8) as f64, ((i / 8) % 4) as f64, (i / 32) as f64];
+                    let el = ["Na", "Cl"][(i + i / 8 + i / 32) % 2];
+                    atom(el, [2.0 * p[0], 2.0 * p[1], 4.0 * p[2]])
+                })
+                .collect();
+        }
+        let msg = export_report_pdf(state.clone(), out.path(), brief).unwrap();
+        assert!(msg.ends_with("(1 page)"), "{msg}");
+        let msg = export_report_pdf(state, out.path(), ExportSettings::default()).unwrap();
+        assert!(!msg.ends_with("(1 page)"), "{msg}");
+    }
+}
@@ -3,24 +3,144 @@
 // Draw order: Polyhedra (background) → Bonds → Atoms (foreground)
 // All unwraps eliminated, NaN-safe
 
+use super::bonds::{effective_tolerance, find_bonds};
 use super::primitives::*;
-use super::scene::RenderAtom;
-use crate::config::ColorMode;
-use crate::model::elements::{ColorScheme, get_atom_cov, get_covalent_radius, get_element_color};
+use super::scene::{DepthRange, RenderAtom};
+use crate::config::{ColorMode, Config, LabelContent, RenderQuality, RenderStyle};
+use crate::model::elements::{ColorScheme, get_covalent_radius, get_element_color};
+use crate::model::structure::OVERLAP_DIST;
 use crate::physics::bond_valence::get_ideal_oxidation_state;
 use crate::physics::operations::miller_algo::MillerMath;
 use crate::rendering::polyhedra;
 use crate::rendering::polyhedra_lighting;
-use crate::state::TabState;
+use crate::state::{AxesMode, SelectedAtom, TabState};
 use crate::utils::spatial_grid::SpatialGrid;
 use gtk4::cairo;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::f64::consts::PI;
 
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// Fill a `width`×`height` canvas with the style's background: a top→bottom
+/// linear gradient when `background_gradient` is set, else the flat color.
+/// Shared by the screen draw and the offscreen exporters so they match.
+pub fn draw_background(cr: &cairo::Context, style: &RenderStyle, width: f64, height: f64) {
+    match style.background_gradient {
+        Some((top, bottom)) => {
+            let grad = cairo::LinearGradient::new(0.0, 0.0, 0.0, height.max(1.0));
+            grad.add_color_stop_rgb(0.0, top.0, top.1, top.2);
+            grad.add_color_stop_rgb(1.0, bottom.0, bottom.1, bottom.2);
+            cr.set_source(&grad).ok();
+            cr.rectangle(0.0, 0.0, width, height);
+            cr.fill().ok();
+        }
+        None => {
+            let (r, g, b) = style.background_color;
+            cr.set_source_rgb(r, g, b);
+            cr.paint().ok();
+        }
+    }
+}
+
+/// What a frame is actually painted on, top and bottom color (equal for a
+/// flat fill). HUD ink and depth fog are picked against it, so an export
+/// with its own background reads like the screen does on the tab's.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backdrop {
+    pub top: (f64, f64, f64),
+    pub bottom: (f64, f64, f64),
+}
+
+impl Backdrop {
+    pub fn flat(rgb: (f64, f64, f64)) -> Self {
+        Self {
+            top: rgb,
+            bottom: rgb,
+        }
+    }
+
+    /// The backdrop [`draw_background`] paints for `style`.
+    pub fn of_style(style: &RenderStyle) -> Self {
+        match style.background_gradient {
+            Some((top, bottom)) => Self { top, bottom },
+            None => Self::flat(style.background_color),
+        }
+    }
+
+    /// Average color, what a gradient looks like as a whole.
+    fn mean(&self) -> (f64, f64, f64) {
+        (
+            (self.top.0 + self.bottom.0) / 2.0,
+            (self.top.1 + self.bottom.1) / 2.0,
+            (self.top.2 + self.bottom.2) / 2.0,
+        )
+    }
+}
+
+/// Depth cueing for one frame: an atom or bond at the far end of `range`
+/// is drawn `strength` of the way from its own color to the background.
+#[derive(Clone, Copy, Debug)]
+pub struct DepthCue {
+    pub range: DepthRange,
+    pub strength: f64,
+    pub background: (f64, f64, f64),
+}
+
+impl DepthCue {
+    /// The cue the config asks for over `range`, `None` when depth cueing
+    /// is off. A gradient backdrop fogs toward the mean of its stops.
+    pub fn from_config(config: &Config, range: DepthRange, backdrop: &Backdrop) -> Option<Self> {
+        if !config.depth_cue_enabled || config.depth_cue_strength <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            range,
+            strength: config.depth_cue_strength.min(1.0),
+            background: backdrop.mean(),
+        })
+    }
+
+    /// Share of the background in the color at depth `z` (Å).
+    fn amount(&self, z: f64) -> f64 {
+        self.strength * self.range.fraction(z)
+    }
+
+    /// `rgb` as seen at depth `z`.
+    fn apply(&self, rgb: (f64, f64, f64), z: f64) -> (f64, f64, f64) {
+        let t = self.amount(z);
+        let mix = |c: f64, bg: f64| c + (bg - c) * t;
+        (
+            mix(rgb.0, self.background.0),
+            mix(rgb.1, self.background.1),
+            mix(rgb.2, self.background.2),
+        )
+    }
+}
+
+/// Merge two rendered eye images into a red/cyan anaglyph, in place in
+/// `left`. Both buffers are Cairo ARGB32 (native-endian, so B, G, R, A in
+/// memory on little-endian hosts): red comes from the left eye, green and
+/// blue from the right, alpha is the union of both.
+pub fn merge_anaglyph(left: &mut [u8], right: &[u8]) {
+    // Byte offsets of B, G, R, A within one pixel.
+    #[cfg(target_endian = "little")]
+    const BGRA: [usize; 4] = [0, 1, 2, 3];
+    #[cfg(target_endian = "big")]
+    const BGRA: [usize; 4] = [3, 2, 1, 0];
+    let [b, g, r, a] = BGRA;
+
+    for (lp, rp) in left.chunks_exact_mut(4).zip(right.chunks_exact(4)) {
+        lp[g] = rp[g];
+        lp[b] = rp[b];
+        lp[a] = lp[a].max(rp[a]);
+        // Keep premultiplied alpha valid: no channel may exceed alpha.
+        lp[r] = lp[r].min(lp[a]);
+    }
+}
+
 /// Map BVS deviation to color gradient
 /// Green (good) → Yellow (warning) → Orange → Red (bad)
 fn get_bvs_color(
@@ -54,19 +174,32 @@ fn get_bvs_color(
     }
 }
 
+/// Blue (still) through green to red (the largest displacement) for a
+/// displacement `t` in [0, 1].
+fn displacement_color(t: f64) -> (f64, f64, f64) {
+    let t = t.clamp(0.0, 1.0);
+    let mid = 1.0 - (2.0 * t - 1.0).abs();
+    (0.15 + 0.75 * t, 0.3 + 0.45 * mid, 0.85 * (1.0 - t) + 0.1)
+}
+
 // ============================================================================
 // UNIT CELL DRAWING
 // ============================================================================
 
-pub fn draw_unit_cell(cr: &cairo::Context, corners: &[[f64; 2]], is_export: bool) {
+/// Cell box in the style's `cell_line_*` color, width and dash. Screen and
+/// export both call this, so figures match what was on screen.
+pub fn draw_unit_cell(cr: &cairo::Context, corners: &[[f64; 2]], style: &RenderStyle) {
     if corners.len() != 8 {
         return;
     }
 
-    cr.set_source_rgb(0.5, 0.5, 0.5);
-
-    // Publication quality: Thicker lines for exports
-    cr.set_line_width(if is_export { 2.5 } else { 1.5 });
+    let (r, g, b) = style.cell_line_color;
+    cr.set_source_rgb(r, g, b);
+    let width = style.cell_line_width.max(0.1);
+    cr.set_line_width(width);
+    if style.cell_line_dashed {
+        cr.set_dash(&[4.0 * width, 3.0 * width], 0.0);
+    }
 
     let edges = [
         (0, 1),
@@ -91,6 +224,7 @@ pub fn draw_unit_cell(cr: &cairo::Context, corners: &[[f64; 2]], is_export: bool
         cr.stroke()
             .expect("Failed to stroke unit cell - reduce complexity");
     }
+    cr.set_dash(&[], 0.0);
 }
 
 // ============================================================================
@@ -168,6 +302,184 @@ fn draw_all_polyhedra(
 // ============================================================================
 // MAIN STRUCTURE DRAWING
 // ============================================================================
+/// Display color of one atom instance, shared by the atom sprites and the
+/// split-color bond halves so the two always agree.
+pub(crate) fn atom_rgb(
+    tab: &TabState,
+    atom: &RenderAtom,
+    color_scheme: ColorScheme,
+) -> (f64, f64, f64) {
+    // Per-atom override beats every color mode — this is exactly what the
+    // user just set in the Atom Instances dialog, so respect it everywhere
+    // including BVS view.
+    if let Some(c) = tab.override_color(atom.original_index) {
+        return c;
+    }
+    let default_rgb = get_element_color(&atom.element, color_scheme);
+    match tab.style.color_mode {
+        ColorMode::Element => tab
+            .style
+            .element_colors
+            .get(&atom.element)
+            .copied()
+            .unwrap_or(default_rgb),
+        ColorMode::BondValence => {
+            if let Some(bvs_value) = tab.bvs_cache.get(atom.original_index) {
+                let ideal = tab
+                    .bvs_expected
+                    .get(atom.original_index)
+                    .copied()
+                    .unwrap_or_else(|| get_ideal_oxidation_state(&atom.element));
+                get_bvs_color(
+                    *bvs_value,
+                    ideal,
+                    tab.style.bvs_threshold_good,
+                    tab.style.bvs_threshold_warn,
+                )
+            } else {
+                (0.7, 0.7, 0.7)
+            }
+        }
+        ColorMode::Displacement => match tab.displacement.get(atom.original_index) {
+            Some(&t) => displacement_color(t),
+            None => (0.7, 0.7, 0.7),
+        },
+        _ => default_rgb,
+    }
+}
+
+/// Pie wedges for mixed-occupancy sites: partially occupied instances
+/// within `OVERLAP_DIST` of each other whose occupancies sum to at most 1
+/// share one sphere, each drawn as the `[start, end)` fraction of the
+/// circle given by its occupancy (normalized over the site, sorted by
+/// structure index). Keyed by `unique_id`; fully occupied atoms and lone
+/// partial sites get no entry and draw as plain spheres.
+fn split_site_wedges(
+    atoms: &[RenderAtom],
+    occupancy: impl Fn(usize) -> f64,
+) -> HashMap<usize, (f64, f64)> {
+    let partial = |a: &RenderAtom| !a.is_coord_only && occupancy(a.original_index) < 1.0 - 1e-6;
+    let mut wedges = HashMap::new();
+    if !atoms.iter().any(partial) {
+        return wedges;
+    }
+
+    let grid = SpatialGrid::build(atoms, OVERLAP_DIST, partial);
+    let mut grouped = vec![false; atoms.len()];
+    let mut site = Vec::new();
+    for (i, atom) in atoms.iter().enumerate() {
+        if grouped[i] || !partial(atom) {
+            continue;
+        }
+        site.clear();
+        grid.query(atom.cart_pos, OVERLAP_DIST, &mut site);
+        site.retain(|&j| !grouped[j]);
+        let total: f64 = site
+            .iter()
+            .map(|&j| occupancy(atoms[j].original_index))
+            .sum();
+        if site.len() < 2 || total > 1.0 + 1e-6 || total <= 0.0 {
+            continue;
+        }
+        site.sort_by_key(|&j| atoms[j].original_index);
+        let mut start = 0.0;
+        for &j in &site {
+            grouped[j] = true;
+            let end = start + occupancy(atoms[j].original_index) / total;
+            wedges.insert(atoms[j].unique_id, (start, end));
+            start = end;
+        }
+    }
+    wedges
+}
+
+/// Opacity of boundary images when `view.faint_ghosts` is on, and of
+/// view replicas always.
+const GHOST_ALPHA: f64 = 0.45;
+
+/// Opacity an atom instance is painted with: boundary images that only
+/// complete the cell are faded when the view asks for faint ghosts, and
+/// the replicas of `view.replication` are always faded.
+pub(crate) fn atom_alpha(tab: &TabState, atom: &RenderAtom) -> f64 {
+    if atom.is_replica || (atom.is_ghost && tab.view.faint_ghosts) {
+        GHOST_ALPHA
+    } else {
+        1.0
+    }
+}
+
+/// Pull bond ends this fraction of the way to the sphere surface, so
+/// antialiasing never leaves a hairline between cylinder and atom.
+const BOND_TUCK: f64 = 0.95;
+
+/// Where a bond of radius `bond_r` leaves two spheres `len` apart (all in
+/// px): the cylinder's rim meets each sphere at √(R² − r²) from its center.
+/// Returns fractions (from atom 1, from atom 2) of the center-to-center
+/// segment, or `None` when the spheres hide the whole bond.
+fn clip_bond_to_spheres(len: f64, r1: f64, r2: f64, bond_r: f64) -> Option<(f64, f64)> {
+    let surface = |r: f64| (r * r - bond_r * bond_r).max(0.0).sqrt() * BOND_TUCK;
+    let (off1, off2) = (surface(r1), surface(r2));
+    if len <= off1 + off2 || len <= 1e-9 {
+        return None;
+    }
+    Some((off1 / len, off2 / len))
+}
+
+/// Radius of the ellipsoid enclosing `probability` of a trivariate normal,
+/// in units of the principal RMS displacements (1.5382 at 50%). Solves the
+/// χ (3 dof) CDF, `erf(c/√2) − √(2/π)·c·e^(−c²/2)`, by bisection.
+fn ellipsoid_scale(probability: f64) -> f64 {
+    let p = probability.clamp(0.01, 0.999);
+    let cdf = |c: f64| erf(c / 2f64.sqrt()) - (2.0 / PI).sqrt() * c * (-c * c / 2.0).exp();
+    let (mut lo, mut hi) = (0.0, 6.0);
+    for _ in 0..60 {
+        let mid = 0.5 * (lo + hi);
+        if cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Error function, |error| < 1.2e-7 (Numerical Recipes erfcc).
+fn erf(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = 1.0 - t * poly.exp();
+    if x >= 0.0 {
+        r
+    } else {
+        -r
+    }
+}
+
+/// Outline of the displacement ellipsoid `u` (Cartesian, Å²) seen through
+/// the view rotation: screen ellipse semi-axes (RMS, Å) and the major
+/// axis angle (rad). `None` for a degenerate tensor.
+fn projected_ellipse(u: &[[f64; 3]; 3], rot: &nalgebra::Matrix3<f64>) -> Option<(f64, f64, f64)> {
+    let u = nalgebra::Matrix3::from_fn(|i, j| u[i][j]);
+    let v = rot * u * rot.transpose();
+    let (a, b, d) = (v[(0, 0)], v[(0, 1)], v[(1, 1)]);
+    let mean = 0.5 * (a + d);
+    let half = (0.25 * (a - d) * (a - d) + b * b).sqrt();
+    let (l1, l2) = (mean + half, mean - half);
+    if l2 <= 0.0 {
+        return None;
+    }
+    Some((l1.sqrt(), l2.sqrt(), 0.5 * (2.0 * b).atan2(a - d)))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn draw_structure(
     cr: &cairo::Context,
     atoms: &[RenderAtom],
@@ -175,13 +487,11 @@ pub fn draw_structure(
     scale: f64,
     is_export: bool,
     color_scheme: ColorScheme,
+    quality: RenderQuality,
+    depth_cue: Option<DepthCue>,
 ) {
-    // Bond detection tolerance
-    let tolerance = if tab.view.bond_cutoff < 0.1 || tab.view.bond_cutoff > 2.0 {
-        1.15
-    } else {
-        tab.view.bond_cutoff
-    };
+    let tolerance = effective_tolerance(tab);
+    let fog = |rgb: (f64, f64, f64), z: f64| depth_cue.map_or(rgb, |cue| cue.apply(rgb, z));
 
     // Whether to show ghost atoms visually. Ghost atoms are always present in
     // the atoms slice (needed for polyhedra/bond detection at cell boundaries),
@@ -193,10 +503,11 @@ pub fn draw_structure(
     let mut render_bonds: Vec<RenderBond> = Vec::with_capacity(atoms.len() * 2);
 
     // ========================================================================
-    // STEP 1: Collect Atoms (skip coord-only ghosts and invisible ghosts)
+    // STEP 1: Collect Atoms (skip coord-only ghosts, invisible ghosts and
+    // hidden species)
     // ========================================================================
     for atom in atoms {
-        if atom.is_coord_only {
+        if atom.is_coord_only || atom.is_hidden {
             continue;
         }
         if atom.is_ghost && !show_ghosts {
@@ -206,89 +517,76 @@ pub fn draw_structure(
     }
 
     // ========================================================================
-    // STEP 2: Collect Bonds (skip bonds involving coord-only or hidden ghosts)
-    //
-    // Uses a spatial grid to avoid the O(N²) nested scan. Grid cell size =
-    // max bond distance (4 Å), so each query visits a 3×3×3 block at most.
-    // Atoms filtered out at grid build time are never returned as neighbors,
-    // so the inner loop doesn't need to re-check is_coord_only / is_ghost.
+    // STEP 2: Collect Bonds (skip bonds involving coord-only ghosts, invisible
+    // ghosts or hidden species)
     // ========================================================================
     if tab.view.show_bonds {
-        const MAX_BOND_DIST: f64 = 4.0;
-
-        let grid = SpatialGrid::build(atoms, MAX_BOND_DIST, |a| {
-            !a.is_coord_only && !(a.is_ghost && !show_ghosts)
+        let bonds = find_bonds(tab, atoms, tolerance, |a| {
+            !a.is_coord_only && !a.is_hidden && !(a.is_ghost && !show_ghosts)
         });
-        let mut neighbors: Vec<usize> = Vec::with_capacity(64);
 
-        for (i, r1) in atoms.iter().enumerate() {
-            if r1.is_coord_only || (r1.is_ghost && !show_ghosts) {
-                continue;
-            }
-            let rad1 = get_atom_cov(&r1.element);
-
-            neighbors.clear();
-            grid.query(r1.cart_pos, MAX_BOND_DIST, &mut neighbors);
+        for (i, j, _) in bonds {
+            let (r1, r2) = (&atoms[i], &atoms[j]);
+            let raw_r1 = get_covalent_radius(&r1.element);
+            let raw_r2 = get_covalent_radius(&r2.element);
+
+            let mult1 = tab.override_radius_scale(r1.original_index);
+            let mult2 = tab.override_radius_scale(r2.original_index);
+            let r1_px = raw_r1 * tab.style.atom_scale * mult1 * scale;
+            let r2_px = raw_r2 * tab.style.atom_scale * mult2 * scale;
+
+            let v_x = r2.screen_pos[0] - r1.screen_pos[0];
+            let v_y = r2.screen_pos[1] - r1.screen_pos[1];
+            let v_z = r2.screen_pos[2] - r1.screen_pos[2];
+            let radius = tab.style.bond_radius * scale;
+            // Bonds reaching into a replica cell fade with it.
+            let alpha = if r1.is_replica || r2.is_replica {
+                GHOST_ALPHA
+            } else {
+                1.0
+            };
 
-            for &j in &neighbors {
-                // Enforce unique (i, j) ordering so each pair is emitted once.
-                if j <= i {
-                    continue;
-                }
-                let r2 = &atoms[j];
-                // Grid filter already excluded coord_only and hidden ghosts.
-
-                // Calculate CARTESIAN distance
-                let dx = r2.cart_pos[0] - r1.cart_pos[0];
-                let dy = r2.cart_pos[1] - r1.cart_pos[1];
-                let dz = r2.cart_pos[2] - r1.cart_pos[2];
-                let dist = (dx * dx + dy * dy + dz * dz).sqrt();
-
-                // Grid query already enforced dist ≤ MAX_BOND_DIST, so no
-                // redundant check needed here.
-
-                let rad2 = get_atom_cov(&r2.element);
-                let max_bond_dist = (rad1 + rad2) * tolerance;
-                let min_bond_dist = 0.4;
-
-                if dist > min_bond_dist && dist < max_bond_dist {
-                    let raw_r1 = get_covalent_radius(&r1.element);
-                    let raw_r2 = get_covalent_radius(&r2.element);
-
-                    let mult1 = tab.override_radius_scale(r1.original_index);
-                    let mult2 = tab.override_radius_scale(r2.original_index);
-                    let r1_px = raw_r1 * tab.style.atom_scale * mult1 * scale;
-                    let r2_px = raw_r2 * tab.style.atom_scale * mult2 * scale;
-
-                    let v_x = r2.screen_pos[0] - r1.screen_pos[0];
-                    let v_y = r2.screen_pos[1] - r1.screen_pos[1];
-                    let v_z = r2.screen_pos[2] - r1.screen_pos[2];
-                    let full_screen_dist = (v_x * v_x + v_y * v_y + v_z * v_z).sqrt();
-
-                    let off1 = r1_px * 0.95;
-                    let off2 = r2_px * 0.95;
-
-                    if full_screen_dist > (off1 + off2) {
-                        let t1 = off1 / full_screen_dist;
-                        let t2 = off2 / full_screen_dist;
-
-                        let start = [
-                            r1.screen_pos[0] + v_x * t1,
-                            r1.screen_pos[1] + v_y * t1,
-                            r1.screen_pos[2] + v_z * t1,
-                        ];
-                        let end = [
-                            r2.screen_pos[0] - v_x * t2,
-                            r2.screen_pos[1] - v_y * t2,
-                            r2.screen_pos[2] - v_z * t2,
-                        ];
-
-                        render_bonds.push(RenderBond {
-                            start,
-                            end,
-                            radius: tab.style.bond_radius * scale,
-                        });
-                    }
+            // Screen depth is still in Å; bring it to px so the clip
+            // follows the bond's true 3D direction.
+            let seg_len = (v_x * v_x + v_y * v_y + (v_z * scale).powi(2)).sqrt();
+            if let Some((t1, t2)) = clip_bond_to_spheres(seg_len, r1_px, r2_px, radius) {
+                let at = |t: f64| {
+                    [
+                        r1.screen_pos[0] + v_x * t,
+                        r1.screen_pos[1] + v_y * t,
+                        r1.screen_pos[2] + v_z * t,
+                    ]
+                };
+                let start = at(t1);
+                let end = at(1.0 - t2);
+
+                if tab.style.bond_split_color {
+                    // Halves meet at the midpoint between the atom
+                    // centers, clamped onto the visible segment when
+                    // one sphere is much larger than the other.
+                    let mid = at(0.5_f64.clamp(t1, 1.0 - t2));
+                    render_bonds.push(RenderBond {
+                        start,
+                        end: mid,
+                        radius,
+                        color: atom_rgb(tab, r1, color_scheme),
+                        alpha,
+                    });
+                    render_bonds.push(RenderBond {
+                        start: mid,
+                        end,
+                        radius,
+                        color: atom_rgb(tab, r2, color_scheme),
+                        alpha,
+                    });
+                } else {
+                    render_bonds.push(RenderBond {
+                        start,
+                        end,
+                        radius,
+                        color: tab.style.bond_color,
+                        alpha,
+                    });
                 }
             }
         }
@@ -317,17 +615,27 @@ pub fn draw_structure(
     // ========================================================================
     // STEP 5: Draw Bonds (on top of polyhedra)
     // ========================================================================
+    // Fast quality trades the lit cylinders for flat rods; exports are
+    // always shaded.
+    let shaded = is_export || quality == RenderQuality::High;
     for bond in render_bonds {
-        draw_cylinder_impostor(
-            cr,
-            bond.start,
-            bond.end,
-            bond.radius,
-            tab.style.bond_color,
-            tab.style.metallic,
-            tab.style.roughness,
-            tab.style.transmission,
-        );
+        let transmission = 1.0 - (1.0 - tab.style.transmission) * bond.alpha;
+        let color = fog(bond.color, (bond.start[2] + bond.end[2]) / 2.0);
+        if shaded {
+            draw_cylinder_impostor(
+                cr,
+                bond.start,
+                bond.end,
+                bond.radius,
+                color,
+                tab.style.metallic,
+                tab.style.roughness,
+                transmission,
+                &tab.style.light,
+            );
+        } else {
+            draw_flat_bond(cr, bond.start, bond.end, bond.radius, color, transmission);
+        }
     }
 
     // ========================================================================
@@ -335,45 +643,58 @@ pub fn draw_structure(
     // ========================================================================
     let sprite_size = 128.0;
     let mut cache_access = tab.style.atom_cache.borrow_mut();
+    let wedges = match &tab.structure {
+        Some(s) => split_site_wedges(atoms, |i| s.atoms.get(i).map_or(1.0, |a| a.occupancy)),
+        None => HashMap::new(),
+    };
+
+    // Thermal ellipsoids replace the spheres of atoms that carry an ADP.
+    let ellipsoid_k = tab
+        .style
+        .show_ellipsoids
+        .then(|| ellipsoid_scale(tab.style.ellipsoid_probability));
+    let view_rot = *tab.view.rotation_matrix().matrix();
 
     for atom in render_atoms {
         let raw_r = get_covalent_radius(&atom.element);
-        let default_rgb = get_element_color(&atom.element, color_scheme);
-
-        // Per-atom override beats every color mode — this is exactly what the
-        // user just set in the Atom Instances dialog, so respect it everywhere
-        // including BVS view.
         let override_rgb = tab.override_color(atom.original_index);
+        let rgb = atom_rgb(tab, atom, color_scheme);
+        // Sprites are cached without depth, so they are built from `rgb`
+        // and fogged after the lookup; everything else draws `shown`.
+        let shown = fog(rgb, atom.screen_pos[2]);
 
-        let rgb = if let Some(c) = override_rgb {
-            c
-        } else {
-            match tab.style.color_mode {
-                ColorMode::Element => tab
-                    .style
-                    .element_colors
-                    .get(&atom.element)
-                    .copied()
-                    .unwrap_or(default_rgb),
-                ColorMode::BondValence => {
-                    if let Some(bvs_value) = tab.bvs_cache.get(atom.original_index) {
-                        let ideal = get_ideal_oxidation_state(&atom.element);
-                        get_bvs_color(
-                            *bvs_value,
-                            ideal,
-                            tab.style.bvs_threshold_good,
-                            tab.style.bvs_threshold_warn,
-                        )
-                    } else {
-                        (0.7, 0.7, 0.7)
-                    }
-                }
-                _ => default_rgb,
-            }
+        let radius_mult = tab.override_radius_scale(atom.original_index);
+        let ellipse = ellipsoid_k.and_then(|k| {
+            let u = tab
+                .structure
+                .as_ref()?
+                .atoms
+                .get(atom.original_index)?
+                .adp?;
+            let (a, b, angle) = projected_ellipse(&u, &view_rot)?;
+            Some((k * a * scale, k * b * scale, angle))
+        });
+        let target_atom_cov = match ellipse {
+            Some((a, _, _)) => a,
+            None => raw_r * tab.style.atom_scale * radius_mult * scale,
         };
 
-        let radius_mult = tab.override_radius_scale(atom.original_index);
-        let target_atom_cov = raw_r * tab.style.atom_scale * radius_mult * scale;
+        // Faded images are painted into a group and composited with alpha,
+        // so glow and sphere fade together. The clip covers the widest ring
+        // and keeps the group atom-sized instead of a full-canvas surface.
+        let alpha = atom_alpha(tab, atom);
+        if alpha < 1.0 {
+            let reach = target_atom_cov + 8.0;
+            cr.save().ok();
+            cr.rectangle(
+                atom.screen_pos[0] - reach,
+                atom.screen_pos[1] - reach,
+                2.0 * reach,
+                2.0 * reach,
+            );
+            cr.clip();
+            cr.push_group();
+        }
 
         // Selection glow — keyed on per-instance unique_id so only the clicked
         // ghost copy lights up, not every symmetry-equivalent corner.
@@ -392,32 +713,104 @@ pub fn draw_structure(
             cr.restore().ok();
         }
 
-        // Draw Atom (Vector vs Sprite)
-        // BVS view and per-atom color overrides both use the vector path —
-        // sprite cache is keyed by element+material, so a per-atom color
-        // change wouldn't get a fresh sprite without a more invasive cache-key
-        // rework. Vector draw is fast enough for the override case (typically
-        // a few atoms, not all of them).
-        if is_export
-            || matches!(tab.style.color_mode, ColorMode::BondValence)
-            || override_rgb.is_some()
+        // Search highlight — open ring so it stays distinct from the filled
+        // selection glow. Marks every instance of a matched structure atom.
+        if !is_export
+            && !tab.interaction.highlight_dimmed
+            && tab.interaction.highlighted.contains(&atom.original_index)
         {
+            cr.save().ok();
+            cr.set_source_rgba(0.0, 0.8, 1.0, 0.9);
+            cr.set_line_width(3.0);
+            cr.arc(
+                atom.screen_pos[0],
+                atom.screen_pos[1],
+                target_atom_cov + 6.0,
+                0.0,
+                2.0 * PI,
+            );
+            cr.stroke().ok();
+            cr.restore().ok();
+        }
+
+        // Hover ring — thin and translucent, on screen only.
+        if !is_export && tab.interaction.hovered == Some(atom.unique_id) {
+            cr.save().ok();
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.6);
+            cr.set_line_width(1.5);
+            cr.arc(
+                atom.screen_pos[0],
+                atom.screen_pos[1],
+                target_atom_cov + 3.0,
+                0.0,
+                2.0 * PI,
+            );
+            cr.stroke().ok();
+            cr.restore().ok();
+        }
+
+        // Mixed site: clip the sphere to this occupant's wedge.
+        let wedge = wedges.get(&atom.unique_id).copied();
+        if let Some((start, end)) = wedge {
+            let (a0, a1) = (-PI / 2.0 + 2.0 * PI * start, -PI / 2.0 + 2.0 * PI * end);
+            cr.save().ok();
+            cr.move_to(atom.screen_pos[0], atom.screen_pos[1]);
+            cr.arc(
+                atom.screen_pos[0],
+                atom.screen_pos[1],
+                target_atom_cov + 1.0,
+                a0,
+                a1,
+            );
+            cr.close_path();
+            cr.clip();
+        }
+
+        // Draw Atom (Vector vs Sprite)
+        // BVS view uses the vector path since every atom gets its own color.
+        // Per-atom color overrides get their own sprite key (element+material
+        // +color), so recoloring a group doesn't fall off the fast path.
+        if let Some((a, b, angle)) = ellipse {
+            draw_ellipsoid_vector(
+                cr,
+                atom.screen_pos[0],
+                atom.screen_pos[1],
+                a,
+                b,
+                angle,
+                shown,
+                &tab.style.light,
+            );
+        } else if is_export || matches!(tab.style.color_mode, ColorMode::BondValence) {
             draw_atom_vector(
                 cr,
                 atom.screen_pos[0],
                 atom.screen_pos[1],
                 target_atom_cov,
-                rgb,
+                shown,
+                &tab.style.light,
             );
         } else {
             use crate::rendering::sprite_cache::SpriteCache;
-            let cache_key = SpriteCache::make_key(
-                &atom.element,
-                tab.style.atom_scale,
-                tab.style.metallic,
-                tab.style.roughness,
-                tab.style.transmission,
-            );
+            let cache_key = match override_rgb {
+                Some(c) => SpriteCache::make_override_key(
+                    &atom.element,
+                    c,
+                    tab.style.atom_scale,
+                    tab.style.metallic,
+                    tab.style.roughness,
+                    tab.style.transmission,
+                    &tab.style.light,
+                ),
+                None => SpriteCache::make_key(
+                    &atom.element,
+                    tab.style.atom_scale,
+                    tab.style.metallic,
+                    tab.style.roughness,
+                    tab.style.transmission,
+                    &tab.style.light,
+                ),
+            };
 
             let sprite = cache_access.get_or_insert(cache_key, || {
                 create_atom_sprite(
@@ -427,6 +820,7 @@ pub fn draw_structure(
                     tab.style.metallic,
                     tab.style.roughness,
                     tab.style.transmission,
+                    &tab.style.light,
                 )
             });
 
@@ -437,78 +831,448 @@ pub fn draw_structure(
             cr.set_source_surface(&sprite, -sprite_size / 2.0, -sprite_size / 2.0)
                 .ok();
             cr.paint().ok();
+            // The background masked by the sprite's own alpha, so the
+            // antialiased rim fogs with the rest of the sphere.
+            if let Some(cue) = depth_cue {
+                let t = cue.amount(atom.screen_pos[2]);
+                if t > 0.0 {
+                    let (r, g, b) = cue.background;
+                    cr.set_source_rgba(r, g, b, t);
+                    cr.mask_surface(&sprite, -sprite_size / 2.0, -sprite_size / 2.0)
+                        .ok();
+                }
+            }
             cr.restore().ok();
         }
 
-        // ====================================================================
-        // ENGRAVED BILLIARD LABELS
-        // ====================================================================
-        if tab.style.show_labels && target_atom_cov > 12.0 {
-            // 1. Determine Contrast & Engraving Colors
-            let lum = 0.299 * rgb.0 + 0.587 * rgb.1 + 0.114 * rgb.2;
-            let (text_col, shadow_col) = if lum > 0.65 {
-                // Bright Atom: Black text with white highlight (stamped in)
-                ((0.0, 0.0, 0.0, 0.8), (1.0, 1.0, 1.0, 0.4))
-            } else {
-                // Dark Atom: White text with dark shadow (embossed)
-                ((1.0, 1.0, 1.0, 0.9), (0.0, 0.0, 0.0, 0.5))
-            };
+        if wedge.is_some() {
+            cr.restore().ok();
+        }
 
-            // 2. Font Settings (Smaller to avoid curvature distortion issues)
-            // Reduced from 0.9 to 0.6 to keep text in the "flat" center zone
-            let font_size = target_atom_cov * 0.6;
-            cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
-            cr.set_font_size(font_size);
+        if alpha < 1.0 {
+            cr.pop_group_to_source().ok();
+            cr.paint_with_alpha(alpha).ok();
+            cr.restore().ok();
+        }
+    }
+}
 
-            if let Ok(extents) = cr.text_extents(&atom.element) {
-                let x_off = extents.width() / 2.0 + extents.x_bearing();
-                let y_off = extents.height() / 2.0 + extents.y_bearing();
+// ============================================================================
+// HUD OVERLAYS
+// ============================================================================
 
-                let base_x = atom.screen_pos[0] - x_off;
-                let base_y = atom.screen_pos[1] - y_off;
+/// Bar length actually drawn: `requested` Å when it fits in `max_px` at
+/// `scale` px/Å, otherwise the longest 1-2-5 length that does.
+fn fit_scale_bar(requested: f64, scale: f64, max_px: f64) -> f64 {
+    if requested * scale <= max_px || scale <= 0.0 {
+        return requested;
+    }
+    let limit = max_px / scale;
+    let decade = 10f64.powf(limit.log10().floor());
+    [5.0, 2.0, 1.0]
+        .iter()
+        .map(|m| m * decade)
+        .find(|len| *len <= limit)
+        .unwrap_or(decade)
+}
 
-                // 3. Draw "Engraving" Shadow (Offset slightly down-right)
-                cr.set_source_rgba(shadow_col.0, shadow_col.1, shadow_col.2, shadow_col.3);
-                cr.move_to(base_x + 1.0, base_y + 1.0);
-                cr.show_text(&atom.element).ok();
+/// Length bar for figures, bottom-right. `scale` is the scene's px (or pt)
+/// per Å, so the bar is true to size at any zoom and export DPI. Black or
+/// white, whichever reads on `backdrop`.
+pub fn draw_scale_bar(
+    cr: &cairo::Context,
+    backdrop: &Backdrop,
+    scale: f64,
+    length: f64,
+    width: f64,
+    height: f64,
+) {
+    if scale <= 0.0 || length <= 0.0 {
+        return;
+    }
+    let margin = (width.min(height) * 0.04).clamp(12.0, 40.0);
+    let length = fit_scale_bar(length, scale, width * 0.4);
+    let bar_px = length * scale;
+    let x1 = width - margin;
+    let x0 = x1 - bar_px;
+    let y = height - margin;
+
+    let ink = hud_ink(backdrop);
+    cr.save().ok();
+    cr.set_source_rgb(ink, ink, ink);
+    cr.set_line_width(3.0);
+    cr.set_line_cap(cairo::LineCap::Butt);
+    cr.move_to(x0, y);
+    cr.line_to(x1, y);
+    for x in [x0, x1] {
+        cr.move_to(x, y - 5.0);
+        cr.line_to(x, y + 5.0);
+    }
+    cr.stroke().ok();
+
+    let label = format!("{} Å", length);
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(14.0);
+    if let Ok(ext) = cr.text_extents(&label) {
+        cr.move_to(
+            (x0 + x1) / 2.0 - ext.width() / 2.0 - ext.x_bearing(),
+            y - 9.0,
+        );
+        cr.show_text(&label).ok();
+    }
+    cr.restore().ok();
+}
 
-                // 4. Draw Main Text
-                cr.set_source_rgba(text_col.0, text_col.1, text_col.2, text_col.3);
-                cr.move_to(base_x, base_y);
-                cr.show_text(&atom.element).ok();
-            }
+/// Black (0.0) or white (1.0) text for HUD overlays, whichever reads on
+/// `backdrop`; corners sit near the bottom of a gradient.
+fn hud_ink(backdrop: &Backdrop) -> f64 {
+    let (r, g, b) = backdrop.bottom;
+    if 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5 {
+        0.0
+    } else {
+        1.0
+    }
+}
 
-            // 5. Heavy Gloss Overlay (Bakes the text under the shine)
-            let grad = cairo::RadialGradient::new(
-                atom.screen_pos[0] - target_atom_cov * 0.3,
-                atom.screen_pos[1] - target_atom_cov * 0.3,
-                target_atom_cov * 0.1,
-                atom.screen_pos[0],
-                atom.screen_pos[1],
-                target_atom_cov,
-            );
-            // Stronger shine to reinforce spherical shape over the text
-            grad.add_color_stop_rgba(0.0, 1.0, 1.0, 1.0, 0.5);
-            grad.add_color_stop_rgba(1.0, 1.0, 1.0, 1.0, 0.0);
+/// Legend rows: each element in the structure, alphabetically like the
+/// sidebar's element list, with its occupancy-weighted site count.
+fn legend_entries(tab: &TabState) -> Vec<(String, f64)> {
+    let Some(structure) = &tab.structure else {
+        return vec![];
+    };
+    let mut counts: HashMap<&str, f64> = HashMap::new();
+    for atom in &structure.atoms {
+        *counts.entry(atom.element.as_str()).or_insert(0.0) += atom.occupancy;
+    }
+    let mut entries: Vec<(String, f64)> = counts
+        .into_iter()
+        .map(|(el, n)| (el.to_string(), n))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
 
-            cr.set_source(&grad).ok();
-            cr.arc(
-                atom.screen_pos[0],
-                atom.screen_pos[1],
-                target_atom_cov,
-                0.0,
-                2.0 * PI,
-            );
-            cr.fill().ok();
+/// Whole counts print as integers, partial occupancies with two decimals.
+fn format_legend_count(n: f64) -> String {
+    if (n - n.round()).abs() < 1e-6 {
+        format!("{}", n.round())
+    } else {
+        format!("{:.2}", n)
+    }
+}
+
+/// Element key in the top-right corner: a swatch in the drawn color, the
+/// symbol and its atom count per row, on a translucent panel. Reads the
+/// structure every frame, so loads and edits show up without a refresh.
+pub fn draw_element_legend(
+    cr: &cairo::Context,
+    tab: &TabState,
+    color_scheme: ColorScheme,
+    backdrop: &Backdrop,
+    width: f64,
+    height: f64,
+) {
+    let entries = legend_entries(tab);
+    if entries.is_empty() {
+        return;
+    }
+    let colors = tab.effective_element_colors(color_scheme);
+    let margin = (width.min(height) * 0.04).clamp(12.0, 40.0);
+    let (font_size, swatch, pad) = (13.0, 12.0, 6.0);
+    let row_h = font_size + 6.0;
+    let ink = hud_ink(backdrop);
+
+    cr.save().ok();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_font_size(font_size);
+    let labels: Vec<String> = entries
+        .iter()
+        .map(|(el, n)| format!("{}  {}", el, format_legend_count(*n)))
+        .collect();
+    let text_w = labels
+        .iter()
+        .filter_map(|l| cr.text_extents(l).ok())
+        .map(|e| e.x_advance())
+        .fold(0.0, f64::max);
+
+    let panel_w = pad * 3.0 + swatch + text_w;
+    let panel_h = pad * 2.0 + row_h * entries.len() as f64;
+    let x0 = width - margin - panel_w;
+    let y0 = margin;
+
+    cr.set_source_rgba(1.0 - ink, 1.0 - ink, 1.0 - ink, 0.6);
+    cr.rectangle(x0, y0, panel_w, panel_h);
+    cr.fill().ok();
+
+    for (i, ((el, _), label)) in entries.iter().zip(&labels).enumerate() {
+        let cy = y0 + pad + row_h * (i as f64 + 0.5);
+        let (r, g, b) = colors
+            .get(el)
+            .copied()
+            .unwrap_or_else(|| get_element_color(el, color_scheme));
+        cr.set_source_rgb(r, g, b);
+        cr.arc(x0 + pad + swatch / 2.0, cy, swatch / 2.0, 0.0, 2.0 * PI);
+        cr.fill_preserve().ok();
+        cr.set_source_rgb(ink, ink, ink);
+        cr.set_line_width(0.75);
+        cr.stroke().ok();
+
+        cr.move_to(x0 + pad * 2.0 + swatch, cy + font_size * 0.35);
+        cr.show_text(label).ok();
+    }
+    cr.restore().ok();
+}
+
+/// |Δ| at the right end of the BVS key: past `warn` by the width of the
+/// gradient zone (at least 0.1 v.u.), so the red zone is visible.
+fn bvs_legend_span(good: f64, warn: f64) -> f64 {
+    warn + (warn - good).max(0.1)
+}
+
+/// BVS color key in the top-left corner while the view is colored by bond
+/// valence: the `get_bvs_color` ramp over |Δ| with ticks at both
+/// thresholds, plus the gray used for atoms without an ideal state. Reads
+/// the thresholds every frame, so the sidebar sliders update it live.
+pub fn draw_bvs_legend(
+    cr: &cairo::Context,
+    tab: &TabState,
+    backdrop: &Backdrop,
+    width: f64,
+    height: f64,
+) {
+    if !matches!(tab.style.color_mode, ColorMode::BondValence) || tab.structure.is_none() {
+        return;
+    }
+    let (good, warn) = (tab.style.bvs_threshold_good, tab.style.bvs_threshold_warn);
+    let span = bvs_legend_span(good, warn);
+    let margin = (width.min(height) * 0.04).clamp(12.0, 40.0);
+    let (font_size, pad, bar_w, bar_h, swatch) = (12.0, 6.0, 150.0, 12.0, 12.0);
+    let ink = hud_ink(backdrop);
+
+    let panel_w = bar_w + pad * 2.0;
+    let panel_h = pad * 5.0 + font_size * 3.0 + bar_h + swatch;
+    let (x0, y0) = (margin, margin);
+
+    cr.save().ok();
+    cr.set_source_rgba(1.0 - ink, 1.0 - ink, 1.0 - ink, 0.6);
+    cr.rectangle(x0, y0, panel_w, panel_h);
+    cr.fill().ok();
+
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(font_size);
+    cr.set_source_rgb(ink, ink, ink);
+    let mut y = y0 + pad + font_size;
+    cr.move_to(x0 + pad, y);
+    cr.show_text("BVS |Δ| (v.u.)").ok();
+
+    // Ramp, one slice per pixel column.
+    let bar_x = x0 + pad;
+    let bar_y = y + pad;
+    let steps = bar_w as usize;
+    for i in 0..steps {
+        let d = span * (i as f64 + 0.5) / steps as f64;
+        let (r, g, b) = get_bvs_color(1.0 + d, 1.0, good, warn);
+        cr.set_source_rgb(r, g, b);
+        cr.rectangle(bar_x + i as f64, bar_y, 1.0, bar_h);
+        cr.fill().ok();
+    }
+    cr.set_source_rgb(ink, ink, ink);
+    cr.set_line_width(0.75);
+    cr.rectangle(bar_x, bar_y, bar_w, bar_h);
+    cr.stroke().ok();
+
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    y = bar_y + bar_h + pad + font_size;
+    for d in [0.0, good, warn] {
+        let x = bar_x + bar_w * d / span;
+        cr.move_to(x, bar_y + bar_h);
+        cr.line_to(x, bar_y + bar_h + 4.0);
+        cr.stroke().ok();
+        let label = format!("{:.2}", d);
+        if let Ok(ext) = cr.text_extents(&label) {
+            let tx = (x - ext.x_advance() / 2.0).clamp(x0 + 2.0, x0 + panel_w - ext.x_advance());
+            cr.move_to(tx, y);
+            cr.show_text(&label).ok();
+        }
+    }
+
+    y += pad;
+    cr.set_source_rgb(0.65, 0.65, 0.65);
+    cr.rectangle(bar_x, y, swatch, swatch);
+    cr.fill_preserve().ok();
+    cr.set_source_rgb(ink, ink, ink);
+    cr.stroke().ok();
+    cr.move_to(bar_x + swatch + pad, y + swatch * 0.85);
+    cr.show_text("no ideal state").ok();
+    cr.restore().ok();
+}
+
+/// Each drawn atom's bond-valence sum printed over its sphere, in BVS
+/// color mode only. Outlined text so it reads on any atom color.
+pub fn draw_bvs_values(cr: &cairo::Context, atoms: &[RenderAtom], tab: &TabState) {
+    if !matches!(tab.style.color_mode, ColorMode::BondValence) || tab.bvs_cache.is_empty() {
+        return;
+    }
+    let show_ghosts = tab.view.show_full_unit_cell;
+    cr.save().ok();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(11.0);
+    cr.set_line_join(cairo::LineJoin::Round);
+    for atom in atoms.iter().filter(|a| a.is_pickable(show_ghosts)) {
+        let Some(bvs) = tab.bvs_cache.get(atom.original_index) else {
+            continue;
+        };
+        let label = format!("{:.2}", bvs);
+        let Ok(ext) = cr.text_extents(&label) else {
+            continue;
+        };
+        cr.move_to(
+            atom.screen_pos[0] - ext.width() / 2.0 - ext.x_bearing(),
+            atom.screen_pos[1] - ext.height() / 2.0 - ext.y_bearing(),
+        );
+        cr.text_path(&label);
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        cr.set_line_width(2.5);
+        cr.stroke_preserve().ok();
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.fill().ok();
+    }
+    cr.restore().ok();
+}
+
+/// Most atom labels drawn in one frame; past it only the nearest atoms are
+/// labelled, since more text would bury the structure.
+pub const MAX_ATOM_LABELS: usize = 400;
+
+fn atom_label(atom: &RenderAtom, content: LabelContent) -> String {
+    match content {
+        LabelContent::Symbol => atom.element.clone(),
+        LabelContent::Index => atom.original_index.to_string(),
+        LabelContent::SymbolIndex => format!("{}{}", atom.element, atom.original_index),
+    }
+}
+
+/// Atoms to label, nearest first: pickable ones whose centre no nearer
+/// drawn sphere covers, at most `max`. The flag is set when the cap left
+/// some out. Nearer atoms are bucketed on a screen grid as large as the
+/// biggest sphere, so only the 3×3 cells around a centre can cover it.
+fn labelled_atoms(atoms: &[RenderAtom], show_ghosts: bool, max: usize) -> (Vec<&RenderAtom>, bool) {
+    let mut drawn: Vec<&RenderAtom> = atoms
+        .iter()
+        .filter(|a| !a.is_coord_only && !a.is_hidden && (!a.is_ghost || show_ghosts))
+        .collect();
+    drawn.sort_by(|a, b| {
+        a.screen_pos[2]
+            .partial_cmp(&b.screen_pos[2])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let cell = drawn.iter().map(|a| a.screen_radius).fold(1.0, f64::max);
+    let mut nearer: HashMap<(i64, i64), Vec<&RenderAtom>> = HashMap::new();
+    let mut out = Vec::new();
+    for atom in drawn {
+        let (x, y) = (atom.screen_pos[0], atom.screen_pos[1]);
+        let (cx, cy) = ((x / cell).floor() as i64, (y / cell).floor() as i64);
+        let covered = (-1..=1)
+            .flat_map(|i| (-1..=1).map(move |j| (cx + i, cy + j)))
+            .filter_map(|key| nearer.get(&key))
+            .flatten()
+            .any(|front| {
+                let (dx, dy) = (x - front.screen_pos[0], y - front.screen_pos[1]);
+                dx * dx + dy * dy < front.screen_radius * front.screen_radius
+            });
+        nearer.entry((cx, cy)).or_default().push(atom);
+        if covered || !atom.is_pickable(show_ghosts) {
+            continue;
+        }
+        if out.len() == max {
+            return (out, true);
         }
+        out.push(atom);
+    }
+    (out, false)
+}
+
+/// Element symbol and/or index centred on each atom, for "Show Labels".
+/// Atoms behind a nearer sphere get none, and past `MAX_ATOM_LABELS` only
+/// the nearest are labelled, with a console warning when that starts.
+/// Outlined like the BVS values so it reads on any atom color.
+pub fn draw_atom_labels(cr: &cairo::Context, atoms: &[RenderAtom], tab: &TabState) {
+    let show_ghosts = tab.view.show_full_unit_cell;
+    let (labelled, culled) = labelled_atoms(atoms, show_ghosts, MAX_ATOM_LABELS);
+    if culled && !tab.labels_culled.get() {
+        crate::utils::console::log_warn(&format!(
+            "Labels limited to the {} nearest atoms; zoom in or hide species to label the rest",
+            MAX_ATOM_LABELS
+        ));
+    }
+    tab.labels_culled.set(culled);
+
+    let size = tab.style.label_font_size;
+    cr.save().ok();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(size);
+    cr.set_line_join(cairo::LineJoin::Round);
+    for atom in labelled {
+        let label = atom_label(atom, tab.style.label_content);
+        let Ok(ext) = cr.text_extents(&label) else {
+            continue;
+        };
+        cr.move_to(
+            atom.screen_pos[0] - ext.width() / 2.0 - ext.x_bearing(),
+            atom.screen_pos[1] - ext.height() / 2.0 - ext.y_bearing(),
+        );
+        cr.text_path(&label);
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        cr.set_line_width((size * 0.2).max(1.0));
+        cr.stroke_preserve().ok();
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.fill().ok();
     }
+    cr.restore().ok();
 }
 
 // ============================================================================
 // COORDINATE AXES DRAWING
 // ============================================================================
 
-pub fn draw_axes(cr: &cairo::Context, tab: &TabState, width: f64, height: f64) {
+/// Shortest arrow, as a fraction of the longest, in lattice mode; keeps the
+/// short axis of a slab with vacuum from vanishing under its arrow head.
+const MIN_AXIS_FRACTION: f64 = 0.35;
+
+/// Gizmo arrows (world direction scaled to at most 1, label). With a lattice
+/// the arrows follow a, b, c at their relative lengths; otherwise x, y, z.
+fn gizmo_axes(lattice: Option<[[f64; 3]; 3]>) -> [([f64; 3], String); 3] {
+    let Some(lat) = lattice else {
+        return [
+            ([1.0, 0.0, 0.0], "x".to_string()),
+            ([0.0, 1.0, 0.0], "y".to_string()),
+            ([0.0, 0.0, 1.0], "z".to_string()),
+        ];
+    };
+    let lengths = lat.map(|v| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt());
+    let longest = lengths.iter().cloned().fold(0.0, f64::max);
+    let mut out: [([f64; 3], String); 3] = Default::default();
+    for (i, name) in ["a", "b", "c"].iter().enumerate() {
+        let len = lengths[i];
+        let dir = if len > 1e-9 {
+            let frac = (len / longest).max(MIN_AXIS_FRACTION);
+            lat[i].map(|x| x / len * frac)
+        } else {
+            [0.0; 3]
+        };
+        out[i] = (dir, format!("{} {:.2} Å", name, len));
+    }
+    out
+}
+
+/// An axis arrow tip: (x, y, unit direction x, unit direction y, color, label).
+type AxisTip<'a> = (f64, f64, f64, f64, &'a (f64, f64, f64), &'a String);
+
+/// Orientation HUD in the bottom-left corner. `visible` selects the X/Y/Z
+/// arrows: the live view passes `tab.view.show_axes`, exports pass their own
+/// choice so a figure doesn't depend on the on-screen toggles.
+pub fn draw_axes(cr: &cairo::Context, tab: &TabState, width: f64, height: f64, visible: [bool; 3]) {
     let hud_size = (width * 0.12).clamp(60.0, 150.0);
     let hud_cx = hud_size * 0.6;
     let hud_cy = height - hud_size * 0.6;
@@ -520,26 +1284,37 @@ pub fn draw_axes(cr: &cairo::Context, tab: &TabState, width: f64, height: f64) {
         [r.x, r.y, r.z]
     };
 
+    let lattice = match tab.view.axes_mode {
+        AxesMode::Lattice => tab
+            .structure
+            .as_ref()
+            .filter(|s| s.is_periodic)
+            .map(|s| s.lattice),
+        AxesMode::Cartesian => None,
+    };
+    let [(va, la), (vb, lb), (vc, lc)] = gizmo_axes(lattice);
     let axes_data = [
-        ([1.0, 0.0, 0.0], (0.85, 0.2, 0.2), tab.view.show_axes[0]), // X Red
-        ([0.0, 1.0, 0.0], (0.2, 0.7, 0.2), tab.view.show_axes[1]),  // Y Green
-        ([0.0, 0.0, 1.0], (0.2, 0.4, 0.85), tab.view.show_axes[2]), // Z Blue
+        (va, (0.85, 0.2, 0.2), visible[0], la), // X / a Red
+        (vb, (0.2, 0.7, 0.2), visible[1], lb),  // Y / b Green
+        (vc, (0.2, 0.4, 0.85), visible[2], lc), // Z / c Blue
     ];
 
     let mut sorted_axes: Vec<_> = axes_data
         .iter()
-        .map(|(v, c, show)| (rotate_vec(*v), c, show))
+        .map(|(v, c, show, label)| (rotate_vec(*v), c, show, label))
         .collect();
 
     // Sort by depth (NaN-safe)
-    sorted_axes.sort_by(|(a, _, _), (b, _, _)| b[2].partial_cmp(&a[2]).unwrap_or(Ordering::Equal));
+    sorted_axes
+        .sort_by(|(a, _, _, _), (b, _, _, _)| b[2].partial_cmp(&a[2]).unwrap_or(Ordering::Equal));
 
     let shaft_radius = 2.5;
     let head_radius = 6.0;
     let head_length = 16.0;
     let axis_length = hud_size;
 
-    for (r, color, show) in sorted_axes {
+    let mut tips: Vec<AxisTip> = Vec::new();
+    for (r, color, show, label) in sorted_axes {
         if !*show {
             continue;
         }
@@ -555,6 +1330,7 @@ pub fn draw_axes(cr: &cairo::Context, tab: &TabState, width: f64, height: f64) {
 
         let nx = -dy / len;
         let ny = dx / len;
+        tips.push((hud_cx + dx, hud_cy + dy, dx / len, dy / len, color, label));
 
         let start_x = hud_cx;
         let start_y = hud_cy;
@@ -619,6 +1395,23 @@ pub fn draw_axes(cr: &cairo::Context, tab: &TabState, width: f64, height: f64) {
         .expect("Failed to set gradient source for axis hub");
     cr.arc(hud_cx, hud_cy, 5.0, 0.0, 2.0 * PI);
     cr.fill().expect("Failed to fill axis hub");
+
+    // Labels just past each arrow tip, on top of every arrow.
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(13.0);
+    for (x, y, ux, uy, color, label) in tips {
+        let Ok(ext) = cr.text_extents(label) else {
+            continue;
+        };
+        let cx = x + ux * (8.0 + ext.width() / 2.0);
+        let cy = y + uy * (8.0 + ext.height() / 2.0);
+        cr.set_source_rgb(color.0 * 0.8, color.1 * 0.8, color.2 * 0.8);
+        cr.move_to(
+            cx - ext.width() / 2.0 - ext.x_bearing(),
+            cy - ext.height() / 2.0 - ext.y_bearing(),
+        );
+        cr.show_text(label).ok();
+    }
 }
 
 // ============================================================================
@@ -716,3 +1509,463 @@ pub fn draw_selection_box(cr: &cairo::Context, tab: &TabState) {
         cr.set_dash(&[], 0.0);
     }
 }
+
+// ============================================================================
+// MEASUREMENTS
+// ============================================================================
+
+/// Label for a measurement over `points` (Cartesian, Å): distance for two,
+/// the angle at the middle atom for three, the dihedral for four.
+fn measurement_label(points: &[[f64; 3]]) -> Option<String> {
+    use crate::utils::geometry::{calculate_angle, calculate_dihedral, calculate_distance};
+    match points {
+        [a, b] => Some(format!("{:.3} Å", calculate_distance(*a, *b))),
+        [a, b, c] => Some(format!("{:.1}°", calculate_angle(*a, *b, *c))),
+        [a, b, c, d] => Some(format!("φ {:.1}°", calculate_dihedral(*a, *b, *c, *d))),
+        _ => None,
+    }
+}
+
+/// Dashed legs and a boxed value for every pinned measurement. Atoms are
+/// looked up in this frame's scene by instance, so the overlay tracks
+/// rotation and zoom; measurements whose atoms are no longer drawn (hidden
+/// ghosts, an edited structure) are skipped rather than dropped.
+pub fn draw_measurements(
+    cr: &cairo::Context,
+    atoms: &[RenderAtom],
+    tab: &TabState,
+    show_labels: bool,
+) {
+    if tab.interaction.measurements.is_empty() {
+        return;
+    }
+    let by_id = pickable_by_id(atoms, tab);
+
+    cr.save().ok();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(13.0);
+    cr.set_line_width(1.5);
+
+    for m in &tab.interaction.measurements {
+        let Some(resolved) = resolve_instances(&by_id, &m.atoms) else {
+            continue;
+        };
+        let points: Vec<[f64; 3]> = resolved.iter().map(|r| r.cart_pos).collect();
+        let Some(label) = measurement_label(&points) else {
+            continue;
+        };
+        draw_measurement_overlay(cr, &resolved, show_labels.then_some(label.as_str()));
+    }
+    cr.restore().ok();
+}
+
+/// The atoms clicked so far in measurement mode: a ring on each, then the
+/// distance or angle once two or three are picked. `show_labels` is the
+/// `label_measurements` preference; without it only the legs and the
+/// arc are drawn, and the value is in the console.
+pub fn draw_measure_picks(
+    cr: &cairo::Context,
+    atoms: &[RenderAtom],
+    tab: &TabState,
+    show_labels: bool,
+) {
+    if tab.interaction.measure_picks.is_empty() {
+        return;
+    }
+    let by_id = pickable_by_id(atoms, tab);
+    let Some(resolved) = resolve_instances(&by_id, &tab.interaction.measure_picks) else {
+        return;
+    };
+
+    cr.save().ok();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(13.0);
+    cr.set_line_width(2.0);
+    cr.set_source_rgba(1.0, 0.85, 0.0, 0.95);
+    for r in &resolved {
+        cr.new_sub_path();
+        cr.arc(
+            r.screen_pos[0],
+            r.screen_pos[1],
+            r.screen_radius + 4.0,
+            0.0,
+            2.0 * PI,
+        );
+        cr.stroke().ok();
+    }
+
+    cr.set_line_width(1.5);
+    let points: Vec<[f64; 3]> = resolved.iter().map(|r| r.cart_pos).collect();
+    let label = measurement_label(&points).filter(|_| show_labels);
+    if resolved.len() >= 2 {
+        draw_measurement_overlay(cr, &resolved, label.as_deref());
+    }
+    cr.restore().ok();
+}
+
+/// This frame's pickable instances keyed by `unique_id`.
+fn pickable_by_id<'a>(atoms: &'a [RenderAtom], tab: &TabState) -> HashMap<usize, &'a RenderAtom> {
+    let show_ghosts = tab.view.show_full_unit_cell;
+    atoms
+        .iter()
+        .filter(|a| a.is_pickable(show_ghosts))
+        .map(|a| (a.unique_id, a))
+        .collect()
+}
+
+/// The scene instances of `picked`, or `None` if any of them is no longer
+/// drawn or now belongs to another atom.
+fn resolve_instances<'a>(
+    by_id: &HashMap<usize, &'a RenderAtom>,
+    picked: &[SelectedAtom],
+) -> Option<Vec<&'a RenderAtom>> {
+    picked
+        .iter()
+        .map(|sel| {
+            by_id
+                .get(&sel.unique_id)
+                .copied()
+                .filter(|r| r.original_index == sel.original_index)
+        })
+        .collect()
+}
+
+/// Dashed legs between consecutive atoms, the arc of an angle, and `label`
+/// boxed at the bond midpoint, the angle vertex or the dihedral axis. Uses
+/// the caller's font and line width.
+fn draw_measurement_overlay(cr: &cairo::Context, resolved: &[&RenderAtom], label: Option<&str>) {
+    let screen: Vec<(f64, f64)> = resolved
+        .iter()
+        .map(|r| (r.screen_pos[0], r.screen_pos[1]))
+        .collect();
+
+    cr.set_source_rgba(1.0, 0.85, 0.0, 0.95);
+    cr.set_dash(&[6.0, 4.0], 0.0);
+    cr.move_to(screen[0].0, screen[0].1);
+    for p in &screen[1..] {
+        cr.line_to(p.0, p.1);
+    }
+    cr.stroke().ok();
+    cr.set_dash(&[], 0.0);
+
+    // Anchor: bond midpoint, the angle vertex, or the dihedral axis.
+    let anchor = match screen.len() {
+        3 => {
+            let (v, a, c) = (screen[1], screen[0], screen[2]);
+            let start = (a.1 - v.1).atan2(a.0 - v.0);
+            let mut end = (c.1 - v.1).atan2(c.0 - v.0);
+            // Sweep the interior angle, not its 360° complement.
+            if end - start > PI {
+                end -= 2.0 * PI;
+            } else if start - end > PI {
+                end += 2.0 * PI;
+            }
+            let r = 22.0;
+            cr.new_sub_path();
+            if end >= start {
+                cr.arc(v.0, v.1, r, start, end);
+            } else {
+                cr.arc_negative(v.0, v.1, r, start, end);
+            }
+            cr.stroke().ok();
+            let mid = (start + end) / 2.0;
+            (v.0 + (r + 16.0) * mid.cos(), v.1 + (r + 16.0) * mid.sin())
+        }
+        4 => (
+            (screen[1].0 + screen[2].0) / 2.0,
+            (screen[1].1 + screen[2].1) / 2.0,
+        ),
+        _ => (
+            (screen[0].0 + screen[1].0) / 2.0,
+            (screen[0].1 + screen[1].1) / 2.0,
+        ),
+    };
+
+    let Some(label) = label else {
+        return;
+    };
+    if let Ok(ext) = cr.text_extents(label) {
+        let (w, h) = (ext.width(), ext.height());
+        let (x, y) = (anchor.0 - w / 2.0, anchor.1 + h / 2.0);
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.65);
+        cr.rectangle(x - 4.0, y - h - 4.0, w + 8.0, h + 8.0);
+        cr.fill().ok();
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.move_to(x - ext.x_bearing(), y);
+        cr.show_text(label).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anaglyph_takes_red_from_left_and_cyan_from_right() {
+        // One opaque pixel per eye, as native-endian ARGB32 words.
+        let mut left = 0xFF_C0_10_20u32.to_ne_bytes().to_vec();
+        let right = 0xFF_30_A0_B0u32.to_ne_bytes().to_vec();
+        merge_anaglyph(&mut left, &right);
+        let merged = u32::from_ne_bytes([left[0], left[1], left[2], left[3]]);
+        assert_eq!(merged, 0xFF_C0_A0_B0);
+    }
+
+    #[test]
+    fn depth_cue_fades_toward_the_background_with_depth() {
+        let mut config = Config::default();
+        let style = RenderStyle::default();
+        let backdrop = Backdrop::of_style(&style);
+        let range = DepthRange {
+            near: -2.0,
+            far: 2.0,
+        };
+        assert!(DepthCue::from_config(&config, range, &backdrop).is_none());
+
+        config.depth_cue_enabled = true;
+        config.depth_cue_strength = 0.5;
+        let cue = DepthCue::from_config(&config, range, &backdrop).unwrap();
+        let bg = style.background_color;
+        assert_eq!(cue.apply((1.0, 0.0, 0.0), -5.0), (1.0, 0.0, 0.0));
+        let far = cue.apply((1.0, 0.0, 0.0), 2.0);
+        assert!((far.0 - (1.0 + bg.0) / 2.0).abs() < 1e-12);
+        assert!((far.1 - bg.1 / 2.0).abs() < 1e-12);
+        assert!((cue.amount(0.0) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn labels_skip_covered_atoms_and_stop_at_the_cap() {
+        let disk = |uid: usize, x: f64, z: f64| {
+            let mut a = instance(uid, uid, [0.0; 3]);
+            a.screen_pos = [x, 0.0, z];
+            a.screen_radius = 10.0;
+            a
+        };
+        // Atom 1 sits behind atom 0, 4 px off its centre; atom 2 is clear.
+        let atoms = [disk(1, 4.0, 1.0), disk(0, 0.0, 0.0), disk(2, 50.0, 2.0)];
+        let (labelled, culled) = labelled_atoms(&atoms, false, 10);
+        let ids: Vec<usize> = labelled.iter().map(|a| a.unique_id).collect();
+        assert_eq!(ids, [0, 2]);
+        assert!(!culled);
+
+        let (labelled, culled) = labelled_atoms(&atoms, false, 1);
+        assert_eq!(labelled.len(), 1);
+        assert!(culled);
+
+        assert_eq!(atom_label(&atoms[2], LabelContent::SymbolIndex), "X2");
+    }
+
+    fn instance(index: usize, uid: usize, pos: [f64; 3]) -> RenderAtom {
+        RenderAtom {
+            screen_pos: [0.0; 3],
+            cart_pos: pos,
+            element: "X".to_string(),
+            original_index: index,
+            unique_id: uid,
+            is_ghost: false,
+            is_coord_only: false,
+            is_replica: false,
+            is_hidden: false,
+            screen_radius: 0.0,
+        }
+    }
+
+    #[test]
+    fn ellipsoid_probability_scale() {
+        assert!((ellipsoid_scale(0.5) - 1.5382).abs() < 1e-3);
+        assert!((ellipsoid_scale(0.9) - 2.5003).abs() < 1e-3);
+    }
+
+    #[test]
+    fn ellipse_projects_through_view_rotation() {
+        let u = [[0.04, 0.0, 0.0], [0.0, 0.01, 0.0], [0.0, 0.0, 0.09]];
+        let (a, b, angle) = projected_ellipse(&u, &nalgebra::Matrix3::identity()).unwrap();
+        assert!((a - 0.2).abs() < 1e-12 && (b - 0.1).abs() < 1e-12);
+        assert!(angle.abs() < 1e-12);
+
+        // Quarter turn about x brings the long z axis onto screen y.
+        let rot =
+            *nalgebra::Rotation3::from_axis_angle(&nalgebra::Vector3::x_axis(), PI / 2.0).matrix();
+        let (a, b, angle) = projected_ellipse(&u, &rot).unwrap();
+        assert!((a - 0.3).abs() < 1e-9 && (b - 0.2).abs() < 1e-9);
+        assert!((angle.abs() - PI / 2.0).abs() < 1e-9);
+
+        assert!(projected_ellipse(&[[0.0; 3]; 3], &rot).is_none());
+    }
+
+    #[test]
+    fn boundary_images_fade_only_when_asked() {
+        let mut tab = TabState::new(&crate::config::Config::default());
+        let real = instance(0, 0, [0.0; 3]);
+        let mut ghost = instance(0, 1, [1.0, 0.0, 0.0]);
+        ghost.is_ghost = true;
+
+        tab.view.faint_ghosts = true;
+        assert_eq!(atom_alpha(&tab, &real), 1.0);
+        assert_eq!(atom_alpha(&tab, &ghost), GHOST_ALPHA);
+        tab.view.faint_ghosts = false;
+        assert_eq!(atom_alpha(&tab, &ghost), 1.0);
+
+        // View replicas are always translucent.
+        let mut replica = instance(0, 2, [4.0, 0.0, 0.0]);
+        replica.is_replica = true;
+        assert_eq!(atom_alpha(&tab, &replica), GHOST_ALPHA);
+    }
+
+    #[test]
+    fn bonds_stop_at_sphere_surfaces() {
+        // Diatomic 100 px apart, radii 30 and 20 px, 6 px bond.
+        let (t1, t2) = clip_bond_to_spheres(100.0, 30.0, 20.0, 6.0).unwrap();
+        let expected = |r: f64| (r * r - 36.0_f64).sqrt() * BOND_TUCK / 100.0;
+        assert!((t1 - expected(30.0)).abs() < 1e-12);
+        assert!((t2 - expected(20.0)).abs() < 1e-12);
+
+        // A bond thicker than the atom starts at its center.
+        assert_eq!(clip_bond_to_spheres(100.0, 5.0, 5.0, 6.0), Some((0.0, 0.0)));
+        // Overlapping spheres leave nothing to draw.
+        assert_eq!(clip_bond_to_spheres(40.0, 30.0, 20.0, 6.0), None);
+        assert_eq!(clip_bond_to_spheres(0.0, 0.0, 0.0, 6.0), None);
+    }
+
+    #[test]
+    fn legend_counts_sites_by_occupancy() {
+        use crate::model::structure::{Atom, Structure};
+        let atom = |el: &str, occupancy: f64| Atom {
+            element: el.to_string(),
+            position: [0.0; 3],
+            original_index: 0,
+            oxidation: None,
+            occupancy,
+            adp: None,
+        };
+        let mut tab = TabState::new(&crate::config::Config::default());
+        assert!(legend_entries(&tab).is_empty());
+        tab.structure = Some(Structure {
+            lattice: [[3.0, 0.0, 0.0], [0.0, 3.0, 0.0], [0.0, 0.0, 3.0]],
+            atoms: vec![
+                atom("O", 1.0),
+                atom("Fe", 0.7),
+                atom("Cr", 0.3),
+                atom("O", 1.0),
+                atom("Fe", 1.0),
+            ],
+            formula: String::new(),
+            is_periodic: true,
+        });
+        let entries = legend_entries(&tab);
+        let labels: Vec<String> = entries
+            .iter()
+            .map(|(el, n)| format!("{} {}", el, format_legend_count(*n)))
+            .collect();
+        assert_eq!(labels, ["Cr 0.30", "Fe 1.70", "O 2"]);
+    }
+
+    #[test]
+    fn split_sites_become_pie_wedges() {
+        // Fe 0.7 / Cr 0.3 sharing a site, drawn twice (cell + ghost image),
+        // next to a fully occupied O and a lone half-occupied Li.
+        let occ = [0.7, 0.3, 1.0, 0.5];
+        let atoms = vec![
+            instance(1, 10, [0.0, 0.0, 0.0]),
+            instance(0, 11, [0.0, 0.0, 0.0]),
+            instance(0, 12, [4.0, 0.0, 0.0]),
+            instance(1, 13, [4.0, 0.0, 0.0]),
+            instance(2, 14, [0.0, 0.0, 0.0]),
+            instance(3, 15, [2.0, 2.0, 2.0]),
+        ];
+        let wedges = split_site_wedges(&atoms, |i| occ[i]);
+        assert_eq!(wedges.len(), 4);
+        for (fe, cr) in [(11, 10), (12, 13)] {
+            let (s, e) = wedges[&fe];
+            assert!(s == 0.0 && (e - 0.7).abs() < 1e-12);
+            let (s, e) = wedges[&cr];
+            assert!((s - 0.7).abs() < 1e-12 && (e - 1.0).abs() < 1e-12);
+        }
+        assert!(!wedges.contains_key(&14) && !wedges.contains_key(&15));
+
+        // Two half-filled copies of one atom overfill the site: no pie.
+        let dup = vec![instance(3, 0, [0.0; 3]), instance(3, 1, [0.1, 0.0, 0.0])];
+        assert!(split_site_wedges(&dup, |_| 0.6).is_empty());
+    }
+
+    #[test]
+    fn bvs_legend_spans_both_thresholds() {
+        let (good, warn) = (0.15, 0.40);
+        let span = bvs_legend_span(good, warn);
+        assert!((span - 0.65).abs() < 1e-12);
+        // Narrow gradient zones still leave room for the red end.
+        assert!((bvs_legend_span(0.25, 0.30) - 0.40).abs() < 1e-12);
+
+        let at = |d: f64| get_bvs_color(1.0 + d, 1.0, good, warn);
+        assert_eq!(at(0.0), (0.15, 0.75, 0.15));
+        assert_eq!(at(good * 0.99), (0.15, 0.75, 0.15));
+        let (r, g, _) = at(span);
+        assert!(r > 0.5 && g < 0.2, "right end must be red");
+    }
+
+    #[test]
+    fn scale_bar_shrinks_to_a_round_length_only_when_too_long() {
+        assert_eq!(fit_scale_bar(5.0, 20.0, 200.0), 5.0);
+        // 50 Å at 20 px/Å needs 1000 px; 10 Å fits in 200.
+        assert_eq!(fit_scale_bar(50.0, 20.0, 200.0), 10.0);
+        assert_eq!(fit_scale_bar(50.0, 20.0, 90.0), 2.0);
+        assert!((fit_scale_bar(5.0, 1000.0, 200.0) - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn lattice_gizmo_keeps_direction_and_relative_length() {
+        let hex = [[3.0, 0.0, 0.0], [-1.5, 2.598, 0.0], [0.0, 0.0, 6.0]];
+        let [(a, la), (b, _), (c, lc)] = gizmo_axes(Some(hex));
+        assert!((a[0] - 0.5).abs() < 1e-12 && a[1] == 0.0);
+        // 120° between a and b survives the scaling.
+        let cos = (a[0] * b[0] + a[1] * b[1]) / (0.5 * 0.5);
+        assert!((cos + 0.5).abs() < 1e-3);
+        assert_eq!(c, [0.0, 0.0, 1.0]);
+        assert_eq!(la, "a 3.00 Å");
+        assert_eq!(lc, "c 6.00 Å");
+
+        let [(x, lx), _, _] = gizmo_axes(None);
+        assert_eq!((x, lx.as_str()), ([1.0, 0.0, 0.0], "x"));
+    }
+
+    #[test]
+    fn measurement_labels_by_atom_count() {
+        let (o, x, y) = ([0.0; 3], [1.5, 0.0, 0.0], [0.0, 2.0, 0.0]);
+        assert_eq!(measurement_label(&[o, x]).as_deref(), Some("1.500 Å"));
+        assert_eq!(measurement_label(&[x, o, y]).as_deref(), Some("90.0°"));
+        assert!(measurement_label(&[o]).is_none());
+    }
+
+    /// Red channel of every pixel in the middle column of a 20 px tall
+    /// surface crossed by one horizontal bond of radius 8.
+    fn bond_cross_section(shaded: bool) -> Vec<u8> {
+        let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 40, 20).unwrap();
+        {
+            let cr = cairo::Context::new(&surface).unwrap();
+            let (p1, p2) = ([0.0, 10.0, 0.0], [40.0, 10.0, 0.0]);
+            let color = (0.8, 0.4, 0.2);
+            if shaded {
+                let light = crate::config::Light::default();
+                draw_cylinder_impostor(&cr, p1, p2, 8.0, color, 0.0, 0.5, 0.0, &light);
+            } else {
+                draw_flat_bond(&cr, p1, p2, 8.0, color, 0.0);
+            }
+        }
+        let stride = surface.stride() as usize;
+        let data = surface.data().unwrap();
+        (3..17)
+            .map(|y| u32::from_ne_bytes(data[y * stride + 80..y * stride + 84].try_into().unwrap()))
+            .map(|px| (px >> 16) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn flat_bonds_are_uniform_and_shaded_bonds_darken_at_the_rim() {
+        let flat = bond_cross_section(false);
+        assert!(flat.iter().all(|&r| r == flat[0]));
+
+        let shaded = bond_cross_section(true);
+        let brightest = *shaded.iter().max().unwrap();
+        assert!(shaded[0] + 40 < brightest);
+        assert!(shaded[shaded.len() - 1] + 40 < brightest);
+    }
+}
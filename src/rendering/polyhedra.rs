@@ -284,7 +284,7 @@ pub fn auto_detect_polyhedra_elements(
     });
 
     for (i, atom) in atoms.iter().enumerate() {
-        if atom.is_ghost {
+        if !atom.is_home() {
             continue;
         }
         // Only cations can be polyhedra centers
@@ -338,7 +338,7 @@ pub fn average_cn_for_element(
     let cns: Vec<usize> = atoms
         .iter()
         .enumerate()
-        .filter(|(_, a)| a.element == element && !a.is_ghost)
+        .filter(|(_, a)| a.element == element && a.is_home())
         .map(|(i, _)| {
             find_coordination_neighbors_with_grid(
                 i,
@@ -396,6 +396,10 @@ fn build_polyhedra_inner(
             if atom.is_ghost && !show_ghosts {
                 return None;
             }
+            // Replicas are drawn as context only, without polyhedra.
+            if atom.is_replica {
+                return None;
+            }
 
             let neighbors = find_coordination_neighbors_with_grid(
                 i,
@@ -441,45 +445,41 @@ fn build_polyhedra_inner(
 // becomes perceptibly slower than a Qhull-class algorithm; left as a
 // future improvement.
 
+/// Points within this distance (Å) of a face's plane lie on that face.
+const PLANE_TOL: f64 = 1e-6;
+
 fn convex_hull_3d(center: [f64; 3], pts: &[[f64; 3]], atom_indices: &[usize]) -> Vec<Face> {
-    let n = pts.len();
-    match n {
-        0..=2 => vec![],
-        3 => vec![make_face(center, pts, atom_indices, 0, 1, 2)],
-        4 => vec![
-            make_face(center, pts, atom_indices, 0, 1, 2),
-            make_face(center, pts, atom_indices, 0, 1, 3),
-            make_face(center, pts, atom_indices, 0, 2, 3),
-            make_face(center, pts, atom_indices, 1, 2, 3),
-        ],
-        _ => match find_initial_tetrahedron(pts) {
-            None => fan_triangulation(center, pts, atom_indices),
-            Some(_) => brute_force_hull(center, pts, atom_indices),
-        },
+    if pts.len() < 3 {
+        return vec![];
+    }
+    match find_initial_tetrahedron(pts) {
+        Some(_) => brute_force_hull(center, pts, atom_indices),
+        None => flat_polygon(center, pts, atom_indices),
     }
 }
 
 /// Brute-force O(n⁴) 3D convex hull. Correct by construction: enumerates
-/// every vertex triple and keeps the ones whose supporting plane has all
-/// other points strictly on one side (or coplanar within `tol`). Faces
-/// are then oriented so normals point outward from `center`.
+/// every vertex triple and keeps the planes that have all other points on
+/// one side (or within `PLANE_TOL` of the plane). Each such plane is one
+/// hull face; the points on it are triangulated once as a convex polygon,
+/// and the triangles are oriented so normals point outward from `center`.
 ///
 /// Rationale over a Qhull-style incremental algorithm:
-/// - Coplanar vertices (common in regular octahedra, cubes, icosahedra)
-///   are handled without the degenerate-face patching that incremental
-///   algorithms require.
-/// - Numerical robustness: a single relative tolerance check, no
-///   cascading topology updates that can accumulate error.
+/// - Coplanar vertices (common in cubes, cuboctahedra, square antiprisms)
+///   are handled by merging them into one polygon, without the
+///   degenerate-face patching that incremental algorithms require.
+/// - Numerical robustness: a single tolerance check, no cascading
+///   topology updates that can accumulate error.
 /// - n is bounded by coordination number (typically 2–12), so O(n⁴) is
 ///   at most a few thousand operations per polyhedron.
 fn brute_force_hull(center: [f64; 3], pts: &[[f64; 3]], atom_indices: &[usize]) -> Vec<Face> {
     let n = pts.len();
-    let tol = 1e-9;
-    let mut raw_faces: Vec<[usize; 3]> = Vec::new();
+    // Hull faces as the sorted set of points on them, with the normal of
+    // the triple that found them. A square face is found by all four of
+    // its triples; keeping one keeps every triangle of the cube drawn once
+    // rather than twice.
+    let mut planes: Vec<(Vec<usize>, Vector3<f64>)> = Vec::new();
 
-    // Enumerate all triples; keep those whose plane has every other point
-    // on one side (or coplanar within tol). That is the definition of a
-    // convex-hull face.
     for i in 0..n {
         for j in (i + 1)..n {
             for k in (j + 1)..n {
@@ -493,50 +493,128 @@ fn brute_force_hull(center: [f64; 3], pts: &[[f64; 3]], atom_indices: &[usize])
                 }
                 let mut pos = 0usize;
                 let mut neg = 0usize;
+                let mut on_plane = vec![i, j, k];
                 for m in 0..n {
                     if m == i || m == j || m == k {
                         continue;
                     }
                     let d = nrm.dot(&(v(pts[m]) - a)) / len;
-                    if d > tol {
+                    if d > PLANE_TOL {
                         pos += 1;
-                    } else if d < -tol {
+                    } else if d < -PLANE_TOL {
                         neg += 1;
+                    } else {
+                        on_plane.push(m);
                     }
                 }
-                // A hull face has all other points strictly on one side
-                // (coplanar points are allowed — they'll form adjacent faces).
                 if pos == 0 || neg == 0 {
-                    raw_faces.push(oriented_face(center, pts, [i, j, k]));
+                    on_plane.sort_unstable();
+                    if !planes.iter().any(|(seen, _)| *seen == on_plane) {
+                        planes.push((on_plane, nrm));
+                    }
                 }
             }
         }
     }
 
-    // Deduplicate faces that share the same vertex set (can happen when
-    // many points are coplanar and multiple triples describe the same
-    // planar region from different triangulations — keep them all; they
-    // are distinct triangles, not duplicates). Only drop exact index-set
-    // duplicates.
-    raw_faces.sort_by_key(|f| {
-        let mut s = *f;
-        s.sort_unstable();
-        s
-    });
-    raw_faces.dedup_by_key(|f| {
-        let mut s = *f;
-        s.sort_unstable();
-        s
+    planes
+        .iter()
+        .flat_map(|(verts, nrm)| polygon_faces(center, pts, atom_indices, verts, *nrm))
+        .collect()
+}
+
+/// Faces of neighbours that all lie in one plane (square-planar or
+/// trigonal coordination): the polygon they span, drawn flat. Collinear
+/// neighbours (linear coordination) span no area and give no faces.
+fn flat_polygon(center: [f64; 3], pts: &[[f64; 3]], atom_indices: &[usize]) -> Vec<Face> {
+    let (i0, i1) = furthest_pair(pts);
+    let (a, b) = (v(pts[i0]), v(pts[i1]));
+    let off_line = |i: usize| dist_to_line(v(pts[i]), a, b);
+    let Some(i2) = (0..pts.len()).max_by(|&x, &y| {
+        off_line(x)
+            .partial_cmp(&off_line(y))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }) else {
+        return vec![];
+    };
+    if off_line(i2) < PLANE_TOL {
+        return vec![];
+    }
+    let normal = (b - a).cross(&(v(pts[i2]) - a));
+    let all: Vec<usize> = (0..pts.len()).collect();
+    polygon_faces(center, pts, atom_indices, &all, normal)
+}
+
+/// Triangles covering the convex polygon spanned by `verts` (indices into
+/// `pts`, all in the plane with normal `normal`). Its corners come from a
+/// 2D hull in the plane (Andrew's monotone chain), so points inside the
+/// polygon or on its edges don't add slivers; the corners are then fanned
+/// from the first one. Fewer than three corners give no faces.
+fn polygon_faces(
+    center: [f64; 3],
+    pts: &[[f64; 3]],
+    atom_indices: &[usize],
+    verts: &[usize],
+    normal: Vector3<f64>,
+) -> Vec<Face> {
+    let Some(n) = normal.try_normalize(1e-12) else {
+        return vec![];
+    };
+    let helper = if n.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let e1 = n.cross(&helper).normalize();
+    let e2 = n.cross(&e1);
+    let origin = v(pts[verts[0]]);
+    let mut flat: Vec<(f64, f64, usize)> = verts
+        .iter()
+        .map(|&i| {
+            let d = v(pts[i]) - origin;
+            (d.dot(&e1), d.dot(&e2), i)
+        })
+        .collect();
+    flat.sort_by(|p, q| {
+        (p.0, p.1)
+            .partial_cmp(&(q.0, q.1))
+            .unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    raw_faces
-        .into_iter()
-        .filter(|f| {
-            let a = v(pts[f[0]]);
-            let cross = (v(pts[f[1]]) - a).cross(&(v(pts[f[2]]) - a));
-            cross.norm_squared() > 1e-16
+    // Twice the signed area of o→a→b; straight or clockwise turns are
+    // dropped from the chain.
+    let turn = |o: (f64, f64, usize), a: (f64, f64, usize), b: (f64, f64, usize)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut corners: Vec<(f64, f64, usize)> = Vec::with_capacity(flat.len() + 1);
+    for chain in [flat.clone(), flat.iter().rev().copied().collect::<Vec<_>>()] {
+        let start = corners.len();
+        for p in chain {
+            while corners.len() >= start + 2
+                && turn(corners[corners.len() - 2], corners[corners.len() - 1], p) <= 1e-12
+            {
+                corners.pop();
+            }
+            corners.push(p);
+        }
+        // The last point of each chain starts the other one.
+        corners.pop();
+    }
+    if corners.len() < 3 {
+        return vec![];
+    }
+
+    (1..corners.len() - 1)
+        .map(|k| {
+            make_face(
+                center,
+                pts,
+                atom_indices,
+                corners[0].2,
+                corners[k].2,
+                corners[k + 1].2,
+            )
         })
-        .map(|f| make_face(center, pts, atom_indices, f[0], f[1], f[2]))
         .collect()
 }
 
@@ -628,31 +706,6 @@ fn dist_to_plane(p: Vector3<f64>, a: Vector3<f64>, b: Vector3<f64>, c: Vector3<f
     n.dot(&(p - a)).abs() / len
 }
 
-fn fan_triangulation(center: [f64; 3], pts: &[[f64; 3]], atom_indices: &[usize]) -> Vec<Face> {
-    let n = pts.len();
-    let centroid: Vector3<f64> = pts.iter().map(|&p| v(p)).sum::<Vector3<f64>>() / n as f64;
-    let mut indices: Vec<usize> = (0..n).collect();
-    indices.sort_by(|&a, &b| {
-        let pa = pts[a];
-        let pb = pts[b];
-        let aa = (pa[1] - centroid.y).atan2(pa[0] - centroid.x);
-        let ab = (pb[1] - centroid.y).atan2(pb[0] - centroid.x);
-        aa.partial_cmp(&ab).unwrap_or(std::cmp::Ordering::Equal)
-    });
-    (1..n - 1)
-        .map(|i| {
-            make_face(
-                center,
-                pts,
-                atom_indices,
-                indices[0],
-                indices[i],
-                indices[i + 1],
-            )
-        })
-        .collect()
-}
-
 // ============================================================================
 // MINERALOGICAL DISTORTION METRICS
 // ============================================================================
@@ -978,6 +1031,68 @@ mod tests {
         );
     }
 
+    /// Σ v₀ · (v₁ × v₂) / 6 over the faces: the enclosed volume when each
+    /// face is covered exactly once and oriented outward.
+    fn signed_volume(pts: &[[f64; 3]], faces: &[Face]) -> f64 {
+        faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertex_atom_indices.map(|i| v(pts[i]));
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn coplanar_cube_faces_are_triangulated_once() {
+        let pts: Vec<[f64; 3]> = (0..8)
+            .map(|i| [0, 1, 2].map(|k| if (i >> k) & 1 == 1 { 1.0 } else { -1.0 }))
+            .collect();
+        let idx: Vec<usize> = (0..8).collect();
+        let faces = convex_hull_3d([0.0; 3], &pts, &idx);
+        assert_eq!(faces.len(), 12);
+        assert!((signed_volume(&pts, &faces) - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flat_and_degenerate_coordination() {
+        // Square-planar: one square, as two triangles, whatever its tilt.
+        let (pts, idx) = mock(vec![
+            [1.0, 0.0, 1.0],
+            [-1.0, 0.0, -1.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+        ]);
+        let faces = convex_hull_3d([0.0; 3], &pts, &idx);
+        assert_eq!(faces.len(), 2);
+        let area: f64 = faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertex_atom_indices.map(|i| v(pts[i]));
+                (b - a).cross(&(c - a)).norm() / 2.0
+            })
+            .sum();
+        assert!((area - 2.0 * 2f64.sqrt()).abs() < 1e-9);
+
+        // A centred point adds no slivers to a flat hexagon.
+        let mut hex: Vec<[f64; 3]> = (0..6)
+            .map(|k| {
+                let t = k as f64 * std::f64::consts::PI / 3.0;
+                [t.cos(), 0.0, t.sin()]
+            })
+            .collect();
+        hex.push([0.0; 3]);
+        let (pts, idx) = mock(hex);
+        assert_eq!(convex_hull_3d([0.0, 1.0, 0.0], &pts, &idx).len(), 4);
+
+        // Linear, single and empty coordination draw nothing.
+        let (pts, idx) = mock(vec![[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [2.0, 0.0, 0.0]]);
+        assert!(convex_hull_3d([0.0; 3], &pts, &idx).is_empty());
+        let (pts, idx) = mock(vec![[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0]]);
+        assert!(convex_hull_3d([0.0; 3], &pts, &idx).is_empty());
+        assert!(convex_hull_3d([0.0; 3], &[], &[]).is_empty());
+    }
+
     // ── Anion classification tests ──────────────────────────────────────────
 
     fn mock_atom(element: &str, x: f64, y: f64, z: f64) -> RenderAtom {
@@ -989,6 +1104,8 @@ mod tests {
             unique_id: 0,
             is_ghost: false,
             is_coord_only: false,
+            is_replica: false,
+            is_hidden: false,
             screen_radius: 0.0,
         }
     }
@@ -1,6 +1,7 @@
 // src/rendering/primitives.rs
 
 use super::scene::RenderAtom;
+use crate::config::{AntialiasLevel, Light};
 use gtk4::cairo::{self, Context, Format, ImageSurface, RadialGradient};
 use std::f64::consts::PI;
 
@@ -10,6 +11,9 @@ pub struct RenderBond {
   pub start: [f64; 3],
   pub end: [f64; 3],
   pub radius: f64,
+  pub color: (f64, f64, f64),
+  /// Opacity on top of the material transmission.
+  pub alpha: f64,
 }
 
 pub enum RenderPrimitive<'a> {
@@ -26,24 +30,47 @@ impl<'a> RenderPrimitive<'a> {
   }
 }
 
-pub fn draw_atom_vector(cr: &cairo::Context, x: f64, y: f64, radius: f64, color: (f64, f64, f64)) {
+/// Cairo antialias mode for the configured `AntialiasLevel`.
+pub fn cairo_antialias(level: AntialiasLevel) -> cairo::Antialias {
+  match level {
+    AntialiasLevel::None => cairo::Antialias::None,
+    AntialiasLevel::Fast => cairo::Antialias::Fast,
+    AntialiasLevel::Good => cairo::Antialias::Good,
+    AntialiasLevel::Best => cairo::Antialias::Best,
+  }
+}
+
+pub fn draw_atom_vector(
+  cr: &cairo::Context,
+  x: f64,
+  y: f64,
+  radius: f64,
+  color: (f64, f64, f64),
+  light: &Light,
+) {
   let (r, g, b) = color;
+  let (lx, ly) = light.screen_offset();
 
   // 1. Create a Radial Gradient to simulate 3D lighting
-  // Inner circle (highlight): offset to top-left (-radius/3)
+  // Inner circle (highlight): offset toward the light
   // Outer circle (shadow): centered
   let gradient = RadialGradient::new(
-    x - radius * 0.3,
-    y - radius * 0.3,
+    x + radius * lx,
+    y + radius * ly,
     radius * 0.1, // Highlight position/size
     x,
     y,
     radius, // Base sphere position/size
   );
 
-  // "Shininess" (Highlight) -> Base Color -> Shadow
-  gradient.add_color_stop_rgb(0.0, 1.0, 1.0, 1.0); // White highlight
-  gradient.add_color_stop_rgb(0.2, r + 0.2, g + 0.2, b + 0.2); // Lighter base
+  // "Shininess" (Highlight) -> Base Color -> Shadow. Intensity scales the
+  // lit base and fades the highlight toward it when dimmed.
+  let lit = |c: f64| ((c + 0.2) * light.intensity).min(1.0);
+  let (lr, lg, lb) = (lit(r), lit(g), lit(b));
+  let shine = light.intensity.min(1.0);
+  let spec = |c: f64| c + (1.0 - c) * shine;
+  gradient.add_color_stop_rgb(0.0, spec(lr), spec(lg), spec(lb)); // Highlight
+  gradient.add_color_stop_rgb(0.2, lr, lg, lb); // Lighter base
   gradient.add_color_stop_rgb(1.0, r * 0.6, g * 0.6, b * 0.6); // Darker shadow
 
   // 2. Draw the Circle
@@ -58,6 +85,69 @@ pub fn draw_atom_vector(cr: &cairo::Context, x: f64, y: f64, radius: f64, color:
   cr.stroke().unwrap();
 }
 
+/// Projected thermal ellipsoid: semi-axes `a` along `angle` (rad) and `b`
+/// across it, in px. Same shading as `draw_atom_vector`, painted on the
+/// unit disc and stretched, with the highlight kept on the light's side.
+/// The principal-axis lines mark it as an ellipsoid (ORTEP style).
+#[allow(clippy::too_many_arguments)]
+pub fn draw_ellipsoid_vector(
+  cr: &cairo::Context,
+  x: f64,
+  y: f64,
+  a: f64,
+  b: f64,
+  angle: f64,
+  color: (f64, f64, f64),
+  light: &Light,
+) {
+  let (r, g, bl) = color;
+  let (lx, ly) = light.screen_offset();
+  // Light offset in the ellipse's own (unrotated) frame.
+  let (c, s) = (angle.cos(), angle.sin());
+  let (ux, uy) = (c * lx + s * ly, -s * lx + c * ly);
+
+  let outline = |cr: &cairo::Context| {
+    cr.save().ok();
+    cr.translate(x, y);
+    cr.rotate(angle);
+    cr.scale(a.max(1e-3), b.max(1e-3));
+    cr.new_path();
+    cr.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+    cr.restore().ok();
+  };
+
+  cr.save().ok();
+  cr.translate(x, y);
+  cr.rotate(angle);
+  cr.scale(a.max(1e-3), b.max(1e-3));
+  let gradient = RadialGradient::new(ux, uy, 0.1, 0.0, 0.0, 1.0);
+  let lit = |c: f64| ((c + 0.2) * light.intensity).min(1.0);
+  let (lr, lg, lb) = (lit(r), lit(g), lit(bl));
+  let shine = light.intensity.min(1.0);
+  let spec = |c: f64| c + (1.0 - c) * shine;
+  gradient.add_color_stop_rgb(0.0, spec(lr), spec(lg), spec(lb));
+  gradient.add_color_stop_rgb(0.2, lr, lg, lb);
+  gradient.add_color_stop_rgb(1.0, r * 0.6, g * 0.6, bl * 0.6);
+  cr.set_source(&gradient).ok();
+  cr.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+  cr.fill().ok();
+  cr.restore().ok();
+
+  // Principal axes, then the rim, stroked in device space so the line
+  // width doesn't stretch with the ellipse.
+  let line_w = (a.min(b) * 0.05).max(0.5);
+  cr.set_source_rgba(0.0, 0.0, 0.0, 0.45);
+  cr.set_line_width(line_w);
+  for (dx, dy) in [(a * c, a * s), (-b * s, b * c)] {
+    cr.move_to(x - dx, y - dy);
+    cr.line_to(x + dx, y + dy);
+  }
+  cr.stroke().ok();
+  outline(cr);
+  cr.set_source_rgba(0.0, 0.0, 0.0, 0.3);
+  cr.stroke().ok();
+}
+
 /// Generates a high-quality 128x128 image of an atom.
 pub fn create_atom_sprite(
   r: f64,
@@ -66,6 +156,7 @@ pub fn create_atom_sprite(
   metallic: f64,
   roughness: f64,
   transmission: f64,
+  light: &Light,
 ) -> ImageSurface {
   let size = 128;
   let surface =
@@ -83,22 +174,25 @@ pub fn create_atom_sprite(
   let spec_b = 1.0 + (blue - 1.0) * metallic;
 
   let highlight_size = 0.05 + roughness * 0.35;
-  let light_offset = 0.25;
+  let (lx, ly) = light.screen_offset();
 
   let pat = cairo::RadialGradient::new(
-    center - radius * light_offset,
-    center - radius * light_offset,
+    center + radius * lx,
+    center + radius * ly,
     radius * highlight_size,
     center,
     center,
     radius,
   );
 
-  let shine_alpha = (1.0 - roughness * 0.5) * alpha;
+  let shine_alpha = (1.0 - roughness * 0.5) * light.intensity.min(1.0) * alpha;
   pat.add_color_stop_rgba(0.0, spec_r, spec_g, spec_b, shine_alpha);
 
+  // Intensity brightens or dims the directly lit band; ambient and rim
+  // stay put so a dim light still reads as a sphere.
+  let lit = |c: f64| (c * light.intensity).min(1.0);
   let lit_pos = 0.1 + roughness * 0.2;
-  pat.add_color_stop_rgba(lit_pos, red, green, blue, alpha);
+  pat.add_color_stop_rgba(lit_pos, lit(red), lit(green), lit(blue), alpha);
 
   let ambient_level = 0.4 - (metallic * 0.3);
   pat.add_color_stop_rgba(
@@ -125,6 +219,62 @@ pub fn create_atom_sprite(
   surface
 }
 
+/// A bond's screen outline: the unit normal across it and its four corners.
+type BondOutline = ((f64, f64), [[f64; 2]; 4]);
+
+/// Screen-space outline of a bond of half-width `radius` from `p1` to `p2`,
+/// corners on the `+n` side first. `None` for a bond seen end-on.
+fn bond_outline(p1: [f64; 3], p2: [f64; 3], radius: f64) -> Option<BondOutline> {
+  let dx = p2[0] - p1[0];
+  let dy = p2[1] - p1[1];
+  let len_sq = dx * dx + dy * dy;
+  if len_sq < 0.0001 {
+    return None;
+  }
+
+  let nx = -dy / len_sq.sqrt();
+  let ny = dx / len_sq.sqrt();
+  Some((
+    (nx, ny),
+    [
+      [p1[0] + nx * radius, p1[1] + ny * radius],
+      [p2[0] + nx * radius, p2[1] + ny * radius],
+      [p2[0] - nx * radius, p2[1] - ny * radius],
+      [p1[0] - nx * radius, p1[1] - ny * radius],
+    ],
+  ))
+}
+
+fn fill_outline(cr: &cairo::Context, corners: &[[f64; 2]; 4]) {
+  cr.move_to(corners[0][0], corners[0][1]);
+  for c in &corners[1..] {
+    cr.line_to(c[0], c[1]);
+  }
+  cr.close_path();
+  cr.fill().unwrap();
+}
+
+/// Flat-coloured bond for `RenderQuality::Fast`: one solid fill, no
+/// lighting.
+pub fn draw_flat_bond(
+  cr: &cairo::Context,
+  p1: [f64; 3],
+  p2: [f64; 3],
+  radius: f64,
+  color: (f64, f64, f64),
+  transmission: f64,
+) {
+  let Some((_, corners)) = bond_outline(p1, p2, radius) else {
+    return;
+  };
+  let (r, g, b) = color;
+  cr.set_source_rgba(r, g, b, 1.0 - transmission);
+  fill_outline(cr, &corners);
+}
+
+/// Bond drawn as a lit cylinder: a gradient across its width, darkened at
+/// the silhouette and with a specular streak on the side facing the light,
+/// using the same material parameters as the atom sprites.
 pub fn draw_cylinder_impostor(
   cr: &cairo::Context,
   p1: [f64; 3],
@@ -134,25 +284,12 @@ pub fn draw_cylinder_impostor(
   metallic: f64,
   roughness: f64,
   transmission: f64,
+  light: &Light,
 ) {
-  let dx = p2[0] - p1[0];
-  let dy = p2[1] - p1[1];
-  let len_sq = dx * dx + dy * dy;
-  if len_sq < 0.0001 {
+  let Some(((nx, ny), corners)) = bond_outline(p1, p2, radius) else {
     return;
-  }
-
-  let nx = -dy / len_sq.sqrt();
-  let ny = dx / len_sq.sqrt();
-
-  let c1x = p1[0] + nx * radius;
-  let c1y = p1[1] + ny * radius;
-  let c2x = p2[0] + nx * radius;
-  let c2y = p2[1] + ny * radius;
-  let c3x = p2[0] - nx * radius;
-  let c3y = p2[1] - ny * radius;
-  let c4x = p1[0] - nx * radius;
-  let c4y = p1[1] - ny * radius;
+  };
+  let [[c1x, c1y], _, _, [c4x, c4y]] = corners;
 
   let gradient = cairo::LinearGradient::new(c1x, c1y, c4x, c4y);
   let (r, g, b) = color;
@@ -164,22 +301,25 @@ pub fn draw_cylinder_impostor(
 
   let shadow = 0.3 - (metallic * 0.2);
 
+  // The gradient runs across the bond from +n (t = 0) to -n (t = 1); the
+  // highlight sits where the surface normal leans toward the light.
+  let (lx, ly) = light.screen_offset();
+  let h_width = 0.05 + roughness * 0.2;
+  let t_hi = ((1.0 - (lx * nx + ly * ny)) / 2.0).clamp(h_width + 0.05, 0.95 - h_width);
+  let lit = |c: f64| (c * light.intensity).min(1.0);
+  let (lr, lg, lb) = (lit(r), lit(g), lit(b));
+  let shine = alpha * (1.0 - roughness * 0.3) * light.intensity.min(1.0);
+
   gradient.add_color_stop_rgba(0.0, r * shadow, g * shadow, b * shadow, alpha);
-  gradient.add_color_stop_rgba(0.3, r, g, b, alpha);
+  gradient.add_color_stop_rgba(t_hi * 0.6, lr, lg, lb, alpha);
 
-  let h_width = 0.05 + roughness * 0.2;
-  gradient.add_color_stop_rgba(0.5 - h_width, r, g, b, alpha);
-  gradient.add_color_stop_rgba(0.5, sr, sg, sb, alpha * (1.0 - roughness * 0.3));
-  gradient.add_color_stop_rgba(0.5 + h_width, r, g, b, alpha);
+  gradient.add_color_stop_rgba(t_hi - h_width, lr, lg, lb, alpha);
+  gradient.add_color_stop_rgba(t_hi, sr, sg, sb, shine);
+  gradient.add_color_stop_rgba(t_hi + h_width, lr, lg, lb, alpha);
 
-  gradient.add_color_stop_rgba(0.7, r, g, b, alpha);
+  gradient.add_color_stop_rgba(1.0 - (1.0 - t_hi) * 0.6, lr, lg, lb, alpha);
   gradient.add_color_stop_rgba(1.0, r * shadow, g * shadow, b * shadow, alpha);
 
   cr.set_source(&gradient).unwrap();
-  cr.move_to(c1x, c1y);
-  cr.line_to(c2x, c2y);
-  cr.line_to(c3x, c3y);
-  cr.line_to(c4x, c4y);
-  cr.close_path();
-  cr.fill().unwrap();
+  fill_outline(cr, &corners);
 }
@@ -7,7 +7,8 @@
 
 use crate::config::{Config, RotationCenter};
 use crate::state::TabState;
-use nalgebra::{Matrix3, Vector3};
+use crate::utils::linalg::{lattice_to_matrix3, PeriodicCell};
+use nalgebra::Vector3;
 use std::cmp::Ordering;
 
 // This struct is used by interactions.rs for hit-testing and painter.rs
@@ -22,13 +23,160 @@ pub struct RenderAtom {
     /// Coordination-only ghost: participates in bond/polyhedra neighbor detection
     /// but is NEVER drawn and NEVER affects the bounding box / zoom.
     pub is_coord_only: bool,
+    /// Image in another cell of the `view.replication` block: drawn
+    /// translucently for context, never picked.
+    pub is_replica: bool,
+    /// Species hidden in the atom list: kept for neighbour searches but
+    /// never drawn, bonded on screen or picked.
+    pub is_hidden: bool,
     pub screen_radius: f64, // Rendered radius in pixels - used for accurate hit-testing
 }
 
+impl RenderAtom {
+    /// Whether the user can pick this instance. Coordination-only ghosts and
+    /// hidden species are never drawn; boundary ghosts are only on screen
+    /// with "Show Full Unit Cell"; replicas are context, not part of the
+    /// structure.
+    pub fn is_pickable(&self, show_ghosts: bool) -> bool {
+        !self.is_coord_only
+            && !self.is_replica
+            && !self.is_hidden
+            && (!self.is_ghost || show_ghosts)
+    }
+
+    /// The instance at the structure's own position (not an image).
+    pub fn is_home(&self) -> bool {
+        !self.is_ghost && !self.is_replica
+    }
+}
+
+/// Atoms of the last frame drawn on screen, for the `size` canvas they were
+/// laid out on. Hover picks from these instead of rebuilding the scene on
+/// every pointer motion.
+pub struct DrawnFrame {
+    pub size: (f64, f64),
+    pub atoms: Vec<RenderAtom>,
+    /// Depth range the frame was drawn with, so a manual range can start
+    /// from what's on screen.
+    pub depth: DepthRange,
+}
+
+/// Instance under the cursor at (x, y). A direct hit on a projected disk
+/// wins, the front-most one when disks overlap (larger depth is farther
+/// away; the painter draws in descending depth). Otherwise the disk whose
+/// rim is nearest, within `tolerance` px. Never returns an unpickable atom.
+pub fn pick_atom(
+    atoms: &[RenderAtom],
+    x: f64,
+    y: f64,
+    tolerance: f64,
+    show_ghosts: bool,
+) -> Option<&RenderAtom> {
+    let mut front: Option<&RenderAtom> = None;
+    let mut near: Option<(&RenderAtom, f64)> = None;
+    for atom in atoms.iter().filter(|a| a.is_pickable(show_ghosts)) {
+        let dx = atom.screen_pos[0] - x;
+        let dy = atom.screen_pos[1] - y;
+        let gap = (dx * dx + dy * dy).sqrt() - atom.screen_radius;
+        if gap <= 0.0 {
+            if front.map_or(true, |f| atom.screen_pos[2] < f.screen_pos[2]) {
+                front = Some(atom);
+            }
+        } else if gap <= tolerance && near.map_or(true, |(_, g)| gap < g) {
+            near = Some((atom, gap));
+        }
+    }
+    front.or(near.map(|(a, _)| a))
+}
+
+/// Fraction of the canvas the structure spans at zoom 1.0.
+pub const FIT_MARGIN: f64 = 0.8;
+
+/// Largest scale (pixels or points per Å) at which a `model_w` × `model_h`
+/// Å box fits inside a `canvas_w` × `canvas_h` canvas.
+pub fn fit_scale(canvas_w: f64, canvas_h: f64, model_w: f64, model_h: f64) -> f64 {
+    (canvas_w / model_w.max(1e-9)).min(canvas_h / model_h.max(1e-9))
+}
+
+/// Zoom-1.0 scale of the interactive view: a sphere of radius `extent` Å
+/// around the pivot spans `FIT_MARGIN` of the shorter canvas side.
+pub fn view_fit_scale(canvas_w: f64, canvas_h: f64, extent: f64) -> f64 {
+    let diameter = (2.0 * extent).max(1.0);
+    let margin = FIT_MARGIN;
+    fit_scale(canvas_w * margin, canvas_h * margin, diameter, diameter)
+}
+
 pub struct SceneBounds {
     pub scale: f64,
     pub width: f64,
     pub height: f64,
+    /// Depth span that fog and depth cueing map onto.
+    pub depth: DepthRange,
+}
+
+/// Near and far depth (Å along the line of sight, larger is farther) of
+/// the drawn scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthRange {
+    pub near: f64,
+    pub far: f64,
+}
+
+impl DepthRange {
+    /// Tight range around the atoms that are drawn (coordination-only
+    /// ghosts and hidden species don't count), padded by each atom's
+    /// radius so the front and back spheres aren't cut at their centres.
+    /// `None` when nothing is drawn.
+    pub fn fit(atoms: &[RenderAtom], scale: f64) -> Option<Self> {
+        atoms
+            .iter()
+            .filter(|a| !a.is_coord_only && !a.is_hidden)
+            .map(|a| {
+                let r = a.screen_radius / scale.max(1e-9);
+                (a.screen_pos[2] - r, a.screen_pos[2] + r)
+            })
+            .reduce(|(n0, f0), (n1, f1)| (n0.min(n1), f0.max(f1)))
+            .map(|(near, far)| Self { near, far })
+    }
+
+    /// Range for this frame: fitted to `atoms` unless the config fixes it.
+    pub fn for_scene(atoms: &[RenderAtom], scale: f64, config: &Config) -> Self {
+        let manual = Self {
+            near: config.depth_near.min(config.depth_far),
+            far: config.depth_near.max(config.depth_far),
+        };
+        if config.depth_range_auto {
+            Self::fit(atoms, scale).unwrap_or(manual)
+        } else {
+            manual
+        }
+    }
+
+    /// Position of depth `z` in the range: 0 at the near end, 1 at the far
+    /// end, clamped. A flat range (one atom, or a planar molecule seen
+    /// face-on) maps everything to 0.
+    pub fn fraction(&self, z: f64) -> f64 {
+        let span = self.far - self.near;
+        if span < 1e-6 {
+            0.0
+        } else {
+            ((z - self.near) / span).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Cell shifts per axis that `instance_id` can tell apart, from -1 up to a
+/// replication of `SHIFT_SPAN - 2`.
+const SHIFT_SPAN: usize = 1 << 10;
+
+/// `unique_id` of atom `index` drawn in the cell at `shift`. A function of
+/// the two alone, so a picked instance keeps its id when the replication,
+/// the ghost ranges or the hidden atoms change.
+fn instance_id(index: usize, shift: [i32; 3]) -> usize {
+    let code = shift.iter().fold(0, |acc, &s| {
+        acc * SHIFT_SPAN + (s + 1).clamp(0, SHIFT_SPAN as i32 - 1) as usize
+    });
+    index * SHIFT_SPAN.pow(3) + code
 }
 
 // Return: (Atoms, Lattice Corners [Screen X, Y], Bounds)
@@ -51,6 +199,7 @@ pub fn calculate_scene(
                     scale: 1.0,
                     width: 100.0,
                     height: 100.0,
+                    depth: DepthRange::for_scene(&[], 1.0, config),
                 },
             )
         }
@@ -59,13 +208,9 @@ pub fn calculate_scene(
     // --- 1. Prepare Matrices (Nalgebra) ---
     let rotation_matrix = tab.view.rotation_matrix();
 
-    let lat = structure.lattice;
-    let lattice_mat = Matrix3::new(
-        lat[0][0], lat[0][1], lat[0][2], lat[1][0], lat[1][1], lat[1][2], lat[2][0], lat[2][1],
-        lat[2][2],
-    );
-
-    let inv_lattice_mat = lattice_mat.try_inverse();
+    // Columns = lattice vectors, so `to_cart * frac` is Cartesian.
+    let to_cart = lattice_to_matrix3(structure.lattice).transpose();
+    let cell = PeriodicCell::new(structure.lattice);
 
     let center_arr = get_rotation_center(tab, config);
     let center = Vector3::new(center_arr[0], center_arr[1], center_arr[2]);
@@ -80,15 +225,24 @@ pub fn calculate_scene(
     let mut max_x = f64::MIN;
     let mut min_y = f64::MAX;
     let mut max_y = f64::MIN;
+    // Radius around the pivot of everything in the bounding box. Unlike the
+    // box it does not change with the rotation, so it sets the on-screen
+    // camera distance.
+    let mut extent: f64 = 0.0;
 
     // --- 2. Lattice Corners (Visual Box) ---
+    // Molecules have no cell: the placeholder lattice is neither drawn nor
+    // allowed to inflate the bounding box. An empty corner list makes the
+    // unit-cell and Miller-plane painters no-ops.
     let mut raw_corners = Vec::new();
-    for x in 0..=1 {
-        for y in 0..=1 {
-            for z in 0..=1 {
-                let frac = Vector3::new(x as f64, y as f64, z as f64);
-                let cart = lattice_mat.transpose() * frac;
-                raw_corners.push(cart);
+    if structure.is_periodic {
+        for x in 0..=1 {
+            for y in 0..=1 {
+                for z in 0..=1 {
+                    let frac = Vector3::new(x as f64, y as f64, z as f64);
+                    let cart = to_cart * frac;
+                    raw_corners.push(cart);
+                }
             }
         }
     }
@@ -97,6 +251,7 @@ pub fn calculate_scene(
     for p in raw_corners {
         let r = transform_point(p);
         rotated_corners.push([r.x, r.y]);
+        extent = extent.max(r.norm());
 
         if r.x < min_x {
             min_x = r.x;
@@ -115,120 +270,151 @@ pub fn calculate_scene(
     // --- 3. Ghost Atom Generation ---
     // Two-tier ghost system for correct coordination polyhedra:
     //
-    //   VISIBLE ghosts  (narrow range [-tol, 1+tol]):
-    //     Atoms at cell boundaries, drawn when "Show Full Unit Cell" is on.
+    //   VISIBLE ghosts  (narrow range [-tol, n+tol]):
+    //     Images of atoms on cell faces, edges and corners, drawn when "Show
+    //     Full Unit Cell" is on (faintly if `view.faint_ghosts`), so the box
+    //     is complete: a corner atom appears at all eight corners.
     //
-    //   COORDINATION ghosts (wider range [-coord_tol, 1+coord_tol]):
+    //   COORDINATION ghosts (wider range [-coord_tol, n+coord_tol]):
     //     Atoms further outside the cell, NEVER drawn and NEVER affect bounding box.
     //     Needed so atoms at corners/edges see their full coordination shell.
     //     Example: Ti at frac(0,0,0) in BaTiO₃ needs O images at frac(-0.5, 0, 0)
     //     to find all 6 neighbors for a correct octahedron.
     //
-    let shifts: Vec<f64> = vec![-1.0, 0.0, 1.0];
+    // With a view replication of n cells along an axis, the images in the
+    // other n-1 cells are translucent replicas and the ghost ranges above
+    // are measured from the edges of the whole block (n = 1 otherwise).
+    //
+    // Non-periodic structures have no images at all.
+    let reps = if structure.is_periodic {
+        tab.view.replication.map(|n| n.max(1) as i32)
+    } else {
+        [1, 1, 1]
+    };
+    let axis_shifts = |hi: i32| -> Vec<i32> {
+        if structure.is_periodic {
+            (-1..=hi).collect()
+        } else {
+            vec![0]
+        }
+    };
+    let mut shifts: Vec<[i32; 3]> = Vec::new();
+    for sx in axis_shifts(reps[0]) {
+        for sy in axis_shifts(reps[1]) {
+            for sz in axis_shifts(reps[2]) {
+                shifts.push([sx, sy, sz]);
+            }
+        }
+    }
     let include_ghosts_in_bounds = tab.view.show_full_unit_cell;
 
     let tol = 0.05; // Visible boundary ghosts
     let coord_tol = 0.55; // Coordination shell ghosts (covers half-cell images)
 
-    // --- 4. Process Atoms ---
-    let mut unique_id_counter = 0;
+    // The whole replicated block frames the view, drawn or not.
+    if reps != [1, 1, 1] {
+        for x in [0, reps[0]] {
+            for y in [0, reps[1]] {
+                for z in [0, reps[2]] {
+                    let frac = Vector3::new(x as f64, y as f64, z as f64);
+                    let r = transform_point(to_cart * frac);
+                    min_x = min_x.min(r.x);
+                    max_x = max_x.max(r.x);
+                    min_y = min_y.min(r.y);
+                    max_y = max_y.max(r.y);
+                    extent = extent.max(r.norm());
+                }
+            }
+        }
+    }
 
+    // --- 4. Process Atoms ---
     for (i, atom) in structure.atoms.iter().enumerate() {
         let pos_cart = Vector3::new(atom.position[0], atom.position[1], atom.position[2]);
 
-        let pos_frac = if let Some(inv) = inv_lattice_mat {
-            inv.transpose() * pos_cart
-        } else {
-            pos_cart
-        };
+        let pos_frac = cell.map_or(pos_cart, |cell| cell.to_frac(pos_cart));
 
         let element_ref = &atom.element;
+        let is_hidden = tab.style.hidden_elements.contains(element_ref.as_str());
+
+        for &shift in &shifts {
+            let frac_vec =
+                pos_frac + Vector3::new(shift[0] as f64, shift[1] as f64, shift[2] as f64);
+
+            // Check against the WIDER coordination range first.
+            // Molecule atoms may sit anywhere relative to the
+            // placeholder cell and are always kept.
+            let within = |margin: f64| {
+                (0..3).all(|k| frac_vec[k] >= -margin && frac_vec[k] <= reps[k] as f64 + margin)
+            };
+            if structure.is_periodic && !within(coord_tol) {
+                continue;
+            }
 
-        for &sx in &shifts {
-            for &sy in &shifts {
-                for &sz in &shifts {
-                    let nx = pos_frac.x + sx;
-                    let ny = pos_frac.y + sy;
-                    let nz = pos_frac.z + sz;
-
-                    // Check against the WIDER coordination range first
-                    if nx < -coord_tol
-                        || nx > 1.0 + coord_tol
-                        || ny < -coord_tol
-                        || ny > 1.0 + coord_tol
-                        || nz < -coord_tol
-                        || nz > 1.0 + coord_tol
-                    {
-                        continue;
-                    }
-
-                    let frac_vec = Vector3::new(nx, ny, nz);
-                    let cart_vec = lattice_mat.transpose() * frac_vec;
-                    let r_pos = transform_point(cart_vec);
-
-                    let is_shift = sx != 0.0 || sy != 0.0 || sz != 0.0;
-
-                    // Is this within the narrow visible-ghost range?
-                    let in_narrow = nx >= -tol
-                        && nx <= 1.0 + tol
-                        && ny >= -tol
-                        && ny <= 1.0 + tol
-                        && nz >= -tol
-                        && nz <= 1.0 + tol;
-
-                    let is_ghost = is_shift;
-                    // Coordination-only: outside narrow range, inside wide range.
-                    // These are NEVER drawn, NEVER affect bounding box.
-                    let is_coord_only = is_shift && !in_narrow;
-
-                    // Bounding box: exclude coord-only ghosts entirely
-                    if !is_coord_only
-                        && (!is_ghost || include_ghosts_in_bounds) {
-                            if r_pos.x < min_x {
-                                min_x = r_pos.x;
-                            }
-                            if r_pos.x > max_x {
-                                max_x = r_pos.x;
-                            }
-                            if r_pos.y < min_y {
-                                min_y = r_pos.y;
-                            }
-                            if r_pos.y > max_y {
-                                max_y = r_pos.y;
-                            }
-                        }
-
-                    render_atoms.push(RenderAtom {
-                        screen_pos: [r_pos.x, r_pos.y, r_pos.z],
-                        cart_pos: [cart_vec.x, cart_vec.y, cart_vec.z],
-                        element: element_ref.clone(),
-                        original_index: i,
-                        unique_id: unique_id_counter,
-                        is_ghost,
-                        is_coord_only,
-                        screen_radius: 0.0,
-                    });
-
-                    unique_id_counter += 1;
-                }
+            let cart_vec = to_cart * frac_vec;
+            let r_pos = transform_point(cart_vec);
+
+            let is_shift = shift != [0, 0, 0];
+            let is_replica = is_shift && (0..3).all(|k| (0..reps[k]).contains(&shift[k]));
+
+            // Is this within the narrow visible-ghost range?
+            let in_narrow = within(tol);
+
+            let is_ghost = is_shift && !is_replica;
+            // Coordination-only: outside narrow range, inside wide range.
+            // These are NEVER drawn, NEVER affect bounding box.
+            let is_coord_only = is_ghost && !in_narrow;
+
+            // Bounding box: exclude coord-only ghosts entirely
+            if !is_coord_only && (!is_ghost || include_ghosts_in_bounds) {
+                min_x = min_x.min(r_pos.x);
+                max_x = max_x.max(r_pos.x);
+                min_y = min_y.min(r_pos.y);
+                max_y = max_y.max(r_pos.y);
+                extent = extent.max(r_pos.norm());
             }
+
+            // Hidden atoms keep their place in the bounding box so
+            // hiding a group doesn't re-fit the camera, but they are
+            // otherwise absent from the scene.
+            if tab.is_hidden(i) {
+                continue;
+            }
+
+            render_atoms.push(RenderAtom {
+                screen_pos: [r_pos.x, r_pos.y, r_pos.z],
+                cart_pos: [cart_vec.x, cart_vec.y, cart_vec.z],
+                element: element_ref.clone(),
+                original_index: i,
+                unique_id: instance_id(i, shift),
+                is_ghost,
+                is_coord_only,
+                is_replica,
+                is_hidden,
+                screen_radius: 0.0,
+            });
         }
     }
 
     // --- 5. Calculate Scaling (World -> Pixel) ---
+    // On screen the rotation pivot (the origin of the rotated frame) stays
+    // at the window center, so turning the structure never drifts it. The
+    // fit is to the sphere around the pivot, so at zoom 1.0 the structure
+    // fits the canvas in every orientation and aspect ratio, and zoom is a
+    // pure multiplier on top. Exports frame the bounding box tightly, at
+    // the given scale or else at the same fit for their canvas.
     let final_scale;
-    let box_cx = (min_x + max_x) / 2.0;
-    let box_cy = (min_y + max_y) / 2.0;
+    let (box_cx, box_cy) = if is_export {
+        ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0)
+    } else {
+        (0.0, 0.0)
+    };
 
     if is_export {
-        final_scale = manual_scale.unwrap_or(50.0);
+        final_scale =
+            manual_scale.unwrap_or_else(|| view_fit_scale(win_w, win_h, extent) * tab.view.zoom);
     } else {
-        let model_w = (max_x - min_x).max(1.0);
-        let model_h = (max_y - min_y).max(1.0);
-        let margin = 0.8;
-        let scale_x = (win_w * margin) / model_w;
-        let scale_y = (win_h * margin) / model_h;
-        final_scale = scale_x.min(scale_y) * tab.view.zoom;
+        final_scale = view_fit_scale(win_w, win_h, extent) * tab.view.zoom;
     }
 
     let export_margin = if is_export { final_scale * 1.5 } else { 0.0 };
@@ -247,6 +433,15 @@ pub fn calculate_scene(
     };
 
     // --- 6. Apply Screen Transform ---
+    // Pan only applies on screen; exports always frame the whole structure.
+    let (pan_x, pan_y) = if is_export {
+        (0.0, 0.0)
+    } else {
+        (tab.view.pan_x, tab.view.pan_y)
+    };
+    let box_cx = box_cx + pan_x;
+    let box_cy = box_cy + pan_y;
+
     for atom in &mut render_atoms {
         atom.screen_pos[0] = (atom.screen_pos[0] - box_cx) * final_scale + win_cx;
         atom.screen_pos[1] = (atom.screen_pos[1] - box_cy) * final_scale + win_cy;
@@ -272,6 +467,10 @@ pub fn calculate_scene(
             .unwrap_or(Ordering::Equal)
     });
 
+    // Refit every frame so cueing spans the structure at every size and
+    // orientation.
+    let depth = DepthRange::for_scene(&render_atoms, final_scale, config);
+
     (
         render_atoms,
         final_corners,
@@ -279,13 +478,19 @@ pub fn calculate_scene(
             scale: final_scale,
             width: if is_export { export_w } else { win_w },
             height: if is_export { export_h } else { win_h },
+            depth,
         },
     )
 }
 
-fn get_rotation_center(tab: &TabState, config: &Config) -> [f64; 3] {
+/// Pivot of the view rotation in Cartesian Å: the tab's own choice when it
+/// has one, else `config.rotation_mode`.
+pub fn get_rotation_center(tab: &TabState, config: &Config) -> [f64; 3] {
     if let Some(s) = &tab.structure {
-        if matches!(config.rotation_mode, RotationCenter::UnitCell) {
+        let mode = tab.view.rotation_center.unwrap_or(config.rotation_mode);
+        // A molecule's placeholder cell says nothing about where it sits, so
+        // it always rotates about its centroid.
+        if s.is_periodic && matches!(mode, RotationCenter::UnitCell) {
             let v = s.lattice;
             return [
                 (v[0][0] + v[1][0] + v[2][0]) * 0.5,
@@ -309,3 +514,258 @@ fn get_rotation_center(tab: &TabState, config: &Config) -> [f64; 3] {
     }
     [0.0; 3]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::structure::{Atom, Structure};
+
+    fn cubic(fracs: &[[f64; 3]]) -> TabState {
+        let a = 4.0;
+        let mut tab = TabState::new(&Config::default());
+        tab.structure = Some(Structure {
+            lattice: [[a, 0.0, 0.0], [0.0, a, 0.0], [0.0, 0.0, a]],
+            atoms: fracs
+                .iter()
+                .enumerate()
+                .map(|(i, f)| Atom {
+                    element: "Na".to_string(),
+                    position: [f[0] * a, f[1] * a, f[2] * a],
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: "Na".to_string(),
+            is_periodic: true,
+        });
+        tab
+    }
+
+    fn disk(uid: usize, x: f64, depth: f64, radius: f64) -> RenderAtom {
+        RenderAtom {
+            screen_pos: [x, 0.0, depth],
+            cart_pos: [0.0; 3],
+            element: "C".to_string(),
+            original_index: uid,
+            unique_id: uid,
+            is_ghost: false,
+            is_coord_only: false,
+            is_replica: false,
+            is_hidden: false,
+            screen_radius: radius,
+        }
+    }
+
+    #[test]
+    fn picking_prefers_the_front_disk() {
+        // A large far disk at the origin partly covered by a small near one.
+        let atoms = vec![disk(0, 0.0, 5.0, 20.0), disk(1, 15.0, -5.0, 10.0)];
+        let uid = |x: f64, tol: f64| pick_atom(&atoms, x, 0.0, tol, true).map(|a| a.unique_id);
+
+        // Inside both: the near one is on top, whatever the center distance.
+        assert_eq!(uid(8.0, 4.0), Some(1));
+        // Only inside the far disk.
+        assert_eq!(uid(-10.0, 4.0), Some(0));
+        // Past both rims: the closer rim within tolerance, else nothing.
+        assert_eq!(uid(27.0, 4.0), Some(1));
+        assert_eq!(uid(27.0, 1.0), None);
+        assert_eq!(uid(-23.0, 4.0), Some(0));
+
+        let mut hidden = atoms.clone();
+        hidden[1].is_ghost = true;
+        assert_eq!(
+            pick_atom(&hidden, 8.0, 0.0, 4.0, false).map(|a| a.unique_id),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn pivot_projects_to_the_window_center() {
+        use nalgebra::UnitQuaternion;
+        let (w, h) = (800.0, 600.0);
+        let mut config = Config::default();
+        // Two atoms in one corner of the cell, so the pivots differ.
+        let mut tab = cubic(&[[0.1, 0.1, 0.1], [0.3, 0.1, 0.2]]);
+        tab.view.show_full_unit_cell = false;
+        tab.view.rotation = UnitQuaternion::from_euler_angles(0.4, -0.7, 1.1);
+
+        let centroid = |atoms: &[RenderAtom]| {
+            let real: Vec<_> = atoms.iter().filter(|a| !a.is_ghost).collect();
+            let n = real.len() as f64;
+            (
+                real.iter().map(|a| a.screen_pos[0]).sum::<f64>() / n,
+                real.iter().map(|a| a.screen_pos[1]).sum::<f64>() / n,
+            )
+        };
+        let cell_center = |corners: &[[f64; 2]]| {
+            (
+                corners.iter().map(|c| c[0]).sum::<f64>() / 8.0,
+                corners.iter().map(|c| c[1]).sum::<f64>() / 8.0,
+            )
+        };
+        let at_center =
+            |(x, y): (f64, f64)| (x - w / 2.0).abs() < 1e-9 && (y - h / 2.0).abs() < 1e-9;
+
+        config.rotation_mode = RotationCenter::Centroid;
+        let (atoms, corners, _) = calculate_scene(&tab, &config, w, h, false, None, None);
+        assert!(at_center(centroid(&atoms)));
+        assert!(!at_center(cell_center(&corners)));
+
+        config.rotation_mode = RotationCenter::UnitCell;
+        let (atoms, corners, _) = calculate_scene(&tab, &config, w, h, false, None, None);
+        assert!(at_center(cell_center(&corners)));
+        assert!(!at_center(centroid(&atoms)));
+
+        // The tab's own pivot wins over the preference.
+        tab.view.rotation_center = Some(RotationCenter::Centroid);
+        let (atoms, _, _) = calculate_scene(&tab, &config, w, h, false, None, None);
+        assert!(at_center(centroid(&atoms)));
+    }
+
+    #[test]
+    fn zoom_multiplies_a_fit_that_ignores_rotation_and_aspect() {
+        use nalgebra::UnitQuaternion;
+        let config = Config::default();
+        let mut tab = cubic(&[[0.5, 0.5, 0.5]]);
+        tab.view.zoom = 1.0;
+        let scale = |tab: &TabState, w: f64, h: f64| {
+            let (_, _, bounds) = calculate_scene(tab, &config, w, h, false, None, None);
+            bounds.scale
+        };
+
+        // Fit follows the shorter side: 800×600 and 600×900 share a 600 px
+        // limit, so both frame the structure identically.
+        let landscape = scale(&tab, 800.0, 600.0);
+        assert!((landscape - scale(&tab, 600.0, 900.0)).abs() < 1e-9);
+        assert!((scale(&tab, 1600.0, 1200.0) - 2.0 * landscape).abs() < 1e-9);
+
+        // The whole cell fits at zoom 1.0 whatever the orientation.
+        tab.view.rotation = UnitQuaternion::from_euler_angles(0.4, -0.7, 1.1);
+        assert!((scale(&tab, 800.0, 600.0) - landscape).abs() < 1e-9);
+        let (_, corners, _) = calculate_scene(&tab, &config, 800.0, 600.0, false, None, None);
+        assert!(corners
+            .iter()
+            .all(|c| (0.0..=800.0).contains(&c[0]) && (0.0..=600.0).contains(&c[1])));
+
+        tab.view.zoom = 2.5;
+        assert!((scale(&tab, 800.0, 600.0) - 2.5 * landscape).abs() < 1e-9);
+    }
+
+    #[test]
+    fn boundary_images_complete_the_cell() {
+        let config = Config::default();
+        // Corner, face center, edge center and body center.
+        let tab = cubic(&[
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0],
+            [0.5, 0.0, 0.0],
+            [0.5, 0.5, 0.5],
+        ]);
+        let (atoms, _, _) = calculate_scene(&tab, &config, 800.0, 600.0, false, None, None);
+        let visible = |i: usize| {
+            atoms
+                .iter()
+                .filter(|a| a.original_index == i && !a.is_coord_only)
+                .count()
+        };
+        assert_eq!(visible(0), 8);
+        assert_eq!(visible(1), 2);
+        assert_eq!(visible(2), 4);
+        assert_eq!(visible(3), 1);
+        // Exactly one instance of each site is the real atom.
+        for i in 0..4 {
+            let real = atoms
+                .iter()
+                .filter(|a| a.original_index == i && !a.is_ghost)
+                .count();
+            assert_eq!(real, 1);
+        }
+    }
+
+    #[test]
+    fn hidden_species_stay_in_the_scene_but_cannot_be_picked() {
+        let config = Config::default();
+        let mut tab = cubic(&[[0.0, 0.0, 0.0], [0.5, 0.5, 0.5]]);
+        tab.structure.as_mut().unwrap().atoms[1].element = "Cl".to_string();
+        let (shown, _, _) = calculate_scene(&tab, &config, 800.0, 600.0, false, None, None);
+
+        tab.style.hidden_elements.insert("Cl".to_string());
+        let (atoms, _, _) = calculate_scene(&tab, &config, 800.0, 600.0, false, None, None);
+        // Still there for coordination and polyhedra, with the same ids.
+        assert_eq!(atoms.len(), shown.len());
+        let ids = |atoms: &[RenderAtom]| atoms.iter().map(|a| a.unique_id).collect::<Vec<_>>();
+        assert_eq!(ids(&atoms), ids(&shown));
+        for atom in &atoms {
+            let drawn = !atom.is_hidden && !atom.is_coord_only;
+            assert_eq!(atom.is_hidden, atom.element == "Cl");
+            assert_eq!(atom.is_pickable(true), drawn);
+        }
+    }
+
+    #[test]
+    fn depth_range_fits_the_drawn_atoms_unless_fixed() {
+        let mut config = Config::default();
+        let tab = cubic(&[[0.0, 0.0, 0.0], [0.5, 0.5, 0.5]]);
+        let (atoms, _, bounds) = calculate_scene(&tab, &config, 800.0, 600.0, false, None, None);
+        let depths: Vec<f64> = atoms
+            .iter()
+            .filter(|a| !a.is_coord_only)
+            .map(|a| a.screen_pos[2])
+            .collect();
+        let front = depths.iter().copied().fold(f64::INFINITY, f64::min);
+        let back = depths.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let depth = bounds.depth;
+        assert!(depth.near < front && depth.far > back);
+        assert_eq!(depth.fraction(depth.near - 1.0), 0.0);
+        assert_eq!(depth.fraction(depth.far + 1.0), 1.0);
+
+        config.depth_range_auto = false;
+        config.depth_near = 5.0;
+        config.depth_far = -5.0;
+        let (_, _, fixed) = calculate_scene(&tab, &config, 800.0, 600.0, false, None, None);
+        assert_eq!((fixed.depth.near, fixed.depth.far), (-5.0, 5.0));
+        assert!((fixed.depth.fraction(0.0) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn replication_adds_unpickable_images_without_renumbering() {
+        let config = Config::default();
+        let mut tab = cubic(&[[0.5, 0.5, 0.5], [0.0, 0.0, 0.0]]);
+        let scene_atoms =
+            |tab: &TabState| calculate_scene(tab, &config, 800.0, 600.0, false, None, None).0;
+        let single = scene_atoms(&tab);
+
+        tab.view.replication = [2, 1, 3];
+        let block = scene_atoms(&tab);
+        let replicas: Vec<_> = block
+            .iter()
+            .filter(|a| a.is_replica && a.original_index == 0)
+            .collect();
+        assert_eq!(replicas.len(), 5);
+        assert!(replicas.iter().all(|a| !a.is_pickable(true) && !a.is_ghost));
+        assert!(replicas.iter().any(|a| a.cart_pos == [6.0, 2.0, 10.0]));
+        assert_eq!(block.iter().filter(|a| a.is_home()).count(), 2);
+        // The corner atom completes the far corners of the whole block.
+        let corners = block
+            .iter()
+            .filter(|a| a.original_index == 1 && !a.is_coord_only)
+            .count();
+        assert_eq!(corners, 3 * 2 * 4);
+        // Every instance of the single cell, ghosts included, keeps its id
+        // and still names the same place.
+        for atom in &single {
+            let same = block
+                .iter()
+                .find(|b| b.unique_id == atom.unique_id)
+                .unwrap();
+            assert_eq!(same.original_index, atom.original_index);
+            assert_eq!(same.cart_pos, atom.cart_pos);
+        }
+        let mut ids: Vec<usize> = block.iter().map(|a| a.unique_id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), block.len());
+    }
+}
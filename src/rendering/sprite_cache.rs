@@ -3,6 +3,7 @@
 // Multi-resolution, material-aware, memory-bounded
 // Designed to outperform VESTA/OVITO rendering systems
 
+use crate::config::Light;
 use gtk4::cairo::ImageSurface;
 use std::collections::HashMap;
 
@@ -102,35 +103,65 @@ impl SpriteCache {
 
     /// Generate intelligent cache key from rendering parameters
     ///
-    /// Key format: "element_sXXX_mXX_rXX_tXX"
+    /// Key format: "element_sXXX_mXX_rXX_tXX_lAAA_eEE_iIII"
     /// - element: Chemical symbol
     /// - sXXX: Size/scale (3 digits, 0-200)
     /// - mXX: Metallic (2 digits, 0-99)
     /// - rXX: Roughness (2 digits, 0-99)
     /// - tXX: Transmission (2 digits, 0-99)
+    /// - lAAA / eEE: Light azimuth / elevation (whole degrees)
+    /// - iIII: Light intensity (percent)
     ///
     /// # Examples
-    /// - "Fe_s042_m30_r40_t00" = Iron at 0.42 scale, 0.3 metallic, 0.4 roughness
-    /// - "O_s038_m00_r30_t00"  = Oxygen at 0.38 scale, 0.0 metallic, 0.3 roughness
+    /// - "Fe_s042_m30_r40_t00_l135_e70_i100" = Iron at 0.42 scale, 0.3 metallic,
+    ///   0.4 roughness, default light
+    /// - "O_s038_m00_r30_t00_l090_e45_i150"  = Oxygen lit from above at 45°, 1.5×
     ///
     /// # Design
     /// - Quantized to reduce cache fragmentation
     /// - Size precision: 0.01 (adequate for visual quality)
     /// - Material precision: 0.01 (human eye threshold)
+    /// - Light precision: 1° and 1%
     pub fn make_key(
         element: &str,
         scale: f64,
         metallic: f64,
         roughness: f64,
         transmission: f64,
+        light: &Light,
     ) -> String {
         format!(
-            "{}_s{:03}_m{:02}_r{:02}_t{:02}",
+            "{}_s{:03}_m{:02}_r{:02}_t{:02}_l{:03}_e{:02}_i{:03}",
             element,
             (scale * 100.0).round() as u32,
             (metallic * 100.0).round() as u32,
             (roughness * 100.0).round() as u32,
             (transmission * 100.0).round() as u32,
+            light.azimuth.rem_euclid(360.0).round() as u32 % 360,
+            light.elevation.round() as i32,
+            (light.intensity * 100.0).round() as u32,
+        )
+    }
+
+    /// Cache key for a sprite whose color comes from a per-atom override
+    /// rather than the element. Appends "_cRRGGBB" to the `make_key` format
+    /// so overridden atoms never collide with the element-colored sprite.
+    pub fn make_override_key(
+        element: &str,
+        rgb: (f64, f64, f64),
+        scale: f64,
+        metallic: f64,
+        roughness: f64,
+        transmission: f64,
+        light: &Light,
+    ) -> String {
+        let channel = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!(
+            "{}_c{:02x}{:02x}{:02x}",
+            Self::make_key(element, scale, metallic, roughness, transmission, light),
+            channel(rgb.0),
+            channel(rgb.1),
+            channel(rgb.2),
         )
     }
 
@@ -285,7 +316,7 @@ impl SpriteCache {
     /// Preloads sprites for most common elements at standard settings
     /// - Elements: H, C, N, O, F, Si, P, S, Cl, Fe, Cu, Zn
     /// - Scale: 0.4 (default)
-    /// - Material: Default (metallic=0.0, roughness=0.3)
+    /// - Material: Default (metallic=0.0, roughness=0.3), default light
     pub fn preload_common<F>(&mut self, mut create_fn: F)
     where
         F: FnMut(&str, f64, f64, f64, f64) -> ImageSurface,
@@ -303,7 +334,14 @@ impl SpriteCache {
         let transmission = 0.0;
 
         for element in &common_elements {
-            let key = Self::make_key(element, scale, metallic, roughness, transmission);
+            let key = Self::make_key(
+                element,
+                scale,
+                metallic,
+                roughness,
+                transmission,
+                &Light::default(),
+            );
             if !self.cache.contains_key(&key) {
                 let sprite = create_fn(element, scale, metallic, roughness, transmission);
                 let entry = CacheEntry::new(sprite);
@@ -424,8 +462,38 @@ mod tests {
 
     #[test]
     fn test_cache_key_generation() {
-        let key = SpriteCache::make_key("Fe", 0.42, 0.30, 0.45, 0.00);
-        assert_eq!(key, "Fe_s042_m30_r45_t00");
+        let key = SpriteCache::make_key("Fe", 0.42, 0.30, 0.45, 0.00, &Light::default());
+        assert_eq!(key, "Fe_s042_m30_r45_t00_l135_e70_i100");
+    }
+
+    #[test]
+    fn test_override_key_includes_color() {
+        let light = Light::default();
+        let key =
+            SpriteCache::make_override_key("Fe", (1.0, 0.5, 0.0), 0.42, 0.30, 0.45, 0.00, &light);
+        assert_eq!(key, "Fe_s042_m30_r45_t00_l135_e70_i100_cff8000");
+        assert_ne!(
+            key,
+            SpriteCache::make_key("Fe", 0.42, 0.30, 0.45, 0.00, &light)
+        );
+    }
+
+    #[test]
+    fn test_key_follows_light() {
+        let key = |azimuth, elevation, intensity| {
+            let light = Light {
+                azimuth,
+                elevation,
+                intensity,
+            };
+            SpriteCache::make_key("O", 0.4, 0.0, 0.3, 0.0, &light)
+        };
+        let base = key(135.0, 70.0, 1.0);
+        assert_ne!(base, key(45.0, 70.0, 1.0));
+        assert_ne!(base, key(135.0, 30.0, 1.0));
+        assert_ne!(base, key(135.0, 70.0, 1.5));
+        // Azimuth wraps: -225° and 135° are the same light.
+        assert_eq!(base, key(-225.0, 70.0, 1.0));
     }
 
     #[test]
@@ -0,0 +1,347 @@
+// src/rendering/threejs.rs
+//
+// Interactive HTML export: one self-contained page that rebuilds the tab's
+// scene in three.js (atoms as instanced spheres, bonds as instanced
+// cylinders, the cell as line segments) and slowly spins it until the
+// reader grabs it. The structure is embedded as JSON, so the file can be
+// shared on its own, but three.js itself is loaded from a CDN: opening the
+// page needs network access. Offline, the page says so instead of the
+// viewer.
+//
+// Coordinates are Cartesian Å turned by the current view rotation and
+// flipped into three.js axes (y up, camera looking down −z), so the page
+// opens on the orientation shown in the app.
+
+use super::bonds::{effective_tolerance, find_bonds};
+use super::export::ExportSettings;
+use super::painter::{atom_alpha, atom_rgb};
+use super::scene;
+use crate::config::Config;
+use crate::model::elements::get_covalent_radius;
+use crate::state::TabState;
+use nalgebra::Vector3;
+use serde_json::{json, Value};
+
+/// three.js release the page imports; pinned so old exports keep working.
+const THREE_VERSION: &str = "0.160.0";
+
+/// Where the page loads three.js from when it is opened.
+const THREE_CDN: &str = "cdn.jsdelivr.net";
+
+/// Scene description consumed by the page script: `atoms`, `bonds`, the
+/// `cell` edges and the material and lighting of `tab.style`.
+pub fn scene_json(tab: &TabState, config: &Config, settings: &ExportSettings) -> Value {
+    let (atoms, _, _) = scene::calculate_scene(tab, config, 0.0, 0.0, true, Some(1.0), None);
+    let rot = tab.view.rotation_matrix();
+    let view = |p: [f64; 3]| {
+        let r = rot * Vector3::from(p);
+        [r.x, -r.y, -r.z]
+    };
+    let show_ghosts = tab.view.show_full_unit_cell;
+    let drawn =
+        |a: &scene::RenderAtom| !a.is_coord_only && !a.is_hidden && (!a.is_ghost || show_ghosts);
+
+    let atom_list: Vec<Value> = atoms
+        .iter()
+        .filter(|a| drawn(a))
+        .map(|a| {
+            let (r, g, b) = atom_rgb(tab, a, config.color_scheme);
+            json!({
+                "element": a.element,
+                "position": view(a.cart_pos),
+                "radius": get_covalent_radius(&a.element)
+                    * tab.style.atom_scale
+                    * tab.override_radius_scale(a.original_index),
+                "color": [r, g, b],
+                "opacity": atom_alpha(tab, a),
+            })
+        })
+        .collect();
+
+    let mut bonds = Vec::new();
+    if tab.view.show_bonds {
+        for (i, j, _) in find_bonds(tab, &atoms, effective_tolerance(tab), drawn) {
+            let (a1, a2) = (&atoms[i], &atoms[j]);
+            let (p1, p2) = (view(a1.cart_pos), view(a2.cart_pos));
+            // Bonds reaching into a replica cell fade with it, as on screen.
+            let opacity = if a1.is_replica || a2.is_replica {
+                atom_alpha(tab, a1).min(atom_alpha(tab, a2))
+            } else {
+                1.0
+            };
+            let segment = |start: [f64; 3], end: [f64; 3], rgb: (f64, f64, f64)| {
+                let color = [rgb.0, rgb.1, rgb.2];
+                json!({ "start": start, "end": end, "color": color, "opacity": opacity })
+            };
+            if tab.style.bond_split_color {
+                let mid = [0, 1, 2].map(|k| 0.5 * (p1[k] + p2[k]));
+                bonds.push(segment(p1, mid, atom_rgb(tab, a1, config.color_scheme)));
+                bonds.push(segment(mid, p2, atom_rgb(tab, a2, config.color_scheme)));
+            } else {
+                bonds.push(segment(p1, p2, tab.style.bond_color));
+            }
+        }
+    }
+
+    let mut cell = Vec::new();
+    if let Some(s) = tab.structure.as_ref().filter(|s| s.is_periodic) {
+        if settings.include_unit_cell {
+            let corner = |f: [f64; 3]| {
+                view([0, 1, 2].map(|k| (0..3).map(|i| f[i] * s.lattice[i][k]).sum::<f64>()))
+            };
+            // Each edge runs from a corner to its neighbour along one axis.
+            for axis in 0..3 {
+                for u in 0..2 {
+                    for v in 0..2 {
+                        let mut start = [0.0; 3];
+                        start[(axis + 1) % 3] = u as f64;
+                        start[(axis + 2) % 3] = v as f64;
+                        let mut end = start;
+                        end[axis] = 1.0;
+                        cell.push(json!([corner(start), corner(end)]));
+                    }
+                }
+            }
+        }
+    }
+
+    let (br, bg, bb) = tab.style.background_color;
+    let (cr, cg, cb) = tab.style.cell_line_color;
+    json!({
+        "title": tab.structure.as_ref().map(|s| s.formula.as_str()).unwrap_or(""),
+        "atoms": atom_list,
+        "bonds": bonds,
+        "bond_radius": tab.style.bond_radius,
+        "cell": cell,
+        "cell_color": [cr, cg, cb],
+        "background": [br, bg, bb],
+        "metallic": tab.style.metallic,
+        "roughness": tab.style.roughness,
+        "light": tab.style.light.intensity,
+    })
+}
+
+/// The complete page for `data` (see [`scene_json`]).
+pub fn html_page(data: &Value) -> String {
+    // A literal "</" inside the JSON would close the script element early.
+    let json = data.to_string().replace("</", "<\\/");
+    let title = match data["title"].as_str().unwrap_or("") {
+        "" => "Structure".to_string(),
+        t => t
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+    };
+    PAGE_TEMPLATE
+        .replace("{{TITLE}}", &title)
+        .replace("{{THREE_VERSION}}", THREE_VERSION)
+        .replace("{{THREE_CDN}}", THREE_CDN)
+        .replace("{{DATA}}", &json)
+}
+
+/// Write the interactive page for `tab` to `path`.
+pub fn write_html(
+    tab: &TabState,
+    config: &Config,
+    path: &str,
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    let data = scene_json(tab, config, settings);
+    let atoms = data["atoms"].as_array().map_or(0, Vec::len);
+    let bonds = data["bonds"].as_array().map_or(0, Vec::len);
+    std::fs::write(path, html_page(&data)).map_err(|e| format!("Failed to create file: {}", e))?;
+    Ok(format!(
+        "Exported interactive HTML to: {} ({} atoms, {} bond segments; \
+         viewing it loads three.js from {})",
+        path, atoms, bonds, THREE_CDN
+    ))
+}
+
+const PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{TITLE}}</title>
+<style>
+  html, body { margin: 0; height: 100%; overflow: hidden; }
+  #title { position: absolute; top: 8px; left: 12px; font: 14px sans-serif; opacity: 0.7; }
+  #offline { margin: 48px 12px; font: 14px sans-serif; }
+</style>
+<script type="importmap">
+{ "imports": {
+  "three": "https://{{THREE_CDN}}/npm/three@{{THREE_VERSION}}/build/three.module.js",
+  "three/addons/": "https://{{THREE_CDN}}/npm/three@{{THREE_VERSION}}/examples/jsm/"
+} }
+</script>
+</head>
+<body>
+<div id="title">{{TITLE}}</div>
+<p id="offline">This viewer loads three.js from {{THREE_CDN}} and needs an internet connection.</p>
+<script type="application/json" id="structure">{{DATA}}</script>
+<script type="module">
+import * as THREE from 'three';
+import { OrbitControls } from 'three/addons/controls/OrbitControls.js';
+
+document.getElementById('offline').remove();
+const data = JSON.parse(document.getElementById('structure').textContent);
+const srgb = (c) => new THREE.Color().setRGB(c[0], c[1], c[2], THREE.SRGBColorSpace);
+
+const scene = new THREE.Scene();
+scene.background = srgb(data.background);
+const luminance = data.background[0] * 0.3 + data.background[1] * 0.59 + data.background[2] * 0.11;
+document.getElementById('title').style.color = luminance > 0.5 ? '#000' : '#fff';
+
+const renderer = new THREE.WebGLRenderer({ antialias: true });
+renderer.setPixelRatio(window.devicePixelRatio);
+renderer.setSize(window.innerWidth, window.innerHeight);
+document.body.appendChild(renderer.domElement);
+
+const camera = new THREE.PerspectiveCamera(30, window.innerWidth / window.innerHeight, 0.1, 10000);
+scene.add(camera);
+scene.add(new THREE.AmbientLight(0xffffff, 0.5));
+const sun = new THREE.DirectionalLight(0xffffff, 2.0 * data.light);
+sun.position.set(-1, 1, 2);
+camera.add(sun);
+
+const root = new THREE.Group();
+scene.add(root);
+
+// One instanced mesh per opacity, so opaque and faded instances sort apart.
+function addInstanced(geometry, items, place) {
+  const matrix = new THREE.Matrix4();
+  for (const opacity of new Set(items.map((item) => item.opacity))) {
+    const group = items.filter((item) => item.opacity === opacity);
+    const material = new THREE.MeshStandardMaterial({
+      metalness: data.metallic,
+      roughness: Math.max(data.roughness, 0.05),
+      transparent: opacity < 1,
+      opacity,
+      depthWrite: opacity >= 1,
+    });
+    const mesh = new THREE.InstancedMesh(geometry, material, group.length);
+    group.forEach((item, k) => {
+      place(item, matrix);
+      mesh.setMatrixAt(k, matrix);
+      mesh.setColorAt(k, srgb(item.color));
+    });
+    root.add(mesh);
+  }
+}
+
+const none = new THREE.Quaternion();
+addInstanced(new THREE.SphereGeometry(1, 32, 16), data.atoms, (atom, matrix) => {
+  const r = atom.radius;
+  matrix.compose(new THREE.Vector3().fromArray(atom.position), none, new THREE.Vector3(r, r, r));
+});
+
+const up = new THREE.Vector3(0, 1, 0);
+addInstanced(new THREE.CylinderGeometry(1, 1, 1, 20, 1, true), data.bonds, (bond, matrix) => {
+  const start = new THREE.Vector3().fromArray(bond.start);
+  const axis = new THREE.Vector3().fromArray(bond.end).sub(start);
+  const turn = new THREE.Quaternion().setFromUnitVectors(up, axis.clone().normalize());
+  const middle = start.addScaledVector(axis, 0.5);
+  const r = data.bond_radius;
+  matrix.compose(middle, turn, new THREE.Vector3(r, axis.length(), r));
+});
+
+if (data.cell.length > 0) {
+  const geometry = new THREE.BufferGeometry();
+  geometry.setAttribute('position', new THREE.Float32BufferAttribute(data.cell.flat(2), 3));
+  root.add(new THREE.LineSegments(geometry, new THREE.LineBasicMaterial({ color: srgb(data.cell_color) })));
+}
+
+const box = new THREE.Box3().setFromObject(root);
+root.position.sub(box.getCenter(new THREE.Vector3()));
+const size = Math.max(box.getSize(new THREE.Vector3()).length(), 1);
+camera.position.set(0, 0, size * 1.8);
+camera.far = size * 20;
+camera.updateProjectionMatrix();
+
+const controls = new OrbitControls(camera, renderer.domElement);
+controls.enableDamping = true;
+controls.autoRotate = true;
+controls.autoRotateSpeed = 1.5;
+renderer.domElement.addEventListener('pointerdown', () => { controls.autoRotate = false; });
+
+window.addEventListener('resize', () => {
+  camera.aspect = window.innerWidth / window.innerHeight;
+  camera.updateProjectionMatrix();
+  renderer.setSize(window.innerWidth, window.innerHeight);
+});
+renderer.setAnimationLoop(() => {
+  controls.update();
+  renderer.render(scene, camera);
+});
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Atom, Structure};
+
+    fn dimer_tab() -> TabState {
+        let mut tab = TabState::new(&Config::default());
+        let atom = |i: usize, x: f64| Atom {
+            element: "C".to_string(),
+            position: [x, 5.0, 5.0],
+            original_index: i,
+            oxidation: None,
+            occupancy: 1.0,
+            adp: None,
+        };
+        tab.structure = Some(Structure {
+            lattice: [[10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [0.0, 0.0, 10.0]],
+            atoms: vec![atom(0, 4.0), atom(1, 5.4)],
+            formula: "C2".to_string(),
+            is_periodic: true,
+        });
+        tab.view.show_bonds = true;
+        tab.style.bond_split_color = true;
+        tab
+    }
+
+    #[test]
+    fn scene_holds_atoms_bonds_and_cell_edges() {
+        let tab = dimer_tab();
+        let data = scene_json(&tab, &Config::default(), &ExportSettings::default());
+        assert_eq!(data["atoms"].as_array().unwrap().len(), 2);
+        // One C–C bond, split into two halves.
+        assert_eq!(data["bonds"].as_array().unwrap().len(), 2);
+        assert_eq!(data["cell"].as_array().unwrap().len(), 12);
+        let r = data["atoms"][0]["radius"].as_f64().unwrap();
+        assert!((r - get_covalent_radius("C") * tab.style.atom_scale).abs() < 1e-12);
+
+        let no_cell = ExportSettings {
+            include_unit_cell: false,
+            ..ExportSettings::default()
+        };
+        let data = scene_json(&tab, &Config::default(), &no_cell);
+        assert!(data["cell"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn page_embeds_the_scene_as_parseable_json() {
+        let mut tab = dimer_tab();
+        tab.structure.as_mut().unwrap().formula = "</script><b>".to_string();
+        let data = scene_json(&tab, &Config::default(), &ExportSettings::default());
+        let page = html_page(&data);
+
+        assert!(!page.contains("</script><b>"));
+        assert!(page.contains("<title>&lt;/script&gt;&lt;b&gt;</title>"));
+        assert!(page.contains(&format!(
+            "https://{}/npm/three@{}",
+            THREE_CDN, THREE_VERSION
+        )));
+        assert!(!page.contains("{{"));
+
+        let open = r#"<script type="application/json" id="structure">"#;
+        let start = page.find(open).unwrap() + open.len();
+        let end = start + page[start..].find("</script>").unwrap();
+        let embedded: Value = serde_json::from_str(&page[start..end]).unwrap();
+        assert_eq!(embedded, data);
+    }
+}
@@ -1,12 +1,39 @@
 // src/state.rs
 // Updated to use Config defaults
 
-use crate::config::{Config, RenderStyle};
+use crate::config::{Config, RenderStyle, RotationCenter};
+use crate::model::elements::{get_atom_cov, get_element_color, ColorScheme};
 use crate::model::miller::MillerPlane;
 use crate::model::structure::Structure;
 use crate::physics::analysis::{kpath::KPathResult, voids::VoidResult};
-use nalgebra::{Rotation3, UnitQuaternion, Vector3};
-use std::collections::HashMap;
+use crate::rendering::bonds::BondCache;
+use crate::rendering::scene::DrawnFrame;
+use nalgebra::{Matrix3, Rotation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+/// Stereo 3D presentation of the main structure view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StereoMode {
+    #[default]
+    Off,
+    /// Red (left eye) / cyan (right eye) composite for anaglyph glasses.
+    Anaglyph,
+    /// Left and right eye images next to each other (parallel viewing).
+    SideBySide,
+}
+
+/// Which frame the orientation gizmo in the corner shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AxesMode {
+    /// Cartesian x/y/z.
+    #[default]
+    Cartesian,
+    /// The lattice vectors a/b/c, drawn to relative length and labelled
+    /// with their lengths. Falls back to x/y/z for molecules.
+    Lattice,
+}
 
 #[derive(Debug, Clone)]
 pub struct ViewState {
@@ -16,6 +43,8 @@ pub struct ViewState {
     /// across many incremental drags.
     pub rotation: UnitQuaternion<f64>,
     pub zoom: f64,
+    /// Screen-plane offset of the view center in Å (rotated frame), applied
+    /// on top of the automatic fit. Zero keeps the structure centered.
     pub pan_x: f64,
     pub pan_y: f64,
     pub show_bonds: bool,
@@ -23,6 +52,35 @@ pub struct ViewState {
     pub bond_cutoff: f64,
     pub scale: f64,
     pub show_full_unit_cell: bool,
+    /// Draw the boundary images added by `show_full_unit_cell` faintly, so
+    /// the completed box reads apart from the atoms the file lists.
+    pub faint_ghosts: bool,
+    /// Periodic images drawn as a translucent nx×ny×nz block around the
+    /// cell, for context only: the structure, picking and analyses still
+    /// see one cell. `[1, 1, 1]` draws the cell alone.
+    pub replication: [u32; 3],
+    pub stereo: StereoMode,
+    /// Total angle between the two stereo eyes, in degrees.
+    pub eye_separation_deg: f64,
+    /// Yaw applied on top of `rotation` while one stereo eye is rendered.
+    /// Zero outside the stereo draw pass, so picking and export see the
+    /// plain camera.
+    pub eye_offset_deg: f64,
+    /// Draw pinned measurements. Hiding keeps them for later.
+    pub show_measurements: bool,
+    /// Frame shown by the orientation gizmo.
+    pub axes_mode: AxesMode,
+    /// Draw a length bar in the bottom-right corner (also in exports).
+    pub show_scale_bar: bool,
+    /// Requested scale bar length in Å.
+    pub scale_bar_length: f64,
+    /// Element key (color, symbol, count) in the top-right corner, also
+    /// drawn in exports.
+    pub show_legend: bool,
+    /// Print each atom's BVS over its sphere in bond-valence color mode.
+    pub show_bvs_values: bool,
+    /// Rotation pivot for this tab; `None` follows `config.rotation_mode`.
+    pub rotation_center: Option<RotationCenter>,
 }
 
 impl ViewState {
@@ -37,12 +95,30 @@ impl ViewState {
             bond_cutoff: config.default_bond_tolerance,
             scale: 30.0,
             show_full_unit_cell: config.default_show_full_cell,
+            faint_ghosts: config.show_ghost_atoms,
+            replication: [1, 1, 1],
+            stereo: StereoMode::Off,
+            eye_separation_deg: 4.0,
+            eye_offset_deg: 0.0,
+            show_measurements: true,
+            axes_mode: AxesMode::Cartesian,
+            show_scale_bar: false,
+            scale_bar_length: 5.0,
+            show_legend: false,
+            show_bvs_values: false,
+            rotation_center: None,
         }
     }
 
-    /// Rotation matrix the renderer applies to world-space points.
+    /// Rotation matrix the renderer applies to world-space points, including
+    /// the per-eye yaw during a stereo pass.
     pub fn rotation_matrix(&self) -> Rotation3<f64> {
-        self.rotation.to_rotation_matrix()
+        if self.eye_offset_deg == 0.0 {
+            return self.rotation.to_rotation_matrix();
+        }
+        let eye =
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.eye_offset_deg.to_radians());
+        (eye * self.rotation).to_rotation_matrix()
     }
 
     /// Set absolute orientation from XYZ-intrinsic Euler angles in degrees
@@ -63,19 +139,108 @@ impl ViewState {
         (rx.to_degrees(), ry.to_degrees(), rz.to_degrees())
     }
 
-    /// Apply a screen-space rotation increment (trackball). `yaw_deg` rotates
-    /// around the screen's vertical axis (horizontal drag), `pitch_deg` around
-    /// the screen's horizontal axis (vertical drag). Left-multiplied so the
-    /// delta is interpreted in camera space regardless of current orientation.
-    pub fn apply_screen_rotation_deg(&mut self, yaw_deg: f64, pitch_deg: f64) {
-        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw_deg.to_radians());
-        let pitch = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), pitch_deg.to_radians());
-        self.rotation = yaw * pitch * self.rotation;
+    /// Arcball rotation for a drag from `p0` to `p1` (widget pixels) on a
+    /// `width` × `height` canvas. Both points are projected onto a virtual
+    /// sphere centred on the canvas; the rotation is the great arc between
+    /// them, so the atom under the cursor follows the pointer and circling
+    /// near the rim rolls the view about the screen normal. Left-multiplied
+    /// so the delta is interpreted in camera space.
+    pub fn apply_trackball(&mut self, p0: (f64, f64), p1: (f64, f64), width: f64, height: f64) {
+        self.rotation = trackball_rotation(p0, p1, width, height) * self.rotation;
+    }
+
+    /// Set `zoom`, clamped to `ZOOM_MIN..=ZOOM_MAX`. Non-finite input is
+    /// ignored. Every zoom path (slider, wheel, pinch) goes through here.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        if zoom.is_finite() {
+            self.zoom = zoom.clamp(ZOOM_MIN, ZOOM_MAX);
+        }
+    }
+
+    /// Multiply `zoom` by `factor` while keeping the model point under
+    /// `cursor` (widget pixels) fixed on screen. `scale` is the pixel-per-Å
+    /// scale of the frame the cursor was read against; the scene's scale is
+    /// proportional to `zoom`, so the new frame's scale is `scale` times the
+    /// factor actually applied after clamping.
+    pub fn zoom_about(&mut self, factor: f64, cursor: (f64, f64), size: (f64, f64), scale: f64) {
+        if scale <= 0.0 || factor <= 0.0 || self.zoom <= 0.0 {
+            return;
+        }
+        let old = self.zoom;
+        self.set_zoom(old * factor);
+        let applied = self.zoom / old;
+        let shift = 1.0 / scale - 1.0 / (scale * applied);
+        self.pan_x += (cursor.0 - size.0 * 0.5) * shift;
+        self.pan_y += (cursor.1 - size.1 * 0.5) * shift;
     }
 
     pub fn reset_rotation(&mut self) {
         self.rotation = UnitQuaternion::identity();
     }
+
+    /// Orientation that looks down `dir` — mapped onto the screen depth axis
+    /// (+z), the same convention the fixed "View Along" presets used — with
+    /// `right` projected onto screen +x. Works for any cell shape because
+    /// both vectors are taken straight from the lattice. Returns `None` if
+    /// `dir` is zero or `right` is parallel to it.
+    pub fn orientation_along(dir: [f64; 3], right: [f64; 3]) -> Option<UnitQuaternion<f64>> {
+        let z = Vector3::from(dir).try_normalize(1e-9)?;
+        let r = Vector3::from(right);
+        let x = (r - z * r.dot(&z)).try_normalize(1e-9)?;
+        let y = z.cross(&x);
+        let m = Matrix3::from_rows(&[x.transpose(), y.transpose(), z.transpose()]);
+        Some(UnitQuaternion::from_rotation_matrix(
+            &Rotation3::from_matrix_unchecked(m),
+        ))
+    }
+}
+
+/// Project a widget point onto the arcball. Screen axes are the renderer's
+/// (x right, y down, +z into the screen), so the visible hemisphere faces
+/// -z. Points beyond the sphere fall on Holroyd's hyperbolic sheet, which
+/// keeps the mapping continuous when the cursor leaves the ball.
+fn trackball_vector(p: (f64, f64), width: f64, height: f64) -> Vector3<f64> {
+    let radius = (width.min(height) * 0.5).max(1.0);
+    let x = (p.0 - width * 0.5) / radius;
+    let y = (p.1 - height * 0.5) / radius;
+    let d2 = x * x + y * y;
+    let z = if d2 <= 0.5 {
+        (1.0 - d2).sqrt()
+    } else {
+        0.5 / d2.sqrt()
+    };
+    Vector3::new(x, y, -z).normalize()
+}
+
+/// Zoom range shared by the sidebar slider and the interactive gestures.
+pub const ZOOM_MIN: f64 = 0.1;
+pub const ZOOM_MAX: f64 = 5.0;
+
+/// Zoom change per scroll step (one wheel notch is `dy = ±1`).
+const SCROLL_ZOOM_RATE: f64 = 0.1;
+
+/// Zoom factor for a scroll of `dy` (positive = down = zoom out). The
+/// response is exponential, so smooth-scroll deltas compose to the same
+/// total as one notch; a burst of fast events is capped so one event never
+/// more than doubles or halves the zoom.
+pub fn scroll_zoom_factor(dy: f64) -> f64 {
+    if !dy.is_finite() {
+        return 1.0;
+    }
+    let max_step = std::f64::consts::LN_2 / SCROLL_ZOOM_RATE;
+    (-SCROLL_ZOOM_RATE * dy.clamp(-max_step, max_step)).exp()
+}
+
+/// Great-arc rotation carrying the arcball point under `p0` to the one under `p1`.
+pub fn trackball_rotation(
+    p0: (f64, f64),
+    p1: (f64, f64),
+    width: f64,
+    height: f64,
+) -> UnitQuaternion<f64> {
+    let v0 = trackball_vector(p0, width, height);
+    let v1 = trackball_vector(p1, width, height);
+    UnitQuaternion::rotation_between(&v0, &v1).unwrap_or_else(UnitQuaternion::identity)
 }
 
 impl Default for ViewState {
@@ -90,6 +255,18 @@ impl Default for ViewState {
             bond_cutoff: 1.15,
             scale: 30.0,
             show_full_unit_cell: true,
+            faint_ghosts: true,
+            replication: [1, 1, 1],
+            stereo: StereoMode::Off,
+            eye_separation_deg: 4.0,
+            eye_offset_deg: 0.0,
+            show_measurements: true,
+            axes_mode: AxesMode::Cartesian,
+            show_scale_bar: false,
+            scale_bar_length: 5.0,
+            show_legend: false,
+            show_bvs_values: false,
+            rotation_center: None,
         }
     }
 }
@@ -107,10 +284,20 @@ pub struct SelectedAtom {
     pub element: String,
 }
 
+/// A distance (2 atoms), angle (3, vertex in the middle) or dihedral (4)
+/// pinned to specific atom instances. Only the instances are stored: value
+/// and label position are recomputed from each frame's scene, so the label
+/// follows rotation and zoom and lands in exports.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub atoms: Vec<SelectedAtom>,
+}
+
 /// Per-atom render override. Purely cosmetic — never written to any IO format.
-/// Keyed by `Atom` index in `Structure.atoms`. Lives only in the session;
-/// reload from file resets it. Use the Tools → Atom Instances dialog to edit.
-#[derive(Debug, Clone, Default)]
+/// Keyed by `Atom` index in `Structure.atoms`. Lives only in the session
+/// (and its autosave snapshot); reload from file resets it. Use the
+/// Tools → Atom Instances dialog to edit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AtomOverride {
     /// Display label shown in tooltips/dialog (e.g. "Fe1", "Fe_oct"). Element
     /// identity stays in `Atom.element`; this is just a tag for the user.
@@ -119,24 +306,93 @@ pub struct AtomOverride {
     pub color: Option<(f64, f64, f64)>,
     /// Multiplier on the element's covalent radius. None ⇒ 1.0.
     pub radius_scale: Option<f64>,
+    /// Hidden atoms are dropped from the scene entirely: not drawn, not
+    /// pickable, and not used as bond/polyhedra neighbors.
+    pub hidden: bool,
 }
 
 impl AtomOverride {
     pub fn is_empty(&self) -> bool {
-        self.display_label.is_none() && self.color.is_none() && self.radius_scale.is_none()
+        self.display_label.is_none()
+            && self.color.is_none()
+            && self.radius_scale.is_none()
+            && !self.hidden
     }
 }
 
+/// Bulk selection edits exposed as menu actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionOp {
+    All,
+    None,
+    Invert,
+}
+
 #[derive(Default)]
 pub struct InteractionState {
     pub selected: HashMap<usize, SelectedAtom>,
     pub undo_stack: Vec<Structure>,
     pub is_shift_pressed: bool,
     pub selection_box: Option<((f64, f64), (f64, f64))>,
+    /// Widget position where the current drag started.
+    pub drag_start: (f64, f64),
     /// Cumulative drag offset reported on the previous drag-update event.
-    /// Reset to (0, 0) on drag-begin. Used to derive per-frame deltas for
-    /// trackball rotation, since GTK's GestureDrag reports cumulative offset.
+    /// Reset to (0, 0) on drag-begin. Together with `drag_start` it gives the
+    /// previous cursor position for the arcball, since GTK's GestureDrag
+    /// reports cumulative offset.
     pub drag_prev_offset: (f64, f64),
+    /// Last pointer position over the canvas, for zoom-to-cursor. `None`
+    /// while the pointer is outside, in which case zoom is about the centre.
+    pub pointer_pos: Option<(f64, f64)>,
+    /// Pan and pixel scale captured when a middle-button pan drag starts.
+    pub pan_drag_origin: Option<((f64, f64), f64)>,
+    /// Gesture scale seen at the previous pinch update, so each update
+    /// applies only its own step. `None` outside a pinch.
+    pub pinch_scale: Option<f64>,
+    /// Structure indices matched by the sidebar atom search. Drawn as a ring
+    /// around every instance; cleared by an empty query.
+    pub highlighted: HashSet<usize>,
+    /// Set on alternate ticks while the highlight ring flashes after a search.
+    pub highlight_dimmed: bool,
+    pub measurements: Vec<Measurement>,
+    /// Clicks pick atoms for a measurement instead of selecting them
+    /// (Tools ▸ Measure Mode, or M over the canvas).
+    pub measure_mode: bool,
+    /// Instances picked in measure mode, in click order: a distance once
+    /// there are two, the angle at the second once there are three.
+    pub measure_picks: Vec<SelectedAtom>,
+    /// `unique_id` of the instance under the pointer, ringed on screen.
+    pub hovered: Option<usize>,
+}
+
+impl InteractionState {
+    /// Apply `op` against the currently pickable instances and return the
+    /// resulting selection size. `pickable` must come from the same scene pass
+    /// the user is looking at, so `unique_id`s line up with what is on screen.
+    pub fn apply_selection_op(
+        &mut self,
+        op: SelectionOp,
+        pickable: impl IntoIterator<Item = SelectedAtom>,
+    ) -> usize {
+        match op {
+            SelectionOp::None => self.selected.clear(),
+            SelectionOp::All => {
+                for atom in pickable {
+                    self.selected.entry(atom.unique_id).or_insert(atom);
+                }
+            }
+            SelectionOp::Invert => {
+                let mut inverted = HashMap::new();
+                for atom in pickable {
+                    if !self.selected.contains_key(&atom.unique_id) {
+                        inverted.insert(atom.unique_id, atom);
+                    }
+                }
+                self.selected = inverted;
+            }
+        }
+        self.selected.len()
+    }
 }
 
 pub struct TabState {
@@ -150,10 +406,63 @@ pub struct TabState {
     pub kpath_result: Option<KPathResult>,
     pub void_result: Option<VoidResult>,
     pub bvs_cache: Vec<f64>,
+    /// Expected |V| per atom behind `bvs_cache`, from the charge-balanced
+    /// assignment; the BVS color mode measures each deviation against it.
+    pub bvs_expected: Vec<f64>,
     pub bvs_cache_valid: bool,
+    /// Oxidation states last assigned for `bvs_cache`, keyed like
+    /// `structure.atoms`; the assignment is logged when it changes.
+    pub oxidation_assignment: HashMap<usize, i32>,
+    /// Displacement of each atom in the last structure comparison as a
+    /// fraction of the largest one, indexed like `structure.atoms`; drives
+    /// `ColorMode::Displacement`. Every structure edit clears it.
+    pub displacement: Vec<f64>,
     /// Per-atom cosmetic overrides keyed by index into `structure.atoms`.
     /// Indices that aren't present here render with element defaults.
     pub overrides: HashMap<usize, AtomOverride>,
+    /// Maximum bond length (Å) per element pair, keyed by [`bond_pair_key`].
+    /// Pairs not listed use the covalent-radius sum × `view.bond_cutoff`.
+    pub bond_pair_cutoffs: HashMap<(String, String), f64>,
+    /// Element pairs forced bonded or unbonded after the distance test,
+    /// keyed by [`bond_pair_key`]. Pairs not listed follow the distance test.
+    pub bond_pair_rules: HashMap<(String, String), BondRule>,
+    /// Every frame of a multi-frame file (an XYZ trajectory), atoms and
+    /// lattice only; empty for single-structure files. `structure` holds a
+    /// copy of `frames[current_frame]`; edits are not written back.
+    pub frames: Vec<Structure>,
+    pub current_frame: usize,
+    /// The structure was edited since it was loaded or last saved.
+    pub modified: bool,
+    /// Memoized bond search for the painter; self-invalidating, see
+    /// [`BondCache`].
+    pub bond_cache: RefCell<BondCache>,
+    /// What the canvas last showed; `None` while stereo views are drawn.
+    pub drawn_frame: RefCell<Option<DrawnFrame>>,
+    /// The last frame drew fewer labels than atoms because of
+    /// `MAX_ATOM_LABELS`; the warning is logged when this turns on.
+    pub labels_culled: Cell<bool>,
+}
+
+/// How far a [`BondRule::Always`] pair may stretch past its usual limit.
+pub const ALWAYS_BOND_STRETCH: f64 = 1.4;
+
+/// Per element-pair override of the distance heuristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BondRule {
+    /// Bonded out to [`ALWAYS_BOND_STRETCH`] × the pair's limit, for
+    /// contacts the heuristic misses (metal–metal in clusters).
+    Always,
+    /// Never bonded, however short (cation–cation contacts).
+    Never,
+}
+
+/// Order-independent key for an element pair: `("O", "Si")` for Si–O and O–Si.
+pub fn bond_pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
 }
 
 impl TabState {
@@ -165,12 +474,52 @@ impl TabState {
             file_name: String::from("Untitled"),
             view: ViewState::from_config(global_config),
             interaction: InteractionState::default(),
-            style: global_config.style.create_session_copy(),
+            style: global_config.new_tab_style(),
             kpath_result: None,
             void_result: None,
             bvs_cache: Vec::new(),
+            bvs_expected: Vec::new(),
             bvs_cache_valid: false,
+            oxidation_assignment: HashMap::new(),
+            displacement: Vec::new(),
             overrides: HashMap::new(),
+            bond_pair_cutoffs: HashMap::new(),
+            bond_pair_rules: HashMap::new(),
+            frames: Vec::new(),
+            current_frame: 0,
+            modified: false,
+            bond_cache: RefCell::new(BondCache::default()),
+            drawn_frame: RefCell::new(None),
+            labels_culled: Cell::new(false),
+        }
+    }
+
+    /// Put the tab's display settings back to the defaults in `config`, as
+    /// a newly opened tab would get them: the whole style and the view
+    /// toggles. The structure, orientation, zoom, pan, rotation center and
+    /// replication stay, as do per-atom overrides and per-pair bond cutoffs.
+    pub fn reset_to_defaults(&mut self, config: &Config) {
+        self.style = config.new_tab_style();
+        let view = &self.view;
+        self.view = ViewState {
+            rotation: view.rotation,
+            zoom: view.zoom,
+            pan_x: view.pan_x,
+            pan_y: view.pan_y,
+            scale: view.scale,
+            replication: view.replication,
+            rotation_center: view.rotation_center,
+            ..ViewState::from_config(config)
+        };
+    }
+
+    /// Notebook tab label: the file name, prefixed with "• " while the
+    /// structure has unsaved edits.
+    pub fn title(&self) -> String {
+        if self.modified {
+            format!("• {}", self.file_name)
+        } else {
+            self.file_name.clone()
         }
     }
 
@@ -195,16 +544,245 @@ impl TabState {
             .and_then(|o| o.display_label.as_deref())
     }
 
+    /// Whether atom at `index` has been hidden via an override.
+    pub fn is_hidden(&self, index: usize) -> bool {
+        self.overrides.get(&index).is_some_and(|o| o.hidden)
+    }
+
+    /// Structure indices behind the current selection. Ghost copies share an
+    /// `original_index`, so the result is deduplicated and sorted.
+    pub fn selected_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .interaction
+            .selected
+            .values()
+            .map(|s| s.original_index)
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Set a color override on every selected atom. Returns the atom count.
+    pub fn recolor_selected(&mut self, rgb: (f64, f64, f64)) -> usize {
+        let indices = self.selected_indices();
+        for &idx in &indices {
+            self.overrides.entry(idx).or_default().color = Some(rgb);
+        }
+        indices.len()
+    }
+
+    /// Pin the current selection as a measurement when it holds 2–4 atoms,
+    /// ordered by `unique_id` like the selection report. Returns false for
+    /// any other selection size.
+    pub fn pin_measurement(&mut self) -> bool {
+        let mut atoms: Vec<SelectedAtom> = self.interaction.selected.values().cloned().collect();
+        if !(2..=4).contains(&atoms.len()) {
+            return false;
+        }
+        atoms.sort_by_key(|a| a.unique_id);
+        self.interaction.measurements.push(Measurement { atoms });
+        true
+    }
+
+    /// Turn measure mode on or off; either way the picks start over.
+    /// Returns the new mode.
+    pub fn toggle_measure_mode(&mut self) -> bool {
+        self.interaction.measure_mode = !self.interaction.measure_mode;
+        self.interaction.measure_picks.clear();
+        self.interaction.measure_mode
+    }
+
+    /// Add a measure-mode pick. A click after a complete angle starts a new
+    /// measurement, and clicking the last picked instance again takes it
+    /// back.
+    pub fn add_measure_pick(&mut self, atom: SelectedAtom) {
+        let picks = &mut self.interaction.measure_picks;
+        if picks.last().is_some_and(|a| a.unique_id == atom.unique_id) {
+            picks.pop();
+            return;
+        }
+        if picks.len() >= 3 {
+            picks.clear();
+        }
+        picks.push(atom);
+    }
+
+    /// Hide every selected atom and drop it from the selection, since hidden
+    /// atoms can no longer be picked. Returns the atom count.
+    pub fn hide_selected(&mut self) -> usize {
+        let indices = self.selected_indices();
+        for &idx in &indices {
+            self.overrides.entry(idx).or_default().hidden = true;
+        }
+        self.interaction.selected.clear();
+        indices.len()
+    }
+
+    /// Unhide all hidden atoms, keeping any other overrides. Returns the count.
+    pub fn show_hidden(&mut self) -> usize {
+        let mut count = 0;
+        for o in self.overrides.values_mut() {
+            if o.hidden {
+                o.hidden = false;
+                count += 1;
+            }
+        }
+        self.overrides.retain(|_, o| !o.is_empty());
+        count
+    }
+
+    /// Sorted unique element symbols in the current structure.
+    pub fn element_symbols(&self) -> Vec<String> {
+        let mut elements: Vec<String> = self
+            .structure
+            .iter()
+            .flat_map(|s| s.atoms.iter().map(|a| a.element.clone()))
+            .collect();
+        elements.sort();
+        elements.dedup();
+        elements
+    }
+
+    /// Color each element in the structure is currently drawn with: the
+    /// sidebar's per-element color if set, else `scheme`'s default.
+    pub fn effective_element_colors(
+        &self,
+        scheme: ColorScheme,
+    ) -> HashMap<String, (f64, f64, f64)> {
+        self.element_symbols()
+            .into_iter()
+            .map(|el| {
+                let rgb = self
+                    .style
+                    .element_colors
+                    .get(&el)
+                    .copied()
+                    .unwrap_or_else(|| get_element_color(&el, scheme));
+                (el, rgb)
+            })
+            .collect()
+    }
+
+    /// Replace the per-element color mapping and drop cached sprites, which
+    /// were rasterized with the old colors.
+    pub fn set_element_colors(&mut self, colors: HashMap<String, (f64, f64, f64)>) {
+        self.style.element_colors = colors;
+        self.style.atom_cache.borrow_mut().clear();
+    }
+
+    /// Upper bond-length limit for an element pair: the per-pair override if
+    /// the user set one, else the covalent-radius sum scaled by `tolerance`.
+    pub fn max_bond_length(&self, a: &str, b: &str, tolerance: f64) -> f64 {
+        self.bond_pair_cutoffs
+            .get(&bond_pair_key(a, b))
+            .copied()
+            .unwrap_or_else(|| (get_atom_cov(a) + get_atom_cov(b)) * tolerance)
+    }
+
+    /// Keep `frames` as the tab's trajectory, starting at the first frame,
+    /// which the caller has already put in `structure`. A single frame is
+    /// not a trajectory and is dropped.
+    pub fn set_frames(&mut self, frames: Vec<Structure>) {
+        self.frames = if frames.len() > 1 { frames } else { Vec::new() };
+        self.current_frame = 0;
+    }
+
+    /// Show trajectory frame `index`. Selections and per-atom overrides
+    /// survive when the atom count is unchanged (the same atoms, moved) and
+    /// are dropped otherwise; undo history is cleared either way, since it
+    /// belongs to the previous frame. Returns false for an index out of
+    /// range.
+    pub fn show_frame(&mut self, index: usize) -> bool {
+        let Some(frame) = self.frames.get(index) else {
+            return false;
+        };
+        let same_atoms = self
+            .structure
+            .as_ref()
+            .is_some_and(|s| s.atoms.len() == frame.atoms.len());
+        self.structure = Some(frame.clone());
+        self.original_structure = Some(frame.clone());
+        self.current_frame = index;
+        if !same_atoms {
+            self.interaction.selected.clear();
+            self.interaction.highlighted.clear();
+            self.overrides.clear();
+        }
+        self.interaction.undo_stack.clear();
+        self.void_result = None;
+        self.displacement.clear();
+        self.invalidate_bvs_cache();
+        true
+    }
+
+    /// Whether two atoms `dist` apart are bonded: the distance test against
+    /// [`Self::max_bond_length`], then the pair's [`BondRule`] if it has one.
+    pub fn is_bonded(&self, a: &str, b: &str, dist: f64, tolerance: f64) -> bool {
+        let limit = self.max_bond_length(a, b, tolerance);
+        match self.bond_pair_rules.get(&bond_pair_key(a, b)) {
+            None => dist < limit,
+            Some(BondRule::Always) => dist < limit * ALWAYS_BOND_STRETCH,
+            Some(BondRule::Never) => false,
+        }
+    }
+
+    /// Every unordered element pair (including like pairs) in the structure,
+    /// sorted, for the pair-cutoff editor.
+    pub fn element_pairs(&self) -> Vec<(String, String)> {
+        let els = self.element_symbols();
+        let mut pairs = Vec::new();
+        for (i, a) in els.iter().enumerate() {
+            for b in &els[i..] {
+                pairs.push(bond_pair_key(a, b));
+            }
+        }
+        pairs
+    }
+
     pub fn invalidate_bvs_cache(&mut self) {
         self.bvs_cache_valid = false;
     }
 
+    /// BVS of every atom under the sidebar's per-element overrides and
+    /// charge-balanced states for the rest (see `assign_oxidation_states`),
+    /// recomputed after `invalidate_bvs_cache`. Fills `bvs_expected`
+    /// alongside.
     pub fn get_bvs_values(&mut self) -> &[f64] {
         if !self.bvs_cache_valid {
             if let Some(ref structure) = self.structure {
-                use crate::physics::bond_valence::calculator::calculate_bvs_all_auto;
-                self.bvs_cache = calculate_bvs_all_auto(structure);
+                use crate::model::structure::NEUTRALITY_TOLERANCE;
+                use crate::physics::bond_valence::{
+                    analyze_structure, assign_oxidation_states, with_oxidation_overrides,
+                };
+                let mut balanced =
+                    with_oxidation_overrides(structure, &self.style.bvs_oxidation_overrides);
+                let assigned = assign_oxidation_states(&balanced);
+                for (&i, &v) in &assigned {
+                    balanced.atoms[i].oxidation = Some(v);
+                }
+                let r = analyze_structure(&balanced);
+                self.bvs_cache = r.atoms.iter().map(|a| a.bvs).collect();
+                self.bvs_expected = r.atoms.iter().map(|a| a.expected).collect();
                 self.bvs_cache_valid = true;
+
+                if assigned != self.oxidation_assignment {
+                    let charges: Vec<f64> = (0..structure.atoms.len())
+                        .map(|i| assigned.get(&i).copied().unwrap_or(0) as f64)
+                        .collect();
+                    let states = crate::utils::report::oxidation_states(structure, &assigned);
+                    let msg = if structure.net_charge(&charges).abs() < NEUTRALITY_TOLERANCE {
+                        format!("BVS oxidation states (charge-balanced): {}", states)
+                    } else {
+                        format!(
+                            "BVS oxidation states: no charge-balanced assignment, \
+                             using the ideal states: {}",
+                            states
+                        )
+                    };
+                    crate::utils::console::log_info(&msg);
+                    self.oxidation_assignment = assigned;
+                }
             }
         }
         &self.bvs_cache
@@ -310,10 +888,13 @@ impl AppState {
                 }
             }
             tab.interaction.selected.clear();
+            tab.interaction.highlighted.clear();
             tab.invalidate_bvs_cache();
             // Atom indices shifted — overrides keyed on those indices are no
             // longer meaningful. Drop them rather than try to remap.
             tab.overrides.clear();
+            tab.modified = true;
+            tab.displacement.clear();
             format!("Deleted {} atom(s)", count)
         } else {
             "No structure loaded.".to_string()
@@ -325,11 +906,14 @@ impl AppState {
         if let Some(prev_structure) = tab.interaction.undo_stack.pop() {
             tab.structure = Some(prev_structure);
             tab.interaction.selected.clear();
+            tab.interaction.highlighted.clear();
             tab.invalidate_bvs_cache();
             // Same reasoning as `delete_selected`: undo can shift atom counts
             // and indices, so any overrides that pointed to the post-delete
             // arrangement are stale.
             tab.overrides.clear();
+            tab.modified = true;
+            tab.displacement.clear();
             "Undo successful.".to_string()
         } else {
             "Nothing to undo.".to_string()
@@ -342,3 +926,356 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_marks_unsaved_edits() {
+        let mut tab = TabState::new(&Config::default());
+        tab.file_name = "NaCl.cif".to_string();
+        assert_eq!(tab.title(), "NaCl.cif");
+        tab.modified = true;
+        assert_eq!(tab.title(), "• NaCl.cif");
+    }
+
+    #[test]
+    fn reset_to_defaults_restores_style_and_toggles_but_keeps_the_view() {
+        let mut config = Config {
+            default_atom_scale: 0.7,
+            ..Default::default()
+        };
+        let mut tab = TabState::new(&config);
+        assert_eq!(tab.style.atom_scale, 0.7);
+
+        tab.style.atom_scale = 1.2;
+        tab.style.background_color = (0.0, 0.0, 0.0);
+        tab.style.hidden_elements.insert("H".to_string());
+        tab.view.show_bonds = !config.default_show_bonds;
+        tab.view.bond_cutoff = 1.5;
+        tab.view.zoom = 2.5;
+        tab.view.replication = [2, 2, 1];
+        tab.view.rotation_center = Some(RotationCenter::UnitCell);
+
+        // Later default edits reach an open tab only through a reset.
+        config.style.background_color = (1.0, 1.0, 1.0);
+        assert_eq!(tab.style.background_color, (0.0, 0.0, 0.0));
+
+        tab.reset_to_defaults(&config);
+        assert_eq!(tab.style.atom_scale, 0.7);
+        assert_eq!(tab.style.background_color, (1.0, 1.0, 1.0));
+        assert!(tab.style.hidden_elements.is_empty());
+        assert_eq!(tab.view.show_bonds, config.default_show_bonds);
+        assert_eq!(tab.view.bond_cutoff, config.default_bond_tolerance);
+        assert_eq!(tab.view.zoom, 2.5);
+        assert_eq!(tab.view.replication, [2, 2, 1]);
+        assert_eq!(tab.view.rotation_center, Some(RotationCenter::UnitCell));
+    }
+
+    #[test]
+    fn trajectory_frames_swap_the_structure() {
+        use crate::model::structure::Atom;
+        let frame = |xs: &[f64]| Structure {
+            lattice: [[10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [0.0, 0.0, 10.0]],
+            atoms: xs
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| Atom {
+                    element: "Ar".to_string(),
+                    position: [x, 0.0, 0.0],
+                    original_index: i,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: String::new(),
+            is_periodic: false,
+        };
+        let mut tab = TabState::new(&Config::default());
+        tab.set_frames(vec![frame(&[0.0])]);
+        assert!(tab.frames.is_empty());
+
+        let frames = vec![frame(&[0.0, 3.0]), frame(&[0.1, 3.2]), frame(&[0.2])];
+        tab.structure = Some(frames[0].clone());
+        tab.set_frames(frames);
+        tab.overrides.insert(1, AtomOverride::default());
+        tab.interaction.undo_stack.push(frame(&[]));
+
+        assert!(tab.show_frame(1));
+        assert_eq!(tab.current_frame, 1);
+        assert_eq!(tab.structure.as_ref().unwrap().atoms[1].position[0], 3.2);
+        assert!(tab.overrides.contains_key(&1));
+        assert!(tab.interaction.undo_stack.is_empty());
+
+        // A frame with fewer atoms is still shown; index-keyed state goes.
+        assert!(tab.show_frame(2));
+        assert_eq!(tab.structure.as_ref().unwrap().atoms.len(), 1);
+        assert!(tab.overrides.is_empty());
+
+        assert!(!tab.show_frame(3));
+        assert_eq!(tab.current_frame, 2);
+    }
+
+    #[test]
+    fn zoom_about_keeps_cursor_point_fixed() {
+        let (w, h) = (800.0, 600.0);
+        let cursor = (650.0, 120.0);
+        let mut view = ViewState {
+            pan_x: 0.7,
+            ..Default::default()
+        };
+        let scale = 40.0;
+        // Model coordinate under the cursor, per scene.rs's screen transform
+        // with the rotation pivot at 0.
+        let model = |v: &ViewState, s: f64| {
+            (
+                (cursor.0 - w / 2.0) / s + v.pan_x,
+                (cursor.1 - h / 2.0) / s + v.pan_y,
+            )
+        };
+        let before = model(&view, scale);
+        view.zoom_about(1.1, cursor, (w, h), scale);
+        let after = model(&view, scale * 1.1);
+        assert!((view.zoom - 1.1).abs() < 1e-12);
+        assert!((before.0 - after.0).abs() < 1e-12);
+        assert!((before.1 - after.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zoom_is_clamped_and_keeps_cursor_point_at_the_limit() {
+        let (w, h) = (800.0, 600.0);
+        let cursor = (700.0, 500.0);
+        let mut view = ViewState::default();
+        view.set_zoom(4.0);
+        let scale = 100.0;
+        let model = |v: &ViewState, s: f64| {
+            (
+                (cursor.0 - w / 2.0) / s + v.pan_x,
+                (cursor.1 - h / 2.0) / s + v.pan_y,
+            )
+        };
+        let before = model(&view, scale);
+        // Asks for 8×, only 1.25× is left before ZOOM_MAX.
+        view.zoom_about(2.0, cursor, (w, h), scale);
+        assert_eq!(view.zoom, ZOOM_MAX);
+        let after = model(&view, scale * 1.25);
+        assert!((before.0 - after.0).abs() < 1e-12);
+        assert!((before.1 - after.1).abs() < 1e-12);
+
+        // Pinned at the limit: no zoom, no pan drift.
+        let pan = (view.pan_x, view.pan_y);
+        view.zoom_about(2.0, cursor, (w, h), scale * 1.25);
+        assert_eq!((view.pan_x, view.pan_y), pan);
+
+        view.set_zoom(-3.0);
+        assert_eq!(view.zoom, ZOOM_MIN);
+        view.set_zoom(f64::NAN);
+        assert_eq!(view.zoom, ZOOM_MIN);
+    }
+
+    #[test]
+    fn scroll_zoom_is_exponential_and_capped() {
+        assert!((scroll_zoom_factor(1.0) * scroll_zoom_factor(-1.0) - 1.0).abs() < 1e-12);
+        let half = scroll_zoom_factor(0.5);
+        assert!((half * half - scroll_zoom_factor(1.0)).abs() < 1e-12);
+        assert!(scroll_zoom_factor(1.0) < 1.0);
+        assert!((scroll_zoom_factor(-1e6) - 2.0).abs() < 1e-12);
+        assert!((scroll_zoom_factor(1e6) - 0.5).abs() < 1e-12);
+        assert_eq!(scroll_zoom_factor(f64::NAN), 1.0);
+    }
+
+    #[test]
+    fn trackball_follows_cursor() {
+        let (w, h) = (400.0, 300.0);
+        let c = (200.0, 150.0);
+        assert!(trackball_rotation(c, c, w, h).angle() < 1e-12);
+
+        // Rightward drag through the centre: the front point (0,0,-1) must
+        // move toward +x, i.e. a rotation about the screen's vertical axis.
+        let q = trackball_rotation(c, (220.0, 150.0), w, h);
+        let axis = q.axis().unwrap();
+        assert!(axis.x.abs() < 1e-9 && axis.z.abs() < 1e-9);
+        assert!((q * Vector3::new(0.0, 0.0, -1.0)).x > 0.0);
+
+        // Circling around the outside of the ball rolls about the view axis.
+        let q = trackball_rotation((500.0, 150.0), (200.0, 450.0), w, h);
+        assert!(q.axis().unwrap().z.abs() > 0.9);
+    }
+
+    #[test]
+    fn orientation_along_maps_direction_to_depth_axis() {
+        // Hexagonal c and [111] of an oblique cell must both land on +z.
+        let lat = [[3.0, 0.0, 0.0], [-1.5, 2.598, 0.0], [0.5, 0.3, 5.0]];
+        let diag = [
+            lat[0][0] + lat[1][0] + lat[2][0],
+            lat[0][1] + lat[1][1] + lat[2][1],
+            lat[0][2] + lat[1][2] + lat[2][2],
+        ];
+        for (dir, right) in [(lat[2], lat[0]), (diag, lat[1])] {
+            let q = ViewState::orientation_along(dir, right).expect("valid basis");
+            let d = q * Vector3::from(dir).normalize();
+            assert!((d - Vector3::z()).norm() < 1e-9, "got {:?}", d);
+            let r = q * Vector3::from(right);
+            assert!(r.x > 0.0 && r.y.abs() < 1e-9);
+        }
+    }
+
+    fn pick(unique_id: usize) -> SelectedAtom {
+        SelectedAtom {
+            unique_id,
+            original_index: unique_id,
+            cart_pos: [0.0; 3],
+            element: "O".to_string(),
+        }
+    }
+
+    #[test]
+    fn selection_ops_all_none_invert() {
+        let mut it = InteractionState::default();
+        it.selected.insert(1, pick(1));
+
+        assert_eq!(it.apply_selection_op(SelectionOp::Invert, (0..4).map(pick)), 3);
+        assert!(!it.selected.contains_key(&1));
+        assert!(it.selected.contains_key(&0) && it.selected.contains_key(&3));
+
+        assert_eq!(it.apply_selection_op(SelectionOp::All, (0..4).map(pick)), 4);
+        assert_eq!(it.apply_selection_op(SelectionOp::None, (0..4).map(pick)), 0);
+    }
+
+    #[test]
+    fn pin_measurement_needs_two_to_four_atoms() {
+        let mut tab = TabState::new(&Config::default());
+        tab.interaction.selected.insert(7, pick(7));
+        assert!(!tab.pin_measurement());
+
+        tab.interaction.selected.insert(3, pick(3));
+        tab.interaction.selected.insert(5, pick(5));
+        assert!(tab.pin_measurement());
+        let ids: Vec<usize> = tab.interaction.measurements[0]
+            .atoms
+            .iter()
+            .map(|a| a.unique_id)
+            .collect();
+        assert_eq!(ids, vec![3, 5, 7]);
+    }
+
+    #[test]
+    fn measure_picks_restart_after_an_angle() {
+        let mut tab = TabState::new(&Config::default());
+        assert!(tab.toggle_measure_mode());
+        for id in [4, 2, 9] {
+            tab.add_measure_pick(pick(id));
+        }
+        // Clicking the last pick again takes it back.
+        tab.add_measure_pick(pick(9));
+        tab.add_measure_pick(pick(6));
+        let ids: Vec<usize> = tab
+            .interaction
+            .measure_picks
+            .iter()
+            .map(|a| a.unique_id)
+            .collect();
+        assert_eq!(ids, vec![4, 2, 6]);
+
+        tab.add_measure_pick(pick(1));
+        assert_eq!(tab.interaction.measure_picks.len(), 1);
+        assert!(!tab.toggle_measure_mode());
+        assert!(tab.interaction.measure_picks.is_empty());
+    }
+
+    #[test]
+    fn hide_and_show_selected_atoms() {
+        let mut tab = TabState::new(&Config::default());
+        // Two ghost copies of atom 2 plus atom 5.
+        tab.interaction.selected.insert(10, SelectedAtom { original_index: 2, ..pick(10) });
+        tab.interaction.selected.insert(11, SelectedAtom { original_index: 2, ..pick(11) });
+        tab.interaction.selected.insert(12, SelectedAtom { original_index: 5, ..pick(12) });
+        tab.overrides.entry(5).or_default().color = Some((1.0, 0.0, 0.0));
+
+        assert_eq!(tab.selected_indices(), vec![2, 5]);
+        assert_eq!(tab.hide_selected(), 2);
+        assert!(tab.is_hidden(2) && tab.is_hidden(5));
+        assert!(tab.interaction.selected.is_empty());
+
+        assert_eq!(tab.show_hidden(), 2);
+        assert!(!tab.is_hidden(2));
+        // Atom 2 had nothing else overridden; atom 5 keeps its color.
+        assert!(!tab.overrides.contains_key(&2));
+        assert_eq!(tab.override_color(5), Some((1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn effective_colors_prefer_sidebar_overrides() {
+        use crate::model::structure::{Atom, Structure};
+        let mut tab = TabState::new(&Config::default());
+        tab.structure = Some(Structure {
+            lattice: [[4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]],
+            atoms: ["O", "Mg", "O"]
+                .iter()
+                .map(|el| Atom {
+                    element: el.to_string(),
+                    position: [0.0; 3],
+                    original_index: 0,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: "MgO2".to_string(),
+            is_periodic: true,
+        });
+        tab.style.element_colors.insert("O".to_string(), (0.1, 0.2, 0.3));
+
+        let colors = tab.effective_element_colors(ColorScheme::Jmol);
+        assert_eq!(colors.len(), 2);
+        assert_eq!(colors["O"], (0.1, 0.2, 0.3));
+        assert_eq!(colors["Mg"], get_element_color("Mg", ColorScheme::Jmol));
+    }
+
+    #[test]
+    fn pair_cutoff_overrides_tolerance_rule() {
+        use crate::model::structure::{Atom, Structure};
+        let mut tab = TabState::new(&Config::default());
+        tab.structure = Some(Structure {
+            lattice: [[5.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 5.0]],
+            atoms: ["Si", "O", "O"]
+                .iter()
+                .map(|el| Atom {
+                    element: el.to_string(),
+                    position: [0.0; 3],
+                    original_index: 0,
+                    oxidation: None,
+                    occupancy: 1.0,
+                    adp: None,
+                })
+                .collect(),
+            formula: "SiO2".to_string(),
+            is_periodic: true,
+        });
+
+        let rule = (get_atom_cov("Si") + get_atom_cov("O")) * 1.1;
+        assert!((tab.max_bond_length("Si", "O", 1.1) - rule).abs() < 1e-12);
+
+        tab.bond_pair_cutoffs.insert(bond_pair_key("Si", "O"), 2.0);
+        assert_eq!(tab.max_bond_length("O", "Si", 1.1), 2.0);
+        assert_eq!(tab.max_bond_length("Si", "O", 0.7), 2.0);
+
+        let pairs = tab.element_pairs();
+        assert_eq!(
+            pairs,
+            vec![
+                ("O".to_string(), "O".to_string()),
+                ("O".to_string(), "Si".to_string()),
+                ("Si".to_string(), "Si".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn orientation_along_rejects_parallel_reference() {
+        assert!(ViewState::orientation_along([0.0, 0.0, 1.0], [0.0, 0.0, 2.0]).is_none());
+        assert!(ViewState::orientation_along([0.0; 3], [1.0, 0.0, 0.0]).is_none());
+    }
+}
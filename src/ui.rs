@@ -1,6 +1,7 @@
 // src/ui.rs
 
 pub mod analysis;
+pub mod autosave;
 pub mod dialogs;
 pub mod export_dialog;
 pub mod interactions;
@@ -10,9 +11,10 @@ pub mod preferences;
 pub use interactions::setup_interactions;
 pub use preferences::show_preferences_window;
 
-use crate::config::ColorMode;
+use crate::config::{ColorMode, Config, RecentDir};
 use crate::rendering;
-use crate::state::AppState;
+use crate::state::{AppState, StereoMode, TabState};
+use gtk4::cairo;
 use gtk4::prelude::*;
 use gtk4::{Box as GtkBox, Button, DrawingArea, Label, Notebook, Orientation, Widget};
 use std::cell::RefCell;
@@ -45,36 +47,144 @@ pub fn create_tab_content(state: Rc<RefCell<AppState>>, tab_id: usize) -> (Drawi
       return;
     }
 
-    let tab = &st.tabs[tid];
+    let stereo = st.tabs[tid].view.stereo;
+    if stereo == StereoMode::Off {
+      let tab = &st.tabs[tid];
+      let frame = draw_view(cr, tab, &st.config, w as f64, h as f64);
+      tab.drawn_frame.replace(Some(frame));
+    } else {
+      st.tabs[tid].drawn_frame.replace(None);
+      drop(st);
+      draw_stereo(cr, &s, tid, stereo, w as f64, h as f64);
+    }
+  });
+
+  let container = GtkBox::new(Orientation::Vertical, 0);
+  container.append(&drawing_area);
+
+  (drawing_area, container)
+}
 
-    // 1. Background
-    let (bg_r, bg_g, bg_b) = tab.style.background_color;
-    cr.set_source_rgb(bg_r, bg_g, bg_b);
-    cr.paint().unwrap();
+/// Draw one full frame of `tab` (background, cell, structure, overlays)
+/// into a `w`×`h` area. Returns the frame as laid out.
+fn draw_view(
+  cr: &cairo::Context,
+  tab: &TabState,
+  config: &Config,
+  w: f64,
+  h: f64,
+) -> rendering::scene::DrawnFrame {
+  cr.set_antialias(rendering::primitives::cairo_antialias(config.antialias_level));
 
-    // 2. Calculate Scene
-    let (atoms, lattice_corners, bounds) =
-      rendering::scene::calculate_scene(tab, &st.config, w as f64, h as f64, false, None, None);
+  // 1. Background
+  rendering::painter::draw_background(cr, &tab.style, w, h);
+  let backdrop = rendering::painter::Backdrop::of_style(&tab.style);
 
-    // 3. Draw Elements
-    rendering::painter::draw_unit_cell(cr, &lattice_corners, false);
-    rendering::painter::draw_structure(cr, &atoms, tab, bounds.scale, false, st.config.color_scheme);
-    rendering::painter::draw_miller_planes(
+  // 2. Calculate Scene
+  let (atoms, lattice_corners, bounds) =
+    rendering::scene::calculate_scene(tab, config, w, h, false, None, None);
+
+  // 3. Draw Elements
+  rendering::painter::draw_unit_cell(cr, &lattice_corners, &tab.style);
+  rendering::painter::draw_structure(
+    cr,
+    &atoms,
+    tab,
+    bounds.scale,
+    false,
+    config.color_scheme,
+    config.render_quality,
+    rendering::painter::DepthCue::from_config(config, bounds.depth, &backdrop),
+  );
+  if tab.style.show_labels {
+    rendering::painter::draw_atom_labels(cr, &atoms, tab);
+  }
+  rendering::painter::draw_miller_planes(cr, tab, &lattice_corners, bounds.scale, w, h);
+  if tab.view.show_bvs_values {
+    rendering::painter::draw_bvs_values(cr, &atoms, tab);
+  }
+  if tab.view.show_measurements {
+    rendering::painter::draw_measurements(cr, &atoms, tab, config.label_measurements);
+  }
+  rendering::painter::draw_measure_picks(cr, &atoms, tab, config.label_measurements);
+  rendering::painter::draw_axes(cr, tab, w, h, tab.view.show_axes);
+  if tab.view.show_scale_bar {
+    rendering::painter::draw_scale_bar(
       cr,
-      tab,
-      &lattice_corners,
+      &backdrop,
       bounds.scale,
-      w as f64,
-      h as f64,
+      tab.view.scale_bar_length,
+      w,
+      h,
     );
-    rendering::painter::draw_axes(cr, tab, w as f64, h as f64);
-    rendering::painter::draw_selection_box(cr, tab);
-  });
+  }
+  if tab.view.show_legend {
+    rendering::painter::draw_element_legend(cr, tab, config.color_scheme, &backdrop, w, h);
+  }
+  rendering::painter::draw_bvs_legend(cr, tab, &backdrop, w, h);
+  rendering::painter::draw_selection_box(cr, tab);
+  rendering::scene::DrawnFrame {
+    size: (w, h),
+    atoms,
+    depth: bounds.depth,
+  }
+}
 
-  let container = GtkBox::new(Orientation::Vertical, 0);
-  container.append(&drawing_area);
+/// Render both eyes of a stereo pair. Each eye is the normal frame with the
+/// camera yawed by half the eye separation; the yaw is cleared afterwards so
+/// picking and export keep seeing the plain camera.
+fn draw_stereo(
+  cr: &cairo::Context,
+  state: &Rc<RefCell<AppState>>,
+  tid: usize,
+  mode: StereoMode,
+  w: f64,
+  h: f64,
+) {
+  let half_sep = state.borrow().tabs[tid].view.eye_separation_deg / 2.0;
+  let render_eye = |target: &cairo::Context, offset: f64, eye_w: f64| {
+    state.borrow_mut().tabs[tid].view.eye_offset_deg = offset;
+    let st = state.borrow();
+    draw_view(target, &st.tabs[tid], &st.config, eye_w, h);
+  };
 
-  (drawing_area, container)
+  match mode {
+    StereoMode::SideBySide => {
+      let half_w = w / 2.0;
+      for (x0, offset) in [(0.0, -half_sep), (half_w, half_sep)] {
+        cr.save().ok();
+        cr.rectangle(x0, 0.0, half_w, h);
+        cr.clip();
+        cr.translate(x0, 0.0);
+        render_eye(cr, offset, half_w);
+        cr.restore().ok();
+      }
+    }
+    StereoMode::Anaglyph => {
+      let eye_surface = |offset: f64| -> Option<cairo::ImageSurface> {
+        let surf =
+          cairo::ImageSurface::create(cairo::Format::ARgb32, w.max(1.0) as i32, h.max(1.0) as i32)
+            .ok()?;
+        {
+          let eye_cr = cairo::Context::new(&surf).ok()?;
+          render_eye(&eye_cr, offset, w);
+        }
+        surf.flush();
+        Some(surf)
+      };
+
+      if let (Some(mut left), Some(mut right)) = (eye_surface(-half_sep), eye_surface(half_sep)) {
+        if let (Ok(mut l), Ok(r)) = (left.data(), right.data()) {
+          rendering::painter::merge_anaglyph(&mut l, &r);
+        }
+        cr.set_source_surface(&left, 0.0, 0.0).ok();
+        cr.paint().ok();
+      }
+    }
+    StereoMode::Off => {}
+  }
+
+  state.borrow_mut().tabs[tid].view.eye_offset_deg = 0.0;
 }
 
 /// HELPER: closing tab
@@ -109,11 +219,148 @@ pub fn add_closable_tab(
       state_weak.upgrade(),
     ) {
       if let Some(page_num) = nb.page_num(&content_widget) {
-        st.borrow_mut().remove_tab(page_num as usize);
+        close_tab(&nb, st, page_num);
+      }
+    }
+  });
+}
+
+/// Close notebook page `page_num`, asking first when its structure has
+/// unsaved edits.
+pub fn close_tab(notebook: &Notebook, state: Rc<RefCell<AppState>>, page_num: u32) {
+  let title = match state.borrow().tabs.get(page_num as usize) {
+    Some(tab) if tab.modified => tab.file_name.clone(),
+    _ => {
+      state.borrow_mut().remove_tab(page_num as usize);
+      notebook.remove_page(Some(page_num));
+      return;
+    }
+  };
+
+  let parent = notebook.root().and_downcast::<gtk4::Window>();
+  let dialog = gtk4::MessageDialog::new(
+    parent.as_ref(),
+    gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+    gtk4::MessageType::Question,
+    gtk4::ButtonsType::None,
+    format!("Close '{}' without saving?", title),
+  );
+  dialog.set_title(Some("Unsaved Changes"));
+  dialog.set_secondary_text(Some(
+    "The structure was edited since it was opened or last saved. \
+     Closing the tab discards those edits.",
+  ));
+  dialog.add_button("Cancel", gtk4::ResponseType::Cancel);
+  dialog.add_button("Close Without Saving", gtk4::ResponseType::Accept);
+  if let Some(btn) = dialog.widget_for_response(gtk4::ResponseType::Accept) {
+    btn.add_css_class("destructive-action");
+  }
+
+  let nb_weak = notebook.downgrade();
+  dialog.connect_response(move |d, resp| {
+    if resp == gtk4::ResponseType::Accept {
+      if let Some(nb) = nb_weak.upgrade() {
+        state.borrow_mut().remove_tab(page_num as usize);
         nb.remove_page(Some(page_num));
       }
     }
+    d.close();
   });
+  dialog.present();
+}
+
+/// Ask before `window` closes while any tab holds unsaved edits. Both the
+/// Quit action and the title-bar button end up here. Install this before
+/// other close handlers: while the question is open the close is stopped,
+/// so they only run once the user chose to quit.
+pub fn guard_unsaved_on_close(window: &gtk4::ApplicationWindow, state: Rc<RefCell<AppState>>) {
+  let confirmed = Rc::new(std::cell::Cell::new(false));
+  window.connect_close_request(move |w| {
+    let edited: Vec<String> = state
+      .borrow()
+      .tabs
+      .iter()
+      .filter(|tab| tab.modified)
+      .map(|tab| tab.file_name.clone())
+      .collect();
+    if confirmed.get() || edited.is_empty() {
+      return gtk4::glib::Propagation::Proceed;
+    }
+
+    let dialog = gtk4::MessageDialog::new(
+      Some(w),
+      gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+      gtk4::MessageType::Question,
+      gtk4::ButtonsType::None,
+      format!("Quit with {} unsaved tab(s)?", edited.len()),
+    );
+    dialog.set_title(Some("Unsaved Changes"));
+    dialog.set_secondary_text(Some(&format!(
+      "Edited since opened or last saved: {}. Quitting discards those edits.",
+      edited.join(", ")
+    )));
+    dialog.add_button("Cancel", gtk4::ResponseType::Cancel);
+    dialog.add_button("Quit Without Saving", gtk4::ResponseType::Accept);
+    if let Some(btn) = dialog.widget_for_response(gtk4::ResponseType::Accept) {
+      btn.add_css_class("destructive-action");
+    }
+
+    let win_weak = w.downgrade();
+    let confirmed = confirmed.clone();
+    dialog.connect_response(move |d, resp| {
+      d.close();
+      if resp == gtk4::ResponseType::Accept {
+        if let Some(win) = win_weak.upgrade() {
+          confirmed.set(true);
+          win.close();
+        }
+      }
+    });
+    dialog.present();
+    gtk4::glib::Propagation::Stop
+  });
+}
+
+/// Sync every notebook tab label with [`TabState::title`], so edited tabs
+/// show the unsaved-changes marker.
+pub fn refresh_tab_labels(notebook: &Notebook, state: &AppState) {
+  for (i, tab) in state.tabs.iter().enumerate() {
+    let Some(page) = notebook.nth_page(Some(i as u32)) else {
+      continue;
+    };
+    let Some(widget) = notebook.tab_label(&page) else {
+      continue;
+    };
+    // Closable tabs wrap the label in a box next to the close button.
+    let label = match widget.downcast_ref::<GtkBox>() {
+      Some(bx) => bx.first_child().and_downcast::<Label>(),
+      None => widget.downcast::<Label>().ok(),
+    };
+    if let Some(label) = label {
+      let title = tab.title();
+      if label.text() != title {
+        label.set_text(&title);
+      }
+    }
+  }
+}
+
+/// Open `chooser` in the remembered `kind` folder, if there is one.
+pub fn start_in_recent_dir(
+  chooser: &impl IsA<gtk4::FileChooser>,
+  config: &Config,
+  kind: RecentDir,
+) {
+  if let Some(dir) = config.recent_dir(kind) {
+    let _ = chooser.set_current_folder(Some(&gtk4::gio::File::for_path(dir)));
+  }
+}
+
+/// Remember the folder of `path` for the next `kind` chooser and persist it.
+pub fn remember_recent_dir(state: &Rc<RefCell<AppState>>, kind: RecentDir, path: &std::path::Path) {
+  let mut st = state.borrow_mut();
+  st.config.remember_dir(kind, path);
+  st.save_config();
 }
 
 /// Finds the DrawingArea inside the currently active Notebook tab.
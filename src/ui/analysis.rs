@@ -1,6 +1,11 @@
 pub mod charge_density_tab;
+pub mod compare_tab;
+pub mod coordination_tab;
 pub mod kpath_tab;
+pub mod pdf_tab;
+pub mod rdf_tab;
 pub mod slab_tab;
+pub mod stereo_tab;
 pub mod symmetry_tab;
 pub mod voids_tab;
 pub mod window;
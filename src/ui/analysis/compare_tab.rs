@@ -0,0 +1,230 @@
+// src/ui/analysis/compare_tab.rs
+//
+// Structure comparison between two open tabs: pick a reference (A) and a
+// compared structure (B), superpose B on A and list each atom's
+// displacement. Optionally colours B's atoms in the main view by how far
+// they moved.
+
+use crate::config::ColorMode;
+use crate::model::structure::Structure;
+use crate::physics::analysis::compare::{self, Comparison};
+use crate::state::AppState;
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Box, Button, CheckButton, DropDown, Grid, Label, Notebook, Orientation, PolicyType,
+    ScrolledWindow, StringList, TextView, WrapMode,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Last successful comparison and the tab index of structure B.
+type LastResult = Rc<RefCell<Option<(usize, Comparison)>>>;
+
+pub fn build(state: Rc<RefCell<AppState>>, notebook: &Notebook) -> Box {
+    let root = Box::new(Orientation::Vertical, 10);
+    root.set_margin_top(15);
+    root.set_margin_bottom(15);
+    root.set_margin_start(15);
+    root.set_margin_end(15);
+
+    let title = Label::new(None);
+    title.set_markup("<span size='large' weight='bold'>Structure Comparison</span>");
+    title.set_halign(Align::Start);
+    root.append(&title);
+
+    let hint = Label::new(Some(
+        "B is superposed on A (Kabsch rotation about the centroids). Atoms are \
+         paired by index, or by nearest same-element neighbour when the order \
+         differs.",
+    ));
+    hint.set_wrap(true);
+    hint.set_xalign(0.0);
+    root.append(&hint);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(6);
+    grid.set_column_spacing(10);
+
+    let tab_list = StringList::new(&[]);
+    let dd_a = DropDown::new(Some(tab_list.clone()), None::<&gtk4::Expression>);
+    let dd_b = DropDown::new(Some(tab_list.clone()), None::<&gtk4::Expression>);
+    dd_a.set_hexpand(true);
+    dd_b.set_hexpand(true);
+
+    let lbl_a = Label::new(Some("Reference (A):"));
+    lbl_a.set_halign(Align::Start);
+    let lbl_b = Label::new(Some("Compared (B):"));
+    lbl_b.set_halign(Align::Start);
+    grid.attach(&lbl_a, 0, 0, 1, 1);
+    grid.attach(&dd_a, 1, 0, 1, 1);
+    grid.attach(&lbl_b, 0, 1, 1, 1);
+    grid.attach(&dd_b, 1, 1, 1, 1);
+
+    let btn_compare = Button::with_label("Compare");
+    grid.attach(&btn_compare, 2, 0, 1, 2);
+    root.append(&grid);
+
+    let check_color = CheckButton::with_label("Color atoms of B by displacement");
+    root.append(&check_color);
+
+    let summary = Label::new(Some("Pick two tabs and press Compare."));
+    summary.set_halign(Align::Start);
+    summary.set_selectable(true);
+    root.append(&summary);
+
+    let table = TextView::new();
+    table.set_editable(false);
+    table.set_monospace(true);
+    table.set_wrap_mode(WrapMode::None);
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Automatic)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .min_content_height(300)
+        .vexpand(true)
+        .child(&table)
+        .build();
+    root.append(&scrolled);
+
+    // ============================================================
+    // LOGIC
+    // ============================================================
+    let last: LastResult = Rc::new(RefCell::new(None));
+
+    // Tabs open and close while this window stays up; list them afresh
+    // whenever the page is shown.
+    let state_map = state.clone();
+    let list_map = tab_list.clone();
+    let (dd_a_map, dd_b_map) = (dd_a.clone(), dd_b.clone());
+    root.connect_map(move |_| {
+        let st = state_map.borrow();
+        let names: Vec<String> = st
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!("{}: {}", i + 1, t.file_name))
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        list_map.splice(0, list_map.n_items(), &names);
+        dd_a_map.set_selected(0);
+        dd_b_map.set_selected(if names.len() > 1 { 1 } else { 0 });
+    });
+
+    let nb_weak = notebook.downgrade();
+    let redraw = move || {
+        if let Some(nb) = nb_weak.upgrade() {
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+        }
+    };
+
+    let state_cmp = state.clone();
+    let last_cmp = last.clone();
+    let check_cmp = check_color.clone();
+    let redraw_cmp = redraw.clone();
+    btn_compare.connect_clicked(move |_| {
+        let (ia, ib) = (dd_a.selected() as usize, dd_b.selected() as usize);
+        let mut st = state_cmp.borrow_mut();
+        let structure = |i: usize| st.tabs.get(i).and_then(|t| t.structure.clone());
+        let (Some(a), Some(b)) = (structure(ia), structure(ib)) else {
+            summary.set_markup("<span color='red'>Both tabs need a structure.</span>");
+            return;
+        };
+        if ia == ib {
+            summary.set_markup("<span color='red'>Pick two different tabs.</span>");
+            return;
+        }
+
+        match compare::kabsch_rmsd(&a, &b) {
+            Ok(cmp) => {
+                summary.set_markup(&format!(
+                    "<b>RMSD:</b> {:.4} Å    <b>Max:</b> {:.4} Å (atom {} {}){}",
+                    cmp.rmsd,
+                    cmp.max_displacement,
+                    cmp.max_atom,
+                    a.atoms[cmp.max_atom].element,
+                    if cmp.reordered {
+                        "    <i>paired by distance</i>"
+                    } else {
+                        ""
+                    }
+                ));
+                table.buffer().set_text(&displacement_table(&a, &cmp));
+
+                clear_coloring(&mut st, last_cmp.borrow().as_ref());
+                if check_cmp.is_active() {
+                    apply_coloring(&mut st, ib, &cmp);
+                }
+                *last_cmp.borrow_mut() = Some((ib, cmp));
+                drop(st);
+                redraw_cmp();
+            }
+            Err(e) => {
+                summary.set_markup(&format!(
+                    "<span color='red'>{}</span>",
+                    gtk4::glib::markup_escape_text(&e)
+                ));
+                table.buffer().set_text("");
+            }
+        }
+    });
+
+    let state_chk = state.clone();
+    check_color.connect_toggled(move |c| {
+        let mut st = state_chk.borrow_mut();
+        let last = last.borrow();
+        if c.is_active() {
+            if let Some((ib, cmp)) = last.as_ref() {
+                apply_coloring(&mut st, *ib, cmp);
+            }
+        } else {
+            clear_coloring(&mut st, last.as_ref());
+        }
+        drop(st);
+        redraw();
+    });
+
+    root
+}
+
+fn displacement_table(a: &Structure, cmp: &Comparison) -> String {
+    let mut out = format!("{:>6}  {:<4} {:>6}  {:>10}\n", "A", "El", "B", "|Δr| (Å)");
+    for (i, (&j, d)) in cmp.partner.iter().zip(&cmp.displacement).enumerate() {
+        out.push_str(&format!(
+            "{:>6}  {:<4} {:>6}  {:>10.4}\n",
+            i, a.atoms[i].element, j, d
+        ));
+    }
+    out
+}
+
+/// Store B's displacements (as fractions of the largest) on its tab and
+/// switch that tab to displacement colouring.
+fn apply_coloring(st: &mut AppState, ib: usize, cmp: &Comparison) {
+    let Some(tab) = st.tabs.get_mut(ib) else {
+        return;
+    };
+    let n = tab.structure.as_ref().map_or(0, |s| s.atoms.len());
+    let mut scaled = vec![0.0; n];
+    for (&j, &d) in cmp.partner.iter().zip(&cmp.displacement) {
+        if let Some(slot) = scaled.get_mut(j) {
+            *slot = if cmp.max_displacement > 1e-12 {
+                d / cmp.max_displacement
+            } else {
+                0.0
+            };
+        }
+    }
+    tab.displacement = scaled;
+    tab.style.color_mode = ColorMode::Displacement;
+}
+
+fn clear_coloring(st: &mut AppState, last: Option<&(usize, Comparison)>) {
+    let Some(tab) = last.and_then(|(ib, _)| st.tabs.get_mut(*ib)) else {
+        return;
+    };
+    if tab.style.color_mode == ColorMode::Displacement {
+        tab.style.color_mode = ColorMode::Element;
+    }
+    tab.displacement.clear();
+}
@@ -0,0 +1,164 @@
+// src/ui/analysis/coordination_tab.rs
+//
+// Coordination number of every atom in the active structure, with its
+// mean bond length, from the covalent-radius criterion scaled by a
+// tolerance. Atoms whose CN is unusual for their element (Si that is not
+// four-coordinate, say) are listed in orange.
+
+use crate::physics::analysis::coordination::{coordination_numbers, is_unusual};
+use crate::state::AppState;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{Align, Box, Grid, Label, Orientation, PolicyType, ScrolledWindow, SpinButton};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Rows put in the table; a Grid with more labels than this gets slow to
+/// build, and the per-element summary still covers every atom.
+const MAX_ROWS: usize = 2000;
+
+/// Pause after the last tolerance change before recomputing, so holding
+/// the spin arrows does not search a large cell on every step.
+const SPIN_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub fn build(state: Rc<RefCell<AppState>>) -> Box {
+    let root = Box::new(Orientation::Vertical, 10);
+    root.set_margin_top(15);
+    root.set_margin_bottom(15);
+    root.set_margin_start(15);
+    root.set_margin_end(15);
+
+    let title = Label::new(None);
+    title.set_markup("<span size='large' weight='bold'>Coordination Numbers</span>");
+    title.set_halign(Align::Start);
+    root.append(&title);
+
+    let hint = Label::new(Some(
+        "Two atoms are neighbours when closer than the sum of their covalent \
+         radii × tolerance. Periodic images count; orange rows have a CN that \
+         is unusual for the element.",
+    ));
+    hint.set_wrap(true);
+    hint.set_xalign(0.0);
+    root.append(&hint);
+
+    let controls = Box::new(Orientation::Horizontal, 10);
+    controls.append(&Label::new(Some("Tolerance ×")));
+    let tolerance = state.borrow().active_tab().view.bond_cutoff;
+    let adj = gtk4::Adjustment::new(tolerance, 0.8, 2.0, 0.05, 0.1, 0.0);
+    let spin = SpinButton::new(Some(&adj), 0.05, 2);
+    controls.append(&spin);
+    root.append(&controls);
+
+    let summary = Label::new(None);
+    summary.set_halign(Align::Start);
+    summary.set_wrap(true);
+    summary.set_selectable(true);
+    root.append(&summary);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(2);
+    grid.set_column_spacing(18);
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Automatic)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .min_content_height(300)
+        .vexpand(true)
+        .child(&grid)
+        .build();
+    root.append(&scrolled);
+
+    // ============================================================
+    // LOGIC
+    // ============================================================
+    let refresh = Rc::new(move |scale: f64| {
+        while let Some(child) = grid.first_child() {
+            grid.remove(&child);
+        }
+        let st = state.borrow();
+        let Some(structure) = &st.active_tab().structure else {
+            summary.set_text("No structure loaded.");
+            return;
+        };
+        let (cns, lengths) = coordination_numbers(structure, scale);
+
+        for (col, head) in ["#", "Element", "CN", "⟨d⟩ (Å)"].iter().enumerate() {
+            let l = Label::new(None);
+            l.set_markup(&format!("<b>{}</b>", head));
+            l.set_halign(Align::Start);
+            grid.attach(&l, col as i32, 0, 1, 1);
+        }
+        for ((i, element, cn, _), d) in cns.iter().zip(&lengths).take(MAX_ROWS) {
+            let cells = [
+                i.to_string(),
+                element.clone(),
+                cn.to_string(),
+                d.map_or_else(|| "—".to_string(), |d| format!("{:.3}", d)),
+            ];
+            let unusual = is_unusual(element, *cn);
+            for (col, text) in cells.iter().enumerate() {
+                let l = Label::new(None);
+                if unusual {
+                    l.set_markup(&format!("<span color='orange'>{}</span>", text));
+                } else {
+                    l.set_text(text);
+                }
+                l.set_halign(Align::Start);
+                grid.attach(&l, col as i32, *i as i32 + 1, 1, 1);
+            }
+        }
+
+        // Mean CN per element, in order of first appearance.
+        let mut per_element: Vec<(&str, u32, u32)> = Vec::new();
+        for (_, element, cn, _) in &cns {
+            match per_element
+                .iter_mut()
+                .find(|(el, _, _)| *el == element.as_str())
+            {
+                Some((_, sum, n)) => {
+                    *sum += cn;
+                    *n += 1;
+                }
+                None => per_element.push((element.as_str(), *cn, 1)),
+            }
+        }
+        let mut text = per_element
+            .iter()
+            .map(|(el, sum, n)| format!("{}: ⟨CN⟩ {:.2}", el, *sum as f64 / *n as f64))
+            .collect::<Vec<_>>()
+            .join("    ");
+        let n_unusual = cns
+            .iter()
+            .filter(|(_, el, cn, _)| is_unusual(el, *cn))
+            .count();
+        if n_unusual > 0 {
+            text.push_str(&format!("\n{} atom(s) with an unusual CN", n_unusual));
+        }
+        if cns.len() > MAX_ROWS {
+            text.push_str(&format!("\nTable shows the first {} atoms", MAX_ROWS));
+        }
+        summary.set_text(&text);
+    });
+
+    let refresh_spin = refresh.clone();
+    let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    spin.connect_value_changed(move |s| {
+        if let Some(id) = pending.borrow_mut().take() {
+            id.remove();
+        }
+        let (refresh, slot, spin) = (refresh_spin.clone(), pending.clone(), s.clone());
+        let id = glib::timeout_add_local_once(SPIN_DEBOUNCE, move || {
+            slot.borrow_mut().take();
+            refresh(spin.value());
+        });
+        *pending.borrow_mut() = Some(id);
+    });
+
+    // The active tab can change while the window is open; recompute
+    // whenever the page is shown.
+    let spin_map = spin.clone();
+    root.connect_map(move |_| refresh(spin_map.value()));
+
+    root
+}
@@ -0,0 +1,303 @@
+// src/ui/analysis/pdf_tab.rs
+//
+// Reduced pair distribution function G(r) of the active structure, with
+// X-ray or neutron pair weighting. Exports the curve as a two-column .gr
+// file (the format PDFgui and diffpy read) or the plot as PDF.
+
+use crate::physics::analysis::pdf::{calculate_gr, Radiation};
+use crate::state::AppState;
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Button, DropDown, FileChooserAction, FileChooserNative, Frame, Grid, Label, Orientation,
+    ResponseType, SpinButton,
+};
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+use cairo::{Context, PdfSurface};
+use plotters::backend::DrawingBackend;
+use plotters::prelude::*;
+use plotters_cairo::CairoBackend;
+
+#[derive(Clone)]
+struct PdfCurve {
+    points: Vec<(f64, f64)>,
+    radiation: Radiation,
+    r_max: f64,
+    sigma: f64,
+    q_damp: f64,
+}
+
+fn radiation_label(radiation: Radiation) -> &'static str {
+    match radiation {
+        Radiation::XRay => "X-ray",
+        Radiation::Neutron => "Neutron",
+    }
+}
+
+fn draw_pdf_chart<DB: DrawingBackend>(
+    root: &plotters::drawing::DrawingArea<DB, plotters::coord::Shift>,
+    curve: &PdfCurve,
+) -> Result<(), std::boxed::Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let (lo, hi) = curve
+        .points
+        .iter()
+        .fold((0.0f64, 0.0f64), |(lo, hi), (_, g)| {
+            (lo.min(*g), hi.max(*g))
+        });
+    let pad = 0.05 * (hi - lo).max(1e-6);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            format!("G(r) — {} weighting", radiation_label(curve.radiation)),
+            ("sans-serif", 26).into_font(),
+        )
+        .margin(20)
+        .x_label_area_size(55)
+        .y_label_area_size(65)
+        .build_cartesian_2d(0.0..curve.r_max, (lo - pad)..(hi + pad))?;
+
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 18).into_font())
+        .axis_desc_style(("sans-serif", 22).into_font())
+        .x_desc("r (Å)")
+        .y_desc("G(r) (Å⁻²)")
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(
+        [(0.0, 0.0), (curve.r_max, 0.0)],
+        BLACK.mix(0.3),
+    ))?;
+    chart.draw_series(LineSeries::new(
+        curve.points.iter().copied(),
+        BLUE.stroke_width(2),
+    ))?;
+
+    Ok(())
+}
+
+/// Two-column `r G(r)` text with a `#` header recording the settings.
+fn write_gr(path: &std::path::Path, curve: &PdfCurve) -> std::io::Result<()> {
+    let mut w = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(w, "# Reduced PDF G(r) calculated by CView")?;
+    writeln!(
+        w,
+        "# radiation = {}, sigma = {} A, qdamp = {} 1/A",
+        radiation_label(curve.radiation),
+        curve.sigma,
+        curve.q_damp
+    )?;
+    writeln!(w, "# r (A)  G(r) (1/A^2)")?;
+    for (r, g) in &curve.points {
+        writeln!(w, "{:.4} {:.6}", r, g)?;
+    }
+    w.flush()
+}
+
+pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
+    let root = gtk4::Box::new(Orientation::Horizontal, 15);
+    root.set_margin_top(15);
+    root.set_margin_bottom(15);
+    root.set_margin_start(15);
+    root.set_margin_end(15);
+
+    let curve_state: Rc<RefCell<Option<PdfCurve>>> = Rc::new(RefCell::new(None));
+
+    // LEFT PANE (Plot)
+    let left_pane = gtk4::Box::new(Orientation::Vertical, 5);
+    left_pane.set_hexpand(true);
+
+    let frame_plot = Frame::new(Some(" Pair Distribution Function "));
+    let drawing_area = gtk4::DrawingArea::new();
+    drawing_area.set_content_width(600);
+    drawing_area.set_content_height(400);
+    drawing_area.set_hexpand(true);
+    drawing_area.set_vexpand(true);
+
+    frame_plot.set_child(Some(&drawing_area));
+    left_pane.append(&frame_plot);
+    root.append(&left_pane);
+
+    // RIGHT PANE (Controls)
+    let right_pane = gtk4::Box::new(Orientation::Vertical, 10);
+    right_pane.set_width_request(260);
+
+    let title = Label::new(Some("Settings"));
+    title.add_css_class("title-3");
+    title.set_halign(Align::Start);
+    right_pane.append(&title);
+
+    let frame_settings = Frame::new(None);
+    let grid = Grid::new();
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(10);
+    grid.set_margin_top(10);
+    grid.set_margin_bottom(10);
+    grid.set_margin_start(10);
+    grid.set_margin_end(10);
+
+    let radiation_dd = DropDown::from_strings(&["X-ray", "Neutron"]);
+
+    let adj_rmax = gtk4::Adjustment::new(20.0, 2.0, 100.0, 1.0, 5.0, 0.0);
+    let spin_rmax = SpinButton::new(Some(&adj_rmax), 1.0, 1);
+
+    let adj_sigma = gtk4::Adjustment::new(0.1, 0.01, 1.0, 0.01, 0.05, 0.0);
+    let spin_sigma = SpinButton::new(Some(&adj_sigma), 0.01, 3);
+
+    let adj_qdamp = gtk4::Adjustment::new(0.03, 0.0, 0.5, 0.005, 0.05, 0.0);
+    let spin_qdamp = SpinButton::new(Some(&adj_qdamp), 0.005, 3);
+
+    grid.attach(&Label::new(Some("Radiation:")), 0, 0, 1, 1);
+    grid.attach(&radiation_dd, 1, 0, 1, 1);
+    grid.attach(&Label::new(Some("r max (Å):")), 0, 1, 1, 1);
+    grid.attach(&spin_rmax, 1, 1, 1, 1);
+    grid.attach(&Label::new(Some("σ (Å):")), 0, 2, 1, 1);
+    grid.attach(&spin_sigma, 1, 2, 1, 1);
+    grid.attach(&Label::new(Some("Q damp (Å⁻¹):")), 0, 3, 1, 1);
+    grid.attach(&spin_qdamp, 1, 3, 1, 1);
+
+    frame_settings.set_child(Some(&grid));
+    right_pane.append(&frame_settings);
+
+    let btn_calc = Button::with_label("Recalculate");
+    btn_calc.add_css_class("suggested-action");
+    right_pane.append(&btn_calc);
+
+    let btn_export_gr = Button::with_label("Export G(r) Data");
+    right_pane.append(&btn_export_gr);
+
+    let btn_export_pdf = Button::with_label("Export PDF");
+    right_pane.append(&btn_export_pdf);
+
+    root.append(&right_pane);
+
+    // LOGIC
+    let cs = curve_state.clone();
+    drawing_area.set_draw_func(move |_, ctx, w, h| {
+        let backend = CairoBackend::new(ctx, (w as u32, h as u32)).unwrap();
+        let root = backend.into_drawing_area();
+        match cs.borrow().as_ref() {
+            Some(curve) if !curve.points.is_empty() => {
+                draw_pdf_chart(&root, curve).unwrap();
+            }
+            _ => {
+                root.fill(&WHITE).unwrap();
+                let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
+                root.draw_text(
+                    "Click 'Recalculate' to compute G(r)",
+                    &style,
+                    (w / 2 - 160, h / 2),
+                )
+                .unwrap();
+            }
+        }
+    });
+
+    let cs_calc = curve_state.clone();
+    let da_calc = drawing_area.clone();
+    btn_calc.connect_clicked(move |_| {
+        let app_st = state.borrow();
+        let Some(structure) = &app_st.active_tab().structure else {
+            return;
+        };
+        if !structure.is_periodic {
+            crate::utils::console::log_warn(
+                "G(r) requires a periodic cell — this structure is a molecule",
+            );
+            return;
+        }
+        let radiation = if radiation_dd.selected() == 1 {
+            Radiation::Neutron
+        } else {
+            Radiation::XRay
+        };
+        let (r_max, sigma, q_damp) = (spin_rmax.value(), spin_sigma.value(), spin_qdamp.value());
+        let points = calculate_gr(structure, r_max, q_damp, sigma, radiation);
+        if points.is_empty() {
+            crate::utils::console::log_warn(
+                "G(r): average scattering power is zero for this composition",
+            );
+        }
+        *cs_calc.borrow_mut() = Some(PdfCurve {
+            points,
+            radiation,
+            r_max,
+            sigma,
+            q_damp,
+        });
+        da_calc.queue_draw();
+    });
+
+    // Export .gr
+    let cs_gr = curve_state.clone();
+    btn_export_gr.connect_clicked(move |_| {
+        let Some(curve) = cs_gr.borrow().clone() else {
+            return;
+        };
+        let native = FileChooserNative::new(
+            Some("Export G(r)"),
+            None::<&gtk4::Window>,
+            FileChooserAction::Save,
+            Some("Save"),
+            Some("Cancel"),
+        );
+        native.set_current_name("structure.gr");
+        native.connect_response(move |d, resp| {
+            if resp == ResponseType::Accept {
+                if let Some(p) = d.file().and_then(|f| f.path()) {
+                    match write_gr(&p, &curve) {
+                        Ok(()) => crate::utils::console::log_info(&format!(
+                            "G(r) saved to {}",
+                            p.display()
+                        )),
+                        Err(e) => {
+                            crate::utils::console::log_error(&format!("G(r) export failed: {}", e))
+                        }
+                    }
+                }
+            }
+        });
+        native.show();
+    });
+
+    // Export PDF
+    let cs_pdf = curve_state.clone();
+    btn_export_pdf.connect_clicked(move |_| {
+        let Some(curve) = cs_pdf.borrow().clone() else {
+            return;
+        };
+        let native = FileChooserNative::new(
+            Some("Export PDF"),
+            None::<&gtk4::Window>,
+            FileChooserAction::Save,
+            Some("Save"),
+            Some("Cancel"),
+        );
+        native.set_current_name("pair_distribution.pdf");
+        native.connect_response(move |d, resp| {
+            if resp == ResponseType::Accept {
+                if let Some(p) = d.file().and_then(|f| f.path()) {
+                    let w = 800.0;
+                    let h = 600.0;
+                    let surf = PdfSurface::new(w, h, &p).expect("PDF Error");
+                    let ctx = Context::new(&surf).expect("Context Error");
+                    let backend = CairoBackend::new(&ctx, (w as u32, h as u32)).unwrap();
+                    let root = backend.into_drawing_area();
+                    draw_pdf_chart(&root, &curve).unwrap();
+                    surf.finish();
+                    crate::utils::console::log_info("G(r) PDF saved.");
+                }
+            }
+        });
+        native.show();
+    });
+
+    root
+}
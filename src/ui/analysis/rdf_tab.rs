@@ -0,0 +1,281 @@
+// src/ui/analysis/rdf_tab.rs
+//
+// Radial distribution function g(r) of the active structure, for all atoms
+// or one element pair. Exports the plot as PDF.
+
+use crate::physics::analysis::rdf::compute_rdf;
+use crate::state::AppState;
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Button, DropDown, FileChooserAction, FileChooserNative, Frame, Grid, Label, Orientation,
+    ResponseType, SpinButton, StringList,
+};
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use cairo::{Context, PdfSurface};
+use plotters::backend::DrawingBackend;
+use plotters::prelude::*;
+use plotters_cairo::CairoBackend;
+
+/// An element pair to restrict the RDF to; `None` means all pairs.
+type PairChoice = Option<(String, String)>;
+
+#[derive(Clone)]
+struct RdfCurve {
+    points: Vec<(f64, f64)>,
+    /// "All pairs" or "A–B", for the caption.
+    label: String,
+    r_max: f64,
+}
+
+fn draw_rdf_chart<DB: DrawingBackend>(
+    root: &plotters::drawing::DrawingArea<DB, plotters::coord::Shift>,
+    curve: &RdfCurve,
+) -> Result<(), std::boxed::Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let hi = curve.points.iter().fold(1.0f64, |hi, (_, g)| hi.max(*g));
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            format!("g(r) — {}", curve.label),
+            ("sans-serif", 26).into_font(),
+        )
+        .margin(20)
+        .x_label_area_size(55)
+        .y_label_area_size(65)
+        .build_cartesian_2d(0.0..curve.r_max, 0.0..hi * 1.05)?;
+
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 18).into_font())
+        .axis_desc_style(("sans-serif", 22).into_font())
+        .x_desc("r (Å)")
+        .y_desc("g(r)")
+        .draw()?;
+
+    // g(r) = 1 is the uncorrelated limit the curve settles to.
+    chart.draw_series(LineSeries::new(
+        [(0.0, 1.0), (curve.r_max, 1.0)],
+        BLACK.mix(0.3),
+    ))?;
+    chart.draw_series(LineSeries::new(
+        curve.points.iter().copied(),
+        BLUE.stroke_width(2),
+    ))?;
+
+    Ok(())
+}
+
+/// Render `curve` onto an 800×600 pt PDF page at `path`.
+fn write_pdf(path: &Path, curve: &RdfCurve) -> Result<(), std::boxed::Box<dyn std::error::Error>> {
+    let (w, h) = (800.0, 600.0);
+    let surf = PdfSurface::new(w, h, path)?;
+    let ctx = Context::new(&surf)?;
+    let backend = CairoBackend::new(&ctx, (w as u32, h as u32))?;
+    draw_rdf_chart(&backend.into_drawing_area(), curve)?;
+    surf.finish();
+    Ok(())
+}
+
+/// "All pairs" followed by every unordered pair of the structure's
+/// elements, in order of first appearance.
+fn pair_choices(state: &AppState) -> Vec<PairChoice> {
+    let mut elements: Vec<String> = Vec::new();
+    if let Some(structure) = &state.active_tab().structure {
+        for atom in &structure.atoms {
+            if !elements.contains(&atom.element) {
+                elements.push(atom.element.clone());
+            }
+        }
+    }
+    let mut choices = vec![None];
+    for (i, a) in elements.iter().enumerate() {
+        for b in &elements[i..] {
+            choices.push(Some((a.clone(), b.clone())));
+        }
+    }
+    choices
+}
+
+fn pair_label(pair: &PairChoice) -> String {
+    match pair {
+        None => "All pairs".to_string(),
+        Some((a, b)) => format!("{}–{}", a, b),
+    }
+}
+
+pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
+    let root = gtk4::Box::new(Orientation::Horizontal, 15);
+    root.set_margin_top(15);
+    root.set_margin_bottom(15);
+    root.set_margin_start(15);
+    root.set_margin_end(15);
+
+    let curve_state: Rc<RefCell<Option<RdfCurve>>> = Rc::new(RefCell::new(None));
+    let pairs: Rc<RefCell<Vec<PairChoice>>> = Rc::new(RefCell::new(vec![None]));
+
+    // LEFT PANE (Plot)
+    let left_pane = gtk4::Box::new(Orientation::Vertical, 5);
+    left_pane.set_hexpand(true);
+
+    let frame_plot = Frame::new(Some(" Radial Distribution Function "));
+    let drawing_area = gtk4::DrawingArea::new();
+    drawing_area.set_content_width(600);
+    drawing_area.set_content_height(400);
+    drawing_area.set_hexpand(true);
+    drawing_area.set_vexpand(true);
+
+    frame_plot.set_child(Some(&drawing_area));
+    left_pane.append(&frame_plot);
+    root.append(&left_pane);
+
+    // RIGHT PANE (Controls)
+    let right_pane = gtk4::Box::new(Orientation::Vertical, 10);
+    right_pane.set_width_request(260);
+
+    let title = Label::new(Some("Settings"));
+    title.add_css_class("title-3");
+    title.set_halign(Align::Start);
+    right_pane.append(&title);
+
+    let frame_settings = Frame::new(None);
+    let grid = Grid::new();
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(10);
+    grid.set_margin_top(10);
+    grid.set_margin_bottom(10);
+    grid.set_margin_start(10);
+    grid.set_margin_end(10);
+
+    let pair_list = StringList::new(&["All pairs"]);
+    let pair_dd = DropDown::new(Some(pair_list.clone()), None::<&gtk4::Expression>);
+
+    let adj_rmax = gtk4::Adjustment::new(10.0, 2.0, 50.0, 1.0, 5.0, 0.0);
+    let spin_rmax = SpinButton::new(Some(&adj_rmax), 1.0, 1);
+
+    let adj_bin = gtk4::Adjustment::new(0.05, 0.01, 0.5, 0.01, 0.05, 0.0);
+    let spin_bin = SpinButton::new(Some(&adj_bin), 0.01, 2);
+
+    grid.attach(&Label::new(Some("Pair:")), 0, 0, 1, 1);
+    grid.attach(&pair_dd, 1, 0, 1, 1);
+    grid.attach(&Label::new(Some("r max (Å):")), 0, 1, 1, 1);
+    grid.attach(&spin_rmax, 1, 1, 1, 1);
+    grid.attach(&Label::new(Some("Bin width (Å):")), 0, 2, 1, 1);
+    grid.attach(&spin_bin, 1, 2, 1, 1);
+
+    frame_settings.set_child(Some(&grid));
+    right_pane.append(&frame_settings);
+
+    let btn_calc = Button::with_label("Recalculate");
+    btn_calc.add_css_class("suggested-action");
+    right_pane.append(&btn_calc);
+
+    let btn_export_pdf = Button::with_label("Export PDF");
+    right_pane.append(&btn_export_pdf);
+
+    root.append(&right_pane);
+
+    // LOGIC
+    let cs = curve_state.clone();
+    drawing_area.set_draw_func(move |_, ctx, w, h| {
+        let backend = CairoBackend::new(ctx, (w as u32, h as u32)).unwrap();
+        let root = backend.into_drawing_area();
+        match cs.borrow().as_ref() {
+            Some(curve) if !curve.points.is_empty() => {
+                draw_rdf_chart(&root, curve).unwrap();
+            }
+            _ => {
+                root.fill(&WHITE).unwrap();
+                let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
+                root.draw_text(
+                    "Click 'Recalculate' to compute g(r)",
+                    &style,
+                    (w / 2 - 160, h / 2),
+                )
+                .unwrap();
+            }
+        }
+    });
+
+    // The active tab can change while the window is open; list its
+    // element pairs afresh whenever the page is shown.
+    let state_map = state.clone();
+    let pairs_map = pairs.clone();
+    let dd_map = pair_dd.clone();
+    root.connect_map(move |_| {
+        let choices = pair_choices(&state_map.borrow());
+        let labels: Vec<String> = choices.iter().map(pair_label).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        pair_list.splice(0, pair_list.n_items(), &labels);
+        dd_map.set_selected(0);
+        *pairs_map.borrow_mut() = choices;
+    });
+
+    let cs_calc = curve_state.clone();
+    let da_calc = drawing_area.clone();
+    btn_calc.connect_clicked(move |_| {
+        let app_st = state.borrow();
+        let Some(structure) = &app_st.active_tab().structure else {
+            return;
+        };
+        if !structure.is_periodic {
+            crate::utils::console::log_warn(
+                "g(r) requires a periodic cell — this structure is a molecule",
+            );
+            return;
+        }
+        let pair = pairs
+            .borrow()
+            .get(pair_dd.selected() as usize)
+            .cloned()
+            .flatten();
+        let label = pair_label(&pair);
+        let (r_max, bin_width) = (spin_rmax.value(), spin_bin.value());
+        let points = compute_rdf(structure, r_max, bin_width, pair);
+        *cs_calc.borrow_mut() = Some(RdfCurve {
+            points,
+            label,
+            r_max,
+        });
+        da_calc.queue_draw();
+    });
+
+    // Export PDF
+    let cs_pdf = curve_state.clone();
+    btn_export_pdf.connect_clicked(move |_| {
+        let Some(curve) = cs_pdf.borrow().clone() else {
+            return;
+        };
+        let native = FileChooserNative::new(
+            Some("Export PDF"),
+            None::<&gtk4::Window>,
+            FileChooserAction::Save,
+            Some("Save"),
+            Some("Cancel"),
+        );
+        native.set_current_name("radial_distribution.pdf");
+        native.connect_response(move |d, resp| {
+            if resp == ResponseType::Accept {
+                if let Some(p) = d.file().and_then(|f| f.path()) {
+                    match write_pdf(&p, &curve) {
+                        Ok(()) => crate::utils::console::log_info("g(r) PDF saved."),
+                        Err(e) => crate::utils::console::log_error(&format!(
+                            "g(r) PDF export to '{}' failed: {}",
+                            p.display(),
+                            e
+                        )),
+                    }
+                }
+            }
+        });
+        native.show();
+    });
+
+    root
+}
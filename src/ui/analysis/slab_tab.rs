@@ -1,12 +1,15 @@
 // src/ui/analysis/slab_tab.rs
 
+use crate::config::RenderQuality;
 use crate::model::structure::Structure;
 use crate::physics::operations::miller_algo::MillerMath;
 use crate::physics::operations::slab;
+use crate::rendering::primitives::cairo_antialias;
 use crate::state::AppState;
+use crate::utils::linalg::PeriodicCell;
 use gtk4::prelude::*;
 use gtk4::{Box, Button, DrawingArea, Frame, Grid, Label, Orientation, SpinButton};
-use nalgebra::{Matrix3, Vector3};
+use nalgebra::Vector3;
 use std::cell::RefCell;
 use std::f64::consts::PI;
 use std::rc::Rc;
@@ -117,8 +120,20 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
     }));
 
     let draw_state = vis_state.clone();
+    let app_draw = state.clone();
 
     drawing_area.set_draw_func(move |_, cr, width, height| {
+        // Fast quality draws each atom once instead of with its boundary images.
+        let (antialias, reach) = {
+            let app = app_draw.borrow();
+            let reach = match app.config.render_quality {
+                RenderQuality::Fast => 0,
+                RenderQuality::High => 1,
+            };
+            (cairo_antialias(app.config.antialias_level), reach)
+        };
+        cr.set_antialias(antialias);
+
         // 1. White Background
         cr.set_source_rgb(1.0, 1.0, 1.0);
         cr.paint().expect("Failed to paint background");
@@ -144,21 +159,13 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
 
         // --- 2. Draw Atoms (Ghosted) ---
         if let Some(structure) = &st.structure {
-            let lat = structure.lattice;
-            let lat_mat = Matrix3::new(
-                lat[0][0], lat[0][1], lat[0][2], lat[1][0], lat[1][1], lat[1][2], lat[2][0],
-                lat[2][1], lat[2][2],
-            );
-
-            if let Some(inv_lat) = lat_mat.try_inverse() {
-                let to_frac = inv_lat.transpose();
+            if let Some(cell) = PeriodicCell::new(structure.lattice) {
                 for atom in &structure.atoms {
-                    let cart = Vector3::new(atom.position[0], atom.position[1], atom.position[2]);
-                    let frac = to_frac * cart;
+                    let frac = cell.to_frac(Vector3::from(atom.position));
 
-                    for dx in -1..=1 {
-                        for dy in -1..=1 {
-                            for dz in -1..=1 {
+                    for dx in -reach..=reach {
+                        for dy in -reach..=reach {
+                            for dz in -reach..=reach {
                                 let nx = frac.x + dx as f64;
                                 let ny = frac.y + dy as f64;
                                 let nz = frac.z + dz as f64;
@@ -313,6 +320,8 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
             match slab::generate_slab(structure, h, k, l, thick, vac) {
                 Ok(new_struct) => {
                     tab.structure = Some(new_struct);
+                    tab.modified = true;
+                    tab.displacement.clear();
                     lbl_gen.set_markup("<span color='green'>Slab generated.</span>");
                     btn_undo_gen.set_sensitive(true);
                 }
@@ -333,6 +342,8 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
         let tab = st.active_tab_mut();
         if let Some(backup) = undo_store_ref.borrow_mut().take() {
             tab.structure = Some(backup);
+            tab.modified = true;
+            tab.displacement.clear();
             lbl_undo.set_text("Undone.");
             btn_undo_ref.set_sensitive(false);
         }
@@ -0,0 +1,428 @@
+// src/ui/analysis/stereo_tab.rs
+//
+// Stereographic (or gnomonic) projection of plane poles and directions of
+// the active structure, viewed down Cartesian z. Each family added from the
+// controls is expanded with the point group of the detected space group,
+// drawn in its own colour, and listed in the legend. Exports as PDF like
+// the XRD tab.
+
+use crate::physics::analysis::stereographic::{
+    family_poles, label, Pole, PoleKind, Projection, GNOMONIC_LIMIT,
+};
+use crate::physics::analysis::symmetry::{self, point_group_rotations};
+use crate::state::AppState;
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Button, CheckButton, DropDown, FileChooserAction, FileChooserNative, Frame, Grid, Label,
+    Orientation, ResponseType, SpinButton,
+};
+use nalgebra::Matrix3;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::path::Path;
+use std::rc::Rc;
+
+use cairo::{Context, PdfSurface};
+use plotters::backend::DrawingBackend;
+use plotters::prelude::*;
+use plotters::style::full_palette::GREY;
+use plotters_cairo::CairoBackend;
+
+/// One plotted plane or direction family, with the cell it was added on.
+#[derive(Clone)]
+struct Family {
+    kind: PoleKind,
+    indices: [i32; 3],
+    lattice: [[f64; 3]; 3],
+    /// Point-group rotations of that structure; empty when symmetry
+    /// expansion was off or detection failed.
+    rotations: Vec<Matrix3<i32>>,
+}
+
+#[derive(Clone)]
+struct StereoPlot {
+    families: Vec<Family>,
+    projection: Projection,
+}
+
+/// `{h k l}` for a plane family, `<u v w>` for a direction family.
+fn family_label(kind: PoleKind, indices: [i32; 3]) -> String {
+    let [a, b, c] = indices;
+    match kind {
+        PoleKind::Plane => format!("{{{} {} {}}}", a, b, c),
+        PoleKind::Direction => format!("<{} {} {}>", a, b, c),
+    }
+}
+
+fn projection_label(projection: Projection) -> &'static str {
+    match projection {
+        Projection::Stereographic => "Stereographic",
+        Projection::Gnomonic => "Gnomonic",
+    }
+}
+
+fn draw_projection_chart<DB: DrawingBackend>(
+    root: &plotters::drawing::DrawingArea<DB, plotters::coord::Shift>,
+    plot: &StereoPlot,
+) -> Result<(), std::boxed::Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let area = root.titled(
+        &format!("{} projection along z", projection_label(plot.projection)),
+        ("sans-serif", 26).into_font(),
+    )?;
+
+    // Square plotting area so the primitive circle stays round.
+    let (w, h) = area.dim_in_pixel();
+    let side = w.min(h);
+    let area = area.shrink(((w - side) / 2, (h - side) / 2), (side, side));
+
+    let lim = match plot.projection {
+        Projection::Stereographic => 1.1,
+        Projection::Gnomonic => GNOMONIC_LIMIT * 1.05,
+    };
+    let mut chart = ChartBuilder::on(&area)
+        .margin(20)
+        .build_cartesian_2d(-lim..lim, -lim..lim)?;
+
+    let circle = |r: f64| {
+        (0..=360).map(move |d| {
+            let t = d as f64 * PI / 180.0;
+            (r * t.cos(), r * t.sin())
+        })
+    };
+    chart.draw_series(LineSeries::new([(-lim, 0.0), (lim, 0.0)], GREY.mix(0.6)))?;
+    chart.draw_series(LineSeries::new([(0.0, -lim), (0.0, lim)], GREY.mix(0.6)))?;
+    match plot.projection {
+        Projection::Stereographic => {
+            chart.draw_series(LineSeries::new(circle(1.0), BLACK.stroke_width(2)))?;
+        }
+        Projection::Gnomonic => {
+            // Guides at 30°, 45° and 60° from the centre.
+            for deg in [30.0_f64, 45.0, 60.0] {
+                let r = deg.to_radians().tan();
+                chart.draw_series(LineSeries::new(circle(r), GREY.mix(0.6)))?;
+            }
+        }
+    }
+
+    for (i, family) in plot.families.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let poles = family_poles(
+            family.lattice,
+            family.kind,
+            family.indices,
+            &family.rotations,
+            plot.projection,
+        );
+        let style = |p: &Pole| {
+            if p.upper {
+                color.filled()
+            } else {
+                color.stroke_width(2)
+            }
+        };
+        let text = |p: &Pole| {
+            Text::new(
+                label(p.kind, p.indices),
+                (6, -16),
+                ("sans-serif", 12).into_font(),
+            )
+        };
+        let legend = family_label(family.kind, family.indices);
+        match family.kind {
+            PoleKind::Plane => {
+                chart
+                    .draw_series(poles.iter().map(|p| {
+                        EmptyElement::at((p.x, p.y)) + Circle::new((0, 0), 5, style(p)) + text(p)
+                    }))?
+                    .label(legend)
+                    .legend(move |(x, y)| Circle::new((x + 10, y), 5, color.filled()));
+            }
+            PoleKind::Direction => {
+                chart
+                    .draw_series(poles.iter().map(|p| {
+                        EmptyElement::at((p.x, p.y))
+                            + TriangleMarker::new((0, 0), 6, style(p))
+                            + text(p)
+                    }))?
+                    .label(legend)
+                    .legend(move |(x, y)| TriangleMarker::new((x + 10, y), 6, color.filled()));
+            }
+        }
+    }
+
+    if !plot.families.is_empty() {
+        chart
+            .configure_series_labels()
+            .label_font(("sans-serif", 16).into_font())
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+
+    Ok(())
+}
+
+pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
+    let root = gtk4::Box::new(Orientation::Horizontal, 15);
+    root.set_margin_top(15);
+    root.set_margin_bottom(15);
+    root.set_margin_start(15);
+    root.set_margin_end(15);
+
+    let plot_state: Rc<RefCell<Option<StereoPlot>>> = Rc::new(RefCell::new(None));
+
+    // LEFT PANE (Plot)
+    let left_pane = gtk4::Box::new(Orientation::Vertical, 5);
+    left_pane.set_hexpand(true);
+
+    let frame_plot = Frame::new(Some(" Crystallographic Projection "));
+    let drawing_area = gtk4::DrawingArea::new();
+    drawing_area.set_content_width(600);
+    drawing_area.set_content_height(500);
+    drawing_area.set_hexpand(true);
+    drawing_area.set_vexpand(true);
+
+    frame_plot.set_child(Some(&drawing_area));
+    left_pane.append(&frame_plot);
+    root.append(&left_pane);
+
+    // RIGHT PANE (Controls)
+    let right_pane = gtk4::Box::new(Orientation::Vertical, 10);
+    right_pane.set_width_request(260);
+
+    let title = Label::new(Some("Settings"));
+    title.add_css_class("title-3");
+    title.set_halign(Align::Start);
+    right_pane.append(&title);
+
+    let frame_settings = Frame::new(None);
+    let grid = Grid::new();
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(10);
+    grid.set_margin_top(10);
+    grid.set_margin_bottom(10);
+    grid.set_margin_start(10);
+    grid.set_margin_end(10);
+
+    let projection_dd = DropDown::from_strings(&["Stereographic", "Gnomonic"]);
+    let kind_dd = DropDown::from_strings(&["Plane (hkl)", "Direction [uvw]"]);
+
+    let index_box = gtk4::Box::new(Orientation::Horizontal, 5);
+    let index_spins: Vec<SpinButton> = [0.0, 0.0, 1.0]
+        .iter()
+        .map(|v| {
+            let spin = SpinButton::with_range(-9.0, 9.0, 1.0);
+            spin.set_digits(0);
+            spin.set_value(*v);
+            index_box.append(&spin);
+            spin
+        })
+        .collect();
+
+    let check_sym = CheckButton::with_label("Add symmetry equivalents");
+    check_sym.set_active(true);
+
+    grid.attach(&Label::new(Some("Projection:")), 0, 0, 1, 1);
+    grid.attach(&projection_dd, 1, 0, 1, 1);
+    grid.attach(&Label::new(Some("Pole type:")), 0, 1, 1, 1);
+    grid.attach(&kind_dd, 1, 1, 1, 1);
+    grid.attach(&Label::new(Some("Indices:")), 0, 2, 1, 1);
+    grid.attach(&index_box, 1, 2, 1, 1);
+    grid.attach(&check_sym, 0, 3, 2, 1);
+
+    frame_settings.set_child(Some(&grid));
+    right_pane.append(&frame_settings);
+
+    let btn_add = Button::with_label("Add Family");
+    btn_add.add_css_class("suggested-action");
+    right_pane.append(&btn_add);
+
+    let btn_clear = Button::with_label("Clear");
+    right_pane.append(&btn_clear);
+
+    let btn_export = Button::with_label("Export PDF");
+    right_pane.append(&btn_export);
+
+    let status = Label::new(None);
+    status.set_wrap(true);
+    status.set_xalign(0.0);
+    right_pane.append(&status);
+
+    root.append(&right_pane);
+
+    // LOGIC
+    let ps = plot_state.clone();
+    drawing_area.set_draw_func(move |_, ctx, w, h| {
+        let backend = CairoBackend::new(ctx, (w as u32, h as u32)).unwrap();
+        let root = backend.into_drawing_area();
+        match ps.borrow().as_ref() {
+            Some(plot) => {
+                draw_projection_chart(&root, plot).unwrap();
+            }
+            None => {
+                root.fill(&WHITE).unwrap();
+                let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
+                root.draw_text(
+                    "Add a plane or direction family to plot its poles",
+                    &style,
+                    (w / 2 - 220, h / 2),
+                )
+                .unwrap();
+            }
+        }
+    });
+
+    let ps_proj = plot_state.clone();
+    let da_proj = drawing_area.clone();
+    projection_dd.connect_selected_notify(move |dd| {
+        if let Some(plot) = ps_proj.borrow_mut().as_mut() {
+            plot.projection = selected_projection(dd);
+        }
+        da_proj.queue_draw();
+    });
+
+    let ps_add = plot_state.clone();
+    let da_add = drawing_area.clone();
+    let status_add = status.clone();
+    btn_add.connect_clicked(move |_| {
+        let app_st = state.borrow();
+        let Some(structure) = &app_st.active_tab().structure else {
+            return;
+        };
+        if !structure.is_periodic {
+            crate::utils::console::log_warn(
+                "Projections require a periodic cell — this structure is a molecule",
+            );
+            return;
+        }
+        let indices = [
+            index_spins[0].value_as_int(),
+            index_spins[1].value_as_int(),
+            index_spins[2].value_as_int(),
+        ];
+        if indices == [0, 0, 0] {
+            status_add.set_text("Indices must not all be zero.");
+            return;
+        }
+        let kind = if kind_dd.selected() == 1 {
+            PoleKind::Direction
+        } else {
+            PoleKind::Plane
+        };
+
+        let rotations = if check_sym.is_active() {
+            match point_group_rotations(structure) {
+                Ok(r) => {
+                    let group = symmetry::analyze(structure)
+                        .map(|info| format!("{} ({})", info.symbol, info.number))
+                        .unwrap_or_default();
+                    status_add.set_text(&format!(
+                        "Equivalents from {} point-group rotations {}",
+                        r.len(),
+                        group
+                    ));
+                    r
+                }
+                Err(e) => {
+                    crate::utils::console::log_warn(&format!(
+                        "Projection: symmetry detection failed ({}); plotting single poles",
+                        e
+                    ));
+                    status_add.set_text("Symmetry detection failed; single poles only.");
+                    Vec::new()
+                }
+            }
+        } else {
+            status_add.set_text("");
+            Vec::new()
+        };
+
+        // Each family keeps the cell it was added on, so families from
+        // different tabs can share one plot. Adding a family again moves it
+        // onto the current structure.
+        let family = Family {
+            kind,
+            indices,
+            lattice: structure.lattice,
+            rotations,
+        };
+        let mut ps = ps_add.borrow_mut();
+        let plot = ps.get_or_insert_with(|| StereoPlot {
+            families: Vec::new(),
+            projection: selected_projection(&projection_dd),
+        });
+        match plot
+            .families
+            .iter_mut()
+            .find(|f| f.kind == kind && f.indices == indices)
+        {
+            Some(existing) => *existing = family,
+            None => plot.families.push(family),
+        }
+        da_add.queue_draw();
+    });
+
+    let ps_clear = plot_state.clone();
+    let da_clear = drawing_area.clone();
+    btn_clear.connect_clicked(move |_| {
+        *ps_clear.borrow_mut() = None;
+        status.set_text("");
+        da_clear.queue_draw();
+    });
+
+    // Export PDF
+    let ps_pdf = plot_state.clone();
+    btn_export.connect_clicked(move |_| {
+        let Some(plot) = ps_pdf.borrow().clone() else {
+            return;
+        };
+        let native = FileChooserNative::new(
+            Some("Export PDF"),
+            None::<&gtk4::Window>,
+            FileChooserAction::Save,
+            Some("Save"),
+            Some("Cancel"),
+        );
+        native.set_current_name("stereogram.pdf");
+        native.connect_response(move |d, resp| {
+            if resp == ResponseType::Accept {
+                if let Some(p) = d.file().and_then(|f| f.path()) {
+                    match write_pdf(&p, &plot) {
+                        Ok(()) => crate::utils::console::log_info("Projection PDF saved."),
+                        Err(e) => crate::utils::console::log_error(&format!(
+                            "Projection PDF export to '{}' failed: {}",
+                            p.display(),
+                            e
+                        )),
+                    }
+                }
+            }
+        });
+        native.show();
+    });
+
+    root
+}
+
+/// Render `plot` onto a 700×700 pt PDF page at `path`.
+fn write_pdf(path: &Path, plot: &StereoPlot) -> Result<(), std::boxed::Box<dyn std::error::Error>> {
+    let (w, h) = (700.0, 700.0);
+    let surf = PdfSurface::new(w, h, path)?;
+    let ctx = Context::new(&surf)?;
+    let backend = CairoBackend::new(&ctx, (w as u32, h as u32))?;
+    draw_projection_chart(&backend.into_drawing_area(), plot)?;
+    surf.finish();
+    Ok(())
+}
+
+fn selected_projection(dd: &DropDown) -> Projection {
+    if dd.selected() == 1 {
+        Projection::Gnomonic
+    } else {
+        Projection::Stereographic
+    }
+}
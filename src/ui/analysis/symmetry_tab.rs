@@ -1,5 +1,7 @@
+use crate::model::symmetry::niggli_reduce;
 use crate::state::AppState;
 use crate::utils::geometry;
+use crate::utils::linalg::is_singular;
 use gtk4::prelude::*;
 use gtk4::{Align, Box, Grid, Label, Orientation, PolicyType, ScrolledWindow, Separator};
 use nalgebra::{Matrix3, Vector3};
@@ -75,22 +77,22 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
     grid_lat.set_column_spacing(15);
     grid_lat.set_margin_top(5);
 
-    let mk_lat_row = |txt: &str, col: i32, row: i32| -> Label {
+    let mk_lat_row = |grid: &Grid, txt: &str, col: i32, row: i32| -> Label {
         let l = Label::builder().label(txt).halign(Align::Start).build();
         l.set_markup(&format!("<b>{}</b>", txt));
-        grid_lat.attach(&l, col, row, 1, 1);
+        grid.attach(&l, col, row, 1, 1);
         let v = Label::new(Some("-"));
         v.set_halign(Align::Start);
-        grid_lat.attach(&v, col + 1, row, 1, 1);
+        grid.attach(&v, col + 1, row, 1, 1);
         v
     };
 
-    let val_a = mk_lat_row("a:", 0, 0);
-    let val_al = mk_lat_row("α:", 2, 0);
-    let val_b = mk_lat_row("b:", 0, 1);
-    let val_be = mk_lat_row("β:", 2, 1);
-    let val_c = mk_lat_row("c:", 0, 2);
-    let val_ga = mk_lat_row("γ:", 2, 2);
+    let val_a = mk_lat_row(&grid_lat, "a:", 0, 0);
+    let val_al = mk_lat_row(&grid_lat, "α:", 2, 0);
+    let val_b = mk_lat_row(&grid_lat, "b:", 0, 1);
+    let val_be = mk_lat_row(&grid_lat, "β:", 2, 1);
+    let val_c = mk_lat_row(&grid_lat, "c:", 0, 2);
+    let val_ga = mk_lat_row(&grid_lat, "γ:", 2, 2);
 
     let l_vol = Label::builder().label("Vol:").halign(Align::Start).build();
     l_vol.set_markup("<b>Vol:</b>");
@@ -99,7 +101,42 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
     val_vol.set_halign(Align::Start);
     grid_lat.attach(&val_vol, 1, 3, 3, 1);
 
-    right_frame.append(&grid_lat);
+    let l_rho = Label::builder().label("Density:").halign(Align::Start).build();
+    l_rho.set_markup("<b>Density:</b>");
+    grid_lat.attach(&l_rho, 0, 4, 1, 1);
+    let val_rho = Label::new(Some("-"));
+    val_rho.set_halign(Align::Start);
+    grid_lat.attach(&val_rho, 1, 4, 3, 1);
+
+    // Niggli-reduced parameters beside the cell as given: same lattice and
+    // volume, so only the six parameters differ.
+    let grid_niggli = Grid::new();
+    grid_niggli.set_row_spacing(6);
+    grid_niggli.set_column_spacing(15);
+    grid_niggli.set_margin_top(5);
+    let niggli_vals = [
+        mk_lat_row(&grid_niggli, "a:", 0, 0),
+        mk_lat_row(&grid_niggli, "b:", 0, 1),
+        mk_lat_row(&grid_niggli, "c:", 0, 2),
+        mk_lat_row(&grid_niggli, "α:", 2, 0),
+        mk_lat_row(&grid_niggli, "β:", 2, 1),
+        mk_lat_row(&grid_niggli, "γ:", 2, 2),
+    ];
+    let niggli_note = Label::new(None);
+    niggli_note.set_halign(Align::Start);
+    grid_niggli.attach(&niggli_note, 0, 3, 4, 1);
+
+    let cells_box = Box::new(Orientation::Horizontal, 25);
+    for (caption, grid) in [("As Given", &grid_lat), ("Niggli-Reduced", &grid_niggli)] {
+        let column = Box::new(Orientation::Vertical, 2);
+        let l = Label::new(None);
+        l.set_markup(&format!("<i>{}</i>", caption));
+        l.set_halign(Align::Start);
+        column.append(&l);
+        column.append(grid);
+        cells_box.append(&column);
+    }
+    right_frame.append(&cells_box);
     top_box.append(&right_frame);
 
     root.append(&Separator::new(Orientation::Horizontal));
@@ -151,32 +188,58 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
         let lat = structure.lattice;
 
         // 1. LATTICE DISPLAY
-        let a_vec = lat[0];
-        let b_vec = lat[1];
-        let c_vec = lat[2];
-        let origin = [0.0, 0.0, 0.0];
-
-        // Use shared geometry utils
-        let a = geometry::calculate_distance(origin, a_vec);
-        let b = geometry::calculate_distance(origin, b_vec);
-        let c = geometry::calculate_distance(origin, c_vec);
-
-        let alpha = geometry::calculate_angle(b_vec, origin, c_vec);
-        let beta = geometry::calculate_angle(a_vec, origin, c_vec);
-        let gamma = geometry::calculate_angle(a_vec, origin, b_vec);
-
-        let cx = b_vec[1] * c_vec[2] - b_vec[2] * c_vec[1];
-        let cy = b_vec[2] * c_vec[0] - b_vec[0] * c_vec[2];
-        let cz = b_vec[0] * c_vec[1] - b_vec[1] * c_vec[0];
-        let vol = (a_vec[0] * cx + a_vec[1] * cy + a_vec[2] * cz).abs();
-
-        val_a.set_text(&format!("{:.4}", a));
-        val_b.set_text(&format!("{:.4}", b));
-        val_c.set_text(&format!("{:.4}", c));
-        val_al.set_text(&format!("{:.2}°", alpha));
-        val_be.set_text(&format!("{:.2}°", beta));
-        val_ga.set_text(&format!("{:.2}°", gamma));
-        val_vol.set_text(&format!("{:.2} Å³", vol));
+        match geometry::cell_parameters(lat) {
+            Ok(cell) => {
+                let [a, b, c] = cell.lengths;
+                let [alpha, beta, gamma] = cell.angles;
+                val_a.set_text(&format!("{:.4}", a));
+                val_b.set_text(&format!("{:.4}", b));
+                val_c.set_text(&format!("{:.4}", c));
+                val_al.set_text(&format!("{:.2}°", alpha));
+                val_be.set_text(&format!("{:.2}°", beta));
+                val_ga.set_text(&format!("{:.2}°", gamma));
+                val_vol.set_text(&format!("{:.2} Å³", cell.volume));
+            }
+            Err(e) => {
+                for val in [&val_a, &val_b, &val_c, &val_al, &val_be, &val_ga] {
+                    val.set_text("n/a");
+                }
+                val_vol.set_text(&format!("Degenerate cell: {}", e));
+            }
+        }
+        let reduced = niggli_reduce(lat);
+        match geometry::cell_parameters(reduced) {
+            Ok(cell) if structure.is_periodic => {
+                let [a, b, c] = cell.lengths;
+                let [alpha, beta, gamma] = cell.angles;
+                let texts = [
+                    format!("{:.4}", a),
+                    format!("{:.4}", b),
+                    format!("{:.4}", c),
+                    format!("{:.2}°", alpha),
+                    format!("{:.2}°", beta),
+                    format!("{:.2}°", gamma),
+                ];
+                for (val, text) in niggli_vals.iter().zip(&texts) {
+                    val.set_text(text);
+                }
+                niggli_note.set_text(if reduced == lat {
+                    "Cell is already reduced."
+                } else {
+                    ""
+                });
+            }
+            _ => {
+                for val in &niggli_vals {
+                    val.set_text("n/a");
+                }
+                niggli_note.set_text("");
+            }
+        }
+        match structure.density_g_cm3() {
+            Some(rho) => val_rho.set_text(&format!("{:.3} g/cm³", rho)),
+            None => val_rho.set_text("n/a"),
+        }
 
         // 2. COORDINATE DISPLAY
         let lattice_mat = Matrix3::new(
@@ -184,7 +247,7 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
             lat[2][2],
         );
 
-        if let Some(inv_mat) = lattice_mat.try_inverse() {
+        if let Some(inv_mat) = lattice_mat.try_inverse().filter(|_| !is_singular(lat)) {
             for (i, atom) in structure.atoms.iter().enumerate() {
                 let v_cart = Vector3::new(atom.position[0], atom.position[1], atom.position[2]);
                 let v_frac = inv_mat.transpose() * v_cart;
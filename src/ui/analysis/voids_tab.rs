@@ -1,16 +1,22 @@
+use crate::config::{ProbePreset, RenderQuality};
 use crate::model::structure::Structure;
+use crate::rendering::primitives::cairo_antialias;
 use crate::state::AppState;
+use crate::utils::linalg::cart_to_frac;
 use gtk4::prelude::*;
 use gtk4::{
-    Align, Box, Button, DrawingArea, DropDown, Frame, Grid, Label, Orientation, Separator,
-    SpinButton, StringList,
+    glib, Align, Box, Button, DrawingArea, DropDown, Frame, Grid, Label, Orientation, ProgressBar,
+    Separator, SpinButton, StringList,
 };
 use std::cell::RefCell;
 use std::f64::consts::PI;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
 // Import constants and types from Physics
-use crate::physics::analysis::voids::{self, RadiusType, VoidConfig, VoidResult};
+use crate::physics::analysis::voids::{self, RadiusType, VoidConfig, VoidError, VoidResult};
 
 struct VoidsVisState {
     structure: Option<Structure>,
@@ -26,6 +32,94 @@ struct DrawableAtom {
     is_void: bool,
 }
 
+/// (Re)populate the preset buttons, four per row. Each sets the probe radius.
+fn fill_probe_grid(grid: &Grid, spin_probe: &SpinButton, state: &Rc<RefCell<AppState>>) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+    let presets = state.borrow().config.all_probe_presets();
+    for (i, preset) in presets.iter().enumerate() {
+        let btn = Button::with_label(&preset.name);
+        btn.set_tooltip_text(Some(&format!("r = {:.2} Å", preset.radius)));
+        let sp = spin_probe.clone();
+        let r_val = preset.radius;
+        btn.connect_clicked(move |_| sp.set_value(r_val));
+        grid.attach(&btn, (i % 4) as i32, (i / 4) as i32, 1, 1);
+    }
+}
+
+/// Name prompt for saving the current radius as a user probe. "Delete"
+/// removes the user probe with the entered name; built-ins stay.
+fn show_probe_preset_dialog(
+    btn: &Button,
+    state: &Rc<RefCell<AppState>>,
+    grid: &Grid,
+    spin_probe: &SpinButton,
+) {
+    let dialog = gtk4::Dialog::builder()
+        .title("Save Probe Preset")
+        .modal(true)
+        .default_width(300)
+        .build();
+    if let Some(win) = btn.root().and_then(|r| r.downcast::<gtk4::Window>().ok()) {
+        dialog.set_transient_for(Some(&win));
+    }
+    let content = dialog.content_area();
+    content.set_margin_top(10);
+    content.set_margin_bottom(10);
+    content.set_margin_start(10);
+    content.set_margin_end(10);
+    content.set_spacing(6);
+    content.append(
+        &Label::builder()
+            .label(format!("Probe radius: {:.2} Å", spin_probe.value()))
+            .halign(Align::Start)
+            .build(),
+    );
+    let entry = gtk4::Entry::new();
+    entry.set_placeholder_text(Some("Preset name, e.g. Na⁺"));
+    entry.set_activates_default(true);
+    content.append(&entry);
+    dialog.add_button("Cancel", gtk4::ResponseType::Cancel);
+    dialog.add_button("Delete", gtk4::ResponseType::Reject);
+    dialog.add_button("Save", gtk4::ResponseType::Ok);
+    dialog.set_default_response(gtk4::ResponseType::Ok);
+
+    let state = state.clone();
+    let grid = grid.clone();
+    let spin = spin_probe.clone();
+    dialog.connect_response(move |dlg, resp| {
+        let name = entry.text().trim().to_string();
+        if name.is_empty() || !matches!(resp, gtk4::ResponseType::Ok | gtk4::ResponseType::Reject) {
+            dlg.close();
+            return;
+        }
+        let msg = {
+            let mut st = state.borrow_mut();
+            let changed = if resp == gtk4::ResponseType::Ok {
+                st.config.upsert_probe_preset(ProbePreset {
+                    name: name.clone(),
+                    radius: spin.value(),
+                });
+                true
+            } else {
+                st.config.remove_probe_preset(&name)
+            };
+            changed.then(|| st.save_config())
+        };
+        match msg {
+            Some(msg) => crate::utils::console::log_info(&msg),
+            None => crate::utils::console::log_warn(&format!(
+                "No saved probe named \"{}\" (built-in probes cannot be deleted)",
+                name
+            )),
+        }
+        fill_probe_grid(&grid, &spin, &state);
+        dlg.close();
+    });
+    dialog.show();
+}
+
 pub fn build(state: Rc<RefCell<AppState>>) -> Box {
     let root = Box::new(Orientation::Horizontal, 15);
     root.set_margin_top(15);
@@ -95,20 +189,40 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
     row_probe.append(&spin_probe);
     ctrl_box.append(&row_probe);
 
-    // 5. Dynamic Probe Buttons (Source: Physics)
+    // 5. Probe Buttons: built-ins (Source: Physics) + user presets (Config)
     let grid_probes = Grid::builder().row_spacing(5).column_spacing(5).build();
-    for (i, (name, rad)) in voids::PRESET_PROBES.iter().enumerate() {
-        let btn = Button::with_label(name);
-        let sp = spin_probe.clone();
-        let r_val = *rad;
-        btn.connect_clicked(move |_| sp.set_value(r_val));
-        grid_probes.attach(&btn, (i % 4) as i32, (i / 4) as i32, 1, 1);
-    }
+    fill_probe_grid(&grid_probes, &spin_probe, &state);
     ctrl_box.append(&grid_probes);
 
+    let btn_save_probe = Button::with_label("Save Probe…");
+    btn_save_probe.set_tooltip_text(Some(
+        "Save the current probe radius as a named preset, or delete a saved one",
+    ));
+    btn_save_probe.set_halign(Align::Start);
+    ctrl_box.append(&btn_save_probe);
+    {
+        let state = state.clone();
+        let grid = grid_probes.clone();
+        let spin = spin_probe.clone();
+        btn_save_probe.connect_clicked(move |btn| {
+            show_probe_preset_dialog(btn, &state, &grid, &spin);
+        });
+    }
+
+    let row_calc = Box::new(Orientation::Horizontal, 10);
     let btn_calc = Button::with_label("Calculate");
     btn_calc.add_css_class("suggested-action");
-    ctrl_box.append(&btn_calc);
+    btn_calc.set_hexpand(true);
+    row_calc.append(&btn_calc);
+    let btn_cancel = Button::with_label("Cancel");
+    btn_cancel.set_sensitive(false);
+    row_calc.append(&btn_cancel);
+    ctrl_box.append(&row_calc);
+
+    let progress = ProgressBar::new();
+    progress.set_show_text(true);
+    progress.set_visible(false);
+    ctrl_box.append(&progress);
 
     right_pane.append(&ctrl_box);
     right_pane.append(&Separator::new(Orientation::Horizontal));
@@ -135,6 +249,15 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
     add_res(0, "Max Radius:", &val_r);
     add_res(1, "Diameter:", &val_d);
     add_res(2, "Void Vol %:", &val_vol);
+    let val_abs = Label::new(Some("-"));
+    add_res(3, "Void Volume:", &val_abs);
+    let val_spec = Label::new(Some("-"));
+    val_spec.set_tooltip_text(Some(
+        "Specific pore volume, comparable to gas adsorption / pycnometry",
+    ));
+    add_res(4, "Pore Volume:", &val_spec);
+    let val_perc = Label::builder().label("-").halign(Align::Start).build();
+    add_res(5, "Percolation:", &val_perc);
 
     right_pane.append(&res_grid);
     right_pane.append(
@@ -151,6 +274,22 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
         .build();
     right_pane.append(&val_cand);
 
+    right_pane.append(
+        &Label::builder()
+            .label("Cavities (largest first):")
+            .halign(Align::Start)
+            .margin_top(8)
+            .build(),
+    );
+    let val_sites = Label::builder()
+        .label("-")
+        .halign(Align::Start)
+        .wrap(true)
+        .selectable(true)
+        .build();
+    val_sites.add_css_class("monospace");
+    right_pane.append(&val_sites);
+
     root.append(&right_pane);
 
     // --- INTERACTION LOGIC ---
@@ -158,71 +297,198 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
         structure: state.borrow().active_tab().structure.clone(),
         result: None,
     }));
-    let state_c = state.clone();
     let vis_c = vis_state.clone();
     let da_c = drawing_area.clone();
-
-    btn_calc.connect_clicked(move |_| {
-        let st = state_c.borrow();
-        if let Some(structure) = &st.active_tab().structure {
-            // Map Index -> Enum
-            let idx = drop_type.selected();
-            let r_type = match idx {
-                0 => RadiusType::Ionic,
-                1 => RadiusType::VanDerWaals,
-                _ => RadiusType::Covalent,
-            };
-
-            // Create Config
-            let config = VoidConfig {
-                grid_resolution: spin_res.value(),
-                probe_radius: spin_probe.value(),
-                radii_scale: spin_scale.value(),
-                radius_type: r_type,
-                max_grid_points: 10_000_000,
-            };
-
-            // Calculate & Handle Result
-            match voids::calculate_voids(structure, config) {
-                Ok(result) => {
-                    let r_max = result.max_sphere_radius;
-
-                    val_r.set_text(&format!("{:.3} Å", r_max));
-                    val_d.set_text(&format!("{:.3} Å", r_max * 2.0));
-                    val_vol.set_text(&format!("{:.2} %", result.void_fraction));
-
-                    if r_max > 0.0 {
-                        let mut fits = Vec::new();
-                        for (ion, rad) in voids::CANDIDATE_IONS {
-                            if *rad <= r_max {
-                                fits.push(*ion);
-                            }
-                        }
-                        if fits.is_empty() {
-                            val_cand.set_markup("<span color='orange'>None (Too Small)</span>");
-                        } else {
-                            val_cand.set_markup(&format!("<b>{}</b>", fits.join(", ")));
+    let show_result =
+        move |structure: Structure, outcome: Result<VoidResult, VoidError>| match outcome {
+            Ok(result) => {
+                let r_max = result.max_sphere_radius;
+
+                val_r.set_text(&format!("{:.3} Å", r_max));
+                val_d.set_text(&format!("{:.3} Å", r_max * 2.0));
+                val_vol.set_text(&format!("{:.2} %", result.void_fraction));
+                val_abs.set_text(&format!("{:.2} Å³/cell", result.void_volume));
+                val_spec.set_text(&match result.specific_pore_volume {
+                    Some(v) => format!("{:.4} cm³/g", v),
+                    None => "-".to_string(),
+                });
+
+                let perc = &result.percolation;
+                let connectivity = match perc.dimensionality {
+                    0 => "none (isolated pockets)",
+                    1 => "1D channels",
+                    2 => "2D layers",
+                    _ => "3D network",
+                };
+                val_perc.set_text(&match perc.bottleneck_radius {
+                    Some(r) => format!("{}\nbottleneck r = {:.3} Å", connectivity, r),
+                    None => connectivity.to_string(),
+                });
+
+                if r_max > 0.0 {
+                    let mut fits = Vec::new();
+                    for (ion, rad) in voids::CANDIDATE_IONS {
+                        if *rad <= r_max {
+                            fits.push(*ion);
                         }
+                    }
+                    if fits.is_empty() {
+                        val_cand.set_markup("<span color='orange'>None (Too Small)</span>");
                     } else {
-                        val_cand.set_markup("<span color='red'>Overlap Detected</span>");
+                        val_cand.set_markup(&format!("<b>{}</b>", fits.join(", ")));
                     }
+                } else {
+                    val_cand.set_markup("<span color='red'>Overlap Detected</span>");
+                }
+
+                let lines: Vec<String> = result
+                    .sites
+                    .iter()
+                    .enumerate()
+                    .map(|(n, site)| {
+                        let ions: Vec<&str> =
+                            site.fitting_ions().iter().map(|(ion, _)| *ion).collect();
+                        format!(
+                            "#{} r = {:.3} Å at ({:.3}, {:.3}, {:.3})\n    fits: {}",
+                            n + 1,
+                            site.radius,
+                            site.frac[0],
+                            site.frac[1],
+                            site.frac[2],
+                            if ions.is_empty() {
+                                "-".to_string()
+                            } else {
+                                ions.join(", ")
+                            }
+                        )
+                    })
+                    .collect();
+                let joined = lines.join("\n");
+                val_sites.set_text(if lines.is_empty() { "-" } else { &joined });
+
+                let mut vs = vis_c.borrow_mut();
+                vs.structure = Some(structure);
+                vs.result = Some(result);
+                da_c.queue_draw();
+            }
+            Err(VoidError::Cancelled) => {
+                val_cand.set_markup("<span color='orange'>Cancelled</span>");
+            }
+            Err(e) => {
+                val_cand.set_markup(&format!("<span color='red'>Error: {}</span>", e));
+                val_r.set_text("-");
+                val_abs.set_text("-");
+                val_spec.set_text("-");
+                val_perc.set_text("-");
+                val_sites.set_text("-");
+            }
+        };
+
+    // The grid scan runs on a worker thread; the main loop polls its slice
+    // counter for the progress bar and picks the result up from a channel.
+    let cancel_flag: Rc<RefCell<Option<Arc<AtomicBool>>>> = Rc::new(RefCell::new(None));
+    let cancel_c = cancel_flag.clone();
+    btn_cancel.connect_clicked(move |btn| {
+        if let Some(flag) = cancel_c.borrow().as_ref() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        btn.set_sensitive(false);
+    });
 
-                    let mut vs = vis_c.borrow_mut();
-                    vs.structure = Some(structure.clone());
-                    vs.result = Some(result);
-                    da_c.queue_draw();
+    let state_c = state.clone();
+    let show_result = Rc::new(show_result);
+    btn_calc.connect_clicked(move |btn| {
+        let Some(structure) = state_c.borrow().active_tab().structure.clone() else {
+            return;
+        };
+        // Map Index -> Enum
+        let idx = drop_type.selected();
+        let r_type = match idx {
+            0 => RadiusType::Ionic,
+            1 => RadiusType::VanDerWaals,
+            _ => RadiusType::Covalent,
+        };
+
+        // Create Config
+        let config = VoidConfig {
+            grid_resolution: spin_res.value(),
+            probe_radius: spin_probe.value(),
+            radii_scale: spin_scale.value(),
+            radius_type: r_type,
+            max_grid_points: 10_000_000,
+            ..VoidConfig::default()
+        };
+
+        let done = Arc::new(AtomicUsize::new(0));
+        let total = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        *cancel_flag.borrow_mut() = Some(cancel.clone());
+
+        let (tx, rx) = mpsc::channel();
+        let (done_w, total_w, cancel_w) = (done.clone(), total.clone(), cancel.clone());
+        let worker_structure = structure.clone();
+        std::thread::spawn(move || {
+            let outcome =
+                voids::calculate_voids_with_progress(&worker_structure, config, &|d, t| {
+                    done_w.fetch_max(d, Ordering::Relaxed);
+                    total_w.store(t, Ordering::Relaxed);
+                    !cancel_w.load(Ordering::Relaxed)
+                });
+            let _ = tx.send(outcome);
+        });
+
+        btn.set_sensitive(false);
+        btn_cancel.set_sensitive(true);
+        progress.set_fraction(0.0);
+        progress.set_text(Some("0 %"));
+        progress.set_visible(true);
+
+        let btn = btn.clone();
+        let btn_cancel = btn_cancel.clone();
+        let progress = progress.clone();
+        let cancel_flag = cancel_flag.clone();
+        let show_result = show_result.clone();
+        glib::timeout_add_local(Duration::from_millis(100), move || {
+            let t = total.load(Ordering::Relaxed);
+            if t > 0 {
+                let frac = done.load(Ordering::Relaxed) as f64 / t as f64;
+                progress.set_fraction(frac);
+                progress.set_text(Some(&format!("{:.0} %", frac * 100.0)));
+            }
+            match rx.try_recv() {
+                Ok(outcome) => {
+                    show_result(structure.clone(), outcome);
+                    btn.set_sensitive(true);
+                    btn_cancel.set_sensitive(false);
+                    progress.set_visible(false);
+                    cancel_flag.borrow_mut().take();
+                    glib::ControlFlow::Break
                 }
-                Err(e) => {
-                    val_cand.set_markup(&format!("<span color='red'>Error: {}</span>", e));
-                    val_r.set_text("-");
+                Err(mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    btn.set_sensitive(true);
+                    btn_cancel.set_sensitive(false);
+                    progress.set_visible(false);
+                    glib::ControlFlow::Break
                 }
             }
-        }
+        });
     });
 
     // --- DRAWING LOGIC (Cartesian + Fixed Sorting) ---
     let vis_draw = vis_state.clone();
+    let state_draw = state.clone();
     drawing_area.set_draw_func(move |_, cr, width, height| {
+        // Fast quality draws each atom once instead of with its boundary images.
+        let (antialias, reach) = {
+            let st = state_draw.borrow();
+            let reach = match st.config.render_quality {
+                RenderQuality::Fast => 0,
+                RenderQuality::High => 1,
+            };
+            (cairo_antialias(st.config.antialias_level), reach)
+        };
+        cr.set_antialias(antialias);
         cr.set_source_rgb(1.0, 1.0, 1.0);
         cr.paint().unwrap();
         let w = width as f64;
@@ -263,36 +529,19 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
                 (cx + x1 * view_scale, cy + y2 * view_scale, z2)
             };
 
-            let det = ax * (by * cz_vec - bz * cy_vec) - ay * (bx * cz_vec - bz * cx_vec)
-                + az * (bx * cy_vec - by * cx_vec);
-            let inv_det = if det.abs() > 1e-6 { 1.0 / det } else { 0.0 };
-
             let mut list = Vec::new();
 
             for atom in &structure.atoms {
-                let x = atom.position[0];
-                let y = atom.position[1];
-                let z = atom.position[2];
-                let mut fx = ((by * cz_vec - bz * cy_vec) * x
-                    + (az * cy_vec - ay * cz_vec) * y
-                    + (ay * bz - az * by) * z)
-                    * inv_det;
-                let mut fy = ((bz * cx_vec - bx * cz_vec) * x
-                    + (ax * cz_vec - az * cx_vec) * y
-                    + (az * bx - ax * bz) * z)
-                    * inv_det;
-                let mut fz = ((bx * cy_vec - by * cx_vec) * x
-                    + (ay * cx_vec - ax * cy_vec) * y
-                    + (ax * by - ay * bx) * z)
-                    * inv_det;
+                let [mut fx, mut fy, mut fz] =
+                    cart_to_frac(atom.position, structure.lattice).unwrap_or([0.0; 3]);
 
                 fx = fx.rem_euclid(1.0);
                 fy = fy.rem_euclid(1.0);
                 fz = fz.rem_euclid(1.0);
 
-                for dx in -1..=1 {
-                    for dy in -1..=1 {
-                        for dz in -1..=1 {
+                for dx in -reach..=reach {
+                    for dy in -reach..=reach {
+                        for dz in -reach..=reach {
                             let nx = fx + dx as f64;
                             let ny = fy + dy as f64;
                             let nz = fz + dz as f64;
@@ -323,17 +572,18 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
             }
 
             if let Some(res) = &vs.result {
-                if res.max_sphere_radius > 0.0 {
-                    let vx = res.max_sphere_center[0];
-                    let vy = res.max_sphere_center[1];
-                    let vz = res.max_sphere_center[2];
+                // Largest cavity red, smaller ones shading toward yellow.
+                let n_sites = res.sites.len().max(2) - 1;
+                for (rank, site) in res.sites.iter().enumerate() {
+                    let [vx, vy, vz] = site.center;
                     let (px, py, pz) = project(vx - center_x, vy - center_y, vz - center_z);
+                    let t = rank as f64 / n_sites as f64;
                     list.push(DrawableAtom {
                         x: px,
                         y: py,
                         z: pz,
-                        r: res.max_sphere_radius * view_scale,
-                        color: (0.9, 0.1, 0.1, 0.6),
+                        r: site.radius * view_scale,
+                        color: (0.9, 0.1 + 0.7 * t, 0.1, 0.6),
                         is_void: true,
                     });
                 }
@@ -385,6 +635,17 @@ pub fn build(state: Rc<RefCell<AppState>>) -> Box {
                 cr.stroke().unwrap();
             }
 
+            // Percolating void cluster as faint blue dots, behind the spheres.
+            if let Some(res) = &vs.result {
+                cr.set_source_rgba(0.1, 0.4, 0.9, 0.35);
+                for [x, y, z] in &res.percolation.cluster_points {
+                    let (px, py, _) = project(x - center_x, y - center_y, z - center_z);
+                    cr.new_path();
+                    cr.arc(px, py, 1.5, 0.0, 2.0 * PI);
+                    cr.fill().unwrap();
+                }
+            }
+
             for d in list {
                 cr.new_path();
                 cr.arc(d.x, d.y, d.r, 0.0, 2.0 * PI);
@@ -1,7 +1,12 @@
 // src/ui/analysis/window.rs
 use super::charge_density_tab;
+use super::compare_tab;
+use super::coordination_tab;
 use super::kpath_tab;
+use super::pdf_tab;
+use super::rdf_tab;
 use super::slab_tab;
+use super::stereo_tab;
 use super::symmetry_tab;
 use super::voids_tab;
 use super::xrd_tab;
@@ -11,8 +16,13 @@ use gtk4::{ApplicationWindow, Label, Notebook, Window};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-/// Opens the main Analysis Tools window: Symmetry, XRD, Band Path, Voids, Slab.
-pub fn show_analysis_window(parent: &ApplicationWindow, state: Rc<RefCell<AppState>>) {
+/// Opens the main Analysis Tools window: Symmetry, XRD, G(r), RDF, Stereogram, Band Path,
+/// Voids, Coordination, Slab, Compare. `main_notebook` holds the structure tabs, redrawn when Compare recolors atoms.
+pub fn show_analysis_window(
+    parent: &ApplicationWindow,
+    state: Rc<RefCell<AppState>>,
+    main_notebook: &Notebook,
+) {
     let window = Window::builder()
         .title("Analysis Tools")
         .transient_for(parent)
@@ -29,15 +39,30 @@ pub fn show_analysis_window(parent: &ApplicationWindow, state: Rc<RefCell<AppSta
     let xrd_page = xrd_tab::build(state.clone());
     notebook.append_page(&xrd_page, Some(&Label::new(Some("XRD"))));
 
+    let pdf_page = pdf_tab::build(state.clone());
+    notebook.append_page(&pdf_page, Some(&Label::new(Some("G(r)"))));
+
+    let rdf_page = rdf_tab::build(state.clone());
+    notebook.append_page(&rdf_page, Some(&Label::new(Some("RDF"))));
+
+    let stereo_page = stereo_tab::build(state.clone());
+    notebook.append_page(&stereo_page, Some(&Label::new(Some("Stereogram"))));
+
     let kpath_page = kpath_tab::build(state.clone());
     notebook.append_page(&kpath_page, Some(&Label::new(Some("Band Path"))));
 
     let voids_page = voids_tab::build(state.clone());
     notebook.append_page(&voids_page, Some(&Label::new(Some("Void Analysis"))));
 
+    let cn_page = coordination_tab::build(state.clone());
+    notebook.append_page(&cn_page, Some(&Label::new(Some("Coordination"))));
+
     let slab_page = slab_tab::build(state.clone());
     notebook.append_page(&slab_page, Some(&Label::new(Some("Slab"))));
 
+    let compare_page = compare_tab::build(state.clone(), main_notebook);
+    notebook.append_page(&compare_page, Some(&Label::new(Some("Compare"))));
+
     window.set_child(Some(&notebook));
     window.present();
 }
@@ -4,14 +4,18 @@ use crate::state::AppState;
 use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{
-    Align, Button, FileChooserAction, FileChooserNative, FileFilter, Frame, Grid, Label,
-    Orientation, ResponseType, SpinButton,
+    Align, Button, CheckButton, DropDown, FileChooserAction, FileChooserNative, FileFilter, Frame,
+    Grid, Label, Orientation, PolicyType, ResponseType, ScrolledWindow, SpinButton, TextView,
+    WrapMode,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::io::xrd_exp::{self, ExperimentalData};
-use crate::physics::analysis::xrd::{calculate_pattern, XRDPattern, XRDSettings};
+use crate::physics::analysis::reflections::{self, ReflectionConditions};
+use crate::physics::analysis::xrd::{
+    broadened_curve, calculate_pattern, PeakShape, XRDPattern, XRDSettings,
+};
 
 use cairo::{Context, PdfSurface};
 use plotters::backend::DrawingBackend;
@@ -26,34 +30,106 @@ struct PlotState {
     peaks: Option<Vec<XRDPattern>>,
     exp_data: Option<ExperimentalData>,
     settings: XRDSettings,
+    /// Why there is nothing to plot, shown in place of the chart.
+    notice: Option<&'static str>,
+    /// Reflection conditions of the structure the peaks were computed for;
+    /// `None` when no space group was found.
+    conditions: Option<ReflectionConditions>,
+    hide_forbidden: bool,
+}
+
+impl PlotState {
+    /// The peaks to plot: with `hide_forbidden`, symmetry-forbidden hkl are
+    /// dropped from the labels and peaks left without any are removed.
+    fn shown_peaks(&self) -> Option<Vec<XRDPattern>> {
+        let peaks = self.peaks.as_ref()?;
+        let Some(rc) = self.conditions.as_ref().filter(|_| self.hide_forbidden) else {
+            return Some(peaks.clone());
+        };
+        Some(
+            peaks
+                .iter()
+                .filter_map(|p| {
+                    let mut p = p.clone();
+                    p.hkl.retain(|&hkl| !rc.is_forbidden(hkl));
+                    (!p.hkl.is_empty()).then_some(p)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A peak whose every reflection is forbidden should have no intensity at
+/// all, so it points at a structure or simulation problem.
+fn is_suspect(peak: &XRDPattern, rc: &ReflectionConditions) -> bool {
+    peak.hkl.iter().all(|&hkl| rc.is_forbidden(hkl))
+}
+
+fn conditions_text(rc: Option<&ReflectionConditions>, error: Option<&str>) -> String {
+    let Some(rc) = rc else {
+        return format!(
+            "Space group not detected{}.",
+            error.map(|e| format!(" ({})", e)).unwrap_or_default()
+        );
+    };
+    let mut out = format!("{} (No. {})", rc.symbol, rc.number);
+    if rc.conditions.is_empty() {
+        out.push_str("\nNo systematic absences.");
+    }
+    for c in &rc.conditions {
+        out.push_str(&format!("\n{}", c));
+    }
+    out
+}
+
+/// Peak list with forbidden reflections starred and suspect peaks flagged.
+/// Returns the text and the number of suspect peaks.
+fn peak_table(peaks: &[XRDPattern], rc: Option<&ReflectionConditions>) -> (String, usize) {
+    let mut out = format!("{:>8} {:>8} {:>7}  {}\n", "2θ (°)", "d (Å)", "I (%)", "hkl");
+    let (mut suspect, mut starred) = (0, false);
+    for p in peaks {
+        let labels: Vec<String> = p
+            .hkl
+            .iter()
+            .map(|&(h, k, l)| {
+                let forbidden = rc.is_some_and(|rc| rc.is_forbidden((h, k, l)));
+                starred |= forbidden;
+                let star = if forbidden { "*" } else { "" };
+                format!("({} {} {}){}", h, k, l, star)
+            })
+            .collect();
+        out.push_str(&format!(
+            "{:>8.3} {:>8.4} {:>7.2}  {}",
+            p.two_theta,
+            p.d_spacing,
+            p.intensity,
+            labels.join(" ")
+        ));
+        if let Some(rc) = rc.filter(|rc| is_suspect(p, rc)) {
+            suspect += 1;
+            if let Some(c) = rc.forbidden_by(p.hkl[0]) {
+                out.push_str(&format!("  <- violates {}", c));
+            }
+        }
+        out.push('\n');
+    }
+    if starred {
+        out.push_str("\n* forbidden by the space group\n");
+    }
+    (out, suspect)
 }
 
 fn draw_xrd_chart<DB: DrawingBackend>(
     root: &plotters::drawing::DrawingArea<DB, plotters::coord::Shift>,
-    peaks: &Vec<XRDPattern>,
+    peaks: &[XRDPattern],
     exp_data: &Option<ExperimentalData>,
     settings: &XRDSettings,
 ) -> Result<(), std::boxed::Box<dyn std::error::Error>>
 where
     DB::ErrorType: 'static,
 {
-    // 1. Generate Raw Simulated Curve (Gaussian convolution)
-    let sigma = settings.smoothing / 2.355;
-    let step = 0.05;
-    let mut raw_curve = Vec::new();
-    let mut t = settings.min_2theta;
-
-    while t <= settings.max_2theta {
-        let mut i_sum = 0.0;
-        for p in peaks {
-            let x = t - p.two_theta;
-            if x.abs() < 5.0 * sigma {
-                i_sum += p.intensity * f64::exp(-0.5 * (x / sigma).powi(2));
-            }
-        }
-        raw_curve.push((t, i_sum));
-        t += step;
-    }
+    // 1. Generate Raw Simulated Curve (convolution with the peak shape)
+    let raw_curve = broadened_curve(peaks, settings, 0.05);
 
     // 2. NORMALIZE Simulation to 0-100%
     let max_sim = raw_curve.iter().map(|(_, y)| *y).fold(0.0f64, f64::max);
@@ -142,6 +218,9 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
         peaks: None,
         exp_data: None,
         settings: XRDSettings::default(),
+        notice: None,
+        conditions: None,
+        hide_forbidden: false,
     }));
 
     // LEFT PANE (Plot)
@@ -157,6 +236,20 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
 
     frame_plot.set_child(Some(&drawing_area));
     left_pane.append(&frame_plot);
+
+    let frame_peaks = Frame::new(Some(" Peaks "));
+    let peak_view = TextView::new();
+    peak_view.set_editable(false);
+    peak_view.set_monospace(true);
+    peak_view.set_wrap_mode(WrapMode::None);
+    let peak_scroll = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Automatic)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .min_content_height(150)
+        .child(&peak_view)
+        .build();
+    frame_peaks.set_child(Some(&peak_scroll));
+    left_pane.append(&frame_peaks);
     root.append(&left_pane);
 
     // RIGHT PANE (Controls)
@@ -189,6 +282,14 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
     let adj_wave = gtk4::Adjustment::new(1.5406, 0.1, 5.0, 0.0001, 0.01, 0.0);
     let spin_wave = SpinButton::new(Some(&adj_wave), 0.0001, 4);
 
+    let dd_shape = DropDown::from_strings(&["Gaussian", "Lorentzian", "Pseudo-Voigt"]);
+    let adj_eta = gtk4::Adjustment::new(0.5, 0.0, 1.0, 0.05, 0.1, 0.0);
+    let spin_eta = SpinButton::new(Some(&adj_eta), 0.05, 2);
+    spin_eta.set_tooltip_text(Some("Lorentzian fraction of the pseudo-Voigt"));
+    spin_eta.set_sensitive(false);
+    let spin_eta_dd = spin_eta.clone();
+    dd_shape.connect_selected_notify(move |dd| spin_eta_dd.set_sensitive(dd.selected() == 2));
+
     grid.attach(&Label::new(Some("Min 2θ:")), 0, 0, 1, 1);
     grid.attach(&spin_min, 1, 0, 1, 1);
     grid.attach(&Label::new(Some("Max 2θ:")), 0, 1, 1, 1);
@@ -197,6 +298,10 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
     grid.attach(&spin_smooth, 1, 2, 1, 1);
     grid.attach(&Label::new(Some("λ (Å):")), 0, 3, 1, 1);
     grid.attach(&spin_wave, 1, 3, 1, 1);
+    grid.attach(&Label::new(Some("Peak Shape:")), 0, 4, 1, 1);
+    grid.attach(&dd_shape, 1, 4, 1, 1);
+    grid.attach(&Label::new(Some("η:")), 0, 5, 1, 1);
+    grid.attach(&spin_eta, 1, 5, 1, 1);
 
     frame_settings.set_child(Some(&grid));
     right_pane.append(&frame_settings);
@@ -211,6 +316,22 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
     let btn_export = Button::with_label("Export PDF");
     right_pane.append(&btn_export);
 
+    // Systematic absences of the detected space group.
+    let check_hide = CheckButton::with_label("Hide symmetry-forbidden reflections");
+    right_pane.append(&check_hide);
+
+    let frame_cond = Frame::new(Some(" Reflection Conditions "));
+    let lbl_cond = Label::new(Some("Recalculate to detect the space group."));
+    lbl_cond.set_wrap(true);
+    lbl_cond.set_xalign(0.0);
+    lbl_cond.set_selectable(true);
+    lbl_cond.set_margin_top(5);
+    lbl_cond.set_margin_bottom(5);
+    lbl_cond.set_margin_start(5);
+    lbl_cond.set_margin_end(5);
+    frame_cond.set_child(Some(&lbl_cond));
+    right_pane.append(&frame_cond);
+
     root.append(&right_pane);
 
     // LOGIC
@@ -218,21 +339,19 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
 
     drawing_area.set_draw_func(move |_, ctx, w, h| {
         let state = ps.borrow();
-        if let Some(peaks) = &state.peaks {
+        if let Some(peaks) = state.shown_peaks() {
             let backend = CairoBackend::new(ctx, (w as u32, h as u32)).unwrap();
             let root = backend.into_drawing_area();
-            draw_xrd_chart(&root, peaks, &state.exp_data, &state.settings).unwrap();
+            draw_xrd_chart(&root, &peaks, &state.exp_data, &state.settings).unwrap();
         } else {
             let backend = CairoBackend::new(ctx, (w as u32, h as u32)).unwrap();
             let root = backend.into_drawing_area();
             root.fill(&WHITE).unwrap();
             let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
-            root.draw_text(
-                "Click 'Recalculate' to Simulate",
-                &style,
-                (w / 2 - 140, h / 2),
-            )
-            .unwrap();
+            let text = state.notice.unwrap_or("Click 'Recalculate' to Simulate");
+            let (tw, _) = root.estimate_text_size(text, &style).unwrap_or((280, 0));
+            root.draw_text(text, &style, (w / 2 - tw as i32 / 2, h / 2))
+                .unwrap();
         }
     });
 
@@ -247,13 +366,45 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
         ps.settings.max_2theta = spin_max.value();
         ps.settings.smoothing = spin_smooth.value();
         ps.settings.wavelength = spin_wave.value();
+        ps.settings.peak_shape = match dd_shape.selected() {
+            1 => PeakShape::Lorentzian,
+            2 => PeakShape::PseudoVoigt {
+                eta: spin_eta.value(),
+            },
+            _ => PeakShape::Gaussian,
+        };
 
         let app_st = st_calc.borrow();
         // FIX: Access the active tab
         let tab = app_st.active_tab();
         if let Some(structure) = &tab.structure {
+            if !structure.is_periodic {
+                ps.peaks = None;
+                ps.conditions = None;
+                ps.notice = Some("XRD requires a periodic cell — this structure is a molecule");
+                lbl_cond.set_text("");
+                peak_view.buffer().set_text("");
+                da_calc.queue_draw();
+                return;
+            }
+            ps.notice = None;
             let peaks = calculate_pattern(structure, &ps.settings);
+            let (conditions, error) = match reflections::analyze(structure) {
+                Ok(rc) => (Some(rc), None),
+                Err(e) => (None, Some(e)),
+            };
+            lbl_cond.set_text(&conditions_text(conditions.as_ref(), error.as_deref()));
+            let (table, suspect) = peak_table(&peaks, conditions.as_ref());
+            peak_view.buffer().set_text(&table);
+            if let Some(rc) = conditions.as_ref().filter(|_| suspect > 0) {
+                crate::utils::console::log_warn(&format!(
+                    "XRD: {} simulated peak(s) consist only of reflections forbidden in {}; \
+                     check the structure or the simulation",
+                    suspect, rc.symbol
+                ));
+            }
             ps.peaks = Some(peaks);
+            ps.conditions = conditions;
             da_calc.queue_draw();
         }
     });
@@ -261,6 +412,13 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
     let refresh = refresh_plot.clone();
     btn_calc.connect_clicked(move |_| refresh());
 
+    let ps_hide = plot_state.clone();
+    let da_hide = drawing_area.clone();
+    check_hide.connect_toggled(move |c| {
+        ps_hide.borrow_mut().hide_forbidden = c.is_active();
+        da_hide.queue_draw();
+    });
+
     // --- LOAD EXPERIMENT LOGIC (ASC/XY/EXCEL) ---
     let ps_exp = plot_state.clone();
     let da_exp = drawing_area.clone();
@@ -324,7 +482,7 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
 
     btn_export.connect_clicked(move |_| {
         let ps = ps_export.borrow();
-        if let Some(peaks) = &ps.peaks {
+        if let Some(peaks) = ps.shown_peaks() {
             let native = FileChooserNative::new(
                 Some("Export PDF"),
                 None::<&gtk4::Window>,
@@ -334,7 +492,7 @@ pub fn build(state: Rc<RefCell<AppState>>) -> gtk4::Box {
             );
             native.set_current_name("xrd_comparison.pdf");
 
-            let peaks_ex: Vec<XRDPattern> = peaks.clone();
+            let peaks_ex: Vec<XRDPattern> = peaks;
             let exp_ex: Option<ExperimentalData> = ps.exp_data.clone();
             let settings_ex: XRDSettings = ps.settings.clone();
 
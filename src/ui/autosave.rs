@@ -0,0 +1,285 @@
+// src/ui/autosave.rs
+//
+// Periodic session snapshots for crash recovery. Every
+// `Config::autosave_minutes` the open tabs are serialized with
+// `io::session` and written to `session::autosave_path()`, unless the
+// snapshot is the same as the last one. Closing the window removes the
+// file; finding it at startup means the previous run crashed, and the user
+// is offered its tabs back.
+//
+// Each window has its own `AppState`, but the process keeps one snapshot:
+// every window registers here, one timer writes the tabs of all of them,
+// and the file is removed only when the last window closes. Recovery is
+// offered by the first window alone.
+
+use crate::io::session::{self, Session};
+use crate::panels::sidebar::{self, SidebarHandles};
+use crate::state::AppState;
+use crate::utils::console;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{ApplicationWindow, Notebook};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the timer wakes to see whether a snapshot is due, so a new
+/// interval from Preferences takes effect without a restart.
+const TICK: Duration = Duration::from_secs(30);
+
+/// The windows being autosaved and what was last written for them.
+struct Autosave {
+    windows: Vec<Weak<RefCell<AppState>>>,
+    last_json: Option<String>,
+    last_check: Instant,
+}
+
+thread_local! {
+    static AUTOSAVE: RefCell<Option<Autosave>> = const { RefCell::new(None) };
+}
+
+impl Autosave {
+    fn live(&self) -> Vec<Rc<RefCell<AppState>>> {
+        self.windows.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    /// One session holding the tabs of every window, the first window's
+    /// active tab active.
+    fn capture(&self) -> serde_json::Result<String> {
+        let mut all: Option<Session> = None;
+        for state in self.live() {
+            let session = Session::capture(&state.borrow());
+            match &mut all {
+                Some(all) => all.tabs.extend(session.tabs),
+                None => all = Some(session),
+            }
+        }
+        all.map_or(Ok(String::new()), |all| all.to_json())
+    }
+
+    /// Write the snapshot if it changed since the last write.
+    fn write(&mut self) {
+        let json = match self.capture() {
+            Ok(json) => json,
+            Err(e) => {
+                console::log_error(&format!("Autosave failed: {}", e));
+                return;
+            }
+        };
+        if self.last_json.as_ref() == Some(&json) {
+            return;
+        }
+        let path = session::autosave_path();
+        match session::write_atomic(&path, &json) {
+            Ok(()) => {
+                console::log_debug(&format!("Session autosaved to {}", path.display()));
+                self.last_json = Some(json);
+            }
+            Err(e) => console::log_error(&format!("Autosave to {} failed: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Add a window to the autosave, starting the timer for the first one.
+/// The window's tabs as they stand now count as saved, so an untouched
+/// window never writes a snapshot.
+pub fn start(state: &Rc<RefCell<AppState>>) {
+    let first = AUTOSAVE.with_borrow_mut(|autosave| {
+        let autosave = autosave.get_or_insert_with(|| Autosave {
+            windows: Vec::new(),
+            last_json: None,
+            last_check: Instant::now(),
+        });
+        let first = autosave.live().is_empty();
+        let saved = first || autosave.capture().ok() == autosave.last_json;
+        autosave.windows.push(Rc::downgrade(state));
+        if saved {
+            autosave.last_json = autosave.capture().ok();
+        }
+        first
+    });
+    if !first {
+        return;
+    }
+
+    glib::timeout_add_local(TICK, || {
+        AUTOSAVE.with_borrow_mut(|autosave| {
+            let Some(autosave) = autosave.as_mut() else {
+                return glib::ControlFlow::Break;
+            };
+            let Some(state) = autosave.live().into_iter().next() else {
+                return glib::ControlFlow::Break;
+            };
+            let minutes = state.borrow().config.autosave_minutes;
+            if minutes == 0
+                || autosave.last_check.elapsed() < Duration::from_secs(minutes as u64 * 60)
+            {
+                return glib::ControlFlow::Continue;
+            }
+            autosave.last_check = Instant::now();
+            autosave.write();
+            glib::ControlFlow::Continue
+        })
+    });
+}
+
+/// Take a closing window out of the autosave. The last window removes
+/// the snapshot, since that is a clean exit; otherwise the snapshot is
+/// rewritten without the closed window's tabs.
+pub fn finish(state: &Rc<RefCell<AppState>>) {
+    let last = AUTOSAVE.with_borrow_mut(|autosave| {
+        let Some(autosave) = autosave.as_mut() else {
+            return true;
+        };
+        autosave
+            .windows
+            .retain(|w| w.upgrade().is_some_and(|s| !Rc::ptr_eq(&s, state)));
+        if autosave.windows.is_empty() {
+            return true;
+        }
+        if autosave.last_json.is_some() && session::autosave_path().exists() {
+            autosave.write();
+        }
+        false
+    });
+    if last {
+        discard();
+    }
+}
+
+/// Remove the snapshot.
+fn discard() {
+    let path = session::autosave_path();
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            console::log_warn(&format!("Could not remove {}: {}", path.display(), e));
+        }
+    }
+}
+
+/// If a snapshot survived the last run, ask whether to reopen its tabs.
+/// "Discard" deletes it; "Recover" keeps it until the next autosave
+/// replaces it, in case this run goes down too. Once another window of
+/// this process is autosaving, the file is that window's, not a leftover.
+pub fn offer_recovery(
+    window: &ApplicationWindow,
+    state: Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+    atom_list_box: &gtk4::Box,
+    handles: Rc<SidebarHandles>,
+) {
+    let running = AUTOSAVE.with_borrow(|a| a.as_ref().is_some_and(|a| !a.live().is_empty()));
+    let path = session::autosave_path();
+    if running || !path.exists() {
+        return;
+    }
+    let snapshot = match session::read(&path) {
+        Ok(s) => s,
+        Err(e) => {
+            console::log_warn(&format!(
+                "Ignoring unreadable autosave {}: {}",
+                path.display(),
+                e
+            ));
+            discard();
+            return;
+        }
+    };
+    if snapshot.tabs.iter().all(|t| t.structure.is_none()) {
+        discard();
+        return;
+    }
+
+    let age = std::fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .map(|d| format!(" from {} min ago", d.as_secs() / 60))
+        .unwrap_or_default();
+    let dialog = gtk4::MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL | gtk4::DialogFlags::DESTROY_WITH_PARENT,
+        gtk4::MessageType::Question,
+        gtk4::ButtonsType::None,
+        "Recover the previous session?",
+    );
+    dialog.set_title(Some("Session Recovery"));
+    dialog.set_secondary_text(Some(&format!(
+        "CView did not shut down normally. An autosave{} holds {} tab(s).",
+        age,
+        snapshot.tabs.len()
+    )));
+    dialog.add_button("Discard", gtk4::ResponseType::Reject);
+    dialog.add_button("Recover", gtk4::ResponseType::Accept);
+    dialog.set_default_response(gtk4::ResponseType::Accept);
+
+    let snapshot = RefCell::new(Some(snapshot));
+    let win_weak = window.downgrade();
+    let nb_weak = notebook.downgrade();
+    let ab_weak = atom_list_box.downgrade();
+    dialog.connect_response(move |d, resp| {
+        d.close();
+        if resp != gtk4::ResponseType::Accept {
+            discard();
+            return;
+        }
+        let (Some(win), Some(nb), Some(ab), Some(snapshot)) = (
+            win_weak.upgrade(),
+            nb_weak.upgrade(),
+            ab_weak.upgrade(),
+            snapshot.take(),
+        ) else {
+            return;
+        };
+        restore(&win, &state, &nb, &ab, &handles, snapshot);
+    });
+    dialog.present();
+}
+
+/// Add the snapshot's tabs to the window. A pristine "Untitled" first tab
+/// is replaced rather than kept beside them.
+fn restore(
+    window: &ApplicationWindow,
+    state: &Rc<RefCell<AppState>>,
+    notebook: &Notebook,
+    atom_list_box: &gtk4::Box,
+    handles: &Rc<SidebarHandles>,
+    snapshot: Session,
+) {
+    let (tabs, active) = {
+        let st = state.borrow();
+        snapshot.into_tabs(&st.config)
+    };
+    let n = tabs.len();
+
+    let first_new_page = {
+        let mut st = state.borrow_mut();
+        let reuse_first = st.tabs.len() == 1
+            && st.tabs[0].structure.is_none()
+            && st.tabs[0].file_name == "Untitled";
+        if reuse_first {
+            st.tabs.clear();
+        }
+        let offset = st.tabs.len();
+        st.tabs.extend(tabs);
+        st.active_tab_index = offset + active;
+        // The reused page already has its drawing area and handlers.
+        offset + usize::from(reuse_first)
+    };
+
+    for idx in first_new_page..state.borrow().tabs.len() {
+        let (da, container) = crate::ui::create_tab_content(state.clone(), idx);
+        crate::ui::add_closable_tab(notebook, &container, "", state.clone());
+        container.show();
+        crate::ui::setup_interactions(window, state.clone(), &da, handles.clone());
+    }
+
+    let active_page = state.borrow().active_tab_index;
+    crate::ui::refresh_tab_labels(notebook, &state.borrow());
+    notebook.set_current_page(Some(active_page as u32));
+    sidebar::refresh_atom_list(atom_list_box, state.clone(), notebook);
+    if let Some(da) = crate::ui::get_active_drawing_area(notebook) {
+        da.queue_draw();
+    }
+    console::log_info(&format!("Recovered {} tab(s) from the autosave", n));
+}
@@ -1,4 +1,9 @@
 pub mod atom_instances_dlg;
 pub mod basis_dlg;
+pub mod bond_cutoffs_dlg;
+pub mod build_supercell_dlg;
 pub mod miller_dlg;
+pub mod strain_dlg;
 pub mod supercell_dlg;
+pub mod symmetrize_dlg;
+pub mod torsion_dlg;
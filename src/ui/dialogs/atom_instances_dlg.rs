@@ -300,9 +300,8 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                     entry.color = Some(color_tuple);
                     count += 1;
                 }
-                // Vector path is used for overridden atoms, so sprite cache
-                // doesn't need invalidation. But element-keyed sprites for
-                // non-overridden atoms are unaffected — leave them.
+                // Overridden atoms use color-keyed sprites, so no cache
+                // invalidation is needed here.
             }
 
             // Refresh list with the same filter.
@@ -133,11 +133,14 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                     let new_s = basis::modify_selection(current_s, &original_indices, &new_el);
                     tab.structure = Some(new_s);
                     tab.invalidate_bvs_cache();
+                    tab.modified = true;
+                    tab.displacement.clear();
 
                     if let Some(nb) = notebook_weak.upgrade() {
                         if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
                             da.queue_draw();
                         }
+                        crate::ui::refresh_tab_labels(&nb, &s);
                     }
 
                     if let Some(dlg) = dialog_weak.upgrade() {
@@ -164,11 +167,14 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                     let new_s = basis::substitute_element(current_s, &from, &to);
                     tab.structure = Some(new_s);
                     tab.invalidate_bvs_cache();
+                    tab.modified = true;
+                    tab.displacement.clear();
 
                     if let Some(nb) = nb_weak_sub.upgrade() {
                         if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
                             da.queue_draw();
                         }
+                        crate::ui::refresh_tab_labels(&nb, &s);
                     }
 
                     if let Some(dlg) = dialog_weak_sub.upgrade() {
@@ -192,11 +198,14 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                 let new_s = basis::standardize_positions(current_s);
                 tab.structure = Some(new_s);
                 tab.invalidate_bvs_cache();
+                tab.modified = true;
+                tab.displacement.clear();
 
                 if let Some(nb) = nb_weak_std.upgrade() {
                     if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
                         da.queue_draw();
                     }
+                    crate::ui::refresh_tab_labels(&nb, &s);
                 }
 
                 if let Some(dlg) = dialog_weak_std.upgrade() {
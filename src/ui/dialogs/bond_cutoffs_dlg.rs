@@ -0,0 +1,177 @@
+// src/ui/dialogs/bond_cutoffs_dlg.rs
+//
+// Per element-pair bond length limits. Each pair present in the structure
+// gets a row; ticking "Custom" stores the spin value in
+// `TabState::bond_pair_cutoffs`, unticking falls back to the global
+// tolerance rule (covalent-radius sum × tolerance). The "Rule" column
+// applies after that distance test: "Always" stretches the limit for pairs
+// the heuristic misses, "Never" drops the pair (`TabState::bond_pair_rules`).
+
+use crate::state::{bond_pair_key, AppState, BondRule, ALWAYS_BOND_STRETCH};
+use gtk4::prelude::*;
+use gtk4::{
+    Align, CheckButton, Dialog, DropDown, Grid, Label, Notebook, ResponseType, SpinButton, Window,
+};
+
+/// Rule dropdown entries, in the order of [`rule_from_index`].
+const RULE_LABELS: [&str; 3] = ["Auto", "Always", "Never"];
+
+fn rule_from_index(i: u32) -> Option<BondRule> {
+    match i {
+        1 => Some(BondRule::Always),
+        2 => Some(BondRule::Never),
+        _ => None,
+    }
+}
+
+fn rule_index(rule: Option<BondRule>) -> u32 {
+    match rule {
+        None => 0,
+        Some(BondRule::Always) => 1,
+        Some(BondRule::Never) => 2,
+    }
+}
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &Notebook) {
+    let dialog = Dialog::builder()
+        .title("Bond Cutoffs and Rules per Element Pair")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(440)
+        .build();
+
+    let content = dialog.content_area();
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_spacing(10);
+
+    let (pairs, tolerance) = {
+        let st = state.borrow();
+        let tab = st.active_tab();
+        (tab.element_pairs(), tab.view.bond_cutoff)
+    };
+
+    if pairs.is_empty() {
+        content.append(&Label::new(Some("No structure loaded.")));
+    } else {
+        let hint = Label::new(Some(&format!(
+            "Unticked pairs use covalent radii × {:.2} (sidebar tolerance). \
+             \"Always\" bonds a pair out to {:.1} × its limit; \"Never\" \
+             hides it at any distance.",
+            tolerance, ALWAYS_BOND_STRETCH
+        )));
+        hint.set_wrap(true);
+        hint.set_xalign(0.0);
+        content.append(&hint);
+
+        let grid = Grid::new();
+        grid.set_row_spacing(5);
+        grid.set_column_spacing(10);
+        grid.set_halign(Align::Center);
+
+        grid.attach(&Label::new(Some("Pair")), 0, 0, 1, 1);
+        grid.attach(&Label::new(Some("Custom")), 1, 0, 1, 1);
+        grid.attach(&Label::new(Some("Max (Å)")), 2, 0, 1, 1);
+        grid.attach(&Label::new(Some("Rule")), 3, 0, 1, 1);
+
+        for (row, (a, b)) in pairs.iter().enumerate() {
+            let row = row as i32 + 1;
+            let (current, custom, rule) = {
+                let st = state.borrow();
+                let tab = st.active_tab();
+                let key = bond_pair_key(a, b);
+                (
+                    tab.max_bond_length(a, b, tolerance),
+                    tab.bond_pair_cutoffs.contains_key(&key),
+                    tab.bond_pair_rules.get(&key).copied(),
+                )
+            };
+
+            let label = Label::new(Some(&format!("{}–{}", a, b)));
+            label.set_xalign(0.0);
+            let check = CheckButton::new();
+            check.set_active(custom);
+            let spin = SpinButton::with_range(0.5, 8.0, 0.05);
+            spin.set_digits(2);
+            spin.set_value(current);
+            spin.set_sensitive(custom);
+            let dd_rule = DropDown::from_strings(&RULE_LABELS);
+            dd_rule.set_selected(rule_index(rule));
+
+            let key = bond_pair_key(a, b);
+            let st_r = state.clone();
+            let nb_r = notebook.downgrade();
+            let key_r = key.clone();
+            dd_rule.connect_selected_notify(move |dd| {
+                {
+                    let mut st = st_r.borrow_mut();
+                    let rules = &mut st.active_tab_mut().bond_pair_rules;
+                    match rule_from_index(dd.selected()) {
+                        Some(rule) => rules.insert(key_r.clone(), rule),
+                        None => rules.remove(&key_r),
+                    };
+                }
+                redraw(&nb_r);
+            });
+
+            let st_c = state.clone();
+            let nb_c = notebook.downgrade();
+            let spin_c = spin.clone();
+            let key_c = key.clone();
+            check.connect_toggled(move |btn| {
+                spin_c.set_sensitive(btn.is_active());
+                {
+                    let mut st = st_c.borrow_mut();
+                    let cutoffs = &mut st.active_tab_mut().bond_pair_cutoffs;
+                    if btn.is_active() {
+                        cutoffs.insert(key_c.clone(), spin_c.value());
+                    } else {
+                        cutoffs.remove(&key_c);
+                    }
+                }
+                redraw(&nb_c);
+            });
+
+            let st_s = state.clone();
+            let nb_s = notebook.downgrade();
+            let check_s = check.clone();
+            spin.connect_value_changed(move |sp| {
+                if !check_s.is_active() {
+                    return;
+                }
+                st_s.borrow_mut()
+                    .active_tab_mut()
+                    .bond_pair_cutoffs
+                    .insert(key.clone(), sp.value());
+                redraw(&nb_s);
+            });
+
+            grid.attach(&label, 0, row, 1, 1);
+            grid.attach(&check, 1, row, 1, 1);
+            grid.attach(&spin, 2, row, 1, 1);
+            grid.attach(&dd_rule, 3, row, 1, 1);
+        }
+
+        let scroller = gtk4::ScrolledWindow::new();
+        scroller.set_propagate_natural_height(true);
+        scroller.set_max_content_height(400);
+        scroller.set_child(Some(&grid));
+        content.append(&scroller);
+    }
+
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.connect_response(|dlg, _| dlg.close());
+    dialog.show();
+}
+
+fn redraw(notebook: &gtk4::glib::WeakRef<Notebook>) {
+    if let Some(nb) = notebook.upgrade() {
+        if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+            da.queue_draw();
+        }
+    }
+}
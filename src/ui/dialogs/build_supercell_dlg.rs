@@ -0,0 +1,149 @@
+// src/ui/dialogs/build_supercell_dlg.rs
+//
+// Replace the current structure by its na × nb × nc supercell. Unlike the
+// Matrix Transformation dialog this works on the structure as it is now,
+// not as loaded: each application pushes the previous structure on the
+// undo stack, so 2×1×1 applied twice gives 4×1×1.
+
+use crate::model::structure::generate_formula;
+use crate::state::AppState;
+use crate::utils::console;
+use gtk4::prelude::*;
+use gtk4::{Align, Dialog, Grid, Label, Notebook, ResponseType, SpinButton, Window};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &Notebook) {
+    let dialog = Dialog::builder()
+        .title("Build Supercell")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(300)
+        .build();
+
+    let content = dialog.content_area();
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_spacing(10);
+
+    let hint = Label::new(Some(
+        "Repetitions along each lattice vector. The atoms are copied and the \
+         cell vectors scaled; Ctrl+Z restores the previous cell.",
+    ));
+    hint.set_wrap(true);
+    hint.set_xalign(0.0);
+    content.append(&hint);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(5);
+    grid.set_column_spacing(10);
+    grid.set_halign(Align::Center);
+
+    let mut spins = Vec::with_capacity(3);
+    for (i, axis) in ["na (a)", "nb (b)", "nc (c)"].iter().enumerate() {
+        let label = Label::new(Some(axis));
+        label.set_xalign(0.0);
+        let spin = SpinButton::with_range(1.0, 20.0, 1.0);
+        spin.set_digits(0);
+        spin.set_value(2.0);
+        spin.set_snap_to_ticks(true);
+        grid.attach(&label, 0, i as i32, 1, 1);
+        grid.attach(&spin, 1, i as i32, 1, 1);
+        spins.push(spin);
+    }
+    content.append(&grid);
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    status.set_wrap(true);
+    content.append(&status);
+
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.add_button("Apply", ResponseType::Apply);
+
+    let state_weak = Rc::downgrade(&state);
+    let notebook_weak = notebook.downgrade();
+    dialog.connect_response(move |dlg, resp| {
+        if resp != ResponseType::Apply {
+            dlg.close();
+            return;
+        }
+        let Some(st) = state_weak.upgrade() else {
+            return;
+        };
+        let [na, nb, nc] = [0, 1, 2].map(|i| spins[i].value().round().max(1.0) as usize);
+
+        {
+            let mut s = st.borrow_mut();
+            let max_atoms = s.config.max_atoms_display;
+            let tab = s.active_tab_mut();
+            match &tab.structure {
+                None => {
+                    status.set_text("No structure loaded.");
+                    return;
+                }
+                Some(structure) if !structure.is_periodic => {
+                    status.set_text("A molecule has no cell to repeat.");
+                    return;
+                }
+                Some(_) => {}
+            }
+            let Some(structure) = tab.structure.take() else {
+                return;
+            };
+
+            // Bonds and boundary images multiply the work of every frame;
+            // past the large-structure threshold they are switched off, as
+            // for a file that large.
+            let n_atoms = structure.atoms.len() * na * nb * nc;
+            if n_atoms > max_atoms {
+                console::log_warn(&format!(
+                    "{}×{}×{} supercell has {} atoms, above the large-structure \
+                     threshold ({}): bonds and boundary images switched off",
+                    na, nb, nc, n_atoms, max_atoms
+                ));
+                tab.view.show_bonds = false;
+                tab.view.show_full_unit_cell = false;
+            }
+
+            tab.interaction.undo_stack.push(structure.clone());
+            let mut supercell = structure.make_supercell(na, nb, nc);
+            supercell.formula = generate_formula(&supercell.atoms);
+            tab.structure = Some(supercell);
+
+            // Atom i's copies are atoms i·n .. (i+1)·n of the supercell, so
+            // every copy keeps the original's override.
+            let copies = na * nb * nc;
+            tab.overrides = std::mem::take(&mut tab.overrides)
+                .into_iter()
+                .flat_map(|(i, o)| (0..copies).map(move |c| (i * copies + c, o.clone())))
+                .collect();
+            tab.interaction.selected.clear();
+            tab.interaction.highlighted.clear();
+            tab.interaction.measure_picks.clear();
+            // Planes, k-path and voids were found for the old cell.
+            tab.miller_planes.clear();
+            tab.kpath_result = None;
+            tab.void_result = None;
+            tab.invalidate_bvs_cache();
+            tab.modified = true;
+            tab.displacement.clear();
+            console::log_info(&format!(
+                "Built {}×{}×{} supercell: {} atoms",
+                na, nb, nc, n_atoms
+            ));
+        }
+        status.set_text("Supercell built.");
+
+        if let Some(notebook) = notebook_weak.upgrade() {
+            if let Some(da) = crate::ui::get_active_drawing_area(&notebook) {
+                da.queue_draw();
+            }
+            crate::ui::refresh_tab_labels(&notebook, &st.borrow());
+        }
+    });
+
+    dialog.show();
+}
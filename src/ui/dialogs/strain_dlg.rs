@@ -0,0 +1,128 @@
+// src/ui/dialogs/strain_dlg.rs
+//
+// Apply a homogeneous strain given as six Voigt components in percent.
+// Shear components are engineering strains (γ = 2ε). Each application
+// strains the current structure and pushes the previous one on the undo
+// stack, so repeated clicks compound.
+
+use crate::model::structure::strain_from_voigt;
+use crate::state::AppState;
+use crate::utils::console;
+use gtk4::prelude::*;
+use gtk4::{Align, Dialog, Grid, Label, Notebook, ResponseType, SpinButton, Window};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const COMPONENTS: [&str; 6] = [
+    "ε₁ (xx)",
+    "ε₂ (yy)",
+    "ε₃ (zz)",
+    "ε₄ (yz)",
+    "ε₅ (zx)",
+    "ε₆ (xy)",
+];
+
+pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &Notebook) {
+    let dialog = Dialog::builder()
+        .title("Apply Strain")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(320)
+        .build();
+
+    let content = dialog.content_area();
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_spacing(10);
+
+    let hint = Label::new(Some(
+        "Voigt components in %. Lattice and atoms deform by (I + ε); \
+         fractional coordinates are kept.",
+    ));
+    hint.set_wrap(true);
+    hint.set_xalign(0.0);
+    content.append(&hint);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(5);
+    grid.set_column_spacing(10);
+    grid.set_halign(Align::Center);
+
+    let mut spins = Vec::with_capacity(6);
+    for (i, name) in COMPONENTS.iter().enumerate() {
+        let label = Label::new(Some(name));
+        label.set_xalign(0.0);
+        let spin = SpinButton::with_range(-50.0, 50.0, 0.1);
+        spin.set_digits(2);
+        spin.set_value(0.0);
+        grid.attach(&label, 0, i as i32, 1, 1);
+        grid.attach(&spin, 1, i as i32, 1, 1);
+        spins.push(spin);
+    }
+    content.append(&grid);
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    content.append(&status);
+
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.add_button("Apply", ResponseType::Apply);
+
+    let state_weak = Rc::downgrade(&state);
+    let notebook_weak = notebook.downgrade();
+    dialog.connect_response(move |dlg, resp| {
+        if resp != ResponseType::Apply {
+            dlg.close();
+            return;
+        }
+        let Some(st) = state_weak.upgrade() else {
+            return;
+        };
+
+        let mut voigt = [0.0; 6];
+        for (v, spin) in voigt.iter_mut().zip(&spins) {
+            *v = spin.value() / 100.0;
+        }
+
+        let msg = {
+            let mut s = st.borrow_mut();
+            let tab = s.active_tab_mut();
+            let Some(structure) = tab.structure.take() else {
+                status.set_text("No structure loaded.");
+                return;
+            };
+            let strained = structure.apply_strain(strain_from_voigt(voigt));
+            let msg = format!(
+                "Applied strain [{}] %: volume {:.3} → {:.3} Å³",
+                voigt
+                    .iter()
+                    .map(|v| format!("{:.2}", v * 100.0))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                structure.cell_volume(),
+                strained.cell_volume()
+            );
+            tab.interaction.undo_stack.push(structure);
+            tab.structure = Some(strained);
+            tab.interaction.selected.clear();
+            tab.interaction.highlighted.clear();
+            tab.invalidate_bvs_cache();
+            tab.modified = true;
+            tab.displacement.clear();
+            msg
+        };
+        console::log_info(&msg);
+        status.set_text("Strain applied.");
+
+        if let Some(nb) = notebook_weak.upgrade() {
+            if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                da.queue_draw();
+            }
+            crate::ui::refresh_tab_labels(&nb, &st.borrow());
+        }
+    });
+
+    dialog.show();
+}
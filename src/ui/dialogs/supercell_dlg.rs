@@ -110,11 +110,14 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                         tab.structure = Some(new_s);
                         tab.interaction.selected.clear();
                         tab.invalidate_bvs_cache();
+                        tab.modified = true;
+                        tab.displacement.clear();
 
                         if let Some(nb) = notebook_weak.upgrade() {
                             if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
                                 da.queue_draw();
                             }
+                            crate::ui::refresh_tab_labels(&nb, &s);
                         }
                     }
                 }
@@ -123,6 +126,9 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                         tab.structure = Some(orig.clone());
                         tab.interaction.selected.clear();
                         tab.invalidate_bvs_cache();
+                        // Back to the structure as loaded.
+                        tab.modified = false;
+                        tab.displacement.clear();
 
                         for (i, spin) in spins_final.iter().enumerate() {
                             let r = i / 3;
@@ -134,6 +140,7 @@ pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &
                             if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
                                 da.queue_draw();
                             }
+                            crate::ui::refresh_tab_labels(&nb, &s);
                         }
                     }
                 }
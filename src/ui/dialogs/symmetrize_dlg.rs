@@ -0,0 +1,145 @@
+// src/ui/dialogs/symmetrize_dlg.rs
+//
+// "Symmetrize Structure": pick a tolerance, preview which space group it
+// detects, then snap the atoms onto that group's ideal positions. The
+// previous structure goes on the undo stack.
+
+use crate::physics::analysis::symmetry::{self, SYMPREC};
+use crate::physics::operations::symmetrize;
+use crate::state::AppState;
+use crate::utils::console;
+use gtk4::prelude::*;
+use gtk4::{Align, Dialog, Grid, Label, Notebook, ResponseType, SpinButton, Window};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Loose enough for typical DFT noise, tight enough not to invent symmetry.
+const DEFAULT_TOLERANCE: f64 = 0.01;
+
+pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>, notebook: &Notebook) {
+    let dialog = Dialog::builder()
+        .title("Symmetrize Structure")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(340)
+        .build();
+
+    let content = dialog.content_area();
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_spacing(10);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(5);
+    grid.set_column_spacing(10);
+    grid.set_halign(Align::Center);
+
+    let current_label = Label::new(Some("Current (strict):"));
+    current_label.set_xalign(0.0);
+    let current_value = Label::new(Some(&detected(&state, SYMPREC)));
+    current_value.set_xalign(0.0);
+
+    let tol_label = Label::new(Some("Tolerance (Å):"));
+    tol_label.set_xalign(0.0);
+    let tol_spin = SpinButton::with_range(1e-4, 0.5, 1e-3);
+    tol_spin.set_digits(4);
+    tol_spin.set_value(DEFAULT_TOLERANCE);
+
+    let target_label = Label::new(Some("Target group:"));
+    target_label.set_xalign(0.0);
+    let target_value = Label::new(Some(&detected(&state, DEFAULT_TOLERANCE)));
+    target_value.set_xalign(0.0);
+
+    grid.attach(&current_label, 0, 0, 1, 1);
+    grid.attach(&current_value, 1, 0, 1, 1);
+    grid.attach(&tol_label, 0, 1, 1, 1);
+    grid.attach(&tol_spin, 1, 1, 1, 1);
+    grid.attach(&target_label, 0, 2, 1, 1);
+    grid.attach(&target_value, 1, 2, 1, 1);
+    content.append(&grid);
+
+    let status = Label::new(None);
+    status.set_wrap(true);
+    status.set_xalign(0.0);
+    content.append(&status);
+
+    let st_t = state.clone();
+    let target_t = target_value.clone();
+    tol_spin.connect_value_changed(move |spin| {
+        target_t.set_text(&detected(&st_t, spin.value()));
+    });
+
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.add_button("Symmetrize", ResponseType::Apply);
+
+    let state_weak = Rc::downgrade(&state);
+    let notebook_weak = notebook.downgrade();
+    dialog.connect_response(move |dlg, resp| {
+        if resp != ResponseType::Apply {
+            dlg.close();
+            return;
+        }
+        let Some(st) = state_weak.upgrade() else {
+            return;
+        };
+
+        let source = st.borrow().active_tab().structure.clone();
+        let Some(structure) = source else {
+            status.set_text("No structure loaded.");
+            return;
+        };
+
+        match symmetrize::symmetrize(&structure, tol_spin.value()) {
+            Ok(result) => {
+                let msg = format!(
+                    "Symmetrized to {} (#{}): max displacement {:.4} Å",
+                    symmetry::spacegroup_symbol(result.number),
+                    result.number,
+                    result.max_displacement
+                );
+                {
+                    let mut s = st.borrow_mut();
+                    let tab = s.active_tab_mut();
+                    tab.interaction.undo_stack.push(structure);
+                    tab.structure = Some(result.structure);
+                    tab.interaction.selected.clear();
+                    tab.interaction.highlighted.clear();
+                    tab.invalidate_bvs_cache();
+                    tab.modified = true;
+                    tab.displacement.clear();
+                }
+                console::log_info(&msg);
+                status.set_text(&msg);
+                current_value.set_text(&detected(&st, SYMPREC));
+
+                if let Some(nb) = notebook_weak.upgrade() {
+                    if let Some(da) = crate::ui::get_active_drawing_area(&nb) {
+                        da.queue_draw();
+                    }
+                    crate::ui::refresh_tab_labels(&nb, &st.borrow());
+                }
+            }
+            Err(e) => {
+                console::log_error(&format!("Symmetrize failed: {}", e));
+                status.set_text(&e);
+            }
+        }
+    });
+
+    dialog.show();
+}
+
+/// "Fm-3m (#225)" for the active structure at `symprec`, or why not.
+fn detected(state: &Rc<RefCell<AppState>>, symprec: f64) -> String {
+    let st = state.borrow();
+    match &st.active_tab().structure {
+        Some(s) if s.is_periodic => match symmetry::analyze_with(s, symprec) {
+            Ok(info) => format!("{} (#{})", info.symbol, info.number),
+            Err(e) => e,
+        },
+        Some(_) => "Not periodic".to_string(),
+        None => "No structure".to_string(),
+    }
+}
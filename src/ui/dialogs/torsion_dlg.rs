@@ -0,0 +1,186 @@
+// src/ui/dialogs/torsion_dlg.rs
+//
+// Torsion scan of a molecule: pick A–B–C–D (pre-filled from a four-atom
+// selection), a step and a step count, and save the series of conformers
+// as one multi-frame XYZ file. The fragment on the C side of the B–C bond
+// turns; connectivity comes from covalent radii, whatever the view shows.
+// The structure in the tab is left unchanged.
+
+use crate::config::RecentDir;
+use crate::io::xyz;
+use crate::physics::operations::torsion;
+use crate::state::AppState;
+use crate::utils::console;
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Dialog, FileChooserAction, FileChooserNative, FileFilter, Grid, Label, ResponseType,
+    SpinButton, Window,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn show(parent: &impl IsA<Window>, state: Rc<RefCell<AppState>>) {
+    let dialog = Dialog::builder()
+        .title("Torsion Scan")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(340)
+        .build();
+
+    let content = dialog.content_area();
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_spacing(10);
+
+    let hint = Label::new(Some(
+        "Atoms A–B–C–D define the torsion; the fragment on the C side of \
+         the B–C bond rotates about it. Frames are saved as multi-frame XYZ.",
+    ));
+    hint.set_wrap(true);
+    hint.set_xalign(0.0);
+    content.append(&hint);
+
+    let (n_atoms, mut picked) = {
+        let st = state.borrow();
+        let tab = st.active_tab();
+        let n = tab.structure.as_ref().map_or(0, |s| s.atoms.len());
+        let mut sel: Vec<_> = tab.interaction.selected.values().collect();
+        sel.sort_by_key(|a| a.unique_id);
+        (n, sel.iter().map(|a| a.original_index).collect::<Vec<_>>())
+    };
+    if picked.len() != 4 {
+        picked = (0..4).map(|i| i.min(n_atoms.saturating_sub(1))).collect();
+    }
+
+    let grid = Grid::new();
+    grid.set_row_spacing(5);
+    grid.set_column_spacing(10);
+    grid.set_halign(Align::Center);
+
+    let max_index = n_atoms.saturating_sub(1) as f64;
+    let mut atom_spins = Vec::with_capacity(4);
+    for (row, (name, &index)) in ["A", "B", "C", "D"].iter().zip(&picked).enumerate() {
+        let label = Label::new(Some(&format!("Atom {}", name)));
+        label.set_xalign(0.0);
+        let spin = SpinButton::with_range(0.0, max_index.max(0.0), 1.0);
+        spin.set_value(index as f64);
+        grid.attach(&label, 0, row as i32, 1, 1);
+        grid.attach(&spin, 1, row as i32, 1, 1);
+        atom_spins.push(spin);
+    }
+
+    let step_label = Label::new(Some("Step (°)"));
+    step_label.set_xalign(0.0);
+    let step_spin = SpinButton::with_range(-180.0, 180.0, 1.0);
+    step_spin.set_digits(1);
+    step_spin.set_value(30.0);
+    grid.attach(&step_label, 0, 4, 1, 1);
+    grid.attach(&step_spin, 1, 4, 1, 1);
+
+    let count_label = Label::new(Some("Steps"));
+    count_label.set_xalign(0.0);
+    let count_spin = SpinButton::with_range(1.0, 360.0, 1.0);
+    count_spin.set_value(12.0);
+    grid.attach(&count_label, 0, 5, 1, 1);
+    grid.attach(&count_spin, 1, 5, 1, 1);
+    content.append(&grid);
+
+    let status = Label::new(None);
+    status.set_wrap(true);
+    status.set_xalign(0.0);
+    content.append(&status);
+
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.add_button("Save Scan...", ResponseType::Apply);
+
+    let state_weak = Rc::downgrade(&state);
+    dialog.connect_response(move |dlg, resp| {
+        if resp != ResponseType::Apply {
+            dlg.close();
+            return;
+        }
+        let Some(st) = state_weak.upgrade() else {
+            return;
+        };
+
+        let ids: Vec<usize> = atom_spins.iter().map(|s| s.value() as usize).collect();
+        let ids = [ids[0], ids[1], ids[2], ids[3]];
+        let step = step_spin.value();
+        let steps = count_spin.value() as usize;
+
+        let frames = {
+            let s = st.borrow();
+            let tab = s.active_tab();
+            let Some(structure) = &tab.structure else {
+                status.set_text("No structure loaded.");
+                return;
+            };
+            if structure.is_periodic {
+                status.set_text("Torsion scans need a molecule (a structure without a cell).");
+                return;
+            }
+            let bonds = torsion::covalent_bonds(structure);
+            let moving = match torsion::moving_fragment(structure.atoms.len(), &bonds, ids) {
+                Ok(m) => m,
+                Err(e) => {
+                    status.set_text(&e);
+                    return;
+                }
+            };
+            let [a, b, c, d] = ids;
+            torsion::scan(structure, ids, &moving, step, steps)
+                .into_iter()
+                .enumerate()
+                .map(|(k, (angle, frame))| {
+                    let comment = format!(
+                        "torsion {}-{}-{}-{} = {:.2} deg (frame {}/{})",
+                        a, b, c, d, angle, k, steps
+                    );
+                    (comment, frame)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let chooser = FileChooserNative::new(
+            Some("Save Torsion Scan"),
+            Some(dlg),
+            FileChooserAction::Save,
+            Some("Save"),
+            Some("Cancel"),
+        );
+        let filter = FileFilter::new();
+        filter.set_name(Some("XYZ Trajectory"));
+        filter.add_pattern("*.xyz");
+        chooser.add_filter(&filter);
+        chooser.set_current_name("torsion_scan.xyz");
+        crate::ui::start_in_recent_dir(&chooser, &st.borrow().config, RecentDir::Export);
+
+        let status = status.clone();
+        let state_save = st.clone();
+        chooser.connect_response(move |chooser, r| {
+            if r == ResponseType::Accept {
+                if let Some(path) = chooser.file().and_then(|f| f.path()) {
+                    match xyz::write_frames(&path.to_string_lossy(), &frames) {
+                        Ok(()) => {
+                            let msg = format!(
+                                "Saved {} torsion frames to {}",
+                                frames.len(),
+                                path.display()
+                            );
+                            console::log_info(&msg);
+                            status.set_text(&msg);
+                            crate::ui::remember_recent_dir(&state_save, RecentDir::Export, &path);
+                        }
+                        Err(e) => status.set_text(&format!("Failed to save scan: {}", e)),
+                    }
+                }
+            }
+            chooser.destroy();
+        });
+        chooser.show();
+    });
+
+    dialog.show();
+}
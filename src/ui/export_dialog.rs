@@ -3,22 +3,34 @@
 
 use gtk4::prelude::*;
 use gtk4::{
-    ApplicationWindow, Box as GtkBox, CheckButton, ComboBoxText, Dialog, FileChooserAction,
-    FileChooserNative, Label, Orientation, ResponseType, SpinButton,
+    glib, ApplicationWindow, Box as GtkBox, ButtonsType, CheckButton, ComboBoxText, Dialog,
+    DialogFlags, FileChooserAction, FileChooserNative, Label, MessageDialog, MessageType,
+    Orientation, ResponseType, SpinButton,
 };
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 
+use crate::config::RecentDir;
 use crate::rendering::export::{
-    export_for_journal, export_for_presentation, export_for_web, export_pdf_advanced,
-    export_png_advanced, ExportFormat, ExportSettings,
+    batch_targets, export_for_journal, export_for_presentation, export_for_web,
+    export_html_advanced, export_pdf_advanced, export_png_advanced, export_report_pdf, export_tab,
+    ExportFormat, ExportSettings,
 };
 use crate::state::AppState;
-
-/// Show the advanced export dialog
-pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState>>) {
+use crate::utils::console;
+
+/// Show the advanced export dialog. With `batch` set, the same settings are
+/// applied to every open tab and the user picks a target folder instead of
+/// a file.
+pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState>>, batch: bool) {
+    let title = if batch {
+        "Export All Tabs"
+    } else {
+        "Export Image"
+    };
     let dialog = Dialog::builder()
-        .title("Export Image")
+        .title(title)
         .transient_for(window)
         .modal(true)
         .default_width(450)
@@ -45,6 +57,8 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
     format_combo.append_text("PDF (Vector Document)");
     // #[cfg(feature = "svg")]
     format_combo.append_text("SVG (Editable Vector)");
+    format_combo.append_text("Report PDF (Structure + Cell Tables)");
+    format_combo.append_text("HTML (Interactive 3D, three.js from the web)");
     format_combo.set_active(Some(0));
     format_combo.set_hexpand(true);
 
@@ -132,6 +146,26 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
     unit_cell_check.set_margin_start(10);
     vbox.append(&unit_cell_check);
 
+    let measurements_check = CheckButton::with_label("Include Pinned Measurements");
+    measurements_check.set_active(true);
+    measurements_check.set_margin_start(10);
+    vbox.append(&measurements_check);
+
+    let bvs_check = CheckButton::with_label("Include BVS / Coordination Summary");
+    bvs_check.set_active(true);
+    bvs_check.set_margin_start(10);
+    bvs_check.set_visible(false);
+    vbox.append(&bvs_check);
+
+    // The report fits its figure to the page, so zoom doesn't apply there.
+    let bvs_check_format = bvs_check.clone();
+    let scale_box_format = scale_box.clone();
+    format_combo.connect_changed(move |combo| {
+        let report = combo.active() == Some(3);
+        bvs_check_format.set_visible(report);
+        scale_box_format.set_sensitive(!report);
+    });
+
     // ========================================================================
     // PRESET HANDLER - Update settings when preset changes
     // ========================================================================
@@ -178,13 +212,46 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
     let window_weak = window.downgrade();
 
     dialog.connect_response(move |dialog_ref, response| {
-        if response == ResponseType::Ok {
+        if response == ResponseType::Ok && batch {
+            let format_idx = format_combo.active().unwrap_or(0);
+            let format = match format_idx {
+                1 => ExportFormat::PDF,
+                2 => ExportFormat::SVG,
+                3 => ExportFormat::Report,
+                4 => ExportFormat::Html,
+                _ => ExportFormat::PNG,
+            };
+            // Same preset → settings mapping as the single-file export; the
+            // presentation and web presets are PNG-only.
+            let (format, settings) = match preset_combo.active().unwrap_or(0) {
+                1 => (format, ExportSettings::journal_preset()),
+                2 => (ExportFormat::PNG, ExportSettings::presentation_preset()),
+                3 => (ExportFormat::PNG, ExportSettings::web_preset()),
+                _ => (
+                    format,
+                    ExportSettings {
+                        dpi: dpi_spin.value() as u32,
+                        scale: scale_spin.value(),
+                        transparent: transparent_check.is_active(),
+                        include_axes: axes_check.is_active(),
+                        include_unit_cell: unit_cell_check.is_active(),
+                        include_measurements: measurements_check.is_active(),
+                        include_bvs_summary: bvs_check.is_active(),
+                        ..ExportSettings::default()
+                    },
+                ),
+            };
+            if let Some(win) = window_weak.upgrade() {
+                choose_batch_folder(&win, state_dialog.clone(), format, settings);
+            }
+        } else if response == ResponseType::Ok {
             // Get format
             let format_idx = format_combo.active().unwrap_or(0);
             let extension = match format_idx {
                 0 => "png",
-                1 => "pdf",
+                1 | 3 => "pdf",
                 2 => "svg",
+                4 => "html",
                 _ => "png",
             };
 
@@ -205,7 +272,7 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                         filter.set_name(Some("PNG Image"));
                         filter.add_pattern("*.png");
                     }
-                    1 => {
+                    1 | 3 => {
                         filter.set_name(Some("PDF Document"));
                         filter.add_pattern("*.pdf");
                     }
@@ -213,12 +280,26 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                         filter.set_name(Some("SVG Vector"));
                         filter.add_pattern("*.svg");
                     }
+                    4 => {
+                        filter.set_name(Some("HTML Page"));
+                        filter.add_pattern("*.html");
+                    }
                     _ => {}
                 }
                 file_dialog.add_filter(&filter);
 
                 // Set suggested filename
-                file_dialog.set_current_name(&format!("structure.{}", extension));
+                let stem = if format_idx == 3 {
+                    "structure_report"
+                } else {
+                    "structure"
+                };
+                file_dialog.set_current_name(&format!("{}.{}", stem, extension));
+                crate::ui::start_in_recent_dir(
+                    &file_dialog,
+                    &state_dialog.borrow().config,
+                    RecentDir::Export,
+                );
 
                 let state_save = state_dialog.clone();
                 let preset_idx = preset_combo.active().unwrap_or(0);
@@ -227,6 +308,8 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                 let transparent = transparent_check.is_active();
                 let axes = axes_check.is_active();
                 let unit_cell = unit_cell_check.is_active();
+                let measurements = measurements_check.is_active();
+                let bvs_summary = bvs_check.is_active();
 
                 file_dialog.connect_response(move |chooser, resp| {
                     if resp == ResponseType::Accept {
@@ -244,6 +327,8 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                                                 0 => ExportFormat::PNG,
                                                 1 => ExportFormat::PDF,
                                                 2 => ExportFormat::SVG,
+                                                3 => ExportFormat::Report,
+                                                4 => ExportFormat::Html,
                                                 _ => ExportFormat::PNG,
                                             };
                                             export_for_journal(
@@ -270,6 +355,8 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                                         transparent,
                                         include_axes: axes,
                                         include_unit_cell: unit_cell,
+                                        include_measurements: measurements,
+                                        include_bvs_summary: bvs_summary,
                                         ..ExportSettings::default()
                                     };
 
@@ -292,6 +379,16 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                                                 settings,
                                             )
                                         }
+                                        3 => export_report_pdf(
+                                            state_save.clone(),
+                                            &path_str,
+                                            settings,
+                                        ),
+                                        4 => export_html_advanced(
+                                            state_save.clone(),
+                                            &path_str,
+                                            settings,
+                                        ),
                                         _ => Err("Unknown format".to_string()),
                                     }
                                 };
@@ -300,6 +397,11 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
                                 match result {
                                     Ok(msg) => {
                                         println!("✓ {}", msg);
+                                        crate::ui::remember_recent_dir(
+                                            &state_save,
+                                            RecentDir::Export,
+                                            &path,
+                                        );
                                     }
                                     Err(e) => {
                                         eprintln!("✗ Export failed: {}", e);
@@ -319,3 +421,146 @@ pub fn show_export_dialog(window: &ApplicationWindow, state: Rc<RefCell<AppState
 
     dialog.show();
 }
+
+/// Ask for a target folder, then export every open tab into it. Files that
+/// already exist are only replaced once the user agrees.
+fn choose_batch_folder(
+    window: &ApplicationWindow,
+    state: Rc<RefCell<AppState>>,
+    format: ExportFormat,
+    settings: ExportSettings,
+) {
+    let chooser = FileChooserNative::new(
+        Some("Export All Tabs To Folder"),
+        Some(window),
+        FileChooserAction::SelectFolder,
+        Some("Export"),
+        Some("Cancel"),
+    );
+    crate::ui::start_in_recent_dir(&chooser, &state.borrow().config, RecentDir::Export);
+
+    let window = window.clone();
+    chooser.connect_response(move |chooser, resp| {
+        if resp == ResponseType::Accept {
+            if let Some(dir) = chooser.file().and_then(|f| f.path()) {
+                crate::ui::remember_recent_dir(&state, RecentDir::Export, &dir);
+                let targets = batch_targets(&state.borrow(), &dir, format);
+                let existing = targets.iter().filter(|(_, p)| p.exists()).count();
+                if existing == 0 {
+                    run_batch(state.clone(), targets, format, settings.clone(), false);
+                } else {
+                    confirm_overwrite(&window, state.clone(), targets, existing, format, &settings);
+                }
+            }
+        }
+        chooser.destroy();
+    });
+
+    chooser.show();
+}
+
+/// Ask whether a batch export may replace the `existing` files already in
+/// the target folder, skip them, or not run at all.
+fn confirm_overwrite(
+    window: &ApplicationWindow,
+    state: Rc<RefCell<AppState>>,
+    targets: Vec<(String, PathBuf)>,
+    existing: usize,
+    format: ExportFormat,
+    settings: &ExportSettings,
+) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        MessageType::Question,
+        ButtonsType::None,
+        format!(
+            "{} of {} files already exist in this folder",
+            existing,
+            targets.len()
+        ),
+    );
+    dialog.set_secondary_text(Some("Overwrite them, or export only the missing files?"));
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Skip Existing", ResponseType::Reject);
+    dialog.add_button("Overwrite", ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Cancel);
+
+    let settings = settings.clone();
+    dialog.connect_response(move |d, resp| {
+        d.close();
+        let skip_existing = match resp {
+            ResponseType::Accept => false,
+            ResponseType::Reject => true,
+            _ => {
+                console::log_info("Batch export cancelled");
+                return;
+            }
+        };
+        run_batch(
+            state.clone(),
+            targets.clone(),
+            format,
+            settings.clone(),
+            skip_existing,
+        );
+    });
+    dialog.show();
+}
+
+/// Export `targets` one tab per main-loop iteration so each file is logged
+/// to the System Log as soon as it is written, not after the whole batch.
+fn run_batch(
+    state: Rc<RefCell<AppState>>,
+    targets: Vec<(String, PathBuf)>,
+    format: ExportFormat,
+    settings: ExportSettings,
+    skip_existing: bool,
+) {
+    let total = targets.len();
+    if let Some((_, path)) = targets.first() {
+        let dir = path.parent().unwrap_or(path);
+        console::log_info(&format!("Exporting {} tabs to {}", total, dir.display()));
+    }
+
+    let mut next = 0;
+    let (mut written, mut skipped, mut failed) = (0, 0, 0);
+    glib::idle_add_local(move || {
+        let Some((file, path)) = targets.get(next) else {
+            console::log_info(&format!(
+                "Batch export finished: {} written, {} skipped, {} failed",
+                written, skipped, failed
+            ));
+            return glib::ControlFlow::Break;
+        };
+        next += 1;
+
+        if skip_existing && path.exists() {
+            skipped += 1;
+            console::log_info(&format!(
+                "[{}/{}] {} skipped (already exists)",
+                next, total, file
+            ));
+            return glib::ControlFlow::Continue;
+        }
+
+        let result = {
+            let st = state.borrow();
+            match st.tabs.get(next - 1) {
+                Some(tab) => export_tab(tab, &st.config, path, format, &settings),
+                None => Err("tab was closed".to_string()),
+            }
+        };
+        match result {
+            Ok(_) => {
+                written += 1;
+                console::log_info(&format!("[{}/{}] {}", next, total, file));
+            }
+            Err(e) => {
+                failed += 1;
+                console::log_error(&format!("[{}/{}] {} failed: {}", next, total, file, e));
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+}
@@ -1,23 +1,20 @@
 // src/ui/interactions.rs
 
+use crate::config::ReportFormat;
 use crate::panels::sidebar::SidebarHandles;
 use crate::rendering::scene;
-use crate::state::{AppState, SelectedAtom};
+use crate::state::{scroll_zoom_factor, AppState, SelectedAtom, TabState};
 use crate::utils::{console, report};
 use gtk4::gdk;
 use gtk4::glib;
 use gtk4::{self as gtk, prelude::*};
 use gtk4::{
-    ApplicationWindow, EventControllerKey, EventControllerScroll, EventControllerScrollFlags,
-    GestureClick, GestureDrag, PropagationPhase,
+    ApplicationWindow, EventControllerKey, EventControllerMotion, EventControllerScroll,
+    EventControllerScrollFlags, GestureClick, GestureDrag, GestureZoom, PropagationPhase,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
 
-/// Mouse-drag rotation sensitivity in degrees per pixel. Tuned to feel close to
-/// VESTA — a swipe across the canvas gives a meaningful spin without overshooting.
-const DRAG_ROTATE_DEG_PER_PX: f64 = 0.4;
-
 pub fn setup_interactions(
     window: &ApplicationWindow,
     state: Rc<RefCell<AppState>>,
@@ -43,6 +40,7 @@ pub fn setup_interactions(
             let msg = st.delete_selected();
             console::info(&msg);
             da.queue_draw();
+            refresh_labels(&da, &st);
             return glib::Propagation::Stop;
         }
 
@@ -51,9 +49,29 @@ pub fn setup_interactions(
             let msg = st.undo();
             console::info(&msg);
             da.queue_draw();
+            refresh_labels(&da, &st);
             return glib::Propagation::Stop;
         }
 
+        // D. Measure mode (M) and clearing its picks (Esc). Every tab adds a
+        // controller to the window, so only the one whose canvas is on
+        // screen acts; text entries see their keys first.
+        let plain =
+            !state_flags.intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK);
+        if plain && da.is_mapped() {
+            if keyval == gdk::Key::m || keyval == gdk::Key::M {
+                drop(st);
+                da.activate_action("app.measure_mode", None).ok();
+                return glib::Propagation::Stop;
+            }
+            let picks = &mut st.active_tab_mut().interaction.measure_picks;
+            if keyval == gdk::Key::Escape && !picks.is_empty() {
+                picks.clear();
+                da.queue_draw();
+                return glib::Propagation::Stop;
+            }
+        }
+
         glib::Propagation::Proceed
     });
 
@@ -71,6 +89,7 @@ pub fn setup_interactions(
     drag.connect_drag_begin(move |_, x, y| {
         let mut st = s.borrow_mut();
         let tab = st.active_tab_mut();
+        tab.interaction.drag_start = (x, y);
         tab.interaction.drag_prev_offset = (0.0, 0.0);
         if tab.interaction.is_shift_pressed {
             tab.interaction.selection_box = Some(((x, y), (x, y)));
@@ -93,16 +112,18 @@ pub fn setup_interactions(
             }
         } else {
             // GestureDrag delivers cumulative offset since drag-begin, so we
-            // diff against the previous frame to get a per-frame delta and feed
-            // that into the trackball. Without diffing, each frame would re-add
-            // the entire offset and rotation would explode.
+            // rebuild the previous and current absolute cursor positions and
+            // feed the arc between them into the trackball. Applying each
+            // frame's arc incrementally keeps the motion path-dependent, the
+            // way a physical ball behaves.
+            let (sx, sy) = tab.interaction.drag_start;
             let (prev_dx, prev_dy) = tab.interaction.drag_prev_offset;
-            let dx = x - prev_dx;
-            let dy = y - prev_dy;
             tab.interaction.drag_prev_offset = (x, y);
 
+            let w = da.width() as f64;
+            let h = da.height() as f64;
             tab.view
-                .apply_screen_rotation_deg(dx * DRAG_ROTATE_DEG_PER_PX, dy * DRAG_ROTATE_DEG_PER_PX);
+                .apply_trackball((sx + prev_dx, sy + prev_dy), (sx + x, sy + y), w, h);
             handles_drag.sync_from_view(&st.active_tab().view);
             da.queue_draw();
         }
@@ -136,12 +157,7 @@ pub fn setup_interactions(
                 let tab_mut = st.active_tab_mut();
                 let mut count = 0;
                 for atom in atoms {
-                    // Ghost shells used only for coordination math are never
-                    // drawn, so they must not be selectable.
-                    if atom.is_coord_only {
-                        continue;
-                    }
-                    if atom.is_ghost && !show_ghosts {
+                    if !atom.is_pickable(show_ghosts) {
                         continue;
                     }
                     let ax = atom.screen_pos[0];
@@ -175,24 +191,134 @@ pub fn setup_interactions(
     drawing_area.add_controller(drag);
 
     // 3. SCROLL (ZOOM)
+    // Zoom keeps the point under the cursor fixed by shifting the pan offset,
+    // so the pointer needs to be tracked separately from the scroll events.
+    // The same tracking drives the hover ring and the atom tooltip.
+    let motion = EventControllerMotion::new();
+    let s = state.clone();
+    let da = drawing_area.clone();
+    motion.connect_motion(move |_, x, y| {
+        let mut st = s.borrow_mut();
+        st.active_tab_mut().interaction.pointer_pos = Some((x, y));
+
+        let w = da.width() as f64;
+        let h = da.height() as f64;
+        let show_ghosts = st.active_tab().view.show_full_unit_cell;
+        let tolerance = st.config.pick_tolerance_px;
+        let pick = |atoms: &[scene::RenderAtom]| {
+            scene::pick_atom(atoms, x, y, tolerance, show_ghosts).map(|a| {
+                let tip = format!("{} (#{})", a.element, a.original_index);
+                (a.unique_id, tip)
+            })
+        };
+        // Pick from the frame on screen; motion events far outnumber frames.
+        let hit = match &*st.active_tab().drawn_frame.borrow() {
+            Some(frame) if frame.size == (w, h) => pick(&frame.atoms),
+            _ => {
+                let (atoms, _, _) =
+                    scene::calculate_scene(st.active_tab(), &st.config, w, h, false, None, None);
+                pick(&atoms)
+            }
+        };
+
+        let hovered = hit.as_ref().map(|(id, _)| *id);
+        if hovered != st.active_tab().interaction.hovered {
+            let tip = hit.map(|(_, tip)| tip);
+            st.active_tab_mut().interaction.hovered = hovered;
+            da.set_tooltip_text(tip.as_deref());
+            da.queue_draw();
+        }
+    });
+    let s = state.clone();
+    let da = drawing_area.clone();
+    motion.connect_leave(move |_| {
+        let mut st = s.borrow_mut();
+        let interaction = &mut st.active_tab_mut().interaction;
+        interaction.pointer_pos = None;
+        if interaction.hovered.take().is_some() {
+            da.set_tooltip_text(None);
+            da.queue_draw();
+        }
+    });
+    drawing_area.add_controller(motion);
+
     let scroll = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
     let s = state.clone();
     let da = drawing_area.clone();
     let handles_scroll = sidebar_handles.clone();
     scroll.connect_scroll(move |_, _, dy| {
         let mut st = s.borrow_mut();
-        let tab = st.active_tab_mut();
-        if dy > 0.0 {
-            tab.view.zoom *= 0.9;
-        } else {
-            tab.view.zoom *= 1.1;
-        }
-        handles_scroll.sync_from_view(&st.active_tab().view);
-        da.queue_draw();
+        let cursor = st.active_tab().interaction.pointer_pos;
+        let factor = scroll_zoom_factor(dy);
+        zoom_view(&mut st, &da, &handles_scroll, factor, cursor);
         glib::Propagation::Stop
     });
     drawing_area.add_controller(scroll);
 
+    // 3a. PINCH (ZOOM) — touchpads and touch screens, about the fingers.
+    let pinch = GestureZoom::new();
+    let s = state.clone();
+    pinch.connect_begin(move |_, _| {
+        s.borrow_mut().active_tab_mut().interaction.pinch_scale = Some(1.0);
+    });
+    let s = state.clone();
+    let da = drawing_area.clone();
+    let handles_pinch = sidebar_handles.clone();
+    pinch.connect_scale_changed(move |gesture, scale| {
+        let mut st = s.borrow_mut();
+        let Some(prev) = st.active_tab().interaction.pinch_scale else {
+            return;
+        };
+        if prev <= 0.0 || scale <= 0.0 {
+            return;
+        }
+        st.active_tab_mut().interaction.pinch_scale = Some(scale);
+        let cursor = gesture.bounding_box_center();
+        zoom_view(&mut st, &da, &handles_pinch, scale / prev, cursor);
+    });
+    let s = state.clone();
+    pinch.connect_end(move |_, _| {
+        s.borrow_mut().active_tab_mut().interaction.pinch_scale = None;
+    });
+    drawing_area.add_controller(pinch);
+
+    // 3b. MIDDLE-DRAG (PAN)
+    let pan = GestureDrag::new();
+    pan.set_button(gdk::BUTTON_MIDDLE);
+    let s = state.clone();
+    let da = drawing_area.clone();
+    pan.connect_drag_begin(move |_, _, _| {
+        let mut st = s.borrow_mut();
+        let w = da.width() as f64;
+        let h = da.height() as f64;
+        let (_, _, bounds) =
+            scene::calculate_scene(st.active_tab(), &st.config, w, h, false, None, None);
+        let tab = st.active_tab_mut();
+        tab.interaction.pan_drag_origin = Some(((tab.view.pan_x, tab.view.pan_y), bounds.scale));
+    });
+
+    let s = state.clone();
+    let da = drawing_area.clone();
+    pan.connect_drag_update(move |_, x, y| {
+        let mut st = s.borrow_mut();
+        let tab = st.active_tab_mut();
+        if let Some(((px, py), scale)) = tab.interaction.pan_drag_origin {
+            if scale > 0.0 {
+                // Content follows the pointer: moving right shifts the view
+                // centre left in model space.
+                tab.view.pan_x = px - x / scale;
+                tab.view.pan_y = py - y / scale;
+                da.queue_draw();
+            }
+        }
+    });
+
+    let s = state.clone();
+    pan.connect_drag_end(move |_, _, _| {
+        s.borrow_mut().active_tab_mut().interaction.pan_drag_origin = None;
+    });
+    drawing_area.add_controller(pan);
+
     // 4. CLICK (SELECTION)
     let click = GestureClick::new();
     click.set_button(0);
@@ -200,11 +326,15 @@ pub fn setup_interactions(
 
     let s = state.clone();
     let da = drawing_area.clone();
+    let handles_click = sidebar_handles.clone();
 
     click.connect_pressed(move |gesture, _n_press, x, y| {
         let mut st = s.borrow_mut();
 
-        if st.active_tab().interaction.is_shift_pressed {
+        // Middle button belongs to the pan gesture.
+        if st.active_tab().interaction.is_shift_pressed
+            || gesture.current_button() == gdk::BUTTON_MIDDLE
+        {
             return;
         }
 
@@ -217,57 +347,105 @@ pub fn setup_interactions(
         let (atoms, _, _) =
             scene::calculate_scene(st.active_tab(), &st.config, w, h, false, None, None);
 
-        let mut sorted_atoms: Vec<_> = atoms.iter().collect();
-        sorted_atoms.sort_by(|a, b| {
-            b.screen_pos[2]
-                .partial_cmp(&a.screen_pos[2])
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        let mut clicked: Option<SelectedAtom> = None;
-        for atom in &sorted_atoms {
-            // Coordination-only ghosts are never drawn; never select them.
-            if atom.is_coord_only {
-                continue;
-            }
-            // Visible ghosts are only present on screen if "Show Full Unit Cell"
-            // is on. When it's off, don't allow clicking through to a hidden one.
-            if atom.is_ghost && !show_ghosts {
-                continue;
-            }
-            let dx = atom.screen_pos[0] - x;
-            let dy = atom.screen_pos[1] - y;
-            let dist = (dx * dx + dy * dy).sqrt();
-            if dist <= atom.screen_radius + 4.0 {
-                clicked = Some(SelectedAtom {
-                    unique_id: atom.unique_id,
-                    original_index: atom.original_index,
-                    cart_pos: atom.cart_pos,
-                    element: atom.element.clone(),
-                });
-                break;
-            }
-        }
+        let tolerance = st.config.pick_tolerance_px;
+        let clicked =
+            scene::pick_atom(&atoms, x, y, tolerance, show_ghosts).map(|atom| SelectedAtom {
+                unique_id: atom.unique_id,
+                original_index: atom.original_index,
+                cart_pos: atom.cart_pos,
+                element: atom.element.clone(),
+            });
 
         if let Some(sel) = clicked {
+            if st.active_tab().interaction.measure_mode {
+                st.active_tab_mut().add_measure_pick(sel);
+                report_measure_picks(st.active_tab(), &st.config.report_format);
+                da.queue_draw();
+                return;
+            }
             st.toggle_selection(sel);
-
-            let tab = st.active_tab();
-            // Use the cart_pos captured at selection time — ghost copies have
-            // positions distinct from structure.atoms[original_index].
-            let mut selected_atoms: Vec<(usize, String, [f64; 3])> = tab
-                .interaction
-                .selected
-                .values()
-                .map(|s| (s.unique_id, s.element.clone(), s.cart_pos))
-                .collect();
-            selected_atoms.sort_by_key(|a| a.0);
-            let text = report::geometry_analysis_from_positions(&selected_atoms);
-            console::info(&text);
-
+            report_selection(st.active_tab(), &st.config.report_format);
             da.queue_draw();
+
+            // The neighbor table follows a single picked atom; extending the
+            // selection (e.g. from that table) keeps it in place.
+            let selected = &st.active_tab().interaction.selected;
+            let center = match selected.len() {
+                0 => Some(None),
+                1 => selected
+                    .values()
+                    .next()
+                    .map(|a| Some((a.original_index, a.cart_pos))),
+                _ => None,
+            };
+            drop(st);
+            if let Some(center) = center {
+                handles_click.show_neighbors(&s, center);
+            }
         }
     });
 
     drawing_area.add_controller(click);
 }
+
+/// Measurement for the current selection (distance, angle, ...) in the
+/// Structure Info tab.
+pub fn report_selection(tab: &TabState, fmt: &ReportFormat) {
+    // Use the cart_pos captured at selection time — ghost copies have
+    // positions distinct from structure.atoms[original_index].
+    let mut selected_atoms: Vec<(usize, String, [f64; 3])> = tab
+        .interaction
+        .selected
+        .values()
+        .map(|s| (s.unique_id, s.element.clone(), s.cart_pos))
+        .collect();
+    selected_atoms.sort_by_key(|a| a.0);
+    let text = report::geometry_analysis_from_positions(&selected_atoms, fmt);
+    console::info(&text);
+}
+
+/// Distance or angle of the measure-mode picks, in click order, in the
+/// Structure Info tab. Nothing is reported for a single pick.
+fn report_measure_picks(tab: &TabState, fmt: &ReportFormat) {
+    let picks: Vec<(usize, String, [f64; 3])> = tab
+        .interaction
+        .measure_picks
+        .iter()
+        .map(|s| (s.unique_id, s.element.clone(), s.cart_pos))
+        .collect();
+    if picks.len() >= 2 {
+        console::info(&report::geometry_analysis_from_positions(&picks, fmt));
+    }
+}
+
+/// Zoom the active tab by `factor` about `cursor` (widget pixels; the
+/// canvas center when `None`), within the view's zoom limits, then move the
+/// sidebar slider to match and redraw.
+fn zoom_view(
+    st: &mut AppState,
+    da: &gtk::DrawingArea,
+    handles: &SidebarHandles,
+    factor: f64,
+    cursor: Option<(f64, f64)>,
+) {
+    let w = da.width() as f64;
+    let h = da.height() as f64;
+    let (_, _, bounds) =
+        scene::calculate_scene(st.active_tab(), &st.config, w, h, false, None, None);
+
+    let view = &mut st.active_tab_mut().view;
+    let cursor = cursor.unwrap_or((w / 2.0, h / 2.0));
+    view.zoom_about(factor, cursor, (w, h), bounds.scale);
+    handles.sync_from_view(view);
+    da.queue_draw();
+}
+
+/// Update the unsaved-changes markers of the notebook holding `da`.
+fn refresh_labels(da: &gtk::DrawingArea, state: &AppState) {
+    if let Some(nb) = da
+        .ancestor(gtk::Notebook::static_type())
+        .and_downcast::<gtk::Notebook>()
+    {
+        crate::ui::refresh_tab_labels(&nb, state);
+    }
+}
@@ -4,16 +4,21 @@
 //   2. Appearance   — 8 settings (colors, toggles, scales)
 //   3. Export/Plot  — 6 settings (charge density export font sizes, colormap)
 //
+// Settings each tab keeps its own copy of (colors, scales, bonds, boundary
+// images) go to the current tab, to the defaults new tabs start from, or to
+// both, as the "Apply changes to" choice at the top says. Everything else
+// is global. The controls show the defaults.
+//
 // Removed tabs (settings kept in Config for serde backward-compat):
 //   - Bond Valence  (3 settings — none were wired to runtime behavior)
 //   - Performance   (5 settings — none were wired to runtime behavior)
 //   - Advanced      (5 settings — none were wired to runtime behavior)
 
-use crate::config::RotationCenter;
+use crate::config::{Config, LengthUnit, ReportFormat, RotationCenter, SaveFormat};
 use crate::model::elements::ColorScheme;
-use crate::state::AppState;
+use crate::state::{AppState, TabState};
 use gtk4::{self as gtk, gdk, prelude::*};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 pub fn show_preferences_window(
@@ -31,21 +36,35 @@ pub fn show_preferences_window(
         .build();
 
     let main_vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
-    let notebook = gtk::Notebook::new();
-    notebook.set_vexpand(true);
-
-    // TAB 1: General
-    let general_tab = build_general_tab(state.clone(), drawing_area.clone());
-    notebook.append_page(&general_tab, Some(&gtk::Label::new(Some("General"))));
 
-    // TAB 2: Appearance
-    let appearance_tab = build_appearance_tab(state.clone(), drawing_area.clone());
-    notebook.append_page(&appearance_tab, Some(&gtk::Label::new(Some("Appearance"))));
+    let scope: SharedScope = Rc::new(Cell::new(Scope::TabAndDefaults));
+    let scope_row = gtk::Box::new(gtk::Orientation::Horizontal, 10);
+    scope_row.set_margin_top(10);
+    scope_row.set_margin_start(20);
+    scope_row.set_margin_end(20);
+    let scope_label = gtk::Label::new(Some("Apply changes to:"));
+    scope_label.set_halign(gtk::Align::Start);
+    scope_label.set_hexpand(true);
+    scope_row.append(&scope_label);
+    let scope_dropdown = gtk::DropDown::from_strings(&Scope::LABELS);
+    scope_dropdown.set_tooltip_text(Some(
+        "Colors, scales, bonds and boundary images belong to each tab; new tabs \
+         start from the defaults. All other settings are global.",
+    ));
+    let scope_dd = scope.clone();
+    scope_dropdown.connect_selected_notify(move |d| scope_dd.set(Scope::from_index(d.selected())));
+    scope_row.append(&scope_dropdown);
+    main_vbox.append(&scope_row);
 
-    // TAB 3: Export / Plot
-    let export_tab = build_export_plot_tab(state.clone());
-    notebook.append_page(&export_tab, Some(&gtk::Label::new(Some("Export / Plot"))));
+    let notebook = gtk::Notebook::new();
+    notebook.set_vexpand(true);
 
+    append_tabs(
+        &notebook,
+        state.clone(),
+        drawing_area.clone(),
+        scope.clone(),
+    );
     main_vbox.append(&notebook);
 
     // Footer
@@ -55,6 +74,20 @@ pub fn show_preferences_window(
     footer.set_margin_end(10);
     footer.set_halign(gtk::Align::End);
 
+    let btn_reset = gtk::Button::with_label("Restore Defaults");
+    let win_reset = window.clone();
+    let nb_reset = notebook.clone();
+    btn_reset.connect_clicked(move |_| {
+        confirm_restore_defaults(
+            &win_reset,
+            state.clone(),
+            &nb_reset,
+            drawing_area.clone(),
+            scope.clone(),
+        );
+    });
+    footer.append(&btn_reset);
+
     let btn_close = gtk::Button::with_label("Close");
     let win_clone = window.clone();
     btn_close.connect_clicked(move |_| win_clone.close());
@@ -65,11 +98,158 @@ pub fn show_preferences_window(
     window.present();
 }
 
+/// Where Preferences writes the settings each tab keeps its own copy of.
+#[derive(Clone, Copy, PartialEq)]
+enum Scope {
+    TabAndDefaults,
+    CurrentTab,
+    Defaults,
+}
+
+impl Scope {
+    const LABELS: [&'static str; 3] = [
+        "Current Tab and New Tabs",
+        "Current Tab Only",
+        "New Tabs Only (Defaults)",
+    ];
+
+    fn from_index(i: u32) -> Self {
+        match i {
+            1 => Scope::CurrentTab,
+            2 => Scope::Defaults,
+            _ => Scope::TabAndDefaults,
+        }
+    }
+
+    fn tab(self) -> bool {
+        self != Scope::Defaults
+    }
+
+    fn defaults(self) -> bool {
+        self != Scope::CurrentTab
+    }
+}
+
+type SharedScope = Rc<Cell<Scope>>;
+
+/// Write a per-tab setting as `scope` asks: `tab` edits the active tab,
+/// `default` the config new tabs copy (saved right away).
+fn set_per_tab(
+    state: &Rc<RefCell<AppState>>,
+    scope: &SharedScope,
+    tab: impl FnOnce(&mut TabState),
+    default: impl FnOnce(&mut Config),
+) {
+    let scope = scope.get();
+    let mut st = state.borrow_mut();
+    if scope.tab() && !st.tabs.is_empty() {
+        tab(st.active_tab_mut());
+    }
+    if scope.defaults() {
+        default(&mut st.config);
+        st.save_config();
+    }
+}
+
+fn append_tabs(
+    notebook: &gtk::Notebook,
+    state: Rc<RefCell<AppState>>,
+    da: gtk::DrawingArea,
+    scope: SharedScope,
+) {
+    // TAB 1: General
+    let general_tab = build_general_tab(state.clone(), da.clone(), scope.clone());
+    notebook.append_page(&general_tab, Some(&gtk::Label::new(Some("General"))));
+
+    // TAB 2: Appearance
+    let appearance_tab = build_appearance_tab(state.clone(), da, scope);
+    notebook.append_page(&appearance_tab, Some(&gtk::Label::new(Some("Appearance"))));
+
+    // TAB 3: Export / Plot
+    let export_tab = build_export_plot_tab(state);
+    notebook.append_page(&export_tab, Some(&gtk::Label::new(Some("Export / Plot"))));
+}
+
+/// Ask before wiping every preference, then reset `config`, persist it,
+/// reset the current tab too when the scope includes it, and rebuild the
+/// pages so every widget shows its default. With "Current Tab Only" just
+/// the tab goes back to the defaults, which the controls already show.
+fn confirm_restore_defaults(
+    window: &gtk::Window,
+    state: Rc<RefCell<AppState>>,
+    notebook: &gtk::Notebook,
+    da: gtk::DrawingArea,
+    scope: SharedScope,
+) {
+    let tab_only = scope.get() == Scope::CurrentTab;
+    let (question, detail) = if tab_only {
+        (
+            "Reset the current tab to the defaults?",
+            "Its colors, scales, bonds and boundary images are replaced by the \
+             settings new tabs start from. The defaults are kept.",
+        )
+    } else {
+        (
+            "Restore all preferences to their defaults?",
+            "Your current settings will be overwritten. Saved presets, recent folders \
+             and the window layout are kept.",
+        )
+    };
+    let dialog = gtk::MessageDialog::new(
+        Some(window),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        gtk::MessageType::Question,
+        gtk::ButtonsType::YesNo,
+        question,
+    );
+    dialog.set_secondary_text(Some(detail));
+
+    let nb = notebook.clone();
+    dialog.connect_response(move |d, response| {
+        d.close();
+        if response != gtk::ResponseType::Yes {
+            return;
+        }
+        if tab_only {
+            let mut st = state.borrow_mut();
+            if !st.tabs.is_empty() {
+                let config = st.config.clone();
+                st.active_tab_mut().reset_to_defaults(&config);
+            }
+            drop(st);
+            da.queue_draw();
+            return;
+        }
+        {
+            let mut st = state.borrow_mut();
+            st.config.restore_defaults();
+            if scope.get().tab() && !st.tabs.is_empty() {
+                let config = st.config.clone();
+                st.active_tab_mut().reset_to_defaults(&config);
+            }
+            st.save_config();
+        }
+
+        let page = nb.current_page();
+        while nb.n_pages() > 0 {
+            nb.remove_page(None);
+        }
+        append_tabs(&nb, state.clone(), da.clone(), scope.clone());
+        nb.set_current_page(page);
+        da.queue_draw();
+    });
+    dialog.present();
+}
+
 // ============================================================================
-// TAB 1: GENERAL (7 settings)
+// TAB 1: GENERAL (11 settings)
 // ============================================================================
 
-fn build_general_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> gtk::Box {
+fn build_general_tab(
+    state: Rc<RefCell<AppState>>,
+    da: gtk::DrawingArea,
+    scope: SharedScope,
+) -> gtk::Box {
     let vbox = gtk::Box::new(gtk::Orientation::Vertical, 15);
     vbox.set_margin_top(20);
     vbox.set_margin_bottom(20);
@@ -88,19 +268,24 @@ fn build_general_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> gtk:
     vbox.append(&check1);
 
     // 2. Show Bonds
-    let check2 = gtk::CheckButton::with_label("Show Bonds by Default");
+    let check2 = gtk::CheckButton::with_label("Show Bonds");
     check2.set_active(state.borrow().config.default_show_bonds);
-    let s2 = state.clone();
+    let (s2, scope2, da2) = (state.clone(), scope.clone(), da.clone());
     check2.connect_toggled(move |c| {
-        let mut st = s2.borrow_mut();
-        st.config.default_show_bonds = c.is_active();
-        st.save_config();
+        let on = c.is_active();
+        set_per_tab(
+            &s2,
+            &scope2,
+            |t| t.view.show_bonds = on,
+            |cfg| cfg.default_show_bonds = on,
+        );
+        da2.queue_draw();
     });
     vbox.append(&check2);
 
     // 3. Bond Tolerance
     vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
-    let tol_label = gtk::Label::new(Some("Default Bond Tolerance:"));
+    let tol_label = gtk::Label::new(Some("Bond Tolerance:"));
     tol_label.set_halign(gtk::Align::Start);
     vbox.append(&tol_label);
 
@@ -108,11 +293,16 @@ fn build_general_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> gtk:
     tol_scale.set_value(state.borrow().config.default_bond_tolerance);
     tol_scale.set_draw_value(true);
     tol_scale.set_value_pos(gtk::PositionType::Right);
-    let s3 = state.clone();
+    let (s3, scope3, da3) = (state.clone(), scope.clone(), da.clone());
     tol_scale.connect_value_changed(move |sc| {
-        let mut st = s3.borrow_mut();
-        st.config.default_bond_tolerance = sc.value();
-        st.save_config();
+        let v = sc.value();
+        set_per_tab(
+            &s3,
+            &scope3,
+            |t| t.view.bond_cutoff = v,
+            |cfg| cfg.default_bond_tolerance = v,
+        );
+        da3.queue_draw();
     });
     vbox.append(&tol_scale);
 
@@ -179,17 +369,230 @@ fn build_general_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> gtk:
     });
     vbox.append(&check7);
 
-    // Suppress unused variable warning for `da` (kept for API consistency)
-    let _ = da;
+    let check7b = gtk::CheckButton::with_label("Remember Window Size and Panes");
+    check7b.set_active(state.borrow().config.remember_window_layout);
+    let s7b = state.clone();
+    check7b.connect_toggled(move |c| {
+        let mut st = s7b.borrow_mut();
+        st.config.remember_window_layout = c.is_active();
+        st.save_config();
+    });
+    vbox.append(&check7b);
+
+    // 8. Standard orientation on save
+    let check8 =
+        gtk::CheckButton::with_label("Rotate to Standard Orientation When Saving POSCAR/QE");
+    check8.set_active(state.borrow().config.standard_orientation_on_save);
+    let s8 = state.clone();
+    check8.connect_toggled(move |c| {
+        let mut st = s8.borrow_mut();
+        st.config.standard_orientation_on_save = c.is_active();
+        st.save_config();
+    });
+    vbox.append(&check8);
+
+    let format_row = gtk::Box::new(gtk::Orientation::Horizontal, 10);
+    let format_label = gtk::Label::new(Some("Default Save Format:"));
+    format_label.set_halign(gtk::Align::Start);
+    format_label.set_hexpand(true);
+    format_row.append(&format_label);
+    let format_names: Vec<&str> = SaveFormat::ALL.iter().map(|f| f.label()).collect();
+    let format_dropdown = gtk::DropDown::from_strings(&format_names);
+    format_dropdown.set_selected(state.borrow().config.default_save_format.index() as u32);
+    let s_format = state.clone();
+    format_dropdown.connect_selected_notify(move |d| {
+        let mut st = s_format.borrow_mut();
+        if let Some(format) = SaveFormat::ALL.get(d.selected() as usize) {
+            st.config.default_save_format = *format;
+            st.save_config();
+        }
+    });
+    format_row.append(&format_dropdown);
+    vbox.append(&format_row);
+
+    // 9. Bond valence on load
+    vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+    let check9 = gtk::CheckButton::with_label("Calculate Bond Valence Sums on Load");
+    check9.set_active(state.borrow().config.auto_calc_bvs);
+    let check10 = gtk::CheckButton::with_label("Show BVS Report");
+    check10.set_active(state.borrow().config.show_bvs_report);
+    check10.set_margin_start(20);
+    check10.set_sensitive(check9.is_active());
+    let check11 = gtk::CheckButton::with_label("Warn When Bond Valence Sums Are Poor");
+    check11.set_active(state.borrow().config.warn_poor_bvs);
+    check11.set_margin_start(20);
+    check11.set_sensitive(check9.is_active());
+
+    let s9 = state.clone();
+    let (c10, c11) = (check10.clone(), check11.clone());
+    check9.connect_toggled(move |c| {
+        let mut st = s9.borrow_mut();
+        st.config.auto_calc_bvs = c.is_active();
+        st.save_config();
+        c10.set_sensitive(c.is_active());
+        c11.set_sensitive(c.is_active());
+    });
+    let s10 = state.clone();
+    check10.connect_toggled(move |c| {
+        let mut st = s10.borrow_mut();
+        st.config.show_bvs_report = c.is_active();
+        st.save_config();
+    });
+    let s11 = state.clone();
+    check11.connect_toggled(move |c| {
+        let mut st = s11.borrow_mut();
+        st.config.warn_poor_bvs = c.is_active();
+        st.save_config();
+    });
+    vbox.append(&check9);
+    vbox.append(&check10);
+    vbox.append(&check11);
+
+    // 10. Report number formatting (Structure Info, neighbor table)
+    vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+    let unit_row = gtk::Box::new(gtk::Orientation::Horizontal, 10);
+    let unit_label = gtk::Label::new(Some("Report Length Unit:"));
+    unit_label.set_halign(gtk::Align::Start);
+    unit_label.set_hexpand(true);
+    unit_row.append(&unit_label);
+    let unit_dropdown = gtk::DropDown::from_strings(&["Å", "pm"]);
+    unit_dropdown.set_selected(match state.borrow().config.report_format.length_unit {
+        LengthUnit::Angstrom => 0,
+        LengthUnit::Picometer => 1,
+    });
+    let s_unit = state.clone();
+    unit_dropdown.connect_selected_notify(move |d| {
+        let mut st = s_unit.borrow_mut();
+        st.config.report_format.length_unit = if d.selected() == 1 {
+            LengthUnit::Picometer
+        } else {
+            LengthUnit::Angstrom
+        };
+        st.save_config();
+    });
+    unit_row.append(&unit_dropdown);
+    vbox.append(&unit_row);
+
+    let fmt = state.borrow().config.report_format.clone();
+    /// (label, current value, setter)
+    type DecimalRow = (&'static str, usize, fn(&mut ReportFormat, usize));
+    let decimal_rows: [DecimalRow; 3] = [
+        ("Length Decimals (Å):", fmt.length_decimals, |f, n| {
+            f.length_decimals = n
+        }),
+        ("Angle Decimals:", fmt.angle_decimals, |f, n| {
+            f.angle_decimals = n
+        }),
+        (
+            "Other Decimals (volume, v.u.):",
+            fmt.value_decimals,
+            |f, n| f.value_decimals = n,
+        ),
+    ];
+    for (label, value, set) in decimal_rows {
+        let row = labeled_spin(label, 0.0, 8.0, 1.0, value as f64);
+        let s_dec = state.clone();
+        row.1.connect_value_changed(move |sp| {
+            let mut st = s_dec.borrow_mut();
+            set(&mut st.config.report_format, sp.value() as usize);
+            st.save_config();
+        });
+        vbox.append(&row.0);
+    }
+
+    // 11. Picking tolerance
+    vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+    let pick_row = labeled_spin(
+        "Atom Picking Tolerance (px):",
+        0.0,
+        20.0,
+        1.0,
+        state.borrow().config.pick_tolerance_px,
+    );
+    let s_pick = state.clone();
+    pick_row.1.connect_value_changed(move |sp| {
+        let mut st = s_pick.borrow_mut();
+        st.config.pick_tolerance_px = sp.value();
+        st.save_config();
+    });
+    vbox.append(&pick_row.0);
+
+    let check_meas = gtk::CheckButton::with_label("Label Measured Distances and Angles");
+    check_meas.set_active(state.borrow().config.label_measurements);
+    let s_meas = state.clone();
+    check_meas.connect_toggled(move |c| {
+        let mut st = s_meas.borrow_mut();
+        st.config.label_measurements = c.is_active();
+        st.save_config();
+    });
+    vbox.append(&check_meas);
+
+    // 12. Large structure threshold
+    let large_row = labeled_spin(
+        "Large Structure Threshold (atoms):",
+        1000.0,
+        1_000_000.0,
+        1000.0,
+        state.borrow().config.max_atoms_display as f64,
+    );
+    large_row.1.set_width_chars(8);
+    large_row.1.set_tooltip_text(Some(
+        "Larger files parse in the background; larger structures open with bonds and boundary images off",
+    ));
+    let s_large = state.clone();
+    large_row.1.connect_value_changed(move |sp| {
+        let mut st = s_large.borrow_mut();
+        st.config.max_atoms_display = sp.value() as usize;
+        st.save_config();
+    });
+    vbox.append(&large_row.0);
+
+    // 12b. Autosave
+    let autosave_row = labeled_spin(
+        "Autosave Session Every (min, 0 = off):",
+        0.0,
+        120.0,
+        1.0,
+        state.borrow().config.autosave_minutes as f64,
+    );
+    autosave_row.1.set_tooltip_text(Some(
+        "Snapshot the open tabs so they can be recovered after a crash; \
+         skipped while nothing has changed",
+    ));
+    let s_autosave = state.clone();
+    autosave_row.1.connect_value_changed(move |sp| {
+        let mut st = s_autosave.borrow_mut();
+        st.config.autosave_minutes = sp.value() as u32;
+        st.save_config();
+    });
+    vbox.append(&autosave_row.0);
+
+    // 13. Format detection
+    let check13 = gtk::CheckButton::with_label("Auto-Detect File Format from Contents");
+    check13.set_active(state.borrow().config.auto_detect_format);
+    check13.set_tooltip_text(Some(
+        "Recognise CIF, POSCAR, QE, SPR-KKR, CASTEP and XYZ files by their contents, so misnamed files still open",
+    ));
+    let s13 = state.clone();
+    check13.connect_toggled(move |c| {
+        let mut st = s13.borrow_mut();
+        st.config.auto_detect_format = c.is_active();
+        st.save_config();
+    });
+    vbox.append(&check13);
 
     vbox
 }
 
 // ============================================================================
-// TAB 2: APPEARANCE (8 settings)
+// TAB 2: APPEARANCE (11 settings)
 // ============================================================================
 
-fn build_appearance_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> gtk::Box {
+fn build_appearance_tab(
+    state: Rc<RefCell<AppState>>,
+    da: gtk::DrawingArea,
+    scope: SharedScope,
+) -> gtk::Box {
     let vbox = gtk::Box::new(gtk::Orientation::Vertical, 15);
     vbox.set_margin_top(20);
     vbox.set_margin_bottom(20);
@@ -206,21 +609,17 @@ fn build_appearance_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> g
     let bg_btn = gtk::ColorButton::new();
     let bg = state.borrow().config.style.background_color;
     bg_btn.set_rgba(&gdk::RGBA::new(bg.0 as f32, bg.1 as f32, bg.2 as f32, 1.0));
-    let s_bg = state.clone();
+    let (s_bg, scope_bg) = (state.clone(), scope.clone());
     let da_bg = da.clone();
     bg_btn.connect_color_set(move |btn| {
         let rgba = btn.rgba();
-        let mut st = s_bg.borrow_mut();
-        st.config.style.background_color =
-            (rgba.red() as f64, rgba.green() as f64, rgba.blue() as f64);
-
-        // Update active tab immediately
-        if !st.tabs.is_empty() {
-            st.active_tab_mut().style.background_color = st.config.style.background_color;
-        }
-
-        st.save_config();
-        drop(st);
+        let c = (rgba.red() as f64, rgba.green() as f64, rgba.blue() as f64);
+        set_per_tab(
+            &s_bg,
+            &scope_bg,
+            |t| t.style.background_color = c,
+            |cfg| cfg.style.background_color = c,
+        );
         da_bg.queue_draw();
     });
     bg_row.append(&bg_btn);
@@ -228,7 +627,7 @@ fn build_appearance_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> g
 
     // 2. Bond Color
     let bc_row = gtk::Box::new(gtk::Orientation::Horizontal, 10);
-    let bc_label = gtk::Label::new(Some("Default Bond Color:"));
+    let bc_label = gtk::Label::new(Some("Bond Color:"));
     bc_label.set_hexpand(true);
     bc_label.set_halign(gtk::Align::Start);
     bc_row.append(&bc_label);
@@ -236,59 +635,119 @@ fn build_appearance_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> g
     let bc_btn = gtk::ColorButton::new();
     let bc = state.borrow().config.style.bond_color;
     bc_btn.set_rgba(&gdk::RGBA::new(bc.0 as f32, bc.1 as f32, bc.2 as f32, 1.0));
-    let s_bc = state.clone();
+    let (s_bc, scope_bc) = (state.clone(), scope.clone());
     let da_bc = da.clone();
     bc_btn.connect_color_set(move |btn| {
         let rgba = btn.rgba();
-        let mut st = s_bc.borrow_mut();
-        st.config.style.bond_color = (rgba.red() as f64, rgba.green() as f64, rgba.blue() as f64);
-
-        // Update active tab immediately
-        if !st.tabs.is_empty() {
-            st.active_tab_mut().style.bond_color = st.config.style.bond_color;
-        }
-
-        st.save_config();
-        drop(st);
+        let c = (rgba.red() as f64, rgba.green() as f64, rgba.blue() as f64);
+        set_per_tab(
+            &s_bc,
+            &scope_bc,
+            |t| t.style.bond_color = c,
+            |cfg| cfg.style.bond_color = c,
+        );
         da_bc.queue_draw();
     });
     bc_row.append(&bc_btn);
     vbox.append(&bc_row);
 
+    // 2b. Unit cell lines: color, width, dashed
+    let cl_row = gtk::Box::new(gtk::Orientation::Horizontal, 10);
+    let cl_label = gtk::Label::new(Some("Unit Cell Lines:"));
+    cl_label.set_hexpand(true);
+    cl_label.set_halign(gtk::Align::Start);
+    cl_row.append(&cl_label);
+
+    let style = state.borrow().config.style.clone();
+    let cl_btn = gtk::ColorButton::new();
+    let cl = style.cell_line_color;
+    cl_btn.set_rgba(&gdk::RGBA::new(cl.0 as f32, cl.1 as f32, cl.2 as f32, 1.0));
+    let (s_cl, scope_cl) = (state.clone(), scope.clone());
+    let da_cl = da.clone();
+    cl_btn.connect_color_set(move |btn| {
+        let rgba = btn.rgba();
+        let c = (rgba.red() as f64, rgba.green() as f64, rgba.blue() as f64);
+        set_per_tab(
+            &s_cl,
+            &scope_cl,
+            |t| t.style.cell_line_color = c,
+            |cfg| cfg.style.cell_line_color = c,
+        );
+        da_cl.queue_draw();
+    });
+    cl_row.append(&cl_btn);
+
+    let cw_spin = gtk::SpinButton::with_range(0.5, 6.0, 0.5);
+    cw_spin.set_digits(1);
+    cw_spin.set_value(style.cell_line_width);
+    cw_spin.set_tooltip_text(Some("Line width"));
+    let (s_cw, scope_cw) = (state.clone(), scope.clone());
+    let da_cw = da.clone();
+    cw_spin.connect_value_changed(move |sp| {
+        let w = sp.value();
+        set_per_tab(
+            &s_cw,
+            &scope_cw,
+            |t| t.style.cell_line_width = w,
+            |cfg| cfg.style.cell_line_width = w,
+        );
+        da_cw.queue_draw();
+    });
+    cl_row.append(&cw_spin);
+
+    let cd_check = gtk::CheckButton::with_label("Dashed");
+    cd_check.set_active(style.cell_line_dashed);
+    let (s_cd, scope_cd) = (state.clone(), scope.clone());
+    let da_cd = da.clone();
+    cd_check.connect_toggled(move |c| {
+        let dashed = c.is_active();
+        set_per_tab(
+            &s_cd,
+            &scope_cd,
+            |t| t.style.cell_line_dashed = dashed,
+            |cfg| cfg.style.cell_line_dashed = dashed,
+        );
+        da_cd.queue_draw();
+    });
+    cl_row.append(&cd_check);
+    vbox.append(&cl_row);
+
     // 3. Atom Color Scheme
     vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
     let cs_label = gtk::Label::new(Some("Atom Color Scheme:"));
     cs_label.set_halign(gtk::Align::Start);
     vbox.append(&cs_label);
 
-    let cs_dropdown = gtk::DropDown::from_strings(&[
-        "Group/Material (Default)",
-        "Jmol",
-        "CPK Classic",
-        "RasMol",
-        "Electronegativity",
-        "Atomic Radius",
-    ]);
-    cs_dropdown.set_selected(match state.borrow().config.color_scheme {
-        ColorScheme::GroupMaterial => 0,
-        ColorScheme::Jmol => 1,
-        ColorScheme::CpkClassic => 2,
-        ColorScheme::RasMol => 3,
-        ColorScheme::Electronegativity => 4,
-        ColorScheme::AtomicRadius => 5,
-    });
+    // Built from `ColorScheme::ALL` so the list can't drift from the enum.
+    // Jmol/CPK are the community-standard palettes (white H, red O, blue N)
+    // for matching published figures; Group/Material stays the default.
+    let cs_names: Vec<String> = ColorScheme::ALL
+        .iter()
+        .map(|s| {
+            if *s == ColorScheme::default() {
+                format!("{} (Default)", s.label())
+            } else {
+                s.label().to_string()
+            }
+        })
+        .collect();
+    let cs_refs: Vec<&str> = cs_names.iter().map(|s| s.as_str()).collect();
+    let cs_dropdown = gtk::DropDown::from_strings(&cs_refs);
+    let current_scheme = state.borrow().config.color_scheme;
+    cs_dropdown.set_selected(
+        ColorScheme::ALL
+            .iter()
+            .position(|s| *s == current_scheme)
+            .unwrap_or(0) as u32,
+    );
     let s_cs = state.clone();
     let da_cs = da.clone();
     cs_dropdown.connect_selected_notify(move |d| {
         let mut st = s_cs.borrow_mut();
-        st.config.color_scheme = match d.selected() {
-            1 => ColorScheme::Jmol,
-            2 => ColorScheme::CpkClassic,
-            3 => ColorScheme::RasMol,
-            4 => ColorScheme::Electronegativity,
-            5 => ColorScheme::AtomicRadius,
-            _ => ColorScheme::GroupMaterial,
-        };
+        st.config.color_scheme = ColorScheme::ALL
+            .get(d.selected() as usize)
+            .copied()
+            .unwrap_or_default();
         // Sprite cache holds rasterized atoms colored under the previous scheme;
         // clear all tabs' caches so the new scheme renders immediately.
         for tab in st.tabs.iter_mut() {
@@ -325,6 +784,33 @@ fn build_appearance_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> g
     }
     vbox.append(&rq_dropdown);
 
+    let aa_label = gtk::Label::new(Some("Antialiasing:"));
+    aa_label.set_halign(gtk::Align::Start);
+    vbox.append(&aa_label);
+
+    let aa_dropdown = gtk::DropDown::from_strings(&["None", "Fast", "Good", "Best"]);
+    {
+        use crate::config::AntialiasLevel;
+        aa_dropdown.set_selected(match state.borrow().config.antialias_level {
+            AntialiasLevel::None => 0,
+            AntialiasLevel::Fast => 1,
+            AntialiasLevel::Good => 2,
+            AntialiasLevel::Best => 3,
+        });
+        let s_aa = state.clone();
+        aa_dropdown.connect_selected_notify(move |d| {
+            let mut st = s_aa.borrow_mut();
+            st.config.antialias_level = match d.selected() {
+                0 => AntialiasLevel::None,
+                1 => AntialiasLevel::Fast,
+                3 => AntialiasLevel::Best,
+                _ => AntialiasLevel::Good,
+            };
+            st.save_config();
+        });
+    }
+    vbox.append(&aa_dropdown);
+
     // 4-6. Checkboxes
     vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
 
@@ -348,49 +834,176 @@ fn build_appearance_tab(state: Rc<RefCell<AppState>>, da: gtk::DrawingArea) -> g
     });
     vbox.append(&check_cell);
 
-    let check_ghost = gtk::CheckButton::with_label("Show Ghost Atoms (Supercell Boundaries)");
+    let check_ghost = gtk::CheckButton::with_label("Draw Cell Boundary Images as Faint Ghosts");
     check_ghost.set_active(state.borrow().config.show_ghost_atoms);
-    let s_ghost = state.clone();
+    let (s_ghost, scope_ghost) = (state.clone(), scope.clone());
+    let da_ghost = da.clone();
     check_ghost.connect_toggled(move |c| {
-        let mut st = s_ghost.borrow_mut();
-        st.config.show_ghost_atoms = c.is_active();
-        st.save_config();
+        let faint = c.is_active();
+        set_per_tab(
+            &s_ghost,
+            &scope_ghost,
+            |t| t.view.faint_ghosts = faint,
+            |cfg| cfg.show_ghost_atoms = faint,
+        );
+        da_ghost.queue_draw();
     });
     vbox.append(&check_ghost);
 
     // 7-8. Sliders
     vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
 
-    let as_label = gtk::Label::new(Some("Default Atom Scale:"));
+    let as_label = gtk::Label::new(Some("Atom Scale:"));
     as_label.set_halign(gtk::Align::Start);
     vbox.append(&as_label);
     let as_scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.3, 1.5, 0.05);
     as_scale.set_value(state.borrow().config.default_atom_scale);
     as_scale.set_draw_value(true);
-    let s_as = state.clone();
+    let (s_as, scope_as, da_as) = (state.clone(), scope.clone(), da.clone());
     as_scale.connect_value_changed(move |sc| {
-        let mut st = s_as.borrow_mut();
-        st.config.default_atom_scale = sc.value();
-        st.save_config();
+        let v = sc.value();
+        set_per_tab(
+            &s_as,
+            &scope_as,
+            |t| t.style.atom_scale = v,
+            |cfg| cfg.default_atom_scale = v,
+        );
+        da_as.queue_draw();
     });
     vbox.append(&as_scale);
 
-    let br_label = gtk::Label::new(Some("Default Bond Radius:"));
+    let br_label = gtk::Label::new(Some("Bond Radius:"));
     br_label.set_halign(gtk::Align::Start);
     vbox.append(&br_label);
     let br_scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.01, 0.5, 0.01);
     br_scale.set_value(state.borrow().config.default_bond_radius);
     br_scale.set_draw_value(true);
-    let s_br = state.clone();
+    let (s_br, scope_br, da_br) = (state.clone(), scope.clone(), da.clone());
     br_scale.connect_value_changed(move |sc| {
-        let mut st = s_br.borrow_mut();
-        st.config.default_bond_radius = sc.value();
-        st.save_config();
+        let v = sc.value();
+        set_per_tab(
+            &s_br,
+            &scope_br,
+            |t| t.style.bond_radius = v,
+            |cfg| cfg.default_bond_radius = v,
+        );
+        da_br.queue_draw();
     });
     vbox.append(&br_scale);
 
-    // Suppress unused variable warning for `da` clone not used beyond color buttons
-    let _ = da;
+    // 9. Depth cueing and the depth range it maps onto
+    vbox.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+
+    let check_cue = gtk::CheckButton::with_label("Depth Cueing (Fade Distant Atoms and Bonds)");
+    check_cue.set_active(state.borrow().config.depth_cue_enabled);
+    vbox.append(&check_cue);
+
+    let cue_label = gtk::Label::new(Some("Depth Cue Strength:"));
+    cue_label.set_halign(gtk::Align::Start);
+    vbox.append(&cue_label);
+    let cue_scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.05, 1.0, 0.05);
+    cue_scale.set_value(state.borrow().config.depth_cue_strength);
+    cue_scale.set_draw_value(true);
+    cue_scale.set_tooltip_text(Some(
+        "How far the farthest atoms blend into the background.",
+    ));
+    cue_scale.set_sensitive(state.borrow().config.depth_cue_enabled);
+    vbox.append(&cue_scale);
+
+    let (s_cue, da_cue, scale_cue) = (state.clone(), da.clone(), cue_scale.clone());
+    check_cue.connect_toggled(move |c| {
+        scale_cue.set_sensitive(c.is_active());
+        let mut st = s_cue.borrow_mut();
+        st.config.depth_cue_enabled = c.is_active();
+        st.save_config();
+        drop(st);
+        da_cue.queue_draw();
+    });
+    let (s_cs, da_cs) = (state.clone(), da.clone());
+    cue_scale.connect_value_changed(move |sc| {
+        let mut st = s_cs.borrow_mut();
+        st.config.depth_cue_strength = sc.value();
+        st.save_config();
+        drop(st);
+        da_cs.queue_draw();
+    });
+
+    let check_depth = gtk::CheckButton::with_label("Fit Depth Range to Structure Each Frame");
+    check_depth.set_tooltip_text(Some(
+        "Fog and depth cueing span the nearest to the farthest atom. \
+         Turn off to fix the range (Å from the rotation centre, negative towards you).",
+    ));
+    check_depth.set_active(state.borrow().config.depth_range_auto);
+    vbox.append(&check_depth);
+
+    let depth_row = gtk::Box::new(gtk::Orientation::Horizontal, 10);
+    let near_spin = gtk::SpinButton::with_range(-500.0, 500.0, 0.5);
+    near_spin.set_digits(1);
+    near_spin.set_value(state.borrow().config.depth_near);
+    let far_spin = gtk::SpinButton::with_range(-500.0, 500.0, 0.5);
+    far_spin.set_digits(1);
+    far_spin.set_value(state.borrow().config.depth_far);
+    depth_row.append(&gtk::Label::new(Some("Near (Å):")));
+    depth_row.append(&near_spin);
+    depth_row.append(&gtk::Label::new(Some("Far (Å):")));
+    depth_row.append(&far_spin);
+    depth_row.set_sensitive(!state.borrow().config.depth_range_auto);
+    vbox.append(&depth_row);
+
+    let btn_lock = gtk::Button::with_label("Fix at Current View");
+    btn_lock.set_halign(gtk::Align::Start);
+    btn_lock.set_tooltip_text(Some(
+        "Copy the range fitted to the structure as it is on screen now and stop refitting",
+    ));
+    btn_lock.set_sensitive(state.borrow().config.depth_range_auto);
+    vbox.append(&btn_lock);
+
+    let s_depth = state.clone();
+    let da_depth = da.clone();
+    let (row_depth, lock_depth) = (depth_row.clone(), btn_lock.clone());
+    check_depth.connect_toggled(move |c| {
+        row_depth.set_sensitive(!c.is_active());
+        lock_depth.set_sensitive(c.is_active());
+        let mut st = s_depth.borrow_mut();
+        st.config.depth_range_auto = c.is_active();
+        st.save_config();
+        drop(st);
+        da_depth.queue_draw();
+    });
+    for (spin, is_near) in [(&near_spin, true), (&far_spin, false)] {
+        let s_d = state.clone();
+        let da_d = da.clone();
+        spin.connect_value_changed(move |sp| {
+            let mut st = s_d.borrow_mut();
+            if is_near {
+                st.config.depth_near = sp.value();
+            } else {
+                st.config.depth_far = sp.value();
+            }
+            st.save_config();
+            drop(st);
+            da_d.queue_draw();
+        });
+    }
+    let s_lock = state.clone();
+    let (near_lock, far_lock, check_lock) =
+        (near_spin.clone(), far_spin.clone(), check_depth.clone());
+    btn_lock.connect_clicked(move |_| {
+        // Nothing on screen yet (or a stereo pair): keep fitting.
+        let depth = s_lock
+            .borrow()
+            .active_tab()
+            .drawn_frame
+            .borrow()
+            .as_ref()
+            .map(|f| f.depth);
+        let Some(depth) = depth else {
+            return;
+        };
+        near_lock.set_value(depth.near);
+        far_lock.set_value(depth.far);
+        check_lock.set_active(false);
+    });
 
     vbox
 }
@@ -48,6 +48,20 @@ fn setup_info_tags(view: &TextView) {
     tag.set_property("foreground", "#888888");
     tag_table.add(&tag);
   }
+
+  // GII bands of utils::report::gii_tag
+  for (name, color) in [
+    ("gii_good", "#66bb6a"), // Green
+    ("gii_fair", "#fdd835"), // Yellow
+    ("gii_poor", "#ef5350"), // Red
+  ] {
+    if tag_table.lookup(name).is_none() {
+      let tag = TextTag::new(Some(name));
+      tag.set_property("foreground", color);
+      tag.set_property("weight", 700);
+      tag_table.add(&tag);
+    }
+  }
 }
 
 // ---------------------------------------------------------------------------
@@ -68,6 +82,13 @@ pub fn info_report(message: &str) {
   do_append(&INFO_VIEW, &formatted);
 }
 
+/// `info_report` with the first occurrence of each (text, tag) pair in
+/// `message` shown in that tag.
+pub fn info_report_tagged(message: &str, tagged: &[(String, &'static str)]) {
+  let formatted = format!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n{}", message);
+  do_append_tagged(&INFO_VIEW, &formatted, tagged.to_vec());
+}
+
 // ---------------------------------------------------------------------------
 // System Log tab — operational events, errors, file I/O
 // ---------------------------------------------------------------------------
@@ -97,6 +118,14 @@ pub fn log_debug(message: &str) {
 // ---------------------------------------------------------------------------
 
 fn do_append(cell: &'static OnceLock<glib::SendWeakRef<TextView>>, message: &str) {
+  do_append_tagged(cell, message, Vec::new());
+}
+
+fn do_append_tagged(
+  cell: &'static OnceLock<glib::SendWeakRef<TextView>>,
+  message: &str,
+  tagged: Vec<(String, &'static str)>,
+) {
   // Clone the weak ref out of the OnceLock so we own it — avoids lifetime escape.
   let weak = match cell.get() {
     Some(w) => w.clone(),
@@ -111,8 +140,19 @@ fn do_append(cell: &'static OnceLock<glib::SendWeakRef<TextView>>, message: &str
       if buffer.char_count() > 0 {
         buffer.insert(&mut end, "\n");
       }
+      let start = buffer.char_count();
       buffer.insert(&mut end, &msg);
 
+      // Buffer offsets count characters, not bytes.
+      for (text, tag) in &tagged {
+        if let Some(at) = msg.find(text.as_str()) {
+          let from = start + msg[..at].chars().count() as i32;
+          let to = from + text.chars().count() as i32;
+          let (from, to) = (buffer.iter_at_offset(from), buffer.iter_at_offset(to));
+          buffer.apply_tag_by_name(tag, &from, &to);
+        }
+      }
+
       // Auto-scroll to bottom
       let mark = buffer.create_mark(None, &buffer.end_iter(), false);
       view.scroll_to_mark(&mark, 0.0, true, 0.0, 1.0);
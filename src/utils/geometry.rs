@@ -1,5 +1,6 @@
 // src/geometry.rs
 use nalgebra::Vector3;
+use std::fmt;
 
 type Point3 = [f64; 3];
 
@@ -50,6 +51,164 @@ pub fn calculate_dihedral(p1: Point3, p2: Point3, p3: Point3, p4: Point3) -> f64
   y.atan2(x).to_degrees()
 }
 
+// --- Cell parameters ---
+
+/// Lattice vectors shorter than this (Å) count as zero.
+const MIN_VECTOR_LENGTH: f64 = 1e-6;
+/// |sin| of the angle between two lattice vectors, and the volume relative
+/// to |a||b||c|, below which the cell counts as degenerate.
+const MIN_RELATIVE_SPAN: f64 = 1e-6;
+
+const AXES: [&str; 3] = ["a", "b", "c"];
+
+/// Why a lattice has no meaningful a, b, c, α, β, γ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellError {
+  /// Lattice vector `i` has (near) zero length.
+  ZeroVector(usize),
+  /// Lattice vectors `i` and `j` are (nearly) parallel.
+  Collinear(usize, usize),
+  /// The three vectors are (nearly) coplanar.
+  Singular,
+}
+
+impl fmt::Display for CellError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CellError::ZeroVector(i) => write!(f, "Lattice vector {} has zero length", AXES[*i]),
+      CellError::Collinear(i, j) => {
+        write!(
+          f,
+          "Lattice vectors {} and {} are collinear",
+          AXES[*i], AXES[*j]
+        )
+      }
+      CellError::Singular => write!(f, "Lattice vectors are coplanar (cell volume is zero)"),
+    }
+  }
+}
+
+impl std::error::Error for CellError {}
+
+/// Conventional cell parameters of a lattice (rows = a, b, c).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellParameters {
+  /// |a|, |b|, |c| in Å.
+  pub lengths: [f64; 3],
+  /// α (b∧c), β (a∧c), γ (a∧b) in degrees.
+  pub angles: [f64; 3],
+  /// Å³
+  pub volume: f64,
+}
+
+/// a, b, c, α, β, γ and the volume of `lattice`, or why they are undefined.
+/// Cosines are clamped to [-1, 1] so rounding never feeds `acos` a NaN.
+pub fn cell_parameters(lattice: [[f64; 3]; 3]) -> Result<CellParameters, CellError> {
+  let v = lattice.map(Vector3::from);
+  let lengths = v.map(|x| x.norm());
+  if let Some(i) = lengths
+    .iter()
+    .position(|&l| l < MIN_VECTOR_LENGTH || l.is_nan())
+  {
+    return Err(CellError::ZeroVector(i));
+  }
+
+  let mut angles = [0.0; 3];
+  for (k, (i, j)) in [(1, 2), (0, 2), (0, 1)].into_iter().enumerate() {
+    let scale = lengths[i] * lengths[j];
+    if v[i].cross(&v[j]).norm() < MIN_RELATIVE_SPAN * scale {
+      return Err(CellError::Collinear(i, j));
+    }
+    angles[k] = (v[i].dot(&v[j]) / scale)
+      .clamp(-1.0, 1.0)
+      .acos()
+      .to_degrees();
+  }
+
+  let volume = v[0].dot(&v[1].cross(&v[2])).abs();
+  if volume < MIN_RELATIVE_SPAN * lengths.iter().product::<f64>() {
+    return Err(CellError::Singular);
+  }
+  Ok(CellParameters {
+    lengths,
+    angles,
+    volume,
+  })
+}
+
+/// Lattice (rows = a, b, c) with a along x and b in the xy plane, from
+/// lengths in Å and α, β, γ in degrees. `None` when the angles cannot close
+/// a cell.
+pub fn abc_to_lattice(lengths: [f64; 3], angles_deg: [f64; 3]) -> Option<[[f64; 3]; 3]> {
+  let [a, b, c] = lengths;
+  let [alpha, beta, gamma] = angles_deg.map(f64::to_radians);
+  if gamma.sin().abs() < 1e-8 {
+    return None;
+  }
+  let cx = c * beta.cos();
+  let cy = c * (alpha.cos() - beta.cos() * gamma.cos()) / gamma.sin();
+  let cz2 = c * c - cx * cx - cy * cy;
+  if cz2.is_nan() || cz2 <= 0.0 {
+    return None;
+  }
+  Some([
+    [a, 0.0, 0.0],
+    [b * gamma.cos(), b * gamma.sin(), 0.0],
+    [cx, cy, cz2.sqrt()],
+  ])
+}
+
 // --- Internal Math Helpers ---
 // WE NO LONGER NEED THESE!
 // nalgebra::Vector3 handles sub, dot, cross, len, and normalize.
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn monoclinic_cell_parameters() {
+    let beta = 100f64.to_radians();
+    let lattice = [
+      [4.0, 0.0, 0.0],
+      [0.0, 5.0, 0.0],
+      [6.0 * beta.cos(), 0.0, 6.0 * beta.sin()],
+    ];
+    let p = cell_parameters(lattice).unwrap();
+    assert!((p.lengths[2] - 6.0).abs() < 1e-12);
+    assert!((p.angles[0] - 90.0).abs() < 1e-9);
+    assert!((p.angles[1] - 100.0).abs() < 1e-9);
+    assert!((p.volume - 120.0 * beta.sin()).abs() < 1e-9);
+  }
+
+  #[test]
+  fn abc_to_lattice_round_trips_through_cell_parameters() {
+    let lattice = abc_to_lattice([4.0, 5.0, 6.0], [80.0, 95.0, 110.0]).unwrap();
+    let p = cell_parameters(lattice).unwrap();
+    for (got, want) in p.lengths.iter().zip([4.0, 5.0, 6.0]) {
+      assert!((got - want).abs() < 1e-9);
+    }
+    for (got, want) in p.angles.iter().zip([80.0, 95.0, 110.0]) {
+      assert!((got - want).abs() < 1e-9);
+    }
+    // α larger than β + γ leaves no room for c.
+    assert_eq!(abc_to_lattice([4.0, 5.0, 6.0], [170.0, 30.0, 30.0]), None);
+    assert_eq!(abc_to_lattice([4.0, 5.0, 6.0], [90.0, 90.0, 180.0]), None);
+  }
+
+  #[test]
+  fn degenerate_cells_are_reported_not_nan() {
+    let zero = [[4.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 5.0]];
+    assert_eq!(cell_parameters(zero), Err(CellError::ZeroVector(1)));
+
+    // c ∥ a: the a∧c angle would come out of acos as 0 or NaN.
+    let collinear = [[4.0, 0.0, 0.0], [0.0, 5.0, 0.0], [8.0, 0.0, 1e-9]];
+    assert_eq!(cell_parameters(collinear), Err(CellError::Collinear(0, 2)));
+
+    let coplanar = [[4.0, 0.0, 0.0], [0.0, 5.0, 0.0], [3.0, 3.0, 0.0]];
+    assert_eq!(cell_parameters(coplanar), Err(CellError::Singular));
+
+    let nan = [[f64::NAN, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 5.0]];
+    assert_eq!(cell_parameters(nan), Err(CellError::ZeroVector(0)));
+  }
+}
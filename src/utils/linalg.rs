@@ -13,6 +13,9 @@ pub fn frac_to_cart(frac: [f64; 3], lattice: [[f64; 3]; 3]) -> [f64; 3] {
 /// Convert Cartesian coordinates to fractional using lattice matrix.
 /// Returns None if the lattice matrix is singular.
 pub fn cart_to_frac(cart: [f64; 3], lattice: [[f64; 3]; 3]) -> Option<[f64; 3]> {
+    if is_singular(lattice) {
+        return None;
+    }
     let cart_vec = Vector3::from(cart);
     let lat_mat = lattice_to_matrix3(lattice);
     lat_mat.transpose().try_inverse().map(|inv| {
@@ -21,13 +24,31 @@ pub fn cart_to_frac(cart: [f64; 3], lattice: [[f64; 3]; 3]) -> Option<[f64; 3]>
     })
 }
 
+/// Invert a 3×3 matrix. Returns None if it is singular.
+pub fn invert3(m: [[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    if is_singular(m) {
+        return None;
+    }
+    lattice_to_matrix3(m).try_inverse().map(matrix3_to_arr)
+}
+
 /// Invert a 3×3 matrix. Returns identity on singular input.
 pub fn invert_matrix_3x3(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
-    let mat = lattice_to_matrix3(m);
-    match mat.try_inverse() {
-        Some(inv) => matrix3_to_arr(inv),
-        None => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
-    }
+    invert3(m).unwrap_or([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+}
+
+/// |det| relative to the product of the row lengths below which a matrix
+/// counts as singular. `try_inverse` only refuses an exactly zero
+/// determinant; a nearly flat cell would invert to huge fractional
+/// coordinates instead.
+const SINGULAR_TOLERANCE: f64 = 1e-10;
+
+/// True when the rows of `m` are (nearly) linearly dependent, including a
+/// zero, NaN or infinite row. Scale-free, so tiny and huge cells are judged
+/// alike.
+pub fn is_singular(m: [[f64; 3]; 3]) -> bool {
+    let scale: f64 = m.iter().map(|r| Vector3::from(*r).norm()).product();
+    !(mat3_det(m).abs() >= SINGULAR_TOLERANCE * scale && scale > 0.0 && scale.is_finite())
 }
 
 /// Determinant of a 3×3 matrix.
@@ -46,6 +67,128 @@ pub fn mat3_mul_vec(m: [[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
     [result.x, result.y, result.z]
 }
 
+// ── Periodic boundaries ────────────────────────────────────────────────────
+
+/// A lattice prepared for minimum-image work. Both conversion matrices are
+/// built once, so loops over atom pairs or grid points don't re-invert.
+#[derive(Clone, Copy, Debug)]
+pub struct PeriodicCell {
+    /// Columns = lattice vectors: fractional → Cartesian.
+    to_cart: Matrix3<f64>,
+    /// Cartesian → fractional.
+    to_frac: Matrix3<f64>,
+    /// Columns = a reduced basis of the same lattice. Its vectors are short
+    /// and close to orthogonal, so few offsets need checking around the
+    /// rounded image even when the input cell is strongly sheared.
+    reduced: Matrix3<f64>,
+    /// Cartesian → fractional in the reduced basis.
+    reduced_inv: Matrix3<f64>,
+    /// Row lengths of `reduced_inv`: a Cartesian length `r` spans at most
+    /// `r * reach[i]` along reduced axis `i`.
+    reach: Vector3<f64>,
+    /// Rounding Δfrac gives the exact minimum image only for orthogonal
+    /// axes; oblique cells search the reduced basis.
+    oblique: bool,
+}
+
+impl PeriodicCell {
+    /// `None` for a singular lattice.
+    pub fn new(lattice: [[f64; 3]; 3]) -> Option<Self> {
+        if is_singular(lattice) {
+            return None;
+        }
+        let to_cart = lattice_to_matrix3(lattice).transpose();
+        let to_frac = to_cart.try_inverse()?;
+        let [a, b, c] = [0, 1, 2].map(|i| to_cart.column(i).into_owned());
+        let oblique = a.dot(&b).abs() > 1e-9 || a.dot(&c).abs() > 1e-9 || b.dot(&c).abs() > 1e-9;
+        let reduced = Matrix3::from_columns(&reduce_basis([a, b, c]));
+        let reduced_inv = reduced.try_inverse()?;
+        let reach = Vector3::from_fn(|i, _| reduced_inv.row(i).norm());
+        Some(Self {
+            to_cart,
+            to_frac,
+            reduced,
+            reduced_inv,
+            reach,
+            oblique,
+        })
+    }
+
+    pub fn to_frac(&self, cart: Vector3<f64>) -> Vector3<f64> {
+        self.to_frac * cart
+    }
+
+    pub fn to_cart(&self, frac: Vector3<f64>) -> Vector3<f64> {
+        self.to_cart * frac
+    }
+
+    /// Shortest Cartesian vector equivalent to the fractional offset `df`
+    /// under lattice translations, exact for any cell shape.
+    pub fn min_image_frac(&self, df: Vector3<f64>) -> Vector3<f64> {
+        if !self.oblique {
+            return self.to_cart * df.map(|x| x - x.round());
+        }
+        // Round in the reduced basis, then try every offset whose image
+        // could still be shorter than the rounded one.
+        let rf = self.reduced_inv * (self.to_cart * df);
+        let rf = rf.map(|x| x - x.round());
+        let mut best = self.reduced * rf;
+        let r = best.norm();
+        let range = |i: usize| {
+            let w = r * self.reach[i];
+            ((-w - rf[i]).ceil() as i64)..=((w - rf[i]).floor() as i64)
+        };
+        for ox in range(0) {
+            for oy in range(1) {
+                for oz in range(2) {
+                    let d = self.reduced * (rf + Vector3::new(ox as f64, oy as f64, oz as f64));
+                    if d.norm_squared() < best.norm_squared() {
+                        best = d;
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Minimum-image form of the Cartesian displacement `delta`.
+    pub fn min_image(&self, delta: Vector3<f64>) -> Vector3<f64> {
+        self.min_image_frac(self.to_frac * delta)
+    }
+}
+
+/// Shorten each basis vector by subtracting whole multiples of the others
+/// until none gets shorter. Every step is a unimodular change of basis, so
+/// the lattice is unchanged; lengths only decrease, so it terminates.
+fn reduce_basis(mut basis: [Vector3<f64>; 3]) -> [Vector3<f64>; 3] {
+    loop {
+        let mut changed = false;
+        for i in 0..3 {
+            for j in (0..3).filter(|&j| j != i) {
+                let mu = (basis[i].dot(&basis[j]) / basis[j].norm_squared()).round();
+                let shorter = basis[i] - basis[j] * mu;
+                if mu != 0.0 && shorter.norm_squared() < basis[i].norm_squared() * (1.0 - 1e-12) {
+                    basis[i] = shorter;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return basis;
+        }
+    }
+}
+
+/// Minimum-image form of the Cartesian displacement `delta` in `lattice`.
+/// A singular lattice leaves `delta` unchanged. Build a `PeriodicCell` once
+/// instead when calling this in a loop.
+pub fn min_image_cart(lattice: [[f64; 3]; 3], delta: [f64; 3]) -> [f64; 3] {
+    match PeriodicCell::new(lattice) {
+        Some(cell) => cell.min_image(Vector3::from(delta)).into(),
+        None => delta,
+    }
+}
+
 // ── Conversion helpers (public for use by physics modules) ─────────────────
 
 /// Convert a [[f64;3];3] lattice (rows = lattice vectors) to nalgebra Matrix3.
@@ -71,3 +214,127 @@ pub fn matrix3_to_arr(m: Matrix3<f64>) -> [[f64; 3]; 3] {
         [m.m31, m.m32, m.m33],
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hexagonal (γ = 120°) and a strongly sheared triclinic cell.
+    const HEXAGONAL: [[f64; 3]; 3] = [[3.0, 0.0, 0.0], [-1.5, 2.598076211, 0.0], [0.0, 0.0, 5.0]];
+    const TRICLINIC: [[f64; 3]; 3] = [[4.0, 0.0, 0.0], [3.2, 2.5, 0.0], [1.1, 1.7, 3.0]];
+
+    /// Shortest image over every translation within ±6 cells, enough for
+    /// the displacements below even in the sheared cells.
+    fn brute_force_min(lattice: [[f64; 3]; 3], delta: [f64; 3]) -> f64 {
+        let mut best = f64::MAX;
+        for i in -6..=6 {
+            for j in -6..=6 {
+                for k in -6..=6 {
+                    let t = frac_to_cart([i as f64, j as f64, k as f64], lattice);
+                    let d = Vector3::from(delta) + Vector3::from(t);
+                    best = best.min(d.norm());
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn fractional_roundtrip_in_oblique_cells() {
+        for lattice in [HEXAGONAL, TRICLINIC] {
+            let cart = [1.3, -0.7, 2.2];
+            let frac = cart_to_frac(cart, lattice).unwrap();
+            let back = frac_to_cart(frac, lattice);
+            for k in 0..3 {
+                assert!((back[k] - cart[k]).abs() < 1e-12);
+            }
+            // a itself is (1, 0, 0) in fractional coordinates.
+            let fa = cart_to_frac(lattice[0], lattice).unwrap();
+            assert!((fa[0] - 1.0).abs() < 1e-12 && fa[1].abs() < 1e-12 && fa[2].abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn minimum_image_matches_brute_force() {
+        // Deterministic spread of displacements, including ones several
+        // cells away.
+        let mut seed = 12345_u64;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 16.0 - 8.0
+        };
+        for lattice in [HEXAGONAL, TRICLINIC] {
+            let cell = PeriodicCell::new(lattice).unwrap();
+            for _ in 0..200 {
+                let delta = [next(), next(), next()];
+                let found = cell.min_image(Vector3::from(delta));
+                let expected = brute_force_min(lattice, delta);
+                assert!((found.norm() - expected).abs() < 1e-9, "{delta:?}");
+                // The result differs from delta by a lattice vector.
+                let shift = cart_to_frac((found - Vector3::from(delta)).into(), lattice).unwrap();
+                assert!(shift.iter().all(|x| (x - x.round()).abs() < 1e-9));
+            }
+        }
+    }
+
+    #[test]
+    fn minimum_image_is_exact_in_unreduced_cells() {
+        // b is 3.4a plus a short step: rounding and checking one offset either
+        // way misses the shortest image, which is several multiples of a away.
+        let sheared = [[1.0, 0.0, 0.0], [3.4, 0.5, 0.0], [0.0, 0.0, 2.0]];
+        let cell = PeriodicCell::new(sheared).unwrap();
+        for delta in [[0.3, 0.2, 0.1], [2.9, 0.55, -0.4], [1.7, -0.6, 0.9]] {
+            let found = cell.min_image(Vector3::from(delta));
+            let expected = brute_force_min(sheared, delta);
+            assert!((found.norm() - expected).abs() < 1e-9, "{delta:?}");
+        }
+    }
+
+    #[test]
+    fn helpers_agree_and_reject_singular_cells() {
+        let delta = [2.9, 0.1, -4.8];
+        let via_cell = PeriodicCell::new(HEXAGONAL)
+            .unwrap()
+            .min_image(Vector3::from(delta));
+        let via_fn = min_image_cart(HEXAGONAL, delta);
+        for k in 0..3 {
+            assert!((via_fn[k] - via_cell[k]).abs() < 1e-12);
+        }
+
+        let flat = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]];
+        assert!(PeriodicCell::new(flat).is_none());
+        assert!(invert3(flat).is_none());
+        assert_eq!(min_image_cart(flat, delta), delta);
+
+        let inv = invert3(TRICLINIC).unwrap();
+        let id = mat3_mul(TRICLINIC, inv);
+        for (i, row) in id.iter().enumerate() {
+            for (j, v) in row.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((v - expected).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn near_singular_lattices_are_refused() {
+        // c lies in the ab plane to 1e-13 Å: det ≠ 0, but the cell is flat.
+        let flat = [[4.0, 0.0, 0.0], [0.0, 5.0, 0.0], [3.0, 3.0, 1e-13]];
+        assert!(is_singular(flat));
+        assert!(PeriodicCell::new(flat).is_none());
+        assert!(cart_to_frac([1.0, 1.0, 1.0], flat).is_none());
+        assert!(invert3(flat).is_none());
+
+        let zero_row = [[4.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 5.0]];
+        assert!(is_singular(zero_row));
+        let nan = [[f64::NAN, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 5.0]];
+        assert!(is_singular(nan));
+
+        // A tiny but well-shaped cell is fine.
+        let tiny = [[1e-3, 0.0, 0.0], [0.0, 1e-3, 0.0], [0.0, 0.0, 1e-3]];
+        assert!(!is_singular(tiny));
+        assert!(!is_singular(TRICLINIC));
+    }
+}
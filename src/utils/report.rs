@@ -1,13 +1,18 @@
 // src/utils/report.rs
 
+use crate::config::ReportFormat;
 use crate::model::structure::Structure;
-use crate::physics::bond_valence::{analyze_structure, BVSQuality};
+use crate::physics::analysis::symmetry;
+use crate::physics::bond_valence::calculator::ideal_instability_index;
+use crate::physics::bond_valence::{
+  analyze_structure, charge_balance, get_ideal_oxidation_state, BVSQuality,
+};
 use crate::utils::geometry;
 use std::collections::{HashMap, HashSet};
 
 // ─── Structure summary ───────────────────────────────────────────────────────
 
-pub fn structure_summary(structure: &Structure, filename: &str) -> String {
+pub fn structure_summary(structure: &Structure, filename: &str, fmt: &ReportFormat) -> String {
   let mut counts: HashMap<String, usize> = HashMap::new();
   for atom in &structure.atoms {
     *counts.entry(atom.element.clone()).or_insert(0) += 1;
@@ -25,6 +30,28 @@ pub fn structure_summary(structure: &Structure, filename: &str) -> String {
   let mut out = String::new();
   out.push_str(&format!("File: {}\n", filename));
   out.push_str(&format!("Formula: {}\n", formula_str));
+  if structure.is_periodic {
+    out.push_str(&format!("Volume:  {}\n", fmt.volume(structure.cell_volume())));
+  } else {
+    out.push_str("Cell:    none (molecule, no periodic boundaries)\n");
+    let [cx, cy, cz] = structure.centroid(false);
+    out.push_str(&format!(
+      "Centroid: ({}, {}, {}) {}\n",
+      fmt.length_value(cx),
+      fmt.length_value(cy),
+      fmt.length_value(cz),
+      fmt.length_symbol()
+    ));
+    out.push_str(&format!(
+      "Rg:      {} (mass-weighted {})\n",
+      fmt.length(structure.radius_of_gyration(false)),
+      fmt.length(structure.radius_of_gyration(true))
+    ));
+  }
+  if let Some(rho) = structure.density_g_cm3() {
+    out.push_str(&format!("Density: {} g/cm³\n", fmt.value(rho)));
+  }
+  out.push_str(&charge_neutrality(structure));
   out.push_str("--------------------------------------------------\n");
   out.push_str(&format!(
     "{:<8} {:<8} {:<10} {:<10} {:<10}\n",
@@ -34,8 +61,12 @@ pub fn structure_summary(structure: &Structure, filename: &str) -> String {
 
   for (i, atom) in structure.atoms.iter().take(20).enumerate() {
     out.push_str(&format!(
-      "{:<8} {:<8} {:<10.4} {:<10.4} {:<10.4}\n",
-      i, atom.element, atom.position[0], atom.position[1], atom.position[2]
+      "{:<8} {:<8} {:<10} {:<10} {:<10}\n",
+      i,
+      atom.element,
+      fmt.length_value(atom.position[0]),
+      fmt.length_value(atom.position[1]),
+      fmt.length_value(atom.position[2])
     ));
   }
   if structure.atoms.len() > 20 {
@@ -47,14 +78,173 @@ pub fn structure_summary(structure: &Structure, filename: &str) -> String {
   out
 }
 
+// ─── Charge neutrality ───────────────────────────────────────────────────────
+
+/// Net charge of the working oxidation states. A periodic cell that does
+/// not balance gets a warning block: with guessed states the guesser could
+/// not find a neutral assignment, with explicit ones the file is
+/// inconsistent. A molecule may legitimately be an ion, so it only gets
+/// the number.
+pub fn charge_neutrality(structure: &Structure) -> String {
+  let b = charge_balance(structure);
+  if b.total == 0 {
+    return String::new();
+  }
+  let source = if b.explicit == b.total {
+    "from file"
+  } else if b.explicit == 0 {
+    "auto-assigned"
+  } else {
+    "partly auto-assigned"
+  };
+
+  let mut out = String::new();
+  if b.is_neutral() {
+    out.push_str(&format!("Charge:  neutral (oxidation states {})\n", source));
+  } else if !structure.is_periodic {
+    out.push_str(&format!(
+      "Charge:  {:+.2} e (oxidation states {})\n",
+      b.net, source
+    ));
+  } else {
+    out.push_str(&format!(
+      "Charge:  ⚠ NOT NEUTRAL — net {:+.2} e per cell (oxidation states {})\n",
+      b.net, source
+    ));
+    if b.is_auto_assigned() {
+      out.push_str("         No neutral assignment was found; set oxidation states\n");
+      out.push_str("         explicitly before trusting BVS or Madelung results.\n");
+    } else {
+      out.push_str("         The oxidation states in the file do not balance;\n");
+      out.push_str("         check the composition and site occupancies.\n");
+    }
+  }
+  if b.unknown > 0 {
+    out.push_str(&format!(
+      "         {} atom(s) have no oxidation state and count as zero.\n",
+      b.unknown
+    ));
+  }
+  out
+}
+
+// ─── Cell parameters ─────────────────────────────────────────────────────────
+
+/// Lattice parameters, volume, density and space group of a periodic
+/// structure; a one-line note for molecules.
+pub fn cell_summary(structure: &Structure, fmt: &ReportFormat) -> String {
+  if !structure.is_periodic {
+    return "Cell:    none (molecule, no periodic boundaries)\n".to_string();
+  }
+  let mut out = String::new();
+  match geometry::cell_parameters(structure.lattice) {
+    Ok(cell) => {
+      let [a, b, c] = cell.lengths;
+      let [alpha, beta, gamma] = cell.angles;
+      out.push_str(&format!("a = {:<14} α = {}\n", fmt.length(a), fmt.angle(alpha)));
+      out.push_str(&format!("b = {:<14} β = {}\n", fmt.length(b), fmt.angle(beta)));
+      out.push_str(&format!("c = {:<14} γ = {}\n", fmt.length(c), fmt.angle(gamma)));
+      out.push_str(&format!("Volume:      {}\n", fmt.volume(cell.volume)));
+    }
+    Err(e) => out.push_str(&format!("Degenerate cell: {}\n", e)),
+  }
+  if let Some(rho) = structure.density_g_cm3() {
+    out.push_str(&format!("Density:     {} g/cm³\n", fmt.value(rho)));
+  }
+  match symmetry::analyze(structure) {
+    Ok(info) => out.push_str(&format!(
+      "Space group: {} (No. {}), {}\n",
+      info.symbol, info.number, info.system
+    )),
+    Err(e) => out.push_str(&format!("Space group: unavailable ({})\n", e)),
+  }
+  out
+}
+
 // ─── BVS analysis ────────────────────────────────────────────────────────────
 
-pub fn bvs_analysis(structure: &Structure) -> String {
+/// Oxidation states keyed by atom index, per element in order of first
+/// appearance: "Fe +2 ×8, +3 ×16; O −2 ×32". Atoms missing from
+/// `states` are counted as "?".
+pub fn oxidation_states(structure: &Structure, states: &HashMap<usize, i32>) -> String {
+  /// (oxidation state, atom count), in order of first appearance.
+  type StateCounts = Vec<(Option<i32>, usize)>;
+  let mut per_element: Vec<(&str, StateCounts)> = Vec::new();
+  for (i, atom) in structure.atoms.iter().enumerate() {
+    let state = states.get(&i).copied();
+    let k = match per_element.iter().position(|(el, _)| *el == atom.element) {
+      Some(k) => k,
+      None => {
+        per_element.push((atom.element.as_str(), Vec::new()));
+        per_element.len() - 1
+      }
+    };
+    let counts = &mut per_element[k].1;
+    match counts.iter_mut().find(|(v, _)| *v == state) {
+      Some((_, n)) => *n += 1,
+      None => counts.push((state, 1)),
+    }
+  }
+  per_element
+    .iter_mut()
+    .map(|(el, counts)| {
+      counts.sort();
+      let counts: Vec<String> = counts
+        .iter()
+        .map(|(v, n)| match v {
+          Some(v) => format!("{:+} ×{}", v, n).replace('-', "−"),
+          None => format!("? ×{}", n),
+        })
+        .collect();
+      format!("{} {}", el, counts.join(", "))
+    })
+    .collect::<Vec<_>>()
+    .join("; ")
+}
+
+/// Structure Info tag for a GII in v.u.: green below 0.1 (stable), yellow
+/// below 0.2, red otherwise. The tags are set up by `console::init`.
+pub fn gii_tag(gii: f64) -> &'static str {
+  if gii < 0.1 {
+    "gii_good"
+  } else if gii < 0.2 {
+    "gii_fair"
+  } else {
+    "gii_poor"
+  }
+}
+
+pub fn bvs_analysis(structure: &Structure, fmt: &ReportFormat) -> String {
+  bvs_analysis_tagged(structure, fmt).0
+}
+
+/// `bvs_analysis` with the lines to color in the Structure Info tab, as
+/// (line, tag) pairs for `console::info_report_tagged`.
+pub fn bvs_analysis_tagged(
+  structure: &Structure,
+  fmt: &ReportFormat,
+) -> (String, Vec<(String, &'static str)>) {
   let r = analyze_structure(structure);
   // Quality banner is banded on the GII — that's what the literature bands
   // (Brown 2002: < 0.1 stable, > 0.2 strained) are defined on. Mean |Δ| is
   // still reported as a statistic below.
   let quality = BVSQuality::from_deviation(r.gii);
+  let gii_line = format!("GII (√⟨Δ²⟩):          {} v.u.", fmt.value(r.gii));
+  let mut tagged = vec![(gii_line.clone(), gii_tag(r.gii))];
+  // Against the ideal states the GII only differs when the file or charge
+  // balance assigned other ones, so it gets a line of its own only then.
+  let ideal_states = structure
+    .atoms
+    .iter()
+    .zip(&r.atoms)
+    .all(|(atom, a)| a.expected == get_ideal_oxidation_state(&atom.element));
+  let ideal_gii_line = (!ideal_states).then(|| {
+    let bvs: Vec<f64> = r.atoms.iter().map(|a| a.bvs).collect();
+    let ideal_gii = ideal_instability_index(structure, &bvs);
+    let line = format!("GII (ideal states):   {} v.u.", fmt.value(ideal_gii));
+    tagged.push((line.clone(), gii_tag(ideal_gii)));
+    line
+  });
   let mut out = String::new();
 
   out.push_str("═══════════════════════════════════════════════════════════════\n");
@@ -63,12 +253,14 @@ pub fn bvs_analysis(structure: &Structure) -> String {
 
   out.push_str(&format!("Atoms:                {}\n", structure.atoms.len()));
   out.push_str(&format!("Validated:            {}\n", r.validated));
-  out.push_str(&format!("Mean |Δ|:             {:.3} v.u.\n", r.mean_abs_dev));
-  out.push_str(&format!("Max  |Δ|:             {:.3} v.u.\n", r.max_abs_dev));
-  out.push_str(&format!(
-    "GII (√⟨Δ²⟩):          {:.3} v.u.\n",
-    r.gii
-  ));
+  out.push_str(&format!("Mean |Δ|:             {} v.u.\n", fmt.value(r.mean_abs_dev)));
+  out.push_str(&format!("Max  |Δ|:             {} v.u.\n", fmt.value(r.max_abs_dev)));
+  out.push_str(&gii_line);
+  out.push('\n');
+  if let Some(line) = &ideal_gii_line {
+    out.push_str(line);
+    out.push('\n');
+  }
   out.push_str(&format!(
     "Overall quality:      {} {}\n\n",
     quality.symbol(),
@@ -127,12 +319,15 @@ pub fn bvs_analysis(structure: &Structure) -> String {
     };
 
     out.push_str(&format!(
-      "{:<5} {:<4} {:>4} {:>8.3} {:>8.3} {:>+8.3} {:>4} {:<8} {:<6}\n",
+      "{:<5} {:<4} {:>4} {:>8.*} {:>8.*} {:>+8.*} {:>4} {:<8} {:<6}\n",
       i,
       atom.element,
       ox_str,
+      fmt.value_decimals,
       a.bvs,
+      fmt.value_decimals,
       a.expected,
+      fmt.value_decimals,
       a.deviation(),
       a.coordination,
       status,
@@ -159,6 +354,12 @@ pub fn bvs_analysis(structure: &Structure) -> String {
   out.push_str(
     "GII    = Global Instability Index √⟨Δ²⟩ over validated atoms\n",
   );
+  if ideal_gii_line.is_some() {
+    out.push_str(
+      "GII (ideal states) = the same against each element's primary oxidation state;\n\
+       the overall quality is banded on GII (√⟨Δ²⟩)\n",
+    );
+  }
 
   // Factual notes about the calculation only — no interpretation of the
   // deviations. What a large Δ *means* is context-dependent (surface atoms
@@ -203,13 +404,97 @@ pub fn bvs_analysis(structure: &Structure) -> String {
     }
   }
 
+  out.push_str("\n═══════════════════════════════════════════════════════════════\n");
+  (out, tagged)
+}
+
+// ─── Madelung / Ewald ─────────────────────────────────────────────────────────
+
+/// Ewald electrostatics with the BVS working oxidation states as point
+/// charges (explicit oxidation states win; partial sites scale by occupancy).
+pub fn madelung_analysis(structure: &Structure) -> String {
+  use crate::physics::analysis::ewald;
+
+  let r = analyze_structure(structure);
+  let charges: Vec<f64> = structure
+    .atoms
+    .iter()
+    .zip(&r.atoms)
+    .map(|(atom, a)| a.assumed_v as f64 * atom.occupancy)
+    .collect();
+  let (energy, phi) = ewald::madelung(structure, &charges);
+  let formal: Vec<f64> = r.atoms.iter().map(|a| a.assumed_v as f64).collect();
+  let net = structure.net_charge(&formal);
+
+  let mut out = String::new();
+  out.push_str("═══════════════════════════════════════════════════════════════\n");
+  out.push_str("                 MADELUNG ENERGY (EWALD SUMMATION)\n");
+  out.push_str("═══════════════════════════════════════════════════════════════\n\n");
+
+  out.push_str(&format!("Atoms:                {}\n", structure.atoms.len()));
+  out.push_str(&format!("Net charge:           {:+.3} e\n", net));
+  out.push_str(&format!("Electrostatic energy: {:.4} eV/cell\n", energy));
+  if !structure.atoms.is_empty() {
+    out.push_str(&format!(
+      "                      {:.4} eV/atom\n",
+      energy / structure.atoms.len() as f64
+    ));
+  }
+  out.push('\n');
+
+  out.push_str("───────────────────────────────────────────────────────────────\n");
+  out.push_str(&format!(
+    "{:<5} {:<4} {:>6} {:>12}\n",
+    "Idx", "Elem", "q (e)", "φ (V)"
+  ));
+  out.push_str("───────────────────────────────────────────────────────────────\n");
+
+  const MAX_ROWS: usize = 50;
+  for (i, (atom, p)) in structure.atoms.iter().zip(&phi).take(MAX_ROWS).enumerate() {
+    out.push_str(&format!(
+      "{:<5} {:<4} {:>+6.2} {:>12.4}\n",
+      i, atom.element, charges[i], p
+    ));
+  }
+  if phi.len() > MAX_ROWS {
+    out.push_str(&format!(
+      "… {} more atoms not shown.\n",
+      phi.len() - MAX_ROWS
+    ));
+  }
+
+  let mut notes: Vec<String> = Vec::new();
+  if !structure.is_periodic {
+    notes.push("Molecule: direct Coulomb sum, no periodic images".to_string());
+  } else if net.abs() > 1e-6 {
+    notes.push(
+      "Cell is not charge-neutral — a uniform compensating background is included".to_string(),
+    );
+  }
+  let unknown = r.atoms.iter().filter(|a| a.is_unknown()).count();
+  if unknown > 0 {
+    notes.push(format!(
+      "{unknown} atom(s) have no oxidation state and carry zero charge"
+    ));
+  }
+  if !notes.is_empty() {
+    out.push_str("\nNotes:\n");
+    for n in &notes {
+      out.push_str(&format!("• {n}\n"));
+    }
+  }
+
   out.push_str("\n═══════════════════════════════════════════════════════════════\n");
   out
 }
 
 // ─── Geometry analysis ───────────────────────────────────────────────────────
 
-pub fn geometry_analysis(structure: &Structure, selected_indices: &HashSet<usize>) -> String {
+pub fn geometry_analysis(
+  structure: &Structure,
+  selected_indices: &HashSet<usize>,
+  fmt: &ReportFormat,
+) -> String {
   let mut sel: Vec<usize> = selected_indices.iter().cloned().collect();
   sel.sort();
 
@@ -234,8 +519,8 @@ pub fn geometry_analysis(structure: &Structure, selected_indices: &HashSet<usize
       let p1 = structure.atoms[sel[0]].position;
       let p2 = structure.atoms[sel[1]].position;
       out.push_str(&format!(
-        "Distance: {:.5} Å",
-        geometry::calculate_distance(p1, p2)
+        "Distance: {}",
+        fmt.length(geometry::calculate_distance(p1, p2))
       ));
     }
     3 => {
@@ -243,16 +528,16 @@ pub fn geometry_analysis(structure: &Structure, selected_indices: &HashSet<usize
       let p2 = structure.atoms[sel[1]].position;
       let p3 = structure.atoms[sel[2]].position;
       out.push_str(&format!(
-        "Angle (A-B-C): {:.2}°\n",
-        geometry::calculate_angle(p1, p2, p3)
+        "Angle (A-B-C): {}\n",
+        fmt.angle(geometry::calculate_angle(p1, p2, p3))
       ));
       out.push_str(&format!(
-        "Dist (A-B):    {:.4} Å\n",
-        geometry::calculate_distance(p1, p2)
+        "Dist (A-B):    {}\n",
+        fmt.length(geometry::calculate_distance(p1, p2))
       ));
       out.push_str(&format!(
-        "Dist (B-C):    {:.4} Å",
-        geometry::calculate_distance(p2, p3)
+        "Dist (B-C):    {}",
+        fmt.length(geometry::calculate_distance(p2, p3))
       ));
     }
     4 => {
@@ -261,12 +546,12 @@ pub fn geometry_analysis(structure: &Structure, selected_indices: &HashSet<usize
       let p3 = structure.atoms[sel[2]].position;
       let p4 = structure.atoms[sel[3]].position;
       out.push_str(&format!(
-        "Dihedral:      {:.2}°\n",
-        geometry::calculate_dihedral(p1, p2, p3, p4)
+        "Dihedral:      {}\n",
+        fmt.angle(geometry::calculate_dihedral(p1, p2, p3, p4))
       ));
       out.push_str(&format!(
-        "Angle (A-B-C): {:.2}°",
-        geometry::calculate_angle(p1, p2, p3)
+        "Angle (A-B-C): {}",
+        fmt.angle(geometry::calculate_angle(p1, p2, p3))
       ));
     }
     _ => {
@@ -276,7 +561,10 @@ pub fn geometry_analysis(structure: &Structure, selected_indices: &HashSet<usize
   out
 }
 
-pub fn geometry_analysis_from_positions(selected_atoms: &[(usize, String, [f64; 3])]) -> String {
+pub fn geometry_analysis_from_positions(
+  selected_atoms: &[(usize, String, [f64; 3])],
+  fmt: &ReportFormat,
+) -> String {
   if selected_atoms.is_empty() {
     return "Select atoms to measure.".to_string();
   }
@@ -296,8 +584,8 @@ pub fn geometry_analysis_from_positions(selected_atoms: &[(usize, String, [f64;
       let p1 = selected_atoms[0].2;
       let p2 = selected_atoms[1].2;
       out.push_str(&format!(
-        "Distance: {:.5} Å",
-        geometry::calculate_distance(p1, p2)
+        "Distance: {}",
+        fmt.length(geometry::calculate_distance(p1, p2))
       ));
     }
     3 => {
@@ -305,16 +593,16 @@ pub fn geometry_analysis_from_positions(selected_atoms: &[(usize, String, [f64;
       let p2 = selected_atoms[1].2;
       let p3 = selected_atoms[2].2;
       out.push_str(&format!(
-        "Angle (A-B-C): {:.2}°\n",
-        geometry::calculate_angle(p1, p2, p3)
+        "Angle (A-B-C): {}\n",
+        fmt.angle(geometry::calculate_angle(p1, p2, p3))
       ));
       out.push_str(&format!(
-        "Dist (A-B):    {:.4} Å\n",
-        geometry::calculate_distance(p1, p2)
+        "Dist (A-B):    {}\n",
+        fmt.length(geometry::calculate_distance(p1, p2))
       ));
       out.push_str(&format!(
-        "Dist (B-C):    {:.4} Å",
-        geometry::calculate_distance(p2, p3)
+        "Dist (B-C):    {}",
+        fmt.length(geometry::calculate_distance(p2, p3))
       ));
     }
     4 => {
@@ -323,12 +611,12 @@ pub fn geometry_analysis_from_positions(selected_atoms: &[(usize, String, [f64;
       let p3 = selected_atoms[2].2;
       let p4 = selected_atoms[3].2;
       out.push_str(&format!(
-        "Dihedral:      {:.2}°\n",
-        geometry::calculate_dihedral(p1, p2, p3, p4)
+        "Dihedral:      {}\n",
+        fmt.angle(geometry::calculate_dihedral(p1, p2, p3, p4))
       ));
       out.push_str(&format!(
-        "Angle (A-B-C): {:.2}°",
-        geometry::calculate_angle(p1, p2, p3)
+        "Angle (A-B-C): {}",
+        fmt.angle(geometry::calculate_angle(p1, p2, p3))
       ));
     }
     _ => {
@@ -112,6 +112,8 @@ mod tests {
             unique_id: 0,
             is_ghost: false,
             is_coord_only: false,
+            is_replica: false,
+            is_hidden: false,
             screen_radius: 0.0,
         }
     }